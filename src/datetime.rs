@@ -0,0 +1,372 @@
+//! Date and time arithmetic
+//!
+//! Timestamps are stored as text in MySQL's canonical formats, `YYYY-MM-DD`
+//! for dates and `YYYY-MM-DD HH:MM:SS` for datetimes, so they compare
+//! correctly as strings. This module parses those strings, applies
+//! `INTERVAL n UNIT` offsets and formats the result back. DATE columns hold
+//! a [`Date`] instead, which serializes to the same `YYYY-MM-DD` text.
+//!
+//! Month and year offsets keep the day of month, clamped to the length of the
+//! target month (`2024-01-31 + INTERVAL 1 MONTH` is `2024-02-29`). All times
+//! are UTC.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Unit of an `INTERVAL n UNIT` expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl IntervalUnit {
+    /// Parse a unit keyword (case-insensitive)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "SECOND" => Some(IntervalUnit::Second),
+            "MINUTE" => Some(IntervalUnit::Minute),
+            "HOUR" => Some(IntervalUnit::Hour),
+            "DAY" => Some(IntervalUnit::Day),
+            "WEEK" => Some(IntervalUnit::Week),
+            "MONTH" => Some(IntervalUnit::Month),
+            "YEAR" => Some(IntervalUnit::Year),
+            _ => None,
+        }
+    }
+
+    /// Whether adding this unit to a date can produce a time of day
+    fn has_time(self) -> bool {
+        matches!(self, IntervalUnit::Second | IntervalUnit::Minute | IntervalUnit::Hour)
+    }
+}
+
+impl fmt::Display for IntervalUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            IntervalUnit::Second => "SECOND",
+            IntervalUnit::Minute => "MINUTE",
+            IntervalUnit::Hour => "HOUR",
+            IntervalUnit::Day => "DAY",
+            IntervalUnit::Week => "WEEK",
+            IntervalUnit::Month => "MONTH",
+            IntervalUnit::Year => "YEAR",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A calendar date with an optional time of day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp {
+    /// Days since 1970-01-01
+    days: i64,
+    /// Seconds since midnight
+    seconds: i64,
+    /// Whether the value carries a time of day (datetime) or is a plain date
+    has_time: bool,
+}
+
+impl Timestamp {
+    /// The current time as a datetime
+    pub fn now() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        Timestamp {
+            days: secs.div_euclid(SECONDS_PER_DAY),
+            seconds: secs.rem_euclid(SECONDS_PER_DAY),
+            has_time: true,
+        }
+    }
+
+    /// Parse `YYYY-MM-DD` or `YYYY-MM-DD HH:MM:SS` (a `T` separator is also accepted)
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (date, time) = match text.find([' ', 'T']) {
+            Some(pos) => (&text[..pos], Some(text[pos + 1..].trim_start())),
+            None => (text, None),
+        };
+
+        let mut parts = date.splitn(3, '-');
+        let year: i64 = parts.next()?.parse().ok()?;
+        let month: u32 = parts.next()?.parse().ok()?;
+        let day: u32 = parts.next()?.parse().ok()?;
+        if !(1..=9999).contains(&year) || !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        let seconds = match time {
+            Some(time) => {
+                let mut parts = time.splitn(3, ':');
+                let hour: i64 = parts.next()?.parse().ok()?;
+                let minute: i64 = parts.next()?.parse().ok()?;
+                let second: i64 = match parts.next() {
+                    Some(s) => s.parse().ok()?,
+                    None => 0,
+                };
+                if !(0..24).contains(&hour) || !(0..60).contains(&minute) || !(0..60).contains(&second) {
+                    return None;
+                }
+                hour * 3600 + minute * 60 + second
+            }
+            None => 0,
+        };
+
+        Some(Timestamp {
+            days: days_from_civil(year, month, day),
+            seconds,
+            has_time: time.is_some(),
+        })
+    }
+
+    /// The calendar date, dropping any time of day
+    pub fn date(self) -> Date {
+        Date { days: self.days }
+    }
+
+    /// Whether the value carries a time of day
+    pub fn has_time(self) -> bool {
+        self.has_time
+    }
+
+    /// Add `amount` units (negative to subtract). Returns `None` when the
+    /// result falls outside years 1..=9999.
+    pub fn add_interval(self, amount: i64, unit: IntervalUnit) -> Option<Self> {
+        let mut result = match unit {
+            IntervalUnit::Month => self.add_months(amount)?,
+            IntervalUnit::Year => self.add_months(amount.checked_mul(12)?)?,
+            _ => {
+                let unit_seconds = match unit {
+                    IntervalUnit::Second => 1,
+                    IntervalUnit::Minute => 60,
+                    IntervalUnit::Hour => 3600,
+                    IntervalUnit::Day => SECONDS_PER_DAY,
+                    _ => 7 * SECONDS_PER_DAY,
+                };
+                let total = self.seconds.checked_add(amount.checked_mul(unit_seconds)?)?;
+                Timestamp {
+                    days: self.days.checked_add(total.div_euclid(SECONDS_PER_DAY))?,
+                    seconds: total.rem_euclid(SECONDS_PER_DAY),
+                    has_time: self.has_time,
+                }
+            }
+        };
+        result.has_time |= unit.has_time();
+
+        let (year, _, _) = civil_from_days(result.days);
+        (1..=9999).contains(&year).then_some(result)
+    }
+
+    fn add_months(self, amount: i64) -> Option<Self> {
+        let (year, month, day) = civil_from_days(self.days);
+        let total = (year * 12 + (month as i64 - 1)).checked_add(amount)?;
+        let (year, month) = (total.div_euclid(12), total.rem_euclid(12) as u32 + 1);
+        let day = day.min(days_in_month(year, month));
+        Some(Timestamp { days: days_from_civil(year, month, day), ..self })
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.days);
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)?;
+        if self.has_time {
+            write!(
+                f,
+                " {:02}:{:02}:{:02}",
+                self.seconds / 3600,
+                self.seconds / 60 % 60,
+                self.seconds % 60
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A calendar date without a time of day, the value of a DATE column
+///
+/// Held as days since 1970-01-01 and serialized as `YYYY-MM-DD` text, so
+/// JSONL table files stay readable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    days: i64,
+}
+
+impl Date {
+    /// Parse `YYYY-MM-DD`. A datetime string is accepted and its time dropped.
+    pub fn parse(text: &str) -> Option<Self> {
+        Timestamp::parse(text).map(Timestamp::date)
+    }
+
+    /// The date `days` days after 1970-01-01
+    pub(crate) fn from_days(days: i64) -> Self {
+        Date { days }
+    }
+
+    /// Days since 1970-01-01
+    pub(crate) fn days(self) -> i64 {
+        self.days
+    }
+
+    pub fn year(self) -> i64 {
+        civil_from_days(self.days).0
+    }
+
+    pub fn month(self) -> u32 {
+        civil_from_days(self.days).1
+    }
+
+    pub fn day(self) -> u32 {
+        civil_from_days(self.days).2
+    }
+
+    /// Midnight of this date, for interval arithmetic
+    pub fn to_timestamp(self) -> Timestamp {
+        Timestamp { days: self.days, seconds: 0, has_time: false }
+    }
+
+    /// Compare with a date or datetime string, taking the date as midnight.
+    /// `None` when `text` is neither.
+    pub fn cmp_text(self, text: &str) -> Option<Ordering> {
+        Timestamp::parse(text).map(|ts| (self.days, 0).cmp(&(ts.days, ts.seconds)))
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_timestamp().fmt(f)
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Date::parse(&text).ok_or_else(|| serde::de::Error::custom(format!("invalid date '{}'", text)))
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Inverse of [`days_from_civil`]
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(text: &str, amount: i64, unit: IntervalUnit) -> String {
+        Timestamp::parse(text).unwrap().add_interval(amount, unit).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        assert_eq!(Timestamp::parse("2024-02-29").unwrap().to_string(), "2024-02-29");
+        assert_eq!(Timestamp::parse("1999-12-31 23:59:59").unwrap().to_string(), "1999-12-31 23:59:59");
+        assert_eq!(Timestamp::parse("2024-03-01T08:05").unwrap().to_string(), "2024-03-01 08:05:00");
+        assert!(Timestamp::parse("2023-02-29").is_none());
+        assert!(Timestamp::parse("2024-13-01").is_none());
+        assert!(Timestamp::parse("2024-01-01 24:00:00").is_none());
+        assert!(Timestamp::parse("yesterday").is_none());
+    }
+
+    #[test]
+    fn test_day_and_time_units() {
+        assert_eq!(add("2024-02-28", 1, IntervalUnit::Day), "2024-02-29");
+        assert_eq!(add("2023-12-31", 1, IntervalUnit::Day), "2024-01-01");
+        assert_eq!(add("2024-03-01", -1, IntervalUnit::Day), "2024-02-29");
+        assert_eq!(add("2024-01-01", -2, IntervalUnit::Week), "2023-12-18");
+        assert_eq!(add("2024-12-31 23:59:59", 1, IntervalUnit::Second), "2025-01-01 00:00:00");
+        assert_eq!(add("2024-01-01 00:30:00", -45, IntervalUnit::Minute), "2023-12-31 23:45:00");
+        // A time unit turns a date into a datetime
+        assert_eq!(add("2024-01-01", 36, IntervalUnit::Hour), "2024-01-02 12:00:00");
+    }
+
+    #[test]
+    fn test_month_and_year_units_clamp_day() {
+        assert_eq!(add("2024-01-31", 1, IntervalUnit::Month), "2024-02-29");
+        assert_eq!(add("2023-01-31", 1, IntervalUnit::Month), "2023-02-28");
+        assert_eq!(add("2024-03-31", -1, IntervalUnit::Month), "2024-02-29");
+        assert_eq!(add("2024-10-31 10:00:00", 2, IntervalUnit::Month), "2024-12-31 10:00:00");
+        assert_eq!(add("2024-11-30", 3, IntervalUnit::Month), "2025-02-28");
+        assert_eq!(add("2024-02-29", 1, IntervalUnit::Year), "2025-02-28");
+        assert_eq!(add("2024-02-29", -4, IntervalUnit::Year), "2020-02-29");
+        assert!(Timestamp::parse("9999-12-31").unwrap().add_interval(1, IntervalUnit::Day).is_none());
+    }
+
+    #[test]
+    fn test_date_parts_and_order() {
+        let date = Date::parse("2024-02-29").unwrap();
+        assert_eq!((date.year(), date.month(), date.day()), (2024, 2, 29));
+        assert_eq!(Date::parse("2024-02-29 23:59:59"), Some(date));
+        assert!(Date::parse("2023-02-29").is_none());
+        assert!(Date::parse("1969-12-31").unwrap() < Date::from_days(0));
+        assert_eq!(Date::from_days(0).to_string(), "1970-01-01");
+
+        assert_eq!(date.cmp_text("2024-02-29"), Some(Ordering::Equal));
+        assert_eq!(date.cmp_text("2024-02-29 00:00:01"), Some(Ordering::Less));
+        assert_eq!(date.cmp_text("2024-02-28 12:00:00"), Some(Ordering::Greater));
+        assert_eq!(date.cmp_text("soon"), None);
+
+        let json = serde_json::to_string(&date).unwrap();
+        assert_eq!(json, "\"2024-02-29\"");
+        assert_eq!(serde_json::from_str::<Date>(&json).unwrap(), date);
+        assert!(serde_json::from_str::<Date>("\"2024-02-30\"").is_err());
+    }
+
+    #[test]
+    fn test_now_round_trips() {
+        let now = Timestamp::now();
+        assert_eq!(Timestamp::parse(&now.to_string()), Some(now));
+    }
+}
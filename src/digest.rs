@@ -0,0 +1,335 @@
+//! Query digests
+//!
+//! A digest is a normalized form of a statement with every literal replaced by
+//! `?`, so structurally identical queries group together regardless of the
+//! values they use:
+//!
+//! ```text
+//! select * from t where id = 42 and name in ('a', 'b')
+//!   => SELECT * FROM t WHERE id = ? AND name IN (...)
+//! ```
+//!
+//! Keywords are upper-cased, whitespace is canonical, `IN` lists collapse to
+//! `(...)` and multi-row `VALUES` lists collapse to their first row. The
+//! digest also carries a 64-bit FNV-1a hash of the normalized text, which is
+//! stable across runs and builds.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::lexer::{Lexer, Token};
+
+/// Normalized text and stable hash of a statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryDigest {
+    /// Statement text with literals replaced by `?`
+    pub text: String,
+    /// FNV-1a hash of `text`
+    pub hash: u64,
+}
+
+impl QueryDigest {
+    /// Compute the digest of a SQL statement.
+    ///
+    /// Statements the lexer rejects are digested from their whitespace-collapsed
+    /// text, so every statement has a digest.
+    pub fn new(sql: &str) -> Self {
+        let text = match Lexer::new(sql).tokenize() {
+            Ok(tokens) => normalize(&tokens),
+            Err(_) => sql.split_whitespace().collect::<Vec<_>>().join(" "),
+        };
+        let hash = fnv1a(text.as_bytes());
+        QueryDigest { text, hash }
+    }
+}
+
+impl fmt::Display for QueryDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.hash)
+    }
+}
+
+/// 64-bit FNV-1a
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+fn is_literal(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::NumberLiteral(_) | Token::StringLiteral(_) | Token::True | Token::False | Token::Placeholder
+    )
+}
+
+/// Whether a token ends an operand, so a following `-` is binary
+fn ends_operand(token: &Token) -> bool {
+    is_literal(token) || matches!(token, Token::Identifier(_) | Token::RightParen | Token::Star | Token::Null)
+}
+
+/// Index just past the `)` matching the `(` at `open`, if any
+fn closing_paren(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token {
+            Token::LeftParen => depth += 1,
+            Token::RightParen => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether `tokens` is a comma-separated list of (possibly negated) literals
+fn is_literal_list(tokens: &[Token]) -> bool {
+    !tokens.is_empty() && tokens.split(|t| *t == Token::Comma).all(|item| match item {
+        [t] => is_literal(t),
+        [Token::Minus, t] => is_literal(t),
+        _ => false,
+    })
+}
+
+/// Replace literals and collapse value lists into a canonical token sequence
+fn normalize_tokens(tokens: &[Token]) -> Vec<Token> {
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        match token {
+            Token::Eof => break,
+            Token::Semicolon if tokens[i + 1..].iter().all(|t| matches!(t, Token::Eof | Token::Semicolon)) => break,
+            // IN (1, 2, 3) => IN (...)
+            Token::LeftParen if out.last() == Some(&Token::In) => {
+                if let Some(end) = closing_paren(tokens, i) {
+                    if is_literal_list(&tokens[i + 1..end - 1]) {
+                        out.extend([Token::LeftParen, Token::Identifier("...".into()), Token::RightParen]);
+                        i = end;
+                        continue;
+                    }
+                }
+                out.push(Token::LeftParen);
+            }
+            // VALUES (..), (..) => VALUES (..) when the rows normalize alike
+            Token::Comma if out.last() == Some(&Token::RightParen) && tokens.get(i + 1) == Some(&Token::LeftParen) => {
+                let row_start = out.iter().rposition(|t| *t == Token::Values);
+                let next_end = closing_paren(tokens, i + 1);
+                match (row_start, next_end) {
+                    (Some(values_at), Some(end)) => {
+                        let first_row = &out[values_at + 1..];
+                        let first_end = closing_paren(first_row, 0);
+                        let next_row = normalize_tokens(&tokens[i + 1..end]);
+                        if first_end == Some(first_row.len()) && next_row == first_row {
+                            i = end;
+                            continue;
+                        }
+                        out.push(Token::Comma);
+                    }
+                    _ => out.push(Token::Comma),
+                }
+            }
+            Token::Minus if !out.last().is_some_and(ends_operand)
+                && tokens.get(i + 1).is_some_and(is_literal) =>
+            {
+                out.push(Token::Placeholder);
+                i += 2;
+                continue;
+            }
+            t if is_literal(t) => out.push(Token::Placeholder),
+            t => out.push(t.clone()),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Render the normalized statement as text
+fn normalize(tokens: &[Token]) -> String {
+    let tokens = normalize_tokens(tokens);
+    let mut text = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|p| &tokens[p]);
+        let before_prev = i.checked_sub(2).map(|p| &tokens[p]);
+        let no_space = match (prev, token) {
+            (None, _) => true,
+            (_, Token::Comma | Token::RightParen | Token::Dot) => true,
+            (Some(Token::LeftParen | Token::Dot), _) => true,
+            // Function call `name(`, but not a column list after a table name
+            (Some(Token::Identifier(_)), Token::LeftParen) => {
+                !matches!(before_prev, Some(Token::Into | Token::Table | Token::On))
+            }
+            _ => false,
+        };
+        if !no_space {
+            text.push(' ');
+        }
+        text.push_str(&token_text(token));
+    }
+    text
+}
+
+/// Canonical text of a token
+fn token_text(token: &Token) -> Cow<'static, str> {
+    let text = match token {
+        Token::Identifier(name) => return Cow::Owned(name.clone()),
+        Token::UserVariable(name) => return Cow::Owned(format!("@{}", name)),
+        Token::SystemVariable(name) => return Cow::Owned(format!("@@{}", name)),
+        Token::StringLiteral(_) | Token::NumberLiteral(_) | Token::Placeholder => "?",
+        Token::Select => "SELECT",
+        Token::From => "FROM",
+        Token::Where => "WHERE",
+        Token::Insert => "INSERT",
+        Token::Into => "INTO",
+        Token::Values => "VALUES",
+        Token::Update => "UPDATE",
+        Token::Set => "SET",
+        Token::Delete => "DELETE",
+        Token::Create => "CREATE",
+        Token::Table => "TABLE",
+        Token::Drop => "DROP",
+        Token::Truncate => "TRUNCATE",
+        Token::If => "IF",
+        Token::Exists => "EXISTS",
+        Token::Not => "NOT",
+        Token::Null => "NULL",
+        Token::And => "AND",
+        Token::Or => "OR",
+        Token::Is => "IS",
+        Token::Like => "LIKE",
+        Token::In => "IN",
+        Token::Begin => "BEGIN",
+        Token::Start => "START",
+        Token::Transaction => "TRANSACTION",
+        Token::Commit => "COMMIT",
+        Token::Rollback => "ROLLBACK",
+        Token::Checkpoint => "CHECKPOINT",
+        Token::Vacuum => "VACUUM",
+        Token::Primary => "PRIMARY",
+        Token::Key => "KEY",
+        Token::Default => "DEFAULT",
+        Token::AutoIncrement => "AUTO_INCREMENT",
+        Token::Unique => "UNIQUE",
+        Token::Show => "SHOW",
+        Token::Tables => "TABLES",
+        Token::Describe => "DESCRIBE",
+        Token::Desc => "DESC",
+        Token::Limit => "LIMIT",
+        Token::Order => "ORDER",
+        Token::By => "BY",
+        Token::Asc => "ASC",
+        Token::Index => "INDEX",
+        Token::On => "ON",
+        Token::Join => "JOIN",
+        Token::Inner => "INNER",
+        Token::Left => "LEFT",
+        Token::Right => "RIGHT",
+        Token::Outer => "OUTER",
+        Token::As => "AS",
+        Token::Group => "GROUP",
+        Token::Having => "HAVING",
+        Token::Alter => "ALTER",
+        Token::Engine => "ENGINE",
+        Token::Int => "INT",
+        Token::Integer => "INTEGER",
+        Token::Bigint => "BIGINT",
+        Token::Float => "FLOAT",
+        Token::Double => "DOUBLE",
+        Token::Real => "REAL",
+        Token::Varchar => "VARCHAR",
+        Token::Text => "TEXT",
+        Token::Boolean => "BOOLEAN",
+        Token::Bool => "BOOL",
+        Token::Json => "JSON",
+        Token::True => "TRUE",
+        Token::False => "FALSE",
+        Token::Star => "*",
+        Token::Comma => ",",
+        Token::LeftParen => "(",
+        Token::RightParen => ")",
+        Token::Semicolon => ";",
+        Token::Equal => "=",
+        Token::NotEqual => "!=",
+        Token::LessThan => "<",
+        Token::LessThanEq => "<=",
+        Token::GreaterThan => ">",
+        Token::GreaterThanEq => ">=",
+        Token::Plus => "+",
+        Token::Minus => "-",
+        Token::Slash => "/",
+        Token::Arrow => "->",
+        Token::ArrowText => "->>",
+        Token::Dot => ".",
+        Token::Eof => "",
+    };
+    Cow::Borrowed(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(sql: &str) -> String {
+        QueryDigest::new(sql).text
+    }
+
+    #[test]
+    fn test_literals_replaced() {
+        assert_eq!(text("SELECT * FROM t WHERE id = 5"), "SELECT * FROM t WHERE id = ?");
+        assert_eq!(
+            text("select name, count(*) from users where age > -3 and name like 'a%' group by name;"),
+            "SELECT name, count(*) FROM users WHERE age > ? AND name LIKE ? GROUP BY name"
+        );
+        assert_eq!(text("SELECT a - 1 FROM t"), "SELECT a - ? FROM t");
+        assert_eq!(text("UPDATE t SET flag = TRUE WHERE id = ?"), "UPDATE t SET flag = ? WHERE id = ?");
+    }
+
+    #[test]
+    fn test_same_shape_same_digest() {
+        let a = QueryDigest::new("SELECT * FROM t WHERE id = 1 AND name = 'x'");
+        let b = QueryDigest::new("select *   from t\nwhere id = 987 and name = 'something else'");
+        assert_eq!(a, b);
+        assert_eq!(a.to_string().len(), 16);
+
+        // IN lists and VALUES rows of any length collapse
+        assert_eq!(
+            QueryDigest::new("SELECT * FROM t WHERE id IN (1, 2, 3)"),
+            QueryDigest::new("SELECT * FROM t WHERE id IN (7)")
+        );
+        assert_eq!(text("SELECT * FROM t WHERE id IN (1, -2)"), "SELECT * FROM t WHERE id IN (...)");
+        assert_eq!(
+            QueryDigest::new("INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, 'c')"),
+            QueryDigest::new("INSERT INTO t VALUES (9, 'z')")
+        );
+        assert_eq!(text("INSERT INTO t (a, b) VALUES (1, 'a'), (2, 'b')"), "INSERT INTO t (a, b) VALUES (?, ?)");
+    }
+
+    #[test]
+    fn test_different_shape_different_digest() {
+        let base = QueryDigest::new("SELECT * FROM t WHERE id = 1");
+        for other in [
+            "SELECT * FROM t WHERE id > 1",
+            "SELECT * FROM u WHERE id = 1",
+            "SELECT id FROM t WHERE id = 1",
+            "SELECT * FROM t WHERE id = 1 LIMIT 5",
+            "SELECT * FROM t WHERE id IS NULL",
+        ] {
+            assert_ne!(QueryDigest::new(other), base, "{}", other);
+        }
+        // A VALUES row with a different shape is kept
+        assert_eq!(text("INSERT INTO t VALUES (1, 2), (1, 2 + 3)"), "INSERT INTO t VALUES (?, ?), (?, ? + ?)");
+    }
+
+    #[test]
+    fn test_hash_is_stable() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,79 @@
+//! Background checkpointer for Granite
+//!
+//! Explicit transactions checkpoint when they commit, but auto-commit writes
+//! never pass through a commit and would grow the WAL without bound. The
+//! checkpointer thread watches the WAL file and, once it exceeds
+//! `GraniteConfig::checkpoint_threshold_bytes`, runs a checkpoint: dirty tables
+//! are flushed, a checkpoint record and marker are written and the WAL is
+//! truncated. Foreground statements only wait on the table locks the flush
+//! takes, one table at a time.
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::engines::granite::TransactionManager;
+use crate::storage::StorageEngine;
+
+/// How often the WAL size is checked
+const CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Handle to the background checkpointer thread; stops it on drop
+pub struct Checkpointer {
+    /// Set to request shutdown; the condvar wakes the thread early
+    shutdown: Arc<(Mutex<bool>, Condvar)>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Checkpointer {
+    /// Start checkpointing `txn_manager`'s WAL against `storage`
+    pub fn start(txn_manager: Arc<TransactionManager>, storage: Arc<StorageEngine>) -> Self {
+        let shutdown = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_shutdown = Arc::clone(&shutdown);
+        let handle = thread::spawn(move || {
+            checkpointer_loop(&txn_manager, &storage, &thread_shutdown);
+        });
+        Self {
+            shutdown,
+            handle: Mutex::new(Some(handle)),
+        }
+    }
+
+    /// Stop the thread and wait for a checkpoint in progress to finish
+    pub fn stop(&self) {
+        let (stop, signal) = &*self.shutdown;
+        *stop.lock().unwrap() = true;
+        signal.notify_all();
+        if let Some(handle) = self.handle.lock().unwrap().take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Checkpointer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn checkpointer_loop(
+    txn_manager: &TransactionManager,
+    storage: &StorageEngine,
+    shutdown: &(Mutex<bool>, Condvar),
+) {
+    let (stop, signal) = shutdown;
+    loop {
+        let guard = stop.lock().unwrap();
+        let (guard, _) = signal
+            .wait_timeout_while(guard, CHECK_INTERVAL, |stop| !*stop)
+            .unwrap();
+        if *guard {
+            break;
+        }
+        drop(guard);
+
+        if let Err(e) = txn_manager.checkpoint_if_needed(storage) {
+            log::error!("Background checkpoint failed: {}", e);
+        }
+    }
+}
@@ -42,10 +42,7 @@ impl EngineHandler for GraniteHandler {
                 let pk_values: Vec<String> = pk_columns.iter()
                     .filter_map(|&idx| values.get(idx).map(|v| v.to_string()))
                     .collect();
-                return Err(MiniSqlError::Constraint(format!(
-                    "Duplicate entry '{}' for key 'PRIMARY'",
-                    pk_values.join("-")
-                )));
+                return Err(MiniSqlError::duplicate_primary_key(&pk_values.join("-")));
             }
         }
 
@@ -58,6 +55,13 @@ impl EngineHandler for GraniteHandler {
         Ok(row_id)
     }
 
+    fn find_duplicate(&self, table_name: &str, columns: &[usize], values: &[Value]) -> Result<Option<Row>> {
+        if columns.is_empty() {
+            return Ok(None);
+        }
+        self.storage.find_unique_conflict(table_name, columns, values, None)
+    }
+
     fn update(
         &self, 
         txn_id: TxnId, 
@@ -81,10 +85,7 @@ impl EngineHandler for GraniteHandler {
                 let pk_values: Vec<String> = pk_columns.iter()
                     .filter_map(|&idx| new_values.get(idx).map(|v| v.to_string()))
                     .collect();
-                return Err(MiniSqlError::Constraint(format!(
-                    "Duplicate entry '{}' for key 'PRIMARY'",
-                    pk_values.join("-")
-                )));
+                return Err(MiniSqlError::duplicate_primary_key(&pk_values.join("-")));
             }
         }
 
@@ -113,6 +114,17 @@ impl EngineHandler for GraniteHandler {
         self.storage.scan_table(table_name)
     }
 
+    fn scan_sampled(&self, table_name: &str, keep: &mut dyn FnMut(usize) -> bool) -> Result<Vec<Row>> {
+        self.storage.scan_table_sampled(table_name, keep)
+    }
+
+    fn row_count(&self, txn_id: TxnId, table_name: &str) -> Result<Option<u64>> {
+        // Storage already holds other transactions' uncommitted changes; take them back out
+        let rows = self.storage.row_count(table_name)?;
+        let uncommitted = self.txn_manager.uncommitted_row_delta(txn_id, table_name);
+        Ok(Some(rows.saturating_add_signed(-uncommitted)))
+    }
+
     fn flush(&self, table_name: &str) -> Result<()> {
         // Check if we should do an async save
         let async_saves = std::env::var("MINISQL_ASYNC_SAVES")
@@ -0,0 +1,197 @@
+//! Row locks held by Granite transactions
+//!
+//! Rows read by `SELECT ... FOR UPDATE` and rows changed by UPDATE or DELETE
+//! inside a transaction are locked until that transaction commits or rolls
+//! back. A statement that needs a row locked by another transaction waits for
+//! it to be released, giving up with MySQL error 1205 once its lock wait
+//! timeout has passed. Autocommit statements wait for conflicting locks but
+//! never hold any, since their changes are committed as soon as they finish.
+//!
+//! Waiting transactions form a wait-for graph. A transaction about to wait
+//! for one that is already waiting, directly or through others, for it would
+//! wait forever: it is chosen as the deadlock victim instead and gets MySQL
+//! error 1213, and the executor rolls it back so the others can proceed.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Condvar, Mutex};
+use std::time::Instant;
+
+use crate::error::{MiniSqlError, Result};
+use super::types::TxnId;
+
+#[derive(Default)]
+struct LockState {
+    /// table -> row id -> transaction holding the lock
+    held: HashMap<String, HashMap<u64, TxnId>>,
+    /// Waiting transaction -> transactions holding the rows it waits for
+    waits_for: HashMap<TxnId, HashSet<TxnId>>,
+}
+
+impl LockState {
+    /// Transactions other than `owner` holding a lock on any of `row_ids`
+    fn blockers(&self, owner: Option<TxnId>, table: &str, row_ids: &[u64]) -> HashSet<TxnId> {
+        let Some(held) = self.held.get(table) else {
+            return HashSet::new();
+        };
+        row_ids.iter()
+            .filter_map(|id| held.get(id).copied())
+            .filter(|holder| Some(*holder) != owner)
+            .collect()
+    }
+
+    /// Whether one of `blockers` waits, directly or transitively, for `txn_id`
+    fn waits_for_txn(&self, blockers: &HashSet<TxnId>, txn_id: TxnId) -> bool {
+        let mut seen = HashSet::new();
+        let mut pending: Vec<TxnId> = blockers.iter().copied().collect();
+        while let Some(txn) = pending.pop() {
+            if txn == txn_id {
+                return true;
+            }
+            if seen.insert(txn) {
+                pending.extend(self.waits_for.get(&txn).into_iter().flatten().copied());
+            }
+        }
+        false
+    }
+}
+
+/// Row locks of every table, keyed by table name and row id
+#[derive(Default)]
+pub struct RowLockTable {
+    state: Mutex<LockState>,
+    /// Signalled whenever a transaction releases its locks
+    released: Condvar,
+}
+
+impl RowLockTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait until no transaction other than `owner` holds a lock on any of
+    /// `row_ids`, then lock them for `owner` (when given). Returns whether the
+    /// call had to wait.
+    ///
+    /// Fails with a deadlock error, without waiting, when a transaction
+    /// holding one of the rows is itself waiting for `owner`.
+    pub fn acquire(&self, owner: Option<TxnId>, table: &str, row_ids: &[u64], deadline: Instant) -> Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        let mut waited = false;
+        let result = loop {
+            let blockers = state.blockers(owner, table, row_ids);
+            if blockers.is_empty() {
+                break Ok(waited);
+            }
+            if let Some(txn_id) = owner {
+                if state.waits_for_txn(&blockers, txn_id) {
+                    break Err(MiniSqlError::deadlock());
+                }
+                state.waits_for.insert(txn_id, blockers);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                break Err(MiniSqlError::lock_wait_timeout());
+            }
+            waited = true;
+            state = self.released.wait_timeout(state, deadline - now).unwrap().0;
+        };
+
+        if let Some(txn_id) = owner {
+            state.waits_for.remove(&txn_id);
+            if result.is_ok() && !row_ids.is_empty() {
+                let held = state.held.entry(table.to_string()).or_default();
+                held.extend(row_ids.iter().map(|id| (*id, txn_id)));
+            }
+        }
+        result
+    }
+
+    /// Whether any row of `table` is locked
+    pub fn has_locks(&self, table: &str) -> bool {
+        self.state.lock().unwrap().held.contains_key(table)
+    }
+
+    /// Release every lock held by `txn_id` and wake the statements waiting for them
+    pub fn release_all(&self, txn_id: TxnId) {
+        let mut state = self.state.lock().unwrap();
+        for held in state.held.values_mut() {
+            held.retain(|_, holder| *holder != txn_id);
+        }
+        state.held.retain(|_, held| !held.is_empty());
+        state.waits_for.remove(&txn_id);
+        self.released.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    fn deadline_in(ms: u64) -> Instant {
+        Instant::now() + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_conflicting_lock_times_out() {
+        let locks = RowLockTable::new();
+        assert!(!locks.acquire(Some(1), "t", &[1, 2], deadline_in(0)).unwrap());
+        // The holder may lock its rows again
+        assert!(!locks.acquire(Some(1), "t", &[2], deadline_in(0)).unwrap());
+        // Other rows and other tables are free
+        assert!(!locks.acquire(Some(2), "t", &[3], deadline_in(0)).unwrap());
+        assert!(!locks.acquire(Some(2), "u", &[1], deadline_in(0)).unwrap());
+
+        let err = locks.acquire(Some(2), "t", &[2], deadline_in(20)).unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1205);
+        assert!(locks.acquire(None, "t", &[1], deadline_in(20)).is_err());
+    }
+
+    #[test]
+    fn test_release_wakes_waiter() {
+        let locks = Arc::new(RowLockTable::new());
+        locks.acquire(Some(1), "t", &[7], deadline_in(0)).unwrap();
+
+        let waiter = {
+            let locks = Arc::clone(&locks);
+            thread::spawn(move || locks.acquire(Some(2), "t", &[7], deadline_in(5_000)))
+        };
+        thread::sleep(Duration::from_millis(50));
+        locks.release_all(1);
+
+        assert!(waiter.join().unwrap().unwrap(), "the waiter should report that it waited");
+        assert!(locks.has_locks("t"));
+        locks.release_all(2);
+        assert!(!locks.has_locks("t"));
+    }
+
+    #[test]
+    fn test_lock_cycle_fails_the_closing_transaction() {
+        let locks = Arc::new(RowLockTable::new());
+        locks.acquire(Some(1), "t", &[1], deadline_in(0)).unwrap();
+        locks.acquire(Some(2), "t", &[2], deadline_in(0)).unwrap();
+        locks.acquire(Some(3), "t", &[3], deadline_in(0)).unwrap();
+
+        // 1 waits for 2, and 2 for 3
+        let waiters: Vec<_> = [(1, 2), (2, 3)].into_iter().map(|(txn, row)| {
+            let locks = Arc::clone(&locks);
+            let waiter = thread::spawn(move || locks.acquire(Some(txn), "t", &[row], deadline_in(5_000)));
+            thread::sleep(Duration::from_millis(50));
+            waiter
+        }).collect();
+
+        // 3 asking for row 1 would close the cycle
+        let err = locks.acquire(Some(3), "t", &[1], deadline_in(5_000)).unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1213);
+
+        // Once the victim releases its locks, the others get theirs in turn
+        locks.release_all(3);
+        let mut waiters = waiters.into_iter();
+        let second = waiters.next_back().unwrap();
+        assert!(second.join().unwrap().unwrap());
+        locks.release_all(2);
+        assert!(waiters.next().unwrap().join().unwrap().unwrap());
+    }
+}
@@ -14,10 +14,12 @@
 //! - **Durable**: Write-ahead logging ensures data survives crashes
 //! - **Strong**: High throughput via deferred fsync with commit latches
 //!
-//! ### Isolation Level: Read Committed
-//! - Transactions only see committed data
-//! - No dirty reads (reading uncommitted changes from other transactions)
-//! - Phantom reads possible (new rows can appear during transaction)
+//! ### Isolation Levels: Read Committed and Repeatable Read
+//! - Read Committed (default): every statement reads the latest rows
+//! - Repeatable Read: the executor snapshots a table the first time a
+//!   transaction reads it; later reads see the snapshot plus its own writes
+//! - Writes are applied in place, so a read outside a snapshot can see
+//!   another transaction's uncommitted changes
 //!
 //! ### Implementation
 //! 1. BEGIN: Allocate transaction ID, create in-memory change buffer
@@ -38,6 +40,12 @@
 //! - COMMIT waits on a "commit latch" until the fsync completes
 //! - This allows many commits to share a single fsync, dramatically improving throughput
 //!
+//! ### Semi-Synchronous Replication
+//!
+//! With `semi_sync_timeout_ms` set, COMMIT and autocommit writes additionally
+//! wait (after fsync) until a replica acknowledges their LSN, falling back to
+//! async on timeout. See the `replication` module.
+//!
 //! ### Recovery
 //! On startup:
 //! 1. Read WAL from last checkpoint
@@ -49,14 +57,16 @@
 pub use super::types::{Lsn, TxnId, TxnState, Transaction};
 pub use super::log::{LogRecord, LogOperation};
 pub use super::wal::{GraniteConfig, GraniteWorkerHandle, GraniteWriteRequest, GraniteMessage};
+use super::locks::RowLockTable;
 use super::recovery;
+use super::replication::ReplicaAckState;
 
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{mpsc, RwLock};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::error::{MiniSqlError, Result};
 use crate::storage::StorageEngine;
@@ -112,6 +122,13 @@ pub struct TransactionManager {
     config: EngineConfig,
     /// Bytes written since last checkpoint (approximate)
     bytes_since_checkpoint: AtomicU64,
+    /// Replica acknowledgements for semi-synchronous commits
+    replica_acks: ReplicaAckState,
+    /// Row locks held until each transaction ends
+    row_locks: RowLockTable,
+    /// Shared by every WAL append and held exclusively by a checkpoint, so
+    /// nothing is logged between its flush and the WAL truncation
+    wal_gate: RwLock<()>,
 }
 
 impl TransactionManager {
@@ -140,6 +157,9 @@ impl TransactionManager {
             committed_txns: RwLock::new(HashSet::new()),
             config,
             bytes_since_checkpoint: AtomicU64::new(0),
+            replica_acks: ReplicaAckState::new(),
+            row_locks: RowLockTable::new(),
+            wal_gate: RwLock::new(()),
         })
     }
 
@@ -150,6 +170,11 @@ impl TransactionManager {
         self.data_dir.join("wal.checkpoint")
     }
 
+    /// Current size of the WAL file in bytes (0 if it cannot be read)
+    pub fn wal_size(&self) -> u64 {
+        fs::metadata(self.data_dir.join("wal.log")).map(|m| m.len()).unwrap_or(0)
+    }
+
     /// Get current timestamp in milliseconds
     fn timestamp() -> u64 {
         match SystemTime::now().duration_since(UNIX_EPOCH) {
@@ -166,6 +191,12 @@ impl TransactionManager {
     /// Write a log record (non-blocking after write, does NOT wait for fsync).
     /// Use `write_log_durable` for operations that require durability guarantee.
     fn write_log(&self, record: LogRecord) -> Result<()> {
+        let _gate = self.wal_gate.read().expect("wal_gate lock poisoned");
+        self.append_log(record)
+    }
+
+    /// Send a log record to the worker without waiting for a checkpoint to end
+    fn append_log(&self, record: LogRecord) -> Result<()> {
         let (tx, rx) = mpsc::sync_channel(1);
         let req = GraniteWriteRequest {
             record: record.clone(),
@@ -190,6 +221,27 @@ impl TransactionManager {
             )))?
     }
 
+    /// Write a batch of log records, queueing them all before waiting on any,
+    /// so the worker writes them in as few batches as it can
+    fn write_logs(&self, records: Vec<LogRecord>) -> Result<()> {
+        let closed = |e: String| MiniSqlError::Io(std::io::Error::other(e));
+        let _gate = self.wal_gate.read().expect("wal_gate lock poisoned");
+        let approx_bytes = std::mem::size_of::<LogRecord>() as u64 * records.len() as u64;
+        self.bytes_since_checkpoint.fetch_add(approx_bytes, Ordering::Relaxed);
+
+        let mut responses = Vec::with_capacity(records.len());
+        for record in records {
+            let (tx, rx) = mpsc::sync_channel(1);
+            self.granite_worker.sender.send(GraniteMessage::Write(GraniteWriteRequest { record, responder: tx }))
+                .map_err(|e| closed(format!("Granite worker channel closed: {}", e)))?;
+            responses.push(rx);
+        }
+        for rx in responses {
+            rx.recv().map_err(|e| closed(format!("Granite worker response lost: {}", e)))??;
+        }
+        Ok(())
+    }
+
     /// Write log record and WAIT for durable fsync (for COMMIT and other critical operations).
     /// This guarantees the record is on disk before returning.
     fn write_log_durable(&self, record: LogRecord) -> Result<()> {
@@ -224,6 +276,23 @@ impl TransactionManager {
         self.granite_worker.durable_lsn()
     }
 
+    /// Record that a replica has received the WAL up to `lsn`.
+    /// Semi-synchronous commits waiting on an LSN at or below it return.
+    pub fn acknowledge_replica(&self, lsn: Lsn) {
+        self.replica_acks.acknowledge(lsn);
+    }
+
+    /// The highest LSN acknowledged by a replica
+    pub fn replica_acked_lsn(&self) -> Lsn {
+        self.replica_acks.acked_lsn()
+    }
+
+    /// Whether commits currently wait for a replica acknowledgement: semi-sync
+    /// is configured and has not fallen back to async after a timeout
+    pub fn semi_sync_active(&self) -> bool {
+        self.config.granite.semi_sync_timeout_ms.is_some() && self.replica_acks.is_active()
+    }
+
     /// Get the engine type being used
     pub fn engine_type(&self) -> EngineType {
         self.config.engine_type()
@@ -231,9 +300,15 @@ impl TransactionManager {
 
     /// Begin a new transaction
     pub fn begin(&self) -> Result<TxnId> {
+        // Registered and logged under the WAL gate, so a checkpoint sees
+        // either no trace of the transaction or both its entry and its BEGIN
+        let _gate = self.wal_gate.read().expect("wal_gate lock poisoned");
         let txn_id = self.next_txn_id.fetch_add(1, Ordering::SeqCst);
         let lsn = self.alloc_lsn();
 
+        let txn = Transaction::new(txn_id, lsn);
+        self.active_txns.write().expect("active_txns lock poisoned").insert(txn_id, txn);
+
         let record = LogRecord {
             lsn,
             txn_id,
@@ -243,10 +318,10 @@ impl TransactionManager {
 
         // BEGIN does not need durable write - if we crash before commit,
         // the transaction is rolled back anyway
-        self.write_log(record)?;
-
-        let txn = Transaction::new(txn_id, lsn);
-        self.active_txns.write().expect("active_txns lock poisoned").insert(txn_id, txn);
+        if let Err(e) = self.append_log(record) {
+            self.active_txns.write().expect("active_txns lock poisoned").remove(&txn_id);
+            return Err(e);
+        }
 
         Ok(txn_id)
     }
@@ -300,6 +375,23 @@ impl TransactionManager {
         };
         self.write_log_durable(record)?;
 
+        // Semi-sync: also wait for a replica to receive the commit. A timeout
+        // is not an error - the commit is durable locally - but degrades to async.
+        if let Some(timeout_ms) = self.config.granite.semi_sync_timeout_ms {
+            self.replica_acks.wait_for_ack(lsn, Duration::from_millis(timeout_ms));
+        }
+
+        Ok(())
+    }
+
+    /// Semi-sync for an autocommit write, which logs no COMMIT record: fsync
+    /// the WAL written so far and wait for a replica to acknowledge it, as
+    /// `commit_durable` does for a COMMIT. Does nothing without semi-sync.
+    pub fn replicate_autocommit(&self) -> Result<()> {
+        if let Some(timeout_ms) = self.config.granite.semi_sync_timeout_ms {
+            let lsn = self.force_sync()?;
+            self.replica_acks.wait_for_ack(lsn, Duration::from_millis(timeout_ms));
+        }
         Ok(())
     }
 
@@ -316,6 +408,7 @@ impl TransactionManager {
             }
             txns.remove(&txn_id);
         }
+        self.row_locks.release_all(txn_id);
 
         // Mark as committed
         self.committed_txns.write().expect("committed_txns lock poisoned").insert(txn_id);
@@ -381,6 +474,7 @@ impl TransactionManager {
             }
             txns.remove(&txn_id);
         }
+        self.row_locks.release_all(txn_id);
 
         // Checkpoint if no active transactions
         // OPTIMIZATION: Only checkpoint if we've accumulated significant log data (>4KB)
@@ -395,6 +489,68 @@ impl TransactionManager {
         Ok(())
     }
 
+    /// Set a named savepoint at the current position of the transaction's undo log.
+    /// An existing savepoint with the same name is replaced (MySQL semantics).
+    pub fn savepoint(&self, txn_id: TxnId, name: &str) -> Result<()> {
+        let mut txns = self.active_txns.write().unwrap();
+        let txn = txns.get_mut(&txn_id).ok_or_else(|| {
+            MiniSqlError::Transaction(format!("Transaction {} not found", txn_id))
+        })?;
+        txn.savepoints.retain(|(sp, _)| !sp.eq_ignore_ascii_case(name));
+        let position = txn.undo_log.len();
+        txn.savepoints.push((name.to_string(), position));
+        Ok(())
+    }
+
+    /// Undo every operation logged after the named savepoint.
+    ///
+    /// The undone operations are removed from the undo log and compensating
+    /// records are written to the WAL, so redoing the transaction after a crash
+    /// reproduces the rolled-back state. Savepoints set after this one are
+    /// discarded; the named savepoint itself is kept.
+    pub fn rollback_to_savepoint(&self, txn_id: TxnId, name: &str, storage: &StorageEngine) -> Result<()> {
+        let undone = {
+            let mut txns = self.active_txns.write().unwrap();
+            let txn = txns.get_mut(&txn_id).ok_or_else(|| {
+                MiniSqlError::Transaction(format!("Transaction {} not found", txn_id))
+            })?;
+            let idx = txn.savepoints.iter()
+                .position(|(sp, _)| sp.eq_ignore_ascii_case(name))
+                .ok_or_else(|| MiniSqlError::savepoint_not_found(name))?;
+            let position = txn.savepoints[idx].1;
+            txn.savepoints.truncate(idx + 1);
+            txn.undo_log.split_off(position)
+        };
+
+        for record in undone.into_iter().rev() {
+            recovery::undo_operation(&record.op, storage)?;
+            if let Some(op) = compensation_for(&record.op) {
+                let lsn = self.alloc_lsn();
+                self.write_log(LogRecord {
+                    lsn,
+                    txn_id,
+                    op,
+                    timestamp: Self::timestamp(),
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove the named savepoint and any savepoints set after it
+    pub fn release_savepoint(&self, txn_id: TxnId, name: &str) -> Result<()> {
+        let mut txns = self.active_txns.write().unwrap();
+        let txn = txns.get_mut(&txn_id).ok_or_else(|| {
+            MiniSqlError::Transaction(format!("Transaction {} not found", txn_id))
+        })?;
+        let idx = txn.savepoints.iter()
+            .position(|(sp, _)| sp.eq_ignore_ascii_case(name))
+            .ok_or_else(|| MiniSqlError::savepoint_not_found(name))?;
+        txn.savepoints.truncate(idx);
+        Ok(())
+    }
+
     /// Log an insert operation (non-durable write - durability comes at commit time)
     pub fn log_insert(
         &self,
@@ -434,6 +590,31 @@ impl TransactionManager {
         Ok(())
     }
 
+    /// Log inserts of many rows at once (non-durable write), as `log_insert` does for one
+    pub fn log_inserts(&self, txn_id: TxnId, table: &str, row_ids: &[u64], rows: &[Vec<Value>]) -> Result<()> {
+        let records: Vec<LogRecord> = row_ids.iter().zip(rows)
+            .map(|(row_id, values)| LogRecord {
+                lsn: self.alloc_lsn(),
+                txn_id,
+                op: LogOperation::Insert {
+                    table: table.to_string(),
+                    row_id: *row_id,
+                    values: values.clone(),
+                },
+                timestamp: Self::timestamp(),
+            })
+            .collect();
+
+        self.write_logs(records.clone())?;
+
+        let mut txns = self.active_txns.write().unwrap();
+        if let Some(txn) = txns.get_mut(&txn_id) {
+            txn.undo_log.extend(records);
+        }
+
+        Ok(())
+    }
+
     /// Log an update operation (non-durable write)
     pub fn log_update(
         &self,
@@ -557,6 +738,56 @@ impl TransactionManager {
         Ok(())
     }
 
+    /// Row IDs of `table` that the transaction has inserted, updated or deleted
+    pub fn modified_row_ids(&self, txn_id: TxnId, table: &str) -> HashSet<u64> {
+        let txns = self.active_txns.read().unwrap();
+        let Some(txn) = txns.get(&txn_id) else {
+            return HashSet::new();
+        };
+        txn.undo_log.iter()
+            .filter_map(|record| match &record.op {
+                LogOperation::Insert { table: t, row_id, .. }
+                | LogOperation::Update { table: t, row_id, .. }
+                | LogOperation::Delete { table: t, row_id, .. } if t == table => Some(*row_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Net rows inserted (positive) or deleted (negative) in `table` by the
+    /// transactions in flight other than `txn_id`: the difference between the
+    /// rows storage holds and those `txn_id` can see
+    pub fn uncommitted_row_delta(&self, txn_id: TxnId, table: &str) -> i64 {
+        let txns = self.active_txns.read().unwrap();
+        txns.values()
+            .filter(|txn| txn.id != txn_id)
+            .flat_map(|txn| &txn.undo_log)
+            .map(|record| match &record.op {
+                LogOperation::Insert { table: t, .. } if t == table => 1,
+                LogOperation::Delete { table: t, .. } if t == table => -1,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Lock `row_ids` of `table` for `txn_id` until it commits or rolls back,
+    /// waiting up to `timeout` for locks held by other transactions. Returns
+    /// whether it had to wait.
+    pub fn lock_rows(&self, txn_id: TxnId, table: &str, row_ids: &[u64], timeout: Duration) -> Result<bool> {
+        self.row_locks.acquire(Some(txn_id), table, row_ids, Instant::now() + timeout)
+    }
+
+    /// Wait up to `timeout` until no transaction holds a lock on `row_ids` of
+    /// `table`, without locking them. Returns whether it had to wait.
+    pub fn wait_for_row_locks(&self, table: &str, row_ids: &[u64], timeout: Duration) -> Result<bool> {
+        self.row_locks.acquire(None, table, row_ids, Instant::now() + timeout)
+    }
+
+    /// Whether any row of `table` is locked by a transaction
+    pub fn has_row_locks(&self, table: &str) -> bool {
+        self.row_locks.has_locks(table)
+    }
+
     /// Check if a transaction is active
     pub fn is_active(&self, txn_id: TxnId) -> bool {
         let txns = self.active_txns.read().unwrap();
@@ -587,7 +818,30 @@ impl TransactionManager {
     }
 
     /// Checkpoint: flush all data and truncate WAL
+    ///
+    /// WAL appends wait until the checkpoint is done, so no transaction can
+    /// log a change after the flush that the truncation would then discard.
     pub fn checkpoint(&self, storage: &StorageEngine) -> Result<()> {
+        let _gate = self.wal_gate.write().expect("wal_gate lock poisoned");
+        self.checkpoint_gated(storage)
+    }
+
+    /// Run `change` with no transaction open and WAL appends held off, then
+    /// checkpoint and truncate the WAL, so recovery replays none of the
+    /// records logged before the change
+    pub fn checkpoint_with(&self, storage: &StorageEngine, change: impl FnOnce() -> Result<()>) -> Result<()> {
+        let _gate = self.wal_gate.write().expect("wal_gate lock poisoned");
+        if !self.active_txns.read().expect("active_txns lock poisoned").is_empty() {
+            return Err(MiniSqlError::Transaction(
+                "Cannot run this statement while other transactions are active".into(),
+            ));
+        }
+        change()?;
+        self.checkpoint_gated(storage)
+    }
+
+    /// Body of a checkpoint; the caller holds the WAL gate exclusively
+    fn checkpoint_gated(&self, storage: &StorageEngine) -> Result<()> {
         // Flush all data to disk
         storage.flush_all()?;
 
@@ -610,7 +864,8 @@ impl TransactionManager {
             },
             timestamp: Self::timestamp(),
         };
-        self.write_log_durable(record)?;
+        self.append_log(record)?;
+        self.granite_worker.wait_for_durable(lsn)?;
 
         // Write checkpoint marker
         let checkpoint_data = serde_json::json!({
@@ -636,6 +891,21 @@ impl TransactionManager {
         Ok(())
     }
 
+    /// Checkpoint if the WAL has grown past `checkpoint_threshold_bytes`.
+    ///
+    /// Skipped while explicit transactions are open: their records must stay in
+    /// the WAL, so the checkpoint could not truncate it. Their commit checkpoints
+    /// instead. Returns whether a checkpoint ran.
+    pub fn checkpoint_if_needed(&self, storage: &StorageEngine) -> Result<bool> {
+        if self.wal_size() <= self.config.granite.checkpoint_threshold_bytes
+            || !self.active_txns.read().unwrap().is_empty()
+        {
+            return Ok(false);
+        }
+        self.checkpoint(storage)?;
+        Ok(true)
+    }
+
     /// Truncate the WAL file (called after checkpoint when safe)
     fn truncate_wal(&self) -> Result<()> {
         match self.truncate_wal_worker() {
@@ -657,6 +927,30 @@ impl TransactionManager {
     }
 }
 
+/// Build the WAL record that reverses a row operation (a compensation record).
+/// Returns None for operations that are not undone row by row.
+fn compensation_for(op: &LogOperation) -> Option<LogOperation> {
+    match op {
+        LogOperation::Insert { table, row_id, values } => Some(LogOperation::Delete {
+            table: table.clone(),
+            row_id: *row_id,
+            old_values: values.clone(),
+        }),
+        LogOperation::Update { table, row_id, old_values, new_values } => Some(LogOperation::Update {
+            table: table.clone(),
+            row_id: *row_id,
+            old_values: new_values.clone(),
+            new_values: old_values.clone(),
+        }),
+        LogOperation::Delete { table, row_id, old_values } => Some(LogOperation::Insert {
+            table: table.clone(),
+            row_id: *row_id,
+            values: old_values.clone(),
+        }),
+        _ => None,
+    }
+}
+
 // Note: TransactionManager is not Clone due to the channel-based WAL worker.
 // The Arc<TransactionManager> pattern should be used for sharing across threads.
 
@@ -50,6 +50,9 @@ fn test_transaction_manager_logging() {
         ],
         auto_increment_counter: 1,
         engine_type: crate::engines::EngineType::default(),
+        storage_format: crate::types::StorageFormat::default(),
+        foreign_keys: Vec::new(),
+        primary_key: Vec::new(),
     };
     storage.apply_schema(schema.clone()).unwrap();
 
@@ -103,6 +106,7 @@ fn test_auto_checkpoint_on_last_commit() {
         checkpoint_threshold_bytes: 100, // Very low threshold
         fsync_interval_ms: 10,
         max_unfsynced_bytes: 1 << 20,
+        semi_sync_timeout_ms: None,
     };
     let txn_mgr = TransactionManager::new_with_config(temp_dir.path().to_path_buf(), config).unwrap();
     let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
@@ -193,6 +197,9 @@ fn test_non_commit_writes_fast() {
         ],
         auto_increment_counter: 1,
         engine_type: crate::engines::EngineType::default(),
+        storage_format: crate::types::StorageFormat::default(),
+        foreign_keys: Vec::new(),
+        primary_key: Vec::new(),
     };
     storage.apply_schema(schema.clone()).unwrap();
 
@@ -393,6 +400,9 @@ fn test_stress_with_deferred_fsync() {
         ],
         auto_increment_counter: 1,
         engine_type: crate::engines::EngineType::default(),
+        storage_format: crate::types::StorageFormat::default(),
+        foreign_keys: Vec::new(),
+        primary_key: Vec::new(),
     };
     storage.apply_schema(schema.clone()).unwrap();
 
@@ -491,3 +501,46 @@ fn test_checkpoint_forces_sync() {
     let checkpoint: serde_json::Value = serde_json::from_str(&checkpoint_content).unwrap();
     assert!(checkpoint["durable_lsn"].as_u64().unwrap() > 0);
 }
+
+#[test]
+fn test_wal_appends_wait_for_checkpoint() {
+    let temp_dir = tempdir().unwrap();
+    let txn_mgr = TransactionManager::new(temp_dir.path().to_path_buf()).unwrap();
+
+    // Hold the gate the way a checkpoint does between its flush and the truncation
+    let gate = txn_mgr.wal_gate.write().unwrap();
+    thread::scope(|scope| {
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        let txn_mgr = &txn_mgr;
+        scope.spawn(move || {
+            let txn_id = txn_mgr.begin().unwrap();
+            txn_mgr.log_insert(txn_id, "t", 1, &[Value::Integer(1)]).unwrap();
+            done_tx.send(txn_id).unwrap();
+        });
+
+        // BEGIN waits for the gate before registering the transaction, so a
+        // checkpoint deciding whether to truncate sees none of it
+        assert!(done_rx.recv_timeout(Duration::from_millis(200)).is_err());
+        assert!(txn_mgr.active_txns.read().unwrap().is_empty());
+
+        drop(gate);
+        let txn_id = done_rx.recv_timeout(Duration::from_secs(5)).expect("the append should finish once the gate opens");
+        assert!(txn_mgr.is_active(txn_id));
+    });
+}
+
+#[test]
+fn test_checkpoint_with_refuses_while_transactions_are_active() {
+    let temp_dir = tempdir().unwrap();
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    let txn_mgr = TransactionManager::new(temp_dir.path().to_path_buf()).unwrap();
+
+    let txn_id = txn_mgr.begin().unwrap();
+    let mut ran = false;
+    assert!(txn_mgr.checkpoint_with(&storage, || { ran = true; Ok(()) }).is_err());
+    assert!(!ran, "the change must not run while a transaction is open");
+
+    txn_mgr.commit(txn_id, &storage).unwrap();
+    txn_mgr.checkpoint_with(&storage, || { ran = true; Ok(()) }).unwrap();
+    assert!(ran);
+}
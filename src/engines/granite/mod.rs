@@ -8,6 +8,9 @@ pub mod recovery;
 pub mod log;
 pub mod types;
 pub mod handler;
+pub mod replication;
+pub mod checkpointer;
+pub mod locks;
 
 // Public API
 pub use manager::TransactionManager;
@@ -15,3 +18,6 @@ pub use wal::{GraniteConfig, GraniteWorkerHandle};
 pub use recovery::*;
 pub use types::*;
 pub use handler::GraniteHandler;
+pub use replication::ReplicaAckState;
+pub use checkpointer::Checkpointer;
+pub use locks::RowLockTable;
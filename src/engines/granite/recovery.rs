@@ -2,14 +2,15 @@
 
 use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::PathBuf;
 
-use crate::error::Result;
+use crate::error::{MiniSqlError, Result};
 use crate::storage::StorageEngine;
 use crate::types::Row;
 use super::log::{LogOperation, LogRecord};
 use super::types::{Lsn, TxnId};
+use super::wal::{wal_header, WAL_FORMAT_VERSION, WAL_HEADER_LEN, WAL_MAGIC};
 
 /// Recovery state tracker
 pub struct RecoveryManager {
@@ -54,20 +55,46 @@ impl RecoveryManager {
         let mut max_txn_id: TxnId = 0;
         let mut current_checkpoint_lsn = checkpoint_lsn;
 
-        // Read binary WAL records (length-prefixed)
+        let file_len = file.metadata()?.len();
+        let legacy = !self.read_format_header(&mut file, file_len)?;
+        if legacy {
+            log::info!("WAL predates format versioning; reading records without checksums");
+        }
+        // Length prefix, then a CRC32 unless the WAL is legacy
+        let frame_header_len: u64 = if legacy { 4 } else { 8 };
+
+        // Read binary WAL records (length-prefixed, checksummed)
+        let mut offset: u64 = if legacy { 0 } else { WAL_HEADER_LEN.min(file_len) };
         loop {
-            // Read 4-byte length prefix
-            let mut len_buf = [0u8; 4];
-            match file.read_exact(&mut len_buf) {
-                Ok(_) => {}
-                Err(ref e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
-                Err(e) => return Err(e.into()),
+            let remaining = file_len - offset;
+            if remaining == 0 {
+                break;
             }
-            let len = u32::from_le_bytes(len_buf) as usize;
-            
+            if remaining < frame_header_len {
+                self.truncate_torn_tail(offset, remaining)?;
+                break;
+            }
+
+            let mut header = [0u8; 8];
+            file.read_exact(&mut header[..frame_header_len as usize])?;
+            let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+            let crc = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+
+            // A record that runs past the end of the file was being written
+            // when the server stopped
+            if frame_header_len + len as u64 > remaining {
+                self.truncate_torn_tail(offset, remaining)?;
+                break;
+            }
+
             // Read the record data
             let mut record_buf = vec![0u8; len];
             file.read_exact(&mut record_buf)?;
+
+            if !legacy && crc32fast::hash(&record_buf) != crc {
+                return Err(MiniSqlError::wal_corrupted(&wal_path.display().to_string(), offset));
+            }
+            offset += frame_header_len + len as u64;
             
             // Deserialize
             let record: LogRecord = match bincode::deserialize(&record_buf) {
@@ -130,12 +157,68 @@ impl RecoveryManager {
         // Flush storage after recovery
         storage.flush_all()?;
 
+        // Every legacy record is now applied, so new records can start a
+        // versioned WAL instead of being appended to the old frames
+        if legacy {
+            self.reset_wal()?;
+            log::info!("Rewrote WAL in format version {}", WAL_FORMAT_VERSION);
+        }
+
         log::info!("Recovery complete. Next LSN: {}, Next TxnId: {}",
                    max_lsn + 1, max_txn_id + 1);
 
         Ok((max_lsn + 1, max_txn_id + 1, committed_txns))
     }
 
+    /// Check the WAL's format header, leaving `file` positioned after it.
+    ///
+    /// Returns `false` for a legacy WAL, which has no header; its records
+    /// start at the beginning of the file. A file too short to hold the
+    /// header but starting like one was cut off while it was being created
+    /// and is treated as an empty versioned WAL.
+    fn read_format_header(&self, file: &mut File, file_len: u64) -> Result<bool> {
+        let mut header = [0u8; WAL_HEADER_LEN as usize];
+        let read = file_len.min(WAL_HEADER_LEN) as usize;
+        file.read_exact(&mut header[..read])?;
+        if read < header.len() {
+            if wal_header().starts_with(&header[..read]) {
+                self.reset_wal()?;
+                return Ok(true);
+            }
+        } else if header.starts_with(WAL_MAGIC) {
+            let version = header[WAL_MAGIC.len()];
+            if version != WAL_FORMAT_VERSION {
+                return Err(MiniSqlError::Internal(format!(
+                    "Unsupported WAL format version {} in '{}'", version, self.wal_path().display()
+                )));
+            }
+            return Ok(true);
+        }
+        file.seek(SeekFrom::Start(0))?;
+        Ok(false)
+    }
+
+    /// Replace the WAL with an empty one in the current format
+    fn reset_wal(&self) -> Result<()> {
+        let mut file = fs::OpenOptions::new().write(true).open(self.wal_path())?;
+        file.set_len(0)?;
+        file.write_all(&wal_header())?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// Drop an incomplete final record so new records are appended after the
+    /// last complete one. `remaining` is the number of bytes from `offset` to
+    /// the end of the file.
+    fn truncate_torn_tail(&self, offset: u64, remaining: u64) -> Result<()> {
+        log::warn!(
+            "WAL ends with an incomplete record at byte offset {} ({} bytes); skipping 1 record",
+            offset, remaining
+        );
+        fs::OpenOptions::new().write(true).open(self.wal_path())?.set_len(offset)?;
+        Ok(())
+    }
+
     /// Read checkpoint LSN from checkpoint file
     fn read_checkpoint(&self) -> Result<Lsn> {
         let cp_path = self.checkpoint_path();
@@ -0,0 +1,155 @@
+//! Granite Engine - semi-synchronous replication acknowledgements
+//!
+//! With semi-sync enabled (`GraniteConfig::semi_sync_timeout_ms`), a COMMIT
+//! or an autocommit write on the primary returns only once it is durable
+//! locally *and* a replica has acknowledged receiving the WAL up to its LSN. Replicas report
+//! their progress through [`TransactionManager::acknowledge_replica`].
+//!
+//! If no acknowledgement arrives within the timeout, the commit returns
+//! anyway (it is already durable on the primary), a warning is logged, and
+//! the primary falls back to asynchronous replication: later commits no
+//! longer wait. Semi-sync resumes once a replica acknowledges the LSN that
+//! timed out, i.e. once it has caught up.
+//!
+//! [`TransactionManager::acknowledge_replica`]: super::TransactionManager::acknowledge_replica
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use super::types::Lsn;
+
+/// Replica acknowledgement state shared by committing transactions
+pub struct ReplicaAckState {
+    /// The highest LSN any replica has acknowledged
+    acked_lsn: AtomicU64,
+    /// LSN of the commit whose wait timed out, or 0 while semi-sync is active
+    degraded_at: AtomicU64,
+    /// Condition variable for commits waiting on an acknowledgement
+    condvar: Condvar,
+    /// Mutex paired with the condvar
+    mutex: Mutex<()>,
+}
+
+impl ReplicaAckState {
+    pub fn new() -> Self {
+        Self {
+            acked_lsn: AtomicU64::new(0),
+            degraded_at: AtomicU64::new(0),
+            condvar: Condvar::new(),
+            mutex: Mutex::new(()),
+        }
+    }
+
+    /// The highest LSN acknowledged by a replica
+    pub fn acked_lsn(&self) -> Lsn {
+        self.acked_lsn.load(Ordering::Acquire)
+    }
+
+    /// Whether commits currently wait for replica acknowledgement
+    pub fn is_active(&self) -> bool {
+        self.degraded_at.load(Ordering::Acquire) == 0
+    }
+
+    /// Record that a replica has received the WAL up to `lsn` and wake the
+    /// commits waiting for it
+    pub fn acknowledge(&self, lsn: Lsn) {
+        let previous = self.acked_lsn.fetch_max(lsn, Ordering::AcqRel);
+        let degraded_at = self.degraded_at.load(Ordering::Acquire);
+        if degraded_at != 0 && lsn >= degraded_at
+            && self.degraded_at.compare_exchange(degraded_at, 0, Ordering::AcqRel, Ordering::Acquire).is_ok()
+        {
+            log::info!("Replica caught up to LSN {}, semi-synchronous replication resumed", lsn);
+        }
+        if lsn > previous {
+            let _guard = self.mutex.lock().unwrap_or_else(|e| e.into_inner());
+            self.condvar.notify_all();
+        }
+    }
+
+    /// Wait until a replica acknowledges `lsn`. Returns immediately while
+    /// degraded to async; on timeout, logs a warning, degrades to async and
+    /// returns false.
+    pub fn wait_for_ack(&self, lsn: Lsn, timeout: Duration) -> bool {
+        if !self.is_active() || self.acked_lsn() >= lsn {
+            return true;
+        }
+
+        let deadline = Instant::now() + timeout;
+        let mut guard = self.mutex.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if self.acked_lsn() >= lsn {
+                return true;
+            }
+            if !self.is_active() {
+                // Another commit timed out meanwhile; don't wait out a second timeout
+                return false;
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            guard = self.condvar.wait_timeout(guard, remaining)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        drop(guard);
+
+        if self.degraded_at.compare_exchange(0, lsn, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+            log::warn!(
+                "No replica acknowledged LSN {} within {}ms (last acknowledged: {}), falling back to asynchronous replication",
+                lsn,
+                timeout.as_millis(),
+                self.acked_lsn()
+            );
+        }
+        false
+    }
+}
+
+impl Default for ReplicaAckState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_ack_wakes_waiter() {
+        let state = Arc::new(ReplicaAckState::new());
+        let replica = Arc::clone(&state);
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            replica.acknowledge(7);
+        });
+        assert!(state.wait_for_ack(7, Duration::from_secs(5)));
+        assert_eq!(state.acked_lsn(), 7);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_timeout_degrades_until_caught_up() {
+        let state = ReplicaAckState::new();
+        state.acknowledge(3);
+        assert!(state.wait_for_ack(3, Duration::from_millis(10)));
+
+        assert!(!state.wait_for_ack(5, Duration::from_millis(10)));
+        assert!(!state.is_active());
+        // Degraded: later commits don't wait
+        assert!(state.wait_for_ack(9, Duration::from_secs(60)));
+
+        // An acknowledgement short of the timed-out LSN is not enough
+        state.acknowledge(4);
+        assert!(!state.is_active());
+        state.acknowledge(5);
+        assert!(state.is_active());
+        // Acknowledged LSNs never go backwards
+        state.acknowledge(2);
+        assert_eq!(state.acked_lsn(), 5);
+    }
+}
@@ -28,6 +28,8 @@ pub struct Transaction {
     pub begin_lsn: Lsn,
     /// Operations for potential rollback
     pub undo_log: Vec<LogRecord>,
+    /// Named savepoints and the undo log length when each was set, oldest first
+    pub savepoints: Vec<(String, usize)>,
 }
 
 impl Transaction {
@@ -37,6 +39,7 @@ impl Transaction {
             state: TxnState::Active,
             begin_lsn,
             undo_log: Vec::new(),
+            savepoints: Vec::new(),
         }
     }
 }
@@ -59,6 +59,10 @@ pub struct GraniteConfig {
     /// Maximum bytes to buffer before forcing an immediate fsync.
     /// This prevents unbounded memory growth during high-throughput periods.
     pub max_unfsynced_bytes: usize,
+    /// Semi-synchronous replication: how long a COMMIT waits for a replica to
+    /// acknowledge its WAL record before falling back to async (milliseconds).
+    /// `None` (the default) disables semi-sync.
+    pub semi_sync_timeout_ms: Option<u64>,
 }
 
 impl Default for GraniteConfig {
@@ -69,6 +73,7 @@ impl Default for GraniteConfig {
             checkpoint_threshold_bytes: 10 * 1024 * 1024, // 10 MB
             fsync_interval_ms: 50,                        // 50ms fsync interval
             max_unfsynced_bytes: 1 << 20,                 // 1 MB max unfsynced buffer
+            semi_sync_timeout_ms: None,
         }
     }
 }
@@ -271,10 +276,11 @@ impl GraniteWorkerHandle {
     /// Create a new Granite engine worker and spawn the worker thread
     pub fn new(wal_path: PathBuf, config: GraniteConfig) -> Result<Self> {
         // Open WAL file in append mode
-        let wal_file = OpenOptions::new()
+        let mut wal_file = OpenOptions::new()
             .create(true)
             .append(true)
             .open(&wal_path)?;
+        write_header_if_empty(&mut wal_file)?;
 
         // Create a bounded sync channel for the Granite engine worker
         let (tx, rx) = mpsc::sync_channel::<GraniteMessage>(10000);
@@ -569,22 +575,60 @@ fn perform_truncate(file: &mut File, wal_path: &PathBuf) -> Result<()> {
 
     // Replace the file handle
     *file = new_file;
+    write_header_if_empty(file)?;
 
     Ok(())
 }
 
+/// Magic bytes at the start of a versioned WAL file, followed by one byte of
+/// [`WAL_FORMAT_VERSION`]. A WAL without them predates versioning and holds
+/// `[payload length: u32 LE][bincode payload]` frames with no checksum.
+pub(super) const WAL_MAGIC: &[u8; 7] = b"MSQLWAL";
+
+/// Version of the record framing written after [`WAL_MAGIC`]
+pub(super) const WAL_FORMAT_VERSION: u8 = 2;
+
+/// Length of the file header: magic plus version byte
+pub(super) const WAL_HEADER_LEN: u64 = 8;
+
+/// The header a new WAL file starts with
+pub(super) fn wal_header() -> [u8; WAL_HEADER_LEN as usize] {
+    let mut header = [0u8; WAL_HEADER_LEN as usize];
+    header[..WAL_MAGIC.len()].copy_from_slice(WAL_MAGIC);
+    header[WAL_MAGIC.len()] = WAL_FORMAT_VERSION;
+    header
+}
+
+/// Start an empty WAL file with the format header
+fn write_header_if_empty(file: &mut File) -> Result<()> {
+    if file.metadata()?.len() == 0 {
+        file.write_all(&wal_header())?;
+        file.flush()?;
+    }
+    Ok(())
+}
+
+/// Encode a log record as it is stored in the WAL, after the file header:
+/// `[payload length: u32 LE][CRC32 of payload: u32 LE][bincode payload]`
+pub(super) fn encode_record(record: &LogRecord) -> Result<Vec<u8>> {
+    let payload = bincode::serialize(record)
+        .map_err(|e| MiniSqlError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
+    let mut frame = Vec::with_capacity(8 + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
 /// Write a batch of log records WITHOUT fsync (for deferred fsync mode).
 /// Returns the number of bytes written.
 pub(super) fn write_records_no_sync(file: &mut File, records: &[LogRecord]) -> Result<usize> {
     let mut total_bytes = 0;
 
     for record in records {
-        let encoded = bincode::serialize(record)
-            .map_err(|e| MiniSqlError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-        let len = encoded.len() as u32;
-        file.write_all(&len.to_le_bytes())?;
-        file.write_all(&encoded)?;
-        total_bytes += 4 + encoded.len();
+        let frame = encode_record(record)?;
+        file.write_all(&frame)?;
+        total_bytes += frame.len();
     }
 
     // Flush to kernel buffer (but don't fsync to disk)
@@ -597,11 +641,7 @@ pub(super) fn write_records_no_sync(file: &mut File, records: &[LogRecord]) -> R
 /// This is the legacy behavior.
 pub(super) fn write_records_with_sync(file: &mut File, records: &[LogRecord]) -> Result<()> {
     for record in records {
-        let encoded = bincode::serialize(record)
-            .map_err(|e| MiniSqlError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-        let len = encoded.len() as u32;
-        file.write_all(&len.to_le_bytes())?;
-        file.write_all(&encoded)?;
+        file.write_all(&encode_record(record)?)?;
     }
 
     file.flush()?;
@@ -654,6 +694,7 @@ mod tests {
             checkpoint_threshold_bytes: 20 * 1024 * 1024,
             fsync_interval_ms: 100,
             max_unfsynced_bytes: 2 << 20,
+            semi_sync_timeout_ms: None,
         };
         assert_eq!(config.batch_timeout_ms, 10);
         assert_eq!(config.max_batch_size, 256);
@@ -1057,16 +1098,16 @@ mod tests {
         handle.sender.send(GraniteMessage::Write(req)).unwrap();
         rx.recv().unwrap().unwrap();
 
-        // File should have content
-        assert!(std::fs::metadata(&wal_path).unwrap().len() > 0);
+        // File should have a record after the header
+        assert!(std::fs::metadata(&wal_path).unwrap().len() > WAL_HEADER_LEN);
 
         // Truncate
         let (tx, rx) = mpsc::sync_channel(1);
         handle.sender.send(GraniteMessage::Truncate(tx)).unwrap();
         rx.recv().unwrap().unwrap();
 
-        // File should be empty
-        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 0);
+        // File should hold only the format header
+        assert_eq!(std::fs::read(&wal_path).unwrap(), wal_header());
 
         // Shutdown
         handle.sender.send(GraniteMessage::Shutdown).unwrap();
@@ -1,7 +1,29 @@
+use std::time::SystemTime;
+
 use crate::error::Result;
 use crate::types::{Row, Value};
 use crate::engines::granite::TxnId;
 
+/// Outcome of an engine's writes of buffered changes to disk (for SHOW STATUS)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlushStatus {
+    /// When a flush last wrote out every dirty table
+    pub last_success: Option<SystemTime>,
+    /// Why the most recent flush failed; cleared by the next successful flush
+    pub last_error: Option<String>,
+    /// Number of flushes that failed
+    pub failures: u64,
+}
+
+/// Size of an engine's replication state (for SHOW STATUS)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CrdtStatus {
+    /// Deltas recorded but not yet pulled by a peer
+    pub pending_deltas: usize,
+    /// Deleted rows whose tombstone is still kept
+    pub tombstones: usize,
+}
+
 /// Trait defining the standard interface for all storage engines.
 /// This allows the Executor to interact with different engines (Granite, Sandstone)
 /// through a uniform API, similar to MySQL's handler API.
@@ -35,12 +57,76 @@ pub trait EngineHandler: Send + Sync {
 
     /// Scan a table returning all rows
     fn scan(&self, table_name: &str) -> Result<Vec<Row>>;
+
+    /// Scan a table in row id order, copying only the rows `keep` accepts.
+    /// `keep` is called once per row with the number of rows not yet
+    /// visited, this one included.
+    /// Default: scan the table, then filter.
+    fn scan_sampled(&self, table_name: &str, keep: &mut dyn FnMut(usize) -> bool) -> Result<Vec<Row>> {
+        let mut rows = self.scan(table_name)?;
+        rows.sort_by_key(|row| row.id);
+        let total = rows.len();
+        Ok(rows.into_iter().enumerate().filter(|(i, _)| keep(total - i)).map(|(_, row)| row).collect())
+    }
+
+    /// Number of rows in a table that `txn_id` sees (committed rows plus its
+    /// own changes), if the engine keeps count, so an unfiltered COUNT(*) can
+    /// skip the scan.
+    /// Default: None, for engines that must be scanned.
+    fn row_count(&self, _txn_id: TxnId, _table_name: &str) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Find the row whose values in `columns` equal those in `values` (the row an
+    /// insert of `values` would duplicate). NULL keys never match.
+    /// Default: scan the table.
+    fn find_duplicate(&self, table_name: &str, columns: &[usize], values: &[Value]) -> Result<Option<Row>> {
+        if columns.is_empty() || columns.iter().any(|&idx| values.get(idx).is_none_or(Value::is_null)) {
+            return Ok(None);
+        }
+        Ok(self.scan(table_name)?
+            .into_iter()
+            .find(|row| columns.iter().all(|&idx| row.values.get(idx) == values.get(idx))))
+    }
     
     /// Optional: Flush changes to disk (for eventually consistent engines)
     fn flush(&self, _table_name: &str) -> Result<()> { 
         Ok(()) 
     }
 
+    /// Optional: Flush every table owned by this engine (used on shutdown)
+    fn flush_all(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Optional: Discard engine-private state for a table that is being dropped
+    fn drop_table(&self, _table_name: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Optional: Discard engine-private rows for a table that is being truncated
+    fn truncate_table(&self, _table_name: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Number of tables with changes not yet written to disk (for SHOW STATUS).
+    /// Default: 0, for engines that write through.
+    fn dirty_table_count(&self) -> usize {
+        0
+    }
+
+    /// How this engine's flushes have gone (for SHOW STATUS).
+    /// Default: None, for engines that write through.
+    fn flush_status(&self) -> Option<FlushStatus> {
+        None
+    }
+
+    /// How much CRDT state this engine holds (for SHOW STATUS).
+    /// Default: None, for engines that do not replicate.
+    fn crdt_status(&self) -> Option<CrdtStatus> {
+        None
+    }
+
     /// Check if this engine supports transactional semantics (BEGIN/COMMIT/ROLLBACK).
     /// Engines that return false will silently ignore transaction boundaries (MySQL MyISAM behavior).
     fn supports_transactions(&self) -> bool {
@@ -8,7 +8,7 @@ pub mod granite;
 pub mod sandstone;
 pub mod handler;
 
-pub use handler::EngineHandler;
+pub use handler::{CrdtStatus, EngineHandler, FlushStatus};
 
 use serde::{Deserialize, Serialize};
 
@@ -94,6 +94,16 @@ impl EngineType {
     }
 }
 
+impl std::str::FromStr for EngineType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s).ok_or_else(|| {
+            format!("invalid engine '{}' (expected granite or sandstone)", s)
+        })
+    }
+}
+
 // Re-exports for convenience
 pub use granite::{GraniteConfig, TransactionManager};
 pub use sandstone::{SandstoneConfig, SandstoneEngine};
@@ -9,6 +9,15 @@ pub struct SandstoneConfig {
     pub max_dirty_tables: Option<usize>,
     /// Enable delta-CRDT tracking for eventual consistency
     pub enable_delta_crdt: bool,
+    /// Identifies this node among replicas exchanging deltas (0 = standalone).
+    /// Replicas need distinct non-zero ids: row ids are allocated so the
+    /// replicas' inserts never collide, and the id breaks ties between
+    /// concurrent writes to the same row.
+    pub replica_id: u16,
+    /// Forget delete tombstones this many milliseconds after the delete, once
+    /// every replica has it. A standalone node also drops the deltas no peer
+    /// pulled by then. (None = keep them)
+    pub tombstone_gc_ms: Option<u64>,
 }
 
 impl Default for SandstoneConfig {
@@ -17,6 +26,8 @@ impl Default for SandstoneConfig {
             flush_interval_ms: 1000,  // 1 second
             max_dirty_tables: None,
             enable_delta_crdt: true,
+            replica_id: 0,
+            tombstone_gc_ms: Some(60_000),  // 1 minute
         }
     }
 }
@@ -28,6 +39,8 @@ impl SandstoneConfig {
             flush_interval_ms: 5000,  // 5 seconds
             max_dirty_tables: None,
             enable_delta_crdt: true,
+            replica_id: 0,
+            tombstone_gc_ms: Some(60_000),  // 1 minute
         }
     }
     
@@ -37,6 +50,8 @@ impl SandstoneConfig {
             flush_interval_ms: 500,  // 500ms
             max_dirty_tables: Some(10),
             enable_delta_crdt: true,
+            replica_id: 0,
+            tombstone_gc_ms: Some(60_000),  // 1 minute
         }
     }
 }
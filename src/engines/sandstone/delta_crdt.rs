@@ -7,9 +7,14 @@
 //! - Last-Write-Wins (LWW) conflict resolution
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
 use crate::types::Value;
 
+/// Low bits of an operation timestamp (and of a replica's row IDs) that hold
+/// the replica id; the Lamport clock sits above them
+pub const REPLICA_ID_BITS: u32 = 16;
+
 /// Represents a delta (incremental change) to a table
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeltaState {
@@ -41,34 +46,47 @@ pub enum DeltaOperation {
 pub struct TableDeltaState {
     /// Current logical clock (Lamport timestamp)
     clock: u64,
-    /// Pending deltas not yet flushed
-    pending_deltas: Vec<DeltaState>,
+    /// Pending deltas not yet flushed, with when they were recorded
+    pending_deltas: Vec<(Instant, DeltaState)>,
     /// Last seen timestamps per row (for LWW conflict resolution)
     row_timestamps: HashMap<u64, u64>,
+    /// Deleted rows whose timestamp is kept so older writes stay ignored,
+    /// with when the delete was recorded or merged
+    tombstones: HashMap<u64, Instant>,
+    /// Replica recording local operations (0 = standalone)
+    replica_id: u16,
 }
 
 impl TableDeltaState {
     pub fn new() -> Self {
+        Self::with_replica_id(0)
+    }
+
+    /// State for a table on replica `replica_id`, whose operations are
+    /// stamped so they never tie with another replica's
+    pub fn with_replica_id(replica_id: u16) -> Self {
         Self {
             clock: 0,
             pending_deltas: Vec::new(),
             row_timestamps: HashMap::new(),
+            tombstones: HashMap::new(),
+            replica_id,
         }
     }
     
-    /// Record a new operation and generate delta
-    pub fn record_operation(&mut self, table_name: String, op: DeltaOperation) -> DeltaState {
+    /// Record a new operation and generate delta.
+    ///
+    /// The operation's timestamp is replaced by the advanced Lamport clock,
+    /// with this replica's id in its low bits as the tie-breaker.
+    pub fn record_operation(&mut self, table_name: String, mut op: DeltaOperation) -> DeltaState {
         self.clock += 1;
+        let stamp = (self.clock << REPLICA_ID_BITS) | u64::from(self.replica_id);
+        match &mut op {
+            DeltaOperation::Upsert { timestamp, .. } | DeltaOperation::Delete { timestamp, .. } => *timestamp = stamp,
+        }
         
         // Update row timestamp for conflict resolution
-        match &op {
-            DeltaOperation::Upsert { row_id, timestamp, .. } => {
-                self.row_timestamps.insert(*row_id, *timestamp);
-            }
-            DeltaOperation::Delete { row_id, timestamp } => {
-                self.row_timestamps.insert(*row_id, *timestamp);
-            }
-        }
+        self.track(&op);
         
         let delta = DeltaState {
             table_name,
@@ -76,7 +94,7 @@ impl TableDeltaState {
             operations: vec![op],
         };
         
-        self.pending_deltas.push(delta.clone());
+        self.pending_deltas.push((Instant::now(), delta.clone()));
         delta
     }
     
@@ -94,7 +112,7 @@ impl TableDeltaState {
                             continue; // Skip older update (idempotent)
                         }
                     }
-                    self.row_timestamps.insert(*row_id, *timestamp);
+                    self.track(&op);
                     applied_ops.push(op);
                 }
                 DeltaOperation::Delete { row_id, timestamp } => {
@@ -103,7 +121,7 @@ impl TableDeltaState {
                             continue; // Skip older delete
                         }
                     }
-                    self.row_timestamps.insert(*row_id, *timestamp);
+                    self.track(&op);
                     applied_ops.push(op);
                 }
             }
@@ -117,9 +135,79 @@ impl TableDeltaState {
         applied_ops
     }
     
+    /// Remember an operation's timestamp as the row's latest, and whether it
+    /// left a tombstone
+    fn track(&mut self, op: &DeltaOperation) {
+        match op {
+            DeltaOperation::Upsert { row_id, timestamp, .. } => {
+                self.row_timestamps.insert(*row_id, *timestamp);
+                self.tombstones.remove(row_id);
+            }
+            DeltaOperation::Delete { row_id, timestamp } => {
+                self.row_timestamps.insert(*row_id, *timestamp);
+                self.tombstones.insert(*row_id, Instant::now());
+            }
+        }
+    }
+
     /// Get all pending deltas and clear the buffer
     pub fn drain_pending_deltas(&mut self) -> Vec<DeltaState> {
         std::mem::take(&mut self.pending_deltas)
+            .into_iter()
+            .map(|(_, delta)| delta)
+            .collect()
+    }
+
+    /// Put drained deltas that could not be delivered back in front of the
+    /// ones recorded since, keeping their order
+    pub fn requeue_deltas(&mut self, deltas: Vec<DeltaState>) {
+        let now = Instant::now();
+        let mut requeued: Vec<_> = deltas.into_iter().map(|delta| (now, delta)).collect();
+        requeued.append(&mut self.pending_deltas);
+        self.pending_deltas = requeued;
+    }
+
+    /// Forget tombstones recorded before `cutoff` that every replica has
+    /// acknowledged, i.e. whose delete is no longer pending for a peer to
+    /// pull. Returns the number of tombstones removed.
+    ///
+    /// A standalone node (replica id 0) has no peer to pull its deltas, so
+    /// pending deltas recorded before `cutoff` are dropped first. Once a
+    /// tombstone is gone, a write older than the delete that arrives later
+    /// would bring the row back, so `cutoff` should leave time for every
+    /// replica's writes to arrive.
+    pub fn collect_garbage(&mut self, cutoff: Instant) -> usize {
+        if self.replica_id == 0 {
+            self.pending_deltas.retain(|(recorded, _)| *recorded >= cutoff);
+        }
+
+        let undelivered: HashSet<u64> = self.pending_deltas.iter()
+            .flat_map(|(_, delta)| &delta.operations)
+            .map(|op| match op {
+                DeltaOperation::Upsert { row_id, .. } | DeltaOperation::Delete { row_id, .. } => *row_id,
+            })
+            .collect();
+
+        let before = self.tombstones.len();
+        let row_timestamps = &mut self.row_timestamps;
+        self.tombstones.retain(|row_id, deleted| {
+            let keep = *deleted >= cutoff || undelivered.contains(row_id);
+            if !keep {
+                row_timestamps.remove(row_id);
+            }
+            keep
+        });
+        before - self.tombstones.len()
+    }
+
+    /// Number of deltas waiting to be pulled by a peer
+    pub fn pending_delta_count(&self) -> usize {
+        self.pending_deltas.len()
+    }
+
+    /// Number of deleted rows whose tombstone is still kept
+    pub fn tombstone_count(&self) -> usize {
+        self.tombstones.len()
     }
     
     /// Get current logical clock value
@@ -275,6 +363,27 @@ mod tests {
         assert!(matches!(delete_ops[0], DeltaOperation::Delete { .. }));
     }
     
+    #[test]
+    fn test_recorded_operations_win_ties_by_replica() {
+        let upsert = |value| DeltaOperation::Upsert { row_id: 1, values: vec![Value::Integer(value)], timestamp: 0 };
+
+        // Two replicas write the same row at the same Lamport time
+        let mut a = TableDeltaState::with_replica_id(1);
+        let mut b = TableDeltaState::with_replica_id(2);
+        let from_a = a.record_operation("test".to_string(), upsert(1));
+        let from_b = b.record_operation("test".to_string(), upsert(2));
+        assert_eq!(from_a.timestamp, from_b.timestamp);
+
+        // Each side applies the other's write only if it wins, so both keep replica 2's
+        assert!(a.merge_delta(from_b.clone()).len() == 1);
+        assert!(b.merge_delta(from_a.clone()).is_empty());
+
+        // A later local write is stamped after everything merged so far
+        let next = a.record_operation("test".to_string(), upsert(3));
+        assert_eq!(next.timestamp, 2);
+        assert_eq!(b.merge_delta(next).len(), 1);
+    }
+
     #[test]
     fn test_lamport_clock_advancement() {
         let mut state = TableDeltaState::new();
@@ -297,4 +406,50 @@ mod tests {
         // Clock should advance to match received timestamp
         assert_eq!(state.current_clock(), 100);
     }
+
+    #[test]
+    fn test_garbage_collection_bounds_standalone_state() {
+        let mut state = TableDeltaState::new();
+        for row_id in 1..=1000 {
+            state.record_operation("test".to_string(), DeltaOperation::Upsert {
+                row_id,
+                values: vec![Value::Integer(row_id as i64)],
+                timestamp: 0,
+            });
+            state.record_operation("test".to_string(), DeltaOperation::Delete { row_id, timestamp: 0 });
+            if row_id % 100 == 0 {
+                state.collect_garbage(Instant::now());
+                assert_eq!(state.pending_delta_count(), 0);
+                assert_eq!(state.tombstone_count(), 0);
+            }
+        }
+        assert!(state.row_timestamps.is_empty());
+
+        // Tombstones newer than the cutoff stay
+        let cutoff = Instant::now();
+        state.record_operation("test".to_string(), DeltaOperation::Delete { row_id: 1, timestamp: 0 });
+        assert_eq!(state.collect_garbage(cutoff), 0);
+        assert_eq!(state.tombstone_count(), 1);
+    }
+
+    #[test]
+    fn test_garbage_collection_keeps_unacknowledged_tombstones() {
+        let mut state = TableDeltaState::with_replica_id(1);
+        state.record_operation("test".to_string(), DeltaOperation::Delete { row_id: 1, timestamp: 0 });
+
+        // The delete has not been pulled by the peer yet
+        assert_eq!(state.collect_garbage(Instant::now()), 0);
+        assert_eq!(state.tombstone_count(), 1);
+
+        // Once pulled, the tombstone goes, and a later upsert revives the row
+        assert_eq!(state.drain_pending_deltas().len(), 1);
+        assert_eq!(state.collect_garbage(Instant::now()), 1);
+        assert_eq!(state.tombstone_count(), 0);
+        let upsert = DeltaState {
+            table_name: "test".to_string(),
+            timestamp: 5,
+            operations: vec![DeltaOperation::Upsert { row_id: 1, values: vec![], timestamp: 5 << REPLICA_ID_BITS }],
+        };
+        assert_eq!(state.merge_delta(upsert).len(), 1);
+    }
 }
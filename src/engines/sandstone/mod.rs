@@ -21,7 +21,7 @@ use std::time::Duration;
 use crate::error::Result;
 use crate::storage::StorageEngine;
 use crate::types::{Row, Value};
-use crate::engines::handler::EngineHandler;
+use crate::engines::handler::{CrdtStatus, EngineHandler, FlushStatus};
 use crate::engines::granite::TxnId;
 
 // Public re-exports
@@ -46,15 +46,16 @@ pub struct SandstoneEngine {
 impl SandstoneEngine {
     /// Create a new Sandstone engine with background worker
     pub fn new(storage: Arc<StorageEngine>, config: SandstoneConfig) -> Result<Self> {
-        let state = Arc::new(SandstoneSharedState::new());
+        let state = Arc::new(SandstoneSharedState::new(config.replica_id));
         
         // Spawn background worker
         let worker_state = Arc::clone(&state);
         let worker_storage = Arc::clone(&storage);
         let flush_interval = Duration::from_millis(config.flush_interval_ms);
+        let tombstone_gc = config.tombstone_gc_ms.map(Duration::from_millis);
         
         let worker_handle = thread::spawn(move || {
-            worker::worker_loop(worker_state, worker_storage, flush_interval);
+            worker::worker_loop(worker_state, worker_storage, flush_interval, tombstone_gc);
         });
 
         Ok(Self {
@@ -79,12 +80,30 @@ impl SandstoneEngine {
         // Initialize CRDT state
         if self.config.enable_delta_crdt {
             let mut crdt_states = self.state.crdt_states.write().unwrap();
-            crdt_states.entry(table_name.to_string()).or_insert_with(TableDeltaState::new);
+            crdt_states.entry(table_name.to_string())
+                .or_insert_with(|| TableDeltaState::with_replica_id(self.config.replica_id));
         }
         
         Ok(())
     }
 
+    /// Discard all in-memory state for a table (page table, dirty flag, CRDT deltas)
+    pub fn drop_table(&self, table_name: &str) {
+        self.state.pages.write().unwrap().remove_table(table_name);
+        self.state.dirty_tables.lock().unwrap().remove(table_name);
+        self.state.crdt_states.write().unwrap().remove(table_name);
+    }
+
+    /// Empty a table in memory, keeping it registered with a fresh CRDT state
+    pub fn truncate_table(&self, table_name: &str) {
+        self.drop_table(table_name);
+        self.state.pages.write().unwrap().init_table(table_name);
+        if self.config.enable_delta_crdt {
+            self.state.crdt_states.write().unwrap()
+                .insert(table_name.to_string(), TableDeltaState::with_replica_id(self.config.replica_id));
+        }
+    }
+
     /// Check if this engine owns the given table (based on schema engine type)
     pub fn owns_table(&self, schema: &crate::types::TableSchema) -> bool {
         schema.engine_type == crate::engines::EngineType::Sandstone
@@ -98,22 +117,14 @@ impl SandstoneEngine {
         };
 
         // Mark dirty
-        self.state.dirty_tables.lock().unwrap().insert(table_name.to_string());
+        self.mark_dirty(table_name);
 
         // Record CRDT delta
-        if self.config.enable_delta_crdt {
-            let mut crdt_states = self.state.crdt_states.write().unwrap();
-            if let Some(state) = crdt_states.get_mut(table_name) {
-                state.record_operation(
-                    table_name.to_string(),
-                    DeltaOperation::Upsert {
-                        row_id,
-                        values,
-                        timestamp: 0, // Will be set by record_operation
-                    },
-                );
-            }
-        }
+        self.record_delta(table_name, DeltaOperation::Upsert {
+            row_id,
+            values,
+            timestamp: 0, // Will be set by record_operation
+        });
 
         Ok(row_id)
     }
@@ -126,21 +137,13 @@ impl SandstoneEngine {
         };
 
         if updated {
-            self.state.dirty_tables.lock().unwrap().insert(table_name.to_string());
-
-            if self.config.enable_delta_crdt {
-                let mut crdt_states = self.state.crdt_states.write().unwrap();
-                if let Some(state) = crdt_states.get_mut(table_name) {
-                    state.record_operation(
-                        table_name.to_string(),
-                        DeltaOperation::Upsert {
-                            row_id,
-                            values,
-                            timestamp: 0,
-                        },
-                    );
-                }
-            }
+            self.mark_dirty(table_name);
+
+            self.record_delta(table_name, DeltaOperation::Upsert {
+                row_id,
+                values,
+                timestamp: 0,
+            });
         }
 
         Ok(updated)
@@ -154,41 +157,71 @@ impl SandstoneEngine {
         };
 
         if deleted {
-            self.state.dirty_tables.lock().unwrap().insert(table_name.to_string());
-
-            if self.config.enable_delta_crdt {
-                let mut crdt_states = self.state.crdt_states.write().unwrap();
-                if let Some(state) = crdt_states.get_mut(table_name) {
-                    state.record_operation(
-                        table_name.to_string(),
-                        DeltaOperation::Delete {
-                            row_id,
-                            timestamp: 0,
-                        },
-                    );
-                }
-            }
+            self.mark_dirty(table_name);
+
+            self.record_delta(table_name, DeltaOperation::Delete {
+                row_id,
+                timestamp: 0,
+            });
         }
 
         Ok(deleted)
     }
 
+    /// Note that a table has changes to flush, waking the worker once more
+    /// than `max_dirty_tables` tables are waiting
+    fn mark_dirty(&self, table_name: &str) {
+        let dirty = {
+            let mut dirty_tables = self.state.dirty_tables.lock().unwrap();
+            dirty_tables.insert(table_name.to_string());
+            dirty_tables.len()
+        };
+        if self.config.max_dirty_tables.is_some_and(|max| dirty > max) {
+            self.state.request_flush();
+        }
+    }
+
+    /// Record a local write as a CRDT delta. Tables written before
+    /// `init_table` (e.g. right after CREATE TABLE) get their state here.
+    fn record_delta(&self, table_name: &str, op: DeltaOperation) {
+        if !self.config.enable_delta_crdt {
+            return;
+        }
+        let mut crdt_states = self.state.crdt_states.write().unwrap();
+        crdt_states.entry(table_name.to_string())
+            .or_insert_with(|| TableDeltaState::with_replica_id(self.config.replica_id))
+            .record_operation(table_name.to_string(), op);
+    }
+
     /// Scan all rows in a table
     pub fn scan_table(&self, table_name: &str) -> Vec<Row> {
         let pages = self.state.pages.read().unwrap();
         pages.scan(table_name)
     }
 
+    /// Number of rows in a table, flushed or not
+    pub fn row_count(&self, table_name: &str) -> u64 {
+        self.state.pages.read().unwrap().row_count(table_name) as u64
+    }
+
     /// Get a single row by ID
     pub fn get_row(&self, table_name: &str, row_id: u64) -> Option<Row> {
         let pages = self.state.pages.read().unwrap();
         pages.get(table_name, row_id)
     }
 
-    /// Force immediate flush of all dirty tables
+    /// Force immediate flush of all dirty tables.
+    ///
+    /// Fails with the first table's write error; tables that could not be
+    /// written stay dirty for the next flush.
     pub fn flush_all(&self) -> Result<()> {
-        worker::flush_dirty_tables(&self.state, &self.storage);
-        Ok(())
+        worker::flush_dirty_tables(&self.state, &self.storage)
+    }
+
+    /// Last successful flush and last flush error, from this call or the
+    /// background worker
+    pub fn flush_status(&self) -> FlushStatus {
+        self.state.flush_status.lock().unwrap().clone()
     }
 
     /// Get delta state for replication
@@ -205,6 +238,36 @@ impl SandstoneEngine {
         }
     }
 
+    /// Prune tombstones past `SandstoneConfig::tombstone_gc_ms` now rather
+    /// than at the next flush interval. Returns the number pruned.
+    pub fn collect_garbage(&self) -> usize {
+        match self.config.tombstone_gc_ms {
+            Some(max_age) => worker::collect_garbage(&self.state, Duration::from_millis(max_age)),
+            None => 0,
+        }
+    }
+
+    /// Size of the CRDT state kept across all tables
+    pub fn crdt_status(&self) -> CrdtStatus {
+        let crdt_states = self.state.crdt_states.read().unwrap();
+        CrdtStatus {
+            pending_deltas: crdt_states.values().map(TableDeltaState::pending_delta_count).sum(),
+            tombstones: crdt_states.values().map(TableDeltaState::tombstone_count).sum(),
+        }
+    }
+
+    /// Return deltas taken by [`SandstoneEngine::get_pending_deltas`] that
+    /// could not be delivered, so the next call hands them out again
+    pub fn requeue_deltas(&self, table_name: &str, deltas: Vec<DeltaState>) {
+        if deltas.is_empty() {
+            return;
+        }
+        let mut crdt_states = self.state.crdt_states.write().unwrap();
+        if let Some(state) = crdt_states.get_mut(table_name) {
+            state.requeue_deltas(deltas);
+        }
+    }
+
     /// Merge incoming delta from another replica
     pub fn merge_delta(&self, table_name: &str, delta: DeltaState) -> Result<()> {
         if !self.config.enable_delta_crdt {
@@ -213,7 +276,8 @@ impl SandstoneEngine {
 
         let operations = {
             let mut crdt_states = self.state.crdt_states.write().unwrap();
-            let state = crdt_states.entry(table_name.to_string()).or_insert_with(TableDeltaState::new);
+            let state = crdt_states.entry(table_name.to_string())
+                .or_insert_with(|| TableDeltaState::with_replica_id(self.config.replica_id));
             state.merge_delta(delta)
         };
 
@@ -238,13 +302,16 @@ impl SandstoneEngine {
             }
         }
 
-        self.state.dirty_tables.lock().unwrap().insert(table_name.to_string());
+        self.mark_dirty(table_name);
         Ok(())
     }
 
-    /// Shutdown the background worker
+    /// Shutdown the background worker.
+    ///
+    /// Wakes the worker immediately; a periodic flush in progress stops at the
+    /// next table boundary, then the worker writes out every remaining dirty table.
     pub fn shutdown(&mut self) {
-        *self.state.shutdown.lock().unwrap() = true;
+        self.state.request_shutdown();
         
         if let Some(handle) = self.worker_handle.take() {
             let _ = handle.join();
@@ -292,10 +359,44 @@ impl EngineHandler for SandstoneEngine {
         Ok(self.scan_table(table_name))
     }
 
+    fn scan_sampled(&self, table_name: &str, keep: &mut dyn FnMut(usize) -> bool) -> Result<Vec<Row>> {
+        Ok(self.state.pages.read().unwrap().scan_sampled(table_name, keep))
+    }
+
+    fn row_count(&self, _txn_id: TxnId, table_name: &str) -> Result<Option<u64>> {
+        Ok(Some(SandstoneEngine::row_count(self, table_name)))
+    }
+
     fn flush(&self, _table_name: &str) -> Result<()> {
         self.flush_all()
     }
 
+    fn flush_all(&self) -> Result<()> {
+        SandstoneEngine::flush_all(self)
+    }
+
+    fn drop_table(&self, table_name: &str) -> Result<()> {
+        SandstoneEngine::drop_table(self, table_name);
+        Ok(())
+    }
+
+    fn truncate_table(&self, table_name: &str) -> Result<()> {
+        SandstoneEngine::truncate_table(self, table_name);
+        Ok(())
+    }
+
+    fn dirty_table_count(&self) -> usize {
+        self.state.dirty_tables.lock().unwrap().len()
+    }
+
+    fn flush_status(&self) -> Option<FlushStatus> {
+        Some(SandstoneEngine::flush_status(self))
+    }
+
+    fn crdt_status(&self) -> Option<CrdtStatus> {
+        Some(SandstoneEngine::crdt_status(self))
+    }
+
     fn supports_transactions(&self) -> bool {
         false  // Sandstone uses eventual consistency, no transaction support
     }
@@ -334,7 +435,7 @@ mod tests {
 
     #[test]
     fn test_memory_page_table_insert() {
-        let mut pages = MemoryPageTable::new();
+        let mut pages = MemoryPageTable::default();
         pages.init_table("test");
         
         let id1 = pages.insert("test", vec![Value::Integer(1)]);
@@ -347,9 +448,22 @@ mod tests {
         assert_eq!(rows.len(), 2);
     }
 
+    #[test]
+    fn test_memory_page_table_replica_row_ids() {
+        let mut pages = MemoryPageTable::with_replica_id(3);
+        pages.init_table("test");
+        assert_eq!(pages.insert("test", vec![Value::Integer(1)]), (1 << 16) | 3);
+        assert_eq!(pages.insert("test", vec![Value::Integer(2)]), (2 << 16) | 3);
+
+        // After loading rows numbered by others, IDs continue above them
+        let mut pages = MemoryPageTable::with_replica_id(3);
+        pages.load_from_storage("test", &[Row::new(5, vec![]), Row::new((7 << 16) | 1, vec![])]);
+        assert_eq!(pages.insert("test", vec![Value::Integer(1)]), (8 << 16) | 3);
+    }
+
     #[test]
     fn test_memory_page_table_update() {
-        let mut pages = MemoryPageTable::new();
+        let mut pages = MemoryPageTable::default();
         pages.init_table("test");
         
         let id = pages.insert("test", vec![Value::Integer(1)]);
@@ -361,7 +475,7 @@ mod tests {
 
     #[test]
     fn test_memory_page_table_delete() {
-        let mut pages = MemoryPageTable::new();
+        let mut pages = MemoryPageTable::default();
         pages.init_table("test");
         
         let id = pages.insert("test", vec![Value::Integer(1)]);
@@ -371,7 +485,7 @@ mod tests {
 
     #[test]
     fn test_memory_page_table_scan() {
-        let mut pages = MemoryPageTable::new();
+        let mut pages = MemoryPageTable::default();
         pages.init_table("test");
         
         for i in 1..=100 {
@@ -381,4 +495,44 @@ mod tests {
         let rows = pages.scan("test");
         assert_eq!(rows.len(), 100);
     }
+
+    #[test]
+    fn test_truncate_clears_rows_dirty_set_and_deltas() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(StorageEngine::new(dir.path().to_path_buf()).unwrap());
+        let config = SandstoneConfig { flush_interval_ms: 60_000, ..SandstoneConfig::default() };
+        let engine = SandstoneEngine::new(storage, config).unwrap();
+        for i in 1..=3 {
+            engine.insert_row("t", vec![Value::Integer(i)]).unwrap();
+        }
+        assert!(engine.state.dirty_tables.lock().unwrap().contains("t"));
+
+        engine.truncate_table("t");
+
+        assert!(engine.scan_table("t").is_empty());
+        assert!(!engine.state.dirty_tables.lock().unwrap().contains("t"));
+        assert!(engine.get_pending_deltas("t").is_empty());
+        // Row IDs start over in the emptied table
+        assert_eq!(engine.insert_row("t", vec![Value::Integer(9)]).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_flush_failure_is_returned_and_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(StorageEngine::new(dir.path().to_path_buf()).unwrap());
+        let config = SandstoneConfig { flush_interval_ms: 60_000, ..SandstoneConfig::default() };
+        let engine = SandstoneEngine::new(storage, config).unwrap();
+        assert_eq!(engine.flush_status(), FlushStatus::default());
+
+        // "t" has no table in storage to write to
+        engine.insert_row("t", vec![Value::Integer(1)]).unwrap();
+        assert!(engine.flush_all().is_err());
+
+        let status = engine.flush_status();
+        assert_eq!(status.failures, 1);
+        assert!(status.last_error.unwrap().contains("t"));
+        assert!(status.last_success.is_none());
+        // The table is kept for the next attempt
+        assert!(engine.state.dirty_tables.lock().unwrap().contains("t"));
+    }
 }
@@ -4,6 +4,7 @@
 
 use std::collections::HashMap;
 use crate::types::{Row, Value};
+use super::delta_crdt::REPLICA_ID_BITS;
 
 /// In-memory page table for fast writes
 /// Maps table_name -> row_id -> row values
@@ -13,24 +14,39 @@ pub struct MemoryPageTable {
     pages: HashMap<String, HashMap<u64, Vec<Value>>>,
     /// Next row ID per table
     next_row_ids: HashMap<String, u64>,
+    /// Replica whose number the low bits of allocated row IDs hold (0 = sequential IDs)
+    replica_id: u16,
 }
 
 impl MemoryPageTable {
-    pub fn new() -> Self {
-        Self::default()
+    /// A page table allocating row IDs that end in `replica_id`'s bits, so
+    /// rows inserted on different replicas never share an ID
+    pub fn with_replica_id(replica_id: u16) -> Self {
+        Self { replica_id, ..Self::default() }
+    }
+
+    /// The first row ID this replica may allocate above `max_id`
+    fn first_id_after(&self, max_id: u64) -> u64 {
+        if self.replica_id == 0 {
+            max_id + 1
+        } else {
+            (((max_id >> REPLICA_ID_BITS) + 1) << REPLICA_ID_BITS) | u64::from(self.replica_id)
+        }
     }
 
     /// Initialize a table's page
-    #[allow(dead_code)]
     pub fn init_table(&mut self, table_name: &str) {
+        let first = self.first_id_after(0);
         self.pages.entry(table_name.to_string()).or_default();
-        self.next_row_ids.entry(table_name.to_string()).or_insert(1);
+        self.next_row_ids.entry(table_name.to_string()).or_insert(first);
     }
 
     /// Insert a row, returns the row ID
     pub fn insert(&mut self, table_name: &str, values: Vec<Value>) -> u64 {
-        let row_id = *self.next_row_ids.entry(table_name.to_string()).or_insert(1);
-        self.next_row_ids.insert(table_name.to_string(), row_id + 1);
+        let first = self.first_id_after(0);
+        let row_id = *self.next_row_ids.entry(table_name.to_string()).or_insert(first);
+        let next = self.first_id_after(row_id);
+        self.next_row_ids.insert(table_name.to_string(), next);
         
         self.pages
             .entry(table_name.to_string())
@@ -74,6 +90,26 @@ impl MemoryPageTable {
         }
     }
 
+    /// Rows of a table in row id order that `keep` accepts (see
+    /// `EngineHandler::scan_sampled`)
+    pub fn scan_sampled(&self, table_name: &str, keep: &mut dyn FnMut(usize) -> bool) -> Vec<Row> {
+        let Some(table) = self.pages.get(table_name) else {
+            return Vec::new();
+        };
+        let mut row_ids: Vec<u64> = table.keys().copied().collect();
+        row_ids.sort_unstable();
+        let total = row_ids.len();
+        row_ids.into_iter().enumerate()
+            .filter(|&(i, _)| keep(total - i))
+            .map(|(_, row_id)| Row { id: row_id, values: table[&row_id].clone() })
+            .collect()
+    }
+
+    /// Number of rows in a table
+    pub fn row_count(&self, table_name: &str) -> usize {
+        self.pages.get(table_name).map_or(0, HashMap::len)
+    }
+
     /// Get a single row by ID
     pub fn get(&self, table_name: &str, row_id: u64) -> Option<Row> {
         self.pages
@@ -110,7 +146,14 @@ impl MemoryPageTable {
             max_id = max_id.max(row.id);
         }
         
-        self.next_row_ids.insert(table_name.to_string(), max_id + 1);
+        let next = self.first_id_after(max_id);
+        self.next_row_ids.insert(table_name.to_string(), next);
+    }
+
+    /// Remove a table's rows and reset its row ID counter
+    pub fn remove_table(&mut self, table_name: &str) {
+        self.pages.remove(table_name);
+        self.next_row_ids.remove(table_name);
     }
 
     /// Expose pages for direct access (needed by merge_delta)
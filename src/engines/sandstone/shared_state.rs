@@ -1,7 +1,11 @@
 //! Shared state between main thread and background worker
 
 use std::collections::{HashMap, HashSet};
-use std::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, RwLock};
+use std::time::SystemTime;
+
+use crate::engines::handler::FlushStatus;
 
 use super::page_table::MemoryPageTable;
 use super::delta_crdt::TableDeltaState;
@@ -16,15 +20,63 @@ pub(crate) struct SandstoneSharedState {
     pub(crate) crdt_states: RwLock<HashMap<String, TableDeltaState>>,
     /// Shutdown flag
     pub(crate) shutdown: Mutex<bool>,
+    /// Set when writes push the dirty set past `max_dirty_tables`
+    pub(crate) flush_requested: AtomicBool,
+    /// Notified when `shutdown` or `flush_requested` is set, waking the
+    /// worker from its flush wait
+    pub(crate) shutdown_signal: Condvar,
+    /// Result of the flushes so far, written by whichever thread flushed
+    pub(crate) flush_status: Mutex<FlushStatus>,
 }
 
 impl SandstoneSharedState {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(replica_id: u16) -> Self {
         Self {
-            pages: RwLock::new(MemoryPageTable::new()),
+            pages: RwLock::new(MemoryPageTable::with_replica_id(replica_id)),
             dirty_tables: Mutex::new(HashSet::new()),
             crdt_states: RwLock::new(HashMap::new()),
             shutdown: Mutex::new(false),
+            flush_requested: AtomicBool::new(false),
+            shutdown_signal: Condvar::new(),
+            flush_status: Mutex::new(FlushStatus::default()),
         }
     }
+
+    /// Whether shutdown has been requested
+    pub(crate) fn is_shutting_down(&self) -> bool {
+        *self.shutdown.lock().unwrap()
+    }
+
+    /// Record a flush that wrote every dirty table
+    pub(crate) fn record_flush_success(&self) {
+        let mut status = self.flush_status.lock().unwrap();
+        status.last_success = Some(SystemTime::now());
+        status.last_error = None;
+    }
+
+    /// Record a flush that left at least one table unwritten
+    pub(crate) fn record_flush_failure(&self, error: String) {
+        let mut status = self.flush_status.lock().unwrap();
+        status.last_error = Some(error);
+        status.failures += 1;
+    }
+
+    /// Wake the worker to flush now rather than at the end of its interval
+    pub(crate) fn request_flush(&self) {
+        // Set under the lock the worker waits with, so the wakeup is not lost
+        let _stop = self.shutdown.lock().unwrap();
+        self.flush_requested.store(true, Ordering::SeqCst);
+        self.shutdown_signal.notify_all();
+    }
+
+    /// Take a pending flush request
+    pub(crate) fn take_flush_request(&self) -> bool {
+        self.flush_requested.swap(false, Ordering::SeqCst)
+    }
+
+    /// Request shutdown and wake the worker
+    pub(crate) fn request_shutdown(&self) {
+        *self.shutdown.lock().unwrap() = true;
+        self.shutdown_signal.notify_all();
+    }
 }
@@ -5,10 +5,11 @@
 //! - Delta-CRDT state management
 //! - Conflict-free merge operations
 
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::thread;
 use std::time::{Duration, Instant};
 
+use crate::error::Result;
 use crate::storage::StorageEngine;
 use super::shared_state::SandstoneSharedState;
 
@@ -17,6 +18,7 @@ pub(crate) fn worker_loop(
     state: Arc<SandstoneSharedState>,
     storage: Arc<StorageEngine>,
     flush_interval: Duration,
+    tombstone_gc: Option<Duration>,
 ) {
     log::info!("Sandstone background worker started (flush interval: {:?})", flush_interval);
     
@@ -24,27 +26,68 @@ pub(crate) fn worker_loop(
     
     loop {
         // Check shutdown
-        if *state.shutdown.lock().unwrap() {
+        if state.is_shutting_down() {
             log::info!("Sandstone worker shutting down");
-            // Final flush before shutdown
-            flush_dirty_tables(&state, &storage);
+            // Final flush before shutdown; a failure is recorded in `state`
+            let _ = flush_dirty_tables(&state, &storage);
             break;
         }
 
-        // Sleep until next flush interval
-        let elapsed = last_flush.elapsed();
-        if elapsed < flush_interval {
-            thread::sleep(flush_interval - elapsed);
+        // Wait until next flush interval, waking early on shutdown or when
+        // too many tables are dirty
+        let remaining = flush_interval.saturating_sub(last_flush.elapsed());
+        let stop = state.shutdown.lock().unwrap();
+        let (stop, _) = state.shutdown_signal
+            .wait_timeout_while(stop, remaining, |stop| {
+                !*stop && !state.flush_requested.load(Ordering::SeqCst)
+            })
+            .unwrap();
+        if *stop {
+            continue;
+        }
+        drop(stop);
+        if state.take_flush_request() {
+            log::debug!("Sandstone flushing early: too many dirty tables");
         }
 
-        // Flush dirty tables
-        flush_dirty_tables(&state, &storage);
+        // Flush dirty tables, yielding to a shutdown request between tables.
+        // Failed tables stay dirty and are retried on the next interval.
+        let _ = flush_tables(&state, &storage, true);
         last_flush = Instant::now();
+
+        if let Some(max_age) = tombstone_gc {
+            collect_garbage(&state, max_age);
+        }
+    }
+}
+
+/// Prune CRDT tombstones older than `max_age` from every table
+pub(crate) fn collect_garbage(state: &SandstoneSharedState, max_age: Duration) -> usize {
+    let Some(cutoff) = Instant::now().checked_sub(max_age) else {
+        return 0;
+    };
+    let mut crdt_states = state.crdt_states.write().unwrap();
+    let pruned = crdt_states.values_mut().map(|table| table.collect_garbage(cutoff)).sum();
+    if pruned > 0 {
+        log::debug!("Sandstone pruned {} tombstone(s)", pruned);
     }
+    pruned
 }
 
 /// Flush all dirty tables to disk
-pub(crate) fn flush_dirty_tables(state: &Arc<SandstoneSharedState>, storage: &Arc<StorageEngine>) {
+pub(crate) fn flush_dirty_tables(state: &Arc<SandstoneSharedState>, storage: &Arc<StorageEngine>) -> Result<()> {
+    flush_tables(state, storage, false)
+}
+
+/// Flush dirty tables one at a time.
+///
+/// When `interruptible` is set, a shutdown request stops the flush before the
+/// next table; tables not yet written are marked dirty again so the final
+/// shutdown flush picks them up.
+///
+/// A table that fails to write stays dirty and the remaining tables are still
+/// written. The outcome is recorded in `state`, and the first error returned.
+fn flush_tables(state: &Arc<SandstoneSharedState>, storage: &Arc<StorageEngine>, interruptible: bool) -> Result<()> {
     // Get and clear dirty set
     let dirty: Vec<String> = {
         let mut dirty_set = state.dirty_tables.lock().unwrap();
@@ -53,12 +96,22 @@ pub(crate) fn flush_dirty_tables(state: &Arc<SandstoneSharedState>, storage: &Ar
     };
 
     if dirty.is_empty() {
-        return;
+        return Ok(());
     }
 
     log::debug!("Sandstone flushing {} dirty tables", dirty.len());
 
-    for table_name in dirty {
+    let mut first_error = None;
+    let mut pending = dirty.into_iter();
+    while let Some(table_name) = pending.next() {
+        if interruptible && state.is_shutting_down() {
+            let mut dirty_set = state.dirty_tables.lock().unwrap();
+            dirty_set.insert(table_name);
+            dirty_set.extend(pending);
+            log::debug!("Sandstone flush interrupted by shutdown");
+            return Ok(());
+        }
+
         // Get rows from page table
         let rows = {
             let pages = state.pages.read().unwrap();
@@ -70,6 +123,18 @@ pub(crate) fn flush_dirty_tables(state: &Arc<SandstoneSharedState>, storage: &Ar
             log::error!("Sandstone flush failed for table '{}': {}", table_name, e);
             // Re-mark as dirty for retry
             state.dirty_tables.lock().unwrap().insert(table_name);
+            first_error.get_or_insert(e);
+        }
+    }
+
+    match first_error {
+        Some(e) => {
+            state.record_flush_failure(e.to_string());
+            Err(e)
+        }
+        None => {
+            state.record_flush_success();
+            Ok(())
         }
     }
 }
@@ -6,6 +6,8 @@
 use std::fmt;
 use std::io;
 
+use crate::types::Privilege;
+
 /// Context for where a column reference appears in a query
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ColumnContext {
@@ -129,6 +131,60 @@ impl MiniSqlError {
         ))
     }
 
+    /// Create a MySQL-compatible "duplicate column" error
+    /// MySQL format: Duplicate column name 'column'
+    pub fn duplicate_column(column_name: &str) -> Self {
+        MiniSqlError::Column(format!("Duplicate column name '{}'", column_name))
+    }
+
+    /// Create a MySQL-compatible error for a child row without a parent
+    /// MySQL format: Cannot add or update a child row: a foreign key constraint fails (`db`.`child`, CONSTRAINT ...)
+    pub fn foreign_key_no_parent(child_table: &str, constraint_sql: &str) -> Self {
+        MiniSqlError::Constraint(format!(
+            "Cannot add or update a child row: a foreign key constraint fails (`{}`.`{}`, {})",
+            DEFAULT_DATABASE, child_table, constraint_sql
+        ))
+    }
+
+    /// Create a MySQL-compatible error for deleting or changing a referenced parent row
+    /// MySQL format: Cannot delete or update a parent row: a foreign key constraint fails (`db`.`child`, CONSTRAINT ...)
+    pub fn foreign_key_parent_referenced(child_table: &str, constraint_sql: &str) -> Self {
+        MiniSqlError::Constraint(format!(
+            "Cannot delete or update a parent row: a foreign key constraint fails (`{}`.`{}`, {})",
+            DEFAULT_DATABASE, child_table, constraint_sql
+        ))
+    }
+
+    /// Create a MySQL-compatible error for dropping a table other tables reference
+    /// MySQL format: Cannot drop table 'parent' referenced by a foreign key constraint 'fk' on table 'child'.
+    pub fn foreign_key_drop_parent(table_name: &str, constraint: &str, child_table: &str) -> Self {
+        MiniSqlError::Constraint(format!(
+            "Cannot drop table '{}' referenced by a foreign key constraint '{}' on table '{}'.",
+            table_name, constraint, child_table
+        ))
+    }
+
+    /// Create a MySQL-compatible error for truncating a table other tables reference
+    /// MySQL format: Cannot truncate a table referenced in a foreign key constraint (`db`.`child`, CONSTRAINT ...)
+    pub fn foreign_key_truncate_parent(child_table: &str, constraint_sql: &str) -> Self {
+        MiniSqlError::Constraint(format!(
+            "Cannot truncate a table referenced in a foreign key constraint (`{}`.`{}`, {})",
+            DEFAULT_DATABASE, child_table, constraint_sql
+        ))
+    }
+
+    /// Create a MySQL-compatible error for a subquery returning the wrong number of columns
+    /// MySQL format: Operand should contain N column(s)
+    pub fn operand_columns(expected: usize) -> Self {
+        MiniSqlError::Syntax(format!("Operand should contain {} column(s)", expected))
+    }
+
+    /// Create a MySQL-compatible error for a scalar subquery returning several rows
+    /// MySQL format: Subquery returns more than 1 row
+    pub fn subquery_multiple_rows() -> Self {
+        MiniSqlError::Syntax("Subquery returns more than 1 row".to_string())
+    }
+
     /// Create a MySQL-compatible "column count mismatch" error
     /// MySQL format: Column count doesn't match value count at row N
     pub fn column_count_mismatch(expected: usize, got: usize) -> Self {
@@ -138,6 +194,175 @@ impl MiniSqlError {
         ))
     }
 
+    /// Create a MySQL-compatible error for a table declaring two primary keys
+    /// MySQL error 1068: Multiple primary key defined
+    pub fn multiple_primary_keys() -> Self {
+        MiniSqlError::Column("Multiple primary key defined".to_string())
+    }
+
+    /// Create a MySQL-compatible error for a key naming a column the table lacks
+    /// MySQL error 1072: Key column 'name' doesn't exist in table
+    pub fn key_column_missing(column_name: &str) -> Self {
+        MiniSqlError::Column(format!("Key column '{}' doesn't exist in table", column_name))
+    }
+
+    /// Create a MySQL-compatible "column specified twice" error
+    /// MySQL error 1110: Column 'name' specified twice
+    pub fn column_specified_twice(column_name: &str) -> Self {
+        MiniSqlError::Column(format!("Column '{}' specified twice", column_name))
+    }
+
+    /// Create a MySQL-compatible "duplicate primary key" error
+    /// MySQL format: Duplicate entry 'key' for key 'PRIMARY'
+    pub fn duplicate_primary_key(key: &str) -> Self {
+        MiniSqlError::Constraint(format!("Duplicate entry '{}' for key 'PRIMARY'", key))
+    }
+
+    /// Create a MySQL-compatible "field has no default" error
+    /// MySQL error 1364: Field 'name' doesn't have a default value
+    pub fn field_no_default(column_name: &str) -> Self {
+        MiniSqlError::Constraint(format!("Field '{}' doesn't have a default value", column_name))
+    }
+
+    /// Create a MySQL-compatible error for a string longer than its column allows
+    /// MySQL error 1406: Data too long for column 'name'
+    pub fn data_too_long(column_name: &str) -> Self {
+        MiniSqlError::Type(format!("Data too long for column '{}'", column_name))
+    }
+
+    /// Create a MySQL-compatible "out of sort memory" error
+    /// MySQL error 1038: Out of sort memory, consider increasing server sort buffer size
+    pub fn out_of_sort_memory(limit_bytes: usize) -> Self {
+        MiniSqlError::Internal(format!(
+            "Out of sort memory, consider increasing server sort buffer size (query limit is {} bytes)",
+            limit_bytes
+        ))
+    }
+
+    /// Create a MySQL-compatible "statement timed out" error
+    /// MySQL error 3024: Query execution was interrupted, maximum statement execution time exceeded
+    pub fn statement_timeout(timeout_ms: u64) -> Self {
+        MiniSqlError::Internal(format!(
+            "Query execution was interrupted, maximum statement execution time exceeded ({} ms)",
+            timeout_ms
+        ))
+    }
+
+    /// Whether this is the error returned for a statement that ran past its timeout
+    pub fn is_statement_timeout(&self) -> bool {
+        self.mysql_error_code() == mysql_error_codes::ER_QUERY_TIMEOUT
+    }
+
+    /// Create a MySQL-compatible "savepoint does not exist" error
+    /// MySQL error 1305: SAVEPOINT name does not exist
+    pub fn savepoint_not_found(name: &str) -> Self {
+        MiniSqlError::Transaction(format!("SAVEPOINT {} does not exist", name))
+    }
+
+    /// Create a MySQL-compatible "can't change transaction characteristics" error
+    /// MySQL error 1568: Transaction characteristics can't be changed while a transaction is in progress
+    pub fn transaction_in_progress_characteristics() -> Self {
+        MiniSqlError::Transaction(
+            "Transaction characteristics can't be changed while a transaction is in progress".into(),
+        )
+    }
+
+    /// Create a "table data corrupted" error for a row that failed its checksum
+    /// MySQL error 1194: Table '%s' is marked as crashed and should be repaired
+    pub fn table_corrupted(table_name: &str, path: &str, offset: u64) -> Self {
+        MiniSqlError::Internal(format!(
+            "Table '{}' is marked as crashed and should be repaired (checksum mismatch in '{}' at byte offset {})",
+            table_name, path, offset
+        ))
+    }
+
+    /// Create a "WAL corrupted" error for a log record that failed its checksum
+    pub fn wal_corrupted(path: &str, offset: u64) -> Self {
+        MiniSqlError::Internal(format!(
+            "WAL is corrupted (checksum mismatch in '{}' at byte offset {})",
+            path, offset
+        ))
+    }
+
+    /// Create a MySQL-compatible "lock wait timeout" error
+    /// MySQL error 1205: Lock wait timeout exceeded; try restarting transaction
+    pub fn lock_wait_timeout() -> Self {
+        MiniSqlError::Transaction("Lock wait timeout exceeded; try restarting transaction".into())
+    }
+
+    /// Create a MySQL-compatible "deadlock" error
+    /// MySQL error 1213: Deadlock found when trying to get lock; try restarting transaction
+    pub fn deadlock() -> Self {
+        MiniSqlError::Transaction("Deadlock found when trying to get lock; try restarting transaction".into())
+    }
+
+    /// Whether this is the error returned to a transaction chosen as a deadlock victim
+    pub fn is_deadlock(&self) -> bool {
+        self.mysql_error_code() == mysql_error_codes::ER_LOCK_DEADLOCK
+    }
+
+    /// Create a MySQL-compatible error for a LOAD DATA file outside the
+    /// import directory
+    /// MySQL error 1290: The MySQL server is running with the --secure-file-priv option so it cannot execute this statement
+    pub fn secure_file_priv() -> Self {
+        MiniSqlError::Internal(
+            "The server is running with the --secure-file-priv option so it cannot execute this statement".into(),
+        )
+    }
+
+    /// Create a MySQL-compatible "read-only server" error
+    /// MySQL error 1290: The MySQL server is running with the --read-only option so it cannot execute this statement
+    pub fn read_only() -> Self {
+        MiniSqlError::Internal(
+            "The server is running with the --read-only option so it cannot execute this statement".into(),
+        )
+    }
+
+    /// Create a MySQL-compatible "table access denied" error
+    /// MySQL error 1142: %s command denied to user '%s' for table '%s'
+    pub fn table_access_denied(privilege: Privilege, user: &str, table_name: &str) -> Self {
+        MiniSqlError::Auth(format!(
+            "{} command denied to user '{}' for table '{}'",
+            privilege, user, table_name
+        ))
+    }
+
+    /// Create a MySQL-compatible "specific access denied" error for statements
+    /// only the superuser may run (DDL, GRANT, maintenance)
+    /// MySQL error 1227: Access denied; you need (at least one of) the %s privilege(s) for this operation
+    pub fn superuser_required() -> Self {
+        MiniSqlError::Auth("Access denied; you need (at least one of) the SUPER privilege(s) for this operation".into())
+    }
+
+    /// Create a MySQL-compatible "no such grant" error for a REVOKE of a privilege that was never granted
+    /// MySQL error 1147: There is no such grant defined for user '%s' on host '%s' on table '%s'
+    pub fn no_such_grant(user: &str, table_name: &str) -> Self {
+        MiniSqlError::Auth(format!(
+            "There is no such grant defined for user '{}' on host '%' on table '{}'",
+            user, table_name
+        ))
+    }
+
+    /// Create a MySQL-compatible "incorrect arguments" error, for a
+    /// COM_STMT_EXECUTE whose parameters do not match the statement
+    /// MySQL error 1210: Incorrect arguments to %s
+    pub fn wrong_arguments(command: &str) -> Self {
+        MiniSqlError::Protocol(format!("Incorrect arguments to {}", command))
+    }
+
+    /// Create a MySQL-compatible "too many connections" error
+    /// MySQL error 1040: Too many connections
+    pub fn too_many_connections() -> Self {
+        MiniSqlError::Protocol("Too many connections".into())
+    }
+
+    /// Create a MySQL-compatible "packet too large" error, for a client
+    /// packet longer than max_allowed_packet
+    /// MySQL error 1153: Got a packet bigger than 'max_allowed_packet' bytes
+    pub fn packet_too_large() -> Self {
+        MiniSqlError::Protocol("Got a packet bigger than 'max_allowed_packet' bytes".into())
+    }
+
     /// Create a MySQL-compatible "duplicate table alias" error
     /// MySQL error 1066: Not unique table/alias: '%s'
     pub fn duplicate_table_alias(alias: &str) -> Self {
@@ -197,20 +422,78 @@ pub mod mysql_error_codes {
     pub const ER_NON_UNIQ_TABLE: u16 = 1066;
     pub const ER_LOCK_WAIT_TIMEOUT: u16 = 1205;
     pub const ER_LOCK_DEADLOCK: u16 = 1213;
+    pub const ER_OUT_OF_SORTMEMORY: u16 = 1038;
+    pub const ER_SP_DOES_NOT_EXIST: u16 = 1305;
+    pub const ER_CANT_CHANGE_TX_CHARACTERISTICS: u16 = 1568;
+    pub const ER_CRASHED_ON_USAGE: u16 = 1194;
+    pub const ER_DUP_FIELDNAME: u16 = 1060;
+    pub const ER_ROW_IS_REFERENCED_2: u16 = 1451;
+    pub const ER_NO_REFERENCED_ROW_2: u16 = 1452;
+    pub const ER_TRUNCATE_ILLEGAL_FK: u16 = 1701;
+    pub const ER_FK_CANNOT_DROP_PARENT: u16 = 3730;
+    pub const ER_OPERAND_COLUMNS: u16 = 1241;
+    pub const ER_SUBQUERY_NO_1_ROW: u16 = 1242;
+    pub const ER_WRONG_VALUE_COUNT_ON_ROW: u16 = 1136;
+    pub const ER_FIELD_SPECIFIED_TWICE: u16 = 1110;
+    pub const ER_NO_DEFAULT_FOR_FIELD: u16 = 1364;
+    pub const ER_WRONG_PARAMCOUNT_TO_NATIVE_FCT: u16 = 1582;
+    pub const ER_CON_COUNT_ERROR: u16 = 1040;
+    pub const ER_QUERY_TIMEOUT: u16 = 3024;
+    pub const ER_OPTION_PREVENTS_STATEMENT: u16 = 1290;
+    pub const ER_DATA_TOO_LONG: u16 = 1406;
+    pub const ER_MULTIPLE_PRI_KEY: u16 = 1068;
+    pub const ER_KEY_COLUMN_DOES_NOT_EXIST: u16 = 1072;
+    pub const ER_TABLEACCESS_DENIED_ERROR: u16 = 1142;
+    pub const ER_SPECIFIC_ACCESS_DENIED_ERROR: u16 = 1227;
+    pub const ER_NONEXISTING_TABLE_GRANT: u16 = 1147;
+    pub const ER_BAD_TABLE_ERROR: u16 = 1051;
+    pub const ER_CANT_DROP_FIELD_OR_KEY: u16 = 1091;
+    pub const ER_DUP_KEYNAME: u16 = 1061;
+    pub const WARN_DATA_TRUNCATED: u16 = 1265;
+    pub const ER_TRUNCATED_WRONG_VALUE_FOR_FIELD: u16 = 1366;
+    pub const ER_WRONG_ARGUMENTS: u16 = 1210;
+    pub const ER_NET_PACKET_TOO_LARGE: u16 = 1153;
 }
 
 impl MiniSqlError {
     /// Get the MySQL error code for this error
     pub fn mysql_error_code(&self) -> u16 {
         match self {
+            MiniSqlError::Syntax(msg) if msg.starts_with("Operand should contain") => mysql_error_codes::ER_OPERAND_COLUMNS,
+            MiniSqlError::Syntax(msg) if msg.starts_with("Subquery returns more than 1 row") => mysql_error_codes::ER_SUBQUERY_NO_1_ROW,
+            MiniSqlError::Syntax(msg) if msg.starts_with("Incorrect parameter count") => mysql_error_codes::ER_WRONG_PARAMCOUNT_TO_NATIVE_FCT,
             MiniSqlError::Syntax(_) => mysql_error_codes::ER_PARSE_ERROR,
             MiniSqlError::Table(msg) if msg.contains("doesn't exist") => mysql_error_codes::ER_NO_SUCH_TABLE,
             MiniSqlError::Table(msg) if msg.contains("Not unique table/alias") => mysql_error_codes::ER_NON_UNIQ_TABLE,
             MiniSqlError::Table(_) => mysql_error_codes::ER_TABLE_EXISTS_ERROR,
+            MiniSqlError::Column(msg) if msg.starts_with("Duplicate column name") => mysql_error_codes::ER_DUP_FIELDNAME,
+            MiniSqlError::Column(msg) if msg.starts_with("Column count doesn't match") => mysql_error_codes::ER_WRONG_VALUE_COUNT_ON_ROW,
+            MiniSqlError::Column(msg) if msg.ends_with("specified twice") => mysql_error_codes::ER_FIELD_SPECIFIED_TWICE,
+            MiniSqlError::Column(msg) if msg.starts_with("Multiple primary key") => mysql_error_codes::ER_MULTIPLE_PRI_KEY,
+            MiniSqlError::Column(msg) if msg.starts_with("Key column") => mysql_error_codes::ER_KEY_COLUMN_DOES_NOT_EXIST,
             MiniSqlError::Column(_) => mysql_error_codes::ER_BAD_FIELD_ERROR,
+            MiniSqlError::Auth(msg) if msg.contains("command denied") => mysql_error_codes::ER_TABLEACCESS_DENIED_ERROR,
+            MiniSqlError::Auth(msg) if msg.starts_with("Access denied; you need") => mysql_error_codes::ER_SPECIFIC_ACCESS_DENIED_ERROR,
+            MiniSqlError::Auth(msg) if msg.starts_with("There is no such grant") => mysql_error_codes::ER_NONEXISTING_TABLE_GRANT,
             MiniSqlError::Auth(_) => mysql_error_codes::ER_ACCESS_DENIED_ERROR,
+            MiniSqlError::Type(msg) if msg.starts_with("Data too long") => mysql_error_codes::ER_DATA_TOO_LONG,
+            MiniSqlError::Constraint(msg) if msg.starts_with("Cannot delete or update a parent row") => mysql_error_codes::ER_ROW_IS_REFERENCED_2,
+            MiniSqlError::Constraint(msg) if msg.starts_with("Cannot add or update a child row") => mysql_error_codes::ER_NO_REFERENCED_ROW_2,
+            MiniSqlError::Constraint(msg) if msg.starts_with("Cannot truncate a table referenced") => mysql_error_codes::ER_TRUNCATE_ILLEGAL_FK,
+            MiniSqlError::Constraint(msg) if msg.starts_with("Cannot drop table") => mysql_error_codes::ER_FK_CANNOT_DROP_PARENT,
+            MiniSqlError::Constraint(msg) if msg.ends_with("doesn't have a default value") => mysql_error_codes::ER_NO_DEFAULT_FOR_FIELD,
             MiniSqlError::Transaction(msg) if msg.contains("timeout") => mysql_error_codes::ER_LOCK_WAIT_TIMEOUT,
-            MiniSqlError::Transaction(msg) if msg.contains("deadlock") => mysql_error_codes::ER_LOCK_DEADLOCK,
+            MiniSqlError::Transaction(msg) if msg.to_lowercase().contains("deadlock") => mysql_error_codes::ER_LOCK_DEADLOCK,
+            MiniSqlError::Transaction(msg) if msg.starts_with("SAVEPOINT ") => mysql_error_codes::ER_SP_DOES_NOT_EXIST,
+            MiniSqlError::Transaction(msg) if msg.starts_with("Transaction characteristics") => mysql_error_codes::ER_CANT_CHANGE_TX_CHARACTERISTICS,
+            MiniSqlError::Protocol(msg) if msg.starts_with("Too many connections") => mysql_error_codes::ER_CON_COUNT_ERROR,
+            MiniSqlError::Protocol(msg) if msg.starts_with("Incorrect arguments to") => mysql_error_codes::ER_WRONG_ARGUMENTS,
+            MiniSqlError::Protocol(msg) if msg.starts_with("Got a packet bigger") => mysql_error_codes::ER_NET_PACKET_TOO_LARGE,
+            MiniSqlError::Internal(msg) if msg.starts_with("Out of sort memory") => mysql_error_codes::ER_OUT_OF_SORTMEMORY,
+            MiniSqlError::Internal(msg) if msg.starts_with("Query execution was interrupted") => mysql_error_codes::ER_QUERY_TIMEOUT,
+            MiniSqlError::Internal(msg) if msg.contains("is marked as crashed") => mysql_error_codes::ER_CRASHED_ON_USAGE,
+            MiniSqlError::Internal(msg) if msg.contains("--read-only option")
+                || msg.contains("--secure-file-priv option") => mysql_error_codes::ER_OPTION_PREVENTS_STATEMENT,
             _ => mysql_error_codes::ER_UNKNOWN_COM_ERROR,
         }
     }
@@ -218,11 +501,31 @@ impl MiniSqlError {
     /// Get the SQL state for this error
     pub fn sql_state(&self) -> &'static str {
         match self {
+            MiniSqlError::Syntax(msg) if msg.starts_with("Operand should contain")
+                || msg.starts_with("Subquery returns more than 1 row") => "21000",
             MiniSqlError::Syntax(_) => "42000",
             MiniSqlError::Table(_) => "42S02",
+            MiniSqlError::Column(msg) if msg.starts_with("Duplicate column name") => "42S21",
+            MiniSqlError::Column(msg) if msg.starts_with("Column count doesn't match") => "21S01",
+            MiniSqlError::Column(msg) if msg.ends_with("specified twice")
+                || msg.starts_with("Multiple primary key")
+                || msg.starts_with("Key column") => "42000",
             MiniSqlError::Column(_) => "42S22",
+            MiniSqlError::Auth(msg) if msg.contains("command denied")
+                || msg.starts_with("Access denied; you need")
+                || msg.starts_with("There is no such grant") => "42000",
             MiniSqlError::Auth(_) => "28000",
+            MiniSqlError::Type(msg) if msg.starts_with("Data too long") => "22001",
+            MiniSqlError::Constraint(msg) if msg.starts_with("Cannot delete or update a parent row")
+                || msg.starts_with("Cannot add or update a child row") => "23000",
+            MiniSqlError::Constraint(msg) if msg.starts_with("Cannot truncate a table referenced") => "42000",
+            MiniSqlError::Transaction(msg) if msg.starts_with("SAVEPOINT ") => "42000",
+            MiniSqlError::Transaction(msg) if msg.starts_with("Transaction characteristics") => "25001",
+            MiniSqlError::Transaction(msg) if msg.starts_with("Lock wait timeout") => "HY000",
             MiniSqlError::Transaction(_) => "40001",
+            MiniSqlError::Protocol(msg) if msg.starts_with("Too many connections") => "08004",
+            MiniSqlError::Protocol(msg) if msg.starts_with("Got a packet bigger") => "08S01",
+            MiniSqlError::Internal(msg) if msg.starts_with("Out of sort memory") => "HY001",
             _ => "HY000",
         }
     }
@@ -3,8 +3,10 @@
 //! This module provides accumulators for aggregate functions like COUNT, SUM, and AVG.
 //! Each accumulator tracks state across multiple rows and produces a final aggregated value.
 
+use std::collections::HashSet;
+
 use crate::error::{MiniSqlError, Result};
-use crate::parser::Expr;
+use crate::parser::{is_aggregate_function, Expr};
 use crate::types::Value;
 
 /// Trait for aggregate function accumulators
@@ -158,6 +160,9 @@ impl AggregateAccumulator for AvgAccumulator {
 }
 
 /// MIN accumulator - finds minimum value
+///
+/// Works for any comparable type (numbers, strings, booleans) using
+/// `Value::partial_cmp`; NULLs are ignored and an all-NULL group gives NULL.
 pub struct MinAccumulator {
     min: Option<Value>,
 }
@@ -199,7 +204,7 @@ impl AggregateAccumulator for MinAccumulator {
     }
 }
 
-/// MAX accumulator - finds maximum value
+/// MAX accumulator - finds maximum value (see [`MinAccumulator`] for ordering)
 pub struct MaxAccumulator {
     max: Option<Value>,
 }
@@ -241,12 +246,74 @@ impl AggregateAccumulator for MaxAccumulator {
     }
 }
 
-/// Check if a function name is an aggregate function
-pub fn is_aggregate_function(name: &str) -> bool {
-    matches!(
-        name.to_uppercase().as_str(),
-        "COUNT" | "SUM" | "AVG" | "MIN" | "MAX"
-    )
+/// GROUP_CONCAT accumulator - joins the non-NULL values of a group
+///
+/// Values are joined in the order they are accumulated (scan order).
+pub struct GroupConcatAccumulator {
+    separator: String,
+    values: Vec<String>,
+}
+
+impl GroupConcatAccumulator {
+    /// MySQL's default separator
+    pub const DEFAULT_SEPARATOR: &'static str = ",";
+
+    pub fn new(separator: impl Into<String>) -> Self {
+        Self { separator: separator.into(), values: Vec::new() }
+    }
+}
+
+impl AggregateAccumulator for GroupConcatAccumulator {
+    fn accumulate(&mut self, value: &Value) -> Result<()> {
+        if let Some(text) = value.to_string_repr() {
+            self.values.push(text);
+        }
+        Ok(())
+    }
+
+    fn finalize(&self) -> Value {
+        if self.values.is_empty() {
+            Value::Null
+        } else {
+            Value::String(self.values.join(&self.separator))
+        }
+    }
+
+    fn clone_empty(&self) -> Box<dyn AggregateAccumulator> {
+        Box::new(GroupConcatAccumulator::new(self.separator.clone()))
+    }
+}
+
+/// DISTINCT wrapper - passes each non-NULL value to the inner accumulator
+/// only the first time it is seen, as for `COUNT(DISTINCT x)`
+///
+/// Values are compared with `Value` equality, so `1` and `1.0` are the same value.
+pub struct DistinctAccumulator {
+    seen: HashSet<Value>,
+    inner: Box<dyn AggregateAccumulator>,
+}
+
+impl DistinctAccumulator {
+    pub fn new(inner: Box<dyn AggregateAccumulator>) -> Self {
+        Self { seen: HashSet::new(), inner }
+    }
+}
+
+impl AggregateAccumulator for DistinctAccumulator {
+    fn accumulate(&mut self, value: &Value) -> Result<()> {
+        if value.is_null() || !self.seen.insert(value.clone()) {
+            return Ok(());
+        }
+        self.inner.accumulate(value)
+    }
+
+    fn finalize(&self) -> Value {
+        self.inner.finalize()
+    }
+
+    fn clone_empty(&self) -> Box<dyn AggregateAccumulator> {
+        Box::new(DistinctAccumulator::new(self.inner.clone_empty()))
+    }
 }
 
 /// Check if an expression contains an aggregate function call
@@ -254,28 +321,59 @@ pub fn is_aggregate_expr(expr: &Expr) -> bool {
     match expr {
         Expr::FunctionCall { name, .. } => is_aggregate_function(name),
         Expr::BinaryOp { left, right, .. } => is_aggregate_expr(left) || is_aggregate_expr(right),
-        Expr::Not(inner) | Expr::IsNull(inner) | Expr::IsNotNull(inner) => is_aggregate_expr(inner),
-        Expr::JsonAccess { expr, .. } => is_aggregate_expr(expr),
+        Expr::Not(inner) | Expr::UnaryMinus(inner) | Expr::IsNull(inner) | Expr::IsNotNull(inner) => is_aggregate_expr(inner),
+        Expr::JsonAccess { expr, .. } | Expr::Cast { expr, .. } | Expr::Like { expr, .. } => is_aggregate_expr(expr),
         _ => false,
     }
 }
 
-/// Create an accumulator for a given aggregate function name
-pub fn create_accumulator(name: &str, args: &[Expr]) -> Result<Box<dyn AggregateAccumulator>> {
-    match name.to_uppercase().as_str() {
+/// Create an accumulator for a given aggregate function name; with
+/// `distinct`, only unique non-NULL values are aggregated, and `separator`
+/// is GROUP_CONCAT's
+pub fn create_accumulator(
+    name: &str,
+    args: &[Expr],
+    distinct: bool,
+    separator: Option<&str>,
+) -> Result<Box<dyn AggregateAccumulator>> {
+    let name = name.to_uppercase();
+    // COUNT(*) is parsed without arguments; everything else takes exactly one
+    let arity = match name.as_str() {
+        "COUNT" => 0..=1,
+        _ => 1..=1,
+    };
+    if !arity.contains(&args.len()) {
+        return Err(MiniSqlError::Syntax(format!(
+            "Incorrect parameter count in the call to native function '{}'",
+            name
+        )));
+    }
+
+    let accumulator: Box<dyn AggregateAccumulator> = match name.as_str() {
         "COUNT" => {
             let count_star = args.is_empty(); // COUNT(*) has no args
-            Ok(Box::new(CountAccumulator::new(count_star)))
+            Box::new(CountAccumulator::new(count_star))
         }
-        "SUM" => Ok(Box::new(SumAccumulator::new())),
-        "AVG" => Ok(Box::new(AvgAccumulator::new())),
-        "MIN" => Ok(Box::new(MinAccumulator::new())),
-        "MAX" => Ok(Box::new(MaxAccumulator::new())),
-        _ => Err(MiniSqlError::Syntax(format!(
-            "Unknown aggregate function: {}",
-            name
-        ))),
-    }
+        "SUM" => Box::new(SumAccumulator::new()),
+        "AVG" => Box::new(AvgAccumulator::new()),
+        "MIN" => Box::new(MinAccumulator::new()),
+        "MAX" => Box::new(MaxAccumulator::new()),
+        "GROUP_CONCAT" => {
+            Box::new(GroupConcatAccumulator::new(separator.unwrap_or(GroupConcatAccumulator::DEFAULT_SEPARATOR)))
+        }
+        _ => {
+            return Err(MiniSqlError::Syntax(format!(
+                "Unknown aggregate function: {}",
+                name
+            )))
+        }
+    };
+
+    Ok(if distinct {
+        Box::new(DistinctAccumulator::new(accumulator))
+    } else {
+        accumulator
+    })
 }
 
 #[cfg(test)]
@@ -368,15 +466,65 @@ mod tests {
     }
 
     #[test]
-    fn test_is_aggregate_function() {
-        assert!(is_aggregate_function("COUNT"));
-        assert!(is_aggregate_function("count"));
-        assert!(is_aggregate_function("SUM"));
-        assert!(is_aggregate_function("AVG"));
-        assert!(is_aggregate_function("MIN"));
-        assert!(is_aggregate_function("MAX"));
-        assert!(!is_aggregate_function("COALESCE"));
-        assert!(!is_aggregate_function("JSON_EXTRACT"));
+    fn test_min_max_strings() {
+        let mut min = MinAccumulator::new();
+        let mut max = MaxAccumulator::new();
+        for value in ["bob", "ann", "carl", "anna"] {
+            min.accumulate(&Value::String(value.into())).unwrap();
+            max.accumulate(&Value::String(value.into())).unwrap();
+        }
+        min.accumulate(&Value::Null).unwrap();
+        max.accumulate(&Value::Null).unwrap();
+        assert_eq!(min.finalize(), Value::String("ann".into()));
+        assert_eq!(max.finalize(), Value::String("carl".into()));
+    }
+
+    #[test]
+    fn test_min_max_all_null_or_empty() {
+        let mut min = MinAccumulator::new();
+        let mut max = MaxAccumulator::new();
+        assert_eq!(min.finalize(), Value::Null);
+        assert_eq!(max.finalize(), Value::Null);
+        min.accumulate(&Value::Null).unwrap();
+        max.accumulate(&Value::Null).unwrap();
+        assert_eq!(min.finalize(), Value::Null);
+        assert_eq!(max.finalize(), Value::Null);
+    }
+
+    #[test]
+    fn test_group_concat() {
+        let mut acc = GroupConcatAccumulator::new("-");
+        assert_eq!(acc.finalize(), Value::Null);
+        acc.accumulate(&Value::String("a".into())).unwrap();
+        acc.accumulate(&Value::Null).unwrap();
+        acc.accumulate(&Value::Integer(2)).unwrap();
+        acc.accumulate(&Value::Boolean(true)).unwrap();
+        assert_eq!(acc.finalize(), Value::String("a-2-1".into()));
+        assert_eq!(acc.clone_empty().finalize(), Value::Null);
+    }
+
+    #[test]
+    fn test_distinct_count_and_sum() {
+        let col = Expr::Column { table: None, name: "x".to_string() };
+        let mut count = create_accumulator("COUNT", &[col.clone()], true, None).unwrap();
+        let mut sum = create_accumulator("SUM", &[col], true, None).unwrap();
+        for value in [Value::Integer(2), Value::Null, Value::Integer(3), Value::Float(2.0), Value::Integer(3)] {
+            count.accumulate(&value).unwrap();
+            sum.accumulate(&value).unwrap();
+        }
+        assert_eq!(count.finalize(), Value::Integer(2));
+        assert_eq!(sum.finalize(), Value::Integer(5));
+        assert_eq!(count.clone_empty().finalize(), Value::Integer(0));
+    }
+
+    #[test]
+    fn test_accumulator_arity() {
+        let col = Expr::Column { table: None, name: "x".to_string() };
+        assert!(create_accumulator("COUNT", &[], false, None).is_ok());
+        assert!(create_accumulator("max", &[col.clone()], false, None).is_ok());
+        assert!(create_accumulator("MIN", &[], false, None).is_err());
+        assert!(create_accumulator("SUM", &[col.clone(), col.clone()], false, None).is_err());
+        assert!(create_accumulator("GROUP_CONCAT", &[col.clone(), col], false, Some(";")).is_err());
     }
 
     #[test]
@@ -384,6 +532,8 @@ mod tests {
         let count_expr = Expr::FunctionCall {
             name: "COUNT".to_string(),
             args: vec![],
+            distinct: false,
+            separator: None,
         };
         assert!(is_aggregate_expr(&count_expr));
 
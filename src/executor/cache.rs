@@ -0,0 +1,201 @@
+//! Query result cache
+//!
+//! An optional LRU map from normalized SELECT text to its result set, so a
+//! repeated read-only query against unchanged tables skips the scan.
+//!
+//! - Keys are the statement's token sequence with literals kept, so queries
+//!   that differ only in whitespace or keyword case share an entry, while
+//!   `id = 1` and `id = 2` do not.
+//! - Only autocommit SELECTs whose result depends on table contents alone are
+//!   cached; anything calling `NOW()`, `LAST_INSERT_ID()` and the like,
+//!   reading a user variable, or sampling without `REPEATABLE`, always runs.
+//! - Every statement that may modify data or schema flushes the whole cache.
+//!   A result computed while such a statement ran is not stored: entries are
+//!   only inserted when the cache generation is unchanged since the SELECT
+//!   started.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::lexer::{Lexer, Token};
+use crate::types::ResultSet;
+
+/// Functions whose result does not depend only on the tables a query reads
+const NONDETERMINISTIC_FUNCTIONS: &[&str] = &[
+    "NOW", "CURRENT_TIMESTAMP", "CURRENT_DATE", "CURRENT_TIME", "CURDATE", "CURTIME",
+    "SYSDATE", "UTC_TIMESTAMP", "UNIX_TIMESTAMP", "LAST_INSERT_ID", "CONNECTION_ID",
+    "RAND", "UUID", "DATABASE", "USER",
+];
+
+/// Cache key for a SELECT lexed with or without ANSI_QUOTES, or `None` when
+/// its result must not be cached
+pub(crate) fn cache_key(sql: &str, ansi_quotes: bool) -> Option<String> {
+    let tokens = Lexer::new(sql).with_ansi_quotes(ansi_quotes).tokenize().ok()?;
+    let mut sampled = false;
+    let mut repeatable = false;
+    for token in &tokens {
+        if matches!(token, Token::UserVariable(_)) {
+            return None;
+        }
+        if let Token::Identifier(name) = token {
+            let upper = name.to_uppercase();
+            if NONDETERMINISTIC_FUNCTIONS.contains(&upper.as_str()) {
+                return None;
+            }
+            sampled |= upper == "TABLESAMPLE";
+            repeatable |= upper == "REPEATABLE";
+        }
+    }
+    if sampled && !repeatable {
+        return None;
+    }
+    let significant = tokens.iter().filter(|t| !matches!(t, Token::Eof | Token::Semicolon));
+    Some(significant.map(|t| format!("{:?}", t)).collect::<Vec<_>>().join(" "))
+}
+
+/// Hit and miss counters of the query cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueryCacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that had to run the query
+    pub misses: u64,
+    /// Results currently cached
+    pub entries: usize,
+}
+
+struct CacheEntry {
+    result: Arc<ResultSet>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    /// Bumped by every invalidation
+    generation: u64,
+    /// Logical clock for least-recently-used eviction
+    clock: u64,
+}
+
+/// LRU cache of SELECT results, flushed on every write
+pub(crate) struct QueryCache {
+    state: Mutex<CacheState>,
+    max_entries: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl QueryCache {
+    pub(crate) fn new(max_entries: usize) -> Self {
+        Self {
+            state: Mutex::new(CacheState::default()),
+            max_entries,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Look up a cached result, counting the hit or miss
+    pub(crate) fn get(&self, key: &str) -> Option<Arc<ResultSet>> {
+        let mut state = self.state.lock().unwrap();
+        state.clock += 1;
+        let now = state.clock;
+        match state.entries.get_mut(key) {
+            Some(entry) => {
+                entry.last_used = now;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(Arc::clone(&entry.result))
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Current generation; pass it to [`QueryCache::insert`] for a result
+    /// computed after this call
+    pub(crate) fn generation(&self) -> u64 {
+        self.state.lock().unwrap().generation
+    }
+
+    /// Store a result unless the cache was invalidated since `generation`
+    pub(crate) fn insert(&self, key: String, result: ResultSet, generation: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.generation != generation || self.max_entries == 0 {
+            return;
+        }
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.max_entries {
+            let oldest = state.entries.iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.clock += 1;
+        let last_used = state.clock;
+        state.entries.insert(key, CacheEntry { result: Arc::new(result), last_used });
+    }
+
+    /// Drop every cached result
+    pub(crate) fn invalidate(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        state.entries.clear();
+    }
+
+    pub(crate) fn stats(&self) -> QueryCacheStats {
+        QueryCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.state.lock().unwrap().entries.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DataType, Value};
+
+    fn result(n: i64) -> ResultSet {
+        ResultSet {
+            columns: vec!["n".into()],
+            column_types: vec![DataType::Integer],
+            rows: vec![vec![Value::Integer(n)]],
+        }
+    }
+
+    #[test]
+    fn test_cache_key_normalization() {
+        assert_eq!(cache_key("select * from t where id = 1", false), cache_key("SELECT *  FROM t\nWHERE id = 1;", false));
+        assert_ne!(cache_key("SELECT * FROM t WHERE id = 1", false), cache_key("SELECT * FROM t WHERE id = 2", false));
+        assert_eq!(cache_key("SELECT NOW()", false), None);
+        assert_eq!(cache_key("SELECT * FROM t WHERE id = last_insert_id()", false), None);
+        assert_eq!(cache_key("SELECT * FROM t WHERE id = @id", false), None);
+        assert_eq!(cache_key("SELECT * FROM t TABLESAMPLE (10 PERCENT)", false), None);
+        assert!(cache_key("SELECT * FROM t TABLESAMPLE (10 PERCENT) REPEATABLE (7)", false).is_some());
+        // "x" is a string by default but a column under ANSI_QUOTES
+        assert_ne!(cache_key("SELECT \"x\" FROM t", false), cache_key("SELECT \"x\" FROM t", true));
+    }
+
+    #[test]
+    fn test_lru_eviction_and_stale_insert() {
+        let cache = QueryCache::new(2);
+        let generation = cache.generation();
+        cache.insert("a".into(), result(1), generation);
+        cache.insert("b".into(), result(2), generation);
+        assert!(cache.get("a").is_some());
+        cache.insert("c".into(), result(3), generation);
+        assert!(cache.get("b").is_none(), "least recently used entry should be evicted");
+        assert!(cache.get("a").is_some());
+
+        cache.invalidate();
+        assert_eq!(cache.stats().entries, 0);
+        cache.insert("d".into(), result(4), generation);
+        assert!(cache.get("d").is_none(), "result from before the invalidation must not be stored");
+    }
+}
@@ -1,16 +1,25 @@
-use crate::error::{MiniSqlError, Result};
+use crate::error::{mysql_error_codes, MiniSqlError, Result};
 use crate::parser::{ColumnDefAst, CreateIndexStmt, CreateTableStmt};
-use crate::types::{ColumnDef, DataType, IndexMetadata, QueryResult, TableSchema};
-use crate::executor::{Executor, Session};
+use crate::types::{ColumnDef, DataType, IndexMetadata, QueryResult, TableSchema, Value};
+use crate::executor::{Executor, Session, WarningLevel};
 use crate::executor::evaluator;
 
 impl Executor {
     /// Execute CREATE TABLE
     pub(crate) fn execute_create_table(
         &self,
-        create: CreateTableStmt,
+        mut create: CreateTableStmt,
         session: &Session,
     ) -> Result<QueryResult> {
+        // IF NOT EXISTS on an existing table leaves it untouched, and the WAL too
+        if create.if_not_exists && self.storage.table_exists(&create.table_name) {
+            let message = MiniSqlError::table_already_exists(&create.table_name).to_string();
+            session.warn(WarningLevel::Note, mysql_error_codes::ER_TABLE_EXISTS_ERROR, message);
+            return Ok(QueryResult::Ok);
+        }
+
+        apply_table_primary_key(&mut create)?;
+
         // Validate AUTO_INCREMENT constraints
         self.validate_auto_increment_constraints(&create)?;
 
@@ -27,12 +36,16 @@ impl Executor {
                 auto_increment: c.auto_increment,
             })
             .collect();
+        let foreign_keys = self.resolve_foreign_keys(&create.table_name, &columns, &create.foreign_keys)?;
 
         let schema = TableSchema {
             name: create.table_name.clone(),
             columns,
-            auto_increment_counter: 1,
-            engine_type: create.engine.unwrap_or_default(),
+            auto_increment_counter: create.auto_increment.unwrap_or(1).max(1),
+            engine_type: create.engine.unwrap_or(self.default_engine),
+            storage_format: create.storage_format.unwrap_or(self.default_storage_format),
+            foreign_keys,
+            primary_key: create.primary_key.clone(),
         };
 
         // Log to WAL
@@ -43,12 +56,12 @@ impl Executor {
         self.storage.create_table(schema.clone(), create.if_not_exists)?;
 
         // Auto-create primary key index if there are primary key columns
-        let pk_columns: Vec<&ColumnDef> = schema.columns.iter().filter(|c| c.primary_key).collect();
+        let pk_columns = schema.primary_key_columns();
         if !pk_columns.is_empty() {
             let pk_index = IndexMetadata {
                 name: schema.primary_key_index_name(),
                 table_name: schema.name.clone(),
-                columns: pk_columns.iter().map(|c| c.name.clone()).collect(),
+                columns: pk_columns.iter().map(|&i| schema.columns[i].name.clone()).collect(),
                 unique: true,
                 is_primary: true,
             };
@@ -95,11 +108,24 @@ impl Executor {
     }
 
     /// Execute DROP TABLE
-    pub(crate) fn execute_drop_table(&self, table_name: &str, session: &Session) -> Result<QueryResult> {
+    pub(crate) fn execute_drop_table(&self, table_name: &str, if_exists: bool, session: &Session) -> Result<QueryResult> {
+        if if_exists && !self.storage.table_exists(table_name) {
+            let message = format!("Unknown table 'minisql.{}'", table_name);
+            session.warn(WarningLevel::Note, mysql_error_codes::ER_BAD_TABLE_ERROR, message);
+            return Ok(QueryResult::Ok);
+        }
+
+        if let Some((child, fk)) = self.referencing_foreign_keys(table_name)?.into_iter().find(|(c, _)| c.name != table_name) {
+            return Err(MiniSqlError::foreign_key_drop_parent(table_name, &fk.name, &child.name));
+        }
+
         // Log to WAL
         let txn_id = self.get_txn_id(session);
         self.txn_manager.log_drop_table(txn_id, table_name)?;
 
+        // Clear engine-held state first so a background flush cannot write it back
+        self.get_engine(table_name)?.drop_table(table_name)?;
+
         // Drop the table (this should also drop associated indexes)
         self.storage.drop_table(table_name)?;
 
@@ -110,8 +136,13 @@ impl Executor {
     pub(crate) fn execute_create_index(
         &self,
         create: CreateIndexStmt,
-        _session: &Session,
+        session: &Session,
     ) -> Result<QueryResult> {
+        if create.if_not_exists && self.storage.get_index(&create.index_name).is_ok() {
+            let message = format!("Duplicate key name '{}'", create.index_name);
+            session.warn(WarningLevel::Note, mysql_error_codes::ER_DUP_KEYNAME, message);
+            return Ok(QueryResult::Ok);
+        }
         let index_metadata = IndexMetadata {
             name: create.index_name.clone(),
             table_name: create.table_name.clone(),
@@ -127,7 +158,13 @@ impl Executor {
     }
 
     /// Execute DROP INDEX
-    pub(crate) fn execute_drop_index(&self, index_name: &str, _session: &Session) -> Result<QueryResult> {
+    pub(crate) fn execute_drop_index(&self, index_name: &str, if_exists: bool, session: &Session) -> Result<QueryResult> {
+        if if_exists && self.storage.get_index(index_name).is_err() {
+            let message = format!("Can't DROP '{}'; check that column/key exists", index_name);
+            session.warn(WarningLevel::Note, mysql_error_codes::ER_CANT_DROP_FIELD_OR_KEY, message);
+            return Ok(QueryResult::Ok);
+        }
+
         // Drop the index
         self.storage.drop_index(index_name)?;
 
@@ -136,10 +173,17 @@ impl Executor {
 
     /// Execute TRUNCATE TABLE
     pub(crate) fn execute_truncate_table(&self, table_name: &str, session: &Session) -> Result<QueryResult> {
+        if let Some((child, fk)) = self.referencing_foreign_keys(table_name)?.into_iter().find(|(c, _)| c.name != table_name) {
+            return Err(MiniSqlError::foreign_key_truncate_parent(&child.name, &fk.constraint_sql()));
+        }
+
         // Log to WAL
         let txn_id = self.get_txn_id(session);
         self.txn_manager.log_truncate_table(txn_id, table_name)?;
 
+        // Clear engine-held rows first so a background flush cannot write them back
+        self.get_engine(table_name)?.truncate_table(table_name)?;
+
         // Truncate the table
         self.storage.truncate_table(table_name)?;
 
@@ -150,7 +194,7 @@ impl Executor {
     pub(crate) fn execute_alter_table(
         &self,
         alter: crate::parser::AlterTableStmt,
-        _session: &Session,
+        session: &Session,
     ) -> Result<QueryResult> {
         use crate::parser::AlterTableAction;
         
@@ -165,6 +209,14 @@ impl Executor {
                     return Ok(QueryResult::Ok);
                 }
                 
+                // The migration writes the table out, which must not pick up
+                // a transaction's uncommitted rows
+                if session.txn_id.is_some() {
+                    return Err(MiniSqlError::Transaction(
+                        "ALTER TABLE ... ENGINE cannot be run inside a transaction".into(),
+                    ));
+                }
+
                 // Check that new engine is enabled
                 if !self.handlers.contains_key(&new_engine) {
                     return Err(MiniSqlError::Table(
@@ -172,28 +224,87 @@ impl Executor {
                     ));
                 }
                 
-                // Migrate data: flush old engine, init new engine
-                // This generic approach works for any engine combination
-                if let Some(old_handler) = self.handlers.get(&old_engine) {
-                    old_handler.flush(&alter.table_name)?;
-                }
-                if let Some(new_handler) = self.handlers.get(&new_engine) {
-                    new_handler.init_table(&alter.table_name)?;
-                }
-                
+                // The migration runs with no transaction open and is followed
+                // by a checkpoint that truncates the WAL, so recovery does not
+                // replay the table's old Granite records over later changes
+                self.txn_manager.checkpoint_with(&self.storage, || {
+                    // Migrate data: the old engine writes every row it holds to
+                    // storage (Sandstone's unflushed changes included) and lets go
+                    // of its copy, so a later background flush cannot overwrite
+                    // rows the new engine has changed since
+                    if let Some(old_handler) = self.handlers.get(&old_engine) {
+                        old_handler.flush(&alter.table_name)?;
+                        old_handler.drop_table(&alter.table_name)?;
+                    }
+
+                    // Update schema with new engine type
+                    schema.engine_type = new_engine;
+
+                    // Update the catalog
+                    self.storage.update_schema(&alter.table_name, schema)?;
+
+                    // The new engine loads the rows from storage; a Sandstone flush
+                    // replaces the rows wholesale, so indexes are rebuilt from them
+                    self.handlers[&new_engine].init_table(&alter.table_name)?;
+                    self.storage.rebuild_table_indexes(&alter.table_name)
+                })?;
+
                 log::info!(
                     "Table '{}' migrated from {} to {}",
                     alter.table_name, old_engine, new_engine
                 );
                 
-                // Update schema with new engine type
-                schema.engine_type = new_engine;
-                
-                // Update the catalog
-                self.storage.update_schema(&alter.table_name, schema)?;
-                
                 Ok(QueryResult::Ok)
             }
+            AlterTableAction::SetAutoIncrement(value) => {
+                let schema = self.storage.get_schema(&alter.table_name)?;
+
+                // Like MySQL, clamp the value up to one past the largest existing id
+                let mut counter = value.max(1);
+                if let Some(col_idx) = schema.auto_increment_column() {
+                    let max_id = self.scan_table(&alter.table_name)?
+                        .iter()
+                        .filter_map(|row| match row.values.get(col_idx) {
+                            Some(Value::Integer(id)) if *id > 0 => Some(*id as u64),
+                            _ => None,
+                        })
+                        .max();
+                    if let Some(max_id) = max_id {
+                        counter = counter.max(max_id + 1);
+                    }
+                }
+
+                self.storage.set_auto_increment(&alter.table_name, counter)?;
+                Ok(QueryResult::Ok)
+            }
+            AlterTableAction::RenameColumn { old_name, new_name } => {
+                self.storage.rename_column(&alter.table_name, &old_name, &new_name)?;
+                Ok(QueryResult::Ok)
+            }
+        }
+    }
+}
+
+/// Flag the columns of a table-level `PRIMARY KEY (cols)` clause as the
+/// column-level form does. The clause's names are replaced by the columns'
+/// own, in declared order, which the key and its index keep.
+fn apply_table_primary_key(create: &mut CreateTableStmt) -> Result<()> {
+    if create.primary_key.is_empty() {
+        return Ok(());
+    }
+    if create.columns.iter().any(|c| c.primary_key) {
+        return Err(MiniSqlError::multiple_primary_keys());
+    }
+    for name in create.primary_key.iter_mut() {
+        let column = create.columns.iter_mut()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| MiniSqlError::key_column_missing(name))?;
+        if column.primary_key {
+            return Err(MiniSqlError::duplicate_column(&column.name));
         }
+        column.primary_key = true;
+        column.nullable = false;
+        *name = column.name.clone();
     }
+    Ok(())
 }
@@ -1,14 +1,67 @@
 use crate::error::{ColumnContext, MiniSqlError, Result};
-use crate::parser::{DeleteStmt, InsertStmt, UpdateStmt};
-use crate::types::{QueryResult, Value};
+use crate::engines::granite::TxnId;
+use crate::parser::{DeleteStmt, Expr, InsertStmt, UpdateStmt};
+use crate::types::{QueryResult, Row, TableSchema, Value};
 use crate::executor::{Executor, Session};
 use crate::executor::evaluator;
+use crate::executor::ordering;
 use crate::executor::schema;
+use crate::executor::subquery::simple_row_columns;
 use std::time::Instant;
 
+/// Arrange `values`, given for `columns`, in schema order; unlisted columns
+/// take their defaults
+pub(crate) fn values_in_schema_order(table_schema: &TableSchema, columns: &[String], values: Vec<Value>) -> Result<Vec<Value>> {
+    let mut provided: Vec<Option<Value>> = vec![None; table_schema.columns.len()];
+    for (col_name, value) in columns.iter().zip(values) {
+        let idx = table_schema.find_column(col_name).ok_or_else(|| {
+            MiniSqlError::unknown_column(col_name, ColumnContext::InsertList)
+        })?;
+        if provided[idx].is_some() {
+            return Err(MiniSqlError::column_specified_twice(col_name));
+        }
+        provided[idx] = Some(value);
+    }
+    provided
+        .into_iter()
+        .zip(&table_schema.columns)
+        .map(|(value, col)| match (value, &col.default) {
+            (Some(value), _) => Ok(value),
+            (None, Some(default)) => Ok(default.clone()),
+            (None, None) if col.nullable || col.auto_increment => Ok(Value::Null),
+            (None, None) => Err(MiniSqlError::field_no_default(&col.name)),
+        })
+        .collect()
+}
+
 impl Executor {
     /// Execute INSERT
+    ///
+    /// Under auto-commit, a multi-row INSERT into a transactional table runs in
+    /// an implicit transaction so the batch is applied or rolled back as a whole.
     pub(crate) fn execute_insert(&self, insert: InsertStmt, session: &mut Session) -> Result<QueryResult> {
+        if session.txn_id.is_some()
+            || insert.values.len() < 2
+            || !self.get_engine(&insert.table_name)?.supports_transactions()
+        {
+            return self.insert_rows(insert, session);
+        }
+
+        session.txn_id = Some(self.txn_manager.begin()?);
+        match self.insert_rows(insert, session) {
+            Ok(result) => {
+                self.execute_commit(session)?;
+                Ok(result)
+            }
+            Err(e) => {
+                self.execute_rollback(session)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Insert every value list of `insert` under the session's transaction
+    fn insert_rows(&self, insert: InsertStmt, session: &mut Session) -> Result<QueryResult> {
         let table_name = &insert.table_name;
         let table_schema = self.storage.get_schema(table_name)?;
         let txn_id = self.get_txn_id(session);
@@ -17,57 +70,50 @@ impl Executor {
         
         let engine = self.get_engine(table_name)?;
         let auto_inc_col_idx = table_schema.auto_increment_column();
+        let pk_columns = table_schema.primary_key_columns();
 
-        for value_list in insert.values {
+        // Every row must match the column list before any of them is inserted
+        let expected_count = insert.columns.as_ref().map_or(table_schema.columns.len(), |columns| columns.len());
+        if let Some(value_list) = insert.values.iter().find(|v| v.len() != expected_count) {
+            return Err(MiniSqlError::column_count_mismatch(expected_count, value_list.len()));
+        }
+
+        for (row, value_list) in insert.values.into_iter().enumerate() {
+            session.current_row = row as u64 + 1;
             // Resolve column order
-            let mut values = if let Some(ref columns) = insert.columns {
-                // Map provided columns to schema order
-                let mut ordered_values = vec![Value::Null; table_schema.columns.len()];
-                
-                for (i, col_name) in columns.iter().enumerate() {
-                    let idx = table_schema.find_column(col_name).ok_or_else(|| {
-                        MiniSqlError::unknown_column(col_name, ColumnContext::InsertList)
-                    })?;
-                    if i < value_list.len() {
-                        ordered_values[idx] = evaluator::eval_const_expr(&value_list[i], session.last_insert_id)?;
-                    }
-                }
-                ordered_values
-            } else {
-                // Values in schema order
-                if value_list.len() != table_schema.columns.len() {
-                    return Err(MiniSqlError::column_count_mismatch(
-                        table_schema.columns.len(),
-                        value_list.len()
-                    ));
-                }
-                value_list
-                    .iter()
-                    .map(|e| evaluator::eval_const_expr(e, session.last_insert_id))
-                    .collect::<Result<Vec<_>>>()?
+            let values = value_list
+                .iter()
+                .map(|e| evaluator::eval_const_expr(e, session.last_insert_id))
+                .collect::<Result<Vec<_>>>()?;
+            let mut values = match insert.columns {
+                Some(ref columns) => values_in_schema_order(&table_schema, columns, values)?,
+                None => values,
             };
 
-            // Handle AUTO_INCREMENT
-            if let Some(col_idx) = auto_inc_col_idx {
-                let current_value = &values[col_idx];
-                
-                if current_value.is_null() || matches!(current_value, Value::Integer(0)) {
-                    // Generate auto-increment value
-                    let auto_val = self.storage.next_auto_increment(table_name)?;
-                    values[col_idx] = Value::Integer(auto_val);
-
-                    // MySQL protocol: last_insert_id is the FIRST generated ID in a multi-row insert
-                    if last_insert_id == 0 {
-                        last_insert_id = auto_val as u64;
-                    }
-                } else if let Value::Integer(explicit_val) = current_value {
-                    // Explicit value provided - update counter if needed
-                    self.storage.update_auto_increment_if_needed(table_name, *explicit_val)?;
+            // Handle AUTO_INCREMENT; last_insert_id is the FIRST generated ID in a multi-row insert
+            if let Some(auto_val) = self.assign_auto_increment(&table_schema, &mut values)? {
+                if last_insert_id == 0 {
+                    last_insert_id = auto_val;
                 }
             }
 
             // Validate and coerce types
-            let coerced_values = schema::coerce_row_types(&values, &table_schema)?;
+            let coerced_values = schema::coerce_row_types(&values, &table_schema, session)?;
+
+            // ON DUPLICATE KEY UPDATE: a row with the same primary key is updated instead.
+            // MySQL counts 2 affected rows for an update and 0 when nothing changed.
+            if !insert.on_duplicate_key_update.is_empty() {
+                if let Some(existing) = engine.find_duplicate(table_name, &pk_columns, &coerced_values)? {
+                    if self.upsert_existing_row(&insert.on_duplicate_key_update, &existing, &coerced_values, &table_schema, txn_id, session)? {
+                        rows_affected += 2;
+                    }
+                    continue;
+                }
+            }
+
+            if session.foreign_key_checks {
+                self.check_foreign_keys(&table_schema, &coerced_values, None)?;
+            }
 
             // Delegate insert to engine
             let _row_id = engine.insert(txn_id, table_name, coerced_values)?;
@@ -106,6 +152,60 @@ impl Executor {
         })
     }
 
+    /// Replace an AUTO_INCREMENT column's NULL or 0 with the next generated
+    /// id, returning it, or raise the counter past an explicit value
+    pub(crate) fn assign_auto_increment(&self, table_schema: &TableSchema, values: &mut [Value]) -> Result<Option<u64>> {
+        let Some(col_idx) = table_schema.auto_increment_column() else {
+            return Ok(None);
+        };
+        match values[col_idx] {
+            Value::Null | Value::Integer(0) => {
+                let auto_val = self.storage.next_auto_increment(&table_schema.name)?;
+                values[col_idx] = Value::Integer(auto_val);
+                Ok(Some(auto_val as u64))
+            }
+            Value::Integer(explicit_val) => {
+                self.storage.update_auto_increment_if_needed(&table_schema.name, explicit_val)?;
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Apply ON DUPLICATE KEY UPDATE assignments to `existing`, the row an insert
+    /// of `attempted` collided with. Assignments see the effect of earlier ones, as in
+    /// MySQL. Returns whether the row changed.
+    fn upsert_existing_row(
+        &self,
+        assignments: &[(String, Expr)],
+        existing: &Row,
+        attempted: &[Value],
+        table_schema: &TableSchema,
+        txn_id: TxnId,
+        session: &Session,
+    ) -> Result<bool> {
+        let mut current = existing.clone();
+        for (col_name, value_expr) in assignments {
+            let idx = table_schema.find_column(col_name).ok_or_else(|| {
+                MiniSqlError::unknown_column(col_name, ColumnContext::UpdateClause)
+            })?;
+            let value_expr = evaluator::substitute_insert_values(value_expr, table_schema, attempted)?;
+            current.values[idx] = evaluator::eval_expr(&value_expr, &current, table_schema, session.last_insert_id)?;
+        }
+        let new_values = schema::coerce_row_types(&current.values, table_schema, session)?;
+        if new_values == existing.values {
+            return Ok(false);
+        }
+
+        if session.foreign_key_checks {
+            let referencing = self.referencing_foreign_keys(&table_schema.name)?;
+            self.check_foreign_keys(table_schema, &new_values, Some(&existing.values))?;
+            self.check_referenced_key_update(table_schema, &referencing, &existing.values, &new_values)?;
+        }
+        let engine = self.get_engine(&table_schema.name)?;
+        engine.update(txn_id, &table_schema.name, existing.id, &existing.values, new_values)
+    }
+
     /// Execute UPDATE
     pub(crate) fn execute_update(&self, update: UpdateStmt, session: &mut Session) -> Result<QueryResult> {
         let table_name = &update.table_name;
@@ -116,32 +216,60 @@ impl Executor {
         let scan_t0 = if profiling { Some(Instant::now()) } else { None };
         
         let engine = self.get_engine(table_name)?;
-        let rows = engine.scan(table_name)?;
+        let where_clause = update.where_clause.as_ref();
+        let mut rows = self.read_locked_rows(&table_schema, table_name, where_clause, session, || {
+            self.candidate_rows(&table_schema, table_name, where_clause, session)
+        })?;
+        if !update.order_by.is_empty() {
+            rows = ordering::sort_table_rows(&update.order_by, rows, &table_schema, table_name, session.last_insert_id)?;
+        }
         
         let scan_elapsed = scan_t0.map(|t| t.elapsed());
+        let referencing = self.referencing_foreign_keys(table_name)?;
         let txn_id = self.get_txn_id(session);
         let mut rows_affected = 0;
+        // LIMIT counts matched rows, including those the SET leaves unchanged
+        let limit = update.limit.unwrap_or(u64::MAX);
+        let mut rows_matched = 0;
 
         // Track per-row update time
         let mut per_row_total = std::time::Duration::default();
 
         for row in rows {
+            if rows_matched >= limit {
+                break;
+            }
             let row_t0 = if profiling { Some(Instant::now()) } else { None };
             // Apply WHERE clause
             if let Some(ref where_expr) = update.where_clause {
-                let matches = evaluator::eval_where(where_expr, &row, &table_schema, session.last_insert_id)?;
+                let outer = simple_row_columns(&row, &table_schema, table_name);
+                let where_expr = self.bind_scalar_subqueries(where_expr, &outer, session)?;
+                let matches = evaluator::eval_where(&where_expr, &row, &table_schema, session.last_insert_id)?;
                 if !matches {
                     continue;
                 }
             }
 
-            // Apply updates
-            let mut new_values = row.values.clone();
+            rows_matched += 1;
+            session.current_row = rows_matched;
+
+            // Apply updates left to right, as MySQL does: each assignment sees
+            // the values given by the assignments before it
+            let mut current = row.clone();
             for (col_name, value_expr) in &update.assignments {
                 let idx = table_schema.find_column(col_name).ok_or_else(|| {
                     MiniSqlError::unknown_column(col_name, ColumnContext::UpdateClause)
                 })?;
-                new_values[idx] = evaluator::eval_expr(value_expr, &row, &table_schema, session.last_insert_id)?;
+                let value = evaluator::eval_expr_simple(
+                    value_expr, &current, &table_schema, table_name, session.last_insert_id,
+                )?;
+                current.values[idx] = schema::fit_to_length(value, &table_schema.columns[idx], session)?;
+            }
+            let new_values = current.values;
+
+            if session.foreign_key_checks {
+                self.check_foreign_keys(&table_schema, &new_values, Some(&row.values))?;
+                self.check_referenced_key_update(&table_schema, &referencing, &row.values, &new_values)?;
             }
 
             // Delegate update to engine
@@ -186,17 +314,28 @@ impl Executor {
         let table_schema = self.storage.get_schema(table_name)?;
         
         let engine = self.get_engine(table_name)?;
-        let rows = engine.scan(table_name)?;
+        let where_clause = delete.where_clause.as_ref();
+        let mut rows = self.read_locked_rows(&table_schema, table_name, where_clause, session, || {
+            self.candidate_rows(&table_schema, table_name, where_clause, session)
+        })?;
+        if !delete.order_by.is_empty() {
+            rows = ordering::sort_table_rows(&delete.order_by, rows, &table_schema, table_name, session.last_insert_id)?;
+        }
         
         let txn_id = self.get_txn_id(session);
-        let mut rows_affected = 0;
 
         // Collect rows to delete first (to avoid modifying while iterating)
+        let limit = delete.limit.unwrap_or(u64::MAX);
         let mut to_delete = Vec::new();
         for row in rows {
+            if to_delete.len() as u64 >= limit {
+                break;
+            }
             // Apply WHERE clause
             if let Some(ref where_expr) = delete.where_clause {
-                let matches = evaluator::eval_where(where_expr, &row, &table_schema, session.last_insert_id)?;
+                let outer = simple_row_columns(&row, &table_schema, table_name);
+                let where_expr = self.bind_scalar_subqueries(where_expr, &outer, session)?;
+                let matches = evaluator::eval_where(&where_expr, &row, &table_schema, session.last_insert_id)?;
                 if !matches {
                     continue;
                 }
@@ -204,12 +343,8 @@ impl Executor {
             to_delete.push(row);
         }
 
-        // Delete rows using engine
-        for row in to_delete {
-             if engine.delete(txn_id, table_name, row.id, &row.values)? {
-                 rows_affected += 1;
-             }
-        }
+        // Delete rows using engine, following foreign key actions
+        let rows_affected = self.delete_rows(table_name, to_delete, txn_id, session)?;
 
         // Track which engine was modified (for transaction commit routing)
         if session.txn_id.is_some() && rows_affected > 0 {
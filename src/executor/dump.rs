@@ -0,0 +1,294 @@
+//! SQL dump and import
+//!
+//! [`Executor::dump`] writes the whole database as a `mysqldump`-style script:
+//! for every table its `CREATE TABLE` statement (the same text `SHOW CREATE
+//! TABLE` returns), batched `INSERT` statements for its rows in primary key
+//! order, then its secondary indexes. Tables come in dependency order,
+//! parents before the tables whose foreign keys reference them.
+//! [`Executor::import`] executes such a script, so a dump loaded into an
+//! empty data directory reproduces the schema and data.
+
+use std::io::Write;
+
+use crate::error::Result;
+use crate::executor::{Executor, Session};
+use crate::parser::Parser;
+use crate::types::{hex_encode, DataType, QueryResult, ResultSet, TableSchema, Value};
+
+/// Rows per INSERT statement in a dump
+const INSERT_BATCH_SIZE: usize = 100;
+
+/// Quote an identifier with backticks
+fn quote_ident(name: &str) -> String {
+    format!("`{}`", name)
+}
+
+/// Render a value as a SQL literal that parses back to the same value
+pub(crate) fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) if !f.is_finite() => "NULL".to_string(),
+        Value::Float(f) => {
+            let text = f.to_string();
+            if text.contains('.') { text } else { format!("{}.0", text) }
+        }
+        Value::Boolean(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        Value::String(s) => quote_string(s),
+        Value::Json(j) => quote_string(&j.to_string()),
+        Value::Date(d) => format!("DATE '{}'", d),
+        Value::Bytes(b) => format!("UNHEX('{}')", hex_encode(b)),
+    }
+}
+
+/// Quote a string literal, escaping quotes and backslashes
+fn quote_string(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+/// Build the CREATE TABLE statement for a schema
+pub fn create_table_sql(schema: &TableSchema) -> String {
+    // A key over several columns is a table-level clause
+    let pk_columns = schema.primary_key_columns();
+    let composite_key = pk_columns.len() > 1;
+    let mut columns: Vec<String> = schema.columns.iter().map(|col| {
+        let mut def = format!("  {} {}", quote_ident(&col.name), col.data_type);
+        if !col.nullable && (composite_key || !col.primary_key) {
+            def.push_str(" NOT NULL");
+        }
+        if col.primary_key && !composite_key {
+            def.push_str(" PRIMARY KEY");
+        }
+        if col.auto_increment {
+            def.push_str(" AUTO_INCREMENT");
+        }
+        if let Some(default) = &col.default {
+            def.push_str(&format!(" DEFAULT {}", sql_literal(default)));
+        }
+        def
+    }).collect();
+    if composite_key {
+        let names: Vec<String> = pk_columns.iter().map(|&i| quote_ident(&schema.columns[i].name)).collect();
+        columns.push(format!("  PRIMARY KEY ({})", names.join(", ")));
+    }
+    columns.extend(schema.foreign_keys.iter().map(|fk| format!("  {}", fk.constraint_sql())));
+
+    let mut sql = format!(
+        "CREATE TABLE {} (\n{}\n) ENGINE={} STORAGE FORMAT={}",
+        quote_ident(&schema.name),
+        columns.join(",\n"),
+        schema.engine_type,
+        schema.storage_format,
+    );
+    if schema.auto_increment_column().is_some() && schema.auto_increment_counter > 1 {
+        sql.push_str(&format!(" AUTO_INCREMENT={}", schema.auto_increment_counter));
+    }
+    sql
+}
+
+/// Order tables so every table follows the tables its foreign keys reference.
+/// Ties (and any reference cycle) fall back to name order.
+fn dependency_order(mut schemas: Vec<TableSchema>) -> Vec<TableSchema> {
+    schemas.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut ordered: Vec<TableSchema> = Vec::with_capacity(schemas.len());
+    while !schemas.is_empty() {
+        let ready = schemas.iter().position(|schema| {
+            schema.foreign_keys.iter().all(|fk| {
+                fk.ref_table == schema.name || ordered.iter().any(|t| t.name == fk.ref_table)
+            })
+        }).unwrap_or(0);
+        ordered.push(schemas.remove(ready));
+    }
+    ordered
+}
+
+/// Split a SQL script into statements on `;`, ignoring semicolons inside
+/// quotes and `/* */` comments and skipping `-- ` and `#` line comments
+pub fn split_statements(script: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = script.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            current.push(c);
+            if c == '\\' && q != '`' {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+
+        match c {
+            '\'' | '"' | '`' => {
+                quote = Some(c);
+                current.push(c);
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for skipped in chars.by_ref() {
+                    if prev == '*' && skipped == '/' {
+                        break;
+                    }
+                    prev = skipped;
+                }
+                current.push(' ');
+            }
+            '-' if chars.peek() == Some(&'-') && {
+                let mut ahead = chars.clone();
+                ahead.next();
+                ahead.peek().is_none_or(|c| c.is_whitespace() || c.is_control())
+            } =>
+            {
+                for skipped in chars.by_ref() {
+                    if skipped == '\n' {
+                        break;
+                    }
+                }
+                current.push('\n');
+            }
+            '#' => {
+                for skipped in chars.by_ref() {
+                    if skipped == '\n' {
+                        break;
+                    }
+                }
+                current.push('\n');
+            }
+            ';' => {
+                let stmt = current.trim();
+                if !stmt.is_empty() {
+                    statements.push(stmt.to_string());
+                }
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+
+    let stmt = current.trim();
+    if !stmt.is_empty() {
+        statements.push(stmt.to_string());
+    }
+    statements
+}
+
+impl Executor {
+    /// Execute SHOW CREATE TABLE
+    pub(crate) fn execute_show_create_table(&self, table_name: &str) -> Result<QueryResult> {
+        let schema = self.storage.get_schema(table_name)?;
+        let mut result = ResultSet::new(
+            vec!["Table".to_string(), "Create Table".to_string()],
+            vec![DataType::Text, DataType::Text],
+        );
+        result.add_row(vec![
+            Value::String(schema.name.clone()),
+            Value::String(create_table_sql(&schema)),
+        ]);
+        Ok(QueryResult::Select(result))
+    }
+
+    /// Write a SQL dump of every table (schema, rows and indexes) to `out`.
+    ///
+    /// Tables are written parents first, each followed by its rows and then
+    /// its secondary indexes, so the script can be replayed top to bottom.
+    pub fn dump<W: Write>(&self, mut out: W) -> Result<()> {
+        let schemas = self.storage.list_tables().iter()
+            .map(|name| self.storage.get_schema(name))
+            .collect::<Result<Vec<_>>>()?;
+        let indexes = self.storage.list_indexes();
+
+        writeln!(out, "-- MiniSQL dump")?;
+        for schema in dependency_order(schemas) {
+            let table_name = &schema.name;
+            writeln!(out)?;
+            writeln!(out, "-- Table {}", table_name)?;
+            writeln!(out, "{};", create_table_sql(&schema))?;
+
+            // Primary key order, so the dump does not depend on the engine's scan order
+            let mut rows = self.scan_table(table_name)?;
+            let pk_columns = schema.primary_key_columns();
+            if !pk_columns.is_empty() {
+                rows.sort_by(|a, b| {
+                    pk_columns.iter()
+                        .map(|&i| a.values[i].partial_cmp(&b.values[i]).unwrap_or(std::cmp::Ordering::Equal))
+                        .find(|ord| ord.is_ne())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+            }
+            for batch in rows.chunks(INSERT_BATCH_SIZE) {
+                let values: Vec<String> = batch.iter().map(|row| {
+                    let literals: Vec<String> = row.values.iter().map(sql_literal).collect();
+                    format!("({})", literals.join(", "))
+                }).collect();
+                writeln!(out, "INSERT INTO {} VALUES\n{};", quote_ident(table_name), values.join(",\n"))?;
+            }
+
+            let mut table_indexes: Vec<_> = indexes.iter()
+                .filter(|idx| idx.table_name == *table_name && !idx.is_primary)
+                .collect();
+            table_indexes.sort_by(|a, b| a.name.cmp(&b.name));
+            for index in table_indexes {
+                let columns: Vec<String> = index.columns.iter().map(|c| quote_ident(c)).collect();
+                writeln!(
+                    out,
+                    "CREATE INDEX {} ON {} ({});",
+                    quote_ident(&index.name),
+                    quote_ident(table_name),
+                    columns.join(", ")
+                )?;
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Execute every statement of a SQL script, such as one produced by
+    /// [`Executor::dump`]. Stops at the first failing statement.
+    ///
+    /// Foreign key checks are off while the script runs, as with mysqldump
+    /// output, so rows of self-referencing tables load in any order.
+    /// Returns the number of statements executed.
+    pub fn import(&self, script: &str, session: &mut Session) -> Result<usize> {
+        let statements = split_statements(script);
+        let foreign_key_checks = std::mem::replace(&mut session.foreign_key_checks, false);
+        let result = statements.iter().try_for_each(|sql| {
+            let stmt = Parser::parse(sql)?;
+            self.execute(stmt, session).map(|_| ())
+        });
+        session.foreign_key_checks = foreign_key_checks;
+        result.map(|_| statements.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_statements() {
+        let script = "-- header; not a statement\nCREATE TABLE t (a TEXT);\n\
+            INSERT INTO t VALUES ('a;b'), ('it''s'), ('back\\\\'), ('x -- y');\n# done\n\
+            /* one; two */ SELECT 3--2;\n/* trailing */";
+        let statements = split_statements(script);
+        assert_eq!(statements, vec![
+            "CREATE TABLE t (a TEXT)".to_string(),
+            "INSERT INTO t VALUES ('a;b'), ('it''s'), ('back\\\\'), ('x -- y')".to_string(),
+            "SELECT 3--2".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_sql_literal() {
+        assert_eq!(sql_literal(&Value::Float(2.0)), "2.0");
+        assert_eq!(sql_literal(&Value::Float(-0.25)), "-0.25");
+        assert_eq!(sql_literal(&Value::String("it's a \\ test".into())), "'it''s a \\\\ test'");
+        assert_eq!(sql_literal(&Value::Boolean(true)), "TRUE");
+        assert_eq!(sql_literal(&Value::Null), "NULL");
+    }
+}
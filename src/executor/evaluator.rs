@@ -1,8 +1,13 @@
+use crate::datetime::{Date, IntervalUnit, Timestamp};
 use crate::error::{ColumnContext, MiniSqlError, Result};
-use crate::parser::{BinaryOperator, Expr};
-use crate::types::{DataType, Row, TableSchema, Value};
+use crate::parser::{BinaryOperator, Expr, SelectColumn, SelectStmt};
+use crate::types::{hex_decode, hex_encode, DataType, Row, TableSchema, Value};
 use crate::executor::schema::JoinTableInfo;
 use crate::join::JoinContext;
+use crate::like::LikePattern;
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::cmp::Ordering;
 
 /// Get a name for an expression (for column headers)
 pub fn expr_name(expr: &Expr) -> String {
@@ -17,9 +22,13 @@ pub fn expr_name(expr: &Expr) -> String {
                 key
             )
         }
-        Expr::FunctionCall { name, args } => {
+        Expr::Cast { expr, target } => format!("CAST({} AS {})", expr_name(expr), target),
+        Expr::UnaryMinus(inner) => format!("-{}", expr_name(inner)),
+        Expr::FunctionCall { name, args, distinct, .. } => {
             if args.is_empty() {
                 format!("{}(*)", name)
+            } else if *distinct {
+                format!("{}(DISTINCT ...)", name)
             } else {
                 format!("{}(...)", name)
             }
@@ -28,6 +37,43 @@ pub fn expr_name(expr: &Expr) -> String {
     }
 }
 
+/// Arguments of COALESCE, IFNULL, NULLIF, GREATEST or LEAST that can be the
+/// result, skipping NULL literals: NULLIF returns its first argument or NULL
+fn null_function_type_args<'a>(name: &str, args: &'a [Expr]) -> Vec<&'a Expr> {
+    let candidates = if name.eq_ignore_ascii_case("NULLIF") { &args[..args.len().min(1)] } else { args };
+    candidates.iter().filter(|a| !matches!(a, Expr::Literal(Value::Null))).collect()
+}
+
+/// Type that holds every one of `types`: their shared type, FLOAT for a mix
+/// of integers and floats, and TEXT for anything else (or no types at all)
+fn common_type(types: &[DataType]) -> DataType {
+    let Some((first, rest)) = types.split_first() else {
+        return DataType::Text;
+    };
+    rest.iter().try_fold(first.clone(), |common, t| match (&common, t) {
+        _ if common == *t => Some(common),
+        (DataType::Integer | DataType::Float, DataType::Integer | DataType::Float) => Some(DataType::Float),
+        _ => None,
+    })
+    .unwrap_or(DataType::Text)
+}
+
+/// Whether `name` (upper case) is one of the numeric functions
+fn is_math_function(name: &str) -> bool {
+    matches!(name, "ABS" | "ROUND" | "CEIL" | "CEILING" | "FLOOR" | "POWER" | "POW" | "SQRT" | "MOD")
+}
+
+/// Result type of a numeric function given its argument types: ABS, ROUND,
+/// CEIL, FLOOR and MOD keep integers integral, everything else is a float
+fn math_function_type(name: &str, arg_types: &[DataType]) -> DataType {
+    let integral = |n: usize| arg_types.len() >= n && arg_types[..n].iter().all(|t| *t == DataType::Integer);
+    match name {
+        "ABS" | "ROUND" | "CEIL" | "CEILING" | "FLOOR" if integral(1) => DataType::Integer,
+        "MOD" if integral(2) => DataType::Integer,
+        _ => DataType::Float,
+    }
+}
+
 /// Infer expression type for simple query
 pub fn infer_expr_type_simple(expr: &Expr, schema: &TableSchema) -> Result<DataType> {
     match expr {
@@ -36,6 +82,8 @@ pub fn infer_expr_type_simple(expr: &Expr, schema: &TableSchema) -> Result<DataT
         Expr::Literal(Value::String(_)) => Ok(DataType::Text),
         Expr::Literal(Value::Boolean(_)) => Ok(DataType::Boolean),
         Expr::Literal(Value::Json(_)) => Ok(DataType::Json),
+        Expr::Literal(Value::Date(_)) => Ok(DataType::Date),
+        Expr::Literal(Value::Bytes(_)) => Ok(DataType::Blob),
         Expr::Literal(Value::Null) => Ok(DataType::Text),
         Expr::Column { table: _, name } => {
             let idx = schema.find_column(name).ok_or_else(|| {
@@ -60,7 +108,7 @@ pub fn infer_expr_type_simple(expr: &Expr, schema: &TableSchema) -> Result<DataT
                 | BinaryOperator::LessThanOrEqual
                 | BinaryOperator::GreaterThan
                 | BinaryOperator::GreaterThanOrEqual
-                | BinaryOperator::Like => Ok(DataType::Boolean),
+                | BinaryOperator::Like { .. } => Ok(DataType::Boolean),
                 BinaryOperator::Plus
                 | BinaryOperator::Minus
                 | BinaryOperator::Multiply
@@ -77,8 +125,12 @@ pub fn infer_expr_type_simple(expr: &Expr, schema: &TableSchema) -> Result<DataT
                 }
             }
         }
-        Expr::Not(_) | Expr::IsNull(_) | Expr::IsNotNull(_) | Expr::In { .. } | Expr::NotIn { .. } => Ok(DataType::Boolean),
-        Expr::FunctionCall { name, args } => {
+        Expr::Not(_) | Expr::IsNull(_) | Expr::IsNotNull(_) | Expr::In { .. } | Expr::NotIn { .. } | Expr::InSubquery { .. } | Expr::Like { .. } => Ok(DataType::Boolean),
+        Expr::UnaryMinus(inner) => match infer_expr_type_simple(inner, schema)? {
+            DataType::Float => Ok(DataType::Float),
+            _ => Ok(DataType::Integer),
+        },
+        Expr::FunctionCall { name, args, .. } => {
             match name.to_uppercase().as_str() {
                 "JSON_EXTRACT" => Ok(DataType::Json),
                 "COUNT" => Ok(DataType::Integer),
@@ -97,10 +149,25 @@ pub fn infer_expr_type_simple(expr: &Expr, schema: &TableSchema) -> Result<DataT
                         infer_expr_type_simple(&args[0], schema)
                     }
                 }
+                "YEAR" | "MONTH" | "DAY" | "DAYOFMONTH" => Ok(DataType::Integer),
+                "DATE" => Ok(DataType::Date),
+                "UNHEX" => Ok(DataType::Blob),
+                "COALESCE" | "IFNULL" | "NULLIF" | "GREATEST" | "LEAST" => {
+                    let args = null_function_type_args(name, args);
+                    let arg_types: Result<Vec<DataType>> = args.iter().map(|a| infer_expr_type_simple(a, schema)).collect();
+                    Ok(common_type(&arg_types?))
+                }
+                upper if is_math_function(upper) => {
+                    let arg_types: Result<Vec<DataType>> = args.iter().map(|a| infer_expr_type_simple(a, schema)).collect();
+                    Ok(math_function_type(upper, &arg_types?))
+                }
                 _ => Ok(DataType::Text),
             }
         }
+        Expr::Cast { target, .. } => Ok(target.clone()),
         Expr::Placeholder(_) => Ok(DataType::Text), // Placeholders default to text type
+        Expr::UserVariable(_) => Ok(DataType::Text),
+        Expr::ScalarSubquery(subquery) => Ok(infer_scalar_subquery_type(subquery)),
     }
 }
 
@@ -112,6 +179,8 @@ pub fn infer_expr_type_join(expr: &Expr, tables: &JoinTableInfo) -> Result<DataT
         Expr::Literal(Value::String(_)) => Ok(DataType::Text),
         Expr::Literal(Value::Boolean(_)) => Ok(DataType::Boolean),
         Expr::Literal(Value::Json(_)) => Ok(DataType::Json),
+        Expr::Literal(Value::Date(_)) => Ok(DataType::Date),
+        Expr::Literal(Value::Bytes(_)) => Ok(DataType::Blob),
         Expr::Literal(Value::Null) => Ok(DataType::Text),
         Expr::Column { table, name } => {
             let (_, dt) = tables.find_column(table.as_deref(), name)?;
@@ -134,7 +203,7 @@ pub fn infer_expr_type_join(expr: &Expr, tables: &JoinTableInfo) -> Result<DataT
                 | BinaryOperator::LessThanOrEqual
                 | BinaryOperator::GreaterThan
                 | BinaryOperator::GreaterThanOrEqual
-                | BinaryOperator::Like => Ok(DataType::Boolean),
+                | BinaryOperator::Like { .. } => Ok(DataType::Boolean),
                 BinaryOperator::Plus
                 | BinaryOperator::Minus
                 | BinaryOperator::Multiply
@@ -151,14 +220,33 @@ pub fn infer_expr_type_join(expr: &Expr, tables: &JoinTableInfo) -> Result<DataT
                 }
             }
         }
-        Expr::Not(_) | Expr::IsNull(_) | Expr::IsNotNull(_) | Expr::In { .. } | Expr::NotIn { .. } => Ok(DataType::Boolean),
-        Expr::FunctionCall { name, .. } => {
+        Expr::Not(_) | Expr::IsNull(_) | Expr::IsNotNull(_) | Expr::In { .. } | Expr::NotIn { .. } | Expr::InSubquery { .. } | Expr::Like { .. } => Ok(DataType::Boolean),
+        Expr::UnaryMinus(inner) => match infer_expr_type_join(inner, tables)? {
+            DataType::Float => Ok(DataType::Float),
+            _ => Ok(DataType::Integer),
+        },
+        Expr::FunctionCall { name, args, .. } => {
             match name.to_uppercase().as_str() {
                 "JSON_EXTRACT" => Ok(DataType::Json),
+                "YEAR" | "MONTH" | "DAY" | "DAYOFMONTH" => Ok(DataType::Integer),
+                "DATE" => Ok(DataType::Date),
+                "UNHEX" => Ok(DataType::Blob),
+                "COALESCE" | "IFNULL" | "NULLIF" | "GREATEST" | "LEAST" => {
+                    let args = null_function_type_args(name, args);
+                    let arg_types: Result<Vec<DataType>> = args.iter().map(|a| infer_expr_type_join(a, tables)).collect();
+                    Ok(common_type(&arg_types?))
+                }
+                upper if is_math_function(upper) => {
+                    let arg_types: Result<Vec<DataType>> = args.iter().map(|a| infer_expr_type_join(a, tables)).collect();
+                    Ok(math_function_type(upper, &arg_types?))
+                }
                 _ => Ok(DataType::Text),
             }
         }
+        Expr::Cast { target, .. } => Ok(target.clone()),
         Expr::Placeholder(_) => Ok(DataType::Text), // Placeholders default to text type
+        Expr::UserVariable(_) => Ok(DataType::Text),
+        Expr::ScalarSubquery(subquery) => Ok(infer_scalar_subquery_type(subquery)),
     }
 }
 
@@ -182,13 +270,15 @@ pub fn eval_const_expr(expr: &Expr, last_insert_id: u64) -> Result<Value> {
                 _ => Err(MiniSqlError::Type("NOT requires boolean".into())),
             }
         }
-        Expr::FunctionCall { name, args } => {
+        Expr::UnaryMinus(inner) => negate(&eval_const_expr(inner, last_insert_id)?),
+        Expr::FunctionCall { name, args, .. } => {
             let mut arg_values = Vec::new();
             for arg in args {
                 arg_values.push(eval_const_expr(arg, last_insert_id)?);
             }
             eval_function_values(name, &arg_values, last_insert_id)
         }
+        Expr::Cast { expr, target } => cast_value(eval_const_expr(expr, last_insert_id)?, target),
         _ => Err(MiniSqlError::Syntax(
             "Expression requires row context".into(),
         )),
@@ -221,6 +311,7 @@ pub fn eval_expr(expr: &Expr, row: &Row, schema: &TableSchema, last_insert_id: u
                 _ => Err(MiniSqlError::Type("NOT requires boolean".into())),
             }
         }
+        Expr::UnaryMinus(inner) => negate(&eval_expr(inner, row, schema, last_insert_id)?),
         Expr::IsNull(inner) => {
             let val = eval_expr(inner, row, schema, last_insert_id)?;
             Ok(Value::Boolean(val.is_null()))
@@ -229,6 +320,7 @@ pub fn eval_expr(expr: &Expr, row: &Row, schema: &TableSchema, last_insert_id: u
             let val = eval_expr(inner, row, schema, last_insert_id)?;
             Ok(Value::Boolean(!val.is_null()))
         }
+        Expr::Like { expr, pattern } => match_like(&eval_expr(expr, row, schema, last_insert_id)?, pattern),
         Expr::JsonAccess { expr, key, as_text } => {
             let val = eval_expr(expr, row, schema, last_insert_id)?;
             if *as_text {
@@ -290,12 +382,15 @@ pub fn eval_expr(expr: &Expr, row: &Row, schema: &TableSchema, last_insert_id: u
             
             Ok(Value::Boolean(true))
         }
-        Expr::FunctionCall { name, args } => {
+        Expr::FunctionCall { name, args, .. } => {
             eval_function(name, args, row, schema, last_insert_id)
         }
+        Expr::Cast { expr, target } => cast_value(eval_expr(expr, row, schema, last_insert_id)?, target),
         Expr::Placeholder(_) => Err(MiniSqlError::Syntax(
             "Unsubstituted placeholder in expression".into(),
         )),
+        Expr::UserVariable(_) => Err(unbound_user_variable()),
+        Expr::InSubquery { .. } | Expr::ScalarSubquery(_) => Err(unresolved_subquery()),
     }
 }
 
@@ -339,6 +434,7 @@ pub fn eval_expr_simple(expr: &Expr, row: &Row, schema: &TableSchema, table_alia
                 _ => Err(MiniSqlError::Type("NOT requires boolean".into())),
             }
         }
+        Expr::UnaryMinus(inner) => negate(&eval_expr_simple(inner, row, schema, table_alias, last_insert_id)?),
         Expr::IsNull(inner) => {
             let val = eval_expr_simple(inner, row, schema, table_alias, last_insert_id)?;
             Ok(Value::Boolean(val.is_null()))
@@ -347,6 +443,7 @@ pub fn eval_expr_simple(expr: &Expr, row: &Row, schema: &TableSchema, table_alia
             let val = eval_expr_simple(inner, row, schema, table_alias, last_insert_id)?;
             Ok(Value::Boolean(!val.is_null()))
         }
+        Expr::Like { expr, pattern } => match_like(&eval_expr_simple(expr, row, schema, table_alias, last_insert_id)?, pattern),
         Expr::JsonAccess { expr, key, as_text } => {
             let val = eval_expr_simple(expr, row, schema, table_alias, last_insert_id)?;
             if *as_text {
@@ -393,12 +490,17 @@ pub fn eval_expr_simple(expr: &Expr, row: &Row, schema: &TableSchema, table_alia
             }
             Ok(Value::Boolean(true))
         }
-        Expr::FunctionCall { name, args } => {
+        Expr::FunctionCall { name, args, .. } => {
             eval_function_simple(name, args, row, schema, table_alias, last_insert_id)
         }
+        Expr::Cast { expr, target } => {
+            cast_value(eval_expr_simple(expr, row, schema, table_alias, last_insert_id)?, target)
+        }
         Expr::Placeholder(_) => Err(MiniSqlError::Syntax(
             "Unsubstituted placeholder in expression".into(),
         )),
+        Expr::UserVariable(_) => Err(unbound_user_variable()),
+        Expr::InSubquery { .. } | Expr::ScalarSubquery(_) => Err(unresolved_subquery()),
     }
 }
 
@@ -433,6 +535,7 @@ pub fn eval_expr_join(expr: &Expr, row: &Row, tables: &JoinTableInfo, last_inser
                 _ => Err(MiniSqlError::Type("NOT requires boolean".into())),
             }
         }
+        Expr::UnaryMinus(inner) => negate(&eval_expr_join(inner, row, tables, last_insert_id)?),
         Expr::IsNull(inner) => {
             let val = eval_expr_join(inner, row, tables, last_insert_id)?;
             Ok(Value::Boolean(val.is_null()))
@@ -441,6 +544,7 @@ pub fn eval_expr_join(expr: &Expr, row: &Row, tables: &JoinTableInfo, last_inser
             let val = eval_expr_join(inner, row, tables, last_insert_id)?;
             Ok(Value::Boolean(!val.is_null()))
         }
+        Expr::Like { expr, pattern } => match_like(&eval_expr_join(expr, row, tables, last_insert_id)?, pattern),
         Expr::JsonAccess { expr, key, as_text } => {
             let val = eval_expr_join(expr, row, tables, last_insert_id)?;
             if *as_text {
@@ -487,16 +591,19 @@ pub fn eval_expr_join(expr: &Expr, row: &Row, tables: &JoinTableInfo, last_inser
             }
             Ok(Value::Boolean(true))
         }
-        Expr::FunctionCall { name, args } => {
+        Expr::FunctionCall { name, args, .. } => {
             let arg_values: Result<Vec<Value>> = args
                 .iter()
                 .map(|a| eval_expr_join(a, row, tables, last_insert_id))
                 .collect();
             eval_function_values(name, &arg_values?, last_insert_id)
         }
+        Expr::Cast { expr, target } => cast_value(eval_expr_join(expr, row, tables, last_insert_id)?, target),
         Expr::Placeholder(_) => Err(MiniSqlError::Syntax(
             "Unsubstituted placeholder in expression".into(),
         )),
+        Expr::UserVariable(_) => Err(unbound_user_variable()),
+        Expr::InSubquery { .. } | Expr::ScalarSubquery(_) => Err(unresolved_subquery()),
     }
 }
 
@@ -531,6 +638,7 @@ pub fn eval_expr_with_join_context(expr: &Expr, values: &[Value], ctx: &JoinCont
                 _ => Err(MiniSqlError::Type("NOT requires boolean".into())),
             }
         }
+        Expr::UnaryMinus(inner) => negate(&eval_expr_with_join_context(inner, values, ctx, last_insert_id)?),
         Expr::IsNull(inner) => {
             let val = eval_expr_with_join_context(inner, values, ctx, last_insert_id)?;
             Ok(Value::Boolean(val.is_null()))
@@ -539,6 +647,7 @@ pub fn eval_expr_with_join_context(expr: &Expr, values: &[Value], ctx: &JoinCont
             let val = eval_expr_with_join_context(inner, values, ctx, last_insert_id)?;
             Ok(Value::Boolean(!val.is_null()))
         }
+        Expr::Like { expr, pattern } => match_like(&eval_expr_with_join_context(expr, values, ctx, last_insert_id)?, pattern),
         Expr::JsonAccess { expr, key, as_text } => {
             let val = eval_expr_with_join_context(expr, values, ctx, last_insert_id)?;
             if *as_text {
@@ -585,19 +694,116 @@ pub fn eval_expr_with_join_context(expr: &Expr, values: &[Value], ctx: &JoinCont
             }
             Ok(Value::Boolean(true))
         }
-        Expr::FunctionCall { name, args } => {
+        Expr::FunctionCall { name, args, .. } => {
             let arg_values: Result<Vec<Value>> = args
                 .iter()
                 .map(|a| eval_expr_with_join_context(a, values, ctx, last_insert_id))
                 .collect();
             eval_function_values(name, &arg_values?, last_insert_id)
         }
+        Expr::Cast { expr, target } => {
+            cast_value(eval_expr_with_join_context(expr, values, ctx, last_insert_id)?, target)
+        }
         Expr::Placeholder(_) => Err(MiniSqlError::Syntax(
             "Unsubstituted placeholder in expression".into(),
         )),
+        Expr::UserVariable(_) => Err(unbound_user_variable()),
+        Expr::InSubquery { .. } | Expr::ScalarSubquery(_) => Err(unresolved_subquery()),
+    }
+}
+
+/// Convert a value for `CAST(expr AS target)`.
+///
+/// Follows MySQL's lenient rules rather than failing: strings convert by their
+/// leading numeric prefix (`'12abc'` is 12, `'abc'` is 0), floats truncate
+/// toward zero when cast to INTEGER, and anything casts to text through its
+/// display form (`CHAR(n)` keeps the first `n` characters). NULL stays NULL.
+/// The only error is casting text that is not valid JSON to JSON.
+pub fn cast_value(value: Value, target: &DataType) -> Result<Value> {
+    if value.is_null() {
+        return Ok(Value::Null);
+    }
+    match target {
+        DataType::Integer => Ok(Value::Integer(match value {
+            Value::Integer(i) => i,
+            Value::Float(f) => f as i64,
+            Value::Boolean(b) => b as i64,
+            Value::String(s) => s.trim().parse().unwrap_or_else(|_| leading_number(&s) as i64),
+            other => leading_number(&other.to_string()) as i64,
+        })),
+        DataType::Float => Ok(Value::Float(match value {
+            Value::Integer(i) => i as f64,
+            Value::Float(f) => f,
+            Value::Boolean(b) => if b { 1.0 } else { 0.0 },
+            other => leading_number(&other.to_string()),
+        })),
+        DataType::Boolean => Ok(Value::Boolean(match value {
+            Value::Boolean(b) => b,
+            Value::Integer(i) => i != 0,
+            Value::Float(f) => f != 0.0,
+            other => leading_number(&other.to_string()) != 0.0,
+        })),
+        DataType::Text | DataType::Varchar(None) => Ok(Value::String(value.to_string())),
+        DataType::Varchar(Some(len)) => {
+            Ok(Value::String(value.to_string().chars().take(*len as usize).collect()))
+        }
+        DataType::Date => Ok(match value {
+            Value::Date(_) => value,
+            other => Date::parse(&other.to_string()).map(Value::Date).unwrap_or(Value::Null),
+        }),
+        DataType::Blob => Ok(match value {
+            Value::Bytes(_) => value,
+            Value::String(s) => Value::Bytes(s.into_bytes()),
+            other => Value::Bytes(other.to_string().into_bytes()),
+        }),
+        DataType::Json => match value {
+            Value::Json(_) => Ok(value),
+            Value::String(s) => serde_json::from_str(&s).map(Value::Json).map_err(|_| {
+                MiniSqlError::Type(format!("Invalid JSON text in argument 1 to function cast_as_json: '{}'", s))
+            }),
+            Value::Integer(i) => Ok(Value::Json(i.into())),
+            Value::Float(f) => Ok(Value::Json(f.into())),
+            Value::Boolean(b) => Ok(Value::Json(b.into())),
+            Value::Date(d) => Ok(Value::Json(d.to_string().into())),
+            Value::Bytes(b) => cast_value(Value::String(String::from_utf8_lossy(&b).into_owned()), target),
+            Value::Null => Ok(Value::Null),
+        },
     }
 }
 
+/// Numeric value of the longest numeric prefix of `s` (after leading
+/// whitespace), or 0 when it does not start with a number
+fn leading_number(s: &str) -> f64 {
+    let s = s.trim_start();
+    let bytes = s.as_bytes();
+    let mut end = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+    let (mut seen_digit, mut seen_dot) = (false, false);
+    while let Some(&b) = bytes.get(end) {
+        match b {
+            b'0'..=b'9' => seen_digit = true,
+            b'.' if !seen_dot => seen_dot = true,
+            _ => break,
+        }
+        end += 1;
+    }
+    if !seen_digit {
+        return 0.0;
+    }
+    // Optional exponent, only taken when digits follow it
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let mut exp_end = end + 1;
+        if matches!(bytes.get(exp_end), Some(b'+' | b'-')) {
+            exp_end += 1;
+        }
+        if bytes.get(exp_end).is_some_and(u8::is_ascii_digit) {
+            while bytes.get(exp_end).is_some_and(u8::is_ascii_digit) {
+                exp_end += 1;
+            }
+            end = exp_end;
+        }
+    }
+    s[..end].parse().unwrap_or(0.0)
+}
 
 /// Apply a binary operator
 pub fn apply_binary_op(left: &Value, op: BinaryOperator, right: &Value) -> Result<Value> {
@@ -622,6 +828,18 @@ pub fn apply_binary_op(left: &Value, op: BinaryOperator, right: &Value) -> Resul
         }
     }
 
+    // MySQL compares a number with a string numerically
+    let (left, right) = match op {
+        BinaryOperator::Equal
+        | BinaryOperator::NotEqual
+        | BinaryOperator::LessThan
+        | BinaryOperator::LessThanOrEqual
+        | BinaryOperator::GreaterThan
+        | BinaryOperator::GreaterThanOrEqual => numeric_comparison_operands(left, right),
+        _ => (Cow::Borrowed(left), Cow::Borrowed(right)),
+    };
+    let (left, right) = (left.as_ref(), right.as_ref());
+
     match op {
         BinaryOperator::Equal => Ok(Value::Boolean(left == right)),
         BinaryOperator::NotEqual => Ok(Value::Boolean(left != right)),
@@ -666,22 +884,68 @@ pub fn apply_binary_op(left: &Value, op: BinaryOperator, right: &Value) -> Resul
                 a / b
             }
         }),
-        BinaryOperator::Like => {
-            match (left, right) {
-                (Value::String(s), Value::String(pattern)) => {
-                    Ok(Value::Boolean(match_like(s, pattern)))
-                }
-                _ => Err(MiniSqlError::Type("LIKE requires string operands".into())),
-            }
-        }
+        BinaryOperator::Like { escape } => match right {
+            Value::String(pattern) => match_like(left, &LikePattern::new(pattern, escape)),
+            _ => Err(MiniSqlError::Type("LIKE requires string operands".into())),
+        },
+    }
+}
+
+/// `value LIKE pattern`; NULL when the value is NULL
+fn match_like(value: &Value, pattern: &LikePattern) -> Result<Value> {
+    match value {
+        Value::Null => Ok(Value::Null),
+        Value::String(s) => Ok(Value::Boolean(pattern.matches(s))),
+        _ => Err(MiniSqlError::Type("LIKE requires string operands".into())),
     }
 }
 
-/// Apply arithmetic operation
+/// When one comparison operand is a number and the other a string, convert
+/// the string to a number (see [`string_to_number`]); otherwise leave both as is
+fn numeric_comparison_operands<'a>(left: &'a Value, right: &'a Value) -> (Cow<'a, Value>, Cow<'a, Value>) {
+    match (left, right) {
+        (Value::String(s), Value::Integer(_) | Value::Float(_)) => (Cow::Owned(string_to_number(s)), Cow::Borrowed(right)),
+        (Value::Integer(_) | Value::Float(_), Value::String(s)) => (Cow::Borrowed(left), Cow::Owned(string_to_number(s))),
+        _ => (Cow::Borrowed(left), Cow::Borrowed(right)),
+    }
+}
+
+/// Numeric value of a string used where MySQL expects a number: an integer
+/// when the whole string is one, otherwise its leading numeric prefix as a
+/// float (`'1.5kg'` is 1.5, `'abc'` is 0)
+pub(crate) fn string_to_number(s: &str) -> Value {
+    match s.trim().parse() {
+        Ok(i) => Value::Integer(i),
+        Err(_) => Value::Float(leading_number(s)),
+    }
+}
+
+/// Apply unary minus; strings are converted to numbers as for arithmetic,
+/// and negating the smallest integer gives a float like ABS does
+fn negate(value: &Value) -> Result<Value> {
+    match arithmetic_operand(value).as_ref() {
+        Value::Null => Ok(Value::Null),
+        Value::Integer(i) => Ok(i.checked_neg().map(Value::Integer).unwrap_or(Value::Float(-(*i as f64)))),
+        Value::Float(f) => Ok(Value::Float(-f)),
+        Value::Boolean(b) => Ok(Value::Integer(-(*b as i64))),
+        _ => Err(MiniSqlError::Type("Unary minus requires a numeric operand".into())),
+    }
+}
+
+fn arithmetic_operand(value: &Value) -> Cow<'_, Value> {
+    match value {
+        Value::String(s) => Cow::Owned(string_to_number(s)),
+        other => Cow::Borrowed(other),
+    }
+}
+
+/// Apply arithmetic operation; string operands are converted to numbers
 fn apply_arithmetic<F>(left: &Value, right: &Value, f: F) -> Result<Value>
 where
     F: Fn(f64, f64) -> f64,
 {
+    let (left, right) = (arithmetic_operand(left), arithmetic_operand(right));
+    let (left, right) = (left.as_ref(), right.as_ref());
     let left_num = match left {
         Value::Integer(i) => *i as f64,
         Value::Float(f) => *f,
@@ -706,42 +970,6 @@ where
     }
 }
 
-/// Simple LIKE pattern matching (supports % and _)
-fn match_like(s: &str, pattern: &str) -> bool {
-    let s_chars: Vec<char> = s.chars().collect();
-    let p_chars: Vec<char> = pattern.chars().collect();
-    
-    match_like_recursive(&s_chars, &p_chars)
-}
-
-fn match_like_recursive(s: &[char], p: &[char]) -> bool {
-    if p.is_empty() {
-        return s.is_empty();
-    }
-
-    match p[0] {
-        '%' => {
-            // % matches any sequence
-            for i in 0..=s.len() {
-                if match_like_recursive(&s[i..], &p[1..]) {
-                    return true;
-                }
-            }
-            false
-        }
-        '_' => {
-            // _ matches exactly one character
-            !s.is_empty() && match_like_recursive(&s[1..], &p[1..])
-        }
-        c => {
-            // Regular character match (case-insensitive)
-            !s.is_empty()
-                && s[0].to_ascii_lowercase() == c.to_ascii_lowercase()
-                && match_like_recursive(&s[1..], &p[1..])
-        }
-    }
-}
-
 /// Evaluate a function call
 pub fn eval_function(
     name: &str,
@@ -763,11 +991,7 @@ pub fn eval_function(
             let json_val = eval_expr(&args[0], row, schema, last_insert_id)?;
             let path = eval_expr(&args[1], row, schema, last_insert_id)?;
             match path {
-                Value::String(key) => {
-                    // Remove leading $. if present
-                    let key = key.trim_start_matches("$.").to_string();
-                    Ok(json_val.json_get(&key))
-                }
+                Value::String(path) => Ok(json_val.json_get(&path)),
                 _ => Err(MiniSqlError::Type("JSON path must be string".into())),
             }
         }
@@ -791,7 +1015,13 @@ pub fn eval_function(
                 Ok(val)
             }
         }
-        _ => Err(MiniSqlError::Syntax(format!("Unknown function: {}", name))),
+        _ => {
+            let arg_values: Result<Vec<Value>> = args
+                .iter()
+                .map(|a| eval_expr(a, row, schema, last_insert_id))
+                .collect();
+            eval_function_values(name, &arg_values?, last_insert_id)
+        }
     }
 }
 
@@ -824,10 +1054,7 @@ pub fn eval_function_values(name: &str, args: &[Value], last_insert_id: u64) ->
             let json_val = &args[0];
             let path = &args[1];
             match path {
-                Value::String(key) => {
-                    let key = key.trim_start_matches("$.").to_string();
-                    Ok(json_val.json_get(&key))
-                }
+                Value::String(path) => Ok(json_val.json_get(path)),
                 _ => Err(MiniSqlError::Type("JSON path must be string".into())),
             }
         }
@@ -849,10 +1076,239 @@ pub fn eval_function_values(name: &str, args: &[Value], last_insert_id: u64) ->
                 Ok(args[0].clone())
             }
         }
+        "NULLIF" => {
+            let [a, b] = args else {
+                return Err(MiniSqlError::Syntax("NULLIF requires 2 arguments".into()));
+            };
+            if a.is_null() || b.is_null() {
+                return Ok(a.clone());
+            }
+            let (left, right) = numeric_comparison_operands(a, b);
+            Ok(if left == right { Value::Null } else { a.clone() })
+        }
+        "GREATEST" => extreme_value(name, args, Ordering::Greater),
+        "LEAST" => extreme_value(name, args, Ordering::Less),
+        "NOW" | "CURRENT_TIMESTAMP" => {
+            if !args.is_empty() {
+                return Err(MiniSqlError::Syntax(format!("{} takes no arguments", name.to_uppercase())));
+            }
+            Ok(Value::String(Timestamp::now().to_string()))
+        }
+        "DATE_ADD" => date_add(args, false),
+        "DATE_SUB" => date_add(args, true),
+        "DATE" => Ok(single_date_arg(name, args)?.map(Value::Date).unwrap_or(Value::Null)),
+        // HEX of a number is its hex digits; of anything else, the hex of its bytes
+        "HEX" => match args {
+            [Value::Null] => Ok(Value::Null),
+            [Value::Integer(i)] => Ok(Value::String(format!("{:X}", i))),
+            [Value::Bytes(b)] => Ok(Value::String(hex_encode(b))),
+            [other] => Ok(Value::String(hex_encode(other.to_string().as_bytes()))),
+            _ => Err(MiniSqlError::Syntax("HEX requires 1 argument".into())),
+        },
+        // UNHEX of text that is not hex digits is NULL, as in MySQL
+        "UNHEX" => match args {
+            [Value::Null] => Ok(Value::Null),
+            [arg] => Ok(hex_decode(&arg.to_string()).map_or(Value::Null, Value::Bytes)),
+            _ => Err(MiniSqlError::Syntax("UNHEX requires 1 argument".into())),
+        },
+        "YEAR" => date_part(name, args, |d| d.year()),
+        "MONTH" => date_part(name, args, |d| d.month() as i64),
+        "DAY" | "DAYOFMONTH" => date_part(name, args, |d| d.day() as i64),
+        "ABS" => numeric_function(name, args, 1, |args| Some(match args[0] {
+            Value::Integer(i) => i.checked_abs().map(Value::Integer).unwrap_or(Value::Float((i as f64).abs())),
+            ref x => Value::Float(as_f64(x).abs()),
+        })),
+        "ROUND" => {
+            if !(1..=2).contains(&args.len()) {
+                return Err(MiniSqlError::Syntax("ROUND requires 1 or 2 arguments".into()));
+            }
+            numeric_function(name, args, args.len(), |args| {
+                let digits = args.get(1).map(|d| as_f64(d).round().clamp(-400.0, 400.0) as i32).unwrap_or(0);
+                Some(round(&args[0], digits))
+            })
+        }
+        "CEIL" | "CEILING" => numeric_function(name, args, 1, |args| Some(match args[0] {
+            Value::Integer(i) => Value::Integer(i),
+            ref x => Value::Float(as_f64(x).ceil()),
+        })),
+        "FLOOR" => numeric_function(name, args, 1, |args| Some(match args[0] {
+            Value::Integer(i) => Value::Integer(i),
+            ref x => Value::Float(as_f64(x).floor()),
+        })),
+        "POWER" | "POW" => numeric_function(name, args, 2, |args| {
+            let result = as_f64(&args[0]).powf(as_f64(&args[1]));
+            result.is_finite().then_some(Value::Float(result))
+        }),
+        // The square root of a negative number is NULL, as in MySQL
+        "SQRT" => numeric_function(name, args, 1, |args| {
+            let x = as_f64(&args[0]);
+            (x >= 0.0).then(|| Value::Float(x.sqrt()))
+        }),
+        // MOD by zero is NULL; the result takes the sign of the dividend
+        "MOD" => numeric_function(name, args, 2, |args| match (&args[0], &args[1]) {
+            (Value::Integer(_), Value::Integer(0)) => None,
+            (Value::Integer(a), Value::Integer(b)) => Some(Value::Integer(a.checked_rem(*b).unwrap_or(0))),
+            (a, b) => {
+                let b = as_f64(b);
+                (b != 0.0).then(|| Value::Float(as_f64(a) % b))
+            }
+        }),
         _ => Err(MiniSqlError::Syntax(format!("Unknown function: {}", name))),
     }
 }
 
+/// GREATEST/LEAST: the argument ordered furthest towards `wanted`, comparing
+/// like `<` and `>` do (a number and a string compare numerically). Any NULL
+/// argument gives NULL, as in MySQL.
+fn extreme_value(name: &str, args: &[Value], wanted: Ordering) -> Result<Value> {
+    if args.len() < 2 {
+        return Err(MiniSqlError::Syntax(format!("{} requires at least 2 arguments", name.to_uppercase())));
+    }
+    if args.iter().any(Value::is_null) {
+        return Ok(Value::Null);
+    }
+    let mut best = &args[0];
+    for arg in &args[1..] {
+        let (candidate, current) = numeric_comparison_operands(arg, best);
+        if candidate.partial_cmp(&current) == Some(wanted) {
+            best = arg;
+        }
+    }
+    Ok(best.clone())
+}
+
+/// Evaluate a numeric function taking `arity` arguments. Arguments are
+/// converted to numbers first (strings by [`string_to_number`], booleans to
+/// 0/1); any NULL argument, or `f` returning `None`, gives NULL.
+fn numeric_function(name: &str, args: &[Value], arity: usize, f: impl Fn(&[Value]) -> Option<Value>) -> Result<Value> {
+    if args.len() != arity {
+        return Err(MiniSqlError::Syntax(format!(
+            "{} requires {} argument{}",
+            name.to_uppercase(),
+            arity,
+            if arity == 1 { "" } else { "s" }
+        )));
+    }
+    if args.iter().any(Value::is_null) {
+        return Ok(Value::Null);
+    }
+    let numbers: Vec<Value> = args.iter().map(|arg| match arg {
+        Value::Integer(_) | Value::Float(_) => arg.clone(),
+        Value::Boolean(b) => Value::Integer(*b as i64),
+        Value::String(s) => string_to_number(s),
+        other => string_to_number(&other.to_string()),
+    }).collect();
+    Ok(f(&numbers).unwrap_or(Value::Null))
+}
+
+/// A number converted by [`numeric_function`] as a float
+fn as_f64(value: &Value) -> f64 {
+    match value {
+        Value::Integer(i) => *i as f64,
+        Value::Float(f) => *f,
+        _ => 0.0,
+    }
+}
+
+/// Round half away from zero to `digits` decimal places (negative digits
+/// round to tens, hundreds, ...). Integers stay integers.
+fn round(value: &Value, digits: i32) -> Value {
+    match value {
+        Value::Integer(i) if digits >= 0 => Value::Integer(*i),
+        Value::Integer(i) => {
+            let Some(factor) = 10i128.checked_pow(digits.unsigned_abs()) else {
+                return Value::Integer(0);
+            };
+            let i = *i as i128;
+            let rounded = (i.abs() + factor / 2) / factor * factor * i.signum();
+            i64::try_from(rounded).map(Value::Integer).unwrap_or(Value::Float(rounded as f64))
+        }
+        other => {
+            let x = as_f64(other);
+            let factor = 10f64.powi(digits);
+            if !factor.is_finite() || !(x * factor).is_finite() {
+                // More digits than a float holds: nothing to round
+                Value::Float(x)
+            } else if factor == 0.0 {
+                Value::Float(0.0)
+            } else {
+                Value::Float((x * factor).round() / factor)
+            }
+        }
+    }
+}
+
+/// The date given as the only argument of a date function: a DATE, or a
+/// date or datetime string. NULL and unparseable text give `None`.
+fn single_date_arg(name: &str, args: &[Value]) -> Result<Option<Date>> {
+    let [arg] = args else {
+        return Err(MiniSqlError::Syntax(format!("{} requires 1 argument", name.to_uppercase())));
+    };
+    Ok(match arg {
+        Value::Date(d) => Some(*d),
+        Value::Null => None,
+        other => Date::parse(&other.to_string()),
+    })
+}
+
+/// YEAR/MONTH/DAY: one integer part of the argument's date, NULL when it has none
+fn date_part(name: &str, args: &[Value], part: impl Fn(Date) -> i64) -> Result<Value> {
+    Ok(single_date_arg(name, args)?.map(|d| Value::Integer(part(d))).unwrap_or(Value::Null))
+}
+
+/// DATE_ADD/DATE_SUB over `[timestamp, amount, unit]`, as produced by
+/// `expr +/- INTERVAL amount UNIT`. Unparseable timestamps and results out
+/// of range give NULL, as in MySQL.
+fn date_add(args: &[Value], subtract: bool) -> Result<Value> {
+    let [date, amount, unit] = args else {
+        return Err(MiniSqlError::Syntax(
+            "DATE_ADD/DATE_SUB require a timestamp and an INTERVAL".into(),
+        ));
+    };
+    if date.is_null() || amount.is_null() {
+        return Ok(Value::Null);
+    }
+    let unit = match unit {
+        Value::String(s) => IntervalUnit::parse(s),
+        _ => None,
+    }
+    .ok_or_else(|| MiniSqlError::Syntax(format!("Unknown INTERVAL unit {}", unit)))?;
+    let amount = match amount {
+        Value::Integer(i) => *i,
+        Value::Float(f) => f.round() as i64,
+        Value::String(s) => s.trim().parse().map_err(|_| {
+            MiniSqlError::Type(format!("INTERVAL amount must be a number, got '{}'", s))
+        })?,
+        other => {
+            return Err(MiniSqlError::Type(format!(
+                "INTERVAL amount must be a number, got {}",
+                other
+            )))
+        }
+    };
+    // A DATE stays a DATE unless the interval adds a time of day
+    let start = match date {
+        Value::Date(d) => Some(d.to_timestamp()),
+        Value::String(text) => Timestamp::parse(text),
+        _ => {
+            return Err(MiniSqlError::Type(format!(
+                "INTERVAL arithmetic requires a date or datetime string, got {}",
+                date
+            )))
+        }
+    };
+
+    let amount = if subtract { amount.checked_neg() } else { Some(amount) };
+    Ok(start
+        .zip(amount)
+        .and_then(|(ts, amount)| ts.add_interval(amount, unit))
+        .map(|ts| match date {
+            Value::Date(_) if !ts.has_time() => Value::Date(ts.date()),
+            _ => Value::String(ts.to_string()),
+        })
+        .unwrap_or(Value::Null))
+}
+
 /// Substitute placeholder expressions with actual parameter values
 /// This is used during prepared statement execution
 pub fn substitute_placeholders(expr: &Expr, params: &[Value]) -> Result<Expr> {
@@ -867,6 +1323,7 @@ pub fn substitute_placeholders(expr: &Expr, params: &[Value]) -> Result<Expr> {
             Ok(Expr::Literal(params[*idx].clone()))
         }
         Expr::Literal(v) => Ok(Expr::Literal(v.clone())),
+        Expr::UserVariable(name) => Ok(Expr::UserVariable(name.clone())),
         Expr::Column { table, name } => Ok(Expr::Column {
             table: table.clone(),
             name: name.clone(),
@@ -877,8 +1334,13 @@ pub fn substitute_placeholders(expr: &Expr, params: &[Value]) -> Result<Expr> {
             right: Box::new(substitute_placeholders(right, params)?),
         }),
         Expr::Not(inner) => Ok(Expr::Not(Box::new(substitute_placeholders(inner, params)?))),
+        Expr::UnaryMinus(inner) => Ok(Expr::UnaryMinus(Box::new(substitute_placeholders(inner, params)?))),
         Expr::IsNull(inner) => Ok(Expr::IsNull(Box::new(substitute_placeholders(inner, params)?))),
         Expr::IsNotNull(inner) => Ok(Expr::IsNotNull(Box::new(substitute_placeholders(inner, params)?))),
+        Expr::Like { expr: inner, pattern } => Ok(Expr::Like {
+            expr: Box::new(substitute_placeholders(inner, params)?),
+            pattern: Arc::clone(pattern),
+        }),
         Expr::JsonAccess { expr: inner, key, as_text } => Ok(Expr::JsonAccess {
             expr: Box::new(substitute_placeholders(inner, params)?),
             key: key.clone(),
@@ -904,7 +1366,7 @@ pub fn substitute_placeholders(expr: &Expr, params: &[Value]) -> Result<Expr> {
                 values: substituted_values?,
             })
         }
-        Expr::FunctionCall { name, args } => {
+        Expr::FunctionCall { name, args, distinct, separator } => {
             let substituted_args: Result<Vec<Expr>> = args
                 .iter()
                 .map(|a| substitute_placeholders(a, params))
@@ -912,7 +1374,274 @@ pub fn substitute_placeholders(expr: &Expr, params: &[Value]) -> Result<Expr> {
             Ok(Expr::FunctionCall {
                 name: name.clone(),
                 args: substituted_args?,
+                distinct: *distinct,
+                separator: separator.clone(),
             })
         }
+        Expr::Cast { expr: inner, target } => Ok(Expr::Cast {
+            expr: Box::new(substitute_placeholders(inner, params)?),
+            target: target.clone(),
+        }),
+        Expr::InSubquery { expr: inner, subquery, negated } => Ok(Expr::InSubquery {
+            expr: Box::new(substitute_placeholders(inner, params)?),
+            subquery: Box::new(substitute_select_placeholders(subquery, params)?),
+            negated: *negated,
+        }),
+        Expr::ScalarSubquery(subquery) => Ok(Expr::ScalarSubquery(Box::new(
+            substitute_select_placeholders(subquery, params)?,
+        ))),
+    }
+}
+
+/// Replace `VALUES(col)` in an ON DUPLICATE KEY UPDATE expression with the
+/// value the INSERT attempted to write to `col` (`attempted` is in schema order)
+pub fn substitute_insert_values(expr: &Expr, schema: &TableSchema, attempted: &[Value]) -> Result<Expr> {
+    let recurse = |e: &Expr| substitute_insert_values(e, schema, attempted);
+    let recurse_all = |exprs: &[Expr]| exprs.iter().map(recurse).collect::<Result<Vec<_>>>();
+    Ok(match expr {
+        Expr::FunctionCall { name, args, .. } if name.eq_ignore_ascii_case("VALUES") => {
+            let [Expr::Column { name: column, .. }] = args.as_slice() else {
+                return Err(MiniSqlError::Syntax("VALUES() expects a column name".into()));
+            };
+            let idx = schema.find_column(column)
+                .ok_or_else(|| MiniSqlError::unknown_column(column, ColumnContext::FieldList))?;
+            Expr::Literal(attempted[idx].clone())
+        }
+        Expr::FunctionCall { name, args, distinct, separator } => Expr::FunctionCall {
+            name: name.clone(),
+            args: recurse_all(args)?,
+            distinct: *distinct,
+            separator: separator.clone(),
+        },
+        Expr::BinaryOp { left, op, right } => Expr::BinaryOp {
+            left: Box::new(recurse(left)?),
+            op: *op,
+            right: Box::new(recurse(right)?),
+        },
+        Expr::Not(inner) => Expr::Not(Box::new(recurse(inner)?)),
+        Expr::UnaryMinus(inner) => Expr::UnaryMinus(Box::new(recurse(inner)?)),
+        Expr::IsNull(inner) => Expr::IsNull(Box::new(recurse(inner)?)),
+        Expr::IsNotNull(inner) => Expr::IsNotNull(Box::new(recurse(inner)?)),
+        Expr::Like { expr: inner, pattern } => Expr::Like {
+            expr: Box::new(recurse(inner)?),
+            pattern: Arc::clone(pattern),
+        },
+        Expr::JsonAccess { expr: inner, key, as_text } => Expr::JsonAccess {
+            expr: Box::new(recurse(inner)?),
+            key: key.clone(),
+            as_text: *as_text,
+        },
+        Expr::In { expr: inner, values } => Expr::In {
+            expr: Box::new(recurse(inner)?),
+            values: recurse_all(values)?,
+        },
+        Expr::NotIn { expr: inner, values } => Expr::NotIn {
+            expr: Box::new(recurse(inner)?),
+            values: recurse_all(values)?,
+        },
+        Expr::Cast { expr: inner, target } => Expr::Cast {
+            expr: Box::new(recurse(inner)?),
+            target: target.clone(),
+        },
+        Expr::InSubquery { expr: inner, subquery, negated } => Expr::InSubquery {
+            expr: Box::new(recurse(inner)?),
+            subquery: subquery.clone(),
+            negated: *negated,
+        },
+        Expr::Literal(_)
+        | Expr::Column { .. }
+        | Expr::ScalarSubquery(_)
+        | Expr::Placeholder(_)
+        | Expr::UserVariable(_) => expr.clone(),
+    })
+}
+
+/// Substitute placeholders in the select list and WHERE clause of a SELECT
+pub fn substitute_select_placeholders(select: &SelectStmt, params: &[Value]) -> Result<SelectStmt> {
+    let columns: Result<Vec<SelectColumn>> = select
+        .columns
+        .iter()
+        .map(|c| match c {
+            SelectColumn::Star => Ok(SelectColumn::Star),
+            SelectColumn::QualifiedStar { table } => {
+                Ok(SelectColumn::QualifiedStar {
+                    table: table.clone(),
+                })
+            }
+            SelectColumn::Expr { expr, alias } => Ok(SelectColumn::Expr {
+                expr: substitute_placeholders(expr, params)?,
+                alias: alias.clone(),
+            }),
+        })
+        .collect();
+
+    let where_clause = match &select.where_clause {
+        Some(expr) => Some(substitute_placeholders(expr, params)?),
+        None => None,
+    };
+
+    Ok(SelectStmt {
+        columns: columns?,
+        from: select.from.clone(),
+        joins: select.joins.clone(),
+        where_clause,
+        group_by: select.group_by.clone(),
+        order_by: select.order_by.clone(),
+        limit: select.limit,
+        for_update: select.for_update,
+    })
+}
+
+/// Error for a user variable that reached row evaluation without being
+/// replaced by its session value
+fn unbound_user_variable() -> MiniSqlError {
+    MiniSqlError::Internal("User variable was not bound before evaluation".into())
+}
+
+/// Error for a subquery that reached row evaluation without being replaced by
+/// its value(s)
+fn unresolved_subquery() -> MiniSqlError {
+    MiniSqlError::Internal("Subquery was not resolved before evaluation".into())
+}
+
+/// Declared type of a scalar subquery's column, as far as it can be told
+/// without the inner table's schema
+pub fn infer_scalar_subquery_type(subquery: &SelectStmt) -> DataType {
+    match subquery.columns.first() {
+        Some(SelectColumn::Expr { expr: Expr::FunctionCall { name, .. }, .. }) => {
+            match name.to_uppercase().as_str() {
+                "COUNT" => DataType::Integer,
+                "AVG" => DataType::Float,
+                _ => DataType::Text,
+            }
+        }
+        Some(SelectColumn::Expr { expr: Expr::Literal(Value::Integer(_)), .. }) => DataType::Integer,
+        Some(SelectColumn::Expr { expr: Expr::Literal(Value::Float(_)), .. }) => DataType::Float,
+        _ => DataType::Text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, args: &[Value]) -> Value {
+        eval_function_values(name, args, 0).unwrap()
+    }
+
+    #[test]
+    fn test_abs() {
+        assert_eq!(call("ABS", &[Value::Integer(-7)]), Value::Integer(7));
+        assert_eq!(call("abs", &[Value::Integer(7)]), Value::Integer(7));
+        assert_eq!(call("ABS", &[Value::Float(-2.5)]), Value::Float(2.5));
+        assert_eq!(call("ABS", &[Value::String("-3".into())]), Value::Integer(3));
+        assert_eq!(call("ABS", &[Value::Integer(i64::MIN)]), Value::Float(9223372036854775808.0));
+        assert_eq!(call("ABS", &[Value::Null]), Value::Null);
+        assert!(eval_function_values("ABS", &[], 0).is_err());
+    }
+
+    #[test]
+    fn test_round() {
+        assert_eq!(call("ROUND", &[Value::Float(2.5)]), Value::Float(3.0));
+        assert_eq!(call("ROUND", &[Value::Float(-2.5)]), Value::Float(-3.0));
+        assert_eq!(call("ROUND", &[Value::Float(2.4)]), Value::Float(2.0));
+        assert_eq!(call("ROUND", &[Value::Integer(42)]), Value::Integer(42));
+        assert_eq!(call("ROUND", &[Value::Float(3.14159), Value::Integer(2)]), Value::Float(3.14));
+        assert_eq!(call("ROUND", &[Value::Float(-1.005), Value::Integer(1)]), Value::Float(-1.0));
+        assert_eq!(call("ROUND", &[Value::Integer(42), Value::Integer(3)]), Value::Integer(42));
+        assert_eq!(call("ROUND", &[Value::Integer(1250), Value::Integer(-2)]), Value::Integer(1300));
+        assert_eq!(call("ROUND", &[Value::Integer(-1249), Value::Integer(-2)]), Value::Integer(-1200));
+        assert_eq!(call("ROUND", &[Value::Float(1234.5), Value::Integer(-2)]), Value::Float(1200.0));
+        assert_eq!(call("ROUND", &[Value::Float(1.5), Value::Integer(400)]), Value::Float(1.5));
+        assert_eq!(call("ROUND", &[Value::Null, Value::Integer(2)]), Value::Null);
+        assert_eq!(call("ROUND", &[Value::Float(1.5), Value::Null]), Value::Null);
+        assert!(eval_function_values("ROUND", &[Value::Integer(1), Value::Integer(2), Value::Integer(3)], 0).is_err());
+    }
+
+    #[test]
+    fn test_ceil_and_floor() {
+        assert_eq!(call("CEIL", &[Value::Float(1.2)]), Value::Float(2.0));
+        assert_eq!(call("CEILING", &[Value::Float(-1.2)]), Value::Float(-1.0));
+        assert_eq!(call("CEIL", &[Value::Integer(5)]), Value::Integer(5));
+        assert_eq!(call("FLOOR", &[Value::Float(1.8)]), Value::Float(1.0));
+        assert_eq!(call("FLOOR", &[Value::Float(-1.2)]), Value::Float(-2.0));
+        assert_eq!(call("FLOOR", &[Value::Integer(-5)]), Value::Integer(-5));
+        assert_eq!(call("FLOOR", &[Value::Null]), Value::Null);
+    }
+
+    #[test]
+    fn test_power_and_sqrt() {
+        assert_eq!(call("POWER", &[Value::Integer(2), Value::Integer(10)]), Value::Float(1024.0));
+        assert_eq!(call("POW", &[Value::Float(4.0), Value::Float(0.5)]), Value::Float(2.0));
+        assert_eq!(call("POWER", &[Value::Integer(2), Value::Integer(-1)]), Value::Float(0.5));
+        assert_eq!(call("POWER", &[Value::Null, Value::Integer(2)]), Value::Null);
+        assert_eq!(call("SQRT", &[Value::Integer(16)]), Value::Float(4.0));
+        assert_eq!(call("SQRT", &[Value::Float(2.25)]), Value::Float(1.5));
+        assert_eq!(call("SQRT", &[Value::Integer(-1)]), Value::Null);
+        assert_eq!(call("SQRT", &[Value::Null]), Value::Null);
+        assert!(eval_function_values("POWER", &[Value::Integer(2)], 0).is_err());
+    }
+
+    #[test]
+    fn test_mod() {
+        assert_eq!(call("MOD", &[Value::Integer(10), Value::Integer(3)]), Value::Integer(1));
+        assert_eq!(call("MOD", &[Value::Integer(-10), Value::Integer(3)]), Value::Integer(-1));
+        assert_eq!(call("MOD", &[Value::Integer(10), Value::Integer(-3)]), Value::Integer(1));
+        assert_eq!(call("MOD", &[Value::Float(5.5), Value::Integer(2)]), Value::Float(1.5));
+        assert_eq!(call("MOD", &[Value::Integer(10), Value::Integer(0)]), Value::Null);
+        assert_eq!(call("MOD", &[Value::Integer(i64::MIN), Value::Integer(-1)]), Value::Integer(0));
+        assert_eq!(call("MOD", &[Value::Null, Value::Integer(3)]), Value::Null);
+    }
+
+    #[test]
+    fn test_nullif() {
+        assert_eq!(call("NULLIF", &[Value::Integer(1), Value::Integer(1)]), Value::Null);
+        assert_eq!(call("NULLIF", &[Value::Integer(1), Value::Integer(2)]), Value::Integer(1));
+        assert_eq!(call("nullif", &[Value::String("a".into()), Value::String("b".into())]), Value::String("a".into()));
+        // A number and a string compare numerically
+        assert_eq!(call("NULLIF", &[Value::Integer(5), Value::String("5".into())]), Value::Null);
+        assert_eq!(call("NULLIF", &[Value::Float(5.0), Value::Integer(5)]), Value::Null);
+        assert_eq!(call("NULLIF", &[Value::Null, Value::Integer(1)]), Value::Null);
+        assert_eq!(call("NULLIF", &[Value::Integer(1), Value::Null]), Value::Integer(1));
+        assert!(eval_function_values("NULLIF", &[Value::Integer(1)], 0).is_err());
+    }
+
+    #[test]
+    fn test_greatest_and_least() {
+        let ints = [Value::Integer(3), Value::Integer(-1), Value::Integer(7)];
+        assert_eq!(call("GREATEST", &ints), Value::Integer(7));
+        assert_eq!(call("LEAST", &ints), Value::Integer(-1));
+        assert_eq!(call("GREATEST", &[Value::Integer(2), Value::Float(2.5)]), Value::Float(2.5));
+        assert_eq!(call("LEAST", &[Value::String("pear".into()), Value::String("apple".into())]), Value::String("apple".into()));
+        // Numbers and numeric strings compare numerically, keeping the original value
+        assert_eq!(call("GREATEST", &[Value::Integer(9), Value::String("10".into())]), Value::String("10".into()));
+        assert_eq!(call("LEAST", &[Value::String("10".into()), Value::Integer(9)]), Value::Integer(9));
+        // Any NULL makes the result NULL
+        assert_eq!(call("GREATEST", &[Value::Integer(1), Value::Null, Value::Integer(2)]), Value::Null);
+        assert_eq!(call("LEAST", &[Value::Null, Value::Integer(2)]), Value::Null);
+        assert!(eval_function_values("GREATEST", &[Value::Integer(1)], 0).is_err());
+    }
+
+    #[test]
+    fn test_common_type() {
+        use DataType::{Float, Integer, Json, Text};
+        assert_eq!(common_type(&[Integer, Integer]), Integer);
+        assert_eq!(common_type(&[Integer, Float, Integer]), Float);
+        assert_eq!(common_type(&[Json]), Json);
+        assert_eq!(common_type(&[Integer, Text]), Text);
+        assert_eq!(common_type(&[]), Text);
+    }
+
+    #[test]
+    fn test_math_function_types() {
+        use DataType::{Float, Integer, Text};
+        assert_eq!(math_function_type("ABS", &[Integer]), Integer);
+        assert_eq!(math_function_type("ROUND", &[Integer, Integer]), Integer);
+        assert_eq!(math_function_type("ROUND", &[Float, Integer]), Float);
+        assert_eq!(math_function_type("FLOOR", &[Text]), Float);
+        assert_eq!(math_function_type("MOD", &[Integer, Integer]), Integer);
+        assert_eq!(math_function_type("MOD", &[Integer, Float]), Float);
+        assert_eq!(math_function_type("SQRT", &[Integer]), Float);
+        assert_eq!(math_function_type("POWER", &[Integer, Integer]), Float);
     }
 }
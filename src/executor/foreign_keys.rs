@@ -0,0 +1,247 @@
+//! Foreign key enforcement
+//!
+//! Foreign keys live on the child table's schema. Child rows are checked on
+//! INSERT and UPDATE: a non-NULL key must exist in the parent table. Parent
+//! rows are checked on UPDATE (a referenced key may not change) and DELETE,
+//! where each referencing constraint either rejects the delete (RESTRICT) or
+//! deletes the children too (CASCADE).
+//!
+//! Lookups use an index covering the key columns when the table's engine
+//! supports indexes, and a full scan otherwise.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::engines::granite::TxnId;
+use crate::error::{ColumnContext, MiniSqlError, Result};
+use crate::executor::{Executor, Session};
+use crate::parser::ForeignKeyDef;
+use crate::types::{ForeignKey, ReferentialAction, Row, TableSchema, Value};
+
+/// Values of `columns` in a row; `None` if any is NULL (NULL keys are never checked)
+fn key_values(schema: &TableSchema, columns: &[String], values: &[Value]) -> Option<Vec<Value>> {
+    columns.iter()
+        .map(|col| {
+            schema.find_column(col)
+                .and_then(|idx| values.get(idx))
+                .filter(|v| !v.is_null())
+                .cloned()
+        })
+        .collect()
+}
+
+impl Executor {
+    /// Resolve FOREIGN KEY clauses of a CREATE TABLE against the new table and
+    /// the tables it references
+    pub(crate) fn resolve_foreign_keys(
+        &self,
+        table_name: &str,
+        columns: &[crate::types::ColumnDef],
+        defs: &[ForeignKeyDef],
+    ) -> Result<Vec<ForeignKey>> {
+        let canonical = |cols: &[crate::types::ColumnDef], name: &str| {
+            cols.iter()
+                .find(|c| c.name.eq_ignore_ascii_case(name))
+                .map(|c| c.name.clone())
+                .ok_or_else(|| MiniSqlError::unknown_column(name, ColumnContext::General))
+        };
+
+        defs.iter().enumerate().map(|(i, def)| {
+            let fk_columns = def.columns.iter()
+                .map(|c| canonical(columns, c))
+                .collect::<Result<Vec<_>>>()?;
+            let ref_columns = if def.ref_table == table_name {
+                def.ref_columns.iter().map(|c| canonical(columns, c)).collect::<Result<Vec<_>>>()?
+            } else {
+                let parent = self.storage.get_schema(&def.ref_table)?;
+                def.ref_columns.iter().map(|c| canonical(&parent.columns, c)).collect::<Result<Vec<_>>>()?
+            };
+            Ok(ForeignKey {
+                name: def.name.clone().unwrap_or_else(|| format!("{}_ibfk_{}", table_name, i + 1)),
+                columns: fk_columns,
+                ref_table: def.ref_table.clone(),
+                ref_columns,
+                on_delete: def.on_delete,
+            })
+        }).collect()
+    }
+
+    /// Foreign keys of other tables (and self-references) that point at `table_name`
+    pub(crate) fn referencing_foreign_keys(&self, table_name: &str) -> Result<Vec<(TableSchema, ForeignKey)>> {
+        let mut result = Vec::new();
+        for name in self.storage.list_tables() {
+            let schema = self.storage.get_schema(&name)?;
+            for fk in schema.foreign_keys.iter().filter(|fk| fk.ref_table == table_name) {
+                result.push((schema.clone(), fk.clone()));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Rows of `table_name` whose `columns` equal `values`
+    fn rows_with_key(&self, table_name: &str, columns: &[String], values: &[Value]) -> Result<Vec<Row>> {
+        let schema = self.storage.get_schema(table_name)?;
+        let positions: Vec<usize> = columns.iter()
+            .map(|c| schema.find_column(c).ok_or_else(|| MiniSqlError::unknown_column(c, ColumnContext::General)))
+            .collect::<Result<_>>()?;
+
+        let mut candidates = None;
+        if self.engine_supports_indexes(table_name)? {
+            if let Some(index) = self.storage.find_index_for_columns(table_name, columns) {
+                // Usable when the index's leading columns are exactly the key columns
+                let ordered: Option<Vec<Value>> = index.columns.iter().take(columns.len())
+                    .map(|ic| columns.iter().position(|c| c.eq_ignore_ascii_case(ic)).map(|i| values[i].clone()))
+                    .collect();
+                if let Some(ordered) = ordered.filter(|o| o.len() == columns.len()) {
+                    candidates = Some(self.storage.get_rows_by_composite_index(
+                        table_name, &index.columns[..columns.len()], &ordered,
+                    )?);
+                }
+            }
+        }
+        let candidates = match candidates {
+            Some(rows) => rows,
+            None => self.scan_table(table_name)?,
+        };
+
+        Ok(candidates.into_iter()
+            .filter(|row| positions.iter().zip(values).all(|(&p, v)| row.values.get(p) == Some(v)))
+            .collect())
+    }
+
+    /// Check that every non-NULL foreign key of a child row references an existing parent row.
+    ///
+    /// `old_values` is the row before an UPDATE; keys it leaves unchanged are not rechecked.
+    pub(crate) fn check_foreign_keys(
+        &self,
+        schema: &TableSchema,
+        values: &[Value],
+        old_values: Option<&[Value]>,
+    ) -> Result<()> {
+        for fk in &schema.foreign_keys {
+            let Some(key) = key_values(schema, &fk.columns, values) else { continue };
+            if let Some(old) = old_values {
+                if key_values(schema, &fk.columns, old).as_ref() == Some(&key) {
+                    continue;
+                }
+            }
+            // A self-referencing row may reference itself
+            if fk.ref_table == schema.name && key_values(schema, &fk.ref_columns, values).as_ref() == Some(&key) {
+                continue;
+            }
+            if self.rows_with_key(&fk.ref_table, &fk.ref_columns, &key)?.is_empty() {
+                return Err(MiniSqlError::foreign_key_no_parent(&schema.name, &fk.constraint_sql()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject an UPDATE that changes a parent key still referenced by child rows
+    pub(crate) fn check_referenced_key_update(
+        &self,
+        schema: &TableSchema,
+        referencing: &[(TableSchema, ForeignKey)],
+        old_values: &[Value],
+        new_values: &[Value],
+    ) -> Result<()> {
+        for (child, fk) in referencing {
+            let Some(old_key) = key_values(schema, &fk.ref_columns, old_values) else { continue };
+            if key_values(schema, &fk.ref_columns, new_values).as_ref() == Some(&old_key) {
+                continue;
+            }
+            if !self.rows_with_key(&child.name, &fk.columns, &old_key)?.is_empty() {
+                return Err(MiniSqlError::foreign_key_parent_referenced(&child.name, &fk.constraint_sql()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Expand a DELETE to the rows it removes once CASCADE constraints are
+    /// followed, and fail if any RESTRICT constraint still has children left.
+    ///
+    /// Returns `(table, row)` pairs, each row appearing once.
+    fn plan_cascading_delete(&self, table_name: &str, rows: Vec<Row>) -> Result<Vec<(String, Row)>> {
+        let mut referencing_cache: HashMap<String, Vec<(TableSchema, ForeignKey)>> = HashMap::new();
+        let mut schemas: HashMap<String, TableSchema> = HashMap::new();
+        let mut seen: HashSet<(String, u64)> = HashSet::new();
+        let mut planned = Vec::new();
+        let mut queue: VecDeque<(String, Row)> = rows.into_iter().map(|r| (table_name.to_string(), r)).collect();
+
+        while let Some((table, row)) = queue.pop_front() {
+            if !seen.insert((table.clone(), row.id)) {
+                continue;
+            }
+            if !referencing_cache.contains_key(&table) {
+                referencing_cache.insert(table.clone(), self.referencing_foreign_keys(&table)?);
+                schemas.insert(table.clone(), self.storage.get_schema(&table)?);
+            }
+            let schema = &schemas[&table];
+            for (child, fk) in &referencing_cache[&table] {
+                if fk.on_delete != ReferentialAction::Cascade {
+                    continue;
+                }
+                let Some(key) = key_values(schema, &fk.ref_columns, &row.values) else { continue };
+                for child_row in self.rows_with_key(&child.name, &fk.columns, &key)? {
+                    queue.push_back((child.name.clone(), child_row));
+                }
+            }
+            planned.push((table, row));
+        }
+
+        for (table, row) in &planned {
+            let schema = &schemas[table];
+            for (child, fk) in &referencing_cache[table] {
+                if fk.on_delete != ReferentialAction::Restrict {
+                    continue;
+                }
+                let Some(key) = key_values(schema, &fk.ref_columns, &row.values) else { continue };
+                let children = self.rows_with_key(&child.name, &fk.columns, &key)?;
+                if children.iter().any(|c| !seen.contains(&(child.name.clone(), c.id))) {
+                    return Err(MiniSqlError::foreign_key_parent_referenced(&child.name, &fk.constraint_sql()));
+                }
+            }
+        }
+        Ok(planned)
+    }
+
+    /// Delete rows of `table_name`, applying the ON DELETE action of every
+    /// foreign key that references them.
+    ///
+    /// With `foreign_key_checks` off the rows are deleted as given.
+    /// Returns the number of rows deleted from `table_name` itself; cascaded
+    /// deletes in other tables are not counted, as in MySQL.
+    pub(crate) fn delete_rows(
+        &self,
+        table_name: &str,
+        rows: Vec<Row>,
+        txn_id: TxnId,
+        session: &mut Session,
+    ) -> Result<u64> {
+        let planned = if !session.foreign_key_checks || self.referencing_foreign_keys(table_name)?.is_empty() {
+            rows.into_iter().map(|r| (table_name.to_string(), r)).collect()
+        } else {
+            self.plan_cascading_delete(table_name, rows)?
+        };
+
+        let mut rows_affected = 0;
+        let mut touched = HashSet::new();
+        for (table, row) in planned {
+            let engine = self.get_engine(&table)?;
+            if engine.delete(txn_id, &table, row.id, &row.values)? {
+                if table == table_name {
+                    rows_affected += 1;
+                }
+                touched.insert(table);
+            }
+        }
+
+        for table in touched.iter().filter(|t| *t != table_name) {
+            let schema = self.storage.get_schema(table)?;
+            if session.txn_id.is_some() {
+                session.modified_engines.insert(schema.engine_type);
+            } else {
+                self.get_engine(table)?.flush(table)?;
+            }
+        }
+        Ok(rows_affected)
+    }
+}
@@ -0,0 +1,103 @@
+//! Row locking for `SELECT ... FOR UPDATE`, UPDATE and DELETE
+//!
+//! Before a statement reads the rows it is going to lock or change, every
+//! Granite row matching its WHERE clause is locked for the session's
+//! transaction (autocommit statements only wait for other transactions'
+//! locks). When that had to wait, the rows are read again: the transaction
+//! that held them may have changed them, and the statement must act on what
+//! it committed. Sandstone tables are never locked.
+
+use std::time::Duration;
+
+use crate::error::{MiniSqlError, Result};
+use crate::executor::evaluator;
+use crate::executor::subquery::simple_row_columns;
+use crate::executor::{Executor, Session};
+use crate::parser::{Expr, SelectStmt};
+use crate::types::{QueryResult, Row, TableSchema};
+
+impl Executor {
+    /// Rows of a table that may match `where_clause`, read through an index
+    /// when one applies
+    pub(crate) fn candidate_rows(
+        &self,
+        schema: &TableSchema,
+        table_alias: &str,
+        where_clause: Option<&Expr>,
+        session: &Session,
+    ) -> Result<Vec<Row>> {
+        let indexed = match where_clause {
+            Some(where_expr) => self.index_candidate_rows(&schema.name, table_alias, where_expr, schema)?,
+            None => None,
+        };
+        let index_used = indexed.is_some();
+        let rows = match indexed {
+            Some(rows) => rows,
+            None => self.get_engine(&schema.name)?.scan(&schema.name)?,
+        };
+        session.record_read(rows.len(), index_used);
+        Ok(rows)
+    }
+
+    /// Rows returned by `read`, read once every row of `schema`'s table that
+    /// matches `where_clause` is locked for the session
+    pub(crate) fn read_locked_rows(
+        &self,
+        schema: &TableSchema,
+        table_alias: &str,
+        where_clause: Option<&Expr>,
+        session: &Session,
+        read: impl Fn() -> Result<Vec<Row>>,
+    ) -> Result<Vec<Row>> {
+        let table_name = &schema.name;
+        let needs_locks = session.txn_id.is_some() || self.txn_manager.has_row_locks(table_name);
+        if !needs_locks || !self.get_engine(table_name)?.supports_transactions() {
+            return read();
+        }
+
+        loop {
+            let rows = read()?;
+            let mut row_ids = Vec::new();
+            for row in &rows {
+                if let Some(where_expr) = where_clause {
+                    let outer = simple_row_columns(row, schema, table_alias);
+                    let where_expr = self.bind_scalar_subqueries(where_expr, &outer, session)?;
+                    if !evaluator::eval_where(&where_expr, row, schema, session.last_insert_id)? {
+                        continue;
+                    }
+                }
+                row_ids.push(row.id);
+            }
+
+            let timeout = Duration::from_millis(session.lock_wait_timeout_ms);
+            let waited = match session.txn_id {
+                Some(txn_id) => self.txn_manager.lock_rows(txn_id, table_name, &row_ids, timeout)?,
+                None => self.txn_manager.wait_for_row_locks(table_name, &row_ids, timeout)?,
+            };
+            if !waited {
+                return Ok(rows);
+            }
+        }
+    }
+
+    /// Execute a `SELECT ... FOR UPDATE`: lock the matching rows, then read
+    /// their latest committed values, bypassing any REPEATABLE READ snapshot
+    pub(crate) fn execute_locking_select(&self, select: SelectStmt, session: &mut Session) -> Result<QueryResult> {
+        if !select.joins.is_empty() {
+            return Err(MiniSqlError::Syntax("FOR UPDATE is not supported with JOIN".into()));
+        }
+        if let Some(from) = &select.from {
+            let schema = self.storage.get_schema(&from.name)?;
+            let alias = from.effective_name();
+            let where_clause = select.where_clause.as_ref();
+            self.read_locked_rows(&schema, alias, where_clause, session, || {
+                self.candidate_rows(&schema, alias, where_clause, session)
+            })?;
+        }
+
+        let snapshot = session.read_snapshot.take();
+        let result = self.execute_select(select, session);
+        session.read_snapshot = snapshot;
+        result
+    }
+}
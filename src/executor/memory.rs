@@ -0,0 +1,92 @@
+//! Per-query memory accounting
+//!
+//! Sorts, GROUP BY buckets and join results are buffered in memory. A
+//! `QueryMemory` tracks a rough estimate of those buffers and aborts the query
+//! with MySQL error 1038 once the configured limit would be exceeded.
+
+use std::mem::size_of;
+
+use serde_json::Value as JsonValue;
+
+use crate::error::{MiniSqlError, Result};
+use crate::types::Value;
+
+/// Running memory estimate for a single query
+pub(crate) struct QueryMemory {
+    limit: Option<usize>,
+    used: usize,
+}
+
+impl QueryMemory {
+    pub(crate) fn new(limit: Option<usize>) -> Self {
+        Self { limit, used: 0 }
+    }
+
+    /// Account for buffering a row with the given values
+    pub(crate) fn charge_row(&mut self, values: &[Value]) -> Result<()> {
+        if self.limit.is_none() {
+            return Ok(());
+        }
+        self.charge(estimate_row_size(values))
+    }
+
+    /// Account for `bytes` of additional buffered data
+    pub(crate) fn charge(&mut self, bytes: usize) -> Result<()> {
+        if let Some(limit) = self.limit {
+            self.used = self.used.saturating_add(bytes);
+            if self.used > limit {
+                return Err(MiniSqlError::out_of_sort_memory(limit));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Estimate the heap and inline size of a buffered row
+pub(crate) fn estimate_row_size(values: &[Value]) -> usize {
+    size_of::<Vec<Value>>() + values.iter().map(estimate_value_size).sum::<usize>()
+}
+
+fn estimate_value_size(value: &Value) -> usize {
+    size_of::<Value>()
+        + match value {
+            Value::String(s) => s.len(),
+            Value::Json(j) => estimate_json_size(j),
+            _ => 0,
+        }
+}
+
+fn estimate_json_size(json: &JsonValue) -> usize {
+    match json {
+        JsonValue::String(s) => s.len(),
+        JsonValue::Array(items) => items
+            .iter()
+            .map(|v| size_of::<JsonValue>() + estimate_json_size(v))
+            .sum(),
+        JsonValue::Object(map) => map
+            .iter()
+            .map(|(k, v)| k.len() + size_of::<JsonValue>() + estimate_json_size(v))
+            .sum(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_never_fails() {
+        let mut mem = QueryMemory::new(None);
+        for _ in 0..1000 {
+            mem.charge_row(&[Value::String("x".repeat(1000))]).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_limit_exceeded_is_error_1038() {
+        let mut mem = QueryMemory::new(Some(100));
+        let err = mem.charge_row(&[Value::String("x".repeat(200))]).unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1038);
+    }
+}
@@ -17,11 +17,13 @@
 //! - ROLLBACK: Undo changes from undo log
 
 use std::collections::{HashMap, HashSet};
-use crate::error::Result;
-use crate::parser::Statement;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use crate::error::{MiniSqlError, Result};
+use crate::parser::{Parser, SelectStmt, Statement};
 use crate::storage::StorageEngine;
 use crate::engines::{TransactionManager, granite::TxnId};
-use crate::types::{DataType, QueryResult};
+use crate::types::{DataType, IsolationLevel, QueryResult, Row, SqlMode, StorageFormat, Value};
 
 pub mod ddl;
 pub mod dml;
@@ -30,6 +32,25 @@ pub mod transaction;
 pub mod evaluator;
 pub mod schema;
 pub mod aggregation;
+pub mod sink;
+pub mod dump;
+mod cache;
+mod foreign_keys;
+mod load;
+mod locking;
+mod memory;
+mod ordering;
+mod planner;
+mod privileges;
+mod replication;
+mod sample;
+mod stats;
+mod subquery;
+mod timeout;
+
+pub use cache::QueryCacheStats;
+pub use load::IMPORT_DIR;
+pub use sink::RowSink;
 
 /// A prepared statement stored in the session
 #[derive(Debug, Clone)]
@@ -42,16 +63,65 @@ pub struct PreparedStatement {
     pub statement: Statement,
     /// Number of parameter placeholders (?)
     pub param_count: usize,
+    /// MySQL type and unsigned flag of each parameter as last bound by
+    /// COM_STMT_EXECUTE; an execute that does not rebind them reuses these.
+    /// Empty until bound.
+    pub param_types: Vec<(u8, bool)>,
     /// Column types for result set (for SELECT statements)
     pub column_types: Vec<DataType>,
     /// Column names for result set
     pub column_names: Vec<String>,
 }
 
+/// Default row lock wait, matching MySQL's `innodb_lock_wait_timeout` of 50 seconds
+pub const DEFAULT_LOCK_WAIT_TIMEOUT_MS: u64 = 50_000;
+
+/// User exempt from privilege checks unless the server configures another
+pub const DEFAULT_SUPERUSER: &str = "root";
+
+/// Rows a statement read from its tables, as reported by the slow query log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadStats {
+    /// Candidate rows read before WHERE filtering
+    pub rows_examined: u64,
+    /// Whether any of the reads went through an index
+    pub index_used: bool,
+}
+
+/// Severity of an entry listed by SHOW WARNINGS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningLevel {
+    Note,
+    Warning,
+    Error,
+}
+
+impl std::fmt::Display for WarningLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WarningLevel::Note => write!(f, "Note"),
+            WarningLevel::Warning => write!(f, "Warning"),
+            WarningLevel::Error => write!(f, "Error"),
+        }
+    }
+}
+
+/// A condition raised by a statement, listed by SHOW WARNINGS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub level: WarningLevel,
+    /// MySQL error code
+    pub code: u16,
+    pub message: String,
+}
+
 /// Session state for a client connection
 pub struct Session {
     /// Active transaction ID (None if auto-commit mode)
     pub txn_id: Option<TxnId>,
+    /// MySQL's autocommit. When off, the first INSERT, UPDATE, DELETE or LOAD
+    /// DATA starts a transaction that lasts until COMMIT or ROLLBACK.
+    pub autocommit: bool,
     /// Engines that have been modified during the current transaction
     pub modified_engines: HashSet<EngineType>,
     /// Prepared statements keyed by statement ID
@@ -60,16 +130,99 @@ pub struct Session {
     pub next_stmt_id: u32,
     /// Last insert ID generated in this session
     pub last_insert_id: u64,
+    /// Server-assigned connection ID (0 for sessions not bound to a connection)
+    pub connection_id: u32,
+    /// User the connection logged in as, whose grants are checked (None for
+    /// sessions not bound to a login, which are not checked)
+    pub user: Option<String>,
+    /// Isolation level for transactions started in this session
+    pub isolation_level: IsolationLevel,
+    /// Whether foreign keys are enforced (MySQL's FOREIGN_KEY_CHECKS)
+    pub foreign_key_checks: bool,
+    /// SELECTs running longer than this many milliseconds fail with error 3024 (None = no limit)
+    pub statement_timeout_ms: Option<u64>,
+    /// How long a statement waits for a row lock before failing with error 1205
+    pub lock_wait_timeout_ms: u64,
+    /// One-shot level set by SET TRANSACTION, consumed by the next BEGIN
+    pub(crate) next_isolation_level: Option<IsolationLevel>,
+    /// Granite rows captured on first read, per table (REPEATABLE READ transactions only)
+    pub(crate) read_snapshot: Option<HashMap<String, Vec<Row>>>,
+    /// User-defined variables set with `SET @name = ...`, keyed by lowercase name
+    pub user_variables: HashMap<String, Value>,
+    /// SQL mode set with `SET sql_mode = ...`; decides how double quotes are lexed
+    pub sql_mode: SqlMode,
+    /// Deadline and timeout of the statement being executed
+    pub(crate) statement_deadline: Option<(std::time::Instant, u64)>,
+    /// Rows read since the last `take_read_stats`, and whether an index was used
+    pub(crate) rows_examined: AtomicU64,
+    pub(crate) index_used: AtomicBool,
+    /// Conditions raised by the last statement, kept until the next one starts
+    pub(crate) warnings: Mutex<Vec<Warning>>,
+    /// 1-based row of the statement being written, named in warnings
+    pub(crate) current_row: u64,
 }
 
 impl Session {
     pub fn new() -> Self {
         Self { 
             txn_id: None,
+            autocommit: true,
             modified_engines: HashSet::new(),
             prepared_statements: HashMap::new(),
             next_stmt_id: 1,
             last_insert_id: 0,
+            connection_id: 0,
+            user: None,
+            isolation_level: IsolationLevel::default(),
+            foreign_key_checks: true,
+            statement_timeout_ms: None,
+            lock_wait_timeout_ms: DEFAULT_LOCK_WAIT_TIMEOUT_MS,
+            next_isolation_level: None,
+            read_snapshot: None,
+            user_variables: HashMap::new(),
+            sql_mode: SqlMode::default(),
+            statement_deadline: None,
+            rows_examined: AtomicU64::new(0),
+            index_used: AtomicBool::new(false),
+            warnings: Mutex::new(Vec::new()),
+            current_row: 0,
+        }
+    }
+
+    /// Count `rows` read from a table, through an index when `index_used`
+    pub(crate) fn record_read(&self, rows: usize, index_used: bool) {
+        self.rows_examined.fetch_add(rows as u64, Ordering::Relaxed);
+        if index_used {
+            self.index_used.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Record a condition of the running statement
+    pub(crate) fn warn(&self, level: WarningLevel, code: u16, message: String) {
+        self.warnings.lock().unwrap().push(Warning { level, code, message });
+    }
+
+    /// Conditions raised by the last statement
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.warnings.lock().unwrap().clone()
+    }
+
+    /// Number of conditions raised by the last statement, as reported in OK packets
+    pub fn warning_count(&self) -> u16 {
+        self.warnings.lock().unwrap().len().min(u16::MAX as usize) as u16
+    }
+
+    /// Forget the previous statement's conditions as a new statement starts
+    pub(crate) fn clear_warnings(&mut self) {
+        self.warnings.get_mut().unwrap().clear();
+        self.current_row = 0;
+    }
+
+    /// Rows read since the previous call, resetting the count
+    pub fn take_read_stats(&mut self) -> ReadStats {
+        ReadStats {
+            rows_examined: std::mem::take(self.rows_examined.get_mut()),
+            index_used: std::mem::take(self.index_used.get_mut()),
         }
     }
 }
@@ -88,6 +241,24 @@ pub struct Executor {
     pub(crate) storage: std::sync::Arc<StorageEngine>,
     pub(crate) txn_manager: std::sync::Arc<TransactionManager>,
     pub(crate) handlers: HashMap<EngineType, Arc<dyn EngineHandler>>,
+    /// The Sandstone engine, also registered in `handlers`, when enabled
+    pub(crate) sandstone: Option<Arc<crate::engines::SandstoneEngine>>,
+    /// Upper bound on memory buffered by a single query (None = unlimited)
+    pub(crate) max_query_memory_bytes: Option<usize>,
+    /// Row file format for tables created without a STORAGE FORMAT option
+    pub(crate) default_storage_format: StorageFormat,
+    /// Engine for tables created without an ENGINE option
+    pub(crate) default_engine: EngineType,
+    /// Cache of autocommit SELECT results (None = disabled)
+    pub(crate) query_cache: Option<cache::QueryCache>,
+    /// Background thread checkpointing the WAL once it outgrows its threshold
+    pub(crate) checkpointer: crate::engines::granite::Checkpointer,
+    /// Statement counters reported by SHOW STATUS
+    pub(crate) stats: stats::StatementStats,
+    /// Reject statements that modify data or schema (read-only replica mode)
+    pub(crate) read_only: bool,
+    /// User exempt from privilege checks, who alone may run DDL and GRANT
+    pub(crate) superuser: String,
 }
 
 impl Executor {
@@ -96,6 +267,49 @@ impl Executor {
         &self.storage
     }
 
+    /// Get reference to the Granite transaction manager
+    pub fn txn_manager(&self) -> &std::sync::Arc<TransactionManager> {
+        &self.txn_manager
+    }
+
+    /// Limit the memory a single query may buffer for sorting, grouping and joins.
+    /// Queries exceeding it fail with error 1038 instead of growing unbounded.
+    pub fn set_max_query_memory_bytes(&mut self, limit: Option<usize>) {
+        self.max_query_memory_bytes = limit;
+    }
+
+    /// Set the row file format used by CREATE TABLE when no STORAGE FORMAT is given
+    pub fn set_default_storage_format(&mut self, format: StorageFormat) {
+        self.default_storage_format = format;
+    }
+
+    /// Set the engine used by CREATE TABLE when no ENGINE is given
+    pub fn set_default_engine(&mut self, engine: EngineType) {
+        self.default_engine = engine;
+    }
+
+    /// Cache up to `max_entries` SELECT results, flushed whenever a statement
+    /// modifies data or schema. `None` or `Some(0)` disables the cache.
+    pub fn set_query_cache_entries(&mut self, max_entries: Option<usize>) {
+        self.query_cache = max_entries.filter(|n| *n > 0).map(cache::QueryCache::new);
+    }
+
+    /// Reject INSERT, UPDATE, DELETE and every DDL statement with error 1290,
+    /// for serving reads from a copy of another server's data directory
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Exempt `user` from privilege checks and reserve DDL and GRANT to it
+    pub fn set_superuser(&mut self, user: impl Into<String>) {
+        self.superuser = user.into();
+    }
+
+    /// Query cache counters, if the cache is enabled
+    pub fn query_cache_stats(&self) -> Option<QueryCacheStats> {
+        self.query_cache.as_ref().map(|cache| cache.stats())
+    }
+
     /// Create a new executor with only Granite engine (backward compatible)
     pub fn new(storage: StorageEngine, txn_manager: TransactionManager) -> Self {
         let storage = std::sync::Arc::new(storage);
@@ -110,10 +324,24 @@ impl Executor {
             ))
         );
 
+        let checkpointer = crate::engines::granite::Checkpointer::start(
+            Arc::clone(&txn_manager),
+            Arc::clone(&storage),
+        );
+
         Self {
             storage,
             txn_manager,
             handlers,
+            sandstone: None,
+            max_query_memory_bytes: None,
+            default_storage_format: StorageFormat::default(),
+            default_engine: EngineType::default(),
+            query_cache: None,
+            checkpointer,
+            stats: stats::StatementStats::new(),
+            read_only: false,
+            superuser: DEFAULT_SUPERUSER.to_string(),
         }
     }
 
@@ -138,19 +366,33 @@ impl Executor {
         );
         
         // Register Sandstone
-        let sandstone = crate::engines::SandstoneEngine::new(
+        let sandstone = Arc::new(crate::engines::SandstoneEngine::new(
             std::sync::Arc::clone(&storage),
             sandstone_config,
-        )?;
+        )?);
         handlers.insert(
             EngineType::Sandstone,
-            Arc::new(sandstone)
+            Arc::clone(&sandstone) as Arc<dyn EngineHandler>
         );
         
+        let checkpointer = crate::engines::granite::Checkpointer::start(
+            Arc::clone(&txn_manager),
+            Arc::clone(&storage),
+        );
+
         Ok(Self {
             storage,
             txn_manager,
             handlers,
+            sandstone: Some(sandstone),
+            max_query_memory_bytes: None,
+            default_storage_format: StorageFormat::default(),
+            default_engine: EngineType::default(),
+            query_cache: None,
+            checkpointer,
+            stats: stats::StatementStats::new(),
+            read_only: false,
+            superuser: DEFAULT_SUPERUSER.to_string(),
         })
     }
     
@@ -195,34 +437,252 @@ impl Executor {
 
     /// Execute a SQL statement
     pub fn execute(&self, stmt: Statement, session: &mut Session) -> Result<QueryResult> {
-        let result = match stmt {
+        let result = self.execute_with(stmt, session, |stmt, session| self.execute_statement(stmt, session))?;
+
+        // Update session state from result
+        if let QueryResult::Modified { last_insert_id, .. } = &result {
+            if *last_insert_id > 0 {
+                session.last_insert_id = *last_insert_id;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Run `stmt` with `run`, wrapped in what every statement goes through
+    /// whichever path executes it: warnings reset, read-only and privilege
+    /// checks, the implicit transaction of autocommit off, statistics, the
+    /// statement timeout, and the error recorded for SHOW WARNINGS
+    pub(crate) fn execute_with<T>(
+        &self,
+        stmt: Statement,
+        session: &mut Session,
+        run: impl FnOnce(Statement, &mut Session) -> Result<T>,
+    ) -> Result<T> {
+        // SHOW WARNINGS lists the previous statement's conditions instead of clearing them
+        if !matches!(stmt, Statement::ShowWarnings) {
+            session.clear_warnings();
+        }
+        let result = self.execute_checked(stmt, session, run);
+        if let Err(e) = &result {
+            session.warn(WarningLevel::Error, e.mysql_error_code(), e.to_string());
+        }
+        result
+    }
+
+    fn execute_checked<T>(
+        &self,
+        stmt: Statement,
+        session: &mut Session,
+        run: impl FnOnce(Statement, &mut Session) -> Result<T>,
+    ) -> Result<T> {
+        if self.read_only && Self::modifies_data(&stmt) {
+            return Err(MiniSqlError::read_only());
+        }
+        self.check_privileges(&stmt, session)?;
+        if !session.autocommit
+            && session.txn_id.is_none()
+            && (matches!(stmt, Statement::Insert(_) | Statement::Update(_) | Statement::Delete(_) | Statement::LoadData(_))
+                || matches!(&stmt, Statement::Select(select) if select.for_update))
+        {
+            self.execute_begin(session)?;
+        }
+        let read_only = Self::is_read_only(&stmt);
+        let autocommit_write = session.txn_id.is_none()
+            && matches!(stmt, Statement::Insert(_) | Statement::Update(_) | Statement::Delete(_) | Statement::LoadData(_));
+        self.stats.record(&stmt);
+        session.statement_deadline = timeout::deadline_from_now(session.statement_timeout_ms);
+        let result = run(stmt, session);
+        session.statement_deadline = None;
+        let result = self.rollback_on_abort(result, session);
+        // An autocommit write is committed once it returns, so semi-sync
+        // waits for a replica here as it does in COMMIT
+        let result = match result {
+            Ok(value) if autocommit_write => self.txn_manager.replicate_autocommit().map(|()| value),
+            result => result,
+        };
+        // A failed statement may still have applied part of its changes
+        if !read_only {
+            if let Some(cache) = &self.query_cache {
+                cache.invalidate();
+            }
+        }
+        result
+    }
+
+    /// Parse and execute a SQL string, answering cacheable SELECTs from the
+    /// query cache when it is enabled
+    pub fn execute_sql(&self, sql: &str, session: &mut Session) -> Result<QueryResult> {
+        match Parser::parse_with_mode(sql, &session.sql_mode)? {
+            Statement::Select(select) => match self.query_cache_key(sql, session) {
+                Some(key) => self.execute_cached_select(&key, select, session),
+                None => self.execute(Statement::Select(select), session),
+            },
+            stmt => self.execute(stmt, session),
+        }
+    }
+
+    /// Cache key for a SELECT whose result may be served from the query cache:
+    /// the cache is enabled, the session is in autocommit mode and the query
+    /// only depends on table contents
+    pub fn query_cache_key(&self, sql: &str, session: &Session) -> Option<String> {
+        self.query_cache.as_ref()?;
+        if session.txn_id.is_some() {
+            return None;
+        }
+        cache::cache_key(sql, session.sql_mode.ansi_quotes())
+    }
+
+    /// Execute a SELECT through the query cache, storing the result on a miss
+    pub fn execute_cached_select(&self, key: &str, select: SelectStmt, session: &mut Session) -> Result<QueryResult> {
+        let Some(cache) = &self.query_cache else {
+            return self.execute(Statement::Select(select), session);
+        };
+        self.execute_with(Statement::Select(select), session, |stmt, session| {
+            if let Some(result) = cache.get(key) {
+                return Ok(QueryResult::Select((*result).clone()));
+            }
+            let generation = cache.generation();
+            let result = self.execute_statement(stmt, session)?;
+            if let QueryResult::Select(result_set) = &result {
+                cache.insert(key.to_string(), result_set.clone(), generation);
+            }
+            Ok(result)
+        })
+    }
+
+    /// Whether `stmt` leaves the data as it was, so the query cache stays valid
+    fn is_read_only(stmt: &Statement) -> bool {
+        match stmt {
+            Statement::SetVariables(statements) => statements.iter().all(Self::is_read_only),
+            _ => matches!(
+                stmt,
+                Statement::Select(_)
+                    | Statement::ShowTables
+                    | Statement::Describe(_)
+                    | Statement::ShowCreateTable(_)
+                    | Statement::ShowIndex(_)
+                    | Statement::ShowStatus
+                    | Statement::ShowWarnings
+                    | Statement::Explain(_)
+                    | Statement::Begin
+                    | Statement::Savepoint(_)
+                    | Statement::SetIsolationLevel { .. }
+                    | Statement::SetUserVariables(_)
+                    | Statement::SetSqlMode(_)
+            ),
+        }
+    }
+
+    /// Whether `stmt` changes rows or schema, and so is refused in read-only mode
+    fn modifies_data(stmt: &Statement) -> bool {
+        matches!(
+            stmt,
+            Statement::Insert(_)
+                | Statement::LoadData(_)
+                | Statement::Update(_)
+                | Statement::Delete(_)
+                | Statement::CreateTable(_)
+                | Statement::CreateIndex(_)
+                | Statement::DropTable { .. }
+                | Statement::DropIndex { .. }
+                | Statement::TruncateTable(_)
+                | Statement::AlterTable(_)
+                | Statement::Vacuum(_)
+                | Statement::Grant(_)
+                | Statement::Revoke(_)
+        )
+    }
+
+    /// Roll back the session's transaction if the statement ran out of time
+    /// or was chosen as a deadlock victim, so no partial work of the
+    /// transaction survives and the locks it held are released
+    pub(crate) fn rollback_on_abort<T>(&self, result: Result<T>, session: &mut Session) -> Result<T> {
+        if let Err(e) = &result {
+            if (e.is_statement_timeout() || e.is_deadlock()) && session.txn_id.is_some() {
+                self.execute_rollback(session)?;
+                if let Some(cache) = &self.query_cache {
+                    cache.invalidate();
+                }
+            }
+        }
+        result
+    }
+
+    fn execute_statement(&self, stmt: Statement, session: &mut Session) -> Result<QueryResult> {
+        match stmt {
             Statement::Begin => self.execute_begin(session),
             Statement::Commit => self.execute_commit(session),
             Statement::Rollback => self.execute_rollback(session),
+            Statement::Savepoint(name) => self.execute_savepoint(&name, session),
+            Statement::RollbackToSavepoint(name) => self.execute_rollback_to_savepoint(&name, session),
+            Statement::ReleaseSavepoint(name) => self.execute_release_savepoint(&name, session),
+            Statement::SetIsolationLevel { level, scope } => self.execute_set_isolation_level(level, scope, session),
+            Statement::SetUserVariables(assignments) => self.execute_set_user_variables(assignments, session),
+            Statement::SetSqlMode(mode) => {
+                session.sql_mode = mode;
+                Ok(QueryResult::Ok)
+            }
+            Statement::SetAutocommit(enabled) => self.execute_set_autocommit(enabled, session),
+            Statement::SetVariables(statements) => {
+                for stmt in statements {
+                    self.execute_statement(stmt, session)?;
+                }
+                Ok(QueryResult::Ok)
+            }
             Statement::CreateTable(create) => self.execute_create_table(create, session),
             Statement::CreateIndex(create_idx) => self.execute_create_index(create_idx, session),
-            Statement::DropTable(name) => self.execute_drop_table(&name, session),
-            Statement::DropIndex(name) => self.execute_drop_index(&name, session),
+            Statement::DropTable { name, if_exists } => self.execute_drop_table(&name, if_exists, session),
+            Statement::DropIndex { name, if_exists } => self.execute_drop_index(&name, if_exists, session),
             Statement::TruncateTable(name) => self.execute_truncate_table(&name, session),
             Statement::AlterTable(alter) => self.execute_alter_table(alter, session),
             Statement::Checkpoint => self.execute_checkpoint(session),
-            Statement::Vacuum => self.execute_vacuum(session),
-            Statement::Select(select) => self.execute_select(select, session),
-            Statement::Insert(insert) => self.execute_insert(insert, session),
-            Statement::Update(update) => self.execute_update(update, session),
-            Statement::Delete(delete) => self.execute_delete(delete, session),
+            Statement::FlushTables => self.execute_flush_tables(session),
+            Statement::Vacuum(storage_format) => self.execute_vacuum(storage_format, session),
+            Statement::Grant(grant) => self.execute_grant(grant),
+            Statement::Revoke(revoke) => self.execute_revoke(revoke),
+            Statement::Select(mut select) => {
+                self.resolve_select_subqueries(&mut select, session)?;
+                if select.for_update {
+                    return self.execute_locking_select(select, session);
+                }
+                self.snapshot_select_tables(&select, session)?;
+                self.execute_select(select, session)
+            }
+            Statement::Insert(mut insert) => {
+                let assignments = insert.on_duplicate_key_update.iter_mut().map(|(_, expr)| expr);
+                for expr in insert.values.iter_mut().flatten().chain(assignments) {
+                    self.resolve_subqueries(expr, session)?;
+                }
+                self.execute_insert(insert, session)
+            }
+            Statement::Update(mut update) => {
+                if let Some(where_expr) = &mut update.where_clause {
+                    self.resolve_subqueries(where_expr, session)?;
+                }
+                for (_, expr) in &mut update.assignments {
+                    self.resolve_subqueries(expr, session)?;
+                }
+                self.execute_update(update, session)
+            }
+            Statement::LoadData(load) => self.execute_load_data(load, session),
+            Statement::Delete(mut delete) => {
+                if let Some(where_expr) = &mut delete.where_clause {
+                    self.resolve_subqueries(where_expr, session)?;
+                }
+                self.execute_delete(delete, session)
+            }
             Statement::ShowTables => self.execute_show_tables(),
             Statement::Describe(table) => self.execute_describe(&table),
-        }?;
-
-        // Update session state from result
-        if let QueryResult::Modified { last_insert_id, .. } = &result {
-            if *last_insert_id > 0 {
-                session.last_insert_id = *last_insert_id;
-            }
+            Statement::ShowCreateTable(table) => self.execute_show_create_table(&table),
+            Statement::ShowIndex(table) => self.execute_show_index(&table),
+            Statement::ShowStatus => self.execute_show_status(),
+            Statement::ShowWarnings => self.execute_show_warnings(session),
+            Statement::Explain(inner) => match *inner {
+                Statement::Select(select) => self.execute_explain(&select, session),
+                _ => Err(MiniSqlError::Syntax("EXPLAIN supports only SELECT".into())),
+            },
         }
-
-        Ok(result)
     }
 }
 
@@ -533,4 +993,37 @@ mod tests {
         let res = executor.execute(stmt, &mut session).unwrap();
         assert!(matches!(res, QueryResult::Ok));
     }
+
+    #[test]
+    fn test_query_memory_limit_rejects_large_group_by() {
+        let (mut executor, mut session, _temp_dir) = setup_executor();
+        executor.set_max_query_memory_bytes(Some(16 * 1024));
+
+        executor.execute(Parser::parse("CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT, payload TEXT)").unwrap(), &mut session).unwrap();
+        for i in 0..200 {
+            let sql = format!("INSERT INTO events VALUES ({}, 'k{}', '{}')", i, i % 50, "x".repeat(100));
+            executor.execute(Parser::parse(&sql).unwrap(), &mut session).unwrap();
+        }
+
+        // Small queries still run under the limit
+        let stmt = Parser::parse("SELECT kind FROM events WHERE id = 1").unwrap();
+        assert!(executor.execute(stmt, &mut session).is_ok());
+
+        let stmt = Parser::parse("SELECT kind, COUNT(*) FROM events GROUP BY kind").unwrap();
+        let err = executor.execute(stmt, &mut session).unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1038);
+        assert!(err.to_string().starts_with("Out of sort memory"));
+
+        let stmt = Parser::parse("SELECT * FROM events ORDER BY payload").unwrap();
+        let err = executor.execute(stmt, &mut session).unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1038);
+
+        // Lifting the limit lets the same query through
+        executor.set_max_query_memory_bytes(None);
+        let stmt = Parser::parse("SELECT kind, COUNT(*) FROM events GROUP BY kind").unwrap();
+        match executor.execute(stmt, &mut session).unwrap() {
+            QueryResult::Select(rs) => assert_eq!(rs.rows.len(), 50),
+            _ => panic!("Expected Select"),
+        }
+    }
 }
@@ -0,0 +1,142 @@
+//! ORDER BY support shared by the SELECT, UPDATE and DELETE paths
+//!
+//! - `ORDER BY n` sorts by the n-th (1-based) column of the select list, with
+//!   `*` and `t.*` counted as the columns they expand to.
+//! - NULLs sort first for ASC and last for DESC unless the clause says
+//!   `NULLS FIRST` or `NULLS LAST`; the placement is not flipped by `DESC`.
+//! - Keys are compared clause by clause and the sort is stable, so rows with
+//!   equal keys keep their scan order.
+
+use std::cmp::Ordering;
+
+use crate::error::{ColumnContext, MiniSqlError, Result};
+use crate::parser::{Expr, OrderByClause, SelectColumn, SortOrder};
+use crate::executor::evaluator;
+use crate::types::{Row, TableSchema, Value};
+
+/// Replace positional references (`ORDER BY 2`) with the expression of that
+/// select-list column. `tables` are the (alias, schema) pairs `*` expands to.
+pub(crate) fn resolve_order_by_positions(
+    order_by: &[OrderByClause],
+    columns: &[SelectColumn],
+    tables: &[(&str, &TableSchema)],
+) -> Result<Vec<OrderByClause>> {
+    order_by
+        .iter()
+        .map(|clause| {
+            let Expr::Literal(Value::Integer(position)) = clause.expr else {
+                return Ok(clause.clone());
+            };
+            let expr = select_list_exprs(columns, tables)?
+                .into_iter()
+                .nth((position as usize).wrapping_sub(1))
+                .filter(|_| position >= 1)
+                .ok_or_else(|| MiniSqlError::unknown_column(&position.to_string(), ColumnContext::OrderClause))?;
+            Ok(OrderByClause { expr, ..clause.clone() })
+        })
+        .collect()
+}
+
+/// One expression per output column of a select list
+fn select_list_exprs(columns: &[SelectColumn], tables: &[(&str, &TableSchema)]) -> Result<Vec<Expr>> {
+    let table_columns = |alias: &str, schema: &TableSchema| -> Vec<Expr> {
+        schema.columns.iter()
+            .map(|c| Expr::Column { table: Some(alias.to_string()), name: c.name.clone() })
+            .collect()
+    };
+
+    let mut exprs = Vec::new();
+    for col in columns {
+        match col {
+            SelectColumn::Star => {
+                for (alias, schema) in tables {
+                    exprs.extend(table_columns(alias, schema));
+                }
+            }
+            SelectColumn::QualifiedStar { table } => {
+                let (alias, schema) = tables.iter()
+                    .find(|(alias, schema)| table.eq_ignore_ascii_case(alias) || table.eq_ignore_ascii_case(&schema.name))
+                    .ok_or_else(|| MiniSqlError::unknown_table_in_field_list(table))?;
+                exprs.extend(table_columns(alias, schema));
+            }
+            SelectColumn::Expr { expr, .. } => exprs.push(expr.clone()),
+        }
+    }
+    Ok(exprs)
+}
+
+/// Sort the rows of a single table by `order_by`, evaluating each key once per row
+pub(crate) fn sort_table_rows(
+    order_by: &[OrderByClause],
+    rows: Vec<Row>,
+    schema: &TableSchema,
+    table_alias: &str,
+    last_insert_id: u64,
+) -> Result<Vec<Row>> {
+    let mut keyed = rows.into_iter()
+        .map(|row| {
+            let keys = order_by.iter()
+                .map(|clause| evaluator::eval_expr_simple(&clause.expr, &row, schema, table_alias, last_insert_id))
+                .collect::<Result<Vec<_>>>()?;
+            Ok((keys, row))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    keyed.sort_by(|(a, _), (b, _)| compare_order_keys(order_by, a, b));
+    Ok(keyed.into_iter().map(|(_, row)| row).collect())
+}
+
+/// Compare two rows by their evaluated ORDER BY keys (one value per clause)
+pub(crate) fn compare_order_keys(order_by: &[OrderByClause], a: &[Value], b: &[Value]) -> Ordering {
+    for (clause, (val_a, val_b)) in order_by.iter().zip(a.iter().zip(b)) {
+        let nulls = if clause.nulls_first() { Ordering::Less } else { Ordering::Greater };
+        let cmp = match (val_a.is_null(), val_b.is_null()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => nulls,
+            (false, true) => nulls.reverse(),
+            (false, false) => {
+                let cmp = val_a.partial_cmp(val_b).unwrap_or(Ordering::Equal);
+                match clause.direction {
+                    SortOrder::Asc => cmp,
+                    SortOrder::Desc => cmp.reverse(),
+                }
+            }
+        };
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::NullsOrder;
+
+    fn clause(direction: SortOrder, nulls: Option<NullsOrder>) -> OrderByClause {
+        OrderByClause { expr: Expr::Literal(Value::Null), direction, nulls }
+    }
+
+    fn sorted(order_by: &[OrderByClause], mut keys: Vec<Vec<Value>>) -> Vec<Vec<Value>> {
+        keys.sort_by(|a, b| compare_order_keys(order_by, a, b));
+        keys
+    }
+
+    #[test]
+    fn test_default_null_placement() {
+        let keys = vec![vec![Value::Integer(2)], vec![Value::Null], vec![Value::Integer(1)]];
+        assert_eq!(sorted(&[clause(SortOrder::Asc, None)], keys.clone()),
+            vec![vec![Value::Null], vec![Value::Integer(1)], vec![Value::Integer(2)]]);
+        assert_eq!(sorted(&[clause(SortOrder::Desc, None)], keys),
+            vec![vec![Value::Integer(2)], vec![Value::Integer(1)], vec![Value::Null]]);
+    }
+
+    #[test]
+    fn test_explicit_null_placement_ignores_direction() {
+        let keys = vec![vec![Value::Integer(2)], vec![Value::Null], vec![Value::Integer(1)]];
+        assert_eq!(sorted(&[clause(SortOrder::Desc, Some(NullsOrder::First))], keys.clone()),
+            vec![vec![Value::Null], vec![Value::Integer(2)], vec![Value::Integer(1)]]);
+        assert_eq!(sorted(&[clause(SortOrder::Asc, Some(NullsOrder::Last))], keys),
+            vec![vec![Value::Integer(1)], vec![Value::Integer(2)], vec![Value::Null]]);
+    }
+}
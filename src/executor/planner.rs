@@ -0,0 +1,384 @@
+//! Access path selection for single-table reads
+//!
+//! `plan_table_access` looks at a WHERE clause and the table's indexes and
+//! decides how candidate rows are read. Planning never touches row data, and
+//! the executor evaluates the full WHERE clause on every candidate, so a plan
+//! only has to produce a superset of the matching rows.
+
+use std::ops::Bound;
+
+use crate::datetime::Timestamp;
+use crate::parser::{BinaryOperator, Expr};
+use crate::storage::StorageEngine;
+use crate::types::{DataType, IndexMetadata, TableSchema, Value};
+use crate::executor::evaluator;
+
+/// How the rows of one table are read
+#[derive(Debug, Clone)]
+pub(crate) enum AccessPlan {
+    /// Read every row
+    FullScan,
+    /// Look up the rows whose leading index columns equal `values`, one value
+    /// per column in index order
+    IndexEquality {
+        index: IndexMetadata,
+        values: Vec<Value>,
+    },
+    /// Read the rows whose value in the index's leading column lies within
+    /// `lower` and `upper`
+    IndexRange {
+        index: IndexMetadata,
+        lower: Bound<Value>,
+        upper: Bound<Value>,
+    },
+    /// Read each branch of an OR through its own plan and merge the rows,
+    /// keeping each row once
+    IndexUnion {
+        branches: Vec<AccessPlan>,
+    },
+}
+
+impl AccessPlan {
+    /// Access type as reported by EXPLAIN
+    pub(crate) fn access_type(&self) -> &'static str {
+        match self {
+            AccessPlan::FullScan => "ALL",
+            AccessPlan::IndexEquality { .. } => "index",
+            AccessPlan::IndexRange { .. } => "range",
+            AccessPlan::IndexUnion { .. } => "index_merge",
+        }
+    }
+
+    /// The indexes the plan reads, in the order it reads them
+    pub(crate) fn indexes(&self) -> Vec<&IndexMetadata> {
+        match self {
+            AccessPlan::FullScan => Vec::new(),
+            AccessPlan::IndexEquality { index, .. } | AccessPlan::IndexRange { index, .. } => vec![index],
+            AccessPlan::IndexUnion { branches } => branches.iter().flat_map(AccessPlan::indexes).collect(),
+        }
+    }
+}
+
+/// Choose how to read `schema`'s rows for `where_expr`.
+///
+/// Equalities on a prefix of an index's columns are preferred, taking the
+/// index that binds the most columns. Otherwise comparisons against the
+/// leading column of an index make a range. Failing both, an OR whose
+/// branches can each be planned through an index becomes a union of those
+/// plans. Anything else is a full scan. The caller decides whether the
+/// table's engine supports indexes at all.
+pub(crate) fn plan_table_access(
+    where_expr: &Expr,
+    schema: &TableSchema,
+    table_alias: &str,
+    storage: &StorageEngine,
+) -> AccessPlan {
+    let mut indexes: Vec<IndexMetadata> = storage.list_indexes()
+        .into_iter()
+        .filter(|idx| idx.table_name == schema.name)
+        .collect();
+    // Primary key first, then by name, so ties are broken the same way every time
+    indexes.sort_by(|a, b| b.is_primary.cmp(&a.is_primary).then_with(|| a.name.cmp(&b.name)));
+    plan_with_indexes(where_expr, schema, table_alias, &indexes)
+}
+
+/// `plan_table_access` over `indexes`, already in preference order
+fn plan_with_indexes(where_expr: &Expr, schema: &TableSchema, table_alias: &str, indexes: &[IndexMetadata]) -> AccessPlan {
+    let mut conjuncts = Vec::new();
+    collect_conjuncts(where_expr, &mut conjuncts);
+    let comparisons: Vec<(String, BinaryOperator, Value)> = conjuncts.iter()
+        .filter_map(|expr| column_comparison(expr, schema, table_alias))
+        .collect();
+
+    let mut best: Option<(IndexMetadata, Vec<Value>)> = None;
+    for index in indexes {
+        let values: Vec<Value> = index.columns.iter()
+            .map_while(|col| {
+                comparisons.iter()
+                    .find(|(c, op, _)| *op == BinaryOperator::Equal && c.eq_ignore_ascii_case(col))
+                    .map(|(_, _, v)| v.clone())
+            })
+            .collect();
+        if !values.is_empty() && best.as_ref().is_none_or(|(_, best_values)| values.len() > best_values.len()) {
+            best = Some((index.clone(), values));
+        }
+    }
+    if let Some((index, values)) = best {
+        return AccessPlan::IndexEquality { index, values };
+    }
+
+    for index in indexes {
+        let mut lower = Bound::Unbounded;
+        let mut upper = Bound::Unbounded;
+        for (column, op, value) in &comparisons {
+            if !column.eq_ignore_ascii_case(&index.columns[0]) {
+                continue;
+            }
+            // The first bound on each side is enough: WHERE is re-checked later
+            match op {
+                BinaryOperator::GreaterThan if lower == Bound::Unbounded => lower = Bound::Excluded(value.clone()),
+                BinaryOperator::GreaterThanOrEqual if lower == Bound::Unbounded => lower = Bound::Included(value.clone()),
+                BinaryOperator::LessThan if upper == Bound::Unbounded => upper = Bound::Excluded(value.clone()),
+                BinaryOperator::LessThanOrEqual if upper == Bound::Unbounded => upper = Bound::Included(value.clone()),
+                _ => {}
+            }
+        }
+        if lower != Bound::Unbounded || upper != Bound::Unbounded {
+            return AccessPlan::IndexRange { index: index.clone(), lower, upper };
+        }
+    }
+
+    // Any OR conjunct narrows the rows, provided none of its branches needs a scan
+    for conjunct in &conjuncts {
+        if !matches!(conjunct, Expr::BinaryOp { op: BinaryOperator::Or, .. }) {
+            continue;
+        }
+        let mut disjuncts = Vec::new();
+        collect_disjuncts(conjunct, &mut disjuncts);
+        let branches: Vec<AccessPlan> = disjuncts.into_iter()
+            .map(|branch| plan_with_indexes(branch, schema, table_alias, indexes))
+            .collect();
+        if branches.iter().all(|branch| !matches!(branch, AccessPlan::FullScan)) {
+            return AccessPlan::IndexUnion { branches };
+        }
+    }
+
+    AccessPlan::FullScan
+}
+
+/// Whether `value` lies within `lower` and `upper`, compared as WHERE does
+pub(crate) fn within_bounds(value: &Value, lower: &Bound<Value>, upper: &Bound<Value>) -> bool {
+    let holds = |op, bound: &Value| {
+        matches!(evaluator::apply_binary_op(value, op, bound), Ok(Value::Boolean(true)))
+    };
+    let above_lower = match lower {
+        Bound::Included(bound) => holds(BinaryOperator::GreaterThanOrEqual, bound),
+        Bound::Excluded(bound) => holds(BinaryOperator::GreaterThan, bound),
+        Bound::Unbounded => true,
+    };
+    let below_upper = match upper {
+        Bound::Included(bound) => holds(BinaryOperator::LessThanOrEqual, bound),
+        Bound::Excluded(bound) => holds(BinaryOperator::LessThan, bound),
+        Bound::Unbounded => true,
+    };
+    above_lower && below_upper
+}
+
+/// Flatten the AND-connected conjuncts of `expr`
+fn collect_conjuncts<'a>(expr: &'a Expr, result: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::BinaryOp { left, op: BinaryOperator::And, right } => {
+            collect_conjuncts(left, result);
+            collect_conjuncts(right, result);
+        }
+        other => result.push(other),
+    }
+}
+
+/// Flatten the OR-connected branches of `expr`
+fn collect_disjuncts<'a>(expr: &'a Expr, result: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::BinaryOp { left, op: BinaryOperator::Or, right } => {
+            collect_disjuncts(left, result);
+            collect_disjuncts(right, result);
+        }
+        other => result.push(other),
+    }
+}
+
+/// A conjunct of the form `column <op> literal` (or `literal <op> column`) on
+/// `schema`, as (column, operator with the column on the left, literal)
+fn column_comparison(expr: &Expr, schema: &TableSchema, table_alias: &str) -> Option<(String, BinaryOperator, Value)> {
+    let Expr::BinaryOp { left, op, right } = expr else {
+        return None;
+    };
+    let (column, value, op) = match (left.as_ref(), right.as_ref()) {
+        (Expr::Column { table, name }, Expr::Literal(value)) => ((table, name), value, *op),
+        (Expr::Literal(value), Expr::Column { table, name }) => ((table, name), value, flip(op)?),
+        _ => return None,
+    };
+    if !matches!(
+        op,
+        BinaryOperator::Equal
+            | BinaryOperator::LessThan
+            | BinaryOperator::LessThanOrEqual
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::GreaterThanOrEqual
+    ) {
+        return None;
+    }
+    let (table, name) = column;
+    if !column_matches_table(table.as_ref(), table_alias, &schema.name) {
+        return None;
+    }
+    let idx = schema.find_column(name)?;
+    Some((name.clone(), op, index_lookup_value(value, &schema.columns[idx].data_type)?))
+}
+
+/// `op` with its operands swapped
+fn flip(op: &BinaryOperator) -> Option<BinaryOperator> {
+    Some(match op {
+        BinaryOperator::Equal => BinaryOperator::Equal,
+        BinaryOperator::LessThan => BinaryOperator::GreaterThan,
+        BinaryOperator::LessThanOrEqual => BinaryOperator::GreaterThanOrEqual,
+        BinaryOperator::GreaterThan => BinaryOperator::LessThan,
+        BinaryOperator::GreaterThanOrEqual => BinaryOperator::LessThanOrEqual,
+        _ => return None,
+    })
+}
+
+/// Literal as it must be looked up in an index on a column of `data_type`:
+/// numeric columns compare with strings numerically and DATE columns with
+/// strings as dates, and BLOB columns with strings by their bytes, so convert
+/// it first. `None` when the index cannot answer the comparison, such as a
+/// DATE against a string with a time of day.
+fn index_lookup_value(value: &Value, data_type: &DataType) -> Option<Value> {
+    Some(match (value, data_type) {
+        (Value::String(s), DataType::Integer | DataType::Float) => evaluator::string_to_number(s),
+        (Value::String(s), DataType::Date) => match Timestamp::parse(s) {
+            Some(ts) if !ts.has_time() => Value::Date(ts.date()),
+            _ => return None,
+        },
+        (Value::String(s), DataType::Blob) => Value::Bytes(s.as_bytes().to_vec()),
+        (Value::Bytes(b), DataType::Varchar(_) | DataType::Text) => {
+            Value::String(String::from_utf8(b.clone()).ok()?)
+        }
+        _ => value.clone(),
+    })
+}
+
+/// Check if a column's table qualifier matches the expected table
+fn column_matches_table(table_qualifier: Option<&String>, table_alias: &str, table_name: &str) -> bool {
+    match table_qualifier {
+        Some(tbl) => tbl.eq_ignore_ascii_case(table_alias) || tbl.eq_ignore_ascii_case(table_name),
+        None => true, // No qualifier means it could match any table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{Parser, Statement};
+    use crate::types::ColumnDef;
+    use tempfile::{tempdir, TempDir};
+
+    /// Table `t (id PK, a, b, c INTEGER, note TEXT)` with an index on (a, b, c)
+    fn setup() -> (StorageEngine, TableSchema, TempDir) {
+        let dir = tempdir().unwrap();
+        let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+        let column = |name: &str, data_type: DataType| ColumnDef {
+            name: name.to_string(),
+            data_type,
+            nullable: true,
+            default: None,
+            primary_key: name == "id",
+            auto_increment: false,
+        };
+        let schema = TableSchema {
+            name: "t".to_string(),
+            columns: vec![
+                column("id", DataType::Integer),
+                column("a", DataType::Integer),
+                column("b", DataType::Integer),
+                column("c", DataType::Integer),
+                column("note", DataType::Text),
+            ],
+            auto_increment_counter: 1,
+            engine_type: crate::engines::EngineType::default(),
+            storage_format: crate::types::StorageFormat::default(),
+            foreign_keys: Vec::new(),
+            primary_key: Vec::new(),
+        };
+        storage.create_table(schema.clone(), false).unwrap();
+        for (name, columns, is_primary) in [("t_pk", vec!["id"], true), ("idx_abc", vec!["a", "b", "c"], false)] {
+            storage.create_index(IndexMetadata {
+                name: name.to_string(),
+                table_name: "t".to_string(),
+                columns: columns.into_iter().map(String::from).collect(),
+                unique: is_primary,
+                is_primary,
+            }, false).unwrap();
+        }
+        (storage, schema, dir)
+    }
+
+    fn plan(sql_where: &str) -> AccessPlan {
+        let (storage, schema, _dir) = setup();
+        let Statement::Select(select) = Parser::parse(&format!("SELECT * FROM t WHERE {}", sql_where)).unwrap() else {
+            panic!("Expected SELECT");
+        };
+        plan_table_access(select.where_clause.as_ref().unwrap(), &schema, "t", &storage)
+    }
+
+    fn equality(plan: AccessPlan) -> (String, Vec<Value>) {
+        match plan {
+            AccessPlan::IndexEquality { index, values } => (index.name, values),
+            other => panic!("Expected an index equality, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_equality_uses_index() {
+        assert_eq!(equality(plan("id = 7")), ("t_pk".to_string(), vec![Value::Integer(7)]));
+        // Literal first, qualified column, string converted for an integer column
+        assert_eq!(equality(plan("'7' = t.id AND note = 'x'")), ("t_pk".to_string(), vec![Value::Integer(7)]));
+    }
+
+    #[test]
+    fn test_composite_prefix_in_index_order() {
+        assert_eq!(
+            equality(plan("b = 2 AND a = 1")),
+            ("idx_abc".to_string(), vec![Value::Integer(1), Value::Integer(2)])
+        );
+        // A gap after `a` leaves only `a` usable
+        assert_eq!(equality(plan("a = 1 AND c = 3")), ("idx_abc".to_string(), vec![Value::Integer(1)]));
+        // The index binding more columns wins
+        assert_eq!(equality(plan("a = 1 AND id = 5 AND b = 2")).0, "idx_abc");
+    }
+
+    #[test]
+    fn test_range_on_leading_column() {
+        match plan("a > 5 AND 9 >= a") {
+            AccessPlan::IndexRange { index, lower, upper } => {
+                assert_eq!(index.name, "idx_abc");
+                assert_eq!(lower, Bound::Excluded(Value::Integer(5)));
+                assert_eq!(upper, Bound::Included(Value::Integer(9)));
+            }
+            other => panic!("Expected an index range, got {:?}", other),
+        }
+        // Equality beats a range
+        assert_eq!(equality(plan("a > 5 AND id = 1")).0, "t_pk");
+    }
+
+    #[test]
+    fn test_unindexed_predicates_scan() {
+        for predicate in ["note = 'x'", "b = 2 AND c = 3", "a = 1 OR b = 2", "id = 1 OR note = 'x'", "a <> 1", "b > 2", "a + 1 = 2"] {
+            assert!(matches!(plan(predicate), AccessPlan::FullScan), "{} should scan", predicate);
+        }
+    }
+
+    #[test]
+    fn test_or_of_indexed_branches_is_union() {
+        let names = |plan: AccessPlan| match plan {
+            AccessPlan::IndexUnion { branches } => branches.iter().map(|b| b.access_type()).collect::<Vec<_>>(),
+            other => panic!("Expected an index union, got {:?}", other),
+        };
+        assert_eq!(names(plan("a = 1 OR id = 5")), vec!["index", "index"]);
+        assert_eq!(names(plan("note = 'x' AND (id = 5 OR a > 3 OR a = 1 AND b = 2)")), vec!["index", "range", "index"]);
+        // The union only reads the indexes of its branches
+        let indexes: Vec<String> = plan("a = 1 OR id = 5").indexes().into_iter().map(|i| i.name.clone()).collect();
+        assert_eq!(indexes, vec!["idx_abc", "t_pk"]);
+        // A conjunct usable on its own is preferred over a union
+        assert_eq!(equality(plan("id = 2 AND (a = 1 OR id = 5)")).0, "t_pk");
+    }
+
+    #[test]
+    fn test_within_bounds() {
+        let lower = Bound::Included(Value::Integer(2));
+        let upper = Bound::Excluded(Value::Integer(5));
+        assert!(within_bounds(&Value::Integer(2), &lower, &upper));
+        assert!(within_bounds(&Value::Float(4.5), &lower, &upper));
+        assert!(!within_bounds(&Value::Integer(5), &lower, &upper));
+        assert!(!within_bounds(&Value::Null, &lower, &Bound::Unbounded));
+    }
+}
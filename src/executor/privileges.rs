@@ -0,0 +1,171 @@
+//! Table privileges (GRANT / REVOKE)
+//!
+//! A session bound to a login carries its user name. Unless that user is the
+//! superuser (the user configured for the server), every statement is checked
+//! against the privileges granted in the catalog before it runs:
+//!
+//! - SELECT needs SELECT on every table it reads, including tables read by
+//!   subqueries; EXPLAIN, DESCRIBE and SHOW CREATE TABLE / SHOW INDEX need
+//!   SELECT on their table
+//! - INSERT needs INSERT, plus UPDATE for ON DUPLICATE KEY UPDATE
+//! - UPDATE and DELETE need UPDATE and DELETE on their table
+//! - transaction control, SET, SHOW TABLES, SHOW STATUS and SHOW WARNINGS
+//!   need nothing
+//! - DDL, GRANT, REVOKE, LOAD DATA (it reads files on the server) and
+//!   maintenance statements are reserved to the superuser
+//!
+//! A missing table privilege fails with error 1142; a statement reserved to
+//! the superuser fails with error 1227. Sessions not bound to a login (the
+//! executor embedded in another program) are not checked.
+
+use crate::error::{MiniSqlError, Result};
+use crate::executor::{Executor, Session};
+use crate::parser::{Expr, GrantStmt, SelectColumn, SelectStmt, Statement};
+use crate::types::{Privilege, QueryResult};
+
+/// Tables read by `select`, including those of its subqueries
+fn select_tables(select: &SelectStmt, tables: &mut Vec<String>) {
+    tables.extend(select.from.iter().map(|table| table.name.clone()));
+    tables.extend(select.joins.iter().map(|join| join.table.name.clone()));
+    for col in &select.columns {
+        if let SelectColumn::Expr { expr, .. } = col {
+            expr_tables(expr, tables);
+        }
+    }
+    for join in &select.joins {
+        expr_tables(&join.on_condition, tables);
+    }
+    select.where_clause.iter().for_each(|expr| expr_tables(expr, tables));
+    select.group_by.iter().for_each(|expr| expr_tables(expr, tables));
+    select.order_by.iter().for_each(|clause| expr_tables(&clause.expr, tables));
+}
+
+/// Tables read by the subqueries of `expr`
+fn expr_tables(expr: &Expr, tables: &mut Vec<String>) {
+    match expr {
+        Expr::ScalarSubquery(subquery) => select_tables(subquery, tables),
+        Expr::InSubquery { expr, subquery, .. } => {
+            expr_tables(expr, tables);
+            select_tables(subquery, tables);
+        }
+        Expr::BinaryOp { left, right, .. } => {
+            expr_tables(left, tables);
+            expr_tables(right, tables);
+        }
+        Expr::Not(inner)
+        | Expr::UnaryMinus(inner)
+        | Expr::IsNull(inner)
+        | Expr::IsNotNull(inner)
+        | Expr::JsonAccess { expr: inner, .. }
+        | Expr::Cast { expr: inner, .. }
+        | Expr::Like { expr: inner, .. } => expr_tables(inner, tables),
+        Expr::In { expr: inner, values } | Expr::NotIn { expr: inner, values } => {
+            expr_tables(inner, tables);
+            values.iter().for_each(|value| expr_tables(value, tables));
+        }
+        Expr::FunctionCall { args, .. } => args.iter().for_each(|arg| expr_tables(arg, tables)),
+        Expr::Literal(_) | Expr::Column { .. } | Expr::Placeholder(_) | Expr::UserVariable(_) => {}
+    }
+}
+
+/// Reads of the tables of `select`, as SELECT requirements
+fn select_reads(select: &SelectStmt) -> Vec<(Privilege, String)> {
+    let mut tables = Vec::new();
+    select_tables(select, &mut tables);
+    tables.into_iter().map(|table| (Privilege::Select, table)).collect()
+}
+
+/// Reads of the tables in `exprs`' subqueries, as SELECT requirements
+fn subquery_reads<'e>(exprs: impl IntoIterator<Item = &'e Expr>) -> Vec<(Privilege, String)> {
+    let mut tables = Vec::new();
+    exprs.into_iter().for_each(|expr| expr_tables(expr, &mut tables));
+    tables.into_iter().map(|table| (Privilege::Select, table)).collect()
+}
+
+/// The table privileges `stmt` needs, or `None` if only the superuser may run it
+fn required_privileges(stmt: &Statement) -> Option<Vec<(Privilege, String)>> {
+    let required = match stmt {
+        Statement::Select(select) => select_reads(select),
+        Statement::Explain(inner) => return required_privileges(inner),
+        Statement::Describe(table) | Statement::ShowCreateTable(table) | Statement::ShowIndex(table) => {
+            vec![(Privilege::Select, table.clone())]
+        }
+        Statement::Insert(insert) => {
+            let assignments = insert.on_duplicate_key_update.iter().map(|(_, expr)| expr);
+            let mut required = subquery_reads(insert.values.iter().flatten().chain(assignments));
+            required.push((Privilege::Insert, insert.table_name.clone()));
+            if !insert.on_duplicate_key_update.is_empty() {
+                required.push((Privilege::Update, insert.table_name.clone()));
+            }
+            required
+        }
+        Statement::Update(update) => {
+            let assignments = update.assignments.iter().map(|(_, expr)| expr);
+            let mut required = subquery_reads(update.where_clause.iter().chain(assignments));
+            required.push((Privilege::Update, update.table_name.clone()));
+            required
+        }
+        Statement::Delete(delete) => {
+            let mut required = subquery_reads(delete.where_clause.iter());
+            required.push((Privilege::Delete, delete.table_name.clone()));
+            required
+        }
+        Statement::Begin
+        | Statement::Commit
+        | Statement::Rollback
+        | Statement::Savepoint(_)
+        | Statement::RollbackToSavepoint(_)
+        | Statement::ReleaseSavepoint(_)
+        | Statement::SetIsolationLevel { .. }
+        | Statement::SetUserVariables(_)
+        | Statement::SetSqlMode(_)
+        | Statement::SetAutocommit(_)
+        | Statement::SetVariables(_)
+        | Statement::ShowTables
+        | Statement::ShowStatus
+        | Statement::ShowWarnings => Vec::new(),
+        Statement::CreateTable(_)
+        | Statement::CreateIndex(_)
+        | Statement::DropIndex { .. }
+        | Statement::AlterTable(_)
+        | Statement::DropTable { .. }
+        | Statement::TruncateTable(_)
+        | Statement::LoadData(_)
+        | Statement::Checkpoint
+        | Statement::FlushTables
+        | Statement::Vacuum(_)
+        | Statement::Grant(_)
+        | Statement::Revoke(_) => return None,
+    };
+    Some(required)
+}
+
+impl Executor {
+    /// Fail unless the session's user may run `stmt`
+    pub(crate) fn check_privileges(&self, stmt: &Statement, session: &Session) -> Result<()> {
+        self.check_required(required_privileges(stmt), session)
+    }
+
+    fn check_required(&self, required: Option<Vec<(Privilege, String)>>, session: &Session) -> Result<()> {
+        let Some(user) = session.user.as_deref().filter(|user| *user != self.superuser) else {
+            return Ok(());
+        };
+        let required = required.ok_or_else(MiniSqlError::superuser_required)?;
+        for (privilege, table) in required {
+            if !self.storage.has_privilege(user, &table, privilege) {
+                return Err(MiniSqlError::table_access_denied(privilege, user, &table));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn execute_grant(&self, grant: GrantStmt) -> Result<QueryResult> {
+        self.storage.grant_privileges(&grant.user, &grant.table_name, &grant.privileges)?;
+        Ok(QueryResult::Ok)
+    }
+
+    pub(crate) fn execute_revoke(&self, revoke: GrantStmt) -> Result<QueryResult> {
+        self.storage.revoke_privileges(&revoke.user, &revoke.table_name, &revoke.privileges)?;
+        Ok(QueryResult::Ok)
+    }
+}
@@ -1,13 +1,19 @@
 use crate::error::Result;
 use crate::join::{JoinContext, select_join_strategy};
-use crate::parser::{Expr, SelectColumn, SelectStmt};
+use crate::parser::{is_aggregate_function, Expr, SelectColumn, SelectStmt, Statement};
+use crate::engines::granite::TxnId;
 use crate::types::{DataType, QueryResult, ResultSet, Row, TableSchema, Value};
-use crate::executor::{Executor, Session};
+use crate::executor::{Executor, RowSink, Session};
 use crate::executor::evaluator;
 use crate::executor::schema::{self, JoinTableInfo};
 use crate::executor::aggregation::{self, is_aggregate_expr};
+use crate::executor::memory::QueryMemory;
+use crate::executor::ordering;
+use crate::executor::planner::{self, AccessPlan};
+use crate::executor::subquery::{join_row_columns, simple_row_columns};
+use crate::executor::timeout::StatementDeadline;
 use crate::error::MiniSqlError;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 
 impl Executor {
@@ -21,235 +27,225 @@ impl Executor {
         }
     }
 
+    /// Execute a SELECT, handing each result row to `sink` as it is produced.
+    ///
+    /// Simple single-table SELECTs without aggregates are streamed: rows are
+    /// filtered and projected one at a time and never collected into a
+    /// `ResultSet` (ORDER BY still buffers the matching rows to sort them).
+    /// Joins and aggregates are computed in full, then replayed into the sink.
+    /// Returns the number of rows sent.
+    pub fn execute_select_streaming(
+        &self,
+        select: SelectStmt,
+        session: &mut Session,
+        sink: &mut dyn RowSink,
+    ) -> Result<u64> {
+        self.execute_with(Statement::Select(select), session, |stmt, session| {
+            let Statement::Select(select) = stmt else {
+                unreachable!("execute_with hands back the statement it was given");
+            };
+            self.stream_select(select, session, sink)
+        })
+    }
+
+    fn stream_select(&self, mut select: SelectStmt, session: &mut Session, sink: &mut dyn RowSink) -> Result<u64> {
+        self.resolve_select_subqueries(&mut select, session)?;
+        let result = if select.for_update {
+            self.execute_locking_select(select, session)?
+        } else {
+            self.snapshot_select_tables(&select, session)?;
+            if select.joins.is_empty() && !Self::is_aggregate_select(&select) {
+                return self.stream_simple_select(select, session, sink);
+            }
+            self.execute_select(select, session)?
+        };
+
+        match result {
+            QueryResult::Select(result) => {
+                sink.begin(&result.columns, &result.column_types)?;
+                let count = result.rows.len() as u64;
+                for row in result.rows {
+                    sink.send_row(row)?;
+                }
+                Ok(count)
+            }
+            _ => Err(MiniSqlError::Internal("SELECT did not produce a result set".into())),
+        }
+    }
+
+    /// Whether a SELECT needs the aggregate (GROUP BY) execution path
+    fn is_aggregate_select(select: &SelectStmt) -> bool {
+        !select.group_by.is_empty()
+            || select.columns.iter().any(|col| {
+                matches!(col, SelectColumn::Expr { expr, .. } if is_aggregate_expr(expr))
+            })
+    }
+
     /// Execute a simple SELECT (no joins)
     fn execute_simple_select(&self, select: SelectStmt, session: &Session) -> Result<QueryResult> {
-        // Check if this is an aggregate query
-        let has_aggregates = select.columns.iter().any(|col| {
-            matches!(col, SelectColumn::Expr { expr, .. } if is_aggregate_expr(expr))
-        });
-        let has_group_by = !select.group_by.is_empty();
-
-        if has_aggregates || has_group_by {
+        if Self::is_aggregate_select(&select) {
             return self.execute_aggregate_select(select, session);
         }
 
+        let mut result = ResultSet::empty();
+        self.stream_simple_select(select, session, &mut result)?;
+        Ok(QueryResult::Select(result))
+    }
+
+    /// Filter, sort and project a single-table SELECT into `sink`
+    fn stream_simple_select(&self, select: SelectStmt, session: &Session, sink: &mut dyn RowSink) -> Result<u64> {
         let (schema, table_alias, rows) = if let Some(ref from) = select.from {
             let schema = self.storage.get_schema(&from.name)?;
             let table_alias = from.effective_name().to_string();
+            schema::check_qualifiers_simple(&select, &table_alias)?;
             let table_name = &from.name;
 
-            // Try to use index for WHERE clauses (single or composite)
-            // When the environment variable MINISQL_DEBUG_INDEX=1, emit diagnostic information
-            let rows = if let Some(ref where_expr) = select.where_clause {
-                let debug = std::env::var("MINISQL_DEBUG_INDEX").map(|v| v != "0").unwrap_or(false);
-                // First try to extract multi-column equalities for composite index lookup
-                let equalities = Self::extract_equality_conjuncts(where_expr, &schema, &table_alias);
-                
-                if !equalities.is_empty() && self.engine_supports_indexes(table_name).unwrap_or(false) {
-                    // Check if we have an index that matches these columns
-                    let query_columns: Vec<String> = equalities.iter().map(|(c, _)| c.clone()).collect();
-                    
-                    if let Some(index_meta) = self.storage.find_index_for_columns(table_name, &query_columns) {
-                        if debug {
-                            eprintln!("[IDX DEBUG] Table='{}' where_expr='{:?}' equalities={:?} -> candidate_index='{}' columns={:?}",
-                                      table_name, where_expr, equalities, index_meta.name, index_meta.columns);
-                        }
-                        // Get values in the order of the index columns
-                        let values: Vec<Value> = index_meta.columns.iter()
-                            .filter_map(|col| {
-                                equalities.iter()
-                                    .find(|(c, _)| c.eq_ignore_ascii_case(col))
-                                    .map(|(_, v)| v.clone())
-                            })
-                            .collect();
-                        
-                        // Only use index if we have values for all index columns used in query
-                        if values.len() == index_meta.columns.len().min(query_columns.len()) {
-                            // Use composite index scan
-                            let columns: Vec<String> = index_meta.columns[..values.len()].to_vec();
-                            if debug {
-                                let t0 = Instant::now();
-                                let result = self.storage.get_rows_by_composite_index(table_name, &columns, &values)?;
-                                let elapsed = t0.elapsed();
-                                eprintln!("[IDX DEBUG] Index scan used: index='{}' columns={:?} values={:?} rows_returned={} time_ms={}",
-                                          index_meta.name, columns, values, result.len(), elapsed.as_millis());
-                                result
-                            } else {
-                                self.storage.get_rows_by_composite_index(table_name, &columns, &values)?
-                            }
-                        } else {
-                            // Fall back to full scan
-                            if debug {
-                                eprintln!("[IDX DEBUG] Index candidate '{}' did not have all required values (have {} expected prefix {}) - falling back to full scan",
-                                          index_meta.name, values.len(), index_meta.columns.len().min(query_columns.len()));
-                                let t0 = Instant::now();
-                                let result = self.scan_table(table_name)?;
-                                let elapsed = t0.elapsed();
-                                eprintln!("[IDX DEBUG] Full scan rows_returned={} time_ms={}", result.len(), elapsed.as_millis());
-                                result
-                            } else {
-                                self.scan_table(table_name)?
-                            }
-                        }
-                    } else if equalities.len() == 1 {
-                        // Single column equality without matching index - try simple lookup
-                        let (col_name, value) = &equalities[0];
-                        if self.storage.has_index_for_columns(table_name, col_name) {
-                            if debug {
-                                let t0 = Instant::now();
-                                let result = self.storage.get_rows_by_index(table_name, col_name, value)?;
-                                let elapsed = t0.elapsed();
-                                eprintln!("[IDX DEBUG] Single-column index used: table='{}' col='{}' val='{:?}' rows={} time_ms={}",
-                                          table_name, col_name, value, result.len(), elapsed.as_millis());
-                                result
-                            } else {
-                                self.storage.get_rows_by_index(table_name, col_name, value)?
-                            }
-                        } else {
-                            if debug {
-                                eprintln!("[IDX DEBUG] No index for single-column equality on '{}.{}' -> full scan", table_name, col_name);
-                                let t0 = Instant::now();
-                                let result = self.scan_table(table_name)?;
-                                let elapsed = t0.elapsed();
-                                eprintln!("[IDX DEBUG] Full scan rows_returned={} time_ms={}", result.len(), elapsed.as_millis());
-                                result
-                            } else {
-                                self.scan_table(table_name)?
-                            }
-                        }
-                    } else {
-                        // No matching index, fall back to full scan
-                        if debug {
-                            eprintln!("[IDX DEBUG] No matching index for equalities {:?} on table '{}' -> full scan", equalities, table_name);
-                            let t0 = Instant::now();
-                            let result = self.scan_table(table_name)?;
-                            let elapsed = t0.elapsed();
-                            eprintln!("[IDX DEBUG] Full scan rows_returned={} time_ms={}", result.len(), elapsed.as_millis());
-                            result
-                        } else {
-                            self.scan_table(table_name)?
-                        }
-                    }
-                } else {
-                    // Complex WHERE clause or Sandstone table, use full scan
-                    if debug {
-                        eprintln!("[IDX DEBUG] Complex WHERE clause {}, using full scan", table_name);
-                        let t0 = Instant::now();
-                        let result = self.scan_table(table_name)?;
-                        let elapsed = t0.elapsed();
-                        eprintln!("[IDX DEBUG] Full scan rows_returned={} time_ms={}", result.len(), elapsed.as_millis());
-                        result
-                    } else {
-                        self.scan_table(table_name)?
-                    }
-                }
+            // Use an index for the WHERE clause when the planner finds one
+            let rows = if from.sample.is_some() {
+                // A sample is drawn from the whole table, so indexes do not apply
+                self.read_table_ref(from, session)?
+            } else if let Some(rows) = self.snapshot_rows(table_name, session)? {
+                // REPEATABLE READ: serve the transaction's snapshot, WHERE is applied below
+                session.record_read(rows.len(), false);
+                rows
+            } else if let Some(ref where_expr) = select.where_clause {
+                let plan = self.plan_access(table_name, &table_alias, where_expr, &schema)?;
+                let rows = self.read_access_plan(table_name, &plan)?;
+                session.record_read(rows.len(), !plan.indexes().is_empty());
+                rows
             } else {
-                // No WHERE clause, full scan required
-                let debug = std::env::var("MINISQL_DEBUG_INDEX").map(|v| v != "0").unwrap_or(false);
-                if debug {
-                    eprintln!("[IDX DEBUG] No WHERE clause on table '{}' -> full scan", table_name);
-                    let t0 = Instant::now();
-                    let result = self.scan_table(table_name)?;
-                    let elapsed = t0.elapsed();
-                    eprintln!("[IDX DEBUG] Full scan rows_returned={} time_ms={}", result.len(), elapsed.as_millis());
-                    result
-                } else {
-                    self.scan_table(table_name)?
-                }
+                let rows = self.scan_table(table_name)?;
+                session.record_read(rows.len(), false);
+                rows
             };
             (schema, table_alias, rows)
         } else {
             // FROM-less SELECT: use a dummy schema and a single empty row
-            (TableSchema { name: "dual".to_string(), columns: Vec::new(), auto_increment_counter: 1, engine_type: crate::engines::EngineType::default() }, "dual".to_string(), vec![Row::new(0, Vec::new())])
+            (TableSchema { name: "dual".to_string(), columns: Vec::new(), auto_increment_counter: 1, engine_type: crate::engines::EngineType::default(), storage_format: crate::types::StorageFormat::default(), foreign_keys: Vec::new(), primary_key: Vec::new() }, "dual".to_string(), vec![Row::new(0, Vec::new())])
         };
 
         // Build result set columns
         let (result_columns, result_types) = schema::resolve_select_columns_simple(&select.columns, &schema, &table_alias)?;
+        sink.begin(&result_columns, &result_types)?;
 
-        let mut result = ResultSet::new(result_columns.clone(), result_types.clone());
+        let limit = select.limit.unwrap_or(u64::MAX);
+        let mut count = 0;
+        let deadline = StatementDeadline::new(session);
+
+        // Without ORDER BY, rows go straight from the filter to the sink
+        if select.order_by.is_empty() {
+            for row in rows {
+                if count >= limit {
+                    break;
+                }
+                deadline.tick()?;
+                if let Some(ref where_expr) = select.where_clause {
+                    let where_expr = self.bind_scalar_subqueries(where_expr, &simple_row_columns(&row, &schema, &table_alias), session)?;
+                    if !evaluator::eval_where_simple(&where_expr, &row, &schema, &table_alias, session.last_insert_id)? {
+                        continue;
+                    }
+                }
+                sink.send_row(self.project_row_simple(&select.columns, &row, &schema, &table_alias, session)?)?;
+                count += 1;
+            }
+            return Ok(count);
+        }
 
-        // Filter and project rows (still needed for non-indexed or complex conditions)
+        // Filter rows (still needed for non-indexed or complex conditions)
+        let order_by = ordering::resolve_order_by_positions(&select.order_by, &select.columns, &[(&table_alias, &schema)])?;
+        let mut sort_memory = QueryMemory::new(self.max_query_memory_bytes);
         let mut filtered_rows = Vec::new();
         for row in rows {
+            deadline.tick()?;
             // Apply WHERE clause
             if let Some(ref where_expr) = select.where_clause {
-                let matches = evaluator::eval_where_simple(where_expr, &row, &schema, &table_alias, session.last_insert_id)?;
+                let where_expr = self.bind_scalar_subqueries(where_expr, &simple_row_columns(&row, &schema, &table_alias), session)?;
+                let matches = evaluator::eval_where_simple(&where_expr, &row, &schema, &table_alias, session.last_insert_id)?;
                 if !matches {
                     continue;
                 }
             }
             
-            filtered_rows.push(row);
+            // Rows buffered for ORDER BY count against the query memory limit
+            sort_memory.charge_row(&row.values)?;
+            let keys = order_by.iter()
+                .map(|clause| evaluator::eval_expr_simple(&clause.expr, &row, &schema, &table_alias, session.last_insert_id))
+                .collect::<Result<Vec<_>>>()?;
+            filtered_rows.push((keys, row));
         }
 
         // Apply ORDER BY sorting
-        if !select.order_by.is_empty() {
-            filtered_rows.sort_by(|a, b| {
-                for order_clause in &select.order_by {
-                    let val_a = evaluator::eval_expr_simple(&order_clause.expr, a, &schema, &table_alias, session.last_insert_id).unwrap_or(Value::Null);
-                    let val_b = evaluator::eval_expr_simple(&order_clause.expr, b, &schema, &table_alias, session.last_insert_id).unwrap_or(Value::Null);
-                    
-                    let cmp = match val_a.partial_cmp(&val_b) {
-                        Some(ord) => ord,
-                        None => std::cmp::Ordering::Equal,
-                    };
-                    
-                    if cmp != std::cmp::Ordering::Equal {
-                        return match order_clause.direction {
-                            crate::parser::SortOrder::Asc => cmp,
-                            crate::parser::SortOrder::Desc => cmp.reverse(),
-                        };
-                    }
-                }
-                std::cmp::Ordering::Equal
-            });
-        }
+        filtered_rows.sort_by(|(a, _), (b, _)| ordering::compare_order_keys(&order_by, a, b));
 
         // Apply LIMIT and project
-        let mut count = 0;
-        for row in filtered_rows {
-            if let Some(limit) = select.limit {
-                if count >= limit {
-                    break;
-                }
+        for (_, row) in filtered_rows {
+            if count >= limit {
+                break;
             }
-
-            // Project columns
-            let result_row = self.project_row_simple(&select.columns, &row, &schema, &table_alias, session)?;
-            result.add_row(result_row);
+            sink.send_row(self.project_row_simple(&select.columns, &row, &schema, &table_alias, session)?)?;
             count += 1;
         }
 
-        Ok(QueryResult::Select(result))
+        Ok(count)
     }
 
     /// Execute an aggregate SELECT (with GROUP BY or aggregate functions)
     fn execute_aggregate_select(&self, select: SelectStmt, session: &Session) -> Result<QueryResult> {
+        if let Some(from) = &select.from {
+            schema::check_qualifiers_simple(&select, from.effective_name())?;
+        }
+        if let Some(count) = self.counted_rows(&select, session)? {
+            let schema = self.storage.get_schema(&select.from.as_ref().unwrap().name)?;
+            let (result_columns, result_types) = self.resolve_aggregate_columns(&select.columns, &schema)?;
+            let mut result = ResultSet::new(result_columns, result_types);
+            result.add_row(vec![Value::Integer(count as i64)]);
+            return Ok(QueryResult::Select(result));
+        }
+
         let (schema, table_alias, rows) = if let Some(ref from) = select.from {
             let schema = self.storage.get_schema(&from.name)?;
-            let rows = self.scan_table(&from.name)?;
             let table_alias = from.effective_name().to_string();
+            let rows = if from.sample.is_some() {
+                self.read_table_ref(from, session)?
+            } else {
+                let (rows, index_used) = match (self.snapshot_rows(&from.name, session)?, &select.where_clause) {
+                    (Some(rows), _) => (rows, false),
+                    (None, Some(where_expr)) => {
+                        match self.index_candidate_rows(&from.name, &table_alias, where_expr, &schema)? {
+                            Some(rows) => (rows, true),
+                            None => (self.scan_table(&from.name)?, false),
+                        }
+                    }
+                    (None, None) => (self.scan_table(&from.name)?, false),
+                };
+                session.record_read(rows.len(), index_used);
+                rows
+            };
             (schema, table_alias, rows)
         } else {
-            (TableSchema { name: "dual".to_string(), columns: Vec::new(), auto_increment_counter: 1, engine_type: crate::engines::EngineType::default() }, "dual".to_string(), vec![Row::new(0, Vec::new())])
+            (TableSchema { name: "dual".to_string(), columns: Vec::new(), auto_increment_counter: 1, engine_type: crate::engines::EngineType::default(), storage_format: crate::types::StorageFormat::default(), foreign_keys: Vec::new(), primary_key: Vec::new() }, "dual".to_string(), vec![Row::new(0, Vec::new())])
         };
 
         // Filter rows with WHERE clause first
-        let filtered_rows: Vec<Row> = rows
-            .into_iter()
-            .filter(|row| {
-                if let Some(ref where_expr) = select.where_clause {
-                    evaluator::eval_where_simple(where_expr, row, &schema, &table_alias, session.last_insert_id)
-                        .unwrap_or(false)
-                } else {
-                    true
+        let deadline = StatementDeadline::new(session);
+        let mut filtered_rows = Vec::new();
+        for row in rows {
+            deadline.tick()?;
+            if let Some(ref where_expr) = select.where_clause {
+                let where_expr = self.bind_scalar_subqueries(where_expr, &simple_row_columns(&row, &schema, &table_alias), session)?;
+                if !evaluator::eval_where_simple(&where_expr, &row, &schema, &table_alias, session.last_insert_id)
+                    .unwrap_or(false)
+                {
+                    continue;
                 }
-            })
-            .collect();
+            }
+            filtered_rows.push(row);
+        }
 
         // Group rows by GROUP BY expressions
         // Key is a string representation of the group values
         let mut groups: HashMap<String, Vec<Row>> = HashMap::new();
+        let mut group_memory = QueryMemory::new(self.max_query_memory_bytes);
 
         for row in filtered_rows {
             let key = if select.group_by.is_empty() {
@@ -268,8 +264,14 @@ impl Executor {
                     .collect::<Vec<_>>()
                     .join("|")
             };
+            group_memory.charge_row(&row.values)?;
+            group_memory.charge(key.len())?;
             groups.entry(key).or_default().push(row);
         }
+        // Without GROUP BY there is always exactly one group, even over no rows
+        if select.group_by.is_empty() && groups.is_empty() {
+            groups.insert(String::new(), Vec::new());
+        }
 
         // Build result columns for aggregate query
         let (result_columns, result_types) = 
@@ -278,10 +280,6 @@ impl Executor {
 
         // Process each group and compute aggregates
         for (_key, group_rows) in groups {
-            if group_rows.is_empty() {
-                continue;
-            }
-
             let mut result_row = Vec::new();
 
             for col in &select.columns {
@@ -315,6 +313,28 @@ impl Executor {
         Ok(QueryResult::Select(result))
     }
 
+    /// The answer to `SELECT COUNT(*) FROM t`, with no WHERE or GROUP BY, from
+    /// the engine's row count instead of a scan. None when the query is any
+    /// other shape, the engine keeps no count, or the session reads the table
+    /// from a snapshot.
+    fn counted_rows(&self, select: &SelectStmt, session: &Session) -> Result<Option<u64>> {
+        let Some(from) = &select.from else {
+            return Ok(None);
+        };
+        let count_star = matches!(
+            select.columns.as_slice(),
+            [SelectColumn::Expr { expr: Expr::FunctionCall { name, args, distinct: false, .. }, .. }]
+                if name.eq_ignore_ascii_case("COUNT") && args.is_empty()
+        );
+        if !count_star || select.where_clause.is_some() || !select.group_by.is_empty() || from.sample.is_some() {
+            return Ok(None);
+        }
+        if session.read_snapshot.as_ref().is_some_and(|snapshot| snapshot.contains_key(&from.name)) {
+            return Ok(None);
+        }
+        self.get_engine(&from.name)?.row_count(self.get_txn_id(session), &from.name)
+    }
+
     /// Evaluate an expression that may contain aggregate functions
     fn evaluate_aggregate_expr(
         &self,
@@ -325,8 +345,8 @@ impl Executor {
         session: &Session,
     ) -> Result<Value> {
         match expr {
-            Expr::FunctionCall { name, args } if aggregation::is_aggregate_function(name) => {
-                let mut acc = aggregation::create_accumulator(name, args)?;
+            Expr::FunctionCall { name, args, distinct, separator } if is_aggregate_function(name) => {
+                let mut acc = aggregation::create_accumulator(name, args, *distinct, separator.as_deref())?;
 
                 for row in rows {
                     let value = if args.is_empty() {
@@ -347,7 +367,8 @@ impl Executor {
                 if rows.is_empty() {
                     Ok(Value::Null)
                 } else {
-                    evaluator::eval_expr_simple(expr, &rows[0], schema, table_alias, session.last_insert_id)
+                    let expr = self.bind_scalar_subqueries(expr, &simple_row_columns(&rows[0], schema, table_alias), session)?;
+                    evaluator::eval_expr_simple(&expr, &rows[0], schema, table_alias, session.last_insert_id)
                 }
             }
         }
@@ -388,16 +409,13 @@ impl Executor {
     /// Generate a column name from an expression
     fn expr_to_column_name(&self, expr: &Expr) -> String {
         match expr {
-            Expr::Column { table, name } => {
-                if let Some(t) = table {
-                    format!("{}.{}", t, name)
-                } else {
-                    name.clone()
-                }
-            }
-            Expr::FunctionCall { name, args } => {
+            // Like a plain SELECT, a qualified column is named without its qualifier
+            Expr::Column { name, .. } => name.clone(),
+            Expr::FunctionCall { name, args, distinct, .. } => {
                 if args.is_empty() {
                     format!("{}(*)", name)
+                } else if *distinct {
+                    format!("{}(DISTINCT ...)", name)
                 } else {
                     format!("{}(...)", name)
                 }
@@ -410,7 +428,7 @@ impl Executor {
     /// Infer the type of an aggregate expression
     fn infer_aggregate_expr_type(&self, expr: &Expr, schema: &TableSchema) -> Result<DataType> {
         match expr {
-            Expr::FunctionCall { name, args } => {
+            Expr::FunctionCall { name, args, .. } => {
                 match name.to_uppercase().as_str() {
                     "COUNT" => Ok(DataType::Integer),
                     "SUM" => {
@@ -421,6 +439,7 @@ impl Executor {
                         }
                     }
                     "AVG" => Ok(DataType::Float),
+                    "GROUP_CONCAT" => Ok(DataType::Text),
                     "MIN" | "MAX" => {
                         if args.is_empty() {
                             Ok(DataType::Integer)
@@ -443,10 +462,11 @@ impl Executor {
         // Get the left table (FROM clause)
         let from = select.from.as_ref().ok_or_else(|| MiniSqlError::Syntax("JOIN requires a FROM clause".into()))?;
         let left_schema = self.storage.get_schema(&from.name)?;
-        let left_rows = self.scan_table(&from.name)?;
+        let left_rows = self.read_table_ref(from, session)?;
         let left_alias = from.effective_name().to_string();
 
         // Start with left table as the "current" result
+        let deadline = StatementDeadline::new(session);
         let mut current_schema = left_schema.clone();
         let mut current_alias = left_alias.clone();
         let mut current_rows = left_rows;
@@ -454,7 +474,7 @@ impl Executor {
         // Process each join sequentially
         for join_clause in &select.joins {
             let right_schema = self.storage.get_schema(&join_clause.table.name)?;
-            let right_rows = self.scan_table(&join_clause.table.name)?;
+            let right_rows = self.read_table_ref(&join_clause.table, session)?;
             let right_alias = join_clause.table.effective_name().to_string();
 
             // Create join context
@@ -468,8 +488,10 @@ impl Executor {
             // Select and execute join strategy
             let strategy = select_join_strategy(&join_clause.on_condition, &context);
             
-            // Create evaluator closure for the join
+            // Create evaluator closure for the join; nested loops call it per
+            // row pair, so it also enforces the statement timeout
             let eval_fn = |expr: &Expr, values: &[Value], ctx: &JoinContext| -> Result<bool> {
+                deadline.tick()?;
                 evaluator::eval_join_condition(expr, values, ctx, session.last_insert_id)
             };
 
@@ -483,14 +505,17 @@ impl Executor {
             )?;
 
             // Build combined rows for the next iteration
+            let mut join_memory = QueryMemory::new(self.max_query_memory_bytes);
             current_rows = joined_rows
                 .into_iter()
                 .enumerate()
                 .map(|(idx, jr)| {
+                    deadline.tick()?;
                     let values = context.combine_rows(&jr.left, jr.right.as_ref());
-                    Row::new(idx as u64, values)
+                    join_memory.charge_row(&values)?;
+                    Ok(Row::new(idx as u64, values))
                 })
-                .collect();
+                .collect::<Result<Vec<_>>>()?;
 
             // Note: Join logic updates schema for next iteration
             // We need to re-fetch/create combined schema logic if `JoinContext::combined_schema` is used
@@ -511,8 +536,10 @@ impl Executor {
         // Filter with WHERE
         let mut filtered_rows = Vec::new();
         for row in current_rows {
+            deadline.tick()?;
             if let Some(ref where_expr) = select.where_clause {
-                let val = evaluator::eval_expr_join(where_expr, &row, &all_tables, session.last_insert_id)?;
+                let where_expr = self.bind_scalar_subqueries(where_expr, &join_row_columns(&row, &all_tables), session)?;
+                let val = evaluator::eval_expr_join(&where_expr, &row, &all_tables, session.last_insert_id)?;
                 if !val.is_truthy() {
                     continue;
                 }
@@ -522,27 +549,19 @@ impl Executor {
 
         // Apply ORDER BY sorting
         if !select.order_by.is_empty() {
-            // We need to clone tables for the closure
-            let tables_clone = all_tables.clone();
-            filtered_rows.sort_by(|a, b| {
-                for order_clause in &select.order_by {
-                    let val_a = evaluator::eval_expr_join(&order_clause.expr, a, &tables_clone, session.last_insert_id).unwrap_or(Value::Null);
-                    let val_b = evaluator::eval_expr_join(&order_clause.expr, b, &tables_clone, session.last_insert_id).unwrap_or(Value::Null);
-                    
-                    let cmp = match val_a.partial_cmp(&val_b) {
-                        Some(ord) => ord,
-                        None => std::cmp::Ordering::Equal,
-                    };
-                    
-                    if cmp != std::cmp::Ordering::Equal {
-                        return match order_clause.direction {
-                            crate::parser::SortOrder::Asc => cmp,
-                            crate::parser::SortOrder::Desc => cmp.reverse(),
-                        };
-                    }
-                }
-                std::cmp::Ordering::Equal
-            });
+            let star_tables: Vec<(&str, &TableSchema)> = all_tables.table_order.iter()
+                .filter_map(|alias| all_tables.schemas.get(alias).map(|schema| (alias.as_str(), schema)))
+                .collect();
+            let order_by = ordering::resolve_order_by_positions(&select.order_by, &select.columns, &star_tables)?;
+            let mut keyed = Vec::with_capacity(filtered_rows.len());
+            for row in filtered_rows {
+                let keys = order_by.iter()
+                    .map(|clause| evaluator::eval_expr_join(&clause.expr, &row, &all_tables, session.last_insert_id))
+                    .collect::<Result<Vec<_>>>()?;
+                keyed.push((keys, row));
+            }
+            keyed.sort_by(|(a, _), (b, _)| ordering::compare_order_keys(&order_by, a, b));
+            filtered_rows = keyed.into_iter().map(|(_, row)| row).collect();
         }
 
         // Apply LIMIT and project
@@ -598,30 +617,148 @@ impl Executor {
     /// Execute DESCRIBE table
     pub(crate) fn execute_describe(&self, table_name: &str) -> Result<QueryResult> {
         let schema = self.storage.get_schema(table_name)?;
+        let indexes: Vec<_> = self.storage.list_indexes()
+            .into_iter()
+            .filter(|idx| idx.table_name == schema.name && !idx.is_primary)
+            .collect();
+
         let mut result = ResultSet::new(
             vec![
                 "Field".to_string(),
                 "Type".to_string(),
                 "Null".to_string(),
                 "Key".to_string(),
+                "Default".to_string(),
+                "Extra".to_string(),
             ],
-            vec![DataType::Text, DataType::Text, DataType::Text, DataType::Text],
+            vec![DataType::Text; 6],
         );
 
         for col in &schema.columns {
+            // MySQL: PRI for primary key columns, UNI for a single-column unique
+            // index, MUL for the leading column of any other index
+            let leading: Vec<_> = indexes.iter()
+                .filter(|idx| idx.columns.first() == Some(&col.name))
+                .collect();
+            let key = if col.primary_key {
+                "PRI"
+            } else if leading.iter().any(|idx| idx.unique && idx.columns.len() == 1) {
+                "UNI"
+            } else if !leading.is_empty() {
+                "MUL"
+            } else {
+                ""
+            };
+            let default = match &col.default {
+                Some(value) if !value.is_null() => Value::String(value.to_string()),
+                _ => Value::Null,
+            };
             result.add_row(vec![
                 Value::String(col.name.clone()),
                 Value::String(col.data_type.to_string()),
                 Value::String(if col.nullable { "YES" } else { "NO" }.to_string()),
-                Value::String(if col.primary_key { "PRI" } else { "" }.to_string()),
+                Value::String(key.to_string()),
+                default,
+                Value::String(if col.auto_increment { "auto_increment" } else { "" }.to_string()),
             ]);
         }
 
         Ok(QueryResult::Select(result))
     }
 
+    /// SHOW INDEX FROM table: one row per indexed column, primary key first,
+    /// composite indexes listed in column order
+    pub(crate) fn execute_show_index(&self, table_name: &str) -> Result<QueryResult> {
+        let schema = self.storage.get_schema(table_name)?;
+        let mut indexes: Vec<_> = self.storage.list_indexes()
+            .into_iter()
+            .filter(|idx| idx.table_name == schema.name)
+            .collect();
+        indexes.sort_by(|a, b| b.is_primary.cmp(&a.is_primary).then_with(|| a.name.cmp(&b.name)));
+
+        let mut result = ResultSet::new(
+            vec![
+                "Table".to_string(),
+                "Non_unique".to_string(),
+                "Key_name".to_string(),
+                "Seq_in_index".to_string(),
+                "Column_name".to_string(),
+            ],
+            vec![DataType::Text, DataType::Integer, DataType::Text, DataType::Integer, DataType::Text],
+        );
+
+        for index in &indexes {
+            let key_name = if index.is_primary { "PRIMARY" } else { index.name.as_str() };
+            for (seq, column) in index.columns.iter().enumerate() {
+                result.add_row(vec![
+                    Value::String(schema.name.clone()),
+                    Value::Integer(if index.unique || index.is_primary { 0 } else { 1 }),
+                    Value::String(key_name.to_string()),
+                    Value::Integer(seq as i64 + 1),
+                    Value::String(column.clone()),
+                ]);
+            }
+        }
+
+        Ok(QueryResult::Select(result))
+    }
+
 
-    /// Project row for simple query
+    /// SHOW STATUS: statement counters plus engine and storage state
+    pub(crate) fn execute_show_status(&self) -> Result<QueryResult> {
+        let mut status: Vec<(&str, String)> = self.stats.snapshot()
+            .into_iter()
+            .map(|(name, value)| (name, value.to_string()))
+            .collect();
+        status.push(("Granite_durable_lsn", self.txn_manager.durable_lsn().to_string()));
+        status.push((
+            "Sandstone_dirty_tables",
+            self.handlers.values().map(|handler| handler.dirty_table_count()).sum::<usize>().to_string(),
+        ));
+        let flush = self.handlers.get(&crate::engines::EngineType::Sandstone)
+            .and_then(|handler| handler.flush_status())
+            .unwrap_or_default();
+        // Seconds since the Unix epoch, 0 before the first successful flush
+        let last_flush = flush.last_success
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_secs());
+        status.push(("Sandstone_last_flush", last_flush.to_string()));
+        status.push(("Sandstone_flush_failures", flush.failures.to_string()));
+        status.push(("Sandstone_last_flush_error", flush.last_error.unwrap_or_default()));
+        let crdt = self.handlers.get(&crate::engines::EngineType::Sandstone)
+            .and_then(|handler| handler.crdt_status())
+            .unwrap_or_default();
+        status.push(("Sandstone_pending_deltas", crdt.pending_deltas.to_string()));
+        status.push(("Sandstone_tombstones", crdt.tombstones.to_string()));
+        status.push(("Open_tables", self.storage.list_tables().len().to_string()));
+
+        let mut result = ResultSet::new(
+            vec!["Variable_name".to_string(), "Value".to_string()],
+            vec![DataType::Text, DataType::Text],
+        );
+        for (name, value) in status {
+            result.add_row(vec![Value::String(name.to_string()), Value::String(value)]);
+        }
+        Ok(QueryResult::Select(result))
+    }
+
+    /// SHOW WARNINGS: the conditions raised by the previous statement
+    pub(crate) fn execute_show_warnings(&self, session: &Session) -> Result<QueryResult> {
+        let mut result = ResultSet::new(
+            vec!["Level".to_string(), "Code".to_string(), "Message".to_string()],
+            vec![DataType::Text, DataType::Integer, DataType::Text],
+        );
+        for warning in session.warnings() {
+            result.add_row(vec![
+                Value::String(warning.level.to_string()),
+                Value::Integer(warning.code as i64),
+                Value::String(warning.message),
+            ]);
+        }
+        Ok(QueryResult::Select(result))
+    }
+
+    /// Project row for simple query; stars expand in place to one value per schema column
     fn project_row_simple(
         &self,
         columns: &[SelectColumn],
@@ -634,15 +771,15 @@ impl Executor {
 
         for col in columns {
             match col {
-                SelectColumn::Star => {
-                    result.extend(row.values.clone());
-                }
-                SelectColumn::QualifiedStar { table: _ } => {
-                     // For simple queries, simple projection implies single table so qualified star is same as star
-                    result.extend(row.values.clone());
+                // For simple queries, simple projection implies single table so qualified star is same as star
+                // (the qualifier was checked when the columns were resolved)
+                SelectColumn::Star | SelectColumn::QualifiedStar { .. } => {
+                    result.extend((0..schema.columns.len()).map(|i| row.values.get(i).cloned().unwrap_or(Value::Null)));
                 }
                 SelectColumn::Expr { expr, .. } => {
-                    let value = evaluator::eval_expr_simple(expr, row, schema, table_alias, session.last_insert_id)?;
+                    let outer = simple_row_columns(row, schema, table_alias);
+                    let expr = self.bind_scalar_subqueries(expr, &outer, session)?;
+                    let value = evaluator::eval_expr_simple(&expr, row, schema, table_alias, session.last_insert_id)?;
                     result.push(value);
                 }
             }
@@ -677,7 +814,9 @@ impl Executor {
                     result.extend(row.values[start..end].to_vec());
                 }
                 SelectColumn::Expr { expr, .. } => {
-                    let value = evaluator::eval_expr_join(expr, row, tables, session.last_insert_id)?;
+                    let outer = join_row_columns(row, tables);
+                    let expr = self.bind_scalar_subqueries(expr, &outer, session)?;
+                    let value = evaluator::eval_expr_join(&expr, row, tables, session.last_insert_id)?;
                     result.push(value);
                 }
             }
@@ -686,78 +825,161 @@ impl Executor {
         Ok(result)
     }
 
-    /// Extract a simple equality condition (column = literal) for index lookup
-    /// Returns Some((column_name, value)) if the expression is a simple equality
-    // extract_simple_equality was removed - it was unused and produced
-    // dead_code warnings. Keep index-related extraction logic in
-    // `extract_equality_conjuncts` which is actively used.
-    
-    /// Extract all equality conjuncts from a WHERE clause for composite index lookup
-    /// Returns a list of (column_name, value) pairs for all equality conditions
-    /// connected by AND. The order of columns matches the order they appear in the expression.
-    fn extract_equality_conjuncts(
-        expr: &Expr,
-        schema: &TableSchema,
+    /// Choose how to read `table_name`'s rows for `where_expr`
+    pub(crate) fn plan_access(
+        &self,
+        table_name: &str,
         table_alias: &str,
-    ) -> Vec<(String, Value)> {
-        // BinaryOperator import not needed here
-        let mut result = Vec::new();
-        Self::collect_equality_conjuncts(expr, schema, table_alias, &mut result);
-        result
-    }
-    
-    /// Helper to recursively collect equality conjuncts connected by AND
-    fn collect_equality_conjuncts(
-        expr: &Expr,
+        where_expr: &Expr,
         schema: &TableSchema,
-        table_alias: &str,
-        result: &mut Vec<(String, Value)>,
-    ) {
-        use crate::parser::BinaryOperator;
-        
-        match expr {
-            // Handle AND: recursively collect from both sides
-            Expr::BinaryOp { left, op: BinaryOperator::And, right } => {
-                Self::collect_equality_conjuncts(left, schema, table_alias, result);
-                Self::collect_equality_conjuncts(right, schema, table_alias, result);
+    ) -> Result<AccessPlan> {
+        if !self.engine_supports_indexes(table_name)? {
+            return Ok(AccessPlan::FullScan);
+        }
+        Ok(planner::plan_table_access(where_expr, schema, table_alias, &self.storage))
+    }
+
+    /// Read the candidate rows of `plan`.
+    ///
+    /// When the environment variable MINISQL_DEBUG_INDEX=1, the plan and the
+    /// number of rows it produced are printed to stderr.
+    pub(crate) fn read_access_plan(&self, table_name: &str, plan: &AccessPlan) -> Result<Vec<Row>> {
+        let started = Instant::now();
+        let rows = self.read_plan_rows(table_name, plan)?;
+        if std::env::var("MINISQL_DEBUG_INDEX").map(|v| v != "0").unwrap_or(false) {
+            eprintln!("[IDX DEBUG] Table='{}' plan={:?} rows_returned={} time_ms={}",
+                      table_name, plan, rows.len(), started.elapsed().as_millis());
+        }
+        Ok(rows)
+    }
+
+    /// Rows of `plan`, without `read_access_plan`'s debug output
+    fn read_plan_rows(&self, table_name: &str, plan: &AccessPlan) -> Result<Vec<Row>> {
+        Ok(match plan {
+            AccessPlan::FullScan => self.scan_table(table_name)?,
+            AccessPlan::IndexEquality { index, values } => {
+                self.storage.get_rows_by_index_key(table_name, &index.name, values)?
             }
-            
-            // Handle equality: extract column = literal
-            Expr::BinaryOp { left, op: BinaryOperator::Equal, right } => {
-                // Try column = literal
-                if let (Expr::Column { table, name }, Expr::Literal(value)) = (left.as_ref(), right.as_ref()) {
-                    if Self::column_matches_table(table.as_ref(), table_alias, &schema.name) {
-                        if schema.find_column(name).is_some() {
-                            // Only add if not already present (avoid duplicates)
-                            if !result.iter().any(|(c, _)| c.eq_ignore_ascii_case(name)) {
-                                result.push((name.clone(), value.clone()));
-                            }
-                        }
-                    }
-                }
-                
-                // Try literal = column (reversed)
-                if let (Expr::Literal(value), Expr::Column { table, name }) = (left.as_ref(), right.as_ref()) {
-                    if Self::column_matches_table(table.as_ref(), table_alias, &schema.name) {
-                        if schema.find_column(name).is_some() {
-                            if !result.iter().any(|(c, _)| c.eq_ignore_ascii_case(name)) {
-                                result.push((name.clone(), value.clone()));
-                            }
+            AccessPlan::IndexRange { index, lower, upper } => {
+                self.storage.get_rows_by_index_filter(table_name, &index.name, &|value| planner::within_bounds(value, lower, upper))?
+            }
+            AccessPlan::IndexUnion { branches } => {
+                // A row matching several branches is read once per branch but kept once
+                let mut seen = HashSet::new();
+                let mut rows = Vec::new();
+                for branch in branches {
+                    for row in self.read_plan_rows(table_name, branch)? {
+                        if seen.insert(row.id) {
+                            rows.push(row);
                         }
                     }
                 }
+                rows
             }
-            
-            // Other expressions are not simple conjuncts
-            _ => {}
+        })
+    }
+
+    /// Estimate how many rows `plan` reads from the engine's row count and the
+    /// index entries, without reading any rows. `None` when the engine keeps
+    /// no count. Rows matching several branches of an index union are counted
+    /// once per branch.
+    fn estimate_plan_rows(&self, table_name: &str, plan: &AccessPlan, txn_id: TxnId) -> Result<Option<u64>> {
+        Ok(match plan {
+            AccessPlan::FullScan => self.get_engine(table_name)?.row_count(txn_id, table_name)?,
+            AccessPlan::IndexEquality { index, values } => {
+                Some(self.storage.count_rows_by_index_key(table_name, &index.name, values)?)
+            }
+            AccessPlan::IndexRange { index, lower, upper } => {
+                Some(self.storage.count_rows_by_index_filter(table_name, &index.name, &|value| planner::within_bounds(value, lower, upper))?)
+            }
+            AccessPlan::IndexUnion { branches } => {
+                branches.iter().map(|branch| self.estimate_plan_rows(table_name, branch, txn_id)).sum::<Result<Option<u64>>>()?
+            }
+        })
+    }
+
+    /// Find candidate rows for `where_expr` through an index, as
+    /// `stream_simple_select` does.
+    ///
+    /// Returns `None` when a full scan is needed. The returned rows are a
+    /// superset of the matches; callers still evaluate the full WHERE clause.
+    pub(crate) fn index_candidate_rows(
+        &self,
+        table_name: &str,
+        table_alias: &str,
+        where_expr: &Expr,
+        schema: &TableSchema,
+    ) -> Result<Option<Vec<Row>>> {
+        match self.plan_access(table_name, table_alias, where_expr, schema)? {
+            AccessPlan::FullScan => Ok(None),
+            plan => self.read_access_plan(table_name, &plan).map(Some),
         }
     }
-    
-    /// Check if a column's table qualifier matches the expected table
-    fn column_matches_table(table_qualifier: Option<&String>, table_alias: &str, table_name: &str) -> bool {
-        match table_qualifier {
-            Some(tbl) => tbl.eq_ignore_ascii_case(table_alias) || tbl.eq_ignore_ascii_case(table_name),
-            None => true, // No qualifier means it could match any table
+
+    /// Execute EXPLAIN SELECT: one row per table read, in join order
+    ///
+    /// `type` is `index` for an index lookup, `range` for a range of an
+    /// index, `index_merge` for a union of index reads and `ALL` for a full
+    /// scan, and `key` names the indexes read. `rows` is an estimate taken
+    /// from the table's row count or the matching index entries, without
+    /// reading any rows; it is NULL when the engine cannot tell. Joined tables
+    /// name the join strategy in `Extra`. A SELECT without FROM gives a
+    /// single `No tables used` row.
+    pub(crate) fn execute_explain(&self, select: &SelectStmt, session: &Session) -> Result<QueryResult> {
+        let mut result = ResultSet::new(
+            vec![
+                "id".to_string(),
+                "table".to_string(),
+                "type".to_string(),
+                "key".to_string(),
+                "rows".to_string(),
+                "Extra".to_string(),
+            ],
+            vec![DataType::Integer, DataType::Text, DataType::Text, DataType::Text, DataType::Integer, DataType::Text],
+        );
+        let text = |s: &str| Value::String(s.to_string());
+        let row_estimate = |rows: Option<u64>| rows.map_or(Value::Null, |n| Value::Integer(n as i64));
+        let txn_id = self.get_txn_id(session);
+
+        let Some(from) = &select.from else {
+            result.add_row(vec![Value::Integer(1), Value::Null, Value::Null, Value::Null, Value::Null, text("No tables used")]);
+            return Ok(QueryResult::Select(result));
+        };
+
+        let schema = self.storage.get_schema(&from.name)?;
+        let alias = from.effective_name().to_string();
+        // Joins and samples always read the whole FROM table
+        let plan = match &select.where_clause {
+            Some(where_expr) if select.joins.is_empty() && from.sample.is_none() => {
+                self.plan_access(&from.name, &alias, where_expr, &schema)?
+            }
+            _ => AccessPlan::FullScan,
+        };
+        // Named as in SHOW INDEX; an index union lists every index it reads
+        let keys: Vec<&str> = plan.indexes().into_iter()
+            .map(|index| if index.is_primary { "PRIMARY" } else { index.name.as_str() })
+            .collect();
+        let key = if keys.is_empty() { Value::Null } else { text(&keys.join(",")) };
+        let extra = if select.where_clause.is_some() { text("Using where") } else { Value::Null };
+        let rows = self.estimate_plan_rows(&from.name, &plan, txn_id)?;
+        result.add_row(vec![Value::Integer(1), text(&alias), text(plan.access_type()), key, row_estimate(rows), extra]);
+
+        let mut current_schema = schema;
+        let mut current_alias = alias;
+        for join_clause in &select.joins {
+            let right_schema = self.storage.get_schema(&join_clause.table.name)?;
+            let right_alias = join_clause.table.effective_name().to_string();
+            let context = JoinContext::new(current_schema, current_alias, right_schema, right_alias.clone());
+            let strategy = match select_join_strategy(&join_clause.on_condition, &context).name() {
+                "HashJoin" => "Using hash join",
+                _ => "Using nested loop join",
+            };
+            let total = self.get_engine(&join_clause.table.name)?.row_count(txn_id, &join_clause.table.name)?;
+            result.add_row(vec![Value::Integer(1), text(&right_alias), text("ALL"), Value::Null, row_estimate(total), text(strategy)]);
+            current_schema = context.combined_schema;
+            current_alias = "joined".to_string();
         }
+
+        Ok(QueryResult::Select(result))
     }
 }
@@ -0,0 +1,69 @@
+//! Sandstone replication: the CRDT deltas this node hands to and takes from
+//! its peers
+//!
+//! Only Sandstone tables replicate. Merged deltas are applied to the page
+//! table like local writes, so they reach disk with the next flush, and they
+//! flush the query cache. A read-only server refuses to merge.
+
+use std::sync::Arc;
+
+use crate::engines::sandstone::DeltaState;
+use crate::engines::{EngineType, SandstoneEngine};
+use crate::error::{MiniSqlError, Result};
+use crate::executor::Executor;
+
+impl Executor {
+    /// The Sandstone engine, when enabled
+    pub fn sandstone(&self) -> Option<&Arc<SandstoneEngine>> {
+        self.sandstone.as_ref()
+    }
+
+    /// Take the deltas recorded for a Sandstone table since the last call
+    pub fn take_replication_deltas(&self, table_name: &str) -> Result<Vec<DeltaState>> {
+        Ok(self.replicated_table(table_name)?.get_pending_deltas(table_name))
+    }
+
+    /// Hand back deltas taken by [`Executor::take_replication_deltas`] that
+    /// could not be delivered, so the next call returns them again
+    pub fn requeue_replication_deltas(&self, table_name: &str, deltas: Vec<DeltaState>) -> Result<()> {
+        self.replicated_table(table_name)?.requeue_deltas(table_name, deltas);
+        Ok(())
+    }
+
+    /// Merge deltas received from a peer into a Sandstone table. Returns the
+    /// number of deltas merged.
+    pub fn merge_replication_deltas(&self, table_name: &str, deltas: Vec<DeltaState>) -> Result<usize> {
+        if self.read_only {
+            return Err(MiniSqlError::read_only());
+        }
+        let sandstone = self.replicated_table(table_name)?;
+        if let Some(delta) = deltas.iter().find(|d| d.table_name != table_name) {
+            return Err(MiniSqlError::Table(format!(
+                "Delta for table '{}' sent for table '{}'",
+                delta.table_name, table_name
+            )));
+        }
+
+        let count = deltas.len();
+        for delta in deltas {
+            sandstone.merge_delta(table_name, delta)?;
+        }
+        if let Some(cache) = &self.query_cache {
+            cache.invalidate();
+        }
+        Ok(count)
+    }
+
+    /// The Sandstone engine, after checking it holds `table_name`
+    fn replicated_table(&self, table_name: &str) -> Result<&Arc<SandstoneEngine>> {
+        let schema = self.storage.get_schema(table_name)?;
+        if schema.engine_type != EngineType::Sandstone {
+            return Err(MiniSqlError::Table(format!(
+                "Table '{}' uses the {} engine; only Sandstone tables replicate",
+                table_name, schema.engine_type
+            )));
+        }
+        self.sandstone.as_ref()
+            .ok_or_else(|| MiniSqlError::Table("Engine 'Sandstone' is not enabled".into()))
+    }
+}
@@ -0,0 +1,77 @@
+//! Table sampling (`TABLESAMPLE`)
+//!
+//! A sampled table is thinned while it is scanned, before any filtering, so
+//! rows left out are never copied and `WHERE`, joins and aggregates see only
+//! the sampled rows:
+//!
+//! - `n PERCENT` keeps each row independently with probability n/100, so the
+//!   sample size is only approximately n% of the table.
+//! - `n ROWS` keeps exactly n rows (or every row of a smaller table), chosen
+//!   uniformly by selection sampling. Rows after the n-th pick are skipped.
+//!
+//! Rows are visited in row id order, so with `REPEATABLE (seed)` the same
+//! seed over the same table contents selects the same rows.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::error::Result;
+use crate::executor::{Executor, Session};
+use crate::parser::{SampleSize, TableRef, TableSample};
+use crate::types::Row;
+
+/// Decides, one row at a time as a table is scanned, which rows a sample keeps
+pub(crate) struct RowSampler {
+    rng: StdRng,
+    size: SampleSize,
+    /// Rows kept so far
+    picked: u64,
+}
+
+impl RowSampler {
+    pub(crate) fn new(sample: &TableSample) -> Self {
+        let rng = match sample.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        RowSampler { rng, size: sample.size, picked: 0 }
+    }
+
+    /// Whether to keep the next row, given the number of rows not yet
+    /// visited (this one included)
+    pub(crate) fn keep(&mut self, remaining: usize) -> bool {
+        let keep = match self.size {
+            SampleSize::Percent(percent) => self.rng.gen::<f64>() < percent / 100.0,
+            SampleSize::Rows(target) => {
+                // Pick this row with probability (still needed) / (still available)
+                let needed = target.saturating_sub(self.picked);
+                needed > 0 && self.rng.gen_range(0..remaining.max(1) as u64) < needed
+            }
+        };
+        self.picked += u64::from(keep);
+        keep
+    }
+}
+
+impl Executor {
+    /// Read all rows of a FROM item visible to the session, applying its
+    /// `TABLESAMPLE` clause if it has one
+    pub(crate) fn read_table_ref(&self, table: &TableRef, session: &Session) -> Result<Vec<Row>> {
+        let Some(sample) = &table.sample else {
+            let rows = self.read_table(&table.name, session)?;
+            session.record_read(rows.len(), false);
+            return Ok(rows);
+        };
+
+        let mut sampler = RowSampler::new(sample);
+        let rows = match self.snapshot_rows(&table.name, session)? {
+            Some(rows) => {
+                let total = rows.len();
+                rows.into_iter().enumerate().filter(|(i, _)| sampler.keep(total - i)).map(|(_, row)| row).collect()
+            }
+            None => self.get_engine(&table.name)?.scan_sampled(&table.name, &mut |remaining| sampler.keep(remaining))?,
+        };
+        session.record_read(rows.len(), false);
+        Ok(rows)
+    }
+}
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
-use crate::error::{ColumnContext, MiniSqlError, Result};
-use crate::parser::SelectColumn;
-use crate::types::{DataType, TableSchema, Value};
-use crate::executor::evaluator;
+use crate::datetime::Date;
+use crate::error::{mysql_error_codes, ColumnContext, MiniSqlError, Result};
+use crate::parser::{Expr, SelectColumn, SelectStmt};
+use crate::types::{ColumnDef, DataType, TableSchema, Value};
+use crate::executor::{evaluator, Session, WarningLevel};
 
 /// Information about tables in a join query
 #[derive(Clone)]
@@ -90,13 +91,15 @@ impl JoinTableInfo {
     }
 }
 
-/// Coerce row values to match schema types
-pub fn coerce_row_types(values: &[Value], schema: &TableSchema) -> Result<Vec<Value>> {
+/// Coerce row values to match schema types. Under a strict sql_mode, strings
+/// longer than a VARCHAR(n) column and non-numeric strings for numeric columns
+/// are errors; otherwise they are truncated or stored as 0 with a warning.
+pub fn coerce_row_types(values: &[Value], schema: &TableSchema, session: &Session) -> Result<Vec<Value>> {
     let mut result = Vec::with_capacity(values.len());
     
     for (i, value) in values.iter().enumerate() {
         let col = &schema.columns[i];
-        let coerced = coerce_value(value, &col.data_type)?;
+        let coerced = fit_to_length(coerce_for_column(value, col, session)?, col, session)?;
         
         // Check NOT NULL constraint
         if !col.nullable && coerced.is_null() {
@@ -112,6 +115,52 @@ pub fn coerce_row_types(values: &[Value], schema: &TableSchema) -> Result<Vec<Va
     Ok(result)
 }
 
+/// Enforce a VARCHAR(n) column's length, counted in characters as in MySQL.
+/// Numbers, booleans and dates are stored as their text first, so their
+/// length is checked too.
+pub fn fit_to_length(value: Value, column: &ColumnDef, session: &Session) -> Result<Value> {
+    let value = match (value, &column.data_type) {
+        (Value::Boolean(b), DataType::Varchar(_)) => Value::String(if b { "1" } else { "0" }.to_string()),
+        (value @ (Value::Integer(_) | Value::Float(_) | Value::Date(_)), DataType::Varchar(_)) => {
+            Value::String(value.to_string())
+        }
+        (value, _) => value,
+    };
+    let (Value::String(s), DataType::Varchar(Some(max))) = (&value, &column.data_type) else {
+        return Ok(value);
+    };
+    match s.char_indices().nth(*max as usize) {
+        None => Ok(value),
+        Some(_) if session.sql_mode.strict() => Err(MiniSqlError::data_too_long(&column.name)),
+        Some((end, _)) => {
+            session.warn(WarningLevel::Warning, mysql_error_codes::WARN_DATA_TRUNCATED, format!(
+                "Data truncated for column '{}' at row {}",
+                column.name, session.current_row
+            ));
+            Ok(Value::String(s[..end].to_string()))
+        }
+    }
+}
+
+/// Coerce a value for `column`. Outside strict mode a string that is not a
+/// number is stored in a numeric column as 0 with a warning, as in MySQL.
+fn coerce_for_column(value: &Value, column: &ColumnDef, session: &Session) -> Result<Value> {
+    let result = coerce_value(value, &column.data_type);
+    let (Err(_), Value::String(s), false) = (&result, value, session.sql_mode.strict()) else {
+        return result;
+    };
+    let (kind, zero) = match column.data_type {
+        DataType::Integer => ("integer", Value::Integer(0)),
+        DataType::Float => ("double", Value::Float(0.0)),
+        _ => return result,
+    };
+    session.warn(WarningLevel::Warning, mysql_error_codes::ER_TRUNCATED_WRONG_VALUE_FOR_FIELD, format!(
+        "Incorrect {} value: '{}' for column '{}' at row {}",
+        kind, s, column.name, session.current_row
+    ));
+    Ok(zero)
+}
+
 /// Coerce a value to a specific type
 fn coerce_value(value: &Value, target_type: &DataType) -> Result<Value> {
     if value.is_null() {
@@ -132,6 +181,8 @@ fn coerce_value(value: &Value, target_type: &DataType) -> Result<Value> {
         (Value::String(_), DataType::Varchar(_) | DataType::Text) => Ok(value.clone()),
         (Value::Boolean(_), DataType::Boolean) => Ok(value.clone()),
         (Value::Json(_), DataType::Json) => Ok(value.clone()),
+        (Value::Date(_), DataType::Date) => Ok(value.clone()),
+        (Value::Bytes(_), DataType::Blob) => Ok(value.clone()),
 
         // Numeric coercion
         (Value::Integer(i), DataType::Float) => Ok(Value::Float(*i as f64)),
@@ -152,6 +203,18 @@ fn coerce_value(value: &Value, target_type: &DataType) -> Result<Value> {
             Ok(Value::Json(json))
         }
 
+        // String to DATE
+        (Value::String(s), DataType::Date) => Date::parse(s).map(Value::Date).ok_or_else(|| {
+            MiniSqlError::Type(format!("Incorrect date value: '{}'", s))
+        }),
+
+        // Binary data: text stores its UTF-8 bytes, and bytes must be UTF-8 to be text
+        (Value::String(s), DataType::Blob) => Ok(Value::Bytes(s.as_bytes().to_vec())),
+        (_, DataType::Blob) => Ok(Value::Bytes(value.to_string().into_bytes())),
+        (Value::Bytes(b), DataType::Varchar(_) | DataType::Text) => String::from_utf8(b.clone())
+            .map(Value::String)
+            .map_err(|_| MiniSqlError::Type("Invalid UTF-8 string value for a text column".into())),
+
         // Numeric string parsing
         (Value::String(s), DataType::Integer) => {
             let i: i64 = s.parse().map_err(|_| {
@@ -185,10 +248,13 @@ fn coerce_value(value: &Value, target_type: &DataType) -> Result<Value> {
 }
 
 /// Resolve SELECT columns to names and types (simple query)
+///
+/// `*` and `table.*` may be mixed with expressions; each star expands in
+/// place to the table's columns.
 pub fn resolve_select_columns_simple(
     columns: &[SelectColumn],
     schema: &TableSchema,
-    _table_alias: &str,
+    table_alias: &str,
 ) -> Result<(Vec<String>, Vec<DataType>)> {
     let mut names = Vec::new();
     let mut types = Vec::new();
@@ -201,8 +267,12 @@ pub fn resolve_select_columns_simple(
                     types.push(c.data_type.clone());
                 }
             }
-            SelectColumn::QualifiedStar { table: _ } => {
-                // For simple queries, qualified star is the same as star
+            SelectColumn::QualifiedStar { table } => {
+                // For simple queries, qualified star is the same as star once
+                // the qualifier is known to name the queried table
+                if !table.eq_ignore_ascii_case(table_alias) && !table.eq_ignore_ascii_case(&schema.name) {
+                    return Err(MiniSqlError::unknown_table_in_field_list(table));
+                }
                 for c in &schema.columns {
                     names.push(c.name.clone());
                     types.push(c.data_type.clone());
@@ -220,6 +290,63 @@ pub fn resolve_select_columns_simple(
     Ok((names, types))
 }
 
+/// Check that every qualified column of a single-table SELECT names its
+/// table the way the query does: by its alias when it has one (as in MySQL,
+/// the table name is then no longer in scope), otherwise by its name.
+/// References inside subqueries are left to the subquery, which may
+/// correlate them with this table.
+pub fn check_qualifiers_simple(select: &SelectStmt, table_alias: &str) -> Result<()> {
+    for col in &select.columns {
+        match col {
+            SelectColumn::Expr { expr, .. } => check_expr_qualifiers(expr, table_alias, ColumnContext::FieldList)?,
+            SelectColumn::QualifiedStar { table } if !table.eq_ignore_ascii_case(table_alias) => {
+                return Err(MiniSqlError::unknown_table_in_field_list(table));
+            }
+            SelectColumn::QualifiedStar { .. } | SelectColumn::Star => {}
+        }
+    }
+    if let Some(where_expr) = &select.where_clause {
+        check_expr_qualifiers(where_expr, table_alias, ColumnContext::WhereClause)?;
+    }
+    for expr in &select.group_by {
+        check_expr_qualifiers(expr, table_alias, ColumnContext::GroupByClause)?;
+    }
+    for clause in &select.order_by {
+        check_expr_qualifiers(&clause.expr, table_alias, ColumnContext::OrderClause)?;
+    }
+    Ok(())
+}
+
+fn check_expr_qualifiers(expr: &Expr, table_alias: &str, context: ColumnContext) -> Result<()> {
+    let check = |expr: &Expr| check_expr_qualifiers(expr, table_alias, context);
+    match expr {
+        Expr::Column { table: Some(table), name } if !table.eq_ignore_ascii_case(table_alias) => {
+            Err(MiniSqlError::unknown_column_qualified(table, name, context))
+        }
+        Expr::BinaryOp { left, right, .. } => {
+            check(left)?;
+            check(right)
+        }
+        Expr::Not(inner)
+        | Expr::UnaryMinus(inner)
+        | Expr::IsNull(inner)
+        | Expr::IsNotNull(inner)
+        | Expr::JsonAccess { expr: inner, .. }
+        | Expr::Cast { expr: inner, .. }
+        | Expr::Like { expr: inner, .. }
+        | Expr::InSubquery { expr: inner, .. } => check(inner),
+        Expr::In { expr: inner, values } | Expr::NotIn { expr: inner, values } => {
+            check(inner)?;
+            values.iter().try_for_each(check)
+        }
+        Expr::FunctionCall { args, .. } => args.iter().try_for_each(check),
+        Expr::Literal(_)
+        | Expr::Column { .. }
+        | Expr::Placeholder(_)
+        | Expr::UserVariable(_)
+        | Expr::ScalarSubquery(_) => Ok(()),
+    }
+}
 
 /// Resolve SELECT columns for join query
 pub fn resolve_select_columns_join(
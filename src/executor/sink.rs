@@ -0,0 +1,31 @@
+//! Row sinks for streaming SELECT results
+//!
+//! A [`RowSink`] receives the column metadata once, then each projected row as
+//! soon as the executor produces it. [`ResultSet`] is itself a sink, so the
+//! buffered `execute()` path and the streaming path share one implementation.
+
+use crate::error::Result;
+use crate::types::{DataType, ResultSet, Value};
+
+/// Receiver for the rows of a SELECT
+pub trait RowSink {
+    /// Called once with the result columns, before any row
+    fn begin(&mut self, columns: &[String], column_types: &[DataType]) -> Result<()>;
+
+    /// Called for every result row, in output order.
+    /// Returning an error aborts the query.
+    fn send_row(&mut self, row: Vec<Value>) -> Result<()>;
+}
+
+impl RowSink for ResultSet {
+    fn begin(&mut self, columns: &[String], column_types: &[DataType]) -> Result<()> {
+        self.columns = columns.to_vec();
+        self.column_types = column_types.to_vec();
+        Ok(())
+    }
+
+    fn send_row(&mut self, row: Vec<Value>) -> Result<()> {
+        self.add_row(row);
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+//! Statement counters reported by SHOW STATUS
+//!
+//! Counters are shared by every connection using the executor and only ever
+//! grow. Names follow MySQL's status variables: `Questions` counts every
+//! statement, `Com_*` counts statements of one kind.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use crate::parser::Statement;
+
+/// Executor-wide statement counters
+pub(crate) struct StatementStats {
+    started: Instant,
+    questions: AtomicU64,
+    com_select: AtomicU64,
+    com_insert: AtomicU64,
+    com_update: AtomicU64,
+    com_delete: AtomicU64,
+}
+
+impl StatementStats {
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            questions: AtomicU64::new(0),
+            com_select: AtomicU64::new(0),
+            com_insert: AtomicU64::new(0),
+            com_update: AtomicU64::new(0),
+            com_delete: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a statement about to run
+    pub(crate) fn record(&self, stmt: &Statement) {
+        self.questions.fetch_add(1, Ordering::Relaxed);
+        let counter = match stmt {
+            Statement::Select(_) => &self.com_select,
+            Statement::Insert(_) => &self.com_insert,
+            Statement::Update(_) => &self.com_update,
+            Statement::Delete(_) => &self.com_delete,
+            _ => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counter values as (Variable_name, value) pairs
+    pub(crate) fn snapshot(&self) -> Vec<(&'static str, u64)> {
+        vec![
+            ("Questions", self.questions.load(Ordering::Relaxed)),
+            ("Com_select", self.com_select.load(Ordering::Relaxed)),
+            ("Com_insert", self.com_insert.load(Ordering::Relaxed)),
+            ("Com_update", self.com_update.load(Ordering::Relaxed)),
+            ("Com_delete", self.com_delete.load(Ordering::Relaxed)),
+            ("Uptime", self.started.elapsed().as_secs()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_record_counts_by_statement_kind() {
+        let stats = StatementStats::new();
+        for sql in ["SELECT 1", "INSERT INTO t VALUES (1)", "DELETE FROM t", "BEGIN", "SELECT 2"] {
+            stats.record(&Parser::parse(sql).unwrap());
+        }
+
+        let snapshot = stats.snapshot();
+        let value = |name: &str| snapshot.iter().find(|(n, _)| *n == name).unwrap().1;
+        assert_eq!(value("Questions"), 5);
+        assert_eq!(value("Com_select"), 2);
+        assert_eq!(value("Com_insert"), 1);
+        assert_eq!(value("Com_update"), 0);
+        assert_eq!(value("Com_delete"), 1);
+    }
+}
@@ -0,0 +1,319 @@
+//! Subquery resolution
+//!
+//! `expr [NOT] IN (SELECT ...)` subqueries are uncorrelated: they cannot see
+//! the outer row, so each runs once per statement. Before the statement
+//! executes, every such subquery is evaluated and the expression is rewritten
+//! to a plain `IN` / `NOT IN` over the values it returned. Row evaluation then
+//! follows the usual three-valued NULL logic of `IN`, and index selection sees
+//! an ordinary value list.
+//!
+//! Scalar subqueries, `(SELECT ...)`, must return one column and at most one
+//! row. An uncorrelated scalar subquery is likewise run once per statement and
+//! replaced by its value, so `WHERE id = (SELECT MAX(order_id) FROM refunds)`
+//! is an ordinary equality that can use an index.
+//!
+//! In the select list and in `WHERE`, a scalar subquery may also reference
+//! the outer row. It then runs once per outer row: columns that do not
+//! resolve against the subquery's own tables are bound to the outer row's
+//! values, and the subquery is executed as an ordinary SELECT. A correlated
+//! subquery therefore costs one query per outer row; an index on the
+//! correlated column keeps each of those a lookup rather than a scan.
+//! Correlated subqueries in `GROUP BY`, `ORDER BY` and `ON` are not
+//! supported.
+//!
+//! The same pass binds user variables (`@name`) to the session's current
+//! values, so a variable is read once per statement like any other constant,
+//! and compiles the pattern of each `LIKE` whose pattern is then a string
+//! literal, so rows are matched without parsing the pattern again.
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::error::{MiniSqlError, Result};
+use crate::executor::schema::JoinTableInfo;
+use crate::executor::{Executor, Session};
+use crate::like::LikePattern;
+use crate::parser::{BinaryOperator, Expr, SelectColumn, SelectStmt};
+use crate::types::{QueryResult, Row, TableSchema, Value};
+
+/// Looks up a column of the outer row by optional table qualifier and name
+pub(crate) type OuterColumns<'a> = dyn Fn(Option<&str>, &str) -> Option<Value> + 'a;
+
+/// Outer-row column lookup for a row of a single-table query
+pub(crate) fn simple_row_columns<'r>(
+    row: &'r Row,
+    schema: &'r TableSchema,
+    table_alias: &'r str,
+) -> impl Fn(Option<&str>, &str) -> Option<Value> + 'r {
+    move |table, name| {
+        let table_matches = table.is_none_or(|t| {
+            t.eq_ignore_ascii_case(table_alias) || t.eq_ignore_ascii_case(&schema.name)
+        });
+        let idx = schema.find_column(name).filter(|_| table_matches)?;
+        row.values.get(idx).cloned()
+    }
+}
+
+/// Outer-row column lookup for a combined row of a join query
+pub(crate) fn join_row_columns<'r>(
+    row: &'r Row,
+    tables: &'r JoinTableInfo,
+) -> impl Fn(Option<&str>, &str) -> Option<Value> + 'r {
+    move |table, name| {
+        let (idx, _) = tables.find_column(table, name).ok()?;
+        row.values.get(idx).cloned()
+    }
+}
+
+/// A table visible inside a subquery: its name, the name it is referenced by, and its schema
+struct ScopeTable {
+    name: String,
+    alias: String,
+    schema: TableSchema,
+}
+
+impl ScopeTable {
+    fn has_column(&self, table: Option<&str>, name: &str) -> bool {
+        match table {
+            Some(t) => t.eq_ignore_ascii_case(&self.alias) || t.eq_ignore_ascii_case(&self.name),
+            None => self.schema.find_column(name).is_some(),
+        }
+    }
+}
+
+/// Apply `f` to each direct sub-expression of `expr`, not descending into subqueries
+fn for_each_child_mut(expr: &mut Expr, mut f: impl FnMut(&mut Expr) -> Result<()>) -> Result<()> {
+    match expr {
+        Expr::BinaryOp { left, right, .. } => {
+            f(left)?;
+            f(right)
+        }
+        Expr::Not(inner)
+        | Expr::UnaryMinus(inner)
+        | Expr::IsNull(inner)
+        | Expr::IsNotNull(inner)
+        | Expr::JsonAccess { expr: inner, .. }
+        | Expr::Cast { expr: inner, .. }
+        | Expr::Like { expr: inner, .. }
+        | Expr::InSubquery { expr: inner, .. } => f(inner),
+        Expr::In { expr: inner, values } | Expr::NotIn { expr: inner, values } => {
+            f(inner)?;
+            values.iter_mut().try_for_each(f)
+        }
+        Expr::FunctionCall { args, .. } => args.iter_mut().try_for_each(f),
+        Expr::Literal(_)
+        | Expr::Column { .. }
+        | Expr::Placeholder(_)
+        | Expr::UserVariable(_)
+        | Expr::ScalarSubquery(_) => Ok(()),
+    }
+}
+
+/// Apply `f` to every top-level expression of a SELECT
+fn for_each_select_expr_mut(select: &mut SelectStmt, mut f: impl FnMut(&mut Expr) -> Result<()>) -> Result<()> {
+    for col in &mut select.columns {
+        if let SelectColumn::Expr { expr, .. } = col {
+            f(expr)?;
+        }
+    }
+    for join in &mut select.joins {
+        f(&mut join.on_condition)?;
+    }
+    if let Some(where_expr) = &mut select.where_clause {
+        f(where_expr)?;
+    }
+    select.group_by.iter_mut().try_for_each(&mut f)?;
+    select.order_by.iter_mut().try_for_each(|clause| f(&mut clause.expr))
+}
+
+/// Whether `expr` contains a scalar subquery outside any nested subquery
+fn contains_scalar_subquery(expr: &Expr) -> bool {
+    match expr {
+        Expr::ScalarSubquery(_) => true,
+        Expr::BinaryOp { left, right, .. } => contains_scalar_subquery(left) || contains_scalar_subquery(right),
+        Expr::Not(inner)
+        | Expr::UnaryMinus(inner)
+        | Expr::IsNull(inner)
+        | Expr::IsNotNull(inner)
+        | Expr::JsonAccess { expr: inner, .. }
+        | Expr::Cast { expr: inner, .. }
+        | Expr::Like { expr: inner, .. }
+        | Expr::InSubquery { expr: inner, .. } => contains_scalar_subquery(inner),
+        Expr::In { expr: inner, values } | Expr::NotIn { expr: inner, values } => {
+            contains_scalar_subquery(inner) || values.iter().any(contains_scalar_subquery)
+        }
+        Expr::FunctionCall { args, .. } => args.iter().any(contains_scalar_subquery),
+        Expr::Literal(_) | Expr::Column { .. } | Expr::Placeholder(_) | Expr::UserVariable(_) => false,
+    }
+}
+
+impl Executor {
+    /// Resolve the IN subqueries of a SELECT and snapshot the tables its
+    /// scalar subqueries read
+    pub(crate) fn resolve_select_subqueries(&self, select: &mut SelectStmt, session: &mut Session) -> Result<()> {
+        // A selected variable keeps its name once it is bound to a literal
+        for col in &mut select.columns {
+            if let SelectColumn::Expr { expr: Expr::UserVariable(name), alias: alias @ None } = col {
+                *alias = Some(format!("@{}", name));
+            }
+        }
+        for_each_select_expr_mut(select, |expr| self.resolve_subqueries(expr, session))
+    }
+
+    /// Run every IN subquery inside `expr` and replace it with the values it
+    /// returned, replace user variables with their session values and compile
+    /// constant LIKE patterns
+    pub(crate) fn resolve_subqueries(&self, expr: &mut Expr, session: &mut Session) -> Result<()> {
+        for_each_child_mut(expr, |child| self.resolve_subqueries(child, session))?;
+        match expr {
+            Expr::InSubquery { expr: inner, subquery, negated } => {
+                let values = self.subquery_values(subquery, session)?;
+                *expr = if values.is_empty() {
+                    // Nothing to match: IN is false and NOT IN true, even for NULL
+                    Expr::Literal(Value::Boolean(*negated))
+                } else {
+                    let inner = std::mem::replace(inner.as_mut(), Expr::Literal(Value::Null));
+                    let values = values.into_iter().map(Expr::Literal).collect();
+                    if *negated {
+                        Expr::NotIn { expr: Box::new(inner), values }
+                    } else {
+                        Expr::In { expr: Box::new(inner), values }
+                    }
+                };
+            }
+            Expr::ScalarSubquery(subquery) => {
+                self.resolve_select_subqueries(subquery, session)?;
+                self.snapshot_select_tables(subquery, session)?;
+                // A correlated subquery is evaluated per outer row later
+                if !self.is_correlated(subquery)? {
+                    *expr = Expr::Literal(self.scalar_subquery_value(subquery.as_ref().clone(), session)?);
+                }
+            }
+            Expr::UserVariable(name) => {
+                // An undefined variable reads as NULL
+                let value = session.user_variables.get(&name.to_lowercase()).cloned();
+                *expr = Expr::Literal(value.unwrap_or(Value::Null));
+            }
+            Expr::BinaryOp { left, op: BinaryOperator::Like { escape }, right } => {
+                if let Expr::Literal(Value::String(pattern)) = right.as_ref() {
+                    let pattern = Arc::new(LikePattern::new(pattern, *escape));
+                    let inner = std::mem::replace(left.as_mut(), Expr::Literal(Value::Null));
+                    *expr = Expr::Like { expr: Box::new(inner), pattern };
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Execute a subquery and return the distinct values of its single column
+    fn subquery_values(&self, subquery: &mut SelectStmt, session: &mut Session) -> Result<Vec<Value>> {
+        self.resolve_select_subqueries(subquery, session)?;
+        self.snapshot_select_tables(subquery, session)?;
+        let result = match self.execute_select(subquery.clone(), session)? {
+            QueryResult::Select(result) => result,
+            _ => return Err(MiniSqlError::Internal("Subquery did not produce a result set".into())),
+        };
+        if result.columns.len() != 1 {
+            return Err(MiniSqlError::operand_columns(1));
+        }
+
+        let mut seen = HashSet::new();
+        Ok(result.rows.into_iter()
+            .filter_map(|mut row| row.pop())
+            .filter(|value| seen.insert(value.clone()))
+            .collect())
+    }
+
+    /// Whether `subquery` references columns that are not its own
+    fn is_correlated(&self, subquery: &SelectStmt) -> Result<bool> {
+        let referenced = Cell::new(false);
+        let outer = |_: Option<&str>, _: &str| {
+            referenced.set(true);
+            None
+        };
+        self.bind_outer_columns(&mut subquery.clone(), &mut Vec::new(), &outer)?;
+        Ok(referenced.get())
+    }
+
+    /// Evaluate the scalar subqueries in `expr` for one outer row, replacing
+    /// each with its value. Returns `expr` itself when it has none.
+    pub(crate) fn bind_scalar_subqueries<'e>(
+        &self,
+        expr: &'e Expr,
+        outer: &OuterColumns,
+        session: &Session,
+    ) -> Result<Cow<'e, Expr>> {
+        if !contains_scalar_subquery(expr) {
+            return Ok(Cow::Borrowed(expr));
+        }
+        let mut expr = expr.clone();
+        self.replace_scalar_subqueries(&mut expr, outer, session)?;
+        Ok(Cow::Owned(expr))
+    }
+
+    fn replace_scalar_subqueries(&self, expr: &mut Expr, outer: &OuterColumns, session: &Session) -> Result<()> {
+        if let Expr::ScalarSubquery(subquery) = expr {
+            let mut bound = subquery.as_ref().clone();
+            self.bind_outer_columns(&mut bound, &mut Vec::new(), outer)?;
+            *expr = Expr::Literal(self.scalar_subquery_value(bound, session)?);
+            return Ok(());
+        }
+        for_each_child_mut(expr, |child| self.replace_scalar_subqueries(child, outer, session))
+    }
+
+    /// Replace the column references in `select` (and subqueries nested in it)
+    /// that do not resolve against its own tables with the outer row's values
+    fn bind_outer_columns(&self, select: &mut SelectStmt, scope: &mut Vec<ScopeTable>, outer: &OuterColumns) -> Result<()> {
+        let scope_len = scope.len();
+        for table in select.from.iter().chain(select.joins.iter().map(|join| &join.table)) {
+            scope.push(ScopeTable {
+                name: table.name.clone(),
+                alias: table.effective_name().to_string(),
+                schema: self.storage.get_schema(&table.name)?,
+            });
+        }
+        let result = for_each_select_expr_mut(select, |expr| self.bind_outer_expr(expr, scope, outer));
+        scope.truncate(scope_len);
+        result
+    }
+
+    fn bind_outer_expr(&self, expr: &mut Expr, scope: &mut Vec<ScopeTable>, outer: &OuterColumns) -> Result<()> {
+        match expr {
+            Expr::Column { table, name } => {
+                if !scope.iter().any(|t| t.has_column(table.as_deref(), name)) {
+                    if let Some(value) = outer(table.as_deref(), name) {
+                        *expr = Expr::Literal(value);
+                    }
+                }
+                Ok(())
+            }
+            Expr::ScalarSubquery(subquery) => self.bind_outer_columns(subquery, scope, outer),
+            Expr::InSubquery { expr: inner, subquery, .. } => {
+                self.bind_outer_expr(inner, scope, outer)?;
+                self.bind_outer_columns(subquery, scope, outer)
+            }
+            _ => for_each_child_mut(expr, |child| self.bind_outer_expr(child, scope, outer)),
+        }
+    }
+
+    /// Execute a scalar subquery: one column, at most one row (none gives NULL)
+    fn scalar_subquery_value(&self, mut subquery: SelectStmt, session: &Session) -> Result<Value> {
+        // Two rows are enough to tell that there is more than one
+        subquery.limit = Some(subquery.limit.map_or(2, |limit| limit.min(2)));
+        let result = match self.execute_select(subquery, session)? {
+            QueryResult::Select(result) => result,
+            _ => return Err(MiniSqlError::Internal("Subquery did not produce a result set".into())),
+        };
+        if result.columns.len() != 1 {
+            return Err(MiniSqlError::operand_columns(1));
+        }
+        match result.rows.len() {
+            0 => Ok(Value::Null),
+            1 => Ok(result.rows.into_iter().next().and_then(|mut row| row.pop()).unwrap_or(Value::Null)),
+            _ => Err(MiniSqlError::subquery_multiple_rows()),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Per-statement execution timeout
+//!
+//! `Session::statement_timeout_ms` bounds how long a single SELECT may run.
+//! The deadline is fixed when the statement starts; scans, joins and
+//! aggregations call [`StatementDeadline::tick`] once per row and abort with
+//! MySQL error 3024 after it has passed. The clock is only read every
+//! `CHECK_INTERVAL` rows, keeping the per-row cost to a counter increment.
+
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use crate::error::{MiniSqlError, Result};
+use crate::executor::Session;
+
+/// Rows processed between two reads of the clock
+const CHECK_INTERVAL: u32 = 256;
+
+/// Deadline of the running statement, checked while it walks rows
+pub(crate) struct StatementDeadline {
+    deadline: Option<(Instant, u64)>,
+    rows: Cell<u32>,
+}
+
+impl StatementDeadline {
+    /// Deadline of the statement `session` is currently running
+    pub(crate) fn new(session: &Session) -> Self {
+        Self {
+            deadline: session.statement_deadline,
+            rows: Cell::new(0),
+        }
+    }
+
+    /// Count one processed row, failing once the deadline has passed
+    pub(crate) fn tick(&self) -> Result<()> {
+        let Some((deadline, timeout_ms)) = self.deadline else {
+            return Ok(());
+        };
+        let rows = self.rows.get().wrapping_add(1);
+        self.rows.set(rows);
+        if rows.is_multiple_of(CHECK_INTERVAL) && Instant::now() >= deadline {
+            return Err(MiniSqlError::statement_timeout(timeout_ms));
+        }
+        Ok(())
+    }
+}
+
+/// Deadline for a statement starting now, as stored in `Session::statement_deadline`
+pub(crate) fn deadline_from_now(timeout_ms: Option<u64>) -> Option<(Instant, u64)> {
+    timeout_ms.map(|ms| (Instant::now() + Duration::from_millis(ms), ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_timeout_never_fails() {
+        let session = Session::new();
+        let deadline = StatementDeadline::new(&session);
+        for _ in 0..10 * CHECK_INTERVAL {
+            deadline.tick().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_expired_deadline_is_error_3024() {
+        let mut session = Session::new();
+        session.statement_deadline = deadline_from_now(Some(0));
+        let deadline = StatementDeadline::new(&session);
+        let err = (0..CHECK_INTERVAL).try_for_each(|_| deadline.tick()).unwrap_err();
+        assert_eq!(err.mysql_error_code(), 3024);
+    }
+}
@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+
 use crate::error::{MiniSqlError, Result};
-use crate::types::QueryResult;
+use crate::parser::{Expr, IsolationScope, SelectStmt};
+use crate::types::{IsolationLevel, QueryResult, Row, StorageFormat};
+use crate::executor::evaluator::eval_const_expr;
 use crate::executor::{Executor, Session};
 use crate::engines::granite::TxnId;
 
@@ -14,6 +18,9 @@ impl Executor {
 
         let txn_id = self.txn_manager.begin()?;
         session.txn_id = Some(txn_id);
+
+        let level = session.next_isolation_level.take().unwrap_or(session.isolation_level);
+        session.read_snapshot = level.uses_snapshot().then(HashMap::new);
         Ok(QueryResult::TransactionStarted)
     }
 
@@ -25,6 +32,7 @@ impl Executor {
         let txn_id = session.txn_id.take().ok_or_else(|| {
             MiniSqlError::Transaction("No transaction in progress".into())
         })?;
+        session.read_snapshot = None;
 
         // Take the set of modified engines from the session
         let modified_engines = std::mem::take(&mut session.modified_engines);
@@ -49,6 +57,7 @@ impl Executor {
         let txn_id = session.txn_id.take().ok_or_else(|| {
             MiniSqlError::Transaction("No transaction in progress".into())
         })?;
+        session.read_snapshot = None;
 
         // Take the set of modified engines from the session
         let modified_engines = std::mem::take(&mut session.modified_engines);
@@ -64,14 +73,164 @@ impl Executor {
         Ok(QueryResult::TransactionRolledBack)
     }
 
+    /// Execute SET autocommit. Turning autocommit back on commits the
+    /// transaction it left open, as MySQL does; a transaction started with
+    /// BEGIN while autocommit was already on stays open.
+    pub(crate) fn execute_set_autocommit(&self, enabled: bool, session: &mut Session) -> Result<QueryResult> {
+        if enabled && !session.autocommit && session.txn_id.is_some() {
+            self.execute_commit(session)?;
+        }
+        session.autocommit = enabled;
+        Ok(QueryResult::Ok)
+    }
+
+    /// Execute SET [SESSION] TRANSACTION ISOLATION LEVEL / SET transaction_isolation
+    ///
+    /// READ UNCOMMITTED is accepted and behaves like READ COMMITTED; SERIALIZABLE
+    /// is accepted and downgraded to REPEATABLE READ.
+    pub(crate) fn execute_set_isolation_level(
+        &self,
+        level: IsolationLevel,
+        scope: IsolationScope,
+        session: &mut Session,
+    ) -> Result<QueryResult> {
+        match scope {
+            IsolationScope::NextTransaction => {
+                if session.txn_id.is_some() {
+                    return Err(MiniSqlError::transaction_in_progress_characteristics());
+                }
+                session.next_isolation_level = Some(level);
+            }
+            IsolationScope::Session => session.isolation_level = level,
+        }
+        Ok(QueryResult::Ok)
+    }
+
+    /// Execute SET @name = expr [, ...]
+    ///
+    /// Each right-hand side is evaluated once, after the assignments before it,
+    /// so `SET @a = 1, @b = @a + 1` sees the new value of `@a`.
+    pub(crate) fn execute_set_user_variables(
+        &self,
+        assignments: Vec<(String, Expr)>,
+        session: &mut Session,
+    ) -> Result<QueryResult> {
+        for (name, mut expr) in assignments {
+            self.resolve_subqueries(&mut expr, session)?;
+            let value = eval_const_expr(&expr, session.last_insert_id)?;
+            session.user_variables.insert(name.to_lowercase(), value);
+        }
+        Ok(QueryResult::Ok)
+    }
+
+    /// Capture a snapshot of each Granite table a SELECT reads, the first time
+    /// the current REPEATABLE READ transaction touches it.
+    pub(crate) fn snapshot_select_tables(&self, select: &SelectStmt, session: &mut Session) -> Result<()> {
+        let Some(snapshot) = session.read_snapshot.as_mut() else {
+            return Ok(());
+        };
+        let tables = select.from.iter()
+            .map(|from| &from.name)
+            .chain(select.joins.iter().map(|join| &join.table.name));
+        for table_name in tables {
+            if snapshot.contains_key(table_name) || !self.get_engine(table_name)?.supports_transactions() {
+                continue;
+            }
+            let rows = self.scan_table(table_name)?;
+            snapshot.insert(table_name.clone(), rows);
+        }
+        Ok(())
+    }
+
+    /// Rows of `table_name` as seen by the session's snapshot, if it has one.
+    ///
+    /// Rows written by the transaction itself are read from the table so that
+    /// the transaction always sees its own changes.
+    pub(crate) fn snapshot_rows(&self, table_name: &str, session: &Session) -> Result<Option<Vec<Row>>> {
+        let (Some(snapshot), Some(txn_id)) = (session.read_snapshot.as_ref(), session.txn_id) else {
+            return Ok(None);
+        };
+        let Some(rows) = snapshot.get(table_name) else {
+            return Ok(None);
+        };
+
+        let modified = self.txn_manager.modified_row_ids(txn_id, table_name);
+        if modified.is_empty() {
+            return Ok(Some(rows.clone()));
+        }
+        let mut visible: Vec<Row> = rows.iter()
+            .filter(|row| !modified.contains(&row.id))
+            .cloned()
+            .collect();
+        visible.extend(
+            self.scan_table(table_name)?
+                .into_iter()
+                .filter(|row| modified.contains(&row.id)),
+        );
+        visible.sort_by_key(|row| row.id);
+        Ok(Some(visible))
+    }
+
+    /// Read every row of a table, honouring the session's snapshot
+    pub(crate) fn read_table(&self, table_name: &str, session: &Session) -> Result<Vec<Row>> {
+        match self.snapshot_rows(table_name, session)? {
+            Some(rows) => Ok(rows),
+            None => self.scan_table(table_name),
+        }
+    }
+
+    /// Execute SAVEPOINT name
+    ///
+    /// Savepoints track the Granite undo log. Sandstone changes are not
+    /// transactional and are unaffected by savepoints.
+    pub(crate) fn execute_savepoint(&self, name: &str, session: &Session) -> Result<QueryResult> {
+        // Outside a transaction there is nothing to roll back to (MySQL accepts it)
+        if let Some(txn_id) = session.txn_id {
+            self.txn_manager.savepoint(txn_id, name)?;
+        }
+        Ok(QueryResult::Ok)
+    }
+
+    /// Execute ROLLBACK TO SAVEPOINT name
+    pub(crate) fn execute_rollback_to_savepoint(&self, name: &str, session: &Session) -> Result<QueryResult> {
+        let txn_id = session.txn_id.ok_or_else(|| MiniSqlError::savepoint_not_found(name))?;
+        self.txn_manager.rollback_to_savepoint(txn_id, name, &self.storage)?;
+        Ok(QueryResult::Ok)
+    }
+
+    /// Execute RELEASE SAVEPOINT name
+    pub(crate) fn execute_release_savepoint(&self, name: &str, session: &Session) -> Result<QueryResult> {
+        let txn_id = session.txn_id.ok_or_else(|| MiniSqlError::savepoint_not_found(name))?;
+        self.txn_manager.release_savepoint(txn_id, name)?;
+        Ok(QueryResult::Ok)
+    }
+
     /// Execute CHECKPOINT command
     pub(crate) fn execute_checkpoint(&self, _session: &Session) -> Result<QueryResult> {
         self.txn_manager.checkpoint(&self.storage)?;
         Ok(QueryResult::Ok)
     }
 
+    /// Execute FLUSH TABLES: persist every engine's in-memory state now
+    ///
+    /// Unlike CHECKPOINT this leaves the WAL alone.
+    pub(crate) fn execute_flush_tables(&self, session: &Session) -> Result<QueryResult> {
+        // Table files must not pick up a transaction's uncommitted rows
+        if session.txn_id.is_some() {
+            return Err(MiniSqlError::Transaction(
+                "FLUSH TABLES cannot be run inside a transaction".into(),
+            ));
+        }
+        self.flush_tables()?;
+        Ok(QueryResult::Ok)
+    }
+
     /// Execute VACUUM command
-    pub(crate) fn execute_vacuum(&self, session: &Session) -> Result<QueryResult> {
+    pub(crate) fn execute_vacuum(
+        &self,
+        storage_format: Option<StorageFormat>,
+        session: &Session,
+    ) -> Result<QueryResult> {
         // Vacuum should not be run inside a transaction
         if session.txn_id.is_some() {
             return Err(MiniSqlError::Transaction(
@@ -80,11 +239,30 @@ impl Executor {
         }
 
         // Perform the vacuum operation
-        self.storage.vacuum()?;
+        self.storage.vacuum_with_format(storage_format)?;
 
         Ok(QueryResult::Ok)
     }
 
+    /// Flush all engines for a clean shutdown.
+    ///
+    /// Stops the background checkpointer, writes out Sandstone's dirty tables,
+    /// forces a final Granite WAL fsync, and saves the catalog and every table to disk.
+    pub fn shutdown(&self) -> Result<()> {
+        self.checkpointer.stop();
+        self.flush_tables()
+    }
+
+    /// Write out Sandstone's dirty tables, fsync the Granite WAL, and save
+    /// the catalog and every table to disk
+    fn flush_tables(&self) -> Result<()> {
+        for handler in self.handlers.values() {
+            handler.flush_all()?;
+        }
+        self.txn_manager.force_sync()?;
+        self.storage.flush_all()
+    }
+
     /// Get the current transaction ID or use auto-commit
     pub(crate) fn get_txn_id(&self, session: &Session) -> TxnId {
         session.txn_id.unwrap_or_else(|| self.txn_manager.auto_commit_txn())
@@ -136,6 +136,9 @@ impl JoinContext {
             columns,
             auto_increment_counter: 1,
             engine_type: crate::engines::EngineType::default(),
+            storage_format: crate::types::StorageFormat::default(),
+            foreign_keys: Vec::new(),
+            primary_key: Vec::new(),
         };
 
         Self {
@@ -274,6 +277,8 @@ impl ValueKey {
             Value::String(s) => s.clone(),
             Value::Boolean(b) => b.to_string(),
             Value::Json(j) => j.to_string(),
+            Value::Date(d) => d.to_string(),
+            Value::Bytes(b) => String::from_utf8_lossy(b).into_owned(),
         })
     }
 }
@@ -443,6 +448,9 @@ mod tests {
                 .collect(),
             auto_increment_counter: 1,
             engine_type: crate::engines::EngineType::default(),
+            storage_format: crate::types::StorageFormat::default(),
+            foreign_keys: Vec::new(),
+            primary_key: Vec::new(),
         }
     }
 
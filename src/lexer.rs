@@ -78,6 +78,8 @@ pub enum Token {
     
     // Literals
     Identifier(String),
+    UserVariable(String), // @name
+    SystemVariable(String), // @@name, @@session.name
     StringLiteral(String),
     NumberLiteral(String),
     True,
@@ -107,10 +109,22 @@ pub enum Token {
     Eof,
 }
 
+/// A token with the range of input characters it was read from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    /// Offset of the token's first character in the input
+    pub start: usize,
+    /// Offset just past the token's last character
+    pub end: usize,
+}
+
 /// Lexer state
 pub struct Lexer {
     input: Vec<char>,
     pos: usize,
+    /// Whether double quotes delimit identifiers (ANSI_QUOTES) rather than strings
+    ansi_quotes: bool,
 }
 
 impl Lexer {
@@ -118,9 +132,16 @@ impl Lexer {
         Self {
             input: input.chars().collect(),
             pos: 0,
+            ansi_quotes: false,
         }
     }
 
+    /// Lex double-quoted text as identifiers, as under the ANSI_QUOTES SQL mode
+    pub fn with_ansi_quotes(mut self, ansi_quotes: bool) -> Self {
+        self.ansi_quotes = ansi_quotes;
+        self
+    }
+
     fn peek(&self) -> Option<char> {
         self.input.get(self.pos).copied()
     }
@@ -135,11 +156,20 @@ impl Lexer {
         Some(c)
     }
 
-    fn skip_whitespace(&mut self) {
+    /// Whether `--` at the current position starts a comment. As in MySQL the
+    /// dashes must be followed by whitespace or the end of input, so `1--2`
+    /// still means `1 - -2`.
+    fn at_dash_comment(&self) -> bool {
+        self.peek() == Some('-')
+            && self.peek_ahead(1) == Some('-')
+            && self.peek_ahead(2).is_none_or(|c| c.is_whitespace() || c.is_control())
+    }
+
+    fn skip_whitespace(&mut self) -> Result<()> {
         while let Some(c) = self.peek() {
             if c.is_whitespace() {
                 self.advance();
-            } else if c == '-' && self.peek_ahead(1) == Some('-') {
+            } else if c == '#' || self.at_dash_comment() {
                 // Line comment
                 while let Some(c) = self.advance() {
                     if c == '\n' {
@@ -147,19 +177,24 @@ impl Lexer {
                     }
                 }
             } else if c == '/' && self.peek_ahead(1) == Some('*') {
-                // Block comment
+                // Block comment, which may span lines
                 self.advance(); // /
                 self.advance(); // *
-                while let Some(c) = self.advance() {
-                    if c == '*' && self.peek() == Some('/') {
-                        self.advance();
-                        break;
+                loop {
+                    match self.advance() {
+                        Some('*') if self.peek() == Some('/') => {
+                            self.advance();
+                            break;
+                        }
+                        Some(_) => {}
+                        None => return Err(MiniSqlError::Syntax("Unterminated comment".into())),
                     }
                 }
             } else {
                 break;
             }
         }
+        Ok(())
     }
 
     fn read_identifier(&mut self) -> String {
@@ -175,6 +210,26 @@ impl Lexer {
         result
     }
 
+    /// Read an identifier quoted with backticks or double quotes; a doubled
+    /// quote character stands for itself
+    fn read_quoted_identifier(&mut self, quote: char) -> Result<String> {
+        let mut ident = String::new();
+        self.advance(); // Skip opening quote
+
+        while let Some(c) = self.advance() {
+            if c != quote {
+                ident.push(c);
+            } else if self.peek() == Some(quote) {
+                ident.push(quote);
+                self.advance();
+            } else {
+                return Ok(ident);
+            }
+        }
+
+        Err(MiniSqlError::Syntax(format!("Unterminated quoted identifier {}{}", quote, ident)))
+    }
+
     fn read_number(&mut self) -> String {
         let mut result = String::new();
         let mut has_dot = false;
@@ -194,6 +249,38 @@ impl Lexer {
         result
     }
 
+    /// Read the digits of a `0x`/`0b` literal, or the quoted digits of an
+    /// `x'...'`/`b'...'` literal, returning its value as a decimal number
+    fn read_radix_literal(&mut self, radix: u32) -> Result<String> {
+        let kind = if radix == 16 { "hexadecimal" } else { "binary" };
+        let mut digits = String::new();
+        if self.peek() == Some('\'') {
+            self.advance();
+            loop {
+                match self.advance() {
+                    Some('\'') => break,
+                    Some(c) => digits.push(c),
+                    None => return Err(MiniSqlError::Syntax(format!("Unterminated {} literal", kind))),
+                }
+            }
+            // x'' must hold whole bytes; an empty literal is zero
+            if radix == 16 && !digits.len().is_multiple_of(2) {
+                return Err(MiniSqlError::Syntax(format!("Invalid {} literal '{}'", kind, digits)));
+            }
+            if digits.is_empty() {
+                return Ok("0".into());
+            }
+        } else {
+            while let Some(c) = self.peek().filter(|c| c.is_alphanumeric() || *c == '_') {
+                digits.push(c);
+                self.advance();
+            }
+        }
+        u64::from_str_radix(&digits, radix)
+            .map(|v| v.to_string())
+            .map_err(|_| MiniSqlError::Syntax(format!("Invalid {} literal '{}'", kind, digits)))
+    }
+
     fn read_string(&mut self, quote: char) -> Result<String> {
         let mut result = String::new();
         self.advance(); // Skip opening quote
@@ -230,7 +317,7 @@ impl Lexer {
     }
 
     pub fn next_token(&mut self) -> Result<Token> {
-        self.skip_whitespace();
+        self.skip_whitespace()?;
         
         let c = match self.peek() {
             Some(c) => c,
@@ -301,32 +388,66 @@ impl Lexer {
             return Ok(Token::GreaterThan);
         }
 
-        // String literals (single quotes) or identifiers (double quotes, backticks)
-        if c == '\'' {
+        // String literals: single quotes, and double quotes unless ANSI_QUOTES
+        // makes them quote identifiers
+        if c == '"' && self.ansi_quotes {
+            return Ok(Token::Identifier(self.read_quoted_identifier(c)?));
+        }
+        if c == '\'' || c == '"' {
             let s = self.read_string(c)?;
             return Ok(Token::StringLiteral(s));
         }
-        
-        // Double quotes can be used for quoted identifiers (SQL standard)
-        if c == '"' {
-            self.advance(); // consume opening quote
-            let mut ident = String::new();
-            while let Some(ch) = self.peek() {
-                if ch == '"' {
-                    self.advance();
-                    // Check for escaped double quote ("")
-                    if self.peek() == Some('"') {
-                        ident.push('"');
-                        self.advance();
-                    } else {
-                        break;
-                    }
-                } else {
-                    ident.push(ch);
-                    self.advance();
-                }
+
+        // System variables: @@name, or a scoped name as in @@session.name
+        if c == '@' && self.peek_ahead(1) == Some('@') {
+            self.advance();
+            self.advance();
+            let mut name = self.read_identifier();
+            if !name.is_empty() && self.peek() == Some('.') {
+                self.advance();
+                name.push('.');
+                name.push_str(&self.read_identifier());
+            }
+            if name.is_empty() || name.ends_with('.') {
+                return Err(MiniSqlError::Syntax("Expected a variable name after '@@'".into()));
+            }
+            return Ok(Token::SystemVariable(name));
+        }
+
+        // User variables: @name, or a quoted name as in @'my var' (also the host of 'user'@'host')
+        if c == '@' {
+            self.advance();
+            let name = match self.peek() {
+                Some(quote @ ('\'' | '"')) => self.read_string(quote)?,
+                Some('`') => self.read_quoted_identifier('`')?,
+                _ => self.read_identifier(),
+            };
+            if name.is_empty() {
+                return Err(MiniSqlError::Syntax("Expected a variable name after '@'".into()));
+            }
+            return Ok(Token::UserVariable(name));
+        }
+
+        // Hexadecimal and binary literals: 0x1F, x'1F', 0b101, b'101'
+        let radix = match c.to_ascii_lowercase() {
+            'x' => 16,
+            'b' => 2,
+            _ => 0,
+        };
+        if radix != 0 && self.peek_ahead(1) == Some('\'') {
+            self.advance();
+            return Ok(Token::NumberLiteral(self.read_radix_literal(radix)?));
+        }
+        if c == '0' {
+            let radix = match self.peek_ahead(1) {
+                Some('x') => 16,
+                Some('b') => 2,
+                _ => 0,
+            };
+            if self.peek_ahead(2).is_some_and(|d| radix != 0 && d.is_digit(radix)) {
+                self.pos += 2;
+                return Ok(Token::NumberLiteral(self.read_radix_literal(radix)?));
             }
-            return Ok(Token::Identifier(ident));
         }
 
         // Numbers
@@ -336,21 +457,11 @@ impl Lexer {
         }
 
         // Identifiers and keywords
-        if c.is_alphabetic() || c == '_' || c == '`' {
-            let is_quoted = c == '`';
-            if is_quoted {
-                self.advance();
-                let mut ident = String::new();
-                while let Some(c) = self.peek() {
-                    if c == '`' {
-                        self.advance();
-                        break;
-                    }
-                    ident.push(c);
-                    self.advance();
-                }
-                return Ok(Token::Identifier(ident));
-            }
+        if c == '`' {
+            // Quoted identifiers are never keywords
+            return Ok(Token::Identifier(self.read_quoted_identifier(c)?));
+        }
+        if c.is_alphabetic() || c == '_' {
 
             let ident = self.read_identifier();
             let upper = ident.to_uppercase();
@@ -433,17 +544,29 @@ impl Lexer {
     }
 
     pub fn tokenize(&mut self) -> Result<Vec<Token>> {
+        Ok(self.tokenize_spanned()?.into_iter().map(|t| t.token).collect())
+    }
+
+    /// Tokenize the input, recording where each token starts and ends
+    pub fn tokenize_spanned(&mut self) -> Result<Vec<SpannedToken>> {
         let mut tokens = Vec::new();
         loop {
+            self.skip_whitespace()?;
+            let start = self.pos;
             let token = self.next_token()?;
-            if token == Token::Eof {
-                tokens.push(token);
+            let done = token == Token::Eof;
+            tokens.push(SpannedToken { token, start, end: self.pos });
+            if done {
                 break;
             }
-            tokens.push(token);
         }
         Ok(tokens)
     }
+
+    /// The input text a token was read from
+    pub fn source_text(&self, token: &SpannedToken) -> String {
+        self.input[token.start..token.end].iter().collect()
+    }
 }
 
 #[cfg(test)]
@@ -474,6 +597,28 @@ mod tests {
         assert_eq!(tokens[3], Token::Identifier("my-table".into()));
     }
 
+    #[test]
+    fn test_lexer_backtick_keywords_and_escapes() {
+        let mut lexer = Lexer::new("SELECT `order`, `group` FROM `table`");
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens, vec![
+            Token::Select,
+            Token::Identifier("order".into()),
+            Token::Comma,
+            Token::Identifier("group".into()),
+            Token::From,
+            Token::Identifier("table".into()),
+            Token::Eof,
+        ]);
+
+        // A doubled backtick is a literal backtick
+        let tokens = Lexer::new("SELECT `a``b`, `x``` FROM t").tokenize().unwrap();
+        assert_eq!(tokens[1], Token::Identifier("a`b".into()));
+        assert_eq!(tokens[3], Token::Identifier("x`".into()));
+
+        assert!(Lexer::new("SELECT `order FROM t").tokenize().is_err());
+    }
+
     #[test]
     fn test_lexer_strings() {
         // Single quotes are for string literals
@@ -484,15 +629,76 @@ mod tests {
         assert_eq!(tokens[9], Token::StringLiteral("O'Reilly".into()));
     }
 
+    #[test]
+    fn test_lexer_double_quoted_strings() {
+        // Double quotes delimit strings by default, as in MySQL
+        let mut lexer = Lexer::new(r#"SELECT "it's", "say ""hi""", "a\"b" FROM t;"#);
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens[1], Token::StringLiteral("it's".into()));
+        assert_eq!(tokens[3], Token::StringLiteral("say \"hi\"".into()));
+        assert_eq!(tokens[5], Token::StringLiteral("a\"b".into()));
+    }
+
     #[test]
     fn test_lexer_double_quoted_identifiers() {
-        // Double quotes are for identifiers (SQL standard)
-        let mut lexer = Lexer::new(r#"SELECT "column name" FROM "table-name";"#);
+        // Under ANSI_QUOTES double quotes are for identifiers (SQL standard)
+        let mut lexer = Lexer::new(r#"SELECT "column name" FROM "table-name";"#).with_ansi_quotes(true);
         let tokens = lexer.tokenize().unwrap();
         assert_eq!(tokens[1], Token::Identifier("column name".into()));
         assert_eq!(tokens[3], Token::Identifier("table-name".into()));
     }
 
+    #[test]
+    fn test_lexer_comments() {
+        let plain = Lexer::new("SELECT a, '-- not # a /* comment */' FROM t WHERE a = 1 - -2")
+            .tokenize()
+            .unwrap();
+        let commented = [
+            "SELECT a, -- first column\n'-- not # a /* comment */' FROM t WHERE a = 1 - -2 -- trailing",
+            "# leading\nSELECT a, '-- not # a /* comment */' # note\nFROM t WHERE a = 1 - -2 #",
+            "/* leading */SELECT/**/a, '-- not # a /* comment */' FROM t /* spans\ntwo lines */ WHERE a = 1 - -2",
+            "SELECT a,--\t\n'-- not # a /* comment */' FROM t WHERE a = 1--2",
+            "SELECT a, '-- not # a /* comment */' FROM t WHERE a = 1 - -2--",
+        ];
+        for sql in commented {
+            assert_eq!(Lexer::new(sql).tokenize().unwrap(), plain, "{}", sql);
+        }
+
+        assert!(Lexer::new("SELECT 1 /* never closed").tokenize().is_err());
+    }
+
+    #[test]
+    fn test_lexer_hex_and_binary_literals() {
+        let tokens = Lexer::new("0x10 0xfF x'1a' X'' 0b101 b'11' B'' 0xFFFFFFFFFFFFFFFF 0 0.5").tokenize().unwrap();
+        let numbers: Vec<Token> = ["16", "255", "26", "0", "5", "3", "0", "18446744073709551615", "0", "0.5"]
+            .iter()
+            .map(|n| Token::NumberLiteral(n.to_string()))
+            .chain([Token::Eof])
+            .collect();
+        assert_eq!(tokens, numbers);
+
+        // Identifiers that merely start with x or b are unaffected
+        let tokens = Lexer::new("SELECT x, b, xb FROM t").tokenize().unwrap();
+        assert_eq!(tokens[1], Token::Identifier("x".into()));
+        assert_eq!(tokens[3], Token::Identifier("b".into()));
+        assert_eq!(tokens[5], Token::Identifier("xb".into()));
+
+        for bad in ["0x1G", "x'ABC'", "x'GG'", "b'102'", "x'1F", "0x10000000000000000"] {
+            assert!(Lexer::new(bad).tokenize().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_lexer_spans() {
+        let mut lexer = Lexer::new("SELECT  `a b`, 'x' /* c */ FROM t");
+        let tokens = lexer.tokenize_spanned().unwrap();
+        let spans: Vec<(usize, usize)> = tokens.iter().map(|t| (t.start, t.end)).collect();
+        assert_eq!(spans, vec![(0, 6), (8, 13), (13, 14), (15, 18), (27, 31), (32, 33), (33, 33)]);
+        assert_eq!(lexer.source_text(&tokens[1]), "`a b`");
+        assert_eq!(tokens[1].token, Token::Identifier("a b".into()));
+        assert_eq!(tokens[6].token, Token::Eof);
+    }
+
     #[test]
     fn test_lexer_operators() {
         let mut lexer = Lexer::new("= <> != < <= > >= -> ->>");
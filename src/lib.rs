@@ -1,8 +1,12 @@
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
-use log::{info, error};
+use tokio::sync::{watch, Semaphore};
+use tokio::task::JoinSet;
+use tokio_rustls::TlsAcceptor;
+use log::{info, error, warn};
 
 pub mod protocol;
 pub mod lexer;
@@ -13,11 +17,31 @@ pub mod storage;
 pub mod types;
 pub mod error;
 pub mod join;
+pub mod logging;
+pub mod digest;
+pub mod datetime;
+pub mod like;
+pub mod rows;
 
+use crate::error::MiniSqlError;
 use crate::executor::Executor;
-use crate::protocol::ConnectionHandler;
+use crate::protocol::{load_tls_acceptor, serve_replication, Authenticator, ConnectionHandler, PasswordAuthenticator};
 use crate::storage::StorageEngine;
-use crate::engines::TransactionManager;
+use crate::engines::{EngineType, GraniteConfig, SandstoneConfig, TransactionManager};
+use crate::logging::LogFormat;
+use crate::types::StorageFormat;
+
+/// Integrity check run on startup, after crash recovery
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StartupCheck {
+    /// No check
+    #[default]
+    Off,
+    /// Verify every index against its table and log the anomalies found
+    Report,
+    /// As `Report`, then rebuild the inconsistent indexes
+    Repair,
+}
 
 /// Server configuration
 #[derive(Clone)]
@@ -26,10 +50,46 @@ pub struct Config {
     pub bind_addr: SocketAddr,
     /// Directory for data files
     pub data_dir: PathBuf,
-    /// Fixed username for authentication (simplified)
+    /// Username accepted by the default authenticator (see [`Server::with_authenticator`]),
+    /// and the superuser exempt from GRANT privilege checks
     pub username: String,
-    /// Fixed password for authentication (simplified)
+    /// Password of `username` for the default authenticator
     pub password: String,
+    /// WAL batching and fsync settings for the Granite engine.
+    /// Use [`GraniteConfig::synchronous()`] to fsync once per batch.
+    pub granite_config: GraniteConfig,
+    /// Background flush settings for the Sandstone engine
+    pub sandstone_config: SandstoneConfig,
+    /// How long shutdown waits for open connections before dropping them
+    pub shutdown_timeout: Duration,
+    /// Memory a single query may buffer for sorts, GROUP BY and joins (None = unlimited)
+    pub max_query_memory_bytes: Option<usize>,
+    /// Default `Session::statement_timeout_ms` for client connections (None = no limit)
+    pub statement_timeout_ms: Option<u64>,
+    /// Log line format (text or JSON); applied by the binary when it installs the logger
+    pub log_format: LogFormat,
+    /// Row file format for tables created without a STORAGE FORMAT option
+    pub default_storage_format: StorageFormat,
+    /// Engine for tables created without an ENGINE option
+    pub default_engine: EngineType,
+    /// Index consistency check to run after recovery
+    pub startup_check: StartupCheck,
+    /// Maximum number of SELECT results kept in the query cache (None = disabled)
+    pub query_cache_entries: Option<usize>,
+    /// PEM certificate chain offered to clients requesting TLS (requires `tls_key_path`)
+    pub tls_cert_path: Option<PathBuf>,
+    /// PEM private key for `tls_cert_path`
+    pub tls_key_path: Option<PathBuf>,
+    /// Connections served at once; further clients get error 1040 and are closed
+    pub max_connections: usize,
+    /// Refuse statements that modify data or schema with error 1290
+    pub read_only: bool,
+    /// Log queries taking longer than this many milliseconds (None = off)
+    pub slow_query_ms: Option<u64>,
+    /// Address of the Sandstone delta replication endpoint (None = off)
+    pub replication_addr: Option<SocketAddr>,
+    /// Secret replication peers must prove they know; required with `replication_addr`
+    pub replication_secret: Option<String>,
 }
 
 impl Default for Config {
@@ -39,14 +99,60 @@ impl Default for Config {
             data_dir: PathBuf::from("./data"),
             username: "root".to_string(),
             password: "password".to_string(),
+            granite_config: GraniteConfig::default(),
+            sandstone_config: SandstoneConfig::default(),
+            shutdown_timeout: Duration::from_secs(10),
+            max_query_memory_bytes: None,
+            statement_timeout_ms: None,
+            log_format: LogFormat::default(),
+            default_storage_format: StorageFormat::default(),
+            default_engine: EngineType::default(),
+            startup_check: StartupCheck::default(),
+            query_cache_entries: None,
+            tls_cert_path: None,
+            tls_key_path: None,
+            max_connections: 151,
+            read_only: false,
+            slow_query_ms: None,
+            replication_addr: None,
+            replication_secret: None,
         }
     }
 }
 
+/// Verify the indexes rebuilt at startup against the recovered rows, logging
+/// each anomaly and, in `Repair` mode, rebuilding the affected indexes
+fn check_indexes_on_startup(storage: &StorageEngine, check: StartupCheck) -> crate::error::Result<()> {
+    if check == StartupCheck::Off {
+        return Ok(());
+    }
+    let anomalies = storage.check_indexes();
+    if anomalies.is_empty() {
+        info!("Startup check: {} index(es) consistent", storage.list_indexes().len());
+        return Ok(());
+    }
+    for anomaly in &anomalies {
+        warn!("Startup check: {}", anomaly);
+    }
+    if check == StartupCheck::Repair {
+        let rebuilt = storage.repair_indexes(&anomalies)?;
+        info!("Startup check: rebuilt {} index(es): {}", rebuilt.len(), rebuilt.join(", "));
+    }
+    Ok(())
+}
+
 /// The main SQL server
 pub struct Server {
     config: Config,
     executor: Arc<Executor>,
+    /// Set to true to stop accepting connections and drain the open ones
+    shutdown_tx: watch::Sender<bool>,
+    /// TLS offered to clients, when a certificate is configured
+    tls: Option<TlsAcceptor>,
+    /// One permit per connection slot; a handler holds its permit until it exits
+    connection_slots: Arc<Semaphore>,
+    /// Checks client credentials; the user and password of `config` by default
+    authenticator: Arc<dyn Authenticator>,
 }
 
 impl Server {
@@ -59,52 +165,183 @@ impl Server {
         let storage = StorageEngine::new(config.data_dir.clone())?;
         
         // Initialize transaction manager (performs crash recovery)
-        let txn_manager = TransactionManager::new(config.data_dir.clone())?;
+        let txn_manager = TransactionManager::new_with_config(
+            config.data_dir.clone(),
+            config.granite_config.clone(),
+        )?;
         
         // Perform crash recovery
         txn_manager.recover(&storage)?;
+        check_indexes_on_startup(&storage, config.startup_check)?;
         
         // Create executor with storage and transaction manager
         // Enable both Granite and Sandstone engines by default
-        let sandstone_config = crate::engines::SandstoneConfig::default();
-        let executor = Arc::new(Executor::with_sandstone(storage, txn_manager, sandstone_config)?);
+        let mut executor = Executor::with_sandstone(
+            storage,
+            txn_manager,
+            config.sandstone_config.clone(),
+        )?;
+        executor.set_max_query_memory_bytes(config.max_query_memory_bytes);
+        executor.set_default_storage_format(config.default_storage_format);
+        executor.set_default_engine(config.default_engine);
+        executor.set_query_cache_entries(config.query_cache_entries);
+        executor.set_read_only(config.read_only);
+        executor.set_superuser(config.username.clone());
+        let executor = Arc::new(executor);
 
-        
-        Ok(Self { config, executor })
+        let tls = match (&config.tls_cert_path, &config.tls_key_path) {
+            (Some(cert), Some(key)) => Some(load_tls_acceptor(cert, key)?),
+            (None, None) => None,
+            _ => return Err("TLS requires both a certificate and a private key".into()),
+        };
+        if config.replication_addr.is_some() && config.replication_secret.as_deref().is_none_or(str::is_empty) {
+            return Err("The replication endpoint requires a replication secret".into());
+        }
+
+        let (shutdown_tx, _) = watch::channel(false);
+        let connection_slots = Arc::new(Semaphore::new(config.max_connections));
+        let authenticator = Arc::new(PasswordAuthenticator::from_config(&config));
+
+        Ok(Self { config, executor, shutdown_tx, tls, connection_slots, authenticator })
+    }
+
+    /// Check client credentials with `authenticator`, e.g. to accept several
+    /// users or delegate to an external service, instead of the fixed user
+    /// and password of `Config`
+    pub fn with_authenticator(mut self, authenticator: Arc<dyn Authenticator>) -> Self {
+        self.authenticator = authenticator;
+        self
+    }
+
+    /// Get the server configuration
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Get a handle to the shared executor
+    pub fn executor(&self) -> Arc<Executor> {
+        Arc::clone(&self.executor)
+    }
+
+    /// Number of client connections currently being served
+    pub fn active_connections(&self) -> usize {
+        self.config.max_connections - self.connection_slots.available_permits()
+    }
+
+    /// Ask a running server to shut down.
+    ///
+    /// `run()`/`serve()` stop accepting connections, wait up to
+    /// `Config::shutdown_timeout` for open connections to finish, flush all
+    /// engines to disk and then return.
+    pub fn shutdown(&self) {
+        self.shutdown_tx.send_replace(true);
     }
 
-    /// Run the server, accepting connections
+    /// Run the server, accepting connections until `shutdown()` is called
     pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
         let listener = TcpListener::bind(&self.config.bind_addr).await?;
-        info!("MiniSQL server listening on {}", self.config.bind_addr);
+        self.serve(listener).await
+    }
+
+    /// Serve connections from an already-bound listener until `shutdown()` is called
+    pub async fn serve(&self, listener: TcpListener) -> Result<(), Box<dyn std::error::Error>> {
+        info!("MiniSQL server listening on {}", listener.local_addr()?);
         info!("Data directory: {:?}", self.config.data_dir);
 
-        loop {
-            match listener.accept().await {
-                Ok((stream, addr)) => {
-                    info!("New connection from {}", addr);
-                    // Disable Nagle on the accepted socket to avoid batching small
-                    // protocol packets which can introduce ~100ms latency.
-                    if let Err(e) = stream.set_nodelay(true) {
-                        error!("Failed to set TCP_NODELAY for {}: {}", addr, e);
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+        let mut connections = JoinSet::new();
+
+        let replication = match (self.config.replication_addr, &self.config.replication_secret) {
+            (Some(addr), Some(secret)) => {
+                let listener = TcpListener::bind(addr).await?;
+                let executor = Arc::clone(&self.executor);
+                let secret = secret.clone();
+                let shutdown = self.shutdown_tx.subscribe();
+                Some(tokio::spawn(async move {
+                    if let Err(e) = serve_replication(listener, executor, secret, shutdown).await {
+                        error!("Replication endpoint failed: {}", e);
                     }
+                }))
+            }
+            _ => None,
+        };
+        let mut next_connection_id: u32 = 0;
 
-                    let executor = Arc::clone(&self.executor);
-                    let config = self.config.clone();
-                    
-                    // Spawn a task to handle this connection
-                    tokio::spawn(async move {
-                        let handler = ConnectionHandler::new(stream, executor, config);
-                        if let Err(e) = handler.run().await {
-                            error!("Connection error from {}: {}", addr, e);
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, addr)) => {
+                        next_connection_id = next_connection_id.wrapping_add(1);
+                        let conn_id = next_connection_id;
+                        let peer = addr.to_string();
+                        info!(conn_id = conn_id, peer = peer.as_str(); "New connection from {}", addr);
+                        // Disable Nagle on the accepted socket to avoid batching small
+                        // protocol packets which can introduce ~100ms latency.
+                        if let Err(e) = stream.set_nodelay(true) {
+                            error!("Failed to set TCP_NODELAY for {}: {}", addr, e);
                         }
-                        info!("Connection closed: {}", addr);
-                    });
-                }
-                Err(e) => {
-                    error!("Failed to accept connection: {}", e);
-                }
+
+                        let executor = Arc::clone(&self.executor);
+                        let config = self.config.clone();
+
+                        let Ok(permit) = Arc::clone(&self.connection_slots).try_acquire_owned() else {
+                            warn!(conn_id = conn_id, peer = peer.as_str();
+                                "Rejecting connection from {}: max_connections ({}) reached",
+                                addr, self.config.max_connections);
+                            connections.spawn(async move {
+                                let handler = ConnectionHandler::new(stream, executor, config);
+                                let _ = handler.reject(&MiniSqlError::too_many_connections()).await;
+                            });
+                            continue;
+                        };
+                        let shutdown = self.shutdown_tx.subscribe();
+                        let tls = self.tls.clone();
+                        let authenticator = Arc::clone(&self.authenticator);
+
+                        // Spawn a task to handle this connection
+                        connections.spawn(async move {
+                            let mut handler = ConnectionHandler::new(stream, executor, config)
+                                .with_connection_id(conn_id)
+                                .with_shutdown(shutdown)
+                                .with_authenticator(authenticator);
+                            if let Some(tls) = tls {
+                                handler = handler.with_tls(tls);
+                            }
+                            if let Err(e) = handler.run().await {
+                                error!(conn_id = conn_id, peer = peer.as_str(); "Connection error from {}: {}", addr, e);
+                            }
+                            info!(conn_id = conn_id, peer = peer.as_str(); "Connection closed: {}", addr);
+                            drop(permit);
+                        });
+                    }
+                    Err(e) => {
+                        error!("Failed to accept connection: {}", e);
+                    }
+                },
+                // Reap finished connection tasks so the set does not grow unbounded
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+                _ = shutdown_rx.wait_for(|stop| *stop) => break,
             }
         }
+
+        // Stop accepting new connections before draining the open ones
+        drop(listener);
+        info!("Shutting down: waiting for {} open connection(s)", connections.len());
+
+        let drain = async { while connections.join_next().await.is_some() {} };
+        if tokio::time::timeout(self.config.shutdown_timeout, drain).await.is_err() {
+            warn!(
+                "Shutdown timeout reached, dropping {} connection(s)",
+                connections.len()
+            );
+            connections.shutdown().await;
+        }
+
+        if let Some(replication) = replication {
+            let _ = replication.await;
+        }
+        self.executor.shutdown()?;
+        info!("MiniSQL server stopped");
+        Ok(())
     }
 }
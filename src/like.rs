@@ -0,0 +1,157 @@
+//! LIKE pattern matching
+//!
+//! A pattern is compiled once into tokens: `%` (any sequence), `_` (any one
+//! character) and literal characters, matched case-insensitively like MySQL's
+//! default collation. The escape character makes the next character literal.
+//!
+//! Matching walks the text and the pattern together. When a literal or `_`
+//! fails to match, it backtracks to the most recent `%` and lets it absorb one
+//! more character. Backtracking to an earlier `%` never finds a match the
+//! later one misses, so only the last one is remembered: matching takes
+//! O(n·m) time at worst for a text of n characters and a pattern of m tokens,
+//! however many `%` the pattern holds.
+
+/// One element of a LIKE pattern
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LikeToken {
+    /// `%`: any sequence of characters, including none
+    AnySequence,
+    /// `_`: exactly one character
+    AnyChar,
+    /// A character matched case-insensitively (including escaped `%` / `_`)
+    Literal(char),
+}
+
+/// A compiled LIKE pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct LikePattern {
+    pattern: String,
+    escape: Option<char>,
+    tokens: Vec<LikeToken>,
+}
+
+impl LikePattern {
+    /// Compile `pattern`. The escape character makes the next character
+    /// literal; at the end of the pattern it is itself literal.
+    pub fn new(pattern: &str, escape: Option<char>) -> Self {
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                c if Some(c) == escape => LikeToken::Literal(chars.next().unwrap_or(c)),
+                '%' => LikeToken::AnySequence,
+                '_' => LikeToken::AnyChar,
+                c => LikeToken::Literal(c),
+            };
+            // Consecutive `%` match the same texts as one
+            if !(token == LikeToken::AnySequence && tokens.last() == Some(&LikeToken::AnySequence)) {
+                tokens.push(token);
+            }
+        }
+        LikePattern { pattern: pattern.to_string(), escape, tokens }
+    }
+
+    /// The pattern text as written
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// The escape character
+    pub fn escape(&self) -> Option<char> {
+        self.escape
+    }
+
+    /// Whether the whole of `s` matches the pattern
+    pub fn matches(&self, s: &str) -> bool {
+        let text: Vec<char> = s.chars().collect();
+        let (mut t, mut p) = (0, 0);
+        // Pattern position after the last `%` seen, and the text position it
+        // currently resumes from
+        let mut backtrack: Option<(usize, usize)> = None;
+
+        while t < text.len() {
+            match self.tokens.get(p) {
+                Some(LikeToken::AnySequence) => {
+                    p += 1;
+                    backtrack = Some((p, t));
+                    continue;
+                }
+                Some(LikeToken::AnyChar) => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                Some(LikeToken::Literal(c)) if chars_eq_ignore_case(text[t], *c) => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            // Mismatch: let the last `%` absorb one more character
+            let Some((after_any, resume)) = backtrack.as_mut() else {
+                return false;
+            };
+            *resume += 1;
+            p = *after_any;
+            t = *resume;
+        }
+        self.tokens[p..].iter().all(|token| *token == LikeToken::AnySequence)
+    }
+}
+
+/// Compare two characters by their full Unicode lowercase mappings
+fn chars_eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn like(s: &str, pattern: &str) -> bool {
+        LikePattern::new(pattern, Some('\\')).matches(s)
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(like("apple", "ap%"));
+        assert!(like("apple", "%"));
+        assert!(like("", "%"));
+        assert!(!like("", "_"));
+        assert!(like("banana", "b_n%a"));
+        assert!(like("banana", "%an%an%"));
+        assert!(!like("banana", "%an%an%an%"));
+        assert!(like("abcabc", "%abc"));
+        assert!(!like("abcab", "%abc"));
+        assert!(like("cat", "c_t"));
+        assert!(!like("cart", "c_t"));
+        assert!(like("mississippi", "m%iss%ppi"));
+        assert!(!like("mississippi", "m%iss%ppix"));
+    }
+
+    #[test]
+    fn test_escape_and_case() {
+        assert!(like("a%b", "a\\%b"));
+        assert!(!like("axb", "a\\%b"));
+        assert!(like("a_b", "a\\_b"));
+        // A trailing escape character is literal
+        assert!(like("ab\\", "ab\\"));
+        assert!(LikePattern::new("%!%", Some('!')).matches("50%"));
+        assert!(like("ÄRGER", "är%"));
+        assert!(like("straße", "STRA_E"));
+    }
+
+    #[test]
+    fn test_many_wildcards_over_long_text() {
+        // Exponential for a recursive matcher: every `%` retries every split
+        let text = "a".repeat(5_000);
+        let pattern = format!("{}b", "%a".repeat(20));
+        assert!(!like(&text, &pattern));
+        assert!(like(&format!("{}b", text), &pattern));
+
+        let text = "xy".repeat(10_000) + "abc";
+        assert!(like(&text, "%a%b%c%"));
+        assert!(!like(&text, "%a%b%c%d%"));
+    }
+}
@@ -0,0 +1,123 @@
+//! Log output formatting
+//!
+//! MiniSQL logs through the `log` facade with `env_logger` as the backend.
+//! Log calls may attach key-value context (e.g. `conn_id`, `sql`), which is
+//! appended as `key=value` pairs in text mode and emitted as top-level fields
+//! in JSON mode:
+//!
+//! ```text
+//! {"timestamp":"2024-05-01T12:00:00.123Z","level":"INFO","target":"minisql::protocol","message":"Query","conn_id":3,"sql":"SELECT 1"}
+//! ```
+
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use log::kv::{Key, Value as KvValue, VisitSource};
+use log::Record;
+use serde_json::{Map, Value as JsonValue};
+
+/// Log line format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Human-readable lines (`[timestamp LEVEL target] message key=value`)
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!("invalid log format '{}' (expected text or json)", other)),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogFormat::Text => write!(f, "text"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Build a logger for the given format.
+///
+/// The level filter comes from `RUST_LOG` (default `info`). Callers can adjust
+/// the builder further, e.g. to redirect output, before calling `init()`.
+pub fn builder(format: LogFormat) -> env_logger::Builder {
+    let mut builder = env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or("info")
+    );
+    match format {
+        LogFormat::Text => builder.format(|buf, record| {
+            let timestamp = buf.timestamp_millis();
+            write!(buf, "[{} {:<5} {}] {}", timestamp, record.level(), record.target(), record.args())?;
+            let mut fields = TextFields(String::new());
+            let _ = record.key_values().visit(&mut fields);
+            writeln!(buf, "{}", fields.0)
+        }),
+        LogFormat::Json => builder.format(|buf, record| {
+            let line = json_line(&buf.timestamp_millis().to_string(), record);
+            writeln!(buf, "{}", line)
+        }),
+    };
+    builder
+}
+
+/// Install the global logger for the given format
+pub fn init(format: LogFormat) {
+    builder(format).init();
+}
+
+/// Render a record as a single-line JSON object
+fn json_line(timestamp: &str, record: &Record) -> String {
+    let mut fields = JsonFields(Map::new());
+    fields.0.insert("timestamp".into(), timestamp.into());
+    fields.0.insert("level".into(), record.level().as_str().into());
+    fields.0.insert("target".into(), record.target().into());
+    fields.0.insert("message".into(), record.args().to_string().into());
+    let _ = record.key_values().visit(&mut fields);
+    JsonValue::Object(fields.0).to_string()
+}
+
+/// Collects key-value context as JSON fields, keeping numbers and booleans typed
+struct JsonFields(Map<String, JsonValue>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: KvValue<'kvs>) -> Result<(), log::kv::Error> {
+        let json = if let Some(n) = value.to_u64() {
+            JsonValue::from(n)
+        } else if let Some(n) = value.to_i64() {
+            JsonValue::from(n)
+        } else if let Some(b) = value.to_bool() {
+            JsonValue::from(b)
+        } else if let Some(f) = value.to_f64() {
+            JsonValue::from(f)
+        } else {
+            JsonValue::from(value.to_string())
+        };
+        self.0.insert(key.as_str().to_string(), json);
+        Ok(())
+    }
+}
+
+/// Collects key-value context as ` key=value` pairs, quoting strings
+struct TextFields(String);
+
+impl<'kvs> VisitSource<'kvs> for TextFields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: KvValue<'kvs>) -> Result<(), log::kv::Error> {
+        match value.to_borrowed_str() {
+            Some(s) => self.0.push_str(&format!(" {}={:?}", key, s)),
+            None => self.0.push_str(&format!(" {}={}", key, value)),
+        }
+        Ok(())
+    }
+}
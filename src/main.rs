@@ -48,18 +48,15 @@
 //! └─────────────────────────────────────────────────────────────┘
 
 use std::path::PathBuf;
+use std::sync::Arc;
 // Removed unused imports that were triggering warnings when building the binary.
 use log::info;
 
-use minisql::{Config, Server};
+use minisql::engines::{GraniteConfig, SandstoneConfig};
+use minisql::{Config, Server, StartupCheck};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize logging
-    env_logger::Builder::from_env(
-        env_logger::Env::default().default_filter_or("info")
-    ).init();
-
     // Parse command line arguments (simple)
     let args: Vec<String> = std::env::args().collect();
     
@@ -94,6 +91,113 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     i += 1;
                 }
             }
+            "--durability" => {
+                if i + 1 < args.len() {
+                    let (granite, sandstone) = match args[i + 1].to_lowercase().as_str() {
+                        "sync" => (GraniteConfig::synchronous(), SandstoneConfig::low_latency()),
+                        "balanced" => (GraniteConfig::default(), SandstoneConfig::default()),
+                        "throughput" => (
+                            GraniteConfig::high_throughput(),
+                            SandstoneConfig::high_throughput(),
+                        ),
+                        other => {
+                            return Err(format!(
+                                "invalid --durability '{}' (expected sync, balanced or throughput)",
+                                other
+                            )
+                            .into());
+                        }
+                    };
+                    config.granite_config = granite;
+                    config.sandstone_config = SandstoneConfig {
+                        replica_id: config.sandstone_config.replica_id,
+                        ..sandstone
+                    };
+                    i += 1;
+                }
+            }
+            "--log-format" => {
+                if i + 1 < args.len() {
+                    config.log_format = args[i + 1].parse()?;
+                    i += 1;
+                }
+            }
+            "--storage-format" => {
+                if i + 1 < args.len() {
+                    config.default_storage_format = args[i + 1].parse()?;
+                    i += 1;
+                }
+            }
+            "--default-engine" => {
+                if i + 1 < args.len() {
+                    config.default_engine = args[i + 1].parse()?;
+                    i += 1;
+                }
+            }
+            "--query-cache" => {
+                if i + 1 < args.len() {
+                    config.query_cache_entries = Some(args[i + 1].parse()?);
+                    i += 1;
+                }
+            }
+            "--slow-query-ms" => {
+                if i + 1 < args.len() {
+                    config.slow_query_ms = Some(args[i + 1].parse()?);
+                    i += 1;
+                }
+            }
+            "--statement-timeout" => {
+                if i + 1 < args.len() {
+                    config.statement_timeout_ms = Some(args[i + 1].parse()?);
+                    i += 1;
+                }
+            }
+            "--max-connections" => {
+                if i + 1 < args.len() {
+                    config.max_connections = args[i + 1].parse()?;
+                    i += 1;
+                }
+            }
+            "--tls-cert" => {
+                if i + 1 < args.len() {
+                    config.tls_cert_path = Some(PathBuf::from(&args[i + 1]));
+                    i += 1;
+                }
+            }
+            "--tls-key" => {
+                if i + 1 < args.len() {
+                    config.tls_key_path = Some(PathBuf::from(&args[i + 1]));
+                    i += 1;
+                }
+            }
+            "--replication-port" => {
+                if i + 1 < args.len() {
+                    let port: u16 = args[i + 1].parse()?;
+                    config.replication_addr = Some(format!("127.0.0.1:{}", port).parse()?);
+                    i += 1;
+                }
+            }
+            "--replication-addr" => {
+                if i + 1 < args.len() {
+                    config.replication_addr = Some(args[i + 1].parse()?);
+                    i += 1;
+                }
+            }
+            "--replication-secret" => {
+                if i + 1 < args.len() {
+                    config.replication_secret = Some(args[i + 1].clone());
+                    i += 1;
+                }
+            }
+            "--replica-id" => {
+                if i + 1 < args.len() {
+                    config.sandstone_config.replica_id = args[i + 1].parse()?;
+                    i += 1;
+                }
+            }
+            "--read-only" => config.read_only = true,
+            "--check-on-startup" => config.startup_check = StartupCheck::Report,
+            "--repair-on-startup" => config.startup_check = StartupCheck::Repair,
             "--help" | "-h" => {
                 println!("MiniSQL - A minimal MySQL-compatible SQL server");
                 println!();
@@ -104,6 +208,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("  -d, --data-dir DIR    Data directory (default: ./data)");
                 println!("  -u, --user USER       Username (default: root)");
                 println!("  -P, --password PASS   Password (default: password)");
+                println!("      --durability MODE sync | balanced | throughput (default: balanced)");
+                println!("                        sync fsyncs the WAL once per batch before COMMIT returns");
+                println!("      --log-format FMT  text | json (default: text)");
+                println!("      --storage-format FMT");
+                println!("                        jsonl | binary row files for new tables (default: jsonl)");
+                println!("      --default-engine ENGINE");
+                println!("                        granite | sandstone for tables created without ENGINE= (default: granite)");
+                println!("      --query-cache N   cache up to N SELECT results (default: off)");
+                println!("      --slow-query-ms MS");
+                println!("                        log queries taking longer than MS milliseconds");
+                println!("      --statement-timeout MS");
+                println!("                        abort SELECTs running longer than MS milliseconds");
+                println!("      --max-connections N");
+                println!("                        clients served at once (default: 151)");
+                println!("      --tls-cert FILE   PEM certificate chain offered to clients requesting SSL");
+                println!("      --tls-key FILE    PEM private key for --tls-cert");
+                println!("      --replication-port PORT");
+                println!("                        serve Sandstone deltas to peers on 127.0.0.1:PORT (default: off)");
+                println!("      --replication-addr ADDR");
+                println!("                        serve Sandstone deltas on ADDR, e.g. 0.0.0.0:4406");
+                println!("      --replication-secret SECRET");
+                println!("                        secret replication peers must know (required with either)");
+                println!("      --replica-id N    distinct non-zero id of this node among replicas");
+                println!("      --read-only       reject INSERT, UPDATE, DELETE and DDL (error 1290)");
+                println!("      --check-on-startup");
+                println!("                        verify indexes against table rows after recovery");
+                println!("      --repair-on-startup");
+                println!("                        as --check-on-startup, rebuilding inconsistent indexes");
                 println!("  -h, --help            Show this help");
                 return Ok(());
             }
@@ -112,10 +244,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         i += 1;
     }
 
+    // Initialize logging (RUST_LOG sets the level, default info)
+    minisql::logging::init(config.log_format);
+
     info!("Starting MiniSQL server...");
     
-    let server = Server::new(config).await?;
+    let server = Arc::new(Server::new(config).await?);
+
+    // Drain connections and flush all engines on Ctrl-C / SIGTERM
+    let signal_server = Arc::clone(&server);
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        info!("Received shutdown signal");
+        signal_server.shutdown();
+    });
+
     server.run().await?;
     
     Ok(())
 }
+
+/// Resolve on Ctrl-C, or on SIGTERM where supported
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
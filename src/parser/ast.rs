@@ -1,5 +1,8 @@
-use crate::types::{DataType, Value};
+use std::sync::Arc;
+
+use crate::types::{DataType, IsolationLevel, Privilege, ReferentialAction, SqlMode, StorageFormat, Value};
 use crate::engines::EngineType;
+use crate::like::LikePattern;
 
 /// Top-level SQL statement
 #[derive(Debug, Clone)]
@@ -8,8 +11,11 @@ pub enum Statement {
     CreateTable(CreateTableStmt),
     /// CREATE INDEX statement
     CreateIndex(CreateIndexStmt),
-    /// DROP INDEX statement
-    DropIndex(String),
+    /// DROP INDEX [IF EXISTS] name
+    DropIndex {
+        name: String,
+        if_exists: bool,
+    },
     /// ALTER TABLE statement
     AlterTable(AlterTableStmt),
     /// SELECT statement
@@ -20,24 +26,82 @@ pub enum Statement {
     Update(UpdateStmt),
     /// DELETE statement
     Delete(DeleteStmt),
+    /// LOAD DATA INFILE statement
+    LoadData(LoadDataStmt),
     /// BEGIN transaction
     Begin,
     /// COMMIT transaction
     Commit,
     /// ROLLBACK transaction
     Rollback,
+    /// SAVEPOINT name
+    Savepoint(String),
+    /// ROLLBACK TO [SAVEPOINT] name
+    RollbackToSavepoint(String),
+    /// RELEASE SAVEPOINT name
+    ReleaseSavepoint(String),
+    /// SET [SESSION] TRANSACTION ISOLATION LEVEL ... / SET transaction_isolation = ...
+    SetIsolationLevel {
+        level: IsolationLevel,
+        scope: IsolationScope,
+    },
+    /// SET @name = expr [, @name = expr ...]
+    SetUserVariables(Vec<(String, Expr)>),
+    /// SET [SESSION] sql_mode = '...'
+    SetSqlMode(SqlMode),
+    /// SET [SESSION] autocommit = 0 | 1
+    SetAutocommit(bool),
+    /// SET of several session variables, applied in order
+    SetVariables(Vec<Statement>),
     /// SHOW TABLES
     ShowTables,
     /// DESCRIBE table
     Describe(String),
-    /// DROP TABLE
-    DropTable(String),
+    /// SHOW CREATE TABLE table
+    ShowCreateTable(String),
+    /// SHOW INDEX FROM table
+    ShowIndex(String),
+    /// SHOW [GLOBAL | SESSION] STATUS
+    ShowStatus,
+    /// SHOW WARNINGS
+    ShowWarnings,
+    /// EXPLAIN SELECT ...
+    Explain(Box<Statement>),
+    /// DROP TABLE [IF EXISTS] name
+    DropTable {
+        name: String,
+        if_exists: bool,
+    },
     /// TRUNCATE TABLE
     TruncateTable(String),
     /// CHECKPOINT
     Checkpoint,
-    /// VACUUM
-    Vacuum,
+    /// FLUSH TABLES
+    FlushTables,
+    /// VACUUM [STORAGE FORMAT = fmt]; a format rewrites every table in it
+    Vacuum(Option<StorageFormat>),
+    /// GRANT privileges ON table TO user
+    Grant(GrantStmt),
+    /// REVOKE privileges ON table FROM user
+    Revoke(GrantStmt),
+}
+
+/// Privileges, table and user of a GRANT or REVOKE statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrantStmt {
+    pub privileges: Vec<Privilege>,
+    pub table_name: String,
+    /// User name; the host part of `'user'@'host'` is accepted and ignored
+    pub user: String,
+}
+
+/// Which transactions a SET ... ISOLATION statement applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationScope {
+    /// SET TRANSACTION ...: only the next transaction started in the session
+    NextTransaction,
+    /// SET SESSION ...: every later transaction in the session
+    Session,
 }
 
 /// ALTER TABLE statement
@@ -52,6 +116,10 @@ pub struct AlterTableStmt {
 pub enum AlterTableAction {
     /// Change the storage engine
     ChangeEngine(EngineType),
+    /// Set the next AUTO_INCREMENT value (`AUTO_INCREMENT = n`)
+    SetAutoIncrement(u64),
+    /// RENAME COLUMN old TO new
+    RenameColumn { old_name: String, new_name: String },
 }
 
 /// CREATE INDEX statement
@@ -71,6 +139,23 @@ pub struct CreateTableStmt {
     pub columns: Vec<ColumnDefAst>,
     pub if_not_exists: bool,
     pub engine: Option<EngineType>,
+    pub storage_format: Option<StorageFormat>,
+    /// Initial AUTO_INCREMENT counter (`AUTO_INCREMENT = n` table option)
+    pub auto_increment: Option<u64>,
+    /// FOREIGN KEY clauses
+    pub foreign_keys: Vec<ForeignKeyDef>,
+    /// Columns of a table-level `PRIMARY KEY (cols)` clause; empty without one
+    pub primary_key: Vec<String>,
+}
+
+/// `[CONSTRAINT name] FOREIGN KEY (cols) REFERENCES table (cols) [ON DELETE action]`
+#[derive(Debug, Clone)]
+pub struct ForeignKeyDef {
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+    pub ref_table: String,
+    pub ref_columns: Vec<String>,
+    pub on_delete: ReferentialAction,
 }
 
 /// Column definition in CREATE TABLE
@@ -94,6 +179,8 @@ pub struct SelectStmt {
     pub group_by: Vec<Expr>,
     pub order_by: Vec<OrderByClause>,
     pub limit: Option<u64>,
+    /// `FOR UPDATE`: lock the rows read until the transaction ends
+    pub for_update: bool,
 }
 
 /// ORDER BY clause
@@ -101,6 +188,26 @@ pub struct SelectStmt {
 pub struct OrderByClause {
     pub expr: Expr,
     pub direction: SortOrder,
+    /// Explicit `NULLS FIRST` / `NULLS LAST`; `None` uses the direction's default
+    pub nulls: Option<NullsOrder>,
+}
+
+impl OrderByClause {
+    /// Whether NULLs sort before other values. Without an explicit choice
+    /// NULL is the smallest value, as in MySQL: first for ASC, last for DESC.
+    pub fn nulls_first(&self) -> bool {
+        match self.nulls {
+            Some(nulls) => nulls == NullsOrder::First,
+            None => self.direction == SortOrder::Asc,
+        }
+    }
+}
+
+/// Placement of NULLs in an ORDER BY key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullsOrder {
+    First,
+    Last,
 }
 
 /// Sort order (ascending or descending)
@@ -126,6 +233,26 @@ pub struct TableRef {
     pub name: String,
     /// Optional alias (e.g., "users u" or "users AS u")
     pub alias: Option<String>,
+    /// Optional `TABLESAMPLE` clause
+    pub sample: Option<TableSample>,
+}
+
+/// A `TABLESAMPLE (n PERCENT | n ROWS) [REPEATABLE (seed)]` clause
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSample {
+    /// How much of the table to sample
+    pub size: SampleSize,
+    /// Seed for a reproducible sample; a random seed is used when absent
+    pub seed: Option<u64>,
+}
+
+/// Size of a table sample
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleSize {
+    /// Each row is included with this probability, in percent (0-100)
+    Percent(f64),
+    /// Exactly this many rows, or the whole table if it is smaller
+    Rows(u64),
 }
 
 impl TableRef {
@@ -163,6 +290,23 @@ pub struct InsertStmt {
     pub table_name: String,
     pub columns: Option<Vec<String>>,
     pub values: Vec<Vec<Expr>>,
+    /// ON DUPLICATE KEY UPDATE assignments, applied to the existing row when a
+    /// value list hits a primary key conflict (empty = plain INSERT)
+    pub on_duplicate_key_update: Vec<(String, Expr)>,
+}
+
+/// `LOAD DATA INFILE 'file' INTO TABLE t [FIELDS TERMINATED BY 'sep'] [IGNORE n LINES] [(cols)]`
+#[derive(Debug, Clone)]
+pub struct LoadDataStmt {
+    /// File to read, relative to the server's data directory unless absolute
+    pub file: String,
+    pub table_name: String,
+    /// Separator between the fields of a line (a tab by default, as in MySQL)
+    pub field_separator: String,
+    /// Leading lines to skip, such as a header
+    pub ignore_lines: u64,
+    /// Columns the fields of each line fill, in order; every column when absent
+    pub columns: Option<Vec<String>>,
 }
 
 /// UPDATE statement
@@ -171,6 +315,10 @@ pub struct UpdateStmt {
     pub table_name: String,
     pub assignments: Vec<(String, Expr)>,
     pub where_clause: Option<Expr>,
+    /// Order in which matching rows are updated, so LIMIT picks a deterministic set
+    pub order_by: Vec<OrderByClause>,
+    /// Stop after this many matching rows (MySQL's single-table UPDATE ... LIMIT)
+    pub limit: Option<u64>,
 }
 
 /// DELETE statement
@@ -178,6 +326,10 @@ pub struct UpdateStmt {
 pub struct DeleteStmt {
     pub table_name: String,
     pub where_clause: Option<Expr>,
+    /// Order in which matching rows are deleted, so LIMIT picks a deterministic set
+    pub order_by: Vec<OrderByClause>,
+    /// Delete at most this many matching rows
+    pub limit: Option<u64>,
 }
 
 /// Expression (for WHERE clauses, values, etc.)
@@ -195,6 +347,8 @@ pub enum Expr {
     },
     /// Unary NOT
     Not(Box<Expr>),
+    /// Unary minus (`-expr`); a minus before a number literal is folded into it
+    UnaryMinus(Box<Expr>),
     /// IS NULL check
     IsNull(Box<Expr>),
     /// IS NOT NULL check
@@ -205,10 +359,18 @@ pub enum Expr {
         key: String,
         as_text: bool, // true for ->>, false for ->
     },
-    /// Function call
+    /// CAST(expr AS type)
+    Cast {
+        expr: Box<Expr>,
+        target: DataType,
+    },
+    /// Function call; `distinct` is set for aggregates such as `COUNT(DISTINCT x)`
+    /// and `separator` holds GROUP_CONCAT's `SEPARATOR 'sep'`
     FunctionCall {
         name: String,
         args: Vec<Expr>,
+        distinct: bool,
+        separator: Option<String>,
     },
     /// IN operator: expr IN (value1, value2, ...)
     In {
@@ -220,8 +382,37 @@ pub enum Expr {
         expr: Box<Expr>,
         values: Vec<Expr>,
     },
+    /// IN with a subquery: expr [NOT] IN (SELECT ...).
+    /// The executor runs the subquery and replaces this with `In`/`NotIn`
+    /// over its values before any row is evaluated.
+    InSubquery {
+        expr: Box<Expr>,
+        subquery: Box<SelectStmt>,
+        negated: bool,
+    },
+    /// Scalar subquery: (SELECT ...) used as a single value. It may reference
+    /// columns of the outer query's row, in which case it runs once per row.
+    ScalarSubquery(Box<SelectStmt>),
     /// Placeholder for prepared statement parameter (? in SQL)
     Placeholder(usize),
+    /// User-defined variable (@name). The executor replaces it with the
+    /// session's value, or NULL if it was never set, before any row is evaluated.
+    UserVariable(String),
+    /// `expr LIKE 'pattern'` with the pattern compiled. The executor rewrites
+    /// a LIKE whose pattern is a string literal into this before any row is
+    /// evaluated, so the pattern is compiled once per statement.
+    Like {
+        expr: Box<Expr>,
+        pattern: Arc<LikePattern>,
+    },
+}
+
+/// Check if a function name is an aggregate function
+pub fn is_aggregate_function(name: &str) -> bool {
+    matches!(
+        name.to_uppercase().as_str(),
+        "COUNT" | "SUM" | "AVG" | "MIN" | "MAX" | "GROUP_CONCAT"
+    )
 }
 
 /// Binary operators
@@ -243,5 +434,7 @@ pub enum BinaryOperator {
     Multiply,
     Divide,
     // String
-    Like,
+    /// `LIKE`, with the character that makes the next `%` / `_` literal
+    /// (`\` unless an `ESCAPE` clause says otherwise; `None` for `ESCAPE ''`)
+    Like { escape: Option<char> },
 }
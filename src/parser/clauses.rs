@@ -58,8 +58,20 @@ impl Parser {
                     }
                     _ => SortOrder::Asc, // Default to ascending
                 };
+                let nulls = if self.peek_keyword("NULLS") {
+                    self.advance();
+                    if self.peek_keyword("FIRST") {
+                        self.advance();
+                        Some(NullsOrder::First)
+                    } else {
+                        self.expect_keyword("LAST")?;
+                        Some(NullsOrder::Last)
+                    }
+                } else {
+                    None
+                };
                 
-                order_by.push(OrderByClause { expr, direction });
+                order_by.push(OrderByClause { expr, direction, nulls });
                 
                 if *self.peek() == Token::Comma {
                     self.advance();
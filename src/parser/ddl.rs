@@ -1,6 +1,6 @@
-use crate::error::Result;
+use crate::error::{MiniSqlError, Result};
 use crate::lexer::Token;
-use crate::types::DataType;
+use crate::types::{DataType, ReferentialAction, StorageFormat};
 use super::ast::*;
 use super::Parser;
 
@@ -37,7 +37,34 @@ impl Parser {
         self.expect(Token::LeftParen)?;
 
         let mut columns = Vec::new();
+        let mut foreign_keys = Vec::new();
+        let mut primary_key = Vec::new();
         loop {
+            if self.peek_keyword("FOREIGN") || self.peek_keyword("CONSTRAINT") || *self.peek() == Token::Primary {
+                let name = if self.peek_keyword("CONSTRAINT") {
+                    self.advance();
+                    Some(self.consume_identifier()?)
+                } else {
+                    None
+                };
+                if *self.peek() == Token::Primary {
+                    // The key is always named PRIMARY, so a constraint name is ignored
+                    self.advance();
+                    self.expect(Token::Key)?;
+                    if !primary_key.is_empty() {
+                        return Err(MiniSqlError::multiple_primary_keys());
+                    }
+                    primary_key = self.parse_column_name_list()?;
+                } else {
+                    foreign_keys.push(self.parse_foreign_key_def(name)?);
+                }
+                if *self.peek() == Token::Comma {
+                    self.advance();
+                    continue;
+                }
+                break;
+            }
+
             let col_name = self.consume_identifier()?;
             let data_type = self.parse_data_type()?;
 
@@ -61,19 +88,20 @@ impl Parser {
 
         self.expect(Token::RightParen)?;
 
-        // Parse optional ENGINE clause
+        // Parse optional table options: ENGINE=..., STORAGE FORMAT=..., AUTO_INCREMENT=...
         let mut engine = None;
-        if *self.peek() == Token::Engine {
-            self.advance();
-            self.expect(Token::Equal)?;
-            
-            let engine_name = self.consume_identifier()?;
-            let engine_type = crate::engines::EngineType::from_name(&engine_name)
-                .ok_or_else(|| self.error_with_context(format!(
-                    "Unknown engine type: '{}'. Valid options: Granite, Sandstone",
-                    engine_name
-                )))?;
-            engine = Some(engine_type);
+        let mut storage_format = None;
+        let mut auto_increment = None;
+        loop {
+            if *self.peek() == Token::Engine {
+                engine = Some(self.parse_engine_option()?);
+            } else if self.peek_keyword("STORAGE") {
+                storage_format = Some(self.parse_storage_format()?);
+            } else if *self.peek() == Token::AutoIncrement {
+                auto_increment = Some(self.parse_auto_increment_option()?);
+            } else {
+                break;
+            }
         }
 
         self.pop_context();
@@ -83,9 +111,126 @@ impl Parser {
             columns,
             if_not_exists,
             engine,
+            storage_format,
+            auto_increment,
+            foreign_keys,
+            primary_key,
         }))
     }
 
+    /// Parse `FOREIGN KEY (cols) REFERENCES table (cols)` followed by optional
+    /// `ON DELETE` / `ON UPDATE` actions. `name` comes from a preceding
+    /// `CONSTRAINT name`.
+    fn parse_foreign_key_def(&mut self, name: Option<String>) -> Result<ForeignKeyDef> {
+        self.expect_keyword("FOREIGN")?;
+        self.expect(Token::Key)?;
+        let columns = self.parse_column_name_list()?;
+        self.expect_keyword("REFERENCES")?;
+        let ref_table = self.consume_identifier()?;
+        let ref_columns = self.parse_column_name_list()?;
+        if ref_columns.len() != columns.len() {
+            return Err(self.error_with_context(format!(
+                "Foreign key has {} column(s) but references {}",
+                columns.len(), ref_columns.len()
+            )));
+        }
+
+        let mut on_delete = ReferentialAction::Restrict;
+        while *self.peek() == Token::On {
+            self.advance();
+            if *self.peek() == Token::Delete {
+                self.advance();
+                on_delete = self.parse_referential_action()?;
+            } else if *self.peek() == Token::Update {
+                self.advance();
+                // Referenced keys can never change while children exist
+                if self.parse_referential_action()? != ReferentialAction::Restrict {
+                    return Err(self.error_with_context("Only RESTRICT is supported for ON UPDATE".to_string()));
+                }
+            } else {
+                return Err(self.error_with_context("Expected DELETE or UPDATE after ON".to_string()));
+            }
+        }
+
+        Ok(ForeignKeyDef { name, columns, ref_table, ref_columns, on_delete })
+    }
+
+    /// Parse `RESTRICT | CASCADE | NO ACTION`
+    fn parse_referential_action(&mut self) -> Result<ReferentialAction> {
+        if self.peek_keyword("RESTRICT") {
+            self.advance();
+            Ok(ReferentialAction::Restrict)
+        } else if self.peek_keyword("CASCADE") {
+            self.advance();
+            Ok(ReferentialAction::Cascade)
+        } else if self.peek_keyword("NO") {
+            self.advance();
+            self.expect_keyword("ACTION")?;
+            Ok(ReferentialAction::Restrict)
+        } else {
+            Err(self.error_with_context(format!(
+                "Unsupported referential action {}. Valid options: RESTRICT, CASCADE, NO ACTION",
+                self.token_brief(self.peek())
+            )))
+        }
+    }
+
+    /// Parse a parenthesized, comma-separated list of column names
+    fn parse_column_name_list(&mut self) -> Result<Vec<String>> {
+        self.expect(Token::LeftParen)?;
+        let mut columns = vec![self.consume_identifier()?];
+        while *self.peek() == Token::Comma {
+            self.advance();
+            columns.push(self.consume_identifier()?);
+        }
+        self.expect(Token::RightParen)?;
+        Ok(columns)
+    }
+
+    /// Parse `STORAGE FORMAT [=] {JSONL | BINARY}`
+    pub(super) fn parse_storage_format(&mut self) -> Result<StorageFormat> {
+        self.expect_keyword("STORAGE")?;
+        self.expect_keyword("FORMAT")?;
+        if *self.peek() == Token::Equal {
+            self.advance();
+        }
+
+        let format_name = self.consume_identifier()?;
+        StorageFormat::from_name(&format_name).ok_or_else(|| self.error_with_context(format!(
+            "Unknown storage format: '{}'. Valid options: JSONL, BINARY",
+            format_name
+        )))
+    }
+
+    /// Parse the `ENGINE [=] name` table option; engine names are case-insensitive
+    fn parse_engine_option(&mut self) -> Result<crate::engines::EngineType> {
+        self.expect(Token::Engine)?;
+        if *self.peek() == Token::Equal {
+            self.advance();
+        }
+
+        let engine_name = self.consume_identifier()?;
+        crate::engines::EngineType::from_name(&engine_name).ok_or_else(|| self.error_with_context(format!(
+            "Unknown engine type: '{}'. Valid options: Granite, Sandstone",
+            engine_name
+        )))
+    }
+
+    /// Parse the `AUTO_INCREMENT [=] n` table option
+    fn parse_auto_increment_option(&mut self) -> Result<u64> {
+        self.expect(Token::AutoIncrement)?;
+        if *self.peek() == Token::Equal {
+            self.advance();
+        }
+        match self.peek().clone() {
+            Token::NumberLiteral(n) => {
+                self.advance();
+                n.parse().map_err(|_| self.error_with_context("Invalid AUTO_INCREMENT value".to_string()))
+            }
+            _ => Err(self.error_with_context("Expected number after AUTO_INCREMENT".to_string())),
+        }
+    }
+
     /// Parse column constraints (NOT NULL, PRIMARY KEY, AUTO_INCREMENT, DEFAULT, etc.)
     /// Returns: (nullable, primary_key, auto_increment, default)
     pub(super) fn parse_column_constraints(&mut self) -> Result<(bool, bool, bool, Option<Expr>)> {
@@ -179,23 +324,15 @@ impl Parser {
         let result = match self.peek() {
             Token::Table => {
                 self.advance();
-                // Optional IF EXISTS
-                if *self.peek() == Token::If {
-                    self.advance();
-                    self.expect(Token::Exists)?;
-                }
-                let table_name = self.consume_identifier()?;
-                Ok(Statement::DropTable(table_name))
+                let if_exists = self.parse_if_exists()?;
+                let name = self.consume_identifier()?;
+                Ok(Statement::DropTable { name, if_exists })
             }
             Token::Index => {
                 self.advance();
-                // Optional IF EXISTS
-                if *self.peek() == Token::If {
-                    self.advance();
-                    self.expect(Token::Exists)?;
-                }
-                let index_name = self.consume_identifier()?;
-                Ok(Statement::DropIndex(index_name))
+                let if_exists = self.parse_if_exists()?;
+                let name = self.consume_identifier()?;
+                Ok(Statement::DropIndex { name, if_exists })
             }
             Token::Eof => Err(self.error_with_context("Expected TABLE or INDEX after DROP".to_string())),
             _ => Err(self.error_with_context("Expected TABLE or INDEX after DROP".to_string())),
@@ -205,6 +342,16 @@ impl Parser {
         result
     }
 
+    /// Parse an optional IF EXISTS
+    fn parse_if_exists(&mut self) -> Result<bool> {
+        if *self.peek() != Token::If {
+            return Ok(false);
+        }
+        self.advance();
+        self.expect(Token::Exists)?;
+        Ok(true)
+    }
+
     pub(super) fn parse_truncate(&mut self) -> Result<Statement> {
         self.expect(Token::Truncate)?;
         self.expect(Token::Table)?;
@@ -220,18 +367,31 @@ impl Parser {
         self.expect(Token::Table)?;
         
         let table_name = self.consume_identifier()?;
-        
-        // Currently only support ENGINE= clause
-        self.expect(Token::Engine)?;
-        self.expect(Token::Equal)?;
-        
-        let engine_name = self.consume_identifier()?;
-        let engine_type = crate::engines::EngineType::from_name(&engine_name)
-            .ok_or_else(|| self.error_with_context(format!(
-                "Unknown engine type: '{}'. Valid options: Granite, Sandstone",
-                engine_name
-            )))?;
-        
+
+        if *self.peek() == Token::AutoIncrement {
+            let value = self.parse_auto_increment_option()?;
+            self.pop_context();
+            return Ok(Statement::AlterTable(AlterTableStmt {
+                table_name,
+                action: AlterTableAction::SetAutoIncrement(value),
+            }));
+        }
+
+        if self.peek_keyword("RENAME") {
+            self.advance();
+            self.expect_keyword("COLUMN")?;
+            let old_name = self.consume_identifier()?;
+            self.expect_keyword("TO")?;
+            let new_name = self.consume_identifier()?;
+            self.pop_context();
+            return Ok(Statement::AlterTable(AlterTableStmt {
+                table_name,
+                action: AlterTableAction::RenameColumn { old_name, new_name },
+            }));
+        }
+
+        // Otherwise only the ENGINE= clause is supported
+        let engine_type = self.parse_engine_option()?;
         self.pop_context();
         Ok(Statement::AlterTable(AlterTableStmt {
             table_name,
@@ -282,6 +442,28 @@ impl Parser {
                 self.advance();
                 DataType::Json
             }
+            // Not reserved, so `date` stays usable as a column name
+            Token::Identifier(name) if name.eq_ignore_ascii_case("DATE") => {
+                self.advance();
+                DataType::Date
+            }
+            Token::Identifier(name) if name.eq_ignore_ascii_case("BLOB") => {
+                self.advance();
+                DataType::Blob
+            }
+            // VARBINARY(n): the length is accepted but not enforced
+            Token::Identifier(name) if name.eq_ignore_ascii_case("VARBINARY") => {
+                self.advance();
+                if *self.peek() == Token::LeftParen {
+                    self.advance();
+                    match self.peek().clone() {
+                        Token::NumberLiteral(n) if n.parse::<u32>().is_ok() => self.advance(),
+                        _ => return Err(self.error_with_context("Invalid VARBINARY length".to_string())),
+                    };
+                    self.expect(Token::RightParen)?;
+                }
+                DataType::Blob
+            }
             other => {
                 return Err(self.error_with_context(format!(
                     "Expected data type, found {:?}",
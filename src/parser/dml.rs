@@ -53,23 +53,109 @@ impl Parser {
             }
         }
 
+        // ON DUPLICATE KEY UPDATE col = expr, ...
+        let on_duplicate_key_update = if *self.peek() == Token::On {
+            self.advance();
+            self.expect_keyword("DUPLICATE")?;
+            self.expect(Token::Key)?;
+            self.expect(Token::Update)?;
+            self.parse_assignments("ON DUPLICATE KEY UPDATE clause")?
+        } else {
+            Vec::new()
+        };
+
         self.pop_context();
         Ok(Statement::Insert(InsertStmt {
             table_name,
             columns,
             values,
+            on_duplicate_key_update,
         }))
     }
 
-    pub(super) fn parse_update(&mut self) -> Result<Statement> {
-        self.push_context("UPDATE statement");
-        self.expect(Token::Update)?;
+    /// Parse `LOAD DATA INFILE 'file' INTO TABLE t [FIELDS TERMINATED BY 'sep']
+    /// [IGNORE n LINES] [(col, ...)]`
+    pub(super) fn parse_load_data(&mut self) -> Result<Statement> {
+        self.push_context("LOAD DATA statement");
+        self.expect_keyword("LOAD")?;
+        self.expect_keyword("DATA")?;
+        if self.peek_keyword("LOCAL") {
+            return Err(self.error_with_context("LOAD DATA LOCAL is not supported".to_string()));
+        }
+        self.expect_keyword("INFILE")?;
+        let file = match self.peek().clone() {
+            Token::StringLiteral(file) => {
+                self.advance();
+                file
+            }
+            _ => return Err(self.error_with_context("Expected file name after INFILE".to_string())),
+        };
+        self.expect(Token::Into)?;
+        self.expect(Token::Table)?;
         let table_name = self.consume_identifier()?;
-        self.expect(Token::Set)?;
 
+        let mut field_separator = "\t".to_string();
+        if self.peek_keyword("FIELDS") || self.peek_keyword("COLUMNS") {
+            self.advance();
+            self.expect_keyword("TERMINATED")?;
+            self.expect(Token::By)?;
+            field_separator = match self.peek().clone() {
+                Token::StringLiteral(sep) if !sep.is_empty() => {
+                    self.advance();
+                    sep
+                }
+                _ => return Err(self.error_with_context("Expected a non-empty field separator".to_string())),
+            };
+        }
+
+        let mut ignore_lines = 0;
+        if self.peek_keyword("IGNORE") {
+            self.advance();
+            ignore_lines = match self.peek().clone() {
+                Token::NumberLiteral(n) => {
+                    self.advance();
+                    n.parse().map_err(|_| self.error_with_context(format!("Invalid line count: {}", n)))?
+                }
+                _ => return Err(self.error_with_context("Expected a line count after IGNORE".to_string())),
+            };
+            if !self.peek_keyword("LINES") && !self.peek_keyword("ROWS") {
+                return Err(self.error_with_context("Expected LINES after IGNORE n".to_string()));
+            }
+            self.advance();
+        }
+
+        let columns = if *self.peek() == Token::LeftParen {
+            self.advance();
+            let mut cols = Vec::new();
+            loop {
+                cols.push(self.consume_identifier()?);
+                if *self.peek() == Token::Comma {
+                    self.advance();
+                } else {
+                    break;
+                }
+            }
+            self.expect(Token::RightParen)?;
+            Some(cols)
+        } else {
+            None
+        };
+
+        self.pop_context();
+        Ok(Statement::LoadData(LoadDataStmt {
+            file,
+            table_name,
+            field_separator,
+            ignore_lines,
+            columns,
+        }))
+    }
+
+    /// Parse `col = expr [, col = expr ...]`
+    fn parse_assignments(&mut self, clause: &str) -> Result<Vec<(String, Expr)>> {
         let mut assignments = Vec::new();
         loop {
-            self.check_eof("SET clause")?;
+            self.check_eof(clause)?;
             let column = self.consume_identifier()?;
             self.expect(Token::Equal)?;
             let value = self.parse_expression()?;
@@ -81,14 +167,27 @@ impl Parser {
                 break;
             }
         }
+        Ok(assignments)
+    }
+
+    pub(super) fn parse_update(&mut self) -> Result<Statement> {
+        self.push_context("UPDATE statement");
+        self.expect(Token::Update)?;
+        let table_name = self.consume_identifier()?;
+        self.expect(Token::Set)?;
+        let assignments = self.parse_assignments("SET clause")?;
 
         let where_clause = self.parse_where_clause()?;
+        let order_by = self.parse_order_by_clause()?;
+        let limit = self.parse_limit_clause()?;
         
         self.pop_context();
         Ok(Statement::Update(UpdateStmt {
             table_name,
             assignments,
             where_clause,
+            order_by,
+            limit,
         }))
     }
 
@@ -99,11 +198,15 @@ impl Parser {
         let table_name = self.consume_identifier()?;
 
         let where_clause = self.parse_where_clause()?;
+        let order_by = self.parse_order_by_clause()?;
+        let limit = self.parse_limit_clause()?;
 
         self.pop_context();
         Ok(Statement::Delete(DeleteStmt {
             table_name,
             where_clause,
+            order_by,
+            limit,
         }))
     }
 }
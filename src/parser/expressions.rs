@@ -1,9 +1,10 @@
 use crate::error::Result;
 use crate::lexer::Token;
-use crate::types::Value;
-use crate::parser::ast::{Expr, BinaryOperator};
+use crate::types::{DataType, Value};
+use crate::parser::ast::{is_aggregate_function, Expr, BinaryOperator, Statement};
 use crate::parser::Parser;
 use crate::error::MiniSqlError;
+use crate::datetime::{Date, IntervalUnit};
 
 impl Parser {
     // Expression parsing with operator precedence
@@ -69,9 +70,17 @@ impl Parser {
         if *self.peek() == Token::Not {
             let checkpoint = self.pos;
             self.advance();
+            if *self.peek() == Token::Like {
+                self.advance();
+                let like = self.parse_like(left)?;
+                return Ok(Expr::Not(Box::new(like)));
+            }
             if *self.peek() == Token::In {
                 self.advance();
                 self.expect(Token::LeftParen)?;
+                if *self.peek() == Token::Select {
+                    return self.parse_in_subquery(left, true);
+                }
                 let mut values = vec![];
                 loop {
                     values.push(self.parse_additive()?);
@@ -86,7 +95,7 @@ impl Parser {
                     values,
                 });
             } else {
-                // Not followed by IN, restore position
+                // Not followed by IN or LIKE, restore position
                 self.pos = checkpoint;
             }
         }
@@ -94,6 +103,9 @@ impl Parser {
         if *self.peek() == Token::In {
             self.advance();
             self.expect(Token::LeftParen)?;
+            if *self.peek() == Token::Select {
+                return self.parse_in_subquery(left, false);
+            }
             let mut values = vec![];
             loop {
                 values.push(self.parse_additive()?);
@@ -116,7 +128,10 @@ impl Parser {
             Token::LessThanEq => BinaryOperator::LessThanOrEqual,
             Token::GreaterThan => BinaryOperator::GreaterThan,
             Token::GreaterThanEq => BinaryOperator::GreaterThanOrEqual,
-            Token::Like => BinaryOperator::Like,
+            Token::Like => {
+                self.advance();
+                return self.parse_like(left);
+            }
             _ => return Ok(left),
         };
 
@@ -129,6 +144,43 @@ impl Parser {
         })
     }
 
+    /// Parse the pattern and optional `ESCAPE 'c'` of `expr LIKE pattern`;
+    /// `LIKE` has been consumed
+    fn parse_like(&mut self, left: Expr) -> Result<Expr> {
+        let right = self.parse_additive()?;
+        let mut escape = Some('\\');
+        if self.peek_keyword("ESCAPE") {
+            self.advance();
+            escape = match self.peek().clone() {
+                Token::StringLiteral(s) if s.chars().count() <= 1 => s.chars().next(),
+                _ => return Err(self.error_with_context("ESCAPE requires a single-character string".to_string())),
+            };
+            self.advance();
+        }
+        Ok(Expr::BinaryOp {
+            left: Box::new(left),
+            op: BinaryOperator::Like { escape },
+            right: Box::new(right),
+        })
+    }
+
+    /// Parse the `SELECT ...)` of `expr [NOT] IN (SELECT ...)`; the opening
+    /// parenthesis has been consumed
+    fn parse_in_subquery(&mut self, expr: Expr, negated: bool) -> Result<Expr> {
+        self.push_context("IN subquery");
+        let subquery = match self.parse_select()? {
+            Statement::Select(select) => select,
+            _ => unreachable!("parse_select returns a SELECT"),
+        };
+        self.expect(Token::RightParen)?;
+        self.pop_context();
+        Ok(Expr::InSubquery {
+            expr: Box::new(expr),
+            subquery: Box::new(subquery),
+            negated,
+        })
+    }
+
     pub(super) fn parse_additive(&mut self) -> Result<Expr> {
         let mut left = self.parse_multiplicative()?;
         loop {
@@ -138,6 +190,13 @@ impl Parser {
                 _ => break,
             };
             self.advance();
+            // `expr +/- INTERVAL n UNIT` is date arithmetic
+            if self.at_interval() {
+                let (amount, unit) = self.parse_interval()?;
+                let name = if op == BinaryOperator::Plus { "DATE_ADD" } else { "DATE_SUB" };
+                left = Expr::FunctionCall { name: name.to_string(), args: vec![left, amount, unit], distinct: false, separator: None };
+                continue;
+            }
             let right = self.parse_multiplicative()?;
             left = Expr::BinaryOp {
                 left: Box::new(left),
@@ -148,6 +207,32 @@ impl Parser {
         Ok(left)
     }
 
+    /// Whether the next tokens start `INTERVAL n UNIT` rather than a column named `interval`
+    fn at_interval(&self) -> bool {
+        self.peek_keyword("INTERVAL")
+            && matches!(
+                self.tokens.get(self.pos + 1),
+                Some(Token::NumberLiteral(_) | Token::StringLiteral(_) | Token::Placeholder
+                    | Token::Minus | Token::LeftParen | Token::Identifier(_))
+            )
+    }
+
+    /// Parse `INTERVAL n UNIT` into the amount expression and the unit name as a
+    /// string literal, the argument form DATE_ADD/DATE_SUB take
+    fn parse_interval(&mut self) -> Result<(Expr, Expr)> {
+        self.expect_keyword("INTERVAL")?;
+        let amount = self.parse_primary_expression()?;
+        let unit = match self.peek().clone() {
+            Token::Identifier(name) => match IntervalUnit::parse(&name) {
+                Some(unit) => unit,
+                None => return Err(self.error_with_context(format!("Unknown INTERVAL unit '{}'", name))),
+            },
+            _ => return Err(self.error_with_context("Expected INTERVAL unit".to_string())),
+        };
+        self.advance();
+        Ok((amount, Expr::Literal(Value::String(unit.to_string()))))
+    }
+
     pub(super) fn parse_multiplicative(&mut self) -> Result<Expr> {
         let mut left = self.parse_json_access()?;
         loop {
@@ -221,21 +306,26 @@ impl Parser {
         Ok(expr)
     }
 
+    /// Parse the text of a number literal, possibly with a leading minus
+    fn number_literal(&self, n: &str) -> Result<Expr> {
+        if n.contains('.') {
+            let f: f64 = n.parse().map_err(|_| {
+                self.error_with_context(format!("Invalid floating point number: {}", n))
+            })?;
+            Ok(Expr::Literal(Value::Float(f)))
+        } else {
+            let i: i64 = n.parse().map_err(|_| {
+                self.error_with_context(format!("Invalid integer: {}", n))
+            })?;
+            Ok(Expr::Literal(Value::Integer(i)))
+        }
+    }
+
     pub(super) fn parse_primary_expression(&mut self) -> Result<Expr> {
         match self.peek().clone() {
             Token::NumberLiteral(n) => {
                 self.advance();
-                if n.contains('.') {
-                    let f: f64 = n.parse().map_err(|_| {
-                        self.error_with_context(format!("Invalid floating point number: {}", n))
-                    })?;
-                    Ok(Expr::Literal(Value::Float(f)))
-                } else {
-                    let i: i64 = n.parse().map_err(|_| {
-                        self.error_with_context(format!("Invalid integer: {}", n))
-                    })?;
-                    Ok(Expr::Literal(Value::Integer(i)))
-                }
+                self.number_literal(&n)
             }
             Token::StringLiteral(s) => {
                 self.advance();
@@ -257,23 +347,73 @@ impl Parser {
                 self.advance();
                 Ok(Expr::Literal(Value::Null))
             }
+            // DATE 'YYYY-MM-DD' typed literal
+            Token::Identifier(name) if name.eq_ignore_ascii_case("DATE")
+                && matches!(self.tokens.get(self.pos + 1), Some(Token::StringLiteral(_))) =>
+            {
+                self.advance();
+                let Token::StringLiteral(text) = self.peek().clone() else { unreachable!() };
+                self.advance();
+                match Date::parse(&text) {
+                    Some(date) => Ok(Expr::Literal(Value::Date(date))),
+                    None => Err(self.error_with_context(format!("Incorrect DATE value: '{}'", text))),
+                }
+            }
             Token::Identifier(name) => {
                 self.advance();
                 // Check for function call
                 if *self.peek() == Token::LeftParen {
                     self.advance();
+
+                    if name.eq_ignore_ascii_case("CAST") {
+                        return self.parse_cast();
+                    }
                     
                     // Special case: COUNT(*) - empty args means count all rows
                     if name.to_uppercase() == "COUNT" && *self.peek() == Token::Star {
                         self.advance(); // consume *
                         self.expect(Token::RightParen)?;
-                        return Ok(Expr::FunctionCall { name, args: vec![] });
+                        return Ok(Expr::FunctionCall { name, args: vec![], distinct: false, separator: None });
+                    }
+
+                    // COUNT(DISTINCT expr), SUM(DISTINCT expr), ...: aggregate unique values only
+                    let distinct = self.peek_keyword("DISTINCT");
+                    if distinct {
+                        if !is_aggregate_function(&name) {
+                            return Err(self.error_with_context(format!("DISTINCT is not allowed in {}()", name)));
+                        }
+                        self.advance();
+                    }
+
+                    // GROUP_CONCAT(expr [SEPARATOR 'sep'])
+                    if name.eq_ignore_ascii_case("GROUP_CONCAT") {
+                        let args = vec![self.parse_expression()?];
+                        let mut separator = None;
+                        if self.peek_keyword("SEPARATOR") {
+                            self.advance();
+                            match self.peek().clone() {
+                                Token::StringLiteral(sep) => {
+                                    self.advance();
+                                    separator = Some(sep);
+                                }
+                                _ => return Err(self.error_with_context("Expected string after SEPARATOR".to_string())),
+                            }
+                        }
+                        self.expect(Token::RightParen)?;
+                        return Ok(Expr::FunctionCall { name, args, distinct, separator });
                     }
                     
                     let mut args = Vec::new();
                     if *self.peek() != Token::RightParen {
                         loop {
-                            args.push(self.parse_expression()?);
+                            // DATE_ADD(date, INTERVAL n UNIT)
+                            if self.at_interval() {
+                                let (amount, unit) = self.parse_interval()?;
+                                args.push(amount);
+                                args.push(unit);
+                            } else {
+                                args.push(self.parse_expression()?);
+                            }
                             if *self.peek() == Token::Comma {
                                 self.advance();
                             } else {
@@ -281,8 +421,11 @@ impl Parser {
                             }
                         }
                     }
+                    if distinct && args.is_empty() {
+                        return Err(self.error_with_context(format!("Expected an expression after {}(DISTINCT", name)));
+                    }
                     self.expect(Token::RightParen)?;
-                    Ok(Expr::FunctionCall { name, args })
+                    Ok(Expr::FunctionCall { name, args, distinct, separator: None })
                 } else if *self.peek() == Token::Dot {
                     // Qualified column name: table.column
                     self.advance(); // consume .
@@ -293,20 +436,48 @@ impl Parser {
                     Ok(Expr::Column { table: None, name })
                 }
             }
+            // VALUES(col) in ON DUPLICATE KEY UPDATE: the value the INSERT tried to write
+            Token::Values => {
+                self.advance();
+                self.expect(Token::LeftParen)?;
+                let column = self.consume_identifier()?;
+                self.expect(Token::RightParen)?;
+                Ok(Expr::FunctionCall {
+                    name: "VALUES".to_string(),
+                    args: vec![Expr::Column { table: None, name: column }],
+                    distinct: false,
+                    separator: None,
+                })
+            }
             Token::LeftParen => {
                 self.advance();
+                if *self.peek() == Token::Select {
+                    self.push_context("subquery");
+                    let subquery = match self.parse_select()? {
+                        Statement::Select(select) => select,
+                        _ => unreachable!("parse_select returns a SELECT"),
+                    };
+                    self.expect(Token::RightParen)?;
+                    self.pop_context();
+                    return Ok(Expr::ScalarSubquery(Box::new(subquery)));
+                }
                 let expr = self.parse_expression()?;
                 self.expect(Token::RightParen)?;
                 Ok(expr)
             }
             Token::Minus => {
                 self.advance();
+                // Folding `-n` keeps -9223372036854775808 in range
+                if let Token::NumberLiteral(n) = self.peek().clone() {
+                    self.advance();
+                    return self.number_literal(&format!("-{}", n));
+                }
                 let expr = self.parse_primary_expression()?;
-                Ok(Expr::BinaryOp {
-                    left: Box::new(Expr::Literal(Value::Integer(0))),
-                    op: BinaryOperator::Minus,
-                    right: Box::new(expr),
-                })
+                Ok(Expr::UnaryMinus(Box::new(expr)))
+            }
+            Token::UserVariable(name) => {
+                self.advance();
+                Ok(Expr::UserVariable(name))
             }
             Token::Placeholder => {
                 self.advance();
@@ -323,4 +494,63 @@ impl Parser {
             ))),
         }
     }
+
+    /// Parse the rest of `CAST(expr AS type)` after the opening parenthesis
+    fn parse_cast(&mut self) -> Result<Expr> {
+        let expr = self.parse_expression()?;
+        self.expect(Token::As)?;
+        let target = self.parse_cast_type()?;
+        self.expect(Token::RightParen)?;
+        Ok(Expr::Cast { expr: Box::new(expr), target })
+    }
+
+    /// Target type of a CAST: any column type, plus MySQL's
+    /// `SIGNED`/`UNSIGNED [INTEGER]`, `CHAR[(n)]`, `BINARY` and `DECIMAL[(p[, s])]`
+    fn parse_cast_type(&mut self) -> Result<DataType> {
+        if self.peek_keyword("SIGNED") || self.peek_keyword("UNSIGNED") {
+            self.advance();
+            if matches!(self.peek(), Token::Int | Token::Integer) {
+                self.advance();
+            }
+            return Ok(DataType::Integer);
+        }
+        if self.peek_keyword("CHAR") {
+            self.advance();
+            let mut len = None;
+            if *self.peek() == Token::LeftParen {
+                self.advance();
+                len = Some(self.parse_cast_length()?);
+                self.expect(Token::RightParen)?;
+            }
+            return Ok(DataType::Varchar(len));
+        }
+        if self.peek_keyword("BINARY") {
+            self.advance();
+            return Ok(DataType::Blob);
+        }
+        if self.peek_keyword("DECIMAL") {
+            self.advance();
+            if *self.peek() == Token::LeftParen {
+                self.advance();
+                self.parse_cast_length()?;
+                if *self.peek() == Token::Comma {
+                    self.advance();
+                    self.parse_cast_length()?;
+                }
+                self.expect(Token::RightParen)?;
+            }
+            return Ok(DataType::Float);
+        }
+        self.parse_data_type()
+    }
+
+    fn parse_cast_length(&mut self) -> Result<u32> {
+        match self.peek().clone() {
+            Token::NumberLiteral(n) => {
+                self.advance();
+                n.parse().map_err(|_| self.error_with_context(format!("Invalid length in CAST: {}", n)))
+            }
+            other => Err(self.error_with_context(format!("Expected length in CAST, found {:?}", other))),
+        }
+    }
 }
@@ -7,11 +7,13 @@
 //! - UPDATE (with WHERE)
 //! - DELETE (with WHERE)
 //! - BEGIN, COMMIT, ROLLBACK
+//! - SET TRANSACTION ISOLATION LEVEL
 //!
 //! The parser produces an Abstract Syntax Tree (AST) that the executor can process.
 
 use crate::error::Result;
 use crate::lexer::{Lexer, Token};
+use crate::types::SqlMode;
 
 pub mod ast;
 pub use self::ast::*;
@@ -31,6 +33,9 @@ mod utils;
 /// SQL Parser
 pub struct Parser {
     pub(super) tokens: Vec<Token>,
+    /// Character range of each token in `sql`, for error messages
+    pub(super) spans: Vec<(usize, usize)>,
+    pub(super) sql: String,
     pub(super) pos: usize,
     /// Counter for placeholder parameters (?) in prepared statements
     pub(super) placeholder_count: usize,
@@ -41,22 +46,41 @@ pub struct Parser {
 impl Parser {
     /// Parse a SQL string into a statement
     pub fn parse(sql: &str) -> Result<Statement> {
-        let mut lexer = Lexer::new(sql);
-        let tokens = lexer.tokenize()?;
-        let mut parser = Parser { tokens, pos: 0, placeholder_count: 0, context_stack: Vec::new() };
-        parser.parse_statement()
+        Self::parse_with_mode(sql, &SqlMode::default())
+    }
+
+    /// Parse a SQL string into a statement under a session's SQL mode
+    pub fn parse_with_mode(sql: &str, mode: &SqlMode) -> Result<Statement> {
+        Self::new(sql, mode)?.parse_statement()
     }
 
     /// Parse a SQL string for prepared statement usage
     /// Returns the statement and the number of placeholders found
     pub fn parse_prepared(sql: &str) -> Result<(Statement, usize)> {
-        let mut lexer = Lexer::new(sql);
-        let tokens = lexer.tokenize()?;
-        let mut parser = Parser { tokens, pos: 0, placeholder_count: 0, context_stack: Vec::new() };
+        Self::parse_prepared_with_mode(sql, &SqlMode::default())
+    }
+
+    /// Parse a SQL string for prepared statement usage under a session's SQL mode
+    pub fn parse_prepared_with_mode(sql: &str, mode: &SqlMode) -> Result<(Statement, usize)> {
+        let mut parser = Self::new(sql, mode)?;
         let stmt = parser.parse_statement()?;
+        // A prepared statement is a single statement; anything after it
+        // would otherwise be dropped without notice
+        if *parser.peek() != Token::Eof {
+            return Err(parser.error_with_context(
+                "A prepared statement must contain a single statement".to_string()
+            ));
+        }
         Ok((stmt, parser.placeholder_count))
     }
 
+    fn new(sql: &str, mode: &SqlMode) -> Result<Self> {
+        let spanned = Lexer::new(sql).with_ansi_quotes(mode.ansi_quotes()).tokenize_spanned()?;
+        let spans = spanned.iter().map(|t| (t.start, t.end)).collect();
+        let tokens = spanned.into_iter().map(|t| t.token).collect();
+        Ok(Parser { tokens, spans, sql: sql.to_string(), pos: 0, placeholder_count: 0, context_stack: Vec::new() })
+    }
+
     pub(super) fn peek(&self) -> &Token {
         self.tokens.get(self.pos).unwrap_or(&Token::Eof)
     }
@@ -133,11 +157,19 @@ impl Parser {
             Token::Alter => self.parse_alter()?,
             Token::Begin | Token::Start => self.parse_begin()?,
             Token::Commit => { self.advance(); Statement::Commit }
-            Token::Rollback => { self.advance(); Statement::Rollback }
+            Token::Rollback => self.parse_rollback()?,
+            Token::Set => self.parse_set()?,
+            Token::Identifier(s) if s.eq_ignore_ascii_case("SAVEPOINT") => self.parse_savepoint()?,
+            Token::Identifier(s) if s.eq_ignore_ascii_case("RELEASE") => self.parse_release_savepoint()?,
             Token::Checkpoint => { self.advance(); Statement::Checkpoint }
-            Token::Vacuum => { self.advance(); Statement::Vacuum }
+            Token::Vacuum => self.parse_vacuum()?,
+            Token::Identifier(s) if s.eq_ignore_ascii_case("FLUSH") => self.parse_flush()?,
+            Token::Identifier(s) if s.eq_ignore_ascii_case("LOAD") => self.parse_load_data()?,
+            Token::Identifier(s) if s.eq_ignore_ascii_case("GRANT") => self.parse_grant()?,
+            Token::Identifier(s) if s.eq_ignore_ascii_case("REVOKE") => self.parse_revoke()?,
             Token::Show => self.parse_show()?,
             Token::Describe | Token::Desc => self.parse_describe()?,
+            Token::Identifier(s) if s.eq_ignore_ascii_case("EXPLAIN") => self.parse_explain()?,
             Token::Eof => return Err(self.error_with_context("Empty statement or unexpected end of input".to_string())),
             _ => return Err(self.error_with_context(format!(
                 "Unexpected token: {:?}",
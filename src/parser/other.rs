@@ -1,5 +1,6 @@
-use crate::error::Result;
+use crate::error::{MiniSqlError, Result};
 use crate::lexer::Token;
+use crate::types::{IsolationLevel, Privilege, SqlMode};
 use super::ast::*;
 use super::Parser;
 
@@ -12,8 +13,285 @@ impl Parser {
         Ok(Statement::Begin)
     }
 
+    /// Parse ROLLBACK [WORK] [TO [SAVEPOINT] name]
+    pub(super) fn parse_rollback(&mut self) -> Result<Statement> {
+        self.expect(Token::Rollback)?;
+        if self.peek_keyword("WORK") {
+            self.advance();
+        }
+        if !self.peek_keyword("TO") {
+            return Ok(Statement::Rollback);
+        }
+        self.advance();
+        if self.peek_keyword("SAVEPOINT") {
+            self.advance();
+        }
+        let name = self.consume_identifier()?;
+        Ok(Statement::RollbackToSavepoint(name))
+    }
+
+    /// Parse SAVEPOINT name
+    pub(super) fn parse_savepoint(&mut self) -> Result<Statement> {
+        self.expect_keyword("SAVEPOINT")?;
+        let name = self.consume_identifier()?;
+        Ok(Statement::Savepoint(name))
+    }
+
+    /// Parse RELEASE SAVEPOINT name
+    pub(super) fn parse_release_savepoint(&mut self) -> Result<Statement> {
+        self.expect_keyword("RELEASE")?;
+        self.expect_keyword("SAVEPOINT")?;
+        let name = self.consume_identifier()?;
+        Ok(Statement::ReleaseSavepoint(name))
+    }
+
+    /// Parse SET, a comma-separated list of assignments:
+    /// SET @name = expr
+    /// SET [SESSION | LOCAL] TRANSACTION ISOLATION LEVEL level
+    /// SET [SESSION | LOCAL | @@[session. | local.]]{transaction_isolation | tx_isolation} = 'level'
+    /// SET [SESSION | LOCAL | @@[session. | local.]]sql_mode = {'mode,...' | DEFAULT | function(...)}
+    /// SET [SESSION | LOCAL | @@[session. | local.]]autocommit = {0 | 1 | ON | OFF}
+    /// SET NAMES charset [COLLATE collation]
+    /// Other session variables are accepted and ignored, as MySQL clients
+    /// set them on connect.
+    pub(super) fn parse_set(&mut self) -> Result<Statement> {
+        self.expect(Token::Set)?;
+        self.push_context("SET statement");
+
+        let mut statements = Vec::new();
+        loop {
+            if let Some(stmt) = self.parse_set_assignment()? {
+                statements.push(stmt);
+            }
+            if *self.peek() != Token::Comma {
+                break;
+            }
+            self.advance();
+        }
+
+        self.pop_context();
+        if statements.len() == 1 {
+            return Ok(statements.remove(0));
+        }
+        if !statements.is_empty() && statements.iter().all(|stmt| matches!(stmt, Statement::SetUserVariables(_))) {
+            let assignments = statements
+                .into_iter()
+                .flat_map(|stmt| match stmt {
+                    Statement::SetUserVariables(assignments) => assignments,
+                    _ => Vec::new(),
+                })
+                .collect();
+            return Ok(Statement::SetUserVariables(assignments));
+        }
+        Ok(Statement::SetVariables(statements))
+    }
+
+    /// Parse one assignment of a SET list; `None` for a variable that is
+    /// accepted and ignored
+    fn parse_set_assignment(&mut self) -> Result<Option<Statement>> {
+        if let Token::UserVariable(name) = self.peek().clone() {
+            self.advance();
+            self.expect(Token::Equal)?;
+            return Ok(Some(Statement::SetUserVariables(vec![(name, self.parse_expression()?)])));
+        }
+
+        let mut explicit_session = if self.peek_keyword("SESSION") || self.peek_keyword("LOCAL") {
+            self.advance();
+            true
+        } else if self.peek_keyword("GLOBAL") {
+            return Err(MiniSqlError::Syntax("SET GLOBAL is not supported".into()));
+        } else {
+            false
+        };
+
+        if *self.peek() == Token::Transaction {
+            self.advance();
+            self.expect_keyword("ISOLATION")?;
+            self.expect_keyword("LEVEL")?;
+            let level = self.parse_isolation_level()?;
+            let scope = if explicit_session { IsolationScope::Session } else { IsolationScope::NextTransaction };
+            return Ok(Some(Statement::SetIsolationLevel { level, scope }));
+        }
+
+        let name = match self.peek().clone() {
+            Token::SystemVariable(text) if !explicit_session => {
+                let name = match text.split_once('.') {
+                    Some((scope, name)) if scope.eq_ignore_ascii_case("session") || scope.eq_ignore_ascii_case("local") => {
+                        explicit_session = true;
+                        name.to_string()
+                    }
+                    Some((scope, _)) if scope.eq_ignore_ascii_case("global") => {
+                        return Err(MiniSqlError::Syntax("SET GLOBAL is not supported".into()));
+                    }
+                    Some(_) => {
+                        return Err(self.error_with_context("Unsupported SET statement".to_string()))
+                    }
+                    None => text,
+                };
+                self.advance();
+                name
+            }
+            Token::Identifier(name) => {
+                self.advance();
+                name
+            }
+            _ => {
+                return Err(self.error_with_context("Unsupported SET statement".to_string()))
+            }
+        };
+
+        if name.eq_ignore_ascii_case("NAMES") && !explicit_session {
+            // Every connection speaks utf8mb4, whatever the client asks for
+            match self.peek() {
+                Token::Identifier(_) | Token::StringLiteral(_) | Token::Default => self.advance(),
+                _ => {
+                    return Err(self.error_with_context("Expected a character set".to_string()))
+                }
+            }
+            if self.peek_keyword("COLLATE") {
+                self.advance();
+                match self.peek() {
+                    Token::Identifier(_) | Token::StringLiteral(_) => self.advance(),
+                    _ => {
+                        return Err(self.error_with_context("Expected a collation".to_string()))
+                    }
+                }
+            }
+            return Ok(None);
+        }
+
+        self.expect(Token::Equal)?;
+        let stmt = if name.eq_ignore_ascii_case("transaction_isolation") || name.eq_ignore_ascii_case("tx_isolation") {
+            let level = match self.peek().clone() {
+                Token::StringLiteral(name) => {
+                    self.advance();
+                    IsolationLevel::from_name(&name).ok_or_else(|| {
+                        MiniSqlError::Syntax(format!(
+                            "Variable 'transaction_isolation' can't be set to the value of '{}'",
+                            name
+                        ))
+                    })?
+                }
+                _ => self.parse_isolation_level()?,
+            };
+            Statement::SetIsolationLevel { level, scope: IsolationScope::Session }
+        } else if name.eq_ignore_ascii_case("sql_mode") {
+            let mode = match self.peek().clone() {
+                Token::StringLiteral(modes) => SqlMode::parse(&modes),
+                Token::Default => SqlMode::default(),
+                // Drivers adjust the mode with expressions such as
+                // CONCAT(@@sql_mode, ',...'), which are accepted and ignored
+                Token::Identifier(_) if self.tokens.get(self.pos + 1) == Some(&Token::LeftParen) => {
+                    self.skip_set_value();
+                    return Ok(None);
+                }
+                _ => {
+                    return Err(self.error_with_context("Expected a quoted sql_mode".to_string()))
+                }
+            };
+            self.advance();
+            Statement::SetSqlMode(mode)
+        } else if name.eq_ignore_ascii_case("autocommit") {
+            let enabled = match self.peek().clone() {
+                Token::NumberLiteral(n) if n == "1" => true,
+                Token::NumberLiteral(n) if n == "0" => false,
+                Token::True | Token::On | Token::Default => true,
+                Token::False => false,
+                Token::Identifier(name) | Token::StringLiteral(name) if name.eq_ignore_ascii_case("ON") => true,
+                Token::Identifier(name) | Token::StringLiteral(name) if name.eq_ignore_ascii_case("OFF") => false,
+                other => {
+                    let value = match other {
+                        Token::NumberLiteral(text) | Token::Identifier(text) | Token::StringLiteral(text) => text,
+                        other => format!("{:?}", other),
+                    };
+                    return Err(MiniSqlError::Syntax(format!(
+                        "Variable 'autocommit' can't be set to the value of '{}'",
+                        value
+                    )));
+                }
+            };
+            self.advance();
+            Statement::SetAutocommit(enabled)
+        } else {
+            match self.peek() {
+                Token::Default | Token::On => self.advance(),
+                _ => {
+                    self.parse_expression()?;
+                }
+            }
+            return Ok(None);
+        };
+        Ok(Some(stmt))
+    }
+
+    /// Skip the value of a SET assignment, up to the next top-level comma
+    fn skip_set_value(&mut self) {
+        let mut depth = 0usize;
+        loop {
+            match self.peek() {
+                Token::Eof | Token::Semicolon => return,
+                Token::Comma if depth == 0 => return,
+                Token::LeftParen => depth += 1,
+                Token::RightParen => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    /// Parse READ UNCOMMITTED | READ COMMITTED | REPEATABLE READ | SERIALIZABLE
+    fn parse_isolation_level(&mut self) -> Result<IsolationLevel> {
+        if self.peek_keyword("READ") {
+            self.advance();
+            if self.peek_keyword("COMMITTED") {
+                self.advance();
+                return Ok(IsolationLevel::ReadCommitted);
+            }
+            self.expect_keyword("UNCOMMITTED")?;
+            return Ok(IsolationLevel::ReadUncommitted);
+        }
+        if self.peek_keyword("REPEATABLE") {
+            self.advance();
+            self.expect_keyword("READ")?;
+            return Ok(IsolationLevel::RepeatableRead);
+        }
+        self.expect_keyword("SERIALIZABLE")?;
+        Ok(IsolationLevel::Serializable)
+    }
+
     pub(super) fn parse_show(&mut self) -> Result<Statement> {
         self.expect(Token::Show)?;
+        if *self.peek() == Token::Create {
+            self.advance();
+            self.expect(Token::Table)?;
+            let table_name = self.consume_identifier()?;
+            return Ok(Statement::ShowCreateTable(table_name));
+        }
+        // SHOW [GLOBAL | SESSION] STATUS: counters are server-wide either way
+        if self.peek_keyword("GLOBAL") || self.peek_keyword("SESSION") {
+            self.advance();
+            self.expect_keyword("STATUS")?;
+            return Ok(Statement::ShowStatus);
+        }
+        if self.peek_keyword("STATUS") {
+            self.advance();
+            return Ok(Statement::ShowStatus);
+        }
+        if self.peek_keyword("WARNINGS") {
+            self.advance();
+            return Ok(Statement::ShowWarnings);
+        }
+        // SHOW {INDEX | INDEXES | KEYS} {FROM | IN} table
+        if *self.peek() == Token::Index || self.peek_keyword("INDEXES") || self.peek_keyword("KEYS") {
+            self.advance();
+            if *self.peek() == Token::In {
+                self.advance();
+            } else {
+                self.expect(Token::From)?;
+            }
+            let table_name = self.consume_identifier()?;
+            return Ok(Statement::ShowIndex(table_name));
+        }
         self.expect(Token::Tables)?;
         Ok(Statement::ShowTables)
     }
@@ -23,4 +301,115 @@ impl Parser {
         let table_name = self.consume_identifier()?;
         Ok(Statement::Describe(table_name))
     }
+
+    /// Parse `EXPLAIN SELECT ...`, or `EXPLAIN table` as a synonym for DESCRIBE
+    pub(super) fn parse_explain(&mut self) -> Result<Statement> {
+        self.advance(); // EXPLAIN
+        if *self.peek() != Token::Select {
+            let table_name = self.consume_identifier()?;
+            return Ok(Statement::Describe(table_name));
+        }
+        Ok(Statement::Explain(Box::new(self.parse_select()?)))
+    }
+
+    /// Parse FLUSH TABLES
+    pub(super) fn parse_flush(&mut self) -> Result<Statement> {
+        self.expect_keyword("FLUSH")?;
+        self.expect(Token::Tables)?;
+        Ok(Statement::FlushTables)
+    }
+
+    /// Parse `VACUUM [STORAGE FORMAT [=] fmt]`
+    pub(super) fn parse_vacuum(&mut self) -> Result<Statement> {
+        self.expect(Token::Vacuum)?;
+        let storage_format = if self.peek_keyword("STORAGE") {
+            Some(self.parse_storage_format()?)
+        } else {
+            None
+        };
+        Ok(Statement::Vacuum(storage_format))
+    }
+
+    /// Parse `GRANT privileges ON table TO user`
+    pub(super) fn parse_grant(&mut self) -> Result<Statement> {
+        self.expect_keyword("GRANT")?;
+        self.push_context("GRANT statement");
+        let privileges = self.parse_privileges()?;
+        let table_name = self.parse_grant_table()?;
+        self.expect_keyword("TO")?;
+        let user = self.parse_grant_user()?;
+        self.pop_context();
+        Ok(Statement::Grant(GrantStmt { privileges, table_name, user }))
+    }
+
+    /// Parse `REVOKE privileges ON table FROM user`
+    pub(super) fn parse_revoke(&mut self) -> Result<Statement> {
+        self.expect_keyword("REVOKE")?;
+        self.push_context("REVOKE statement");
+        let privileges = self.parse_privileges()?;
+        let table_name = self.parse_grant_table()?;
+        self.expect(Token::From)?;
+        let user = self.parse_grant_user()?;
+        self.pop_context();
+        Ok(Statement::Revoke(GrantStmt { privileges, table_name, user }))
+    }
+
+    /// Parse `ALL [PRIVILEGES]` or a comma-separated list of SELECT, INSERT, UPDATE and DELETE
+    fn parse_privileges(&mut self) -> Result<Vec<Privilege>> {
+        if self.peek_keyword("ALL") {
+            self.advance();
+            if self.peek_keyword("PRIVILEGES") {
+                self.advance();
+            }
+            return Ok(Privilege::ALL.to_vec());
+        }
+        let mut privileges = Vec::new();
+        loop {
+            let privilege = match self.peek() {
+                Token::Select => Privilege::Select,
+                Token::Insert => Privilege::Insert,
+                Token::Update => Privilege::Update,
+                Token::Delete => Privilege::Delete,
+                _ => {
+                    return Err(self.error_with_context(format!(
+                        "Expected SELECT, INSERT, UPDATE, DELETE or ALL, found {}",
+                        self.token_brief(self.peek())
+                    )))
+                }
+            };
+            self.advance();
+            if !privileges.contains(&privilege) {
+                privileges.push(privilege);
+            }
+            if *self.peek() != Token::Comma {
+                return Ok(privileges);
+            }
+            self.advance();
+        }
+    }
+
+    /// Parse `ON [TABLE] table`
+    fn parse_grant_table(&mut self) -> Result<String> {
+        self.expect(Token::On)?;
+        if *self.peek() == Token::Table {
+            self.advance();
+        }
+        self.consume_identifier()
+    }
+
+    /// Parse `user`, `'user'` or `'user'@'host'`, returning the user name
+    fn parse_grant_user(&mut self) -> Result<String> {
+        let user = match self.peek() {
+            Token::StringLiteral(name) => {
+                let name = name.clone();
+                self.advance();
+                name
+            }
+            _ => self.consume_identifier()?,
+        };
+        if matches!(self.peek(), Token::UserVariable(_)) {
+            self.advance();
+        }
+        Ok(user)
+    }
 }
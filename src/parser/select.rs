@@ -37,6 +37,13 @@ impl Parser {
         // Optional LIMIT clause
         let limit = self.parse_limit_clause()?;
 
+        // Optional FOR UPDATE locking clause
+        let for_update = self.peek_keyword("FOR");
+        if for_update {
+            self.advance();
+            self.expect(Token::Update)?;
+        }
+
         self.pop_context();
         
         Ok(Statement::Select(SelectStmt {
@@ -47,6 +54,7 @@ impl Parser {
             group_by,
             order_by,
             limit,
+            for_update,
         }))
     }
 
@@ -174,14 +182,74 @@ impl Parser {
                 s.to_uppercase() == "WHERE" || 
                 s.to_uppercase() == "ORDER" || 
                 s.to_uppercase() == "LIMIT" ||
-                s.to_uppercase() == "ON")
+                s.to_uppercase() == "ON" ||
+                s.to_uppercase() == "FOR" ||
+                s.to_uppercase() == "TABLESAMPLE")
         {
             Some(self.consume_identifier()?)
         } else {
             None
         };
 
-        Ok(TableRef { name, alias })
+        let sample = if self.peek_keyword("TABLESAMPLE") {
+            Some(self.parse_table_sample()?)
+        } else {
+            None
+        };
+
+        Ok(TableRef { name, alias, sample })
+    }
+
+    /// Parse `TABLESAMPLE (n PERCENT | n ROWS) [REPEATABLE (seed)]`
+    fn parse_table_sample(&mut self) -> Result<TableSample> {
+        self.push_context("TABLESAMPLE clause");
+        self.expect_keyword("TABLESAMPLE")?;
+        self.expect(Token::LeftParen)?;
+        let amount = match self.peek().clone() {
+            Token::NumberLiteral(n) => {
+                self.advance();
+                n
+            }
+            other => {
+                return Err(self.error_with_context(format!(
+                    "Expected sample size, found {}",
+                    self.token_brief(&other)
+                )));
+            }
+        };
+        let size = if self.peek_keyword("PERCENT") {
+            self.advance();
+            match amount.parse::<f64>() {
+                Ok(percent) if (0.0..=100.0).contains(&percent) => SampleSize::Percent(percent),
+                _ => return Err(self.error_with_context("Sample percentage must be between 0 and 100".to_string())),
+            }
+        } else if self.peek_keyword("ROWS") {
+            self.advance();
+            SampleSize::Rows(amount.parse().map_err(|_| self.error_with_context("Invalid sample row count".to_string()))?)
+        } else {
+            return Err(self.error_with_context(format!(
+                "Expected PERCENT or ROWS, found {}",
+                self.token_brief(self.peek())
+            )));
+        };
+        self.expect(Token::RightParen)?;
+
+        let seed = if self.peek_keyword("REPEATABLE") {
+            self.advance();
+            self.expect(Token::LeftParen)?;
+            let seed = match self.peek().clone() {
+                Token::NumberLiteral(n) => n.parse().map_err(|_| self.error_with_context("Invalid REPEATABLE seed".to_string()))?,
+                _ => return Err(self.error_with_context("Expected number in REPEATABLE".to_string())),
+            };
+            self.advance();
+            self.expect(Token::RightParen)?;
+            Some(seed)
+        } else {
+            None
+        };
+
+        self.pop_context();
+        Ok(TableSample { size, seed })
     }
 
     /// Parse a JOIN clause
@@ -1,5 +1,5 @@
 use super::*;
-use crate::types::Value;
+use crate::types::{DataType, IsolationLevel, Privilege, ReferentialAction, SqlMode, StorageFormat, Value};
 
 #[test]
 fn test_parse_select() {
@@ -30,6 +30,269 @@ fn test_parse_create_table() {
     }
 }
 
+#[test]
+fn test_parse_storage_format() {
+    let stmt = Parser::parse(
+        "CREATE TABLE t (id INTEGER) STORAGE FORMAT = BINARY ENGINE=Sandstone"
+    ).unwrap();
+    match stmt {
+        Statement::CreateTable(c) => {
+            assert_eq!(c.storage_format, Some(StorageFormat::Binary));
+            assert_eq!(c.engine, Some(crate::engines::EngineType::Sandstone));
+        }
+        _ => panic!("Expected CREATE TABLE"),
+    }
+
+    assert!(matches!(Parser::parse("VACUUM").unwrap(), Statement::Vacuum(None)));
+    assert!(matches!(
+        Parser::parse("vacuum storage format jsonl").unwrap(),
+        Statement::Vacuum(Some(StorageFormat::Jsonl))
+    ));
+    assert!(Parser::parse("CREATE TABLE t (id INTEGER) STORAGE FORMAT = XML").is_err());
+
+    assert!(matches!(Parser::parse("FLUSH TABLES").unwrap(), Statement::FlushTables));
+    assert!(Parser::parse("FLUSH").is_err());
+}
+
+#[test]
+fn test_parse_create_table_auto_increment_option() {
+    match Parser::parse("CREATE TABLE t (id INTEGER PRIMARY KEY AUTO_INCREMENT) ENGINE=Granite AUTO_INCREMENT=100").unwrap() {
+        Statement::CreateTable(c) => assert_eq!(c.auto_increment, Some(100)),
+        _ => panic!("Expected CREATE TABLE"),
+    }
+    assert!(Parser::parse("CREATE TABLE t (id INTEGER) AUTO_INCREMENT = x").is_err());
+
+    match Parser::parse("ALTER TABLE t AUTO_INCREMENT = 1000").unwrap() {
+        Statement::AlterTable(a) => assert!(matches!(a.action, AlterTableAction::SetAutoIncrement(1000))),
+        _ => panic!("Expected ALTER TABLE"),
+    }
+}
+
+#[test]
+fn test_parse_alter_rename_column() {
+    match Parser::parse("ALTER TABLE t RENAME COLUMN a TO b").unwrap() {
+        Statement::AlterTable(alter) => match alter.action {
+            AlterTableAction::RenameColumn { old_name, new_name } => {
+                assert_eq!(old_name, "a");
+                assert_eq!(new_name, "b");
+            }
+            other => panic!("Expected RENAME COLUMN, got {:?}", other),
+        },
+        _ => panic!("Expected ALTER TABLE"),
+    }
+    assert!(Parser::parse("ALTER TABLE t RENAME COLUMN a b").is_err());
+}
+
+#[test]
+fn test_parse_create_table_foreign_keys() {
+    let sql = "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER, \
+        FOREIGN KEY (user_id) REFERENCES users(id), \
+        CONSTRAINT fk_parent FOREIGN KEY (id) REFERENCES orders (id) ON DELETE CASCADE ON UPDATE RESTRICT)";
+    match Parser::parse(sql).unwrap() {
+        Statement::CreateTable(c) => {
+            assert_eq!(c.columns.len(), 2);
+            assert_eq!(c.foreign_keys.len(), 2);
+            let fk = &c.foreign_keys[0];
+            assert_eq!(fk.name, None);
+            assert_eq!(fk.columns, vec!["user_id".to_string()]);
+            assert_eq!(fk.ref_table, "users");
+            assert_eq!(fk.ref_columns, vec!["id".to_string()]);
+            assert_eq!(fk.on_delete, ReferentialAction::Restrict);
+            assert_eq!(c.foreign_keys[1].name.as_deref(), Some("fk_parent"));
+            assert_eq!(c.foreign_keys[1].on_delete, ReferentialAction::Cascade);
+        }
+        _ => panic!("Expected CREATE TABLE"),
+    }
+    assert!(Parser::parse("CREATE TABLE t (a INTEGER, FOREIGN KEY (a) REFERENCES p (x, y))").is_err());
+    assert!(Parser::parse("CREATE TABLE t (a INTEGER, FOREIGN KEY (a) REFERENCES p (x) ON UPDATE CASCADE)").is_err());
+}
+
+#[test]
+fn test_parse_table_level_primary_key() {
+    let sql = "CREATE TABLE t (a INTEGER, b TEXT, CONSTRAINT pk PRIMARY KEY (a, b), FOREIGN KEY (a) REFERENCES p (x))";
+    match Parser::parse(sql).unwrap() {
+        Statement::CreateTable(c) => {
+            assert_eq!(c.primary_key, vec!["a".to_string(), "b".to_string()]);
+            assert!(c.columns.iter().all(|col| !col.primary_key));
+            assert_eq!(c.foreign_keys.len(), 1);
+        }
+        _ => panic!("Expected CREATE TABLE"),
+    }
+    assert!(Parser::parse("CREATE TABLE t (a INTEGER, PRIMARY KEY (a), PRIMARY KEY (a))").is_err());
+}
+
+#[test]
+fn test_parse_grant_and_revoke() {
+    let grant = |privileges: &[Privilege], table: &str, user: &str| GrantStmt {
+        privileges: privileges.to_vec(),
+        table_name: table.into(),
+        user: user.into(),
+    };
+    match Parser::parse("GRANT SELECT, INSERT, SELECT ON orders TO alice").unwrap() {
+        Statement::Grant(g) => assert_eq!(g, grant(&[Privilege::Select, Privilege::Insert], "orders", "alice")),
+        other => panic!("Expected GRANT, got {:?}", other),
+    }
+    match Parser::parse("grant all privileges on table orders to 'bob'@'%'").unwrap() {
+        Statement::Grant(g) => assert_eq!(g, grant(&Privilege::ALL, "orders", "bob")),
+        other => panic!("Expected GRANT, got {:?}", other),
+    }
+    match Parser::parse("REVOKE DELETE ON orders FROM 'bob'@'localhost'").unwrap() {
+        Statement::Revoke(g) => assert_eq!(g, grant(&[Privilege::Delete], "orders", "bob")),
+        other => panic!("Expected REVOKE, got {:?}", other),
+    }
+    assert!(Parser::parse("GRANT DROP ON orders TO bob").is_err());
+    assert!(Parser::parse("GRANT SELECT ON orders FROM bob").is_err());
+    assert!(Parser::parse("REVOKE SELECT orders FROM bob").is_err());
+}
+
+#[test]
+fn test_parse_interval_arithmetic() {
+    match Parser::parse("SELECT * FROM t WHERE created_at > NOW() - INTERVAL 7 DAY").unwrap() {
+        Statement::Select(s) => match s.where_clause.unwrap() {
+            Expr::BinaryOp { op: BinaryOperator::GreaterThan, right, .. } => match *right {
+                Expr::FunctionCall { name, args, .. } => {
+                    assert_eq!(name, "DATE_SUB");
+                    assert!(matches!(&args[0], Expr::FunctionCall { name, .. } if name == "NOW"));
+                    assert!(matches!(args[1], Expr::Literal(Value::Integer(7))));
+                    assert!(matches!(&args[2], Expr::Literal(Value::String(u)) if u == "DAY"));
+                }
+                other => panic!("Expected DATE_SUB, got {:?}", other),
+            },
+            other => panic!("Expected comparison, got {:?}", other),
+        },
+        _ => panic!("Expected SELECT"),
+    }
+    assert!(matches!(
+        Parser::parse("SELECT DATE_ADD(d, INTERVAL 1 month) FROM t").unwrap(),
+        Statement::Select(s) if matches!(&s.columns[0], SelectColumn::Expr { expr: Expr::FunctionCall { args, .. }, .. } if args.len() == 3)
+    ));
+    assert!(Parser::parse("SELECT d + INTERVAL 1 FORTNIGHT FROM t").is_err());
+}
+
+#[test]
+fn test_parse_in_subquery() {
+    match Parser::parse("SELECT * FROM orders WHERE customer_id NOT IN (SELECT id FROM customers WHERE active = ?) AND id > ?").unwrap() {
+        Statement::Select(s) => match s.where_clause.unwrap() {
+            Expr::BinaryOp { left, op: BinaryOperator::And, right } => {
+                match *left {
+                    Expr::InSubquery { expr, subquery, negated } => {
+                        assert!(negated);
+                        assert!(matches!(*expr, Expr::Column { ref name, .. } if name == "customer_id"));
+                        assert_eq!(subquery.from.as_ref().unwrap().name, "customers");
+                        assert!(matches!(subquery.where_clause, Some(Expr::BinaryOp { ref right, .. }) if matches!(**right, Expr::Placeholder(0))));
+                    }
+                    other => panic!("Expected IN subquery, got {:?}", other),
+                }
+                // Placeholders are numbered across the subquery and the outer query
+                assert!(matches!(*right, Expr::BinaryOp { ref right, .. } if matches!(**right, Expr::Placeholder(1))));
+            }
+            other => panic!("Expected AND, got {:?}", other),
+        },
+        _ => panic!("Expected SELECT"),
+    }
+    assert!(Parser::parse("SELECT * FROM t WHERE a IN (SELECT id FROM u").is_err());
+}
+
+#[test]
+fn test_parse_group_concat_separator() {
+    match Parser::parse("SELECT GROUP_CONCAT(name SEPARATOR '; ') FROM t").unwrap() {
+        Statement::Select(s) => match &s.columns[0] {
+            SelectColumn::Expr { expr: Expr::FunctionCall { name, args, separator, .. }, .. } => {
+                assert_eq!(name, "GROUP_CONCAT");
+                assert_eq!(args.len(), 1);
+                assert_eq!(separator.as_deref(), Some("; "));
+            }
+            other => panic!("Expected function call, got {:?}", other),
+        },
+        _ => panic!("Expected SELECT"),
+    }
+}
+
+#[test]
+fn test_parse_not_like_escape() {
+    match Parser::parse("SELECT * FROM t WHERE name NOT LIKE 'a|%' ESCAPE '|'").unwrap() {
+        Statement::Select(s) => match s.where_clause.unwrap() {
+            Expr::Not(inner) => assert!(matches!(*inner, Expr::BinaryOp { op: BinaryOperator::Like { escape: Some('|') }, .. })),
+            other => panic!("Expected NOT, got {:?}", other),
+        },
+        _ => panic!("Expected SELECT"),
+    }
+    match Parser::parse("SELECT * FROM t WHERE name LIKE 'a%' ESCAPE ''").unwrap() {
+        Statement::Select(s) => assert!(matches!(s.where_clause, Some(Expr::BinaryOp { op: BinaryOperator::Like { escape: None }, .. }))),
+        _ => panic!("Expected SELECT"),
+    }
+}
+
+#[test]
+fn test_parse_order_by_nulls() {
+    match Parser::parse("SELECT a, b FROM t ORDER BY 2 DESC NULLS FIRST, a NULLS LAST, b").unwrap() {
+        Statement::Select(s) => {
+            assert!(matches!(s.order_by[0].expr, Expr::Literal(Value::Integer(2))));
+            assert_eq!(s.order_by[0].nulls, Some(NullsOrder::First));
+            assert_eq!(s.order_by[1].direction, SortOrder::Asc);
+            assert_eq!(s.order_by[1].nulls, Some(NullsOrder::Last));
+            assert_eq!(s.order_by[2].nulls, None);
+            assert!(s.order_by[2].nulls_first());
+        }
+        _ => panic!("Expected SELECT"),
+    }
+    assert!(Parser::parse("SELECT a FROM t ORDER BY a NULLS").is_err());
+}
+
+#[test]
+fn test_parse_count_distinct() {
+    match Parser::parse("SELECT COUNT(DISTINCT category), COUNT(category) FROM t").unwrap() {
+        Statement::Select(s) => {
+            assert!(matches!(&s.columns[0], SelectColumn::Expr { expr: Expr::FunctionCall { distinct: true, args, .. }, .. } if args.len() == 1));
+            assert!(matches!(&s.columns[1], SelectColumn::Expr { expr: Expr::FunctionCall { distinct: false, .. }, .. }));
+        }
+        _ => panic!("Expected SELECT"),
+    }
+    assert!(Parser::parse("SELECT COUNT(DISTINCT) FROM t").is_err());
+    assert!(Parser::parse("SELECT UPPER(DISTINCT name) FROM t").is_err());
+}
+
+#[test]
+fn test_parse_table_sample() {
+    match Parser::parse("SELECT * FROM events e TABLESAMPLE (12.5 PERCENT) REPEATABLE (7) WHERE id > 1").unwrap() {
+        Statement::Select(s) => {
+            let from = s.from.unwrap();
+            assert_eq!(from.alias.as_deref(), Some("e"));
+            assert_eq!(from.sample, Some(TableSample { size: SampleSize::Percent(12.5), seed: Some(7) }));
+            assert!(s.where_clause.is_some());
+        }
+        _ => panic!("Expected SELECT"),
+    }
+    match Parser::parse("SELECT * FROM events TABLESAMPLE (100 ROWS)").unwrap() {
+        Statement::Select(s) => {
+            let from = s.from.unwrap();
+            assert!(from.alias.is_none());
+            assert_eq!(from.sample, Some(TableSample { size: SampleSize::Rows(100), seed: None }));
+        }
+        _ => panic!("Expected SELECT"),
+    }
+}
+
+#[test]
+fn test_parse_scalar_subquery() {
+    match Parser::parse("SELECT name, (SELECT COUNT(*) FROM orders o WHERE o.user_id = u.id) AS n FROM users u").unwrap() {
+        Statement::Select(s) => match &s.columns[1] {
+            SelectColumn::Expr { expr: Expr::ScalarSubquery(subquery), alias } => {
+                assert_eq!(alias.as_deref(), Some("n"));
+                assert_eq!(subquery.from.as_ref().unwrap().alias.as_deref(), Some("o"));
+                assert!(subquery.where_clause.is_some());
+            }
+            other => panic!("Expected scalar subquery, got {:?}", other),
+        },
+        _ => panic!("Expected SELECT"),
+    }
+    // A parenthesized expression is still just an expression
+    match Parser::parse("SELECT (1 + 2) * 3").unwrap() {
+        Statement::Select(s) => assert!(matches!(&s.columns[0], SelectColumn::Expr { expr: Expr::BinaryOp { .. }, .. })),
+        _ => panic!("Expected SELECT"),
+    }
+}
+
 #[test]
 fn test_parse_json_access() {
     let stmt = Parser::parse("SELECT data->'name' FROM users").unwrap();
@@ -44,6 +307,20 @@ fn test_parse_json_access() {
     }
 }
 
+#[test]
+fn test_parse_cast() {
+    let stmt = Parser::parse("SELECT CAST(price AS SIGNED), CAST(id AS CHAR(4)), CAST('1.5' AS DECIMAL(10, 2)) FROM items").unwrap();
+    let Statement::Select(s) = stmt else { panic!("Expected SELECT") };
+    let targets: Vec<DataType> = s.columns.iter().map(|col| match col {
+        SelectColumn::Expr { expr: Expr::Cast { target, .. }, .. } => target.clone(),
+        other => panic!("Expected CAST, got {:?}", other),
+    }).collect();
+    assert_eq!(targets, vec![DataType::Integer, DataType::Varchar(Some(4)), DataType::Float]);
+
+    assert!(Parser::parse("SELECT CAST(1 INTEGER)").is_err());
+    assert!(Parser::parse("SELECT CAST(1 AS WIDGET)").is_err());
+}
+
 #[test]
 fn test_parse_inner_join() {
     let stmt = Parser::parse(
@@ -195,11 +472,136 @@ fn test_parse_delete() {
     }
 }
 
+#[test]
+fn test_parse_insert_on_duplicate_key_update() {
+    let sql = "INSERT INTO t (id, n) VALUES (1, 2) ON DUPLICATE KEY UPDATE n = n + VALUES(n), m = 0";
+    match Parser::parse(sql).unwrap() {
+        Statement::Insert(i) => {
+            assert_eq!(i.on_duplicate_key_update.len(), 2);
+            let (column, expr) = &i.on_duplicate_key_update[0];
+            assert_eq!(column, "n");
+            match expr {
+                Expr::BinaryOp { right, .. } => assert!(matches!(
+                    right.as_ref(),
+                    Expr::FunctionCall { name, args, .. } if name == "VALUES" && args.len() == 1
+                )),
+                other => panic!("Expected BinaryOp, got {:?}", other),
+            }
+        }
+        _ => panic!("Expected INSERT"),
+    }
+    match Parser::parse("INSERT INTO t VALUES (1)").unwrap() {
+        Statement::Insert(i) => assert!(i.on_duplicate_key_update.is_empty()),
+        _ => panic!("Expected INSERT"),
+    }
+    assert!(Parser::parse("INSERT INTO t VALUES (1) ON DUPLICATE UPDATE n = 1").is_err());
+}
+
+#[test]
+fn test_parse_load_data() {
+    match Parser::parse("LOAD DATA INFILE 'users.tsv' INTO TABLE users").unwrap() {
+        Statement::LoadData(load) => {
+            assert_eq!(load.file, "users.tsv");
+            assert_eq!(load.table_name, "users");
+            assert_eq!(load.field_separator, "\t");
+            assert_eq!(load.ignore_lines, 0);
+            assert!(load.columns.is_none());
+        }
+        _ => panic!("Expected LOAD DATA"),
+    }
+    let sql = "LOAD DATA INFILE 'u.csv' INTO TABLE users FIELDS TERMINATED BY ',' IGNORE 1 LINES (name, id)";
+    match Parser::parse(sql).unwrap() {
+        Statement::LoadData(load) => {
+            assert_eq!(load.field_separator, ",");
+            assert_eq!(load.ignore_lines, 1);
+            assert_eq!(load.columns, Some(vec!["name".to_string(), "id".to_string()]));
+        }
+        _ => panic!("Expected LOAD DATA"),
+    }
+    assert!(Parser::parse("LOAD DATA LOCAL INFILE 'u.csv' INTO TABLE users").is_err());
+    assert!(Parser::parse("LOAD DATA INFILE 'u.csv' INTO users").is_err());
+}
+
+#[test]
+fn test_parse_update_delete_limit() {
+    match Parser::parse("UPDATE users SET active = 0 WHERE age > 30 LIMIT 5").unwrap() {
+        Statement::Update(u) => assert_eq!(u.limit, Some(5)),
+        _ => panic!("Expected UPDATE"),
+    }
+    match Parser::parse("DELETE FROM users LIMIT 2").unwrap() {
+        Statement::Delete(d) => {
+            assert!(d.where_clause.is_none());
+            assert_eq!(d.limit, Some(2));
+        }
+        _ => panic!("Expected DELETE"),
+    }
+    match Parser::parse("DELETE FROM users WHERE id = 1").unwrap() {
+        Statement::Delete(d) => assert_eq!(d.limit, None),
+        _ => panic!("Expected DELETE"),
+    }
+    assert!(Parser::parse("DELETE FROM users LIMIT x").is_err());
+}
+
+#[test]
+fn test_parse_update_delete_order_by() {
+    match Parser::parse("DELETE FROM logs WHERE level = 'debug' ORDER BY created_at, id DESC LIMIT 10").unwrap() {
+        Statement::Delete(d) => {
+            assert_eq!(d.order_by.len(), 2);
+            assert_eq!(d.order_by[1].direction, SortOrder::Desc);
+            assert_eq!(d.limit, Some(10));
+        }
+        _ => panic!("Expected DELETE"),
+    }
+    match Parser::parse("UPDATE jobs SET state = 'run' ORDER BY priority DESC LIMIT 1").unwrap() {
+        Statement::Update(u) => {
+            assert_eq!(u.order_by.len(), 1);
+            assert_eq!(u.limit, Some(1));
+        }
+        _ => panic!("Expected UPDATE"),
+    }
+}
+
 #[test]
 fn test_parse_ddl() {
     assert!(matches!(Parser::parse("SHOW TABLES").unwrap(), Statement::ShowTables));
     assert!(matches!(Parser::parse("DESCRIBE users").unwrap(), Statement::Describe(t) if t == "users"));
-    assert!(matches!(Parser::parse("DROP TABLE users").unwrap(), Statement::DropTable(t) if t == "users"));
+    assert!(matches!(Parser::parse("SHOW CREATE TABLE users").unwrap(), Statement::ShowCreateTable(t) if t == "users"));
+    assert!(matches!(Parser::parse("SHOW INDEX FROM users").unwrap(), Statement::ShowIndex(t) if t == "users"));
+    assert!(matches!(Parser::parse("SHOW INDEXES IN users").unwrap(), Statement::ShowIndex(t) if t == "users"));
+    assert!(matches!(Parser::parse("SHOW KEYS FROM users").unwrap(), Statement::ShowIndex(t) if t == "users"));
+    assert!(matches!(Parser::parse("SHOW WARNINGS").unwrap(), Statement::ShowWarnings));
+    assert!(matches!(Parser::parse("DROP TABLE users").unwrap(), Statement::DropTable { name, if_exists: false } if name == "users"));
+}
+
+#[test]
+fn test_parse_if_exists_flags() {
+    match Parser::parse("CREATE TABLE IF NOT EXISTS users (id INTEGER)").unwrap() {
+        Statement::CreateTable(c) => assert!(c.if_not_exists),
+        _ => panic!("Expected CREATE TABLE"),
+    }
+    assert!(matches!(
+        Parser::parse("DROP TABLE IF EXISTS users;").unwrap(),
+        Statement::DropTable { name, if_exists: true } if name == "users"
+    ));
+    assert!(matches!(
+        Parser::parse("drop index if exists idx_email").unwrap(),
+        Statement::DropIndex { name, if_exists: true } if name == "idx_email"
+    ));
+    assert!(matches!(
+        Parser::parse("DROP INDEX idx_email").unwrap(),
+        Statement::DropIndex { if_exists: false, .. }
+    ));
+    assert!(Parser::parse("DROP TABLE IF users").is_err());
+}
+
+#[test]
+fn test_parse_explain() {
+    match Parser::parse("EXPLAIN SELECT * FROM users WHERE id = 1;").unwrap() {
+        Statement::Explain(inner) => assert!(matches!(*inner, Statement::Select(ref s) if s.where_clause.is_some())),
+        _ => panic!("Expected EXPLAIN"),
+    }
+    assert!(matches!(Parser::parse("explain users").unwrap(), Statement::Describe(t) if t == "users"));
+    assert!(Parser::parse("EXPLAIN DELETE FROM users").is_err());
 }
 
 #[test]
@@ -209,6 +611,179 @@ fn test_parse_transactions() {
     assert!(matches!(Parser::parse("ROLLBACK").unwrap(), Statement::Rollback));
 }
 
+#[test]
+fn test_parse_savepoints() {
+    assert!(matches!(Parser::parse("SAVEPOINT sp1").unwrap(), Statement::Savepoint(n) if n == "sp1"));
+    assert!(matches!(Parser::parse("ROLLBACK TO SAVEPOINT sp1").unwrap(), Statement::RollbackToSavepoint(n) if n == "sp1"));
+    assert!(matches!(Parser::parse("rollback work to sp1").unwrap(), Statement::RollbackToSavepoint(n) if n == "sp1"));
+    assert!(matches!(Parser::parse("RELEASE SAVEPOINT sp1").unwrap(), Statement::ReleaseSavepoint(n) if n == "sp1"));
+    assert!(Parser::parse("RELEASE sp1").is_err());
+    assert!(Parser::parse("SAVEPOINT").is_err());
+}
+
+#[test]
+fn test_parse_set_isolation_level() {
+    let cases = [
+        ("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ", IsolationLevel::RepeatableRead, IsolationScope::NextTransaction),
+        ("SET SESSION TRANSACTION ISOLATION LEVEL READ COMMITTED", IsolationLevel::ReadCommitted, IsolationScope::Session),
+        ("set local transaction isolation level serializable", IsolationLevel::Serializable, IsolationScope::Session),
+        ("SET SESSION transaction_isolation = 'REPEATABLE-READ'", IsolationLevel::RepeatableRead, IsolationScope::Session),
+        ("SET tx_isolation = 'read-uncommitted'", IsolationLevel::ReadUncommitted, IsolationScope::Session),
+    ];
+    for (sql, expected_level, expected_scope) in cases {
+        match Parser::parse(sql).unwrap() {
+            Statement::SetIsolationLevel { level, scope } => {
+                assert_eq!(level, expected_level, "{}", sql);
+                assert_eq!(scope, expected_scope, "{}", sql);
+            }
+            other => panic!("Expected SetIsolationLevel for {}, got {:?}", sql, other),
+        }
+    }
+    assert!(Parser::parse("SET TRANSACTION ISOLATION LEVEL READ SOMETHING").is_err());
+    assert!(Parser::parse("SET transaction_isolation = 'SNAPSHOT'").is_err());
+    assert!(Parser::parse("SET GLOBAL TRANSACTION ISOLATION LEVEL SERIALIZABLE").is_err());
+}
+
+#[test]
+fn test_parse_select_for_update() {
+    match Parser::parse("SELECT balance FROM accounts WHERE id = 1 LIMIT 1 FOR UPDATE").unwrap() {
+        Statement::Select(s) => {
+            assert!(s.for_update);
+            assert_eq!(s.limit, Some(1));
+        }
+        other => panic!("Expected SELECT, got {:?}", other),
+    }
+    match Parser::parse("SELECT * FROM accounts FOR UPDATE").unwrap() {
+        Statement::Select(s) => {
+            assert!(s.for_update);
+            assert!(s.from.unwrap().alias.is_none());
+        }
+        other => panic!("Expected SELECT, got {:?}", other),
+    }
+    match Parser::parse("SELECT * FROM accounts").unwrap() {
+        Statement::Select(s) => assert!(!s.for_update),
+        other => panic!("Expected SELECT, got {:?}", other),
+    }
+    assert!(Parser::parse("SELECT * FROM accounts FOR SHARE").is_err());
+}
+
+#[test]
+fn test_parse_set_user_variables() {
+    match Parser::parse("SET @total = 2 * 21, @Label = 'x'").unwrap() {
+        Statement::SetUserVariables(assignments) => {
+            assert_eq!(assignments.len(), 2);
+            assert_eq!(assignments[0].0, "total");
+            assert!(matches!(assignments[0].1, Expr::BinaryOp { .. }));
+            assert_eq!(assignments[1].0, "Label");
+            assert!(matches!(&assignments[1].1, Expr::Literal(Value::String(s)) if s == "x"));
+        }
+        other => panic!("Expected SetUserVariables, got {:?}", other),
+    }
+
+    match Parser::parse("SELECT id FROM t WHERE id > @min").unwrap() {
+        Statement::Select(s) => assert!(matches!(
+            s.where_clause,
+            Some(Expr::BinaryOp { ref right, .. }) if matches!(&**right, Expr::UserVariable(name) if name == "min")
+        )),
+        other => panic!("Expected SELECT, got {:?}", other),
+    }
+
+    assert!(Parser::parse("SET @ = 1").is_err());
+    assert!(Parser::parse("SET @x 1").is_err());
+}
+
+#[test]
+fn test_parse_set_sql_mode() {
+    match Parser::parse("SET SESSION sql_mode = 'ansi_quotes,STRICT_TRANS_TABLES'").unwrap() {
+        Statement::SetSqlMode(mode) => {
+            assert!(mode.ansi_quotes());
+            assert_eq!(mode.to_string(), "ANSI_QUOTES,STRICT_TRANS_TABLES");
+        }
+        other => panic!("Expected SetSqlMode, got {:?}", other),
+    }
+    assert!(matches!(
+        Parser::parse("SET sql_mode = DEFAULT").unwrap(),
+        Statement::SetSqlMode(mode) if mode == SqlMode::default()
+    ));
+
+    // A mode computed by an expression is accepted and left unchanged
+    for sql in [
+        "SET @@SESSION.sql_mode = CONCAT(CONCAT(@@sql_mode, ',STRICT_ALL_TABLES'), ',NO_AUTO_VALUE_ON_ZERO')",
+        "SET SESSION sql_mode = CONCAT(@@sql_mode, ',STRICT_TRANS_TABLES')",
+    ] {
+        assert!(matches!(Parser::parse(sql).unwrap(), Statement::SetVariables(s) if s.is_empty()), "{}", sql);
+    }
+    match Parser::parse("SET NAMES utf8mb4, sql_mode = 'ANSI_QUOTES'") {
+        Ok(Statement::SetSqlMode(mode)) => assert!(mode.ansi_quotes()),
+        other => panic!("Expected SetSqlMode, got {:?}", other),
+    }
+    assert!(Parser::parse("SET sql_mode = 5").is_err());
+
+    // The mode decides whether double quotes delimit a string or an identifier
+    let ansi = SqlMode::parse("ANSI_QUOTES");
+    match Parser::parse_with_mode("SELECT \"id\" FROM \"t\"", &ansi).unwrap() {
+        Statement::Select(s) => assert_eq!(s.from.unwrap().name, "t"),
+        other => panic!("Expected SELECT, got {:?}", other),
+    }
+    match Parser::parse("SELECT \"id\"").unwrap() {
+        Statement::Select(s) => assert!(matches!(
+            &s.columns[0],
+            SelectColumn::Expr { expr: Expr::Literal(Value::String(v)), .. } if v == "id"
+        )),
+        other => panic!("Expected SELECT, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_set_autocommit() {
+    let cases = [
+        ("SET autocommit = 0", false),
+        ("SET AUTOCOMMIT=1", true),
+        ("SET SESSION autocommit = OFF", false),
+        ("set autocommit = on", true),
+        ("SET autocommit = 'off'", false),
+        ("SET autocommit = TRUE", true),
+        ("SET autocommit = DEFAULT", true),
+        ("SET @@autocommit = 0", false),
+        ("SET @@session.autocommit = OFF", false),
+        ("SET @@LOCAL.autocommit = ON", true),
+    ];
+    for (sql, expected) in cases {
+        match Parser::parse(sql).unwrap() {
+            Statement::SetAutocommit(enabled) => assert_eq!(enabled, expected, "{}", sql),
+            other => panic!("Expected SetAutocommit for {}, got {:?}", sql, other),
+        }
+    }
+    let err = Parser::parse("SET autocommit = 2").unwrap_err();
+    assert_eq!(err.to_string(), "Variable 'autocommit' can't be set to the value of '2'");
+    assert!(Parser::parse("SET @@global.autocommit = 0").is_err());
+    assert!(Parser::parse("SET @@ = 0").is_err());
+}
+
+#[test]
+fn test_parse_set_variable_list() {
+    match Parser::parse("SET autocommit=0, sql_mode='ANSI_QUOTES', @x = 1").unwrap() {
+        Statement::SetVariables(statements) => {
+            assert_eq!(statements.len(), 3);
+            assert!(matches!(statements[0], Statement::SetAutocommit(false)));
+            assert!(matches!(&statements[1], Statement::SetSqlMode(mode) if mode.ansi_quotes()));
+            assert!(matches!(&statements[2], Statement::SetUserVariables(a) if a.len() == 1));
+        }
+        other => panic!("Expected SetVariables, got {:?}", other),
+    }
+
+    // Variables the server does not keep are accepted and ignored
+    assert!(matches!(
+        Parser::parse("SET NAMES utf8mb4 COLLATE utf8mb4_unicode_ci, @@session.sql_mode = ''").unwrap(),
+        Statement::SetSqlMode(mode) if mode.to_string().is_empty()
+    ));
+    assert!(matches!(
+        Parser::parse("SET NAMES utf8mb4, character_set_results = NULL").unwrap(),
+        Statement::SetVariables(statements) if statements.is_empty()
+    ));
+    assert!(Parser::parse("SET autocommit = 0,").is_err());
+}
+
 #[test]
 fn test_parse_count_star() {
     let stmt = Parser::parse("SELECT COUNT(*) FROM users").unwrap();
@@ -216,7 +791,7 @@ fn test_parse_count_star() {
         Statement::Select(s) => {
             assert_eq!(s.columns.len(), 1);
             match &s.columns[0] {
-                SelectColumn::Expr { expr: Expr::FunctionCall { name, args }, .. } => {
+                SelectColumn::Expr { expr: Expr::FunctionCall { name, args, .. }, .. } => {
                     assert_eq!(name.to_uppercase(), "COUNT");
                     assert!(args.is_empty(), "COUNT(*) should have empty args");
                 }
@@ -233,7 +808,7 @@ fn test_parse_count_column() {
     match stmt {
         Statement::Select(s) => {
             match &s.columns[0] {
-                SelectColumn::Expr { expr: Expr::FunctionCall { name, args }, .. } => {
+                SelectColumn::Expr { expr: Expr::FunctionCall { name, args, .. }, .. } => {
                     assert_eq!(name.to_uppercase(), "COUNT");
                     assert_eq!(args.len(), 1);
                 }
@@ -523,7 +1098,7 @@ fn test_backtick_quoted_column() {
 
 #[test]
 fn test_double_quoted_identifier() {
-    let stmt = Parser::parse(r#"SELECT * FROM "users""#).unwrap();
+    let stmt = Parser::parse_with_mode(r#"SELECT * FROM "users""#, &SqlMode::parse("ANSI_QUOTES")).unwrap();
     match stmt {
         Statement::Select(s) => {
             let from = s.from.as_ref().unwrap();
@@ -535,7 +1110,7 @@ fn test_double_quoted_identifier() {
 
 #[test]
 fn test_double_quoted_column() {
-    let stmt = Parser::parse(r#"SELECT "user name" FROM users"#).unwrap();
+    let stmt = Parser::parse_with_mode(r#"SELECT "user name" FROM users"#, &SqlMode::parse("ANSI_QUOTES")).unwrap();
     match stmt {
         Statement::Select(s) => {
             match &s.columns[0] {
@@ -758,3 +1333,103 @@ fn test_parse_qualified_star_with_table_alias() {
         _ => panic!("Expected SELECT"),
     }
 }
+
+#[test]
+fn test_parse_date_type_and_literal() {
+    match Parser::parse("CREATE TABLE t (id INT, date DATE)").unwrap() {
+        Statement::CreateTable(c) => {
+            assert_eq!(c.columns[1].name, "date");
+            assert_eq!(c.columns[1].data_type, DataType::Date);
+        }
+        _ => panic!("Expected CREATE TABLE"),
+    }
+    match Parser::parse("SELECT * FROM t WHERE date < DATE '2024-01-31'").unwrap() {
+        Statement::Select(s) => match s.where_clause.unwrap() {
+            Expr::BinaryOp { left, right, .. } => {
+                assert!(matches!(*left, Expr::Column { ref name, .. } if name == "date"));
+                assert!(matches!(*right, Expr::Literal(Value::Date(d)) if d.to_string() == "2024-01-31"));
+            }
+            other => panic!("Expected comparison, got {:?}", other),
+        },
+        _ => panic!("Expected SELECT"),
+    }
+    assert!(Parser::parse("SELECT DATE '2024-02-30'").is_err());
+}
+
+#[test]
+fn test_parse_blob_types() {
+    match Parser::parse("CREATE TABLE t (data BLOB, tag VARBINARY(16), raw VARBINARY)").unwrap() {
+        Statement::CreateTable(c) => {
+            assert!(c.columns.iter().all(|col| col.data_type == DataType::Blob));
+        }
+        _ => panic!("Expected CREATE TABLE"),
+    }
+}
+
+#[test]
+fn test_parse_unary_minus() {
+    let where_clause = |sql: &str| match Parser::parse(sql).unwrap() {
+        Statement::Select(s) => s.where_clause.unwrap(),
+        _ => panic!("Expected SELECT"),
+    };
+
+    // A minus before a number is part of the literal
+    match where_clause("SELECT * FROM t WHERE x = -3") {
+        Expr::BinaryOp { right, .. } => assert!(matches!(*right, Expr::Literal(Value::Integer(-3)))),
+        other => panic!("Expected comparison, got {:?}", other),
+    }
+
+    // `1 - -2` subtracts a negative literal
+    match where_clause("SELECT * FROM t WHERE x = 1 - -2") {
+        Expr::BinaryOp { right, .. } => match *right {
+            Expr::BinaryOp { left, op: BinaryOperator::Minus, right } => {
+                assert!(matches!(*left, Expr::Literal(Value::Integer(1))));
+                assert!(matches!(*right, Expr::Literal(Value::Integer(-2))));
+            }
+            other => panic!("Expected subtraction, got {:?}", other),
+        },
+        other => panic!("Expected comparison, got {:?}", other),
+    }
+
+    // Unary minus binds tighter than multiplication
+    match where_clause("SELECT * FROM t WHERE -balance * 2 > 0") {
+        Expr::BinaryOp { left, .. } => match *left {
+            Expr::BinaryOp { left, op: BinaryOperator::Multiply, .. } => {
+                assert!(matches!(*left, Expr::UnaryMinus(ref inner) if matches!(**inner, Expr::Column { ref name, .. } if name == "balance")));
+            }
+            other => panic!("Expected multiplication, got {:?}", other),
+        },
+        other => panic!("Expected comparison, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_error_positions() {
+    let err = Parser::parse("SELECT FROM users").unwrap_err().to_string();
+    assert!(err.contains("at position 8 near 'FROM'"), "{}", err);
+
+    // Positions count characters, not bytes, and point at the offending token
+    let err = Parser::parse("SELECT 1 FROM t WHERE é = = 1").unwrap_err().to_string();
+    assert!(err.contains("at position 27 near '='"), "{}", err);
+    let err = Parser::parse("INSERT INTO t VALUES `my col`").unwrap_err().to_string();
+    assert!(err.contains("at position 22 near '`my col`'"), "{}", err);
+    let err = Parser::parse("SET sql_mode = 1").unwrap_err().to_string();
+    assert!(err.contains("Expected a quoted sql_mode at position 16 near '1'"), "{}", err);
+
+    // Running out of input reports the position just past the end
+    let err = Parser::parse("SELECT * FROM").unwrap_err().to_string();
+    assert!(err.contains("at position 14"), "{}", err);
+}
+
+#[test]
+fn test_is_aggregate_function() {
+    assert!(is_aggregate_function("COUNT"));
+    assert!(is_aggregate_function("count"));
+    assert!(is_aggregate_function("SUM"));
+    assert!(is_aggregate_function("AVG"));
+    assert!(is_aggregate_function("MIN"));
+    assert!(is_aggregate_function("MAX"));
+    assert!(is_aggregate_function("GROUP_CONCAT"));
+    assert!(!is_aggregate_function("COALESCE"));
+    assert!(!is_aggregate_function("JSON_EXTRACT"));
+}
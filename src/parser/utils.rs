@@ -1,4 +1,4 @@
-use crate::error::MiniSqlError;
+use crate::error::{MiniSqlError, Result};
 use crate::lexer::Token;
 use super::Parser;
 
@@ -34,6 +34,8 @@ impl Parser {
         fn human_token(t: &Token) -> String {
             match t {
                 Token::Identifier(s) => format!("identifier '{}'", s),
+                Token::UserVariable(s) => format!("user variable '@{}'", s),
+                Token::SystemVariable(s) => format!("system variable '@@{}'", s),
                 Token::StringLiteral(s) => format!("string literal '{}'", s),
                 Token::NumberLiteral(n) => format!("number '{}'", n),
                 Token::Star => "'*'".into(),
@@ -59,7 +61,6 @@ impl Parser {
             }
         }
 
-        let found = human_token(self.peek());
         let prev = self.tokens.get(self.pos.saturating_sub(1)).map(|t| human_token(t)).unwrap_or_else(|| "start of input".into());
         let next = self.tokens.get(self.pos + 1).map(|t| human_token(t)).unwrap_or_else(|| "end of input".into());
 
@@ -72,15 +73,45 @@ impl Parser {
             String::new()
         };
 
-        MiniSqlError::Syntax(format!("{} (found {} at token position {}; prev: {}; next: {}){}",
-            msg, found, self.pos, prev, next, context_info
+        // Positions are 1-based character offsets into the statement text
+        let location = match self.spans.get(self.pos) {
+            Some(&(start, end)) if *self.peek() != Token::Eof => {
+                let near: String = self.sql.chars().skip(start).take(end - start).collect();
+                format!("at position {} near '{}'", start + 1, near)
+            }
+            _ => format!("at position {}", self.sql.chars().count() + 1),
+        };
+
+        MiniSqlError::Syntax(format!("{} {} (prev: {}; next: {}){}",
+            msg, location, prev, next, context_info
         ))
     }
 
+    /// Check whether the current token is a non-reserved keyword (lexed as an identifier)
+    pub(super) fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Identifier(s) if s.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Consume a non-reserved keyword (lexed as an identifier)
+    pub(super) fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.peek_keyword(keyword) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.error_with_context(format!(
+                "Expected {}, found {}",
+                keyword,
+                self.token_brief(self.peek())
+            )))
+        }
+    }
+
     /// Helper to produce a brief, human readable token description for error messages
     pub(super) fn token_brief(&self, t: &Token) -> String {
         match t {
             Token::Identifier(s) => format!("identifier '{}'", s),
+            Token::UserVariable(s) => format!("user variable '@{}'", s),
+            Token::SystemVariable(s) => format!("system variable '@@{}'", s),
             Token::StringLiteral(s) => format!("string literal '{}'", s),
             Token::NumberLiteral(n) => format!("number '{}'", n),
             Token::Star => "'*'".into(),
@@ -2,14 +2,67 @@
 
 use std::sync::Arc;
 
-use crate::error::Result;
-use crate::executor::{Executor, Session};
-use crate::parser::Parser;
+use tokio::sync::mpsc;
+
+use crate::digest::QueryDigest;
+use crate::error::{MiniSqlError, Result};
+use crate::executor::{Executor, RowSink, Session};
+use crate::parser::{Parser, SelectStmt, Statement};
 use crate::types::{DataType, QueryResult, ResultSet, Value};
 
-use super::packet::PacketIO;
+use super::packet::{PacketIO, MAX_ALLOWED_PACKET};
 use super::resultset::ResultSetSender;
 
+/// Rows buffered between the executor and the socket while streaming a SELECT
+const STREAM_BUFFER_ROWS: usize = 256;
+
+/// Run `run` on a blocking thread, with the session moved in and back out.
+/// A statement can wait for row locks another connection holds, which must
+/// not stall the runtime's worker thread.
+pub(super) async fn run_blocking<T: Send + 'static>(
+    executor: &Arc<Executor>,
+    session: &mut Session,
+    run: impl FnOnce(&Executor, &mut Session) -> Result<T> + Send + 'static,
+) -> Result<T> {
+    let executor = Arc::clone(executor);
+    let mut owned_session = std::mem::take(session);
+    let (owned_session, result) = tokio::task::spawn_blocking(move || {
+        let result = run(&executor, &mut owned_session);
+        (owned_session, result)
+    })
+    .await
+    .map_err(|e| MiniSqlError::Internal(format!("Statement task failed: {}", e)))?;
+    *session = owned_session;
+    result
+}
+
+/// Output of a streaming SELECT, passed from the executor thread to the connection
+enum StreamEvent {
+    Header(Vec<String>, Vec<DataType>),
+    Row(Vec<Value>),
+}
+
+/// Row sink that forwards rows to the connection task, blocking when it falls behind
+struct ChannelSink(mpsc::Sender<StreamEvent>);
+
+impl ChannelSink {
+    fn send(&self, event: StreamEvent) -> Result<()> {
+        self.0
+            .blocking_send(event)
+            .map_err(|_| MiniSqlError::Protocol("Connection stopped reading the result set".into()))
+    }
+}
+
+impl RowSink for ChannelSink {
+    fn begin(&mut self, columns: &[String], column_types: &[DataType]) -> Result<()> {
+        self.send(StreamEvent::Header(columns.to_vec(), column_types.to_vec()))
+    }
+
+    fn send_row(&mut self, row: Vec<Value>) -> Result<()> {
+        self.send(StreamEvent::Row(row))
+    }
+}
+
 /// Handles SQL query execution and special MySQL queries
 pub struct CommandHandler {
     executor: Arc<Executor>,
@@ -32,13 +85,23 @@ impl CommandHandler {
         sql: &str,
         session: &mut Session,
     ) -> Result<()> {
-        log::debug!("Query: {}", sql);
+        if log::log_enabled!(log::Level::Debug) {
+            let digest = QueryDigest::new(sql).to_string();
+            log::debug!(conn_id = session.connection_id, sql = sql, digest = digest.as_str(); "Query");
+        }
 
         // Handle some special queries that MySQL clients send
         let sql_upper = sql.trim().to_uppercase();
 
-        // Handle SET and SELECT variable queries
-        if sql_upper.starts_with("SET ") {
+        // Handle SET and SELECT variable queries. Only isolation-level, sql_mode,
+        // autocommit and user variable SETs are executed; other session
+        // variables are accepted and ignored.
+        let sets_user_variable = sql_upper.starts_with("SET @") && !sql_upper.starts_with("SET @@");
+        let executed_set = sql_upper.contains("ISOLATION")
+            || sql_upper.contains("SQL_MODE")
+            || sql_upper.contains("AUTOCOMMIT")
+            || sets_user_variable;
+        if sql_upper.starts_with("SET ") && !executed_set {
             self.result_sender
                 .send_ok(io, 0, 0, "", session.txn_id.is_some())
                 .await?;
@@ -54,8 +117,22 @@ impl CommandHandler {
         }
 
         // Parse and execute the SQL
-        let stmt = Parser::parse(sql)?;
-        let result = self.executor.execute(stmt, session)?;
+        let stmt = Parser::parse_with_mode(sql, &session.sql_mode)?;
+        if let Statement::Select(select) = stmt {
+            // Cacheable queries are buffered so their result can be stored
+            if let Some(key) = self.executor.query_cache_key(sql, session) {
+                let result = run_blocking(&self.executor, session, move |executor, session| {
+                    executor.execute_cached_select(&key, select, session)
+                })
+                .await?;
+                if let QueryResult::Select(result_set) = result {
+                    self.result_sender.send_result_set(io, &result_set).await?;
+                }
+                return Ok(());
+            }
+            return self.stream_select(io, select, session).await;
+        }
+        let result = run_blocking(&self.executor, session, move |executor, session| executor.execute(stmt, session)).await?;
 
         // Send result
         match result {
@@ -64,27 +141,27 @@ impl CommandHandler {
             }
             QueryResult::Modified { rows_affected, last_insert_id } => {
                 self.result_sender
-                    .send_ok(io, rows_affected, last_insert_id, "", session.txn_id.is_some())
+                    .send_ok_with_warnings(io, rows_affected, last_insert_id, "", session.txn_id.is_some(), session.warning_count())
                     .await?;
             }
             QueryResult::Ok => {
                 self.result_sender
-                    .send_ok(io, 0, 0, "", session.txn_id.is_some())
+                    .send_ok_with_warnings(io, 0, 0, "", session.txn_id.is_some(), session.warning_count())
                     .await?;
             }
             QueryResult::TransactionStarted => {
                 self.result_sender
-                    .send_ok(io, 0, 0, "", session.txn_id.is_some())
+                    .send_ok_with_warnings(io, 0, 0, "", session.txn_id.is_some(), session.warning_count())
                     .await?;
             }
             QueryResult::TransactionCommitted => {
                 self.result_sender
-                    .send_ok(io, 0, 0, "", session.txn_id.is_some())
+                    .send_ok_with_warnings(io, 0, 0, "", session.txn_id.is_some(), session.warning_count())
                     .await?;
             }
             QueryResult::TransactionRolledBack => {
                 self.result_sender
-                    .send_ok(io, 0, 0, "", session.txn_id.is_some())
+                    .send_ok_with_warnings(io, 0, 0, "", session.txn_id.is_some(), session.warning_count())
                     .await?;
             }
         }
@@ -92,6 +169,47 @@ impl CommandHandler {
         Ok(())
     }
 
+    /// Run a SELECT on a blocking thread and write each row packet as soon as
+    /// the executor produces it, instead of buffering the whole result set.
+    async fn stream_select(
+        &self,
+        io: &mut PacketIO,
+        select: SelectStmt,
+        session: &mut Session,
+    ) -> Result<()> {
+        let (tx, mut rx) = mpsc::channel(STREAM_BUFFER_ROWS);
+        let executor = Arc::clone(&self.executor);
+        let mut owned_session = std::mem::take(session);
+        let query = tokio::task::spawn_blocking(move || {
+            let result = executor.execute_select_streaming(select, &mut owned_session, &mut ChannelSink(tx));
+            (owned_session, result)
+        });
+
+        let mut write_result = Ok(());
+        while let Some(event) = rx.recv().await {
+            write_result = match event {
+                StreamEvent::Header(columns, types) => {
+                    self.result_sender.send_result_header(io, &columns, &types).await
+                }
+                StreamEvent::Row(row) => self.result_sender.send_row(io, &row).await,
+            };
+            if write_result.is_err() {
+                break;
+            }
+        }
+        // Closing the channel makes the executor stop at its next row
+        drop(rx);
+
+        let (owned_session, result) = query
+            .await
+            .map_err(|e| MiniSqlError::Internal(format!("SELECT task failed: {}", e)))?;
+        *session = owned_session;
+
+        write_result?;
+        result?;
+        self.result_sender.send_result_end(io).await
+    }
+
     /// Handle SELECT @@variable queries
     async fn handle_variable_query(
         &self,
@@ -109,16 +227,20 @@ impl CommandHandler {
             result.add_row(vec![Value::String("5.7.0-MiniSQL".to_string())]);
         } else if sql_lower.contains("autocommit") {
             result.columns = vec!["@@autocommit".to_string()];
-            let autocommit = if session.txn_id.is_none() { "1" } else { "0" };
+            let autocommit = if session.autocommit { "1" } else { "0" };
             result.add_row(vec![Value::String(autocommit.to_string())]);
+        } else if sql_lower.contains("isolation") {
+            result.columns = vec!["@@transaction_isolation".to_string()];
+            result.add_row(vec![Value::String(session.isolation_level.to_string())]);
         } else if sql_lower.contains("sql_mode") {
             result.columns = vec!["@@sql_mode".to_string()];
-            result.add_row(vec![Value::String(
-                "ONLY_FULL_GROUP_BY,STRICT_TRANS_TABLES".to_string(),
-            )]);
+            result.add_row(vec![Value::String(session.sql_mode.to_string())]);
+        } else if sql_lower.contains("warning_count") {
+            result.columns = vec!["@@warning_count".to_string()];
+            result.add_row(vec![Value::String(session.warning_count().to_string())]);
         } else if sql_lower.contains("max_allowed_packet") {
             result.columns = vec!["@@max_allowed_packet".to_string()];
-            result.add_row(vec![Value::String("67108864".to_string())]);
+            result.add_row(vec![Value::String(MAX_ALLOWED_PACKET.to_string())]);
         } else if sql_lower.contains("character_set") || sql_lower.contains("collation") {
             result.columns = vec!["@@character_set_client".to_string()];
             result.add_row(vec![Value::String("utf8mb4".to_string())]);
@@ -9,6 +9,7 @@ pub const CLIENT_LONG_FLAG: u32 = 4;
 pub const CLIENT_CONNECT_WITH_DB: u32 = 8;
 pub const CLIENT_NO_SCHEMA: u32 = 16;
 pub const CLIENT_PROTOCOL_41: u32 = 512;
+pub const CLIENT_SSL: u32 = 2048;
 pub const CLIENT_TRANSACTIONS: u32 = 8192;
 pub const CLIENT_SECURE_CONNECTION: u32 = 32768;
 pub const CLIENT_PLUGIN_AUTH: u32 = 0x00080000;
@@ -27,12 +28,19 @@ pub const COM_STMT_EXECUTE: u8 = 0x17;
 pub const COM_STMT_CLOSE: u8 = 0x19;
 pub const COM_STMT_RESET: u8 = 0x1A;
 pub const COM_SET_OPTION: u8 = 0x1B;
+pub const COM_RESET_CONNECTION: u8 = 0x1F;
 
 // Response types
 pub const OK_PACKET: u8 = 0x00;
+pub const AUTH_MORE_DATA: u8 = 0x01;
+pub const AUTH_SWITCH_REQUEST: u8 = 0xFE;
 pub const EOF_PACKET: u8 = 0xFE;
 pub const ERR_PACKET: u8 = 0xFF;
 
+// caching_sha2_password AuthMoreData payloads
+pub const FAST_AUTH_SUCCESS: u8 = 0x03;
+pub const PERFORM_FULL_AUTHENTICATION: u8 = 0x04;
+
 // Column types for result sets
 pub const MYSQL_TYPE_DECIMAL: u8 = 0x00;
 pub const MYSQL_TYPE_TINY: u8 = 0x01;
@@ -44,9 +52,19 @@ pub const MYSQL_TYPE_NULL: u8 = 0x06;
 pub const MYSQL_TYPE_TIMESTAMP: u8 = 0x07;
 pub const MYSQL_TYPE_LONGLONG: u8 = 0x08;
 pub const MYSQL_TYPE_INT24: u8 = 0x09;
+pub const MYSQL_TYPE_DATE: u8 = 0x0A;
+pub const MYSQL_TYPE_TIME: u8 = 0x0B;
+pub const MYSQL_TYPE_DATETIME: u8 = 0x0C;
+pub const MYSQL_TYPE_YEAR: u8 = 0x0D;
 pub const MYSQL_TYPE_VARCHAR: u8 = 0x0F;
 pub const MYSQL_TYPE_BIT: u8 = 0x10;
 pub const MYSQL_TYPE_JSON: u8 = 0xF5;
+pub const MYSQL_TYPE_NEWDECIMAL: u8 = 0xF6;
+pub const MYSQL_TYPE_ENUM: u8 = 0xF7;
+pub const MYSQL_TYPE_SET: u8 = 0xF8;
+pub const MYSQL_TYPE_TINY_BLOB: u8 = 0xF9;
+pub const MYSQL_TYPE_MEDIUM_BLOB: u8 = 0xFA;
+pub const MYSQL_TYPE_LONG_BLOB: u8 = 0xFB;
 pub const MYSQL_TYPE_BLOB: u8 = 0xFC;
 pub const MYSQL_TYPE_VAR_STRING: u8 = 0xFD;
 pub const MYSQL_TYPE_STRING: u8 = 0xFE;
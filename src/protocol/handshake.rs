@@ -1,7 +1,25 @@
 //! MySQL authentication and handshake handling
+//!
+//! Two auth plugins are supported; the greeting offers `caching_sha2_password`
+//! (the MySQL 8 default) and clients answering with another plugin are sent an
+//! auth switch request.
+//!
+//! - `mysql_native_password`: `SHA1(pw) XOR SHA1(nonce + SHA1(SHA1(pw)))`
+//! - `caching_sha2_password`: `SHA256(pw) XOR SHA256(SHA256(SHA256(pw)) + nonce)`
+//!
+//! The server holds the plaintext password, so a `caching_sha2_password`
+//! scramble can always be checked directly, the equivalent of MySQL's cache
+//! hit: the server answers "fast auth success" and then OK. When the scramble
+//! does not match, full authentication is requested, but only over TLS, where
+//! the client sends the password in cleartext. There is no RSA key exchange,
+//! so on a plaintext connection a mismatch is rejected outright.
+//!
+//! Who may log in is decided by an [`Authenticator`]. The default,
+//! [`PasswordAuthenticator`], accepts the single user and password of `Config`.
 
 use rand::Rng;
 use sha1::{Digest, Sha1};
+use sha2::Sha256;
 
 use crate::error::{MiniSqlError, Result};
 use crate::Config;
@@ -9,6 +27,109 @@ use crate::Config;
 use super::constants::*;
 use super::packet::{LenencInt, PacketIO};
 
+/// Authentication plugins understood by the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthPlugin {
+    NativePassword,
+    CachingSha2Password,
+}
+
+impl AuthPlugin {
+    /// Plugin name as sent on the wire
+    pub fn name(self) -> &'static str {
+        match self {
+            AuthPlugin::NativePassword => "mysql_native_password",
+            AuthPlugin::CachingSha2Password => "caching_sha2_password",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mysql_native_password" => Some(AuthPlugin::NativePassword),
+            "caching_sha2_password" => Some(AuthPlugin::CachingSha2Password),
+            _ => None,
+        }
+    }
+
+    /// The auth response a client sends for `password` and the server's `nonce`
+    pub fn scramble(self, password: &str, nonce: &[u8]) -> Vec<u8> {
+        match self {
+            AuthPlugin::NativePassword => compute_auth_response(password, nonce),
+            AuthPlugin::CachingSha2Password => compute_sha2_auth_response(password, nonce),
+        }
+    }
+}
+
+/// Decides which clients may log in. The handshake hands it the scramble the
+/// client computed over the server's challenge, so an implementation can keep
+/// its own users or consult an external service.
+pub trait Authenticator: Send + Sync {
+    /// Whether `user` may log in with `auth_response`, computed with `plugin`
+    /// over `challenge`. An empty response means the client sent no password.
+    fn verify(&self, user: &str, plugin: AuthPlugin, auth_response: &[u8], challenge: &[u8]) -> bool;
+
+    /// Whether `user` may log in with the cleartext `password` a client sends
+    /// for caching_sha2_password full authentication, which only happens over
+    /// TLS. Refused unless implemented.
+    fn verify_cleartext(&self, user: &str, password: &[u8]) -> bool {
+        let _ = (user, password);
+        false
+    }
+}
+
+/// The default [`Authenticator`]: one user with a fixed password
+#[derive(Debug, Clone)]
+pub struct PasswordAuthenticator {
+    username: String,
+    password: String,
+}
+
+impl PasswordAuthenticator {
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self { username: username.into(), password: password.into() }
+    }
+
+    /// The user and password of `config`
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.username.clone(), config.password.clone())
+    }
+}
+
+impl Authenticator for PasswordAuthenticator {
+    fn verify(&self, user: &str, plugin: AuthPlugin, auth_response: &[u8], challenge: &[u8]) -> bool {
+        if user != self.username {
+            return false;
+        }
+        match plugin {
+            // Without a configured password any mysql_native_password response is let in
+            AuthPlugin::NativePassword if self.password.is_empty() => true,
+            AuthPlugin::NativePassword => auth_response == plugin.scramble(&self.password, challenge),
+            AuthPlugin::CachingSha2Password if self.password.is_empty() => auth_response.is_empty(),
+            AuthPlugin::CachingSha2Password => auth_response == plugin.scramble(&self.password, challenge),
+        }
+    }
+
+    fn verify_cleartext(&self, user: &str, password: &[u8]) -> bool {
+        user == self.username && password == self.password.as_bytes()
+    }
+}
+
+/// MySQL's error for a refused login
+fn access_denied(user: &str, auth_response: &[u8]) -> MiniSqlError {
+    let using_password = if auth_response.is_empty() { "NO" } else { "YES" };
+    MiniSqlError::Auth(format!("Access denied for user '{}' (using password: {})", user, using_password))
+}
+
+/// Fields of the client's handshake response used for authentication
+#[derive(Debug, Clone)]
+pub struct HandshakeResponse {
+    pub capabilities: u32,
+    pub username: String,
+    pub auth_response: Vec<u8>,
+    /// Plugin the auth response was computed with (absent for pre-4.1 style clients)
+    pub auth_plugin: Option<String>,
+}
+
 /// Handles MySQL handshake and authentication
 pub struct HandshakeHandler {
     auth_data: [u8; 20],
@@ -22,8 +143,8 @@ impl HandshakeHandler {
         Self { auth_data }
     }
 
-    /// Send the initial handshake packet
-    pub async fn send_handshake(&self, io: &mut PacketIO) -> Result<()> {
+    /// Send the initial handshake packet, offering TLS when `tls` is set
+    pub async fn send_handshake(&self, io: &mut PacketIO, connection_id: u32, tls: bool) -> Result<()> {
         let mut packet = Vec::new();
 
         // Protocol version (10)
@@ -33,7 +154,7 @@ impl HandshakeHandler {
         packet.extend_from_slice(b"5.7.0-MiniSQL\0");
 
         // Connection ID (4 bytes, little-endian)
-        packet.extend_from_slice(&1u32.to_le_bytes());
+        packet.extend_from_slice(&connection_id.to_le_bytes());
 
         // Auth-plugin-data-part-1 (8 bytes)
         packet.extend_from_slice(&self.auth_data[0..8]);
@@ -42,7 +163,7 @@ impl HandshakeHandler {
         packet.push(0);
 
         // Capability flags (lower 2 bytes)
-        let capabilities: u32 = CLIENT_LONG_PASSWORD
+        let mut capabilities: u32 = CLIENT_LONG_PASSWORD
             | CLIENT_FOUND_ROWS
             | CLIENT_LONG_FLAG
             | CLIENT_CONNECT_WITH_DB
@@ -50,6 +171,9 @@ impl HandshakeHandler {
             | CLIENT_TRANSACTIONS
             | CLIENT_SECURE_CONNECTION
             | CLIENT_PLUGIN_AUTH;
+        if tls {
+            capabilities |= CLIENT_SSL;
+        }
         packet.extend_from_slice(&(capabilities as u16).to_le_bytes());
 
         // Character set (utf8mb4 = 45)
@@ -72,7 +196,8 @@ impl HandshakeHandler {
         packet.push(0);
 
         // Auth plugin name (null-terminated)
-        packet.extend_from_slice(b"mysql_native_password\0");
+        packet.extend_from_slice(AuthPlugin::CachingSha2Password.name().as_bytes());
+        packet.push(0);
 
         io.reset_sequence();
         io.write_packet(&packet).await?;
@@ -85,9 +210,9 @@ impl HandshakeHandler {
         self.auth_data
     }
 
-    /// Parse the client's handshake response and validate credentials
-    /// Returns the client capabilities on success
-    pub fn parse_and_validate(&self, data: &[u8], config: &Config) -> Result<u32> {
+    /// Parse the client's handshake response. The credentials in it are
+    /// checked by [`HandshakeHandler::authenticate`].
+    pub fn parse_and_validate(&self, data: &[u8]) -> Result<HandshakeResponse> {
         if data.len() < 32 {
             return Err(MiniSqlError::Protocol("Handshake response too short".into()));
         }
@@ -115,63 +240,114 @@ impl HandshakeHandler {
         let username = String::from_utf8_lossy(&data[pos..pos + username_end]).to_string();
         pos += username_end + 1;
 
+        let truncated = || MiniSqlError::Protocol("Truncated auth response".into());
+
         // Auth response
         let auth_response = if capabilities & CLIENT_PLUGIN_AUTH_LENENC_CLIENT_DATA != 0 {
             // Length-encoded auth data
             let (len, bytes_read) = LenencInt::read(&data[pos..])?;
             pos += bytes_read;
-            let auth = &data[pos..pos + len as usize];
-            // no need to advance `pos` here; it's not read after this branch
+            let auth = data.get(pos..pos + len as usize).ok_or_else(truncated)?;
+            pos += len as usize;
             auth.to_vec()
         } else if capabilities & CLIENT_SECURE_CONNECTION != 0 {
             // 1-byte length prefix
-            let len = data[pos] as usize;
+            let len = *data.get(pos).ok_or_else(truncated)? as usize;
             pos += 1;
-            let auth = &data[pos..pos + len];
-            // no need to advance `pos` here; it's not read after this branch
+            let auth = data.get(pos..pos + len).ok_or_else(truncated)?;
+            pos += len;
             auth.to_vec()
         } else {
             // Null-terminated
             let end = data[pos..].iter().position(|&b| b == 0).unwrap_or(0);
             let auth = &data[pos..pos + end];
-            // pos += end + 1;  // Not needed as we don't use pos after this
+            pos += end + 1;
             auth.to_vec()
         };
 
-        // Validate credentials
-        if username != config.username {
-            return Err(MiniSqlError::Auth(format!(
-                "Access denied for user '{}'",
-                username
-            )));
+        // Database (null-terminated), then the auth plugin name
+        if capabilities & CLIENT_CONNECT_WITH_DB != 0 && pos < data.len() {
+            pos += data[pos..].iter().position(|&b| b == 0).map_or(data.len() - pos, |end| end + 1);
         }
+        let auth_plugin = (capabilities & CLIENT_PLUGIN_AUTH != 0 && pos < data.len())
+            .then(|| {
+                let end = data[pos..].iter().position(|&b| b == 0).unwrap_or(data.len() - pos);
+                String::from_utf8_lossy(&data[pos..pos + end]).to_string()
+            })
+            .filter(|name| !name.is_empty());
+
+        Ok(HandshakeResponse { capabilities, username, auth_response, auth_plugin })
+    }
+
+    /// Verify the client's auth response, running any extra exchanges the
+    /// plugin needs (auth switch, fast auth, full auth). Returns the client
+    /// capabilities; the caller sends the final OK packet.
+    pub async fn authenticate(
+        &self,
+        io: &mut PacketIO,
+        response: HandshakeResponse,
+        authenticator: &dyn Authenticator,
+    ) -> Result<u32> {
+        let HandshakeResponse { capabilities, username, mut auth_response, auth_plugin } = response;
+
+        let plugin = match auth_plugin.as_deref() {
+            None => AuthPlugin::NativePassword,
+            Some(name) => match AuthPlugin::from_name(name) {
+                Some(plugin) => plugin,
+                None => {
+                    // Ask the client to redo the exchange with our default plugin
+                    let plugin = AuthPlugin::CachingSha2Password;
+                    let mut switch = vec![AUTH_SWITCH_REQUEST];
+                    switch.extend_from_slice(plugin.name().as_bytes());
+                    switch.push(0);
+                    switch.extend_from_slice(&self.auth_data);
+                    switch.push(0);
+                    io.write_packet(&switch).await?;
+                    auth_response = io.read_packet().await?;
+                    plugin
+                }
+            },
+        };
 
-        // Verify password if auth_response is not empty
-        if !auth_response.is_empty() && !config.password.is_empty() {
-            let expected = compute_auth_response(&config.password, &self.auth_data);
-            if auth_response != expected {
-                return Err(MiniSqlError::Auth(format!(
-                    "Access denied for user '{}' (using password: YES)",
-                    username
-                )));
+        if authenticator.verify(&username, plugin, &auth_response, &self.auth_data) {
+            if plugin == AuthPlugin::CachingSha2Password && !auth_response.is_empty() {
+                io.write_packet(&[AUTH_MORE_DATA, FAST_AUTH_SUCCESS]).await?;
+            }
+        } else if plugin == AuthPlugin::CachingSha2Password && io.is_tls() {
+            io.write_packet(&[AUTH_MORE_DATA, PERFORM_FULL_AUTHENTICATION]).await?;
+            let packet = io.read_packet().await?;
+            let password = packet.split(|&b| b == 0).next().unwrap_or_default();
+            if !authenticator.verify_cleartext(&username, password) {
+                return Err(access_denied(&username, &auth_response));
             }
+        } else {
+            return Err(access_denied(&username, &auth_response));
         }
 
         Ok(capabilities)
     }
 }
 
+/// Whether a handshake response is an SSL request: the fixed 32-byte header
+/// (capabilities, max packet size, charset, filler) with `CLIENT_SSL` set and
+/// nothing after it
+pub fn is_ssl_request(data: &[u8]) -> bool {
+    data.len() == 32 && u32::from_le_bytes([data[0], data[1], data[2], data[3]]) & CLIENT_SSL != 0
+}
+
 /// Parse a COM_CHANGE_USER packet payload and validate credentials.
 ///
 /// The COM_CHANGE_USER payload contains: <user NUL><auth_response>...<database NUL>...[optional fields]
 /// This function uses the provided `client_capabilities` to know how the auth_response is encoded
-/// and the `challenge` (from the original handshake) to validate mysql_native_password responses.
+/// and the `challenge` (from the original handshake) to validate the scramble of the auth plugin
+/// named in the trailing fields (mysql_native_password when absent). Returns the new user and
+/// whether the client awaits a caching_sha2_password fast-auth success before the OK.
 pub fn parse_and_validate_change_user(
     data: &[u8],
     client_capabilities: u32,
     challenge: &[u8; 20],
-    config: &Config,
-) -> Result<u32> {
+    authenticator: &dyn Authenticator,
+) -> Result<(String, bool)> {
     let mut pos = 0usize;
 
     // username (NUL-terminated)
@@ -220,35 +396,32 @@ pub fn parse_and_validate_change_user(
             .iter()
             .position(|&b| b == 0)
             .ok_or_else(|| MiniSqlError::Protocol("Invalid database in COM_CHANGE_USER".into()))?;
-    let db = String::from_utf8_lossy(&data[pos..pos + db_end]).to_string();
-    // advancing `pos` here is unnecessary because it's not used after parsing the DB name
+        let db = String::from_utf8_lossy(&data[pos..pos + db_end]).to_string();
+        pos += db_end + 1;
         db
     } else {
         String::new()
     };
 
-    // Validate username
-    if username != config.username {
-        return Err(MiniSqlError::Auth(format!(
-            "Access denied for user '{}'",
-            username
-        )));
-    }
+    // character set (2 bytes), then the auth plugin name (NUL-terminated)
+    pos += 2;
+    let plugin = if client_capabilities & CLIENT_PLUGIN_AUTH != 0 && pos < data.len() {
+        let end = data[pos..].iter().position(|&b| b == 0).unwrap_or(data.len() - pos);
+        let name = String::from_utf8_lossy(&data[pos..pos + end]);
+        AuthPlugin::from_name(&name)
+            .ok_or_else(|| MiniSqlError::Protocol(format!("Unsupported auth plugin '{}' in COM_CHANGE_USER", name)))?
+    } else {
+        AuthPlugin::NativePassword
+    };
 
-    // If auth response present and server has password configured, validate
-    if !auth_response.is_empty() && !config.password.is_empty() {
-        let expected = compute_auth_response(&config.password, challenge);
-        if auth_response != expected {
-            return Err(MiniSqlError::Auth(format!(
-                "Access denied for user '{}' (using password: YES)",
-                username
-            )));
-        }
+    if !authenticator.verify(&username, plugin, &auth_response, challenge) {
+        return Err(access_denied(&username, &auth_response));
     }
 
-    // For now, we don't process optional trailing fields (charset, plugin name, attrs)
+    // Connection attributes after the plugin name are ignored
 
-    Ok(client_capabilities)
+    let fast_auth = plugin == AuthPlugin::CachingSha2Password && !auth_response.is_empty();
+    Ok((username, fast_auth))
 }
 
 /// Compute the expected auth response for mysql_native_password
@@ -278,6 +451,24 @@ fn compute_auth_response(password: &str, challenge: &[u8]) -> Vec<u8> {
         .collect()
 }
 
+/// Compute the expected auth response for caching_sha2_password
+/// SHA256(password) XOR SHA256(SHA256(SHA256(password)) + challenge)
+fn compute_sha2_auth_response(password: &str, challenge: &[u8]) -> Vec<u8> {
+    let stage1 = Sha256::digest(password.as_bytes());
+    let stage2 = Sha256::digest(stage1);
+
+    let mut hasher = Sha256::new();
+    hasher.update(stage2);
+    hasher.update(challenge);
+    let stage3 = hasher.finalize();
+
+    stage1
+        .iter()
+        .zip(stage3.iter())
+        .map(|(a, b)| a ^ b)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -319,6 +510,44 @@ mod tests {
         assert_ne!(response1, response2);
     }
 
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_compute_sha2_auth_response_known_password() {
+        let challenge = b"12345678901234567890";
+        let response = AuthPlugin::CachingSha2Password.scramble("secret", challenge);
+        assert_eq!(response, hex("51ecd6dedbd34d5445c0a190d4f51acf0d23b94db66c91f3f789faa9193751cd"));
+        assert_ne!(response, AuthPlugin::CachingSha2Password.scramble("Secret", challenge));
+
+        // mysql_native_password for the same inputs, for comparison
+        assert_eq!(
+            AuthPlugin::NativePassword.scramble("secret", challenge),
+            hex("0f8b9033e0897c0a8338ebe3dea9010dda47ab56")
+        );
+    }
+
+    #[test]
+    fn test_parse_handshake_response_auth_plugin() {
+        let handler = HandshakeHandler::new();
+        let config = Config::default();
+        let caps = CLIENT_PROTOCOL_41 | CLIENT_SECURE_CONNECTION | CLIENT_PLUGIN_AUTH | CLIENT_CONNECT_WITH_DB;
+        let scramble = AuthPlugin::CachingSha2Password.scramble(&config.password, &handler.auth_data);
+
+        let mut data = vec![0u8; 32];
+        data[0..4].copy_from_slice(&caps.to_le_bytes());
+        data.extend_from_slice(b"root\0");
+        data.push(scramble.len() as u8);
+        data.extend_from_slice(&scramble);
+        data.extend_from_slice(b"mydb\0caching_sha2_password\0");
+
+        let response = handler.parse_and_validate(&data).unwrap();
+        assert_eq!(response.username, "root");
+        assert_eq!(response.auth_response, scramble);
+        assert_eq!(response.auth_plugin.as_deref(), Some("caching_sha2_password"));
+    }
+
     #[test]
     fn test_handshake_handler_creation() {
         let handler1 = HandshakeHandler::new();
@@ -331,32 +560,41 @@ mod tests {
     #[test]
     fn test_parse_handshake_response_too_short() {
         let handler = HandshakeHandler::new();
-        let config = Config::default();
         let short_data = vec![0u8; 10];
         
-        let result = handler.parse_and_validate(&short_data, &config);
+        let result = handler.parse_and_validate(&short_data);
         assert!(result.is_err());
     }
 
     #[test]
-    fn test_parse_handshake_response_wrong_username() {
-        let handler = HandshakeHandler::new();
-        let mut config = Config::default();
-        config.username = "correct_user".to_string();
-        
-        // Build minimal valid handshake response with wrong username
-        let mut data = vec![0u8; 32];
-        // Capabilities
-        data[0..4].copy_from_slice(&0u32.to_le_bytes());
-        // Username at position 32
-        data.extend_from_slice(b"wrong_user\0");
-        // Auth response (empty)
-        data.push(0);
-        
-        let result = handler.parse_and_validate(&data, &config);
-        assert!(result.is_err());
-        if let Err(e) = result {
-            assert!(e.to_string().contains("Access denied"));
+    fn test_password_authenticator_wrong_username() {
+        let authenticator = PasswordAuthenticator::new("correct_user", "secret");
+        let challenge = b"12345678901234567890";
+        for plugin in [AuthPlugin::NativePassword, AuthPlugin::CachingSha2Password] {
+            let scramble = plugin.scramble("secret", challenge);
+            assert!(authenticator.verify("correct_user", plugin, &scramble, challenge));
+            assert!(!authenticator.verify("wrong_user", plugin, &scramble, challenge));
+            assert!(!authenticator.verify("correct_user", plugin, &plugin.scramble("other", challenge), challenge));
+        }
+        assert!(authenticator.verify_cleartext("correct_user", b"secret"));
+        assert!(!authenticator.verify_cleartext("wrong_user", b"secret"));
+        assert_eq!(
+            access_denied("wrong_user", &[]).to_string(),
+            "Access denied for user 'wrong_user' (using password: NO)"
+        );
+    }
+
+    #[test]
+    fn test_password_authenticator_rejects_empty_response() {
+        let authenticator = PasswordAuthenticator::new("root", "secret");
+        let challenge = b"12345678901234567890";
+        for plugin in [AuthPlugin::NativePassword, AuthPlugin::CachingSha2Password] {
+            assert!(!authenticator.verify("root", plugin, &[], challenge));
+        }
+
+        let no_password = PasswordAuthenticator::new("root", "");
+        for plugin in [AuthPlugin::NativePassword, AuthPlugin::CachingSha2Password] {
+            assert!(no_password.verify("root", plugin, &[], challenge));
         }
     }
 }
@@ -25,14 +25,21 @@
 //! - `resultset`: Result set formatting and sending
 //! - `commands`: Query execution and special query handlers
 //! - `prepared`: Prepared statement handling and binary protocol
+//! - `tls`: TLS upgrade of client connections
+//! - `slow_log`: Formatting of slow query log entries
+//! - `replication`: Sandstone delta exchange between nodes, on its own port
 
 use std::io;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::net::TcpStream;
+use tokio::sync::watch;
+use tokio_rustls::TlsAcceptor;
 
+use crate::digest::QueryDigest;
 use crate::error::{MiniSqlError, Result};
 use crate::executor::{Executor, Session};
+use crate::parser::Statement;
 use crate::Config;
 
 mod constants;
@@ -41,12 +48,22 @@ mod handshake;
 mod resultset;
 mod commands;
 mod prepared;
+mod tls;
+mod slow_log;
+mod replication;
 
 use constants::*;
 use packet::PacketIO;
 use handshake::HandshakeHandler;
+pub use handshake::{AuthPlugin, Authenticator, PasswordAuthenticator};
 use commands::CommandHandler;
 use prepared::PreparedStatementHandler;
+use tls::ClientStream;
+
+pub use tls::load_tls_acceptor;
+pub use replication::{
+    serve_replication, sync_table, ReplicationClient, ReplicationRequest, ReplicationResponse,
+};
 
 // Re-export the build_select_metadata function for tests
 pub use prepared::build_select_metadata;
@@ -61,26 +78,70 @@ pub struct ConnectionHandler {
     prepared_handler: Option<PreparedStatementHandler>,
     // Store the original handshake challenge so we can validate COM_CHANGE_USER auth responses
     auth_challenge: [u8; 20],
+    /// Server shutdown signal; idle connections close when it flips to true
+    shutdown: Option<watch::Receiver<bool>>,
+    /// Offered to the client in the handshake when set
+    tls: Option<TlsAcceptor>,
+    /// Checks the credentials of the handshake and of COM_CHANGE_USER
+    authenticator: Arc<dyn Authenticator>,
 }
 
 impl ConnectionHandler {
     /// Create a new connection handler
     pub fn new(stream: TcpStream, executor: Arc<Executor>, config: Config) -> Self {
+        let mut session = Session::new();
+        session.statement_timeout_ms = config.statement_timeout_ms;
+        let authenticator = Arc::new(PasswordAuthenticator::from_config(&config));
         Self {
-            io: PacketIO::new(stream),
+            io: PacketIO::new(ClientStream::Plain(stream)),
             executor,
             config,
-            session: Session::new(),
+            session,
             command_handler: None,
             prepared_handler: None,
             auth_challenge: [0u8; 20],
+            shutdown: None,
+            tls: None,
+            authenticator,
         }
     }
 
+    /// Set the server-assigned connection ID (reported in the handshake and logs)
+    pub fn with_connection_id(mut self, connection_id: u32) -> Self {
+        self.session.connection_id = connection_id;
+        self
+    }
+
+    /// Close the connection once it is idle after `shutdown` is set to true.
+    /// A connection with an open transaction keeps serving until it ends.
+    pub fn with_shutdown(mut self, shutdown: watch::Receiver<bool>) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Offer TLS to the client; clients that do not request it stay in plaintext
+    pub fn with_tls(mut self, acceptor: TlsAcceptor) -> Self {
+        self.tls = Some(acceptor);
+        self
+    }
+
+    /// Check credentials with `authenticator` instead of the user and password of `Config`
+    pub fn with_authenticator(mut self, authenticator: Arc<dyn Authenticator>) -> Self {
+        self.authenticator = authenticator;
+        self
+    }
+
     /// Run the connection handler (main loop)
     pub async fn run(mut self) -> Result<()> {
-        // Perform handshake
-        let client_capabilities = self.do_handshake().await?;
+        // Perform handshake; a rejected login gets an ERR packet before the connection closes
+        let client_capabilities = match self.do_handshake().await {
+            Ok(caps) => caps,
+            Err(e @ MiniSqlError::Auth(_)) => {
+                let _ = self.send_error(&e).await;
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
 
         // Initialize handlers with client capabilities
         self.command_handler = Some(CommandHandler::new(
@@ -93,22 +154,36 @@ impl ConnectionHandler {
         ));
 
         // Command loop
-        loop {
-            match self.handle_command().await {
+        let result = loop {
+            let packet = match self.read_command().await {
+                Ok(Some(p)) => p,
+                Ok(None) => break Ok(()), // Client quit or server shutting down
+                Err(e) => break Err(e),
+            };
+
+            match self.handle_command(packet).await {
                 Ok(true) => continue,
-                Ok(false) => break, // Client quit
+                Ok(false) => break Ok(()), // Client quit
                 Err(e) => {
-                    log::error!("Command error: {}", e);
+                    log::error!(conn_id = self.session.connection_id; "Command error: {}", e);
                     // Try to send error to client
                     if let Err(send_err) = self.send_error(&e).await {
-                        log::error!("Failed to send error: {}", send_err);
-                        break;
+                        log::error!(conn_id = self.session.connection_id; "Failed to send error: {}", send_err);
+                        break Ok(());
                     }
                 }
             }
-        }
+        };
+
+        // A transaction left open by the client is rolled back, releasing its row locks
+        self.rollback_open_transaction();
+        result
+    }
 
-        Ok(())
+    /// Refuse the connection before the handshake: send `error` as the first
+    /// packet and close, as MySQL does when `max_connections` is reached
+    pub async fn reject(mut self, error: &MiniSqlError) -> Result<()> {
+        self.send_error(error).await
     }
 
     /// Perform the MySQL handshake
@@ -120,14 +195,24 @@ impl ConnectionHandler {
         let t0 = if profiling { Some(Instant::now()) } else { None };
 
         // Send server greeting
-        handshake.send_handshake(&mut self.io).await?;
+        handshake.send_handshake(&mut self.io, self.session.connection_id, self.tls.is_some()).await?;
 
         // Receive and validate client response
-        let response = self.io.read_packet().await?;
-        let client_capabilities = handshake.parse_and_validate(&response, &self.config)?;
+        let mut response = self.io.read_packet().await?;
+        if handshake::is_ssl_request(&response) {
+            let acceptor = self.tls.as_ref()
+                .ok_or_else(|| MiniSqlError::Protocol("Client requested SSL but TLS is not configured".into()))?;
+            self.io.upgrade_to_tls(acceptor).await?;
+            log::debug!(conn_id = self.session.connection_id; "Connection upgraded to TLS");
+            response = self.io.read_packet().await?;
+        }
+        let response = handshake.parse_and_validate(&response)?;
+        let username = response.username.clone();
+        let client_capabilities = handshake.authenticate(&mut self.io, response, self.authenticator.as_ref()).await?;
 
     // keep a copy of the auth challenge so COM_CHANGE_USER can validate client responses
     self.auth_challenge = handshake.auth_data();
+        self.session.user = Some(username);
 
         // Send OK packet to confirm authentication
         let result_sender = resultset::ResultSetSender::new(client_capabilities);
@@ -137,24 +222,46 @@ impl ConnectionHandler {
 
         if let Some(start) = t0 {
             let elapsed = start.elapsed();
-            log::info!("Handshake completed in {} ms", elapsed.as_millis());
+            log::info!(
+                conn_id = self.session.connection_id, time_ms = elapsed.as_millis() as u64;
+                "Handshake completed in {} ms", elapsed.as_millis()
+            );
         }
 
-        log::info!("Client authenticated successfully");
+        log::info!(
+            conn_id = self.session.connection_id, tls = self.io.is_tls();
+            "Client authenticated successfully"
+        );
         Ok(client_capabilities)
     }
 
-    /// Handle a single command from the client
-    /// Returns Ok(true) to continue, Ok(false) to quit
-    async fn handle_command(&mut self) -> Result<bool> {
-        let packet = match self.io.read_packet().await {
-            Ok(p) => p,
-            Err(MiniSqlError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
-                return Ok(false);
+    /// Wait for the next command packet from the client.
+    /// Returns Ok(None) if the client disconnected or the server is shutting down.
+    async fn read_command(&mut self) -> Result<Option<Vec<u8>>> {
+        let idle = self.session.txn_id.is_none();
+        let read = match self.shutdown.as_mut() {
+            Some(shutdown) if idle => {
+                tokio::select! {
+                    res = self.io.read_packet() => res,
+                    _ = shutdown.wait_for(|stop| *stop) => {
+                        log::info!(conn_id = self.session.connection_id; "Closing idle connection for server shutdown");
+                        return Ok(None);
+                    }
+                }
             }
-            Err(e) => return Err(e),
+            _ => self.io.read_packet().await,
         };
 
+        match read {
+            Ok(p) => Ok(Some(p)),
+            Err(MiniSqlError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Handle a single command from the client
+    /// Returns Ok(true) to continue, Ok(false) to quit
+    async fn handle_command(&mut self, packet: Vec<u8>) -> Result<bool> {
         if packet.is_empty() {
             return Ok(false);
         }
@@ -164,7 +271,7 @@ impl ConnectionHandler {
 
         match command {
             COM_QUIT => {
-                log::info!("Client sent QUIT");
+                log::info!(conn_id = self.session.connection_id; "Client sent QUIT");
                 Ok(false)
             }
             COM_PING => {
@@ -174,7 +281,7 @@ impl ConnectionHandler {
             COM_INIT_DB => {
                 // We ignore database selection (single-database system)
                 let db_name = String::from_utf8_lossy(data);
-                log::info!("Client selected database: {}", db_name);
+                log::info!(conn_id = self.session.connection_id; "Client selected database: {}", db_name);
                 self.send_ok(0, 0, "").await?;
                 Ok(true)
             }
@@ -182,21 +289,31 @@ impl ConnectionHandler {
                 let sql = String::from_utf8_lossy(data).to_string();
                 // Optionally measure query execution time per command
                 let profiling = std::env::var("MINISQL_PROFILE").map(|v| v != "0").unwrap_or(false);
+                let start = Instant::now();
+                self.session.take_read_stats();
+                let result = self.command_handler
+                    .as_ref()
+                    .unwrap()
+                    .handle_query(&mut self.io, &sql, &mut self.session)
+                    .await;
+                let elapsed = start.elapsed();
+                let stats = self.session.take_read_stats();
+                if let Some(entry) = slow_log::slow_query_entry(self.config.slow_query_ms, &sql, elapsed, stats) {
+                    log::warn!(
+                        conn_id = self.session.connection_id, time_ms = elapsed.as_millis() as u64,
+                        rows_examined = stats.rows_examined, index_used = stats.index_used;
+                        "{}", entry
+                    );
+                }
+                result?;
                 if profiling {
-                    let start = Instant::now();
-                    self.command_handler
-                        .as_ref()
-                        .unwrap()
-                        .handle_query(&mut self.io, &sql, &mut self.session)
-                        .await?;
-                    let elapsed = start.elapsed();
-                    log::info!("COM_QUERY finished: sql='{}' time_ms={}", sql, elapsed.as_millis());
-                } else {
-                    self.command_handler
-                        .as_ref()
-                        .unwrap()
-                        .handle_query(&mut self.io, &sql, &mut self.session)
-                        .await?;
+                    let digest = QueryDigest::new(&sql);
+                    let digest_hash = digest.to_string();
+                    log::info!(
+                        conn_id = self.session.connection_id, sql = sql.as_str(), time_ms = elapsed.as_millis() as u64,
+                        digest = digest_hash.as_str(), digest_text = digest.text.as_str();
+                        "COM_QUERY finished"
+                    );
                 }
                 Ok(true)
             }
@@ -212,14 +329,16 @@ impl ConnectionHandler {
                     data,
                     client_caps,
                     &self.auth_challenge,
-                    &self.config,
+                    self.authenticator.as_ref(),
                 ) {
-                    Ok(_) => {
+                    Ok((username, fast_auth)) => {
+                        if fast_auth {
+                            self.io.write_packet(&[AUTH_MORE_DATA, FAST_AUTH_SUCCESS]).await?;
+                        }
+
                         // Reset session-scoped state per COM_CHANGE_USER semantics
-                        self.session.prepared_statements.clear();
-                        self.session.next_stmt_id = 1;
-                        self.session.txn_id = None;
-                        self.session.last_insert_id = 0;
+                        self.reset_session();
+                        self.session.user = Some(username);
 
                         // Acknowledge success
                         self.send_ok(0, 0, "").await?;
@@ -232,6 +351,12 @@ impl ConnectionHandler {
 
                 Ok(true)
             }
+            COM_RESET_CONNECTION => {
+                // Like COM_CHANGE_USER, but keeps the authenticated user
+                self.reset_session();
+                self.send_ok(0, 0, "").await?;
+                Ok(true)
+            }
             COM_STMT_PREPARE => {
                 let sql = String::from_utf8_lossy(data).to_string();
                 self.prepared_handler
@@ -262,8 +387,26 @@ impl ConnectionHandler {
                 Ok(true)
             }
             COM_FIELD_LIST => {
-                // Not supported, send empty result
-                self.send_eof().await?;
+                // Payload: NUL-terminated table name, then a column wildcard we ignore
+                let table_end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+                let table = String::from_utf8_lossy(&data[..table_end]).to_string();
+                // The column list is what DESCRIBE shows, and needs the same privilege
+                let describe = Statement::Describe(table.clone());
+                let schema = self
+                    .executor
+                    .check_privileges(&describe, &self.session)
+                    .and_then(|_| self.executor.storage().get_schema(&table));
+                match schema {
+                    Ok(schema) => {
+                        self.command_handler
+                            .as_ref()
+                            .unwrap()
+                            .result_sender()
+                            .send_field_list(&mut self.io, &schema, self.session.txn_id.is_some())
+                            .await?;
+                    }
+                    Err(e) => self.send_error(&e).await?,
+                }
                 Ok(true)
             }
             COM_SET_OPTION => {
@@ -272,7 +415,7 @@ impl ConnectionHandler {
                 Ok(true)
             }
             _ => {
-                log::warn!("Unknown command: 0x{:02X}", command);
+                log::warn!(conn_id = self.session.connection_id; "Unknown command: 0x{:02X}", command);
                 self.send_error(&MiniSqlError::Protocol(format!(
                     "Unknown command: 0x{:02X}",
                     command
@@ -283,6 +426,26 @@ impl ConnectionHandler {
         }
     }
 
+    /// Return the session to its state right after login: roll back any open
+    /// transaction and drop prepared statements and session variables
+    fn reset_session(&mut self) {
+        self.rollback_open_transaction();
+        let mut session = Session::new();
+        session.connection_id = self.session.connection_id;
+        session.statement_timeout_ms = self.config.statement_timeout_ms;
+        session.user = self.session.user.take();
+        self.session = session;
+    }
+
+    /// Roll back the session's transaction, if one is open
+    fn rollback_open_transaction(&mut self) {
+        if self.session.txn_id.is_some() {
+            if let Err(e) = self.executor.execute(Statement::Rollback, &mut self.session) {
+                log::warn!(conn_id = self.session.connection_id; "Rollback of open transaction failed: {}", e);
+            }
+        }
+    }
+
     /// Send an OK packet
     async fn send_ok(&mut self, affected_rows: u64, last_insert_id: u64, info: &str) -> Result<()> {
         self.command_handler
@@ -299,16 +462,6 @@ impl ConnectionHandler {
             .await
     }
 
-    /// Send an EOF packet
-    async fn send_eof(&mut self) -> Result<()> {
-        self.command_handler
-            .as_ref()
-            .unwrap()
-            .result_sender()
-            .send_eof(&mut self.io, self.session.txn_id.is_some())
-            .await
-    }
-
     /// Send an error packet
     async fn send_error(&mut self, error: &MiniSqlError) -> Result<()> {
         let mut packet = Vec::new();
@@ -348,6 +501,7 @@ mod tests {
     use crate::parser::Parser;
     use crate::storage::StorageEngine;
     use crate::engines::TransactionManager;
+    use crate::types::{QueryResult, Value};
     use tempfile::tempdir;
 
     fn setup() -> (Arc<Executor>, Config) {
@@ -397,7 +551,11 @@ mod tests {
                     auto_increment: false,
                 },
             ],
-            auto_increment_counter: 1, engine_type: crate::engines::EngineType::default(),
+            auto_increment_counter: 1,
+            engine_type: crate::engines::EngineType::default(),
+            storage_format: crate::types::StorageFormat::default(),
+            foreign_keys: Vec::new(),
+            primary_key: Vec::new(),
         };
         (&*executor)
             .storage
@@ -419,9 +577,9 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn test_com_change_user_clears_prepared_statements() {
-        let (executor, mut cfg) = setup();
+    /// Serve one connection on a local port and log in to it with an empty
+    /// password, returning the client side and the server task
+    async fn connect_client(executor: &Arc<Executor>, mut cfg: Config) -> (PacketIO<TcpStream>, tokio::task::JoinHandle<()>) {
         // Allow empty password so we can avoid computing auth_response in the test
         cfg.password = "".to_string();
 
@@ -429,11 +587,10 @@ mod tests {
         let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
 
-        let exec_arc = Arc::clone(&executor);
-        let cfg_server = cfg.clone();
+        let exec_arc = Arc::clone(executor);
         let server_task = tokio::spawn(async move {
             let (stream, _) = listener.accept().await.expect("accept failed");
-            let handler = ConnectionHandler::new(stream, exec_arc, cfg_server);
+            let handler = ConnectionHandler::new(stream, exec_arc, cfg);
             // Run handler - it will return when client closes connection
             handler.run().await.expect("handler run failed");
         });
@@ -467,10 +624,15 @@ mod tests {
         let ok = client.read_packet().await.unwrap();
         assert_eq!(ok[0], OK_PACKET);
 
-        // Send COM_STMT_PREPARE to create a prepared statement in the session
+        (client, server_task)
+    }
+
+    /// Prepare `sql` and read packets until COM_STMT_PREPARE_OK, then drain
+    /// the column and parameter definitions that follow it
+    async fn prepare_statement(client: &mut PacketIO<TcpStream>, sql: &[u8]) {
         let mut prep = Vec::new();
         prep.push(COM_STMT_PREPARE);
-        prep.extend_from_slice(b"SELECT 1");
+        prep.extend_from_slice(sql);
         client.write_packet(&prep).await.unwrap();
 
         // Read response packets from server until we see COM_STMT_PREPARE_OK (0x00)
@@ -502,6 +664,26 @@ mod tests {
                 }
             }
         }
+    }
+
+    /// Execute prepared statement `stmt_id` with no parameters and return the first response packet
+    async fn execute_statement(client: &mut PacketIO<TcpStream>, stmt_id: u32) -> Vec<u8> {
+        let mut exec = Vec::new();
+        exec.push(COM_STMT_EXECUTE);
+        exec.extend_from_slice(&stmt_id.to_le_bytes()); // statement id
+        exec.push(0u8); // flags
+        exec.extend_from_slice(&1u32.to_le_bytes()); // iteration count
+        client.write_packet(&exec).await.unwrap();
+        client.read_packet().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_com_change_user_clears_prepared_statements() {
+        let (executor, cfg) = setup();
+        let (mut client, server_task) = connect_client(&executor, cfg).await;
+
+        // Create a prepared statement in the session
+        prepare_statement(&mut client, b"SELECT 1").await;
 
         // Now send COM_CHANGE_USER (0x11) with empty auth and empty database
         let mut change = Vec::new();
@@ -517,21 +699,298 @@ mod tests {
         let pkt2 = client.read_packet().await.unwrap();
         assert_eq!(pkt2[0], OK_PACKET);
 
-        // Attempt to execute the previously prepared statement id=1
-        let mut exec = Vec::new();
-        exec.push(COM_STMT_EXECUTE);
-        exec.extend_from_slice(&1u32.to_le_bytes()); // statement id 1
+        // Attempt to execute the previously prepared statement id=1;
+        // expect an ERR packet since prepared statements should have been cleared
+        let pkt3 = execute_statement(&mut client, 1).await;
+        assert_eq!(pkt3[0], ERR_PACKET);
+
+        // Close client and wait for server task to finish
+        drop(client);
+        // Give server a moment to exit gracefully
+        let _ = tokio::time::timeout(std::time::Duration::from_millis(200), server_task).await;
+    }
+
+    #[tokio::test]
+    async fn test_com_reset_connection_clears_session() {
+        let (executor, cfg) = setup();
+        let (mut client, server_task) = connect_client(&executor, cfg).await;
+
+        let mut begin = vec![COM_QUERY];
+        begin.extend_from_slice(b"BEGIN");
+        client.write_packet(&begin).await.unwrap();
+        assert_eq!(client.read_packet().await.unwrap()[0], OK_PACKET);
+        prepare_statement(&mut client, b"SELECT 1").await;
+
+        client.write_packet(&[COM_RESET_CONNECTION]).await.unwrap();
+        assert_eq!(client.read_packet().await.unwrap()[0], OK_PACKET);
+
+        // The prepared statement is gone
+        assert_eq!(execute_statement(&mut client, 1).await[0], ERR_PACKET);
+
+        // The transaction was rolled back, so there is nothing left to commit
+        let mut commit = vec![COM_QUERY];
+        commit.extend_from_slice(b"COMMIT");
+        client.write_packet(&commit).await.unwrap();
+        let err = client.read_packet().await.unwrap();
+        assert_eq!(err[0], ERR_PACKET);
+        assert!(String::from_utf8_lossy(&err).contains("No transaction in progress"));
+
+        // The connection stays authenticated and usable
+        client.write_packet(&[COM_PING]).await.unwrap();
+        assert_eq!(client.read_packet().await.unwrap()[0], OK_PACKET);
+
+        drop(client);
+        let _ = tokio::time::timeout(std::time::Duration::from_millis(200), server_task).await;
+    }
+
+    /// Run `sql` and return every packet of its result set
+    async fn query_packets(client: &mut PacketIO<TcpStream>, sql: &[u8]) -> Vec<Vec<u8>> {
+        let mut query = vec![COM_QUERY];
+        query.extend_from_slice(sql);
+        client.write_packet(&query).await.unwrap();
+
+        // Column count, column definitions and EOF, rows and the final EOF
+        let mut packets = vec![client.read_packet().await.unwrap()];
+        let mut eofs = 0;
+        while eofs < 2 {
+            let pkt = client.read_packet().await.unwrap();
+            if pkt[0] == 0xFE && pkt.len() < 9 {
+                eofs += 1;
+            }
+            packets.push(pkt);
+        }
+        packets
+    }
+
+    #[tokio::test]
+    async fn test_user_variables_over_the_wire() {
+        let (executor, cfg) = setup();
+        let (mut client, server_task) = connect_client(&executor, cfg).await;
+
+        let mut set = vec![COM_QUERY];
+        set.extend_from_slice(b"SET @answer = 42");
+        client.write_packet(&set).await.unwrap();
+        assert_eq!(client.read_packet().await.unwrap()[0], OK_PACKET);
+
+        // Text-protocol row: the value as a length-encoded string
+        let packets = query_packets(&mut client, b"SELECT @answer").await;
+        assert!(packets.contains(&vec![2, b'4', b'2']), "row with 42 not found in {:?}", packets);
+
+        // Session resets forget the variable
+        client.write_packet(&[COM_RESET_CONNECTION]).await.unwrap();
+        assert_eq!(client.read_packet().await.unwrap()[0], OK_PACKET);
+        let packets = query_packets(&mut client, b"SELECT @answer").await;
+        assert!(packets.contains(&vec![0xFB]), "NULL row not found in {:?}", packets);
+
+        drop(client);
+        let _ = tokio::time::timeout(std::time::Duration::from_millis(200), server_task).await;
+    }
+
+    #[tokio::test]
+    async fn test_com_field_list_sends_column_definitions() {
+        // `setup` drops its data directory, and CREATE TABLE needs one
+        let (_dir, executor, cfg) = setup_with_dir();
+        let (mut client, server_task) = connect_client(&executor, cfg).await;
+
+        let mut create = vec![COM_QUERY];
+        create.extend_from_slice(b"CREATE TABLE pets (id INT PRIMARY KEY, name TEXT, age INT DEFAULT 1)");
+        client.write_packet(&create).await.unwrap();
+        assert_eq!(client.read_packet().await.unwrap()[0], OK_PACKET);
+
+        client.write_packet(b"\x04pets\0%").await.unwrap();
+        let mut definitions = Vec::new();
+        loop {
+            let pkt = client.read_packet().await.unwrap();
+            if pkt[0] == EOF_PACKET && pkt.len() < 9 {
+                break;
+            }
+            definitions.push(pkt);
+        }
+        assert_eq!(definitions.len(), 3);
+
+        // Each definition names the table and column and ends with the default value
+        let expected = [(&b"id"[..], &[0xFB][..]), (b"name", &[0xFB]), (b"age", &[1, b'1'])];
+        for (pkt, (column, default)) in definitions.iter().zip(expected) {
+            let mut prefix = vec![3, b'd', b'e', b'f', 7];
+            prefix.extend_from_slice(b"minisql");
+            for field in [&b"pets"[..], b"pets", column, column] {
+                prefix.push(field.len() as u8);
+                prefix.extend_from_slice(field);
+            }
+            assert!(pkt.starts_with(&prefix), "{:?}", pkt);
+            assert!(pkt.ends_with(default), "{:?}", pkt);
+        }
+
+        client.write_packet(b"\x04missing\0").await.unwrap();
+        let err = client.read_packet().await.unwrap();
+        assert_eq!(err[0], ERR_PACKET);
+        assert!(String::from_utf8_lossy(&err).contains("missing"));
+
+        drop(client);
+        let _ = tokio::time::timeout(std::time::Duration::from_millis(200), server_task).await;
+    }
+
+    /// Execute prepared statement `stmt_id` with `params` as (MySQL type,
+    /// binary value) pairs and return the first response packet. NULL-typed
+    /// parameters are flagged in the NULL bitmap. With `bind_types` unset the
+    /// types are left out, reusing the previous ones.
+    async fn execute_with_params(
+        client: &mut PacketIO<TcpStream>,
+        stmt_id: u32,
+        params: &[(u8, Vec<u8>)],
+        bind_types: bool,
+    ) -> Vec<u8> {
+        let mut exec = vec![COM_STMT_EXECUTE];
+        exec.extend_from_slice(&stmt_id.to_le_bytes());
         exec.push(0u8); // flags
         exec.extend_from_slice(&1u32.to_le_bytes()); // iteration count
+        let mut null_bitmap = vec![0u8; params.len().div_ceil(8)];
+        for (i, (ty, _)) in params.iter().enumerate() {
+            if *ty == MYSQL_TYPE_NULL {
+                null_bitmap[i / 8] |= 1 << (i % 8);
+            }
+        }
+        exec.extend_from_slice(&null_bitmap);
+        exec.push(bind_types as u8);
+        if bind_types {
+            params.iter().for_each(|(ty, _)| exec.extend_from_slice(&[*ty, 0]));
+        }
+        params.iter().for_each(|(_, value)| exec.extend_from_slice(value));
         client.write_packet(&exec).await.unwrap();
+        client.read_packet().await.unwrap()
+    }
 
-        // Expect an ERR packet since prepared statements should have been cleared
-        let pkt3 = client.read_packet().await.unwrap();
-        assert_eq!(pkt3[0], ERR_PACKET);
+    fn longlong(n: i64) -> (u8, Vec<u8>) {
+        (MYSQL_TYPE_LONGLONG, n.to_le_bytes().to_vec())
+    }
+
+    fn var_string(s: &str) -> (u8, Vec<u8>) {
+        let mut bytes = vec![s.len() as u8];
+        bytes.extend_from_slice(s.as_bytes());
+        (MYSQL_TYPE_VAR_STRING, bytes)
+    }
+
+    fn double(f: f64) -> (u8, Vec<u8>) {
+        (MYSQL_TYPE_DOUBLE, f.to_le_bytes().to_vec())
+    }
+
+    fn null() -> (u8, Vec<u8>) {
+        (MYSQL_TYPE_NULL, Vec::new())
+    }
+
+    /// Executor and config over a data directory that outlives the test body
+    fn setup_with_dir() -> (tempfile::TempDir, Arc<Executor>, Config) {
+        let dir = tempdir().unwrap();
+        let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+        let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+        let executor = Arc::new(Executor::new(storage, txn_manager));
+        let cfg = Config { data_dir: dir.path().to_path_buf(), ..Config::default() };
+        (dir, executor, cfg)
+    }
+
+    #[tokio::test]
+    async fn test_prepared_ddl_insert_and_update() {
+        let (_dir, executor, cfg) = setup_with_dir();
+        let (mut client, server_task) = connect_client(&executor, cfg).await;
+
+        prepare_statement(&mut client, b"CREATE TABLE items (id INT PRIMARY KEY, name TEXT, qty INT)").await;
+        assert_eq!(execute_statement(&mut client, 1).await[0], OK_PACKET);
+
+        prepare_statement(&mut client, b"INSERT INTO items VALUES (?, ?, ?)").await;
+        let ok = execute_with_params(&mut client, 2, &[longlong(1), var_string("apple"), longlong(3)], true).await;
+        assert_eq!(ok[..2], [OK_PACKET, 1]);
+        // A second execute may leave out the types bound by the first
+        let ok = execute_with_params(&mut client, 2, &[longlong(2), var_string("pear"), longlong(5)], false).await;
+        assert_eq!(ok[..2], [OK_PACKET, 1]);
+
+        prepare_statement(&mut client, b"UPDATE items SET qty = qty + ? WHERE name = ?").await;
+        let ok = execute_with_params(&mut client, 3, &[longlong(10), var_string("apple")], true).await;
+        assert_eq!(ok[..2], [OK_PACKET, 1]);
+
+        match executor.execute_sql("SELECT id, name, qty FROM items ORDER BY id", &mut Session::new()).unwrap() {
+            QueryResult::Select(rs) => assert_eq!(rs.rows, vec![
+                vec![Value::Integer(1), Value::String("apple".into()), Value::Integer(13)],
+                vec![Value::Integer(2), Value::String("pear".into()), Value::Integer(5)],
+            ]),
+            other => panic!("Expected SELECT result, got {:?}", other),
+        }
+
+        drop(client);
+        let _ = tokio::time::timeout(std::time::Duration::from_millis(200), server_task).await;
+    }
+
+    #[tokio::test]
+    async fn test_prepared_binary_parameter_types() {
+        let (_dir, executor, cfg) = setup_with_dir();
+        executor.execute_sql(
+            "CREATE TABLE readings (id INT PRIMARY KEY, value FLOAT, label TEXT, note TEXT)",
+            &mut Session::new(),
+        ).unwrap();
+        let (mut client, server_task) = connect_client(&executor, cfg).await;
+
+        let big = 1i64 << 40;
+        prepare_statement(&mut client, b"INSERT INTO readings VALUES (?, ?, ?, ?)").await;
+        let rows = [
+            [longlong(big), double(2.75), var_string("alpha"), null()],
+            [longlong(-7), double(-0.5), var_string("beta"), var_string("x")],
+        ];
+        for row in &rows {
+            assert_eq!(execute_with_params(&mut client, 1, row, true).await[..2], [OK_PACKET, 1]);
+        }
+
+        // Parameters filter rows as well as supply values
+        prepare_statement(&mut client, b"DELETE FROM readings WHERE value = ?").await;
+        assert_eq!(execute_with_params(&mut client, 2, &[double(-0.5)], true).await[..2], [OK_PACKET, 1]);
+        prepare_statement(&mut client, b"UPDATE readings SET label = ? WHERE id = ? AND note IS NULL").await;
+        let ok = execute_with_params(&mut client, 3, &[var_string("gamma"), longlong(big)], true).await;
+        assert_eq!(ok[..2], [OK_PACKET, 1]);
+
+        match executor.execute_sql("SELECT * FROM readings", &mut Session::new()).unwrap() {
+            QueryResult::Select(rs) => assert_eq!(rs.rows, vec![vec![
+                Value::Integer(big),
+                Value::Float(2.75),
+                Value::String("gamma".into()),
+                Value::Null,
+            ]]),
+            other => panic!("Expected SELECT result, got {:?}", other),
+        }
+
+        drop(client);
+        let _ = tokio::time::timeout(std::time::Duration::from_millis(200), server_task).await;
+    }
+
+    #[tokio::test]
+    async fn test_prepared_execute_rejects_wrong_arguments() {
+        let (_dir, executor, cfg) = setup_with_dir();
+        executor.execute_sql("CREATE TABLE items (id INT PRIMARY KEY, name TEXT)", &mut Session::new()).unwrap();
+        let (mut client, server_task) = connect_client(&executor, cfg).await;
+
+        prepare_statement(&mut client, b"INSERT INTO items VALUES (?, ?)").await;
+        let ok = execute_with_params(&mut client, 1, &[longlong(1), var_string("a")], true).await;
+        assert_eq!(ok[0], OK_PACKET);
+        let too_few = execute_with_params(&mut client, 1, &[longlong(2)], false).await;
+        let too_many = execute_with_params(&mut client, 1, &[longlong(1), var_string("a"), longlong(2)], false).await;
+        for err in [too_few, too_many] {
+            assert_eq!(err[0], ERR_PACKET);
+            assert_eq!(u16::from_le_bytes([err[1], err[2]]), 1210);
+            assert!(String::from_utf8_lossy(&err).contains("Incorrect arguments to mysqld_stmt_execute"));
+        }
+
+        // A prepared statement holds exactly one statement
+        let mut prep = vec![COM_STMT_PREPARE];
+        prep.extend_from_slice(b"INSERT INTO items VALUES (9, 'x'); DROP TABLE items");
+        client.write_packet(&prep).await.unwrap();
+        let err = client.read_packet().await.unwrap();
+        assert_eq!(err[0], ERR_PACKET);
+        assert_eq!(u16::from_le_bytes([err[1], err[2]]), 1064);
+
+        // Only the valid execute wrote a row, and the table is still there
+        match executor.execute_sql("SELECT COUNT(*) FROM items", &mut Session::new()).unwrap() {
+            QueryResult::Select(rs) => assert_eq!(rs.rows, vec![vec![Value::Integer(1)]]),
+            other => panic!("Expected SELECT result, got {:?}", other),
+        }
 
-        // Close client and wait for server task to finish
         drop(client);
-        // Give server a moment to exit gracefully
         let _ = tokio::time::timeout(std::time::Duration::from_millis(200), server_task).await;
     }
 
@@ -560,7 +1019,11 @@ mod tests {
                     auto_increment: false,
                 },
             ],
-            auto_increment_counter: 1, engine_type: crate::engines::EngineType::default(),
+            auto_increment_counter: 1,
+            engine_type: crate::engines::EngineType::default(),
+            storage_format: crate::types::StorageFormat::default(),
+            foreign_keys: Vec::new(),
+            primary_key: Vec::new(),
         };
         (&*executor)
             .storage
@@ -601,7 +1064,11 @@ mod tests {
                     auto_increment: false,
                 },
             ],
-            auto_increment_counter: 1, engine_type: crate::engines::EngineType::default(),
+            auto_increment_counter: 1,
+            engine_type: crate::engines::EngineType::default(),
+            storage_format: crate::types::StorageFormat::default(),
+            foreign_keys: Vec::new(),
+            primary_key: Vec::new(),
         };
         (&*executor)
             .storage
@@ -617,6 +1084,26 @@ mod tests {
         } else {
             panic!("Not a select")
         }
+
+        // COALESCE and friends take the common type of their non-NULL arguments
+        let stmt = Parser::parse(
+            "SELECT COALESCE(value, 0), IFNULL(value, 1.5), COALESCE(NULL, value), NULLIF(value, 'x'), COALESCE(value, 'none'), \
+             GREATEST(value, 2), LEAST(value, 2.5) FROM items",
+        )
+        .unwrap();
+        if let crate::parser::Statement::Select(s) = stmt {
+            let (_count, _names, types) = build_select_metadata(&*executor, &s).unwrap();
+            use crate::types::DataType;
+            assert_eq!(
+                types,
+                vec![
+                    DataType::Integer, DataType::Float, DataType::Integer, DataType::Integer, DataType::Text,
+                    DataType::Integer, DataType::Float,
+                ]
+            );
+        } else {
+            panic!("Not a select")
+        }
     }
 
     #[test]
@@ -644,7 +1131,11 @@ mod tests {
                     auto_increment: false,
                 },
             ],
-            auto_increment_counter: 1, engine_type: crate::engines::EngineType::default(),
+            auto_increment_counter: 1,
+            engine_type: crate::engines::EngineType::default(),
+            storage_format: crate::types::StorageFormat::default(),
+            foreign_keys: Vec::new(),
+            primary_key: Vec::new(),
         };
         let t2_schema = crate::types::TableSchema {
             name: "t2".to_string(),
@@ -674,7 +1165,11 @@ mod tests {
                     auto_increment: false,
                 },
             ],
-            auto_increment_counter: 1, engine_type: crate::engines::EngineType::default(),
+            auto_increment_counter: 1,
+            engine_type: crate::engines::EngineType::default(),
+            storage_format: crate::types::StorageFormat::default(),
+            foreign_keys: Vec::new(),
+            primary_key: Vec::new(),
         };
         (&*executor)
             .storage
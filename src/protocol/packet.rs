@@ -2,20 +2,31 @@
 //!
 //! Handles reading/writing MySQL protocol packets and length-encoded values.
 
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_rustls::TlsAcceptor;
 
 use crate::error::{MiniSqlError, Result};
 
-/// Handles low-level packet I/O operations for MySQL protocol
-pub struct PacketIO {
-    stream: TcpStream,
+use super::tls::ClientStream;
+
+/// Largest payload a single packet can carry; longer payloads continue in
+/// the following packets
+pub const MAX_PACKET_PAYLOAD: usize = 0xFF_FFFF;
+
+/// Largest payload accepted from a client across continuation packets,
+/// reported as `@@max_allowed_packet`
+pub const MAX_ALLOWED_PACKET: usize = 64 * 1024 * 1024;
+
+/// Handles low-level packet I/O operations for MySQL protocol over any
+/// async byte stream (a plain socket, a TLS stream or either)
+pub struct PacketIO<S = ClientStream> {
+    stream: S,
     sequence_id: u8,
 }
 
-impl PacketIO {
+impl<S: AsyncRead + AsyncWrite + Unpin> PacketIO<S> {
     /// Create a new PacketIO instance
-    pub fn new(stream: TcpStream) -> Self {
+    pub fn new(stream: S) -> Self {
         Self {
             stream,
             sequence_id: 0,
@@ -40,49 +51,85 @@ impl PacketIO {
     }
 
     /// Read a packet from the client
-    /// Returns the payload bytes
+    /// Returns the payload bytes, joined across continuation packets, or an
+    /// error once they would exceed `MAX_ALLOWED_PACKET`
     pub async fn read_packet(&mut self) -> Result<Vec<u8>> {
-        // Read 4-byte header
-        let mut header = [0u8; 4];
-        self.stream.read_exact(&mut header).await?;
-
-        // Parse header
-        let payload_len = u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize;
-        // Store the client's sequence ID and increment for our next response
-        self.sequence_id = header[3].wrapping_add(1);
-
-        // Read payload
-        let mut payload = vec![0u8; payload_len];
-        self.stream.read_exact(&mut payload).await?;
+        let mut payload = Vec::new();
+        loop {
+            // Read 4-byte header
+            let mut header = [0u8; 4];
+            self.stream.read_exact(&mut header).await?;
+
+            // Parse header
+            let chunk_len = u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize;
+            // Store the client's sequence ID and increment for our next response
+            self.sequence_id = header[3].wrapping_add(1);
+
+            // Read payload
+            let start = payload.len();
+            if start + chunk_len > MAX_ALLOWED_PACKET {
+                return Err(MiniSqlError::packet_too_large());
+            }
+            payload.resize(start + chunk_len, 0);
+            self.stream.read_exact(&mut payload[start..]).await?;
 
-        Ok(payload)
+            // A full-size packet is continued by the next one
+            if chunk_len < MAX_PACKET_PAYLOAD {
+                return Ok(payload);
+            }
+        }
     }
 
-    /// Write a packet to the client
+    /// Write a packet to the client, splitting payloads of 16MB or more
+    /// into full-size packets followed by a shorter (possibly empty) one
     pub async fn write_packet(&mut self, payload: &[u8]) -> Result<()> {
-        let len = payload.len() as u32;
-        let header = [
-            (len & 0xFF) as u8,
-            ((len >> 8) & 0xFF) as u8,
-            ((len >> 16) & 0xFF) as u8,
-            self.sequence_id,
-        ];
-
-        self.stream.write_all(&header).await?;
-        self.stream.write_all(payload).await?;
-        self.stream.flush().await?;
+        let mut rest = payload;
+        loop {
+            let chunk = &rest[..rest.len().min(MAX_PACKET_PAYLOAD)];
+            let len = chunk.len() as u32;
+            let header = [
+                (len & 0xFF) as u8,
+                ((len >> 8) & 0xFF) as u8,
+                ((len >> 16) & 0xFF) as u8,
+                self.sequence_id,
+            ];
+
+            self.stream.write_all(&header).await?;
+            self.stream.write_all(chunk).await?;
+            self.sequence_id = self.sequence_id.wrapping_add(1);
+
+            rest = &rest[chunk.len()..];
+            if chunk.len() < MAX_PACKET_PAYLOAD {
+                break;
+            }
+        }
 
-        self.sequence_id = self.sequence_id.wrapping_add(1);
+        self.stream.flush().await?;
         Ok(())
     }
 
     /// Consume self and return the underlying stream
     #[allow(dead_code)]
-    pub fn into_stream(self) -> TcpStream {
+    pub fn into_stream(self) -> S {
         self.stream
     }
 }
 
+impl PacketIO<ClientStream> {
+    /// Switch the connection to TLS. Packets after the SSL request continue
+    /// the sequence, so the sequence ID is kept.
+    pub async fn upgrade_to_tls(&mut self, acceptor: &TlsAcceptor) -> Result<()> {
+        let stream = std::mem::replace(&mut self.stream, ClientStream::Closed);
+        self.stream = stream.upgrade(acceptor).await?;
+        Ok(())
+    }
+
+    /// Whether the connection is encrypted
+    pub fn is_tls(&self) -> bool {
+        self.stream.is_tls()
+    }
+}
+
 /// Length-encoded integer encoding/decoding utilities
 pub struct LenencInt;
 
@@ -147,8 +194,13 @@ pub struct LenencString;
 impl LenencString {
     /// Write a length-encoded string to a buffer
     pub fn write(buf: &mut Vec<u8>, s: &str) {
-        LenencInt::write(buf, s.len() as u64);
-        buf.extend_from_slice(s.as_bytes());
+        Self::write_bytes(buf, s.as_bytes());
+    }
+
+    /// Write length-encoded binary data to a buffer
+    pub fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+        LenencInt::write(buf, bytes.len() as u64);
+        buf.extend_from_slice(bytes);
     }
 
     /// Read a length-encoded string, returning (string, bytes_read)
@@ -171,6 +223,88 @@ impl LenencString {
 mod tests {
     use super::*;
 
+    /// Write `payload` as one logical packet, returning the raw bytes sent
+    async fn write_raw(payload: &[u8]) -> Vec<u8> {
+        let mut io = PacketIO::new(std::io::Cursor::new(Vec::new()));
+        io.write_packet(payload).await.unwrap();
+        io.into_stream().into_inner()
+    }
+
+    #[tokio::test]
+    async fn test_large_packet_round_trip() {
+        let payload: Vec<u8> = (0..20 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let raw = write_raw(&payload).await;
+
+        // One full-size packet, then the remainder with the next sequence ID
+        let rest = payload.len() - MAX_PACKET_PAYLOAD;
+        assert_eq!(raw.len(), payload.len() + 8);
+        assert_eq!(raw[..4], [0xFF, 0xFF, 0xFF, 0]);
+        let second = &raw[4 + MAX_PACKET_PAYLOAD..][..4];
+        assert_eq!(second, [(rest & 0xFF) as u8, (rest >> 8 & 0xFF) as u8, (rest >> 16) as u8, 1]);
+
+        let mut io = PacketIO::new(std::io::Cursor::new(raw));
+        assert!(io.read_packet().await.unwrap() == payload);
+        assert_eq!(io.sequence_id(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_exact_multiple_packet_ends_with_empty_packet() {
+        let payload = vec![7u8; MAX_PACKET_PAYLOAD];
+        let raw = write_raw(&payload).await;
+        assert_eq!(raw.len(), payload.len() + 8);
+        assert_eq!(raw[raw.len() - 4..], [0, 0, 0, 1]);
+
+        // Followed by an ordinary packet, which is read separately
+        let mut raw = raw;
+        raw.extend_from_slice(&[1, 0, 0, 2, 42]);
+        let mut io = PacketIO::new(std::io::Cursor::new(raw));
+        assert!(io.read_packet().await.unwrap() == payload);
+        assert_eq!(io.read_packet().await.unwrap(), vec![42]);
+    }
+
+    /// Full-size packets of zeros without end, as a client streaming an
+    /// unbounded payload would send
+    struct EndlessPacket {
+        sent: usize,
+    }
+
+    impl AsyncRead for EndlessPacket {
+        fn poll_read(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            let packet_len = 4 + MAX_PACKET_PAYLOAD;
+            let pos = self.sent % packet_len;
+            let n = if pos < 4 {
+                let header = [0xFF, 0xFF, 0xFF, (self.sent / packet_len) as u8];
+                let n = (4 - pos).min(buf.remaining());
+                buf.put_slice(&header[pos..pos + n]);
+                n
+            } else {
+                let n = (packet_len - pos).min(buf.remaining());
+                buf.initialize_unfilled_to(n).fill(0);
+                buf.advance(n);
+                n
+            };
+            self.get_mut().sent += n;
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_packet_over_max_allowed_is_rejected() {
+        let mut io = PacketIO::new(tokio::io::join(EndlessPacket { sent: 0 }, tokio::io::sink()));
+        let err = io.read_packet().await.unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1153);
+        assert_eq!(err.to_string(), "Got a packet bigger than 'max_allowed_packet' bytes");
+
+        // Stopped before reading the chunk that would cross the limit
+        let full_packets = MAX_ALLOWED_PACKET / MAX_PACKET_PAYLOAD;
+        let read = io.into_stream().into_inner().0.sent;
+        assert_eq!(read, full_packets * (4 + MAX_PACKET_PAYLOAD) + 4);
+    }
+
     #[test]
     fn test_lenenc_int_small() {
         let mut buf = Vec::new();
@@ -7,6 +7,7 @@ use crate::executor::{Executor, PreparedStatement, Session};
 use crate::parser::{self, Parser};
 use crate::types::{DataType, QueryResult, TableSchema, Value};
 
+use super::commands::run_blocking;
 use super::constants::*;
 use super::packet::{LenencInt, LenencString, PacketIO};
 use super::resultset::ResultSetSender;
@@ -33,10 +34,13 @@ impl PreparedStatementHandler {
         sql: &str,
         session: &mut Session,
     ) -> Result<()> {
-        log::debug!("Preparing statement: {}", sql);
+        log::debug!(conn_id = session.connection_id, sql = sql; "Preparing statement");
 
         // Parse the SQL and count placeholders
-        let (statement, param_count) = Parser::parse_prepared(sql)?;
+        let (statement, param_count) = Parser::parse_prepared_with_mode(sql, &session.sql_mode)?;
+        // The result metadata reveals the table's columns, so the statement's
+        // privileges are checked now as well as on every execution
+        self.executor.check_privileges(&statement, session)?;
 
         // Assign a statement ID
         let stmt_id = session.next_stmt_id;
@@ -56,6 +60,7 @@ impl PreparedStatementHandler {
             sql: sql.to_string(),
             statement,
             param_count,
+            param_types: Vec::new(),
             column_types: column_types.clone(),
             column_names: column_names.clone(),
         };
@@ -115,23 +120,23 @@ impl PreparedStatementHandler {
         let _flags = data[4]; // cursor flags, we ignore these
         let _iteration_count = u32::from_le_bytes([data[5], data[6], data[7], data[8]]);
 
-        log::debug!("Executing prepared statement {}", stmt_id);
+        log::debug!(conn_id = session.connection_id, stmt_id = stmt_id; "Executing prepared statement {}", stmt_id);
 
         // Get the prepared statement
         let prepared = session
             .prepared_statements
-            .get(&stmt_id)
+            .get_mut(&stmt_id)
             .ok_or_else(|| {
                 MiniSqlError::Protocol(format!("Unknown prepared statement ID: {}", stmt_id))
-            })?
-            .clone();
+            })?;
 
-        // Parse parameters if any
+        // Parse parameters if any, remembering the types they were bound with
         let params = if prepared.param_count > 0 {
-            parse_execute_params(data, prepared.param_count)?
+            parse_execute_params(data, prepared.param_count, &mut prepared.param_types)?
         } else {
             vec![]
         };
+        let prepared = prepared.clone();
 
         log::debug!("Parsed {} parameters: {:?}", params.len(), params);
 
@@ -139,7 +144,10 @@ impl PreparedStatementHandler {
         let stmt_with_params = substitute_statement_params(&prepared.statement, &params)?;
 
         // Execute the statement
-        let result = self.executor.execute(stmt_with_params, session)?;
+        let result = run_blocking(&self.executor, session, move |executor, session| {
+            executor.execute(stmt_with_params, session)
+        })
+        .await?;
 
         // Send result using binary protocol for prepared statements
         match result {
@@ -148,27 +156,27 @@ impl PreparedStatementHandler {
             }
             QueryResult::Modified { rows_affected, last_insert_id } => {
                 self.result_sender
-                    .send_ok(io, rows_affected, last_insert_id, "", session.txn_id.is_some())
+                    .send_ok_with_warnings(io, rows_affected, last_insert_id, "", session.txn_id.is_some(), session.warning_count())
                     .await?;
             }
             QueryResult::Ok => {
                 self.result_sender
-                    .send_ok(io, 0, 0, "", session.txn_id.is_some())
+                    .send_ok_with_warnings(io, 0, 0, "", session.txn_id.is_some(), session.warning_count())
                     .await?;
             }
             QueryResult::TransactionStarted => {
                 self.result_sender
-                    .send_ok(io, 0, 0, "", session.txn_id.is_some())
+                    .send_ok_with_warnings(io, 0, 0, "", session.txn_id.is_some(), session.warning_count())
                     .await?;
             }
             QueryResult::TransactionCommitted => {
                 self.result_sender
-                    .send_ok(io, 0, 0, "", session.txn_id.is_some())
+                    .send_ok_with_warnings(io, 0, 0, "", session.txn_id.is_some(), session.warning_count())
                     .await?;
             }
             QueryResult::TransactionRolledBack => {
                 self.result_sender
-                    .send_ok(io, 0, 0, "", session.txn_id.is_some())
+                    .send_ok_with_warnings(io, 0, 0, "", session.txn_id.is_some(), session.warning_count())
                     .await?;
             }
         }
@@ -263,7 +271,7 @@ impl PreparedStatementHandler {
 
         // Character set (2 bytes) - binary for numeric types, utf8mb4 for text
         let charset: u16 = match data_type {
-            DataType::Integer | DataType::Float | DataType::Boolean => 63, // binary
+            DataType::Integer | DataType::Float | DataType::Boolean | DataType::Date | DataType::Blob => 63, // binary
             DataType::Varchar(_) | DataType::Text => 45, // utf8mb4
             DataType::Json => 45, // utf8mb4
         };
@@ -278,6 +286,8 @@ impl PreparedStatementHandler {
             DataType::Text => 65535,
             DataType::Boolean => 1,
             DataType::Json => 1073741824,
+            DataType::Date => 10,
+            DataType::Blob => 65535,
         };
         packet.extend_from_slice(&col_len.to_le_bytes());
 
@@ -289,6 +299,8 @@ impl PreparedStatementHandler {
             DataType::Text => MYSQL_TYPE_BLOB,
             DataType::Boolean => MYSQL_TYPE_TINY,
             DataType::Json => MYSQL_TYPE_JSON,
+            DataType::Date => MYSQL_TYPE_DATE,
+            DataType::Blob => MYSQL_TYPE_BLOB,
         };
         packet.push(col_type);
 
@@ -297,7 +309,8 @@ impl PreparedStatementHandler {
             DataType::Integer => NUM_FLAG,
             DataType::Float => NUM_FLAG,
             DataType::Boolean => NUM_FLAG,
-            DataType::Varchar(_) | DataType::Text | DataType::Json => 0,
+            DataType::Varchar(_) | DataType::Text | DataType::Json | DataType::Date => 0,
+            DataType::Blob => BLOB_FLAG | BINARY_FLAG,
         };
         packet.extend_from_slice(&flags.to_le_bytes());
 
@@ -323,7 +336,7 @@ pub fn build_select_metadata(
             let table_alias = from.effective_name().to_string();
             (schema, table_alias)
         } else {
-            (TableSchema { name: "dual".to_string(), columns: Vec::new(), auto_increment_counter: 1, engine_type: crate::engines::EngineType::default() }, "dual".to_string())
+            (TableSchema { name: "dual".to_string(), columns: Vec::new(), auto_increment_counter: 1, engine_type: crate::engines::EngineType::default(), storage_format: crate::types::StorageFormat::default(), foreign_keys: Vec::new(), primary_key: Vec::new() }, "dual".to_string())
         };
         let (names, types) =
             crate::executor::schema::resolve_select_columns_simple(&select.columns, &schema, &table_alias)?;
@@ -350,8 +363,13 @@ pub fn build_select_metadata(
     Ok((names.len(), names, types))
 }
 
-/// Parse parameters from COM_STMT_EXECUTE packet
-fn parse_execute_params(data: &[u8], param_count: usize) -> Result<Vec<Value>> {
+/// Flag in the second byte of a bound parameter type: the integer is unsigned
+const PARAM_UNSIGNED_FLAG: u8 = 0x80;
+
+/// Parse parameters from COM_STMT_EXECUTE packet. `param_types` holds the
+/// types of an earlier execute and is replaced when the packet binds new ones.
+/// A packet with fewer or more parameter values than `param_count` is rejected.
+fn parse_execute_params(data: &[u8], param_count: usize, param_types: &mut Vec<(u8, bool)>) -> Result<Vec<Value>> {
     if param_count == 0 {
         return Ok(vec![]);
     }
@@ -359,7 +377,7 @@ fn parse_execute_params(data: &[u8], param_count: usize) -> Result<Vec<Value>> {
     let mut pos = 9; // Skip header (4 stmt_id + 1 flags + 4 iteration_count)
 
     // NULL bitmap
-    let null_bitmap_len = (param_count + 7) / 8;
+    let null_bitmap_len = param_count.div_ceil(8);
     if pos + null_bitmap_len > data.len() {
         return Err(MiniSqlError::Protocol("Truncated NULL bitmap".into()));
     }
@@ -375,26 +393,26 @@ fn parse_execute_params(data: &[u8], param_count: usize) -> Result<Vec<Value>> {
     let new_params_bound = data[pos];
     pos += 1;
 
-    let mut param_types = Vec::new();
     if new_params_bound == 1 {
-        // Read parameter types
+        // Read parameter types: the type, then a flag byte
+        param_types.clear();
         for _ in 0..param_count {
             if pos + 2 > data.len() {
                 return Err(MiniSqlError::Protocol("Truncated parameter types".into()));
             }
             let type_byte = data[pos];
-            let _unsigned_flag = data[pos + 1];
-            param_types.push(type_byte);
+            let unsigned = data[pos + 1] & PARAM_UNSIGNED_FLAG != 0;
+            param_types.push((type_byte, unsigned));
             pos += 2;
         }
-    } else {
-        // Use default types (assume all strings)
-        param_types = vec![MYSQL_TYPE_VAR_STRING; param_count];
+    } else if param_types.len() != param_count {
+        // Never bound: assume all strings
+        *param_types = vec![(MYSQL_TYPE_VAR_STRING, false); param_count];
     }
 
     // Read parameter values
     let mut params = Vec::new();
-    for i in 0..param_count {
+    for (i, &(type_byte, unsigned)) in param_types.iter().enumerate() {
         // Check NULL bitmap
         let byte_idx = i / 8;
         let bit_idx = i % 8;
@@ -403,77 +421,106 @@ fn parse_execute_params(data: &[u8], param_count: usize) -> Result<Vec<Value>> {
             continue;
         }
 
-        let type_byte = param_types[i];
-        let value = read_binary_value(type_byte, &data[pos..])?;
+        if pos >= data.len() && type_byte != MYSQL_TYPE_NULL {
+            return Err(MiniSqlError::wrong_arguments("mysqld_stmt_execute"));
+        }
+        let value = read_binary_value(type_byte, unsigned, &data[pos..])?;
         pos += binary_value_length(type_byte, &data[pos..]);
         params.push(value);
     }
+    // Values left over belong to parameters the statement does not have
+    if pos != data.len() {
+        return Err(MiniSqlError::wrong_arguments("mysqld_stmt_execute"));
+    }
 
     Ok(params)
 }
 
-/// Read a value from binary format
-fn read_binary_value(type_byte: u8, data: &[u8]) -> Result<Value> {
+/// The first `N` bytes of `data`, or an error naming the truncated type
+fn fixed_bytes<const N: usize>(data: &[u8], type_name: &str) -> Result<[u8; N]> {
+    data.get(..N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| MiniSqlError::Protocol(format!("Missing {} value", type_name)))
+}
+
+/// Read a value from binary format; `unsigned` is the parameter's unsigned
+/// flag, which only integer types use
+fn read_binary_value(type_byte: u8, unsigned: bool, data: &[u8]) -> Result<Value> {
     match type_byte {
         MYSQL_TYPE_TINY => {
-            if data.is_empty() {
-                return Err(MiniSqlError::Protocol("Missing TINY value".into()));
-            }
-            Ok(Value::Integer(data[0] as i64))
+            let [byte] = fixed_bytes(data, "TINY")?;
+            Ok(Value::Integer(if unsigned { byte as i64 } else { byte as i8 as i64 }))
         }
-        MYSQL_TYPE_SHORT => {
-            if data.len() < 2 {
-                return Err(MiniSqlError::Protocol("Missing SHORT value".into()));
-            }
-            let val = i16::from_le_bytes([data[0], data[1]]);
-            Ok(Value::Integer(val as i64))
+        MYSQL_TYPE_SHORT | MYSQL_TYPE_YEAR => {
+            let bytes = fixed_bytes(data, "SHORT")?;
+            Ok(Value::Integer(if unsigned {
+                u16::from_le_bytes(bytes) as i64
+            } else {
+                i16::from_le_bytes(bytes) as i64
+            }))
         }
         MYSQL_TYPE_LONG | MYSQL_TYPE_INT24 => {
-            if data.len() < 4 {
-                return Err(MiniSqlError::Protocol("Missing LONG value".into()));
-            }
-            let val = i32::from_le_bytes([data[0], data[1], data[2], data[3]]);
-            Ok(Value::Integer(val as i64))
+            let bytes = fixed_bytes(data, "LONG")?;
+            Ok(Value::Integer(if unsigned {
+                u32::from_le_bytes(bytes) as i64
+            } else {
+                i32::from_le_bytes(bytes) as i64
+            }))
         }
         MYSQL_TYPE_LONGLONG => {
-            if data.len() < 8 {
-                return Err(MiniSqlError::Protocol("Missing LONGLONG value".into()));
+            let bytes = fixed_bytes(data, "LONGLONG")?;
+            if unsigned {
+                // Beyond the range of a signed BIGINT, keep the exact value
+                // as decimal text; a float would round it
+                let val = u64::from_le_bytes(bytes);
+                Ok(i64::try_from(val).map_or_else(|_| Value::String(val.to_string()), Value::Integer))
+            } else {
+                Ok(Value::Integer(i64::from_le_bytes(bytes)))
             }
-            let val = i64::from_le_bytes([
-                data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
-            ]);
-            Ok(Value::Integer(val))
         }
         MYSQL_TYPE_FLOAT => {
-            if data.len() < 4 {
-                return Err(MiniSqlError::Protocol("Missing FLOAT value".into()));
-            }
-            let val = f32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            let val = f32::from_le_bytes(fixed_bytes(data, "FLOAT")?);
             Ok(Value::Float(val as f64))
         }
         MYSQL_TYPE_DOUBLE => {
-            if data.len() < 8 {
-                return Err(MiniSqlError::Protocol("Missing DOUBLE value".into()));
-            }
-            let val = f64::from_le_bytes([
-                data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
-            ]);
+            let val = f64::from_le_bytes(fixed_bytes(data, "DOUBLE")?);
             Ok(Value::Float(val))
         }
+        MYSQL_TYPE_DATE | MYSQL_TYPE_DATETIME | MYSQL_TYPE_TIMESTAMP => read_binary_datetime(type_byte, data),
+        MYSQL_TYPE_TIME => read_binary_time(data),
+        MYSQL_TYPE_BLOB
+        | MYSQL_TYPE_TINY_BLOB
+        | MYSQL_TYPE_MEDIUM_BLOB
+        | MYSQL_TYPE_LONG_BLOB => {
+            // Length-encoded bytes
+            let (len, bytes_read) = LenencInt::read(data)?;
+            let start = bytes_read;
+            let end = start + len as usize;
+            if data.len() < end {
+                return Err(MiniSqlError::Protocol("Truncated BLOB value".into()));
+            }
+            Ok(Value::Bytes(data[start..end].to_vec()))
+        }
         MYSQL_TYPE_VARCHAR
         | MYSQL_TYPE_VAR_STRING
         | MYSQL_TYPE_STRING
-        | MYSQL_TYPE_BLOB
-        | MYSQL_TYPE_DECIMAL => {
-            // Length-encoded string
+        | MYSQL_TYPE_DECIMAL
+        | MYSQL_TYPE_NEWDECIMAL
+        | MYSQL_TYPE_ENUM
+        | MYSQL_TYPE_SET
+        | MYSQL_TYPE_JSON => {
+            // Length-encoded string; clients also bind binary data this way,
+            // so bytes that are not UTF-8 stay binary
             let (len, bytes_read) = LenencInt::read(data)?;
             let start = bytes_read;
             let end = start + len as usize;
             if data.len() < end {
                 return Err(MiniSqlError::Protocol("Truncated string value".into()));
             }
-            let s = String::from_utf8_lossy(&data[start..end]).to_string();
-            Ok(Value::String(s))
+            Ok(match String::from_utf8(data[start..end].to_vec()) {
+                Ok(s) => Value::String(s),
+                Err(e) => Value::Bytes(e.into_bytes()),
+            })
         }
         MYSQL_TYPE_NULL => Ok(Value::Null),
         _ => {
@@ -490,30 +537,70 @@ fn read_binary_value(type_byte: u8, data: &[u8]) -> Result<Value> {
     }
 }
 
+/// The fields of a length-prefixed DATE / DATETIME / TIME value, checking
+/// that the length is one of `lengths`
+fn temporal_fields<'d>(data: &'d [u8], lengths: &[usize], type_name: &str) -> Result<&'d [u8]> {
+    let (&len, rest) = data
+        .split_first()
+        .ok_or_else(|| MiniSqlError::Protocol(format!("Missing {} value", type_name)))?;
+    match rest.get(..len as usize) {
+        Some(fields) if lengths.contains(&fields.len()) => Ok(fields),
+        _ => Err(MiniSqlError::Protocol(format!("Malformed {} value", type_name))),
+    }
+}
+
+/// Read a DATE, DATETIME or TIMESTAMP value: a length byte (0, 4, 7 or 11),
+/// then year (2 bytes), month, day, hour, minute, second and microseconds
+/// (4 bytes), each omitted when zero along with those after it. Timestamps
+/// are stored as text, so the value is formatted as MySQL writes it.
+fn read_binary_datetime(type_byte: u8, data: &[u8]) -> Result<Value> {
+    let fields = temporal_fields(data, &[0, 4, 7, 11], "DATETIME")?;
+    let field = |i: usize| fields.get(i).copied().unwrap_or(0);
+    let year = u16::from_le_bytes([field(0), field(1)]);
+    let date = format!("{:04}-{:02}-{:02}", year, field(2), field(3));
+    if type_byte == MYSQL_TYPE_DATE {
+        return Ok(Value::String(date));
+    }
+    let mut text = format!("{} {:02}:{:02}:{:02}", date, field(4), field(5), field(6));
+    let micros = u32::from_le_bytes([field(7), field(8), field(9), field(10)]);
+    if micros > 0 {
+        text.push_str(&format!(".{:06}", micros));
+    }
+    Ok(Value::String(text))
+}
+
+/// Read a TIME value: a length byte (0, 8 or 12), then a negative flag, days
+/// (4 bytes), hour, minute, second and microseconds (4 bytes)
+fn read_binary_time(data: &[u8]) -> Result<Value> {
+    let fields = temporal_fields(data, &[0, 8, 12], "TIME")?;
+    let field = |i: usize| fields.get(i).copied().unwrap_or(0);
+    let days = u32::from_le_bytes([field(1), field(2), field(3), field(4)]);
+    let hours = days as u64 * 24 + field(5) as u64;
+    let sign = if field(0) == 1 { "-" } else { "" };
+    let mut text = format!("{}{:02}:{:02}:{:02}", sign, hours, field(6), field(7));
+    let micros = u32::from_le_bytes([field(8), field(9), field(10), field(11)]);
+    if micros > 0 {
+        text.push_str(&format!(".{:06}", micros));
+    }
+    Ok(Value::String(text))
+}
+
 /// Calculate length of a binary value
 fn binary_value_length(type_byte: u8, data: &[u8]) -> usize {
     match type_byte {
         MYSQL_TYPE_TINY => 1,
-        MYSQL_TYPE_SHORT => 2,
+        MYSQL_TYPE_SHORT | MYSQL_TYPE_YEAR => 2,
         MYSQL_TYPE_LONG | MYSQL_TYPE_INT24 => 4,
         MYSQL_TYPE_LONGLONG => 8,
         MYSQL_TYPE_FLOAT => 4,
         MYSQL_TYPE_DOUBLE => 8,
-        MYSQL_TYPE_VARCHAR
-        | MYSQL_TYPE_VAR_STRING
-        | MYSQL_TYPE_STRING
-        | MYSQL_TYPE_BLOB
-        | MYSQL_TYPE_DECIMAL
-        | MYSQL_TYPE_NULL => {
-            // Length-encoded string
-            if let Ok((len, bytes_read)) = LenencInt::read(data) {
-                bytes_read + len as usize
-            } else {
-                0
-            }
+        MYSQL_TYPE_NULL => 0,
+        // Length byte, then that many bytes
+        MYSQL_TYPE_DATE | MYSQL_TYPE_DATETIME | MYSQL_TYPE_TIMESTAMP | MYSQL_TYPE_TIME => {
+            data.first().map_or(0, |len| 1 + *len as usize)
         }
         _ => {
-            // Unknown type, try length-encoded
+            // Length-encoded string or bytes
             if let Ok((len, bytes_read)) = LenencInt::read(data) {
                 bytes_read + len as usize
             } else {
@@ -528,42 +615,12 @@ fn substitute_statement_params(
     stmt: &parser::Statement,
     params: &[Value],
 ) -> Result<parser::Statement> {
-    use crate::executor::evaluator::substitute_placeholders;
+    use crate::executor::evaluator::{substitute_placeholders, substitute_select_placeholders};
     use parser::*;
 
     match stmt {
         Statement::Select(select) => {
-            let columns: Result<Vec<SelectColumn>> = select
-                .columns
-                .iter()
-                .map(|c| match c {
-                    SelectColumn::Star => Ok(SelectColumn::Star),
-                    SelectColumn::QualifiedStar { table } => {
-                        Ok(SelectColumn::QualifiedStar {
-                            table: table.clone(),
-                        })
-                    }
-                    SelectColumn::Expr { expr, alias } => Ok(SelectColumn::Expr {
-                        expr: substitute_placeholders(expr, params)?,
-                        alias: alias.clone(),
-                    }),
-                })
-                .collect();
-
-            let where_clause = match &select.where_clause {
-                Some(expr) => Some(substitute_placeholders(expr, params)?),
-                None => None,
-            };
-
-            Ok(Statement::Select(SelectStmt {
-                columns: columns?,
-                from: select.from.clone(),
-                joins: select.joins.clone(),
-                where_clause,
-                group_by: select.group_by.clone(),
-                order_by: select.order_by.clone(),
-                limit: select.limit,
-            }))
+            Ok(Statement::Select(substitute_select_placeholders(select, params)?))
         }
         Statement::Insert(insert) => {
             let values: Result<Vec<Vec<Expr>>> = insert
@@ -575,10 +632,16 @@ fn substitute_statement_params(
                         .collect()
                 })
                 .collect();
+            let on_duplicate_key_update: Result<Vec<(String, Expr)>> = insert
+                .on_duplicate_key_update
+                .iter()
+                .map(|(col, expr)| Ok((col.clone(), substitute_placeholders(expr, params)?)))
+                .collect();
             Ok(Statement::Insert(InsertStmt {
                 table_name: insert.table_name.clone(),
                 columns: insert.columns.clone(),
                 values: values?,
+                on_duplicate_key_update: on_duplicate_key_update?,
             }))
         }
         Statement::Update(update) => {
@@ -595,6 +658,8 @@ fn substitute_statement_params(
                 table_name: update.table_name.clone(),
                 assignments: assignments?,
                 where_clause,
+                order_by: update.order_by.clone(),
+                limit: update.limit,
             }))
         }
         Statement::Delete(delete) => {
@@ -605,8 +670,20 @@ fn substitute_statement_params(
             Ok(Statement::Delete(DeleteStmt {
                 table_name: delete.table_name.clone(),
                 where_clause,
+                order_by: delete.order_by.clone(),
+                limit: delete.limit,
             }))
         }
+        Statement::SetUserVariables(assignments) => {
+            let assignments: Result<Vec<(String, Expr)>> = assignments
+                .iter()
+                .map(|(name, expr)| Ok((name.clone(), substitute_placeholders(expr, params)?)))
+                .collect();
+            Ok(Statement::SetUserVariables(assignments?))
+        }
+        Statement::Explain(inner) => {
+            Ok(Statement::Explain(Box::new(substitute_statement_params(inner, params)?)))
+        }
         // Statements that don't have parameters
         _ => Ok(stmt.clone()),
     }
@@ -631,27 +708,27 @@ mod tests {
         // TINY
         let data = vec![42];
         assert_eq!(
-            read_binary_value(MYSQL_TYPE_TINY, &data).unwrap(),
+            read_binary_value(MYSQL_TYPE_TINY, false, &data).unwrap(),
             Value::Integer(42)
         );
 
         // SHORT
         let data = vec![0xFF, 0x00]; // 255
         assert_eq!(
-            read_binary_value(MYSQL_TYPE_SHORT, &data).unwrap(),
+            read_binary_value(MYSQL_TYPE_SHORT, false, &data).unwrap(),
             Value::Integer(255)
         );
 
         // LONG
         let data = vec![0x00, 0x00, 0x01, 0x00]; // 65536
         assert_eq!(
-            read_binary_value(MYSQL_TYPE_LONG, &data).unwrap(),
+            read_binary_value(MYSQL_TYPE_LONG, false, &data).unwrap(),
             Value::Integer(65536)
         );
 
         // LONGLONG
         let data = vec![0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F]; // i64::MAX
-        let result = read_binary_value(MYSQL_TYPE_LONGLONG, &data).unwrap();
+        let result = read_binary_value(MYSQL_TYPE_LONGLONG, false, &data).unwrap();
         assert!(matches!(result, Value::Integer(_)));
     }
 
@@ -660,7 +737,7 @@ mod tests {
         // FLOAT
         let val = 3.14f32;
         let data = val.to_le_bytes().to_vec();
-        let result = read_binary_value(MYSQL_TYPE_FLOAT, &data).unwrap();
+        let result = read_binary_value(MYSQL_TYPE_FLOAT, false, &data).unwrap();
         if let Value::Float(f) = result {
             assert!((f - 3.14).abs() < 0.01);
         } else {
@@ -670,7 +747,7 @@ mod tests {
         // DOUBLE
         let val = 3.14159265359f64;
         let data = val.to_le_bytes().to_vec();
-        let result = read_binary_value(MYSQL_TYPE_DOUBLE, &data).unwrap();
+        let result = read_binary_value(MYSQL_TYPE_DOUBLE, false, &data).unwrap();
         assert_eq!(result, Value::Float(val));
     }
 
@@ -678,24 +755,105 @@ mod tests {
     fn test_read_binary_value_string() {
         // Length-encoded string: [length, ...bytes]
         let data = vec![5, b'h', b'e', b'l', b'l', b'o'];
-        let result = read_binary_value(MYSQL_TYPE_VAR_STRING, &data).unwrap();
+        let result = read_binary_value(MYSQL_TYPE_VAR_STRING, false, &data).unwrap();
         assert_eq!(result, Value::String("hello".to_string()));
     }
 
     #[test]
     fn test_read_binary_value_null() {
-        let result = read_binary_value(MYSQL_TYPE_NULL, &[]).unwrap();
+        let result = read_binary_value(MYSQL_TYPE_NULL, false, &[]).unwrap();
         assert_eq!(result, Value::Null);
     }
 
+    #[test]
+    fn test_read_binary_value_signedness() {
+        assert_eq!(read_binary_value(MYSQL_TYPE_TINY, false, &[0xFF]).unwrap(), Value::Integer(-1));
+        assert_eq!(read_binary_value(MYSQL_TYPE_TINY, true, &[0xFF]).unwrap(), Value::Integer(255));
+        assert_eq!(read_binary_value(MYSQL_TYPE_SHORT, true, &[0xFF, 0xFF]).unwrap(), Value::Integer(65535));
+        assert_eq!(
+            read_binary_value(MYSQL_TYPE_LONG, true, &u32::MAX.to_le_bytes()).unwrap(),
+            Value::Integer(u32::MAX as i64)
+        );
+        assert_eq!(
+            read_binary_value(MYSQL_TYPE_LONGLONG, false, &(-5i64).to_le_bytes()).unwrap(),
+            Value::Integer(-5)
+        );
+        assert_eq!(
+            read_binary_value(MYSQL_TYPE_LONGLONG, true, &u64::MAX.to_le_bytes()).unwrap(),
+            Value::String("18446744073709551615".into())
+        );
+        assert_eq!(
+            read_binary_value(MYSQL_TYPE_LONGLONG, true, &(i64::MAX as u64 + 1).to_le_bytes()).unwrap(),
+            Value::String("9223372036854775808".into())
+        );
+        assert_eq!(
+            read_binary_value(MYSQL_TYPE_LONGLONG, true, &(i64::MAX as u64).to_le_bytes()).unwrap(),
+            Value::Integer(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_read_binary_value_temporal_types() {
+        let date = [4, 0xE8, 0x07, 2, 29];
+        assert_eq!(read_binary_value(MYSQL_TYPE_DATE, false, &date).unwrap(), Value::String("2024-02-29".into()));
+        assert_eq!(binary_value_length(MYSQL_TYPE_DATE, &date), 5);
+
+        let datetime = [7, 0xE8, 0x07, 12, 31, 23, 59, 58];
+        assert_eq!(
+            read_binary_value(MYSQL_TYPE_DATETIME, false, &datetime).unwrap(),
+            Value::String("2024-12-31 23:59:58".into())
+        );
+        let mut precise = vec![11, 0xE8, 0x07, 1, 2, 3, 4, 5];
+        precise.extend_from_slice(&1500u32.to_le_bytes());
+        assert_eq!(
+            read_binary_value(MYSQL_TYPE_TIMESTAMP, false, &precise).unwrap(),
+            Value::String("2024-01-02 03:04:05.001500".into())
+        );
+        assert_eq!(
+            read_binary_value(MYSQL_TYPE_DATETIME, false, &[0]).unwrap(),
+            Value::String("0000-00-00 00:00:00".into())
+        );
+
+        let time = [8, 1, 1, 0, 0, 0, 2, 30, 0];
+        assert_eq!(read_binary_value(MYSQL_TYPE_TIME, false, &time).unwrap(), Value::String("-26:30:00".into()));
+        assert_eq!(binary_value_length(MYSQL_TYPE_TIME, &time), 9);
+
+        assert!(read_binary_value(MYSQL_TYPE_DATE, false, &[5, 0xE8, 0x07, 1, 1, 0]).is_err());
+        assert!(read_binary_value(MYSQL_TYPE_DATETIME, false, &[7, 0xE8, 0x07]).is_err());
+    }
+
+    #[test]
+    fn test_parse_execute_params() {
+        // Header, NULL bitmap with the third parameter set, new-params-bound flag
+        let mut data = vec![1, 0, 0, 0, 0, 1, 0, 0, 0, 0b100, 1];
+        for (ty, flags) in [(MYSQL_TYPE_LONGLONG, 0), (MYSQL_TYPE_DOUBLE, 0), (MYSQL_TYPE_NULL, 0), (MYSQL_TYPE_TINY, 0x80), (MYSQL_TYPE_STRING, 0)] {
+            data.extend_from_slice(&[ty, flags]);
+        }
+        data.extend_from_slice(&i64::MIN.to_le_bytes());
+        data.extend_from_slice(&2.5f64.to_le_bytes());
+        data.push(200);
+        data.extend_from_slice(b"\x02hi");
+
+        let mut types = Vec::new();
+        let params = parse_execute_params(&data, 5, &mut types).unwrap();
+        assert_eq!(params, vec![
+            Value::Integer(i64::MIN),
+            Value::Float(2.5),
+            Value::Null,
+            Value::Integer(200),
+            Value::String("hi".into()),
+        ]);
+        assert_eq!(types[3], (MYSQL_TYPE_TINY, true));
+    }
+
     #[test]
     fn test_read_binary_value_truncated() {
         // SHORT but only 1 byte
         let data = vec![42];
-        assert!(read_binary_value(MYSQL_TYPE_SHORT, &data).is_err());
+        assert!(read_binary_value(MYSQL_TYPE_SHORT, false, &data).is_err());
 
         // LONG but only 2 bytes
         let data = vec![42, 0];
-        assert!(read_binary_value(MYSQL_TYPE_LONG, &data).is_err());
+        assert!(read_binary_value(MYSQL_TYPE_LONG, false, &data).is_err());
     }
 }
@@ -0,0 +1,258 @@
+//! Sandstone delta replication endpoint
+//!
+//! With `Config::replication_addr` set, the server listens on a second port
+//! where peers exchange the CRDT deltas of Sandstone tables. Each message is a
+//! bincode-encoded [`ReplicationRequest`] or [`ReplicationResponse`] prefixed
+//! with its length as a 4-byte little-endian integer, and a connection may
+//! carry any number of request/response pairs.
+//!
+//! Peers share a secret (`Config::replication_secret`). On connect the
+//! endpoint sends a random nonce, and the peer must answer with
+//! [`ReplicationRequest::Auth`] carrying `SHA256(nonce + secret)` before any
+//! other request is served; a wrong or missing proof closes the connection.
+//! Pushes are refused on a read-only server.
+//!
+//! Pulling a table takes its pending deltas, so each delta goes to one peer:
+//! a node should have a single peer pulling from it. [`sync_table`] pushes a
+//! node's deltas to its peer and pulls the peer's back, after which both hold
+//! the same rows. Replicating nodes need distinct `SandstoneConfig::replica_id`s.
+
+use std::sync::Arc;
+
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+use crate::engines::sandstone::DeltaState;
+use crate::error::{MiniSqlError, Result};
+use crate::executor::Executor;
+
+/// Largest message accepted, so a corrupt length prefix cannot exhaust memory
+const MAX_MESSAGE_BYTES: usize = 256 * 1024 * 1024;
+
+/// Length of the nonce the endpoint challenges each peer with
+const NONCE_BYTES: usize = 32;
+
+/// A request sent to a node's replication endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ReplicationRequest {
+    /// Prove knowledge of the shared secret: `SHA256(nonce + secret)`
+    Auth { proof: Vec<u8> },
+    /// Take the deltas recorded for a table since the last pull
+    Pull { table: String },
+    /// Merge deltas recorded on the sender
+    Push { table: String, deltas: Vec<DeltaState> },
+}
+
+/// The endpoint's answer to a [`ReplicationRequest`]
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ReplicationResponse {
+    /// The peer's proof matched; requests are served from now on
+    Authenticated,
+    /// The deltas taken by a pull
+    Deltas(Vec<DeltaState>),
+    /// The number of deltas a push merged
+    Merged(usize),
+    /// The request failed, e.g. because the table is not a Sandstone table
+    Error(String),
+}
+
+/// The proof a peer answers `nonce` with
+fn auth_proof(nonce: &[u8], secret: &str) -> Vec<u8> {
+    Sha256::new().chain_update(nonce).chain_update(secret.as_bytes()).finalize().to_vec()
+}
+
+/// Compare without stopping at the first differing byte
+fn proofs_match(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+async fn write_message<W: AsyncWrite + Unpin, T: Serialize>(writer: &mut W, message: &T) -> Result<()> {
+    let payload = bincode::serialize(message)
+        .map_err(|e| MiniSqlError::Protocol(format!("Cannot encode replication message: {}", e)))?;
+    let len = u32::try_from(payload.len())
+        .ok()
+        .filter(|len| *len as usize <= MAX_MESSAGE_BYTES)
+        .ok_or_else(|| MiniSqlError::Protocol("Replication message too large".into()))?;
+    writer.write_all(&len.to_le_bytes()).await?;
+    writer.write_all(&payload).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Read one message, or `None` if the peer closed the connection between messages
+async fn read_message<R: AsyncRead + Unpin, T: DeserializeOwned>(reader: &mut R) -> Result<Option<T>> {
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_MESSAGE_BYTES {
+        return Err(MiniSqlError::Protocol(format!("Replication message of {} bytes is too large", len)));
+    }
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).await?;
+    bincode::deserialize(&payload)
+        .map(Some)
+        .map_err(|e| MiniSqlError::Protocol(format!("Malformed replication message: {}", e)))
+}
+
+/// Serve replication requests from `listener` to peers that know `secret`,
+/// until `shutdown` turns true
+pub async fn serve_replication(
+    listener: TcpListener,
+    executor: Arc<Executor>,
+    secret: String,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<()> {
+    let secret: Arc<str> = secret.into();
+    log::info!("Sandstone replication listening on {}", listener.local_addr()?);
+    let mut peers = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, addr)) => {
+                    let executor = Arc::clone(&executor);
+                    let secret = Arc::clone(&secret);
+                    peers.spawn(async move {
+                        if let Err(e) = serve_peer(stream, &executor, &secret).await {
+                            log::warn!("Replication connection from {} failed: {}", addr, e);
+                        }
+                    });
+                }
+                Err(e) => log::error!("Failed to accept replication connection: {}", e),
+            },
+            Some(_) = peers.join_next(), if !peers.is_empty() => {}
+            _ = shutdown.wait_for(|stop| *stop) => break,
+        }
+    }
+    peers.shutdown().await;
+    Ok(())
+}
+
+/// Challenge a peer for the shared secret, then answer its requests until it
+/// disconnects
+async fn serve_peer(mut stream: TcpStream, executor: &Executor, secret: &str) -> Result<()> {
+    let mut nonce = vec![0u8; NONCE_BYTES];
+    rand::thread_rng().fill(&mut nonce[..]);
+    write_message(&mut stream, &nonce).await?;
+    match read_message(&mut stream).await? {
+        Some(ReplicationRequest::Auth { proof }) if proofs_match(&proof, &auth_proof(&nonce, secret)) => {
+            write_message(&mut stream, &ReplicationResponse::Authenticated).await?;
+        }
+        Some(_) => {
+            let refused = ReplicationResponse::Error("Replication authentication failed".into());
+            write_message(&mut stream, &refused).await?;
+            return Err(MiniSqlError::Auth("Replication peer failed authentication".into()));
+        }
+        None => return Ok(()),
+    }
+
+    while let Some(request) = read_message(&mut stream).await? {
+        match request {
+            ReplicationRequest::Auth { .. } => {
+                write_message(&mut stream, &ReplicationResponse::Error("Already authenticated".into())).await?;
+            }
+            ReplicationRequest::Pull { table } => {
+                let (response, taken) = match executor.take_replication_deltas(&table) {
+                    Ok(deltas) => (ReplicationResponse::Deltas(deltas.clone()), deltas),
+                    Err(e) => (ReplicationResponse::Error(e.to_string()), Vec::new()),
+                };
+                if let Err(e) = write_message(&mut stream, &response).await {
+                    // The peer never got them; keep them for the next pull
+                    executor.requeue_replication_deltas(&table, taken)?;
+                    return Err(e);
+                }
+            }
+            ReplicationRequest::Push { table, deltas } => {
+                let response = match executor.merge_replication_deltas(&table, deltas) {
+                    Ok(merged) => ReplicationResponse::Merged(merged),
+                    Err(e) => ReplicationResponse::Error(e.to_string()),
+                };
+                write_message(&mut stream, &response).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A connection to a peer's replication endpoint
+pub struct ReplicationClient {
+    stream: TcpStream,
+}
+
+impl ReplicationClient {
+    /// Connect to the replication endpoint at `addr` and authenticate with
+    /// the shared `secret`
+    pub async fn connect(addr: impl ToSocketAddrs, secret: &str) -> Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        let mut client = Self { stream };
+        let nonce: Vec<u8> = read_message(&mut client.stream).await?
+            .ok_or_else(|| MiniSqlError::Protocol("Replication peer closed the connection".into()))?;
+        match client.request(&ReplicationRequest::Auth { proof: auth_proof(&nonce, secret) }).await? {
+            ReplicationResponse::Authenticated => Ok(client),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Take the peer's pending deltas for `table`
+    pub async fn pull(&mut self, table: &str) -> Result<Vec<DeltaState>> {
+        match self.request(&ReplicationRequest::Pull { table: table.to_string() }).await? {
+            ReplicationResponse::Deltas(deltas) => Ok(deltas),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Have the peer merge `deltas` into `table`; returns how many it merged
+    pub async fn push(&mut self, table: &str, deltas: Vec<DeltaState>) -> Result<usize> {
+        match self.request(&ReplicationRequest::Push { table: table.to_string(), deltas }).await? {
+            ReplicationResponse::Merged(merged) => Ok(merged),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    async fn request(&mut self, request: &ReplicationRequest) -> Result<ReplicationResponse> {
+        write_message(&mut self.stream, request).await?;
+        read_message(&mut self.stream).await?
+            .ok_or_else(|| MiniSqlError::Protocol("Replication peer closed the connection".into()))
+    }
+}
+
+fn unexpected(response: ReplicationResponse) -> MiniSqlError {
+    match response {
+        ReplicationResponse::Error(message) => MiniSqlError::Protocol(format!("Replication peer: {}", message)),
+        other => MiniSqlError::Protocol(format!("Unexpected replication response: {:?}", other)),
+    }
+}
+
+/// Exchange `table`'s deltas with the peer at `addr`, authenticating with
+/// the shared `secret`: push this node's pending deltas, then pull and merge
+/// the peer's. Deltas that could not be pushed stay pending. Returns the
+/// number of deltas pushed and pulled.
+pub async fn sync_table(
+    executor: &Executor,
+    addr: impl ToSocketAddrs,
+    secret: &str,
+    table: &str,
+) -> Result<(usize, usize)> {
+    let mut client = ReplicationClient::connect(addr, secret).await?;
+
+    let local = executor.take_replication_deltas(table)?;
+    let pushed = local.len();
+    if let Err(e) = client.push(table, local.clone()).await {
+        executor.requeue_replication_deltas(table, local)?;
+        return Err(e);
+    }
+
+    let remote = client.pull(table).await?;
+    let pulled = executor.merge_replication_deltas(table, remote)?;
+    Ok((pushed, pulled))
+}
@@ -1,7 +1,8 @@
 //! Result set formatting and sending
 
 use crate::error::Result;
-use crate::types::{DataType, ResultSet, Value};
+use crate::datetime::Date;
+use crate::types::{DataType, ResultSet, TableSchema, Value};
 
 use super::constants::*;
 use super::packet::{LenencInt, LenencString, PacketIO};
@@ -26,15 +27,32 @@ impl ResultSetSender {
 
     /// Send a result set to the client (text protocol)
     pub async fn send_result_set(&self, io: &mut PacketIO, result: &ResultSet) -> Result<()> {
+        self.send_result_header(io, &result.columns, &result.column_types).await?;
+
+        // Row packets
+        for row in &result.rows {
+            self.send_row(io, row).await?;
+        }
+
+        self.send_result_end(io).await
+    }
+
+    /// Send the column count, column definitions and (for pre-DEPRECATE_EOF
+    /// clients) the EOF that precede the rows of a text result set
+    pub async fn send_result_header(
+        &self,
+        io: &mut PacketIO,
+        columns: &[String],
+        column_types: &[DataType],
+    ) -> Result<()> {
         // Column count packet
         let mut packet = Vec::new();
-        LenencInt::write(&mut packet, result.columns.len() as u64);
+        LenencInt::write(&mut packet, columns.len() as u64);
         io.write_packet(&packet).await?;
 
         // Column definition packets
-        for (i, col_name) in result.columns.iter().enumerate() {
-            self.send_column_definition(io, col_name, &result.column_types[i])
-                .await?;
+        for (col_name, col_type) in columns.iter().zip(column_types) {
+            self.send_column_definition(io, col_name, col_type).await?;
         }
 
         // EOF packet after columns (if client doesn't have DEPRECATE_EOF)
@@ -42,19 +60,16 @@ impl ResultSetSender {
             self.send_eof(io, false).await?;
         }
 
-        // Row packets
-        for row in &result.rows {
-            self.send_row(io, row).await?;
-        }
+        Ok(())
+    }
 
-        // Final EOF packet
+    /// Send the packet that terminates a result set after its last row
+    pub async fn send_result_end(&self, io: &mut PacketIO) -> Result<()> {
         if self.client_capabilities & CLIENT_DEPRECATE_EOF == 0 {
-            self.send_eof(io, false).await?;
+            self.send_eof(io, false).await
         } else {
-            self.send_ok(io, 0, 0, "", false).await?;
+            self.send_ok(io, 0, 0, "", false).await
         }
-
-        Ok(())
     }
 
     /// Send a result set to the client (binary protocol - for prepared statements)
@@ -92,6 +107,20 @@ impl ResultSetSender {
         Ok(())
     }
 
+    /// Send the response to COM_FIELD_LIST: a column definition per column of
+    /// `schema`, each carrying its default value, followed by an EOF
+    pub async fn send_field_list(&self, io: &mut PacketIO, schema: &TableSchema, in_transaction: bool) -> Result<()> {
+        for column in &schema.columns {
+            let mut packet = column_definition(&schema.name, &column.name, &column.data_type);
+            match column.default.as_ref().and_then(Value::to_string_repr) {
+                Some(default) => LenencString::write(&mut packet, &default),
+                None => packet.push(0xFB), // NULL
+            }
+            io.write_packet(&packet).await?;
+        }
+        self.send_eof(io, in_transaction).await
+    }
+
     /// Send a column definition packet
     async fn send_column_definition(
         &self,
@@ -99,86 +128,21 @@ impl ResultSetSender {
         name: &str,
         data_type: &DataType,
     ) -> Result<()> {
-        let mut packet = Vec::new();
-
-        // Catalog (lenenc string) - always "def"
-        LenencString::write(&mut packet, "def");
-
-        // Schema (lenenc string)
-        LenencString::write(&mut packet, "minisql");
-
-        // Virtual table (lenenc string)
-        LenencString::write(&mut packet, "");
-
-        // Physical table (lenenc string)
-        LenencString::write(&mut packet, "");
-
-        // Virtual column (lenenc string)
-        LenencString::write(&mut packet, name);
-
-        // Physical column (lenenc string)
-        LenencString::write(&mut packet, name);
-
-        // Fixed length fields marker
-        packet.push(0x0C);
-
-        // Character set (2 bytes) - binary for numeric types, utf8mb4 for text
-        let charset: u16 = match data_type {
-            DataType::Integer | DataType::Float | DataType::Boolean => 63, // binary
-            DataType::Varchar(_) | DataType::Text => 45, // utf8mb4
-            DataType::Json => 45, // utf8mb4
-        };
-        packet.extend_from_slice(&charset.to_le_bytes());
-
-        // Column length (4 bytes)
-        let col_len: u32 = match data_type {
-            DataType::Integer => 11,
-            DataType::Float => 22,
-            DataType::Varchar(Some(len)) => *len,
-            DataType::Varchar(None) => 255,
-            DataType::Text => 65535,
-            DataType::Boolean => 1,
-            DataType::Json => 1073741824,
-        };
-        packet.extend_from_slice(&col_len.to_le_bytes());
-
-        // Column type (1 byte)
-        let col_type = match data_type {
-            DataType::Integer => MYSQL_TYPE_LONGLONG,
-            DataType::Float => MYSQL_TYPE_DOUBLE,
-            DataType::Varchar(_) => MYSQL_TYPE_VAR_STRING,
-            DataType::Text => MYSQL_TYPE_BLOB,
-            DataType::Boolean => MYSQL_TYPE_TINY,
-            DataType::Json => MYSQL_TYPE_JSON,
-        };
-        packet.push(col_type);
-
-        // Flags (2 bytes)
-        let flags: u16 = match data_type {
-            DataType::Integer => NUM_FLAG,
-            DataType::Float => NUM_FLAG,
-            DataType::Boolean => NUM_FLAG,
-            DataType::Varchar(_) | DataType::Text | DataType::Json => 0,
-        };
-        packet.extend_from_slice(&flags.to_le_bytes());
-
-        // Decimals (1 byte)
-        packet.push(0);
-
-        // Filler (2 bytes)
-        packet.extend_from_slice(&0u16.to_le_bytes());
-
-        io.write_packet(&packet).await
+        io.write_packet(&column_definition("", name, data_type)).await
     }
 
     /// Send a row packet (text protocol)
-    async fn send_row(&self, io: &mut PacketIO, values: &[Value]) -> Result<()> {
+    pub async fn send_row(&self, io: &mut PacketIO, values: &[Value]) -> Result<()> {
         let mut packet = Vec::new();
 
         for value in values {
-            match value.to_string_repr() {
-                Some(s) => LenencString::write(&mut packet, &s),
-                None => packet.push(0xFB), // NULL
+            match value {
+                // Binary data goes out as is, not as (lossy) text
+                Value::Bytes(b) => LenencString::write_bytes(&mut packet, b),
+                _ => match value.to_string_repr() {
+                    Some(s) => LenencString::write(&mut packet, &s),
+                    None => packet.push(0xFB), // NULL
+                },
             }
         }
 
@@ -226,6 +190,19 @@ impl ResultSetSender {
         last_insert_id: u64,
         info: &str,
         in_transaction: bool,
+    ) -> Result<()> {
+        self.send_ok_with_warnings(io, affected_rows, last_insert_id, info, in_transaction, 0).await
+    }
+
+    /// Send an OK packet reporting the statement's warning count
+    pub async fn send_ok_with_warnings(
+        &self,
+        io: &mut PacketIO,
+        affected_rows: u64,
+        last_insert_id: u64,
+        info: &str,
+        in_transaction: bool,
+        warnings: u16,
     ) -> Result<()> {
         let mut packet = Vec::new();
 
@@ -244,7 +221,7 @@ impl ResultSetSender {
             packet.extend_from_slice(&status.to_le_bytes());
 
             // Warnings (2 bytes)
-            packet.extend_from_slice(&0u16.to_le_bytes());
+            packet.extend_from_slice(&warnings.to_le_bytes());
         }
 
         // Info string
@@ -272,6 +249,86 @@ impl ResultSetSender {
     }
 }
 
+/// Build a column definition packet; `table` is left empty for computed
+/// result sets
+fn column_definition(table: &str, name: &str, data_type: &DataType) -> Vec<u8> {
+    let mut packet = Vec::new();
+
+    // Catalog (lenenc string) - always "def"
+    LenencString::write(&mut packet, "def");
+
+    // Schema (lenenc string)
+    LenencString::write(&mut packet, "minisql");
+
+    // Virtual table (lenenc string)
+    LenencString::write(&mut packet, table);
+
+    // Physical table (lenenc string)
+    LenencString::write(&mut packet, table);
+
+    // Virtual column (lenenc string)
+    LenencString::write(&mut packet, name);
+
+    // Physical column (lenenc string)
+    LenencString::write(&mut packet, name);
+
+    // Fixed length fields marker
+    packet.push(0x0C);
+
+    // Character set (2 bytes) - binary for numeric types, utf8mb4 for text
+    let charset: u16 = match data_type {
+        DataType::Integer | DataType::Float | DataType::Boolean | DataType::Date | DataType::Blob => 63, // binary
+        DataType::Varchar(_) | DataType::Text => 45, // utf8mb4
+        DataType::Json => 45, // utf8mb4
+    };
+    packet.extend_from_slice(&charset.to_le_bytes());
+
+    // Column length (4 bytes)
+    let col_len: u32 = match data_type {
+        DataType::Integer => 11,
+        DataType::Float => 22,
+        DataType::Varchar(Some(len)) => *len,
+        DataType::Varchar(None) => 255,
+        DataType::Text => 65535,
+        DataType::Boolean => 1,
+        DataType::Json => 1073741824,
+        DataType::Date => 10,
+        DataType::Blob => 65535,
+    };
+    packet.extend_from_slice(&col_len.to_le_bytes());
+
+    // Column type (1 byte)
+    let col_type = match data_type {
+        DataType::Integer => MYSQL_TYPE_LONGLONG,
+        DataType::Float => MYSQL_TYPE_DOUBLE,
+        DataType::Varchar(_) => MYSQL_TYPE_VAR_STRING,
+        DataType::Text => MYSQL_TYPE_BLOB,
+        DataType::Boolean => MYSQL_TYPE_TINY,
+        DataType::Json => MYSQL_TYPE_JSON,
+        DataType::Date => MYSQL_TYPE_DATE,
+        DataType::Blob => MYSQL_TYPE_BLOB,
+    };
+    packet.push(col_type);
+
+    // Flags (2 bytes)
+    let flags: u16 = match data_type {
+        DataType::Integer => NUM_FLAG,
+        DataType::Float => NUM_FLAG,
+        DataType::Boolean => NUM_FLAG,
+        DataType::Varchar(_) | DataType::Text | DataType::Json | DataType::Date => 0,
+        DataType::Blob => BLOB_FLAG | BINARY_FLAG,
+    };
+    packet.extend_from_slice(&flags.to_le_bytes());
+
+    // Decimals (1 byte)
+    packet.push(0);
+
+    // Filler (2 bytes)
+    packet.extend_from_slice(&0u16.to_le_bytes());
+
+    packet
+}
+
 /// Normalize row values to match their schema types
 /// This ensures that values stored in one format (e.g., Integer) are
 /// converted to match the expected schema type before encoding
@@ -332,6 +389,15 @@ fn normalize_row_types(row: &[Value], types: &[DataType]) -> Result<Vec<Value>>
             // JSON conversions
             (Value::Json(j), DataType::Json) => Value::Json(j.clone()),
             (Value::Json(j), DataType::Varchar(_) | DataType::Text) => Value::String(j.to_string()),
+
+            // Date conversions
+            (Value::Date(d), DataType::Date) => Value::Date(*d),
+            (Value::Date(d), DataType::Varchar(_) | DataType::Text) => Value::String(d.to_string()),
+            (Value::String(s), DataType::Date) => Date::parse(s).map_or_else(|| value.clone(), Value::Date),
+
+            // Binary conversions
+            (Value::Bytes(_), DataType::Blob) => value.clone(),
+            (v, DataType::Blob) => Value::Bytes(v.to_string().into_bytes()),
             
             // For any other combination, try to keep the value as-is
             _ => value.clone(),
@@ -386,6 +452,19 @@ fn encode_binary_value(packet: &mut Vec<u8>, value: &Value, data_type: &DataType
             let json_str = j.to_string();
             LenencString::write(packet, &json_str);
         }
+
+        // Binary data (sent as length-encoded bytes, text columns included)
+        (Value::Bytes(b), DataType::Blob | DataType::Varchar(_) | DataType::Text) => {
+            LenencString::write_bytes(packet, b);
+        }
+
+        // MYSQL_TYPE_DATE - length byte, then year (2 bytes), month and day
+        (Value::Date(d), DataType::Date) => {
+            packet.push(4);
+            packet.extend_from_slice(&(d.year() as u16).to_le_bytes());
+            packet.push(d.month() as u8);
+            packet.push(d.day() as u8);
+        }
         
         // Type conversions
         (Value::Integer(v), DataType::Float) => {
@@ -432,6 +511,7 @@ mod tests {
             (DataType::Boolean, MYSQL_TYPE_TINY),
             (DataType::Json, MYSQL_TYPE_JSON),
             (DataType::Varchar(Some(50)), MYSQL_TYPE_VAR_STRING),
+            (DataType::Date, MYSQL_TYPE_DATE),
         ];
 
         for (data_type, expected) in mappings {
@@ -442,6 +522,8 @@ mod tests {
                 DataType::Text => MYSQL_TYPE_BLOB,
                 DataType::Boolean => MYSQL_TYPE_TINY,
                 DataType::Json => MYSQL_TYPE_JSON,
+                DataType::Date => MYSQL_TYPE_DATE,
+                DataType::Blob => MYSQL_TYPE_BLOB,
             };
             assert_eq!(mysql_type, expected);
         }
@@ -467,6 +549,8 @@ mod tests {
                 DataType::Text => 65535,
                 DataType::Boolean => 1,
                 DataType::Json => 1073741824,
+                DataType::Date => 10,
+                DataType::Blob => 65535,
             };
             assert_eq!(col_len, expected_len);
         }
@@ -0,0 +1,61 @@
+//! Slow query log
+//!
+//! With `Config::slow_query_ms` set, every COM_QUERY that takes longer than
+//! the threshold is logged at warn level with its SQL, duration, the number
+//! of rows it read from tables, whether any of those reads used an index and
+//! its digest, so slow runs of the same statement can be grouped.
+//! Faster queries are not logged.
+
+use std::time::Duration;
+
+use crate::digest::QueryDigest;
+use crate::executor::ReadStats;
+
+/// The log line for a query that took `elapsed`, or `None` when logging is
+/// off or the query finished within `threshold_ms`
+pub(crate) fn slow_query_entry(threshold_ms: Option<u64>, sql: &str, elapsed: Duration, stats: ReadStats) -> Option<String> {
+    let threshold = Duration::from_millis(threshold_ms?);
+    if elapsed <= threshold {
+        return None;
+    }
+    let digest = QueryDigest::new(sql);
+    Some(format!(
+        "Slow query: {:.3} ms, rows_examined={}, index_used={}, digest={}: {} (digest text: {})",
+        elapsed.as_secs_f64() * 1000.0,
+        stats.rows_examined,
+        if stats.index_used { "yes" } else { "no" },
+        digest,
+        sql.trim(),
+        digest.text
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_query_entry_format() {
+        let stats = ReadStats { rows_examined: 1500, index_used: false };
+        let digest = QueryDigest::new("SELECT * FROM t WHERE name = 'y'");
+        assert_eq!(
+            slow_query_entry(Some(100), " SELECT * FROM t WHERE name = 'x' ", Duration::from_micros(250_500), stats).unwrap(),
+            format!(
+                "Slow query: 250.500 ms, rows_examined=1500, index_used=no, digest={}: \
+                 SELECT * FROM t WHERE name = 'x' (digest text: SELECT * FROM t WHERE name = ?)",
+                digest
+            )
+        );
+        let stats = ReadStats { rows_examined: 1, index_used: true };
+        assert!(slow_query_entry(Some(0), "SELECT 1", Duration::from_millis(1), stats).unwrap().contains("index_used=yes"));
+    }
+
+    #[test]
+    fn test_fast_queries_are_not_logged() {
+        let stats = ReadStats::default();
+        assert_eq!(slow_query_entry(Some(100), "SELECT 1", Duration::from_millis(100), stats), None);
+        assert_eq!(slow_query_entry(Some(100), "SELECT 1", Duration::from_millis(5), stats), None);
+        // Off unless a threshold is configured
+        assert_eq!(slow_query_entry(None, "SELECT 1", Duration::from_secs(60), stats), None);
+    }
+}
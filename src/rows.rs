@@ -0,0 +1,263 @@
+//! Typed access to query results
+//!
+//! An ergonomics layer over [`ResultSet`] for applications embedding the
+//! executor. Rows are borrowed from the result set and columns can be read by
+//! index or by name with typed accessors. SQL `NULL` is `None`; a value of the
+//! wrong type is an error rather than a silent conversion.
+//!
+//! ```
+//! use minisql::engines::granite::TransactionManager;
+//! use minisql::executor::{Executor, Session};
+//! use minisql::parser::Parser;
+//! use minisql::storage::StorageEngine;
+//!
+//! # fn main() -> minisql::error::Result<()> {
+//! # let dir = tempfile::tempdir()?;
+//! let storage = StorageEngine::new(dir.path().to_path_buf())?;
+//! let executor = Executor::new(storage, TransactionManager::new(dir.path().to_path_buf())?);
+//! let mut session = Session::new();
+//! for sql in [
+//!     "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, email TEXT)",
+//!     "INSERT INTO users VALUES (1, 'ann', 'ann@example.com'), (2, 'bob', NULL)",
+//! ] {
+//!     executor.execute(Parser::parse(sql)?, &mut session)?;
+//! }
+//!
+//! let result = executor.execute(Parser::parse("SELECT id, name, email FROM users ORDER BY id")?, &mut session)?;
+//! for row in result.rows() {
+//!     let id = row.get_i64("id")?.expect("id is never NULL");
+//!     let name = row.get_str(1)?.unwrap_or_default();
+//!     match row.get_str("email")? {
+//!         Some(email) => println!("{} {} <{}>", id, name, email),
+//!         None => println!("{} {} (no email)", id, name),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use serde_json::Value as JsonValue;
+
+use crate::datetime::Date;
+use crate::error::{ColumnContext, MiniSqlError, Result};
+use crate::types::{QueryResult, ResultSet, Value};
+
+/// A way of selecting a column in a [`ResultRow`]: its position or its name
+pub trait ColumnIndex {
+    /// Position of the column among `columns`
+    fn index(&self, columns: &[String]) -> Result<usize>;
+}
+
+impl ColumnIndex for usize {
+    fn index(&self, columns: &[String]) -> Result<usize> {
+        if *self < columns.len() {
+            Ok(*self)
+        } else {
+            Err(MiniSqlError::Column(format!(
+                "Column index {} out of range for {} column(s)",
+                self,
+                columns.len()
+            )))
+        }
+    }
+}
+
+impl ColumnIndex for &str {
+    /// Names match case-insensitively; the first of several equal names wins
+    fn index(&self, columns: &[String]) -> Result<usize> {
+        columns
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(self))
+            .ok_or_else(|| MiniSqlError::unknown_column(self, ColumnContext::FieldList))
+    }
+}
+
+/// One row of a [`ResultSet`], with typed column accessors
+#[derive(Debug, Clone, Copy)]
+pub struct ResultRow<'a> {
+    columns: &'a [String],
+    values: &'a [Value],
+}
+
+impl<'a> ResultRow<'a> {
+    /// Column names, in order
+    pub fn columns(&self) -> &'a [String] {
+        self.columns
+    }
+
+    /// The raw values, in column order
+    pub fn values(&self) -> &'a [Value] {
+        self.values
+    }
+
+    /// The raw value of a column
+    pub fn get(&self, column: impl ColumnIndex) -> Result<&'a Value> {
+        let idx = column.index(self.columns)?;
+        Ok(self.values.get(idx).unwrap_or(&Value::Null))
+    }
+
+    /// An INTEGER (or BOOLEAN, as 0/1) column
+    pub fn get_i64(&self, column: impl ColumnIndex) -> Result<Option<i64>> {
+        let idx = column.index(self.columns)?;
+        match self.value(idx) {
+            Value::Null => Ok(None),
+            Value::Integer(i) => Ok(Some(*i)),
+            Value::Boolean(b) => Ok(Some(*b as i64)),
+            other => Err(self.mismatch(idx, other, "an integer")),
+        }
+    }
+
+    /// A FLOAT or INTEGER column
+    pub fn get_f64(&self, column: impl ColumnIndex) -> Result<Option<f64>> {
+        let idx = column.index(self.columns)?;
+        match self.value(idx) {
+            Value::Null => Ok(None),
+            Value::Float(f) => Ok(Some(*f)),
+            Value::Integer(i) => Ok(Some(*i as f64)),
+            other => Err(self.mismatch(idx, other, "a number")),
+        }
+    }
+
+    /// A TEXT column
+    pub fn get_str(&self, column: impl ColumnIndex) -> Result<Option<&'a str>> {
+        let idx = column.index(self.columns)?;
+        match self.value(idx) {
+            Value::Null => Ok(None),
+            Value::String(s) => Ok(Some(s)),
+            other => Err(self.mismatch(idx, other, "a string")),
+        }
+    }
+
+    /// A BLOB column, or the bytes of a TEXT column
+    pub fn get_bytes(&self, column: impl ColumnIndex) -> Result<Option<&'a [u8]>> {
+        let idx = column.index(self.columns)?;
+        match self.value(idx) {
+            Value::Null => Ok(None),
+            Value::Bytes(b) => Ok(Some(b)),
+            Value::String(s) => Ok(Some(s.as_bytes())),
+            other => Err(self.mismatch(idx, other, "binary data")),
+        }
+    }
+
+    /// A BOOLEAN (or INTEGER, as non-zero) column
+    pub fn get_bool(&self, column: impl ColumnIndex) -> Result<Option<bool>> {
+        let idx = column.index(self.columns)?;
+        match self.value(idx) {
+            Value::Null => Ok(None),
+            Value::Boolean(b) => Ok(Some(*b)),
+            Value::Integer(i) => Ok(Some(*i != 0)),
+            other => Err(self.mismatch(idx, other, "a boolean")),
+        }
+    }
+
+    /// A DATE column, or a TEXT column holding a `YYYY-MM-DD` date
+    pub fn get_date(&self, column: impl ColumnIndex) -> Result<Option<Date>> {
+        let idx = column.index(self.columns)?;
+        match self.value(idx) {
+            Value::Null => Ok(None),
+            Value::Date(d) => Ok(Some(*d)),
+            other @ Value::String(s) => Date::parse(s).map(Some).ok_or_else(|| self.mismatch(idx, other, "a date")),
+            other => Err(self.mismatch(idx, other, "a date")),
+        }
+    }
+
+    /// A JSON column, or a TEXT column holding a JSON document
+    pub fn get_json(&self, column: impl ColumnIndex) -> Result<Option<JsonValue>> {
+        let idx = column.index(self.columns)?;
+        match self.value(idx) {
+            Value::Null => Ok(None),
+            Value::Json(j) => Ok(Some(j.clone())),
+            Value::String(s) => serde_json::from_str(s).map(Some).map_err(|e| {
+                MiniSqlError::Json(format!("Column '{}' is not valid JSON: {}", self.columns[idx], e))
+            }),
+            other => Err(self.mismatch(idx, other, "JSON")),
+        }
+    }
+
+    fn value(&self, idx: usize) -> &'a Value {
+        self.values.get(idx).unwrap_or(&Value::Null)
+    }
+
+    fn mismatch(&self, idx: usize, value: &Value, expected: &str) -> MiniSqlError {
+        let actual = match value {
+            Value::Null => "NULL",
+            Value::Integer(_) => "an integer",
+            Value::Float(_) => "a float",
+            Value::String(_) => "a string",
+            Value::Boolean(_) => "a boolean",
+            Value::Json(_) => "JSON",
+            Value::Date(_) => "a date",
+            Value::Bytes(_) => "binary data",
+        };
+        MiniSqlError::Type(format!(
+            "Column '{}' holds {}, not {}",
+            self.columns[idx], actual, expected
+        ))
+    }
+}
+
+/// Iterator over the rows of a result set
+#[derive(Debug, Clone)]
+pub struct Rows<'a> {
+    columns: &'a [String],
+    rows: std::slice::Iter<'a, Vec<Value>>,
+}
+
+impl<'a> Iterator for Rows<'a> {
+    type Item = ResultRow<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let columns = self.columns;
+        self.rows.next().map(|values| ResultRow { columns, values })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Rows<'_> {}
+
+impl ResultSet {
+    /// Iterate over the rows with typed accessors
+    pub fn iter(&self) -> Rows<'_> {
+        Rows { columns: &self.columns, rows: self.rows.iter() }
+    }
+
+    /// The row at `index`, if there is one
+    pub fn row(&self, index: usize) -> Option<ResultRow<'_>> {
+        self.rows.get(index).map(|values| ResultRow { columns: &self.columns, values })
+    }
+
+    /// Position of the column called `name` (case-insensitive)
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        name.index(&self.columns).ok()
+    }
+}
+
+impl<'a> IntoIterator for &'a ResultSet {
+    type Item = ResultRow<'a>;
+    type IntoIter = Rows<'a>;
+
+    fn into_iter(self) -> Rows<'a> {
+        self.iter()
+    }
+}
+
+impl QueryResult {
+    /// The result set of a SELECT, or `None` for other statements
+    pub fn result_set(&self) -> Option<&ResultSet> {
+        match self {
+            QueryResult::Select(result) => Some(result),
+            _ => None,
+        }
+    }
+
+    /// Iterate over the rows of a SELECT; other statements have no rows
+    pub fn rows(&self) -> Rows<'_> {
+        match self {
+            QueryResult::Select(result) => result.iter(),
+            _ => Rows { columns: &[], rows: [].iter() },
+        }
+    }
+}
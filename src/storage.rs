@@ -17,18 +17,28 @@
 //! ```
 //!
 //! ### Table Data Format (.dat files)
-//! Each row is stored as a JSON line (JSONL format) for simplicity:
-//! ```json
-//! {"id":1,"values":[1,"alice","alice@example.com",{"meta":"data"}]}
-//! {"id":2,"values":[2,"bob","bob@example.com",null]}
+//! Each row is stored as a JSON line (JSONL format) for simplicity, followed
+//! by a tab and the CRC32 of the JSON text in hex:
+//! ```text
+//! {"id":1,"values":[1,"alice","alice@example.com",{"meta":"data"}]}<TAB><crc32>
+//! {"id":2,"values":[2,"bob","bob@example.com",null]}<TAB><crc32>
 //! ```
 //!
+//! A line whose checksum doesn't match fails the load with a "table is
+//! crashed" error naming the table and byte offset. Lines without a checksum
+//! (files from older versions) are accepted as-is.
+//!
 //! This format is:
 //! - Human readable (good for educational purposes)
 //! - Easy to parse and debug
 //! - Supports JSON columns naturally
 //! - Append-friendly
 //!
+//! ### Binary Table Format (.bdat files)
+//! Tables created with `STORAGE FORMAT = BINARY` store each row as a
+//! bincode-encoded record prefixed with its length and CRC32 as little-endian
+//! u32s, mirroring the WAL encoding. JSON columns are stored as text.
+//!
 //! ### Catalog Format (catalog.json)
 //! ```json
 //! {
@@ -45,24 +55,32 @@
 //! }
 //! ```
 
-use std::collections::HashMap;
-use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
 
 use serde::{Deserialize, Serialize};
 
-use crate::error::{MiniSqlError, Result};
-use crate::types::{IndexMetadata, Row, TableSchema, Value};
+use crate::datetime::Date;
+use crate::error::{ColumnContext, MiniSqlError, Result};
+use crate::types::{float_as_exact_int, hex_encode, IndexMetadata, Privilege, Row, StorageFormat, TableSchema, Value};
 
 /// Catalog file storing table metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Catalog {
     tables: HashMap<String, TableSchema>,
+    /// Older catalogs predate indexes entirely
+    #[serde(default)]
     indexes: HashMap<String, IndexMetadata>,
+    /// Table privileges granted with GRANT: user -> table -> privileges
+    #[serde(default)]
+    grants: BTreeMap<String, BTreeMap<String, BTreeSet<Privilege>>>,
     next_row_id: u64,
 }
 
@@ -71,9 +89,30 @@ impl Catalog {
         Self {
             tables: HashMap::new(),
             indexes: HashMap::new(),
+            grants: BTreeMap::new(),
             next_row_id: 1,
         }
     }
+
+    /// Column positions of every index on `table_name`, keyed by index name
+    fn index_columns(&self, table_name: &str) -> Vec<(String, Vec<usize>)> {
+        let Some(schema) = self.tables.get(table_name) else {
+            return Vec::new();
+        };
+        self.indexes.values()
+            .filter(|idx| idx.table_name == table_name)
+            .filter_map(|idx| {
+                let indices: Vec<usize> = idx.columns.iter()
+                    .filter_map(|col| schema.find_column(col))
+                    .collect();
+                if indices.len() == idx.columns.len() {
+                    Some((idx.name.clone(), indices))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 /// Index entry mapping (composite key -> Vec<row_id>)
@@ -97,6 +136,106 @@ impl TableData {
             indexes: HashMap::new(),
         }
     }
+
+    /// Ids of the rows filed under `values` in `index_name`, a prefix of the
+    /// index's `column_count` key columns
+    fn index_key_row_ids(&self, index_name: &str, values: &[Value], column_count: usize) -> Vec<u64> {
+        let Some(index_data) = self.indexes.get(index_name) else {
+            return Vec::new();
+        };
+
+        // Build the composite key prefix from the provided values
+        let value_refs: Vec<&Value> = values.iter().collect();
+        let key_prefix = build_composite_key(&value_refs);
+
+        if values.len() == column_count {
+            return index_data.get(&key_prefix).cloned().unwrap_or_default();
+        }
+
+        // Prefix match: scan all keys that start with this prefix
+        // Use BTreeMap range to find all matching keys
+        let prefix_with_separator = format!("{}\x00", key_prefix);
+        let mut row_ids = Vec::new();
+        for (key, ids) in index_data.range(key_prefix.clone()..) {
+            // Check if this key starts with our prefix
+            if key.starts_with(&key_prefix) && (key == &key_prefix || key.starts_with(&prefix_with_separator)) {
+                row_ids.extend(ids.iter().copied());
+            } else if key > &prefix_with_separator && !key.starts_with(&key_prefix) {
+                // We've passed all matching keys
+                break;
+            }
+        }
+        row_ids
+    }
+
+    /// Ids of the rows whose value at `column`, the leading column of
+    /// `index_name`, satisfies `keep`
+    fn index_filter_row_ids(&self, index_name: &str, column: usize, keep: &dyn Fn(&Value) -> bool) -> Vec<u64> {
+        let Some(index_data) = self.indexes.get(index_name) else {
+            return Vec::new();
+        };
+
+        let mut row_ids = Vec::new();
+        for ids in index_data.values() {
+            let leading = ids.iter().find_map(|id| self.rows.get(id)).and_then(|row| row.values.get(column));
+            if leading.is_some_and(keep) {
+                row_ids.extend(ids.iter().copied());
+            }
+        }
+        row_ids
+    }
+}
+
+/// An index being built from a snapshot of its table's rows. Writers record
+/// the rows they change so the build can re-check them before the index
+/// goes live.
+#[derive(Debug)]
+struct IndexBuild {
+    table_name: String,
+    changed_rows: HashSet<u64>,
+}
+
+/// An index whose in-memory entries disagree with the rows of its table,
+/// as found by [`StorageEngine::check_indexes`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexAnomaly {
+    /// The index is in the catalog but has no entries loaded
+    MissingIndex { index: String },
+    /// An entry points at a row that does not exist
+    DanglingEntry { index: String, row_id: u64 },
+    /// An entry's key does not match the current values of its row
+    StaleEntry { index: String, row_id: u64 },
+    /// A row is missing from the index or listed more than once
+    EntryCount { index: String, row_id: u64, entries: usize },
+}
+
+impl IndexAnomaly {
+    /// Name of the affected index
+    pub fn index(&self) -> &str {
+        match self {
+            IndexAnomaly::MissingIndex { index }
+            | IndexAnomaly::DanglingEntry { index, .. }
+            | IndexAnomaly::StaleEntry { index, .. }
+            | IndexAnomaly::EntryCount { index, .. } => index,
+        }
+    }
+}
+
+impl std::fmt::Display for IndexAnomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexAnomaly::MissingIndex { index } => write!(f, "index '{}' has no entries loaded", index),
+            IndexAnomaly::DanglingEntry { index, row_id } => {
+                write!(f, "index '{}' points at missing row {}", index, row_id)
+            }
+            IndexAnomaly::StaleEntry { index, row_id } => {
+                write!(f, "index '{}' has a stale key for row {}", index, row_id)
+            }
+            IndexAnomaly::EntryCount { index, row_id, entries } => {
+                write!(f, "index '{}' lists row {} {} time(s), expected once", index, row_id, entries)
+            }
+        }
+    }
 }
 
 /// The storage engine manages all table data and persistence
@@ -107,6 +246,225 @@ pub struct StorageEngine {
     tables: Arc<RwLock<HashMap<String, TableData>>>,
     /// Catalog data (schemas and metadata)
     catalog: Arc<RwLock<Catalog>>,
+    /// Number of full table scans served, for diagnostics and tests
+    full_scans: Arc<AtomicU64>,
+    /// Index builds in progress, keyed by index name. Locked after `tables`.
+    index_builds: Arc<Mutex<HashMap<String, IndexBuild>>>,
+    /// Serializes table and catalog file writes: concurrent saves of the same
+    /// file (e.g. an auto-commit save racing a checkpoint) share a temp path
+    file_writes: Arc<Mutex<()>>,
+}
+
+/// Path of a table's row file in the given format
+fn table_file_path(tables_dir: &Path, table_name: &str, format: StorageFormat) -> PathBuf {
+    tables_dir.join(format!("{}.{}", table_name, format.extension()))
+}
+
+/// Write a table's rows in its schema's storage format, via a temp file and
+/// atomic rename.
+///
+/// The temp file is removed if any step fails, so an interrupted or failed
+/// save never leaves a partial `.tmp` behind. A row file in the other format
+/// (left over from a format change) is removed once the new file is in place.
+fn write_table_file(tables_dir: &Path, table: &TableData) -> Result<()> {
+    let format = table.schema.storage_format;
+    let path = table_file_path(tables_dir, &table.schema.name, format);
+    let temp_path = path.with_extension(format!("{}.tmp", format.extension()));
+
+    let result = (|| -> Result<()> {
+        let file = File::create(&temp_path)?;
+        let mut writer = BufWriter::new(file);
+
+        // Write rows sorted by ID for consistency
+        let mut row_ids: Vec<_> = table.rows.keys().collect();
+        row_ids.sort();
+
+        for id in row_ids {
+            if let Some(row) = table.rows.get(id) {
+                match format {
+                    StorageFormat::Jsonl => {
+                        let json = serde_json::to_string(row)?;
+                        writeln!(writer, "{}\t{:08x}", json, crc32fast::hash(json.as_bytes()))?;
+                    }
+                    StorageFormat::Binary => {
+                        let encoded = encode_binary_row(row)?;
+                        writer.write_all(&(encoded.len() as u32).to_le_bytes())?;
+                        writer.write_all(&crc32fast::hash(&encoded).to_le_bytes())?;
+                        writer.write_all(&encoded)?;
+                    }
+                }
+            }
+        }
+
+        writer.flush()?;
+        fs::rename(&temp_path, &path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+        return result;
+    }
+
+    for other in [StorageFormat::Jsonl, StorageFormat::Binary] {
+        if other != format {
+            remove_if_exists(&table_file_path(tables_dir, &table.schema.name, other))?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove a file, treating "not found" as success
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Remove `.dat.tmp`/`.bdat.tmp` files left behind by a process that died
+/// mid-save. The matching row file (if any) is still the last complete version.
+fn remove_stale_temp_files(tables_dir: &Path) -> Result<()> {
+    for entry in fs::read_dir(tables_dir)? {
+        let path = entry?.path();
+        let name = path.to_string_lossy();
+        if name.ends_with(".dat.tmp") || name.ends_with(".bdat.tmp") {
+            log::warn!("Removing incomplete table file {}", path.display());
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// A column value as stored in `.bdat` files. JSON documents are kept as text
+/// because bincode cannot decode self-describing `serde_json::Value`s.
+#[derive(Serialize, Deserialize)]
+enum BinaryValue<'a> {
+    Null,
+    Integer(i64),
+    Float(f64),
+    String(Cow<'a, str>),
+    Boolean(bool),
+    Json(Cow<'a, str>),
+    /// Days since 1970-01-01
+    Date(i64),
+    Bytes(Cow<'a, [u8]>),
+}
+
+/// A row as stored in `.bdat` files
+#[derive(Serialize, Deserialize)]
+struct BinaryRow<'a> {
+    id: u64,
+    values: Vec<BinaryValue<'a>>,
+}
+
+/// Encode a row as one bincode record (without the length prefix)
+fn encode_binary_row(row: &Row) -> Result<Vec<u8>> {
+    let values = row.values.iter().map(|v| match v {
+        Value::Null => BinaryValue::Null,
+        Value::Integer(i) => BinaryValue::Integer(*i),
+        Value::Float(f) => BinaryValue::Float(*f),
+        Value::String(s) => BinaryValue::String(Cow::Borrowed(s)),
+        Value::Boolean(b) => BinaryValue::Boolean(*b),
+        Value::Json(j) => BinaryValue::Json(Cow::Owned(j.to_string())),
+        Value::Date(d) => BinaryValue::Date(d.days()),
+        Value::Bytes(b) => BinaryValue::Bytes(Cow::Borrowed(b)),
+    }).collect();
+    bincode::serialize(&BinaryRow { id: row.id, values })
+        .map_err(|e| MiniSqlError::Io(std::io::Error::other(e)))
+}
+
+/// Read every record of a `.bdat` file, verifying each record's CRC32
+fn read_binary_rows(table_name: &str, path: &Path) -> Result<Vec<Row>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut rows = Vec::new();
+    let mut offset: u64 = 0;
+
+    while !reader.fill_buf()?.is_empty() {
+        let corrupt = |what: &str| MiniSqlError::Internal(format!(
+            "Failed to read binary row in '{}' at byte offset {}: {}",
+            path.display(), offset, what
+        ));
+
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header).map_err(|_| corrupt("truncated record header"))?;
+        let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let crc = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let mut buf = vec![0u8; len];
+        reader.read_exact(&mut buf).map_err(|_| corrupt("truncated record"))?;
+        if crc32fast::hash(&buf) != crc {
+            return Err(MiniSqlError::table_corrupted(table_name, &path.display().to_string(), offset));
+        }
+
+        let record: BinaryRow = bincode::deserialize(&buf).map_err(|e| corrupt(&e.to_string()))?;
+        let values = record.values.into_iter().map(|v| Ok(match v {
+            BinaryValue::Null => Value::Null,
+            BinaryValue::Integer(i) => Value::Integer(i),
+            BinaryValue::Float(f) => Value::Float(f),
+            BinaryValue::String(s) => Value::String(s.into_owned()),
+            BinaryValue::Boolean(b) => Value::Boolean(b),
+            BinaryValue::Json(text) => Value::Json(
+                serde_json::from_str(&text).map_err(|e| corrupt(&e.to_string()))?
+            ),
+            BinaryValue::Date(days) => Value::Date(Date::from_days(days)),
+            BinaryValue::Bytes(b) => Value::Bytes(b.into_owned()),
+        })).collect::<Result<Vec<_>>>()?;
+        rows.push(Row::new(record.id, values));
+        offset += 8 + len as u64;
+    }
+
+    Ok(rows)
+}
+
+/// Read every row of a JSONL `.dat` file.
+///
+/// Each line is `<json>\t<crc32 hex>`; the checksum is verified when present,
+/// so files written before checksums were added still load.
+fn read_jsonl_rows(table_name: &str, path: &Path) -> Result<Vec<Row>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut rows = Vec::new();
+    let mut offset: u64 = 0;
+    let mut line_no = 0;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        if read == 0 {
+            break;
+        }
+        line_no += 1;
+        let line_offset = offset;
+        offset += read as u64;
+
+        let content = line.trim_end_matches(['\n', '\r']);
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        let json = match content.rsplit_once('\t') {
+            Some((json, checksum)) => {
+                let expected = u32::from_str_radix(checksum, 16).ok();
+                if expected != Some(crc32fast::hash(json.as_bytes())) {
+                    return Err(MiniSqlError::table_corrupted(
+                        table_name, &path.display().to_string(), line_offset
+                    ));
+                }
+                json
+            }
+            None => content,
+        };
+
+        let row: Row = serde_json::from_str(json).map_err(|e| {
+            MiniSqlError::Json(format!(
+                "Failed to parse JSON row in '{}', line {}: {}. Content: {}",
+                path.display(), line_no, e, json
+            ))
+        })?;
+        rows.push(row);
+    }
+
+    Ok(rows)
 }
 
 impl StorageEngine {
@@ -115,11 +473,15 @@ impl StorageEngine {
         // Ensure directories exist
         fs::create_dir_all(&data_dir)?;
         fs::create_dir_all(data_dir.join("tables"))?;
+        remove_stale_temp_files(&data_dir.join("tables"))?;
 
         let engine = Self {
             data_dir: data_dir.clone(),
             tables: Arc::new(RwLock::new(HashMap::new())),
             catalog: Arc::new(RwLock::new(Catalog::new())),
+            full_scans: Arc::new(AtomicU64::new(0)),
+            index_builds: Arc::new(Mutex::new(HashMap::new())),
+            file_writes: Arc::new(Mutex::new(())),
         };
 
         // Load existing catalog
@@ -157,10 +519,15 @@ impl StorageEngine {
         Ok(())
     }
 
-    /// Get path to table data file (validates name first)
-    fn table_path(&self, table_name: &str) -> PathBuf {
-        // Note: Callers should validate before calling this, but we sanitize anyway
-        self.data_dir.join("tables").join(format!("{}.dat", table_name))
+    /// Get path to the tables directory
+    fn tables_dir(&self) -> PathBuf {
+        self.data_dir.join("tables")
+    }
+
+    /// Get path to a table's row file in the given format
+    fn table_path(&self, table_name: &str, format: StorageFormat) -> PathBuf {
+        // Note: Callers should validate the name before calling this
+        table_file_path(&self.tables_dir(), table_name, format)
     }
 
     /// Load catalog from disk
@@ -184,6 +551,7 @@ impl StorageEngine {
         let path = self.catalog_path();
         let temp_path = path.with_extension("json.tmp");
         
+        let _writing = self.file_writes.lock().unwrap();
         let catalog = self.catalog.read().unwrap();
         let file = File::create(&temp_path)?;
         let writer = BufWriter::new(file);
@@ -201,27 +569,30 @@ impl StorageEngine {
 
         for (name, schema) in &catalog.tables {
             let mut table_data = TableData::new(schema.clone());
-            
-            let path = self.table_path(name);
-            if path.exists() {
-                let file = File::open(&path)?;
-                let reader = BufReader::new(file);
-                
-                for (idx, line_res) in reader.lines().enumerate() {
-                    let line = line_res?;
-                    if line.trim().is_empty() {
-                        continue;
-                    }
-                    let row: Row = serde_json::from_str(&line).map_err(|e| {
-                        MiniSqlError::Json(format!(
-                            "Failed to parse JSON row in '{}', line {}: {}. Content: {}",
-                            path.display(), idx + 1, e, &line
-                        ))
-                    })?;
+
+            // Prefer the schema's format, but read whichever file exists so a
+            // table whose format changed before its next save still loads
+            let preferred = schema.storage_format;
+            let other = match preferred {
+                StorageFormat::Jsonl => StorageFormat::Binary,
+                StorageFormat::Binary => StorageFormat::Jsonl,
+            };
+            for format in [preferred, other] {
+                let path = self.table_path(name, format);
+                if !path.exists() {
+                    continue;
+                }
+                let rows = match format {
+                    StorageFormat::Jsonl => read_jsonl_rows(name, &path)?,
+                    StorageFormat::Binary => read_binary_rows(name, &path)?,
+                };
+                for row in rows {
                     table_data.rows.insert(row.id, row);
                 }
+                break;
             }
-            
+
+
             tables.insert(name.clone(), table_data);
         }
 
@@ -230,32 +601,13 @@ impl StorageEngine {
 
     /// Save a table to disk (full rewrite)
     pub fn save_table(&self, table_name: &str) -> Result<()> {
+        let _writing = self.file_writes.lock().unwrap();
         let tables = self.tables.read().unwrap();
         let table = tables.get(table_name).ok_or_else(|| {
             MiniSqlError::table_not_found(table_name)
         })?;
 
-        let path = self.table_path(table_name);
-        let temp_path = path.with_extension("dat.tmp");
-
-        let file = File::create(&temp_path)?;
-        let mut writer = BufWriter::new(file);
-
-        // Write rows sorted by ID for consistency
-        let mut row_ids: Vec<_> = table.rows.keys().collect();
-        row_ids.sort();
-
-        for id in row_ids {
-            if let Some(row) = table.rows.get(id) {
-                let json = serde_json::to_string(row)?;
-                writeln!(writer, "{}", json)?;
-            }
-        }
-
-        writer.flush()?;
-        fs::rename(temp_path, path)?;
-        
-        Ok(())
+        write_table_file(&self.tables_dir(), table)
     }
 
     /// Save a table asynchronously: spawn a background thread that performs the
@@ -268,8 +620,9 @@ impl StorageEngine {
     /// this method for auto-commit flows instead of blocking.
     pub fn save_table_async(&self, table_name: &str) -> Result<()> {
         let tables = Arc::clone(&self.tables);
+        let file_writes = Arc::clone(&self.file_writes);
         let table_name = table_name.to_string();
-        let path = self.table_path(&table_name);
+        let tables_dir = self.tables_dir();
 
     // Clone the data dir/catalog references we might need; catalog isn't
     // needed for writing rows here but keep for possible future use.
@@ -278,29 +631,13 @@ impl StorageEngine {
         thread::spawn(move || {
             // Perform the same logic as save_table but in the background.
             if let Err(e) = (|| -> Result<()> {
+                let _writing = file_writes.lock().unwrap();
                 let tables_lock = tables.read().unwrap();
                 let table = tables_lock.get(&table_name).ok_or_else(|| {
                     MiniSqlError::table_not_found(&table_name)
                 })?;
 
-                let temp_path = path.with_extension("dat.tmp");
-                let file = File::create(&temp_path)?;
-                let mut writer = BufWriter::new(file);
-
-                let mut row_ids: Vec<_> = table.rows.keys().collect();
-                row_ids.sort();
-
-                for id in row_ids {
-                    if let Some(row) = table.rows.get(id) {
-                        let json = serde_json::to_string(row)?;
-                        writeln!(writer, "{}", json)?;
-                    }
-                }
-
-                writer.flush()?;
-                fs::rename(temp_path, &path)?;
-
-                Ok(())
+                write_table_file(&tables_dir, table)
             })() {
                 log::error!("async save_table('{}') failed: {}", table_name, e);
             }
@@ -325,6 +662,7 @@ impl StorageEngine {
         }
 
         let name = schema.name.clone();
+        let format = schema.storage_format;
         catalog.tables.insert(name.clone(), schema.clone());
         tables.insert(name.clone(), TableData::new(schema));
 
@@ -334,7 +672,7 @@ impl StorageEngine {
         self.save_catalog()?;
         
         // Create empty table file
-        File::create(self.table_path(&name))?;
+        File::create(self.table_path(&name, format))?;
 
         Ok(())
     }
@@ -376,10 +714,9 @@ impl StorageEngine {
 
         self.save_catalog()?;
 
-        // Remove data file
-        let path = self.table_path(table_name);
-        if path.exists() {
-            fs::remove_file(path)?;
+        // Remove data file (in either format)
+        for format in [StorageFormat::Jsonl, StorageFormat::Binary] {
+            remove_if_exists(&self.table_path(table_name, format))?;
         }
 
         Ok(())
@@ -395,12 +732,15 @@ impl StorageEngine {
         }
 
         // Reset auto-increment counter
+        let mut format = StorageFormat::default();
         if let Some(schema) = catalog.tables.get_mut(table_name) {
             schema.auto_increment_counter = 1;
+            format = schema.storage_format;
         }
 
         // Clear all rows and indexes
         if let Some(table) = tables.get_mut(table_name) {
+            self.note_changed_rows(table_name, table.rows.keys().copied());
             table.rows.clear();
             table.indexes.clear();
         }
@@ -411,7 +751,7 @@ impl StorageEngine {
         self.save_catalog()?;
 
         // Truncate data file
-        let path = self.table_path(table_name);
+        let path = self.table_path(table_name, format);
         if path.exists() {
             // Create a new empty file (truncate)
             File::create(path)?;
@@ -435,7 +775,10 @@ impl StorageEngine {
             if !catalog.tables.contains_key(table_name) {
                 return Err(MiniSqlError::table_not_found(table_name));
             }
-            catalog.tables.insert(table_name.to_string(), new_schema);
+            catalog.tables.insert(table_name.to_string(), new_schema.clone());
+        }
+        if let Some(table) = self.tables.write().unwrap().get_mut(table_name) {
+            table.schema = new_schema;
         }
         
         // Persist the change
@@ -443,6 +786,67 @@ impl StorageEngine {
         Ok(())
     }
 
+    /// Rename a column in a table's schema and in the metadata of its indexes
+    /// and of the foreign keys that use it.
+    ///
+    /// Rows and index data are positional, so neither needs rewriting.
+    pub fn rename_column(&self, table_name: &str, old_name: &str, new_name: &str) -> Result<()> {
+        let changed: Vec<TableSchema> = {
+            let mut catalog = self.catalog.write().unwrap();
+            let schema = catalog.tables.get_mut(table_name).ok_or_else(|| {
+                MiniSqlError::table_not_found(table_name)
+            })?;
+            let col_idx = schema.find_column(old_name).ok_or_else(|| {
+                MiniSqlError::unknown_column(old_name, ColumnContext::General)
+            })?;
+            if schema.find_column(new_name).is_some_and(|idx| idx != col_idx) {
+                return Err(MiniSqlError::duplicate_column(new_name));
+            }
+            let old_name = schema.columns[col_idx].name.clone();
+            schema.columns[col_idx].name = new_name.to_string();
+
+            let rename = |columns: &mut Vec<String>| {
+                for column in columns.iter_mut().filter(|c| c.eq_ignore_ascii_case(&old_name)) {
+                    *column = new_name.to_string();
+                }
+            };
+            for index in catalog.indexes.values_mut().filter(|idx| idx.table_name == table_name) {
+                rename(&mut index.columns);
+            }
+
+            let mut changed = Vec::new();
+            for schema in catalog.tables.values_mut() {
+                let is_table = schema.name == table_name;
+                let mut references = false;
+                if is_table {
+                    rename(&mut schema.primary_key);
+                }
+                for fk in schema.foreign_keys.iter_mut() {
+                    if is_table {
+                        rename(&mut fk.columns);
+                    }
+                    if fk.ref_table == table_name {
+                        rename(&mut fk.ref_columns);
+                        references = true;
+                    }
+                }
+                if is_table || references {
+                    changed.push(schema.clone());
+                }
+            }
+            changed
+        };
+        let mut tables = self.tables.write().unwrap();
+        for schema in changed {
+            if let Some(table) = tables.get_mut(&schema.name) {
+                table.schema = schema;
+            }
+        }
+        drop(tables);
+
+        self.save_catalog()
+    }
+
     /// Check if table exists
     pub fn table_exists(&self, table_name: &str) -> bool {
         let catalog = self.catalog.read().unwrap();
@@ -467,24 +871,11 @@ impl StorageEngine {
     pub fn insert_row(&self, table_name: &str, values: Vec<Value>) -> Result<u64> {
         let row_id = self.next_row_id();
         
-        // Get index column indices from catalog
-        let index_col_indices: Vec<(String, Vec<usize>)> = {
-            let catalog = self.catalog.read().unwrap();
-            catalog.indexes.values()
-                .filter(|idx| idx.table_name == table_name)
-                .filter_map(|idx| {
-                    let schema = catalog.tables.get(table_name)?;
-                    let indices: Vec<usize> = idx.columns.iter()
-                        .filter_map(|col| schema.find_column(col))
-                        .collect();
-                    if indices.len() == idx.columns.len() {
-                        Some((idx.name.clone(), indices))
-                    } else {
-                        None
-                    }
-                })
-                .collect()
-        };
+        // Get index column indices from catalog. The catalog stays locked
+        // until the row is in place, so an index published meanwhile by
+        // create_index cannot miss it.
+        let catalog = self.catalog.read().unwrap();
+        let index_col_indices = catalog.index_columns(table_name);
         
         {
             let mut tables = self.tables.write().unwrap();
@@ -511,11 +902,92 @@ impl StorageEngine {
             }
             
             table.rows.insert(row_id, row);
+            self.note_changed_rows(table_name, [row_id]);
         }
 
         Ok(row_id)
     }
 
+    /// Reserve `count` consecutive row ids, returning the first
+    pub fn reserve_row_ids(&self, count: u64) -> u64 {
+        let mut catalog = self.catalog.write().unwrap();
+        let first = catalog.next_row_id;
+        catalog.next_row_id += count;
+        first
+    }
+
+    /// Append many rows at once, returning their row ids in order.
+    ///
+    /// Nothing is inserted if any row has the wrong number of values or
+    /// repeats the `unique_columns` key of an existing row or of another row
+    /// of the batch. Instead of updating the table's indexes row by row, each
+    /// is rebuilt once from the whole table after the rows are added.
+    pub fn insert_rows_bulk(&self, table_name: &str, rows: &[Vec<Value>], unique_columns: &[usize]) -> Result<Vec<u64>> {
+        let first_id = self.reserve_row_ids(rows.len() as u64);
+
+        // Held until the rows and indexes are in place, as in insert_row
+        let catalog = self.catalog.read().unwrap();
+        let index_col_indices = catalog.index_columns(table_name);
+
+        let mut tables = self.tables.write().unwrap();
+        let table = tables.get_mut(table_name).ok_or_else(|| {
+            MiniSqlError::table_not_found(table_name)
+        })?;
+        if let Some(values) = rows.iter().find(|values| values.len() != table.schema.columns.len()) {
+            return Err(MiniSqlError::column_count_mismatch(table.schema.columns.len(), values.len()));
+        }
+
+        // A row with a NULL key value never conflicts (NULL != NULL)
+        if !unique_columns.is_empty() {
+            let unique_key = |values: &[Value]| -> Option<String> {
+                let key_values: Vec<&Value> = unique_columns.iter().map(|&idx| &values[idx]).collect();
+                (!key_values.iter().any(|v| v.is_null())).then(|| build_composite_key(&key_values))
+            };
+            let mut keys: HashSet<String> = table.rows.values()
+                .filter_map(|row| unique_key(&row.values))
+                .collect();
+            for values in rows {
+                if let Some(key) = unique_key(values) {
+                    if !keys.insert(key) {
+                        let key_text: Vec<String> = unique_columns.iter().map(|&idx| values[idx].to_string()).collect();
+                        return Err(MiniSqlError::duplicate_primary_key(&key_text.join("-")));
+                    }
+                }
+            }
+        }
+
+        let row_ids: Vec<u64> = (first_id..first_id + rows.len() as u64).collect();
+        for (row_id, values) in row_ids.iter().zip(rows) {
+            table.rows.insert(*row_id, Row::new(*row_id, values.clone()));
+        }
+
+        for (index_name, col_indices) in &index_col_indices {
+            if let Some(index) = table.indexes.get_mut(index_name) {
+                let mut entries: Vec<(String, u64)> = table.rows.iter()
+                    .map(|(row_id, row)| (build_composite_key_from_row(row, col_indices), *row_id))
+                    .collect();
+                entries.sort_unstable();
+                let mut index_data: IndexData = BTreeMap::new();
+                for (key, row_id) in entries {
+                    index_data.entry(key).or_default().push(row_id);
+                }
+                *index = index_data;
+            }
+        }
+        self.note_changed_rows(table_name, row_ids.iter().copied());
+
+        Ok(row_ids)
+    }
+
+    /// Number of rows a table holds, including those written by
+    /// transactions that have not committed yet
+    pub fn row_count(&self, table_name: &str) -> Result<u64> {
+        let tables = self.tables.read().unwrap();
+        tables.get(table_name)
+            .map(|table| table.rows.len() as u64)
+            .ok_or_else(|| MiniSqlError::table_not_found(table_name))
+    }
+
     /// Get all rows from a table
     pub fn scan_table(&self, table_name: &str) -> Result<Vec<Row>> {
         let tables = self.tables.read().unwrap();
@@ -523,11 +995,32 @@ impl StorageEngine {
             MiniSqlError::table_not_found(table_name)
         })?;
 
+        self.full_scans.fetch_add(1, AtomicOrdering::Relaxed);
         let mut rows: Vec<_> = table.rows.values().cloned().collect();
         rows.sort_by_key(|r| r.id);
         Ok(rows)
     }
 
+    /// Rows of a table in row id order that `keep` accepts; rows it turns
+    /// down are never copied. See `EngineHandler::scan_sampled`.
+    pub fn scan_table_sampled(&self, table_name: &str, keep: &mut dyn FnMut(usize) -> bool) -> Result<Vec<Row>> {
+        let tables = self.tables.read().unwrap();
+        let table = tables.get(table_name).ok_or_else(|| {
+            MiniSqlError::table_not_found(table_name)
+        })?;
+
+        self.full_scans.fetch_add(1, AtomicOrdering::Relaxed);
+        let mut rows: Vec<&Row> = table.rows.values().collect();
+        rows.sort_by_key(|r| r.id);
+        let total = rows.len();
+        Ok(rows.into_iter().enumerate().filter(|&(i, _)| keep(total - i)).map(|(_, r)| r.clone()).collect())
+    }
+
+    /// Number of full table scans performed since startup
+    pub fn full_scan_count(&self) -> u64 {
+        self.full_scans.load(AtomicOrdering::Relaxed)
+    }
+
     /// Get a row by ID
     pub fn get_row(&self, table_name: &str, row_id: u64) -> Result<Option<Row>> {
         let tables = self.tables.read().unwrap();
@@ -540,24 +1033,9 @@ impl StorageEngine {
 
     /// Update a row
     pub fn update_row(&self, table_name: &str, row_id: u64, values: Vec<Value>) -> Result<bool> {
-        // Get index column indices from catalog
-        let index_col_indices: Vec<(String, Vec<usize>)> = {
-            let catalog = self.catalog.read().unwrap();
-            catalog.indexes.values()
-                .filter(|idx| idx.table_name == table_name)
-                .filter_map(|idx| {
-                    let schema = catalog.tables.get(table_name)?;
-                    let indices: Vec<usize> = idx.columns.iter()
-                        .filter_map(|col| schema.find_column(col))
-                        .collect();
-                    if indices.len() == idx.columns.len() {
-                        Some((idx.name.clone(), indices))
-                    } else {
-                        None
-                    }
-                })
-                .collect()
-        };
+        // Get index column indices from catalog, holding it as insert_row does
+        let catalog = self.catalog.read().unwrap();
+        let index_col_indices = catalog.index_columns(table_name);
         
         let mut tables = self.tables.write().unwrap();
         let table = tables.get_mut(table_name).ok_or_else(|| {
@@ -565,6 +1043,7 @@ impl StorageEngine {
         })?;
 
         if let Some(row) = table.rows.get_mut(&row_id) {
+            self.note_changed_rows(table_name, [row_id]);
             let old_row = Row::new(row_id, row.values.clone());
             let new_row = Row::new(row_id, values.clone());
             row.values = values;
@@ -595,24 +1074,9 @@ impl StorageEngine {
 
     /// Delete a row
     pub fn delete_row(&self, table_name: &str, row_id: u64) -> Result<bool> {
-        // Get index column indices from catalog
-        let index_col_indices: Vec<(String, Vec<usize>)> = {
-            let catalog = self.catalog.read().unwrap();
-            catalog.indexes.values()
-                .filter(|idx| idx.table_name == table_name)
-                .filter_map(|idx| {
-                    let schema = catalog.tables.get(table_name)?;
-                    let indices: Vec<usize> = idx.columns.iter()
-                        .filter_map(|col| schema.find_column(col))
-                        .collect();
-                    if indices.len() == idx.columns.len() {
-                        Some((idx.name.clone(), indices))
-                    } else {
-                        None
-                    }
-                })
-                .collect()
-        };
+        // Get index column indices from catalog, holding it as insert_row does
+        let catalog = self.catalog.read().unwrap();
+        let index_col_indices = catalog.index_columns(table_name);
         
         let mut tables = self.tables.write().unwrap();
         let table = tables.get_mut(table_name).ok_or_else(|| {
@@ -620,6 +1084,7 @@ impl StorageEngine {
         })?;
 
         if let Some(row) = table.rows.remove(&row_id) {
+            self.note_changed_rows(table_name, [row_id]);
             // Remove from composite indexes
             for (index_name, col_indices) in &index_col_indices {
                 if let Some(index) = table.indexes.get_mut(index_name) {
@@ -640,11 +1105,31 @@ impl StorageEngine {
 
     /// Restore a row (used during recovery)
     pub fn restore_row(&self, table_name: &str, row: Row) -> Result<()> {
+        // Get index column indices from catalog, and keep row ID allocation
+        // ahead of any row replayed from the WAL
+        let mut catalog = self.catalog.write().unwrap();
+        if row.id >= catalog.next_row_id {
+            catalog.next_row_id = row.id + 1;
+        }
+        let index_col_indices = catalog.index_columns(table_name);
+
         let mut tables = self.tables.write().unwrap();
         let table = tables.get_mut(table_name).ok_or_else(|| {
             MiniSqlError::table_not_found(table_name)
         })?;
 
+        // Add to composite indexes
+        for (index_name, col_indices) in &index_col_indices {
+            if let Some(index) = table.indexes.get_mut(index_name) {
+                let key = build_composite_key_from_row(&row, col_indices);
+                let row_ids = index.entry(key).or_insert_with(Vec::new);
+                if !row_ids.contains(&row.id) {
+                    row_ids.push(row.id);
+                }
+            }
+        }
+
+        self.note_changed_rows(table_name, [row.id]);
         table.rows.insert(row.id, row);
         Ok(())
     }
@@ -689,6 +1174,7 @@ impl StorageEngine {
             })?;
 
             // Clear existing rows
+            self.note_changed_rows(table_name, table.rows.keys().copied());
             table.rows.clear();
             table.indexes.clear();
 
@@ -696,6 +1182,15 @@ impl StorageEngine {
             for (row_id, values) in rows {
                 table.rows.insert(*row_id, Row::new(*row_id, values.clone()));
             }
+            self.note_changed_rows(table_name, rows.iter().map(|(row_id, _)| *row_id));
+        }
+
+        // Keep row ID allocation ahead of rows numbered by the other engine
+        if let Some(max_id) = rows.iter().map(|(row_id, _)| *row_id).max() {
+            let mut catalog = self.catalog.write().unwrap();
+            if max_id >= catalog.next_row_id {
+                catalog.next_row_id = max_id + 1;
+            }
         }
 
         // Save to disk
@@ -710,11 +1205,15 @@ impl StorageEngine {
     }
 
     /// Create an index on a table column
+    ///
+    /// The index is built without blocking queries on the table and only
+    /// becomes visible once it is complete.
     pub fn create_index(&self, index: IndexMetadata, if_not_exists: bool) -> Result<()> {
-        let mut catalog = self.catalog.write().unwrap();
+        let catalog = self.catalog.read().unwrap();
+        let mut builds = self.index_builds.lock().unwrap();
         
-        // Check if index already exists
-        if catalog.indexes.contains_key(&index.name) {
+        // Check if index already exists or is being built
+        if catalog.indexes.contains_key(&index.name) || builds.contains_key(&index.name) {
             if if_not_exists {
                 return Ok(());
             }
@@ -737,11 +1236,16 @@ impl StorageEngine {
             }
         }
         
-        catalog.indexes.insert(index.name.clone(), index.clone());
+        // Claim the name and start recording changed rows before building
+        builds.insert(index.name.clone(), IndexBuild {
+            table_name: index.table_name.clone(),
+            changed_rows: HashSet::new(),
+        });
+        drop(builds);
         drop(catalog);
         
-        // Build the index
-        self.build_composite_index(&index.name, &index.table_name, &index.columns)?;
+        // Build the index and add it to the catalog
+        self.build_composite_index(&index, true)?;
         
         // Save catalog with new index metadata
         self.save_catalog()?;
@@ -771,6 +1275,51 @@ impl StorageEngine {
         
         Ok(())
     }
+
+    /// Grant `privileges` on `table_name` to `user`
+    pub fn grant_privileges(&self, user: &str, table_name: &str, privileges: &[Privilege]) -> Result<()> {
+        {
+            let mut catalog = self.catalog.write().unwrap();
+            if !catalog.tables.contains_key(table_name) {
+                return Err(MiniSqlError::table_not_found(table_name));
+            }
+            catalog.grants
+                .entry(user.to_string())
+                .or_default()
+                .entry(table_name.to_string())
+                .or_default()
+                .extend(privileges);
+        }
+        self.save_catalog()
+    }
+
+    /// Revoke `privileges` on `table_name` from `user`. As in MySQL, this
+    /// fails only when the user holds no privilege on the table at all.
+    pub fn revoke_privileges(&self, user: &str, table_name: &str, privileges: &[Privilege]) -> Result<()> {
+        {
+            let mut catalog = self.catalog.write().unwrap();
+            let tables = catalog.grants.get_mut(user)
+                .filter(|tables| tables.contains_key(table_name))
+                .ok_or_else(|| MiniSqlError::no_such_grant(user, table_name))?;
+            let granted = tables.get_mut(table_name).unwrap();
+            granted.retain(|privilege| !privileges.contains(privilege));
+            if granted.is_empty() {
+                tables.remove(table_name);
+            }
+            if tables.is_empty() {
+                catalog.grants.remove(user);
+            }
+        }
+        self.save_catalog()
+    }
+
+    /// Whether `user` was granted `privilege` on `table_name`
+    pub fn has_privilege(&self, user: &str, table_name: &str, privilege: Privilege) -> bool {
+        let catalog = self.catalog.read().unwrap();
+        catalog.grants.get(user)
+            .and_then(|tables| tables.get(table_name))
+            .is_some_and(|granted| granted.contains(&privilege))
+    }
     
     /// Rebuild all indexes from catalog metadata on startup
     fn rebuild_all_indexes(&self) -> Result<()> {
@@ -794,7 +1343,7 @@ impl StorageEngine {
                    columns_str);
             std::io::stdout().flush().ok();
             
-            match self.build_composite_index(&index_meta.name, &index_meta.table_name, &index_meta.columns) {
+            match self.build_composite_index(index_meta, false) {
                 Ok(_) => {
                     // Count rows in the index to report
                     let tables = self.tables.read().unwrap();
@@ -822,34 +1371,179 @@ impl StorageEngine {
     }
 
     /// Build a composite index for a table with multiple columns
-    fn build_composite_index(&self, index_name: &str, table_name: &str, columns: &[String]) -> Result<()> {
+    ///
+    /// The index keys are read from the rows under the tables read lock, so
+    /// queries keep running for the whole build; the entries are then sorted
+    /// outside any lock and swapped in under a short write lock. Rows written
+    /// since the build started are re-checked at the swap. With `publish`,
+    /// the index is added to the catalog in the same step, after which
+    /// writers maintain it themselves.
+    fn build_composite_index(&self, index: &IndexMetadata, publish: bool) -> Result<()> {
+        self.index_builds.lock().unwrap().entry(index.name.clone()).or_insert_with(|| IndexBuild {
+            table_name: index.table_name.clone(),
+            changed_rows: HashSet::new(),
+        });
+        let result = self.build_and_swap_index(index, publish);
+        if result.is_err() {
+            self.index_builds.lock().unwrap().remove(&index.name);
+        }
+        result
+    }
+
+    fn build_and_swap_index(&self, index: &IndexMetadata, publish: bool) -> Result<()> {
+        let table_name = &index.table_name;
+        let (col_indices, snapshot_keys) = {
+            let tables = self.tables.read().unwrap();
+            let table = tables.get(table_name).ok_or_else(|| {
+                MiniSqlError::table_not_found(table_name)
+            })?;
+
+            // Get column indices
+            let col_indices: Vec<usize> = index.columns.iter()
+                .filter_map(|name| table.schema.find_column(name))
+                .collect();
+
+            if col_indices.len() != index.columns.len() {
+                return Err(MiniSqlError::Syntax("One or more index columns not found".to_string()));
+            }
+
+            let keys: HashMap<u64, String> = table.rows.iter()
+                .map(|(row_id, row)| (*row_id, build_composite_key_from_row(row, &col_indices)))
+                .collect();
+            (col_indices, keys)
+        };
+
+        let mut index_data: IndexData = BTreeMap::new();
+        for (row_id, key) in &snapshot_keys {
+            index_data.entry(key.clone()).or_default().push(*row_id);
+        }
+
+        let mut catalog = publish.then(|| self.catalog.write().unwrap());
         let mut tables = self.tables.write().unwrap();
         let table = tables.get_mut(table_name).ok_or_else(|| {
             MiniSqlError::table_not_found(table_name)
         })?;
-        
-        // Get column indices
-        let col_indices: Vec<usize> = columns.iter()
-            .filter_map(|name| table.schema.find_column(name))
-            .collect();
-        
-        if col_indices.len() != columns.len() {
-            return Err(MiniSqlError::Syntax("One or more index columns not found".to_string()));
+
+        // Catch up with the rows written during the build
+        let changed_rows = self.index_builds.lock().unwrap()
+            .remove(&index.name)
+            .map(|build| build.changed_rows)
+            .unwrap_or_default();
+        for row_id in changed_rows {
+            if let Some(old_key) = snapshot_keys.get(&row_id) {
+                if let Some(row_ids) = index_data.get_mut(old_key) {
+                    row_ids.retain(|&id| id != row_id);
+                    if row_ids.is_empty() {
+                        index_data.remove(old_key);
+                    }
+                }
+            }
+            if let Some(row) = table.rows.get(&row_id) {
+                let key = build_composite_key_from_row(row, &col_indices);
+                index_data.entry(key).or_default().push(row_id);
+            }
         }
-        
-        let mut index_data: IndexData = BTreeMap::new();
-        
-        // Scan all rows and populate the index
-        for (row_id, row) in &table.rows {
-            let key = build_composite_key_from_row(row, &col_indices);
-            index_data.entry(key).or_insert_with(Vec::new).push(*row_id);
+
+        table.indexes.insert(index.name.clone(), index_data);
+        if let Some(catalog) = catalog.as_mut() {
+            catalog.indexes.insert(index.name.clone(), index.clone());
         }
-        
-        table.indexes.insert(index_name.to_string(), index_data);
-        
         Ok(())
     }
+
+    /// Record `row_ids` as changed for every index being built on
+    /// `table_name`. Called with the tables write lock held.
+    fn note_changed_rows(&self, table_name: &str, row_ids: impl IntoIterator<Item = u64>) {
+        let mut builds = self.index_builds.lock().unwrap();
+        let mut building: Vec<&mut IndexBuild> = builds.values_mut()
+            .filter(|build| build.table_name == table_name)
+            .collect();
+        if building.is_empty() {
+            return;
+        }
+        for row_id in row_ids {
+            for build in building.iter_mut() {
+                build.changed_rows.insert(row_id);
+            }
+        }
+    }
     
+    /// Verify that every index agrees with its table: each entry resolves to
+    /// an existing row under that row's current key, and each row is indexed
+    /// exactly once. Returns the anomalies found (empty when consistent).
+    pub fn check_indexes(&self) -> Vec<IndexAnomaly> {
+        let catalog = self.catalog.read().unwrap();
+        let tables = self.tables.read().unwrap();
+        let mut anomalies = Vec::new();
+
+        let mut indexes: Vec<&IndexMetadata> = catalog.indexes.values().collect();
+        indexes.sort_by(|a, b| a.name.cmp(&b.name));
+        for index_meta in indexes {
+            let index = || index_meta.name.clone();
+            let Some(table) = tables.get(&index_meta.table_name) else {
+                anomalies.push(IndexAnomaly::MissingIndex { index: index() });
+                continue;
+            };
+            let Some(index_data) = table.indexes.get(&index_meta.name) else {
+                anomalies.push(IndexAnomaly::MissingIndex { index: index() });
+                continue;
+            };
+            let col_indices: Vec<usize> = index_meta.columns.iter()
+                .filter_map(|name| table.schema.find_column(name))
+                .collect();
+
+            let mut entries: HashMap<u64, usize> = HashMap::new();
+            for (key, row_ids) in index_data {
+                for &row_id in row_ids {
+                    match table.rows.get(&row_id) {
+                        None => anomalies.push(IndexAnomaly::DanglingEntry { index: index(), row_id }),
+                        Some(row) if build_composite_key_from_row(row, &col_indices) != *key => {
+                            anomalies.push(IndexAnomaly::StaleEntry { index: index(), row_id })
+                        }
+                        Some(_) => *entries.entry(row_id).or_insert(0) += 1,
+                    }
+                }
+            }
+
+            let mut row_ids: Vec<u64> = table.rows.keys().copied().collect();
+            row_ids.sort_unstable();
+            for row_id in row_ids {
+                let count = entries.get(&row_id).copied().unwrap_or(0);
+                if count != 1 {
+                    anomalies.push(IndexAnomaly::EntryCount { index: index(), row_id, entries: count });
+                }
+            }
+        }
+
+        anomalies
+    }
+
+    /// Rebuild every index named in `anomalies` from its table's rows.
+    /// Returns the names of the rebuilt indexes.
+    pub fn repair_indexes(&self, anomalies: &[IndexAnomaly]) -> Result<Vec<String>> {
+        let mut names: Vec<String> = anomalies.iter().map(|a| a.index().to_string()).collect();
+        names.sort();
+        names.dedup();
+        for name in &names {
+            let index_meta = self.get_index(name)?;
+            self.build_composite_index(&index_meta, false)?;
+        }
+        Ok(names)
+    }
+
+    /// Rebuild every index on a table from its rows, as needed after
+    /// [`StorageEngine::replace_table_rows`] discards them
+    pub fn rebuild_table_indexes(&self, table_name: &str) -> Result<()> {
+        let indexes: Vec<IndexMetadata> = self.catalog.read().unwrap().indexes.values()
+            .filter(|index| index.table_name == table_name)
+            .cloned()
+            .collect();
+        for index in &indexes {
+            self.build_composite_index(index, false)?;
+        }
+        Ok(())
+    }
+
     /// Get index metadata for a specific index
     pub fn get_index(&self, index_name: &str) -> Result<IndexMetadata> {
         let catalog = self.catalog.read().unwrap();
@@ -873,71 +1567,98 @@ impl StorageEngine {
     /// Supports prefix matching: if you query with fewer columns than the index has,
     /// it will return all rows that match the prefix.
     pub fn get_rows_by_composite_index(&self, table_name: &str, columns: &[String], values: &[Value]) -> Result<Vec<Row>> {
-        let catalog = self.catalog.read().unwrap();
-        let tables = self.tables.read().unwrap();
-        
-        let table = tables.get(table_name).ok_or_else(|| {
-            MiniSqlError::table_not_found(table_name)
-        })?;
-        
-        // Find an index that matches these columns (exact match or prefix)
-        let matching_index = catalog.indexes.values()
-            .find(|idx| idx.table_name == table_name && idx.can_use_for_columns(columns));
-        
-        if let Some(index_meta) = matching_index {
-            if let Some(index_data) = table.indexes.get(&index_meta.name) {
-                // Build the composite key prefix from the provided values
-                let value_refs: Vec<&Value> = values.iter().collect();
-                let key_prefix = build_composite_key(&value_refs);
-                
-                // If we're querying with all index columns, do exact match
-                if columns.len() == index_meta.columns.len() {
-                    if let Some(row_ids) = index_data.get(&key_prefix) {
-                        let rows: Vec<Row> = row_ids
-                            .iter()
-                            .filter_map(|id| table.rows.get(id).cloned())
-                            .collect();
-                        return Ok(rows);
-                    }
-                } else {
-                    // Prefix match: scan all keys that start with this prefix
-                    // Use BTreeMap range to find all matching keys
-                    let prefix_with_separator = format!("{}\x00", key_prefix);
-                    let mut rows = Vec::new();
-                    
-                    for (key, row_ids) in index_data.range(key_prefix.clone()..) {
-                        // Check if this key starts with our prefix
-                        if key.starts_with(&key_prefix) && (key == &key_prefix || key.starts_with(&prefix_with_separator)) {
-                            for row_id in row_ids {
-                                if let Some(row) = table.rows.get(row_id) {
-                                    rows.push(row.clone());
-                                }
-                            }
-                        } else if key > &prefix_with_separator && !key.starts_with(&key_prefix) {
-                            // We've passed all matching keys
-                            break;
-                        }
-                    }
-                    
-                    return Ok(rows);
-                }
-            }
+        // Prefer an index on exactly these columns, then the primary key, so a
+        // full key is a single lookup whichever indexes the table has
+        let index_name = {
+            let catalog = self.catalog.read().unwrap();
+            catalog.indexes.values()
+                .filter(|idx| idx.table_name == table_name && idx.can_use_for_columns(columns))
+                .max_by(|a, b| {
+                    (a.columns.len() == columns.len(), a.is_primary).cmp(&(b.columns.len() == columns.len(), b.is_primary))
+                        .then_with(|| b.name.cmp(&a.name))
+                })
+                .map(|idx| idx.name.clone())
+        };
+        match index_name {
+            Some(index_name) => self.get_rows_by_index_key(table_name, &index_name, values),
+            None if self.tables.read().unwrap().contains_key(table_name) => Ok(Vec::new()),
+            None => Err(MiniSqlError::table_not_found(table_name)),
         }
-        
-        Ok(Vec::new())
     }
-    
+
+    /// Get the rows filed under `values` in `index_name`, one value per
+    /// leading index column. A value for every column is a single key lookup;
+    /// fewer read every key sharing that prefix.
+    pub fn get_rows_by_index_key(&self, table_name: &str, index_name: &str, values: &[Value]) -> Result<Vec<Row>> {
+        let column_count = self.index_column_count(index_name)?;
+        let tables = self.tables.read().unwrap();
+        let table = tables.get(table_name).ok_or_else(|| MiniSqlError::table_not_found(table_name))?;
+        Ok(table.index_key_row_ids(index_name, values, column_count).iter()
+            .filter_map(|id| table.rows.get(id).cloned())
+            .collect())
+    }
+
+    /// Number of rows `get_rows_by_index_key` would return, read from the
+    /// index alone
+    pub fn count_rows_by_index_key(&self, table_name: &str, index_name: &str, values: &[Value]) -> Result<u64> {
+        let column_count = self.index_column_count(index_name)?;
+        let tables = self.tables.read().unwrap();
+        let table = tables.get(table_name).ok_or_else(|| MiniSqlError::table_not_found(table_name))?;
+        Ok(table.index_key_row_ids(index_name, values, column_count).len() as u64)
+    }
+
+    /// Get the rows whose value in the leading column of `index_name` satisfies `keep`.
+    ///
+    /// Every row filed under one key has the same leading value, so `keep` is
+    /// called once per distinct key, and rows it rejects are never cloned.
+    pub fn get_rows_by_index_filter(&self, table_name: &str, index_name: &str, keep: &dyn Fn(&Value) -> bool) -> Result<Vec<Row>> {
+        let column = self.index_leading_column(table_name, index_name)?;
+        let tables = self.tables.read().unwrap();
+        let table = tables.get(table_name).ok_or_else(|| MiniSqlError::table_not_found(table_name))?;
+        Ok(table.index_filter_row_ids(index_name, column, keep).iter()
+            .filter_map(|id| table.rows.get(id).cloned())
+            .collect())
+    }
+
+    /// Number of rows `get_rows_by_index_filter` would return, without cloning any
+    pub fn count_rows_by_index_filter(&self, table_name: &str, index_name: &str, keep: &dyn Fn(&Value) -> bool) -> Result<u64> {
+        let column = self.index_leading_column(table_name, index_name)?;
+        let tables = self.tables.read().unwrap();
+        let table = tables.get(table_name).ok_or_else(|| MiniSqlError::table_not_found(table_name))?;
+        Ok(table.index_filter_row_ids(index_name, column, keep).len() as u64)
+    }
+
+    /// Number of columns in an index key
+    fn index_column_count(&self, index_name: &str) -> Result<usize> {
+        let catalog = self.catalog.read().unwrap();
+        catalog.indexes.get(index_name)
+            .map(|index| index.columns.len())
+            .ok_or_else(|| MiniSqlError::Table(format!("Index '{}' not found", index_name)))
+    }
+
+    /// Position in the table schema of an index's leading column
+    fn index_leading_column(&self, table_name: &str, index_name: &str) -> Result<usize> {
+        let catalog = self.catalog.read().unwrap();
+        let index = catalog.indexes.get(index_name)
+            .ok_or_else(|| MiniSqlError::Table(format!("Index '{}' not found", index_name)))?;
+        let schema = catalog.tables.get(table_name).ok_or_else(|| MiniSqlError::table_not_found(table_name))?;
+        schema.find_column(&index.columns[0])
+            .ok_or_else(|| MiniSqlError::Table(format!("Index '{}' has no column '{}'", index_name, index.columns[0])))
+    }
+
     /// Find the best index for the given columns on a table
     pub fn find_index_for_columns(&self, table_name: &str, columns: &[String]) -> Option<IndexMetadata> {
         let catalog = self.catalog.read().unwrap();
         
-        // Find the best matching index (prefer exact matches, then longer prefix matches)
+        // Find the best matching index (longest prefix match, the primary key on a tie)
         catalog.indexes.values()
             .filter(|idx| idx.table_name == table_name)
             .filter_map(|idx| {
                 idx.matches_columns(columns).map(|matched| (idx.clone(), matched))
             })
-            .max_by_key(|(_, matched)| *matched)
+            .max_by(|(a, a_matched), (b, b_matched)| {
+                (a_matched, a.is_primary).cmp(&(b_matched, b.is_primary)).then_with(|| b.name.cmp(&a.name))
+            })
             .map(|(idx, _)| idx)
     }
 
@@ -972,6 +1693,18 @@ impl StorageEngine {
         Ok(())
     }
 
+    /// Set the auto-increment counter and persist the catalog
+    pub fn set_auto_increment(&self, table_name: &str, value: u64) -> Result<()> {
+        {
+            let mut catalog = self.catalog.write().unwrap();
+            let schema = catalog.tables.get_mut(table_name).ok_or_else(|| {
+                MiniSqlError::table_not_found(table_name)
+            })?;
+            schema.auto_increment_counter = value;
+        }
+        self.save_catalog()
+    }
+
     /// Get the current auto-increment counter value (without incrementing)
     pub fn get_auto_increment(&self, table_name: &str) -> Result<u64> {
         let catalog = self.catalog.read().unwrap();
@@ -989,6 +1722,35 @@ impl StorageEngine {
         values: &[Value],
         exclude_row_id: Option<u64>,
     ) -> Result<bool> {
+        Ok(self.find_unique_conflict(table_name, column_indices, values, exclude_row_id)?.is_some())
+    }
+
+    /// Find the row whose key columns equal those of `values`, i.e. the row an
+    /// insert of `values` would duplicate
+    pub fn find_unique_conflict(
+        &self,
+        table_name: &str,
+        column_indices: &[usize],
+        values: &[Value],
+        exclude_row_id: Option<u64>,
+    ) -> Result<Option<Row>> {
+        // A unique index on exactly these columns, such as the primary key's,
+        // finds the conflict with one key lookup instead of a scan
+        let unique_index = {
+            let catalog = self.catalog.read().unwrap();
+            catalog.tables.get(table_name).and_then(|schema| {
+                let names: Vec<&str> = column_indices.iter()
+                    .filter_map(|&idx| schema.columns.get(idx).map(|c| c.name.as_str()))
+                    .collect();
+                catalog.indexes.values()
+                    .find(|idx| {
+                        idx.table_name == table_name && idx.unique && idx.columns.len() == names.len()
+                            && idx.columns.iter().zip(&names).all(|(a, b)| a.eq_ignore_ascii_case(b))
+                    })
+                    .map(|idx| idx.name.clone())
+            })
+        };
+
         let tables = self.tables.read().unwrap();
         let table = tables.get(table_name).ok_or_else(|| {
             MiniSqlError::table_not_found(table_name)
@@ -1001,33 +1763,28 @@ impl StorageEngine {
 
         // If any key value is NULL, no uniqueness violation (NULL != NULL)
         if key_values.iter().any(|v| v.is_null()) {
-            return Ok(false);
+            return Ok(None);
         }
 
-        // Check for duplicate in existing rows
-        for (row_id, row) in &table.rows {
-            // Skip the row we're updating
-            if let Some(exclude_id) = exclude_row_id {
-                if *row_id == exclude_id {
-                    continue;
-                }
-            }
-
-            // Check if all key columns match
-            let matches = column_indices.iter().enumerate().all(|(_, &col_idx)| {
-                if col_idx < row.values.len() && col_idx < values.len() {
-                    row.values[col_idx] == values[col_idx]
-                } else {
-                    false
-                }
-            });
+        // Check if all key columns match, skipping the row we're updating
+        let conflicts = |row_id: u64, row: &Row| {
+            exclude_row_id != Some(row_id) && column_indices.iter().all(|&col_idx| {
+                col_idx < row.values.len() && col_idx < values.len() && row.values[col_idx] == values[col_idx]
+            })
+        };
 
-            if matches {
-                return Ok(true); // Duplicate found
-            }
+        if let Some(index_data) = unique_index.and_then(|name| table.indexes.get(&name)) {
+            let row_ids = index_data.get(&build_composite_key(&key_values));
+            return Ok(row_ids.into_iter().flatten()
+                .filter_map(|id| table.rows.get(id).map(|row| (*id, row)))
+                .find(|(id, row)| conflicts(*id, row))
+                .map(|(_, row)| row.clone()));
         }
 
-        Ok(false) // No duplicate
+        // Check for duplicate in existing rows
+        Ok(table.rows.iter()
+            .find(|(id, row)| conflicts(**id, row))
+            .map(|(_, row)| row.clone()))
     }
 
     /// Check if index exists for the given column (single column, backward compatible)
@@ -1079,6 +1836,12 @@ impl StorageEngine {
     /// Note: This does NOT reset auto-increment counters or primary key values.
     /// Foreign key relationships based on primary keys remain intact.
     pub fn vacuum(&self) -> Result<()> {
+        self.vacuum_with_format(None)
+    }
+
+    /// Vacuum the database, optionally rewriting every table in `storage_format`
+    /// (e.g. to migrate JSONL tables to the binary format).
+    pub fn vacuum_with_format(&self, storage_format: Option<StorageFormat>) -> Result<()> {
         println!("VACUUM: Starting database rebuild...");
         
         // Get list of all tables
@@ -1133,10 +1896,21 @@ impl StorageEngine {
                 total_rows += new_rows.len() as u64;
 
                 // Replace old rows with new rows
+                self.note_changed_rows(table_name, row_ids.iter().copied().chain(new_rows.keys().copied()));
                 table.rows = new_rows;
 
                 // Clear all indexes (they will be rebuilt)
                 table.indexes.clear();
+
+                if let Some(format) = storage_format {
+                    table.schema.storage_format = format;
+                }
+            }
+
+            if let Some(format) = storage_format {
+                if let Some(schema) = self.catalog.write().unwrap().tables.get_mut(table_name) {
+                    schema.storage_format = format;
+                }
             }
 
             // Save the rebuilt table to disk
@@ -1171,19 +1945,31 @@ impl Clone for StorageEngine {
             data_dir: self.data_dir.clone(),
             tables: Arc::clone(&self.tables),
             catalog: Arc::clone(&self.catalog),
+            full_scans: Arc::clone(&self.full_scans),
+            index_builds: Arc::clone(&self.index_builds),
+            file_writes: Arc::clone(&self.file_writes),
         }
     }
 }
 
 /// Convert a Value to an index key (string representation for BTreeMap ordering)
+///
+/// Integral floats use the integer encoding, so keys agree with `Value`
+/// equality (`1 = 1.0`) and an index lookup finds rows of either type.
 pub fn value_to_index_key(value: &Value) -> String {
     match value {
+        Value::Float(f) if float_as_exact_int(*f).is_some() => {
+            value_to_index_key(&Value::Integer(*f as i64))
+        }
         Value::Null => "\0NULL".to_string(),
         Value::Integer(n) => format!("I{:020}", n), // Pad integers for proper sorting
         Value::Float(f) => format!("F{:020}", f.to_bits()), // Convert float bits for sorting
         Value::String(s) => format!("S{}", s),
         Value::Boolean(b) => format!("B{}", if *b { "1" } else { "0" }),
         Value::Json(j) => format!("J{}", j.to_string()),
+        Value::Date(d) => format!("D{}", d),
+        // Hex digits keep the keys in byte order
+        Value::Bytes(b) => format!("X{}", hex_encode(b)),
     }
 }
 
@@ -1230,7 +2016,11 @@ mod tests {
                     auto_increment: false,
                 },
             ],
-            auto_increment_counter: 1, engine_type: crate::engines::EngineType::default(),
+            auto_increment_counter: 1,
+            engine_type: crate::engines::EngineType::default(),
+            storage_format: crate::types::StorageFormat::default(),
+            foreign_keys: Vec::new(),
+            primary_key: Vec::new(),
         }
     }
 
@@ -1345,6 +2135,13 @@ mod tests {
         assert_eq!(rows.len(), 0);
     }
     
+    #[test]
+    fn test_index_key_matches_numeric_equality() {
+        assert_eq!(value_to_index_key(&Value::Float(42.0)), value_to_index_key(&Value::Integer(42)));
+        assert_ne!(value_to_index_key(&Value::Float(42.5)), value_to_index_key(&Value::Integer(42)));
+        assert_ne!(value_to_index_key(&Value::Float(1e300)), value_to_index_key(&Value::Integer(i64::MAX)));
+    }
+
     #[test]
     fn test_composite_index_creation_and_lookup() {
         let dir = tempdir().unwrap();
@@ -1395,7 +2192,11 @@ mod tests {
                     auto_increment: false,
                 },
             ],
-            auto_increment_counter: 1, engine_type: crate::engines::EngineType::default(),
+            auto_increment_counter: 1,
+            engine_type: crate::engines::EngineType::default(),
+            storage_format: crate::types::StorageFormat::default(),
+            foreign_keys: Vec::new(),
+            primary_key: Vec::new(),
         };
         engine.create_table(schema, false).unwrap();
         
@@ -1520,4 +2321,149 @@ mod tests {
         ).unwrap();
         assert_eq!(rows.len(), 0);
     }
+
+    #[test]
+    fn test_legacy_single_column_index_catalog() {
+        let dir = tempdir().unwrap();
+        let legacy_catalog = serde_json::json!({
+            "tables": {
+                "users": create_test_schema("users")
+            },
+            "indexes": {
+                "idx_name": {
+                    "name": "idx_name",
+                    "table_name": "users",
+                    "column": "name",
+                    "unique": false
+                },
+                "idx_legacy_id": {
+                    "name": "idx_legacy_id",
+                    "table": "users",
+                    "column_name": "id"
+                }
+            },
+            "next_row_id": 3
+        });
+        fs::write(
+            dir.path().join("catalog.json"),
+            serde_json::to_string_pretty(&legacy_catalog).unwrap(),
+        ).unwrap();
+        fs::create_dir_all(dir.path().join("tables")).unwrap();
+        let rows = [
+            Row::new(1, vec![Value::Integer(1), Value::String("Alice".into())]),
+            Row::new(2, vec![Value::Integer(2), Value::String("Bob".into())]),
+        ];
+        let data: String = rows.iter()
+            .map(|r| serde_json::to_string(r).unwrap() + "\n")
+            .collect();
+        fs::write(dir.path().join("tables").join("users.dat"), data).unwrap();
+
+        let engine = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+
+        let index = engine.find_index_for_columns("users", &["name".to_string()]).unwrap();
+        assert_eq!(index.columns, vec!["name".to_string()]);
+        let rows = engine.get_rows_by_index("users", "name", &Value::String("Bob".into())).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].id, 2);
+
+        let rows = engine.get_rows_by_index("users", "id", &Value::Integer(1)).unwrap();
+        assert_eq!(rows.len(), 1);
+
+        // Saving writes the normalized composite representation
+        engine.save_catalog().unwrap();
+        let saved: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(dir.path().join("catalog.json")).unwrap(),
+        ).unwrap();
+        assert_eq!(saved["indexes"]["idx_name"]["columns"], serde_json::json!(["name"]));
+        assert!(saved["indexes"]["idx_name"].get("column").is_none());
+    }
+
+    #[test]
+    fn test_check_indexes_detects_and_repairs_inconsistent_entries() {
+        let dir = tempdir().unwrap();
+        let engine = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+        engine.create_table(create_test_schema("people"), false).unwrap();
+        let alice = engine.insert_row("people", vec![Value::Integer(1), Value::String("Alice".into())]).unwrap();
+        let bob = engine.insert_row("people", vec![Value::Integer(2), Value::String("Bob".into())]).unwrap();
+        engine.create_index(crate::types::IndexMetadata {
+            name: "idx_people_name".to_string(),
+            table_name: "people".to_string(),
+            columns: vec!["name".to_string()],
+            unique: false,
+            is_primary: false,
+        }, false).unwrap();
+        assert!(engine.check_indexes().is_empty());
+
+        // Corrupt the index: a dangling entry, Bob filed under the wrong key
+        {
+            let mut tables = engine.tables.write().unwrap();
+            let index = tables.get_mut("people").unwrap().indexes.get_mut("idx_people_name").unwrap();
+            index.entry(value_to_index_key(&Value::String("Carol".into()))).or_default().push(999);
+            index.remove(&value_to_index_key(&Value::String("Bob".into())));
+            index.get_mut(&value_to_index_key(&Value::String("Alice".into()))).unwrap().push(bob);
+        }
+
+        let anomalies = engine.check_indexes();
+        let index = "idx_people_name".to_string();
+        assert!(anomalies.contains(&IndexAnomaly::DanglingEntry { index: index.clone(), row_id: 999 }));
+        assert!(anomalies.contains(&IndexAnomaly::StaleEntry { index: index.clone(), row_id: bob }));
+        assert!(anomalies.contains(&IndexAnomaly::EntryCount { index: index.clone(), row_id: bob, entries: 0 }));
+        assert!(!anomalies.iter().any(|a| matches!(a, IndexAnomaly::EntryCount { row_id, .. } if *row_id == alice)));
+
+        assert_eq!(engine.repair_indexes(&anomalies).unwrap(), vec![index]);
+        assert!(engine.check_indexes().is_empty());
+        let rows = engine.get_rows_by_index("people", "name", &Value::String("Bob".into())).unwrap();
+        assert_eq!(rows.len(), 1);
+    }
+
+    #[test]
+    fn test_create_index_captures_concurrent_writes() {
+        use std::sync::atomic::AtomicBool;
+
+        let dir = tempdir().unwrap();
+        let engine = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+        engine.create_table(create_test_schema("people"), false).unwrap();
+        let mut row_ids = Vec::new();
+        for i in 0..20_000 {
+            row_ids.push(engine.insert_row("people", vec![Value::Integer(i), Value::String(format!("name{}", i % 100))]).unwrap());
+        }
+
+        let done = AtomicBool::new(false);
+        let inserted = thread::scope(|scope| {
+            let writer = scope.spawn(|| {
+                // Insert new rows, rename old ones and delete others until the index is live
+                let mut inserted = 0;
+                for i in 20_000i64.. {
+                    engine.insert_row("people", vec![Value::Integer(i), Value::String("late".into())]).unwrap();
+                    let old = row_ids[(i as usize * 7) % row_ids.len()];
+                    if i % 2 == 0 {
+                        engine.update_row("people", old, vec![Value::Integer(i), Value::String("renamed".into())]).unwrap();
+                    } else {
+                        engine.delete_row("people", old).unwrap();
+                    }
+                    inserted += 1;
+                    if done.load(AtomicOrdering::Relaxed) && inserted >= 100 {
+                        break;
+                    }
+                }
+                inserted
+            });
+            engine.create_index(crate::types::IndexMetadata {
+                name: "idx_people_name".to_string(),
+                table_name: "people".to_string(),
+                columns: vec!["name".to_string()],
+                unique: false,
+                is_primary: false,
+            }, false).unwrap();
+            done.store(true, AtomicOrdering::Relaxed);
+            writer.join().unwrap()
+        });
+
+        assert!(engine.check_indexes().is_empty());
+        let late = engine.get_rows_by_index("people", "name", &Value::String("late".into())).unwrap();
+        assert_eq!(late.len(), inserted);
+        let rows = engine.scan_table("people").unwrap();
+        let renamed = rows.iter().filter(|r| r.values[1] == Value::String("renamed".into())).count();
+        assert_eq!(engine.get_rows_by_index("people", "name", &Value::String("renamed".into())).unwrap().len(), renamed);
+    }
 }
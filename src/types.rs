@@ -2,6 +2,7 @@
 //!
 //! Defines SQL data types, values, table schemas, and rows.
 
+use crate::datetime::Date;
 use crate::engines::EngineType;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -24,6 +25,10 @@ pub enum DataType {
     Boolean,
     /// JSON document
     Json,
+    /// Calendar date (`YYYY-MM-DD`)
+    Date,
+    /// Binary data (BLOB, VARBINARY)
+    Blob,
 }
 
 impl fmt::Display for DataType {
@@ -36,6 +41,8 @@ impl fmt::Display for DataType {
             DataType::Text => write!(f, "TEXT"),
             DataType::Boolean => write!(f, "BOOLEAN"),
             DataType::Json => write!(f, "JSON"),
+            DataType::Date => write!(f, "DATE"),
+            DataType::Blob => write!(f, "BLOB"),
         }
     }
 }
@@ -54,12 +61,139 @@ pub enum Value {
     /// Boolean value
     Boolean(bool),
     /// JSON value
-    Json(JsonValue),
+    Json(#[serde(with = "json_text")] JsonValue),
+    /// Date value
+    Date(Date),
+    /// Binary value
+    Bytes(#[serde(with = "bytes_base64")] Vec<u8>),
+}
+
+/// Serde for JSON values: nested as JSON in human-readable formats, and as
+/// text in binary ones (WAL records, replication messages), since bincode
+/// cannot decode a self-describing `serde_json::Value`
+mod json_text {
+    use super::JsonValue;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &JsonValue, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            value.serialize(serializer)
+        } else {
+            serializer.serialize_str(&value.to_string())
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<JsonValue, D::Error> {
+        if deserializer.is_human_readable() {
+            JsonValue::deserialize(deserializer)
+        } else {
+            serde_json::from_str(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+        }
+    }
+}
+
+/// Serde for binary values: base64 text in human-readable formats (JSONL row
+/// files), and raw bytes in binary ones
+mod bytes_base64 {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&STANDARD.encode(bytes))
+        } else {
+            serializer.serialize_bytes(bytes)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        if deserializer.is_human_readable() {
+            STANDARD.decode(String::deserialize(deserializer)?).map_err(D::Error::custom)
+        } else {
+            Vec::<u8>::deserialize(deserializer)
+        }
+    }
+}
+
+/// Uppercase hex digits of `bytes`, two per byte (as MySQL's HEX())
+pub(crate) fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Bytes from hex digits, or None if `hex` has an odd length or a non-hex digit
+pub(crate) fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Compare an integer with a float by exact numeric value.
+///
+/// Casting the integer to f64 would round values beyond 2^53, making e.g.
+/// `9007199254740993 = 9007199254740992.0` true; instead the float is compared
+/// against the integer range and, when integral, as an i64.
+pub(crate) fn compare_int_float(i: i64, f: f64) -> Option<Ordering> {
+    if f.is_nan() {
+        return None;
+    }
+    // i64::MAX as f64 rounds up to 2^63, which is out of range
+    if f >= 9_223_372_036_854_775_808.0 {
+        return Some(Ordering::Less);
+    }
+    if f < -9_223_372_036_854_775_808.0 {
+        return Some(Ordering::Greater);
+    }
+    let truncated = f.trunc();
+    match i.cmp(&(truncated as i64)) {
+        Ordering::Equal => 0.0.partial_cmp(&(f - truncated)),
+        ord => Some(ord),
+    }
+}
+
+/// The integer equal to `f`, if `f` is integral and within i64 range
+pub(crate) fn float_as_exact_int(f: f64) -> Option<i64> {
+    let i = f as i64;
+    if compare_int_float(i, f) == Some(Ordering::Equal) {
+        Some(i)
+    } else {
+        None
+    }
+}
+
+/// The date `s` equals under `Value`'s equality: a date or a datetime at midnight
+fn string_as_date(s: &str) -> Option<Date> {
+    Date::parse(s).filter(|d| d.cmp_text(s) == Some(Ordering::Equal))
 }
 
 // Implement Hash for Value to enable efficient hash-based lookups
 impl Hash for Value {
     fn hash<H: Hasher>(&self, state: &mut H) {
+        // Integral floats equal the matching integer, so they must hash alike
+        if let Value::Float(f) = self {
+            if let Some(i) = float_as_exact_int(*f) {
+                return Value::Integer(i).hash(state);
+            }
+        }
+        // Strings holding a date equal that date, so they hash as the date
+        if let Value::String(s) = self {
+            if let Some(d) = string_as_date(s) {
+                return Value::Date(d).hash(state);
+            }
+        }
+        // Binary values equal the string with the same bytes, so valid UTF-8 hashes as that string
+        if let Value::Bytes(b) = self {
+            if let Ok(s) = std::str::from_utf8(b) {
+                if let Some(d) = string_as_date(s) {
+                    return Value::Date(d).hash(state);
+                }
+                std::mem::discriminant(&Value::String(String::new())).hash(state);
+                return s.hash(state);
+            }
+        }
         // Hash the discriminant first to differentiate types
         std::mem::discriminant(self).hash(state);
         match self {
@@ -69,6 +203,8 @@ impl Hash for Value {
             Value::String(s) => s.hash(state),
             Value::Boolean(b) => b.hash(state),
             Value::Json(j) => j.to_string().hash(state), // Fallback to string for JSON
+            Value::Date(d) => d.hash(state),
+            Value::Bytes(b) => b.hash(state),
         }
     }
 }
@@ -86,9 +222,18 @@ impl PartialEq for Value {
             (Value::String(a), Value::String(b)) => a == b,
             (Value::Boolean(a), Value::Boolean(b)) => a == b,
             (Value::Json(a), Value::Json(b)) => a == b,
+            (Value::Date(a), Value::Date(b)) => a == b,
+            (Value::Bytes(a), Value::Bytes(b)) => a == b,
+            // Binary values compare with strings byte by byte
+            (Value::Bytes(b), Value::String(s)) | (Value::String(s), Value::Bytes(b)) => b == s.as_bytes(),
+            // A date equals a string holding the same date
+            (Value::Date(d), Value::String(s)) | (Value::String(s), Value::Date(d)) => {
+                d.cmp_text(s) == Some(Ordering::Equal)
+            }
             // Cross-type numeric comparison
-            (Value::Integer(a), Value::Float(b)) => (*a as f64) == *b,
-            (Value::Float(a), Value::Integer(b)) => *a == (*b as f64),
+            (Value::Integer(a), Value::Float(b)) | (Value::Float(b), Value::Integer(a)) => {
+                compare_int_float(*a, *b) == Some(Ordering::Equal)
+            }
             // Affinity: String to Integer
             (Value::String(s), Value::Integer(i)) | (Value::Integer(i), Value::String(s)) => {
                 if let Ok(parsed) = s.parse::<i64>() {
@@ -120,9 +265,19 @@ impl PartialOrd for Value {
             (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
             (Value::String(a), Value::String(b)) => a.partial_cmp(b),
             (Value::Boolean(a), Value::Boolean(b)) => a.partial_cmp(b),
+            (Value::Date(a), Value::Date(b)) => a.partial_cmp(b),
+            (Value::Bytes(a), Value::Bytes(b)) => a.partial_cmp(b),
+            (Value::Bytes(b), Value::String(s)) => b.as_slice().partial_cmp(s.as_bytes()),
+            (Value::String(s), Value::Bytes(b)) => s.as_bytes().partial_cmp(b.as_slice()),
+            // Dates compare with date and datetime strings chronologically,
+            // and with other strings as text
+            (Value::Date(d), Value::String(s)) => {
+                Some(d.cmp_text(s).unwrap_or_else(|| d.to_string().as_str().cmp(s)))
+            }
+            (Value::String(_), Value::Date(_)) => other.partial_cmp(self).map(Ordering::reverse),
             // Cross-type numeric comparison
-            (Value::Integer(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
-            (Value::Float(a), Value::Integer(b)) => a.partial_cmp(&(*b as f64)),
+            (Value::Integer(a), Value::Float(b)) => compare_int_float(*a, *b),
+            (Value::Float(a), Value::Integer(b)) => compare_int_float(*b, *a).map(Ordering::reverse),
             // Affinity: String to Integer
             (Value::String(s), Value::Integer(i)) => {
                 if let Ok(parsed) = s.parse::<i64>() {
@@ -169,6 +324,8 @@ impl fmt::Display for Value {
             Value::String(v) => write!(f, "{}", v),
             Value::Boolean(v) => write!(f, "{}", if *v { "TRUE" } else { "FALSE" }),
             Value::Json(v) => write!(f, "{}", v),
+            Value::Date(v) => write!(f, "{}", v),
+            Value::Bytes(v) => write!(f, "{}", String::from_utf8_lossy(v)),
         }
     }
 }
@@ -187,7 +344,8 @@ impl Value {
             Value::Integer(i) => *i != 0,
             Value::Float(f) => *f != 0.0,
             Value::String(s) => !s.is_empty(),
-            Value::Json(_) => true,
+            Value::Bytes(b) => !b.is_empty(),
+            Value::Json(_) | Value::Date(_) => true,
         }
     }
 
@@ -200,6 +358,8 @@ impl Value {
             Value::String(v) => v.clone(),
             Value::Boolean(v) => if *v { "TRUE".to_string() } else { "FALSE".to_string() },
             Value::Json(v) => v.to_string(),
+            Value::Date(v) => v.to_string(),
+            Value::Bytes(v) => String::from_utf8_lossy(v).into_owned(),
         }
     }
 
@@ -212,30 +372,31 @@ impl Value {
             Value::String(v) => Some(v.clone()),
             Value::Boolean(v) => Some(if *v { "1".to_string() } else { "0".to_string() }),
             Value::Json(v) => Some(v.to_string()),
+            Value::Date(v) => Some(v.to_string()),
+            Value::Bytes(v) => Some(String::from_utf8_lossy(v).into_owned()),
         }
     }
 
     /// Extract a field from a JSON value using the -> operator
+    ///
+    /// `key` is either a JSON path such as `$.addresses[0].city` or a bare
+    /// object key / array index. Missing paths and malformed paths yield NULL.
     pub fn json_get(&self, key: &str) -> Value {
-        match self {
-            Value::Json(json) => {
-                if let Some(obj) = json.as_object() {
-                    if let Some(val) = obj.get(key) {
-                        return Value::Json(val.clone());
-                    }
-                }
-                // Also try array index access
-                if let Ok(idx) = key.parse::<usize>() {
-                    if let Some(arr) = json.as_array() {
-                        if let Some(val) = arr.get(idx) {
-                            return Value::Json(val.clone());
-                        }
-                    }
-                }
-                Value::Null
-            }
-            _ => Value::Null,
-        }
+        let Value::Json(json) = self else {
+            return Value::Null;
+        };
+        let found = if key.starts_with('$') {
+            parse_json_path(key).and_then(|steps| {
+                steps.iter().try_fold(json, |node, step| match step {
+                    JsonPathStep::Key(name) => node.get(name.as_str()),
+                    JsonPathStep::Index(idx) => node.get(*idx),
+                })
+            })
+        } else {
+            json.get(key)
+                .or_else(|| key.parse::<usize>().ok().and_then(|idx| json.get(idx)))
+        };
+        found.map_or(Value::Null, |val| Value::Json(val.clone()))
     }
 
     /// Extract a field from a JSON value and return as text (->>) operator
@@ -253,6 +414,44 @@ impl Value {
     }
 }
 
+/// One step of a JSON path
+#[derive(Debug, PartialEq)]
+enum JsonPathStep {
+    /// `.key` or `."quoted key"`
+    Key(String),
+    /// `[index]`
+    Index(usize),
+}
+
+/// Parse a MySQL-style JSON path (`$`, `$.a.b`, `$[0]`, `$."a b"[1].c`)
+fn parse_json_path(path: &str) -> Option<Vec<JsonPathStep>> {
+    let mut rest = path.strip_prefix('$')?;
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            if let Some(quoted) = after_dot.strip_prefix('"') {
+                let end = quoted.find('"')?;
+                steps.push(JsonPathStep::Key(quoted[..end].to_string()));
+                rest = &quoted[end + 1..];
+            } else {
+                let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+                if end == 0 {
+                    return None;
+                }
+                steps.push(JsonPathStep::Key(after_dot[..end].to_string()));
+                rest = &after_dot[end..];
+            }
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let end = after_bracket.find(']')?;
+            steps.push(JsonPathStep::Index(after_bracket[..end].trim().parse().ok()?));
+            rest = &after_bracket[end + 1..];
+        } else {
+            return None;
+        }
+    }
+    Some(steps)
+}
+
 /// A column definition in a table schema
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnDef {
@@ -284,6 +483,16 @@ pub struct TableSchema {
     /// Storage engine type for this table
     #[serde(default = "default_engine_type")]
     pub engine_type: EngineType,
+    /// On-disk encoding of the table's rows
+    #[serde(default)]
+    pub storage_format: StorageFormat,
+    /// Foreign keys declared on this (child) table
+    #[serde(default)]
+    pub foreign_keys: Vec<ForeignKey>,
+    /// Columns of a table-level `PRIMARY KEY (...)` in declared order; empty
+    /// when the key is declared on a column
+    #[serde(default)]
+    pub primary_key: Vec<String>,
 }
 
 /// Default value for auto_increment_counter (for backwards compatibility)
@@ -297,8 +506,132 @@ fn default_engine_type() -> EngineType {
     EngineType::Granite
 }
 
+/// On-disk encoding of a table's row file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StorageFormat {
+    /// One JSON object per line (`.dat`)
+    #[default]
+    Jsonl,
+    /// Length-prefixed bincode records (`.bdat`); smaller and faster to load
+    Binary,
+}
+
+impl StorageFormat {
+    /// Parse a format name as used by `STORAGE FORMAT = ...`, case-insensitively
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "JSONL" | "JSON" => Some(StorageFormat::Jsonl),
+            "BINARY" => Some(StorageFormat::Binary),
+            _ => None,
+        }
+    }
+
+    /// File extension of the table's row file
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StorageFormat::Jsonl => "dat",
+            StorageFormat::Binary => "bdat",
+        }
+    }
+}
+
+impl fmt::Display for StorageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageFormat::Jsonl => write!(f, "JSONL"),
+            StorageFormat::Binary => write!(f, "BINARY"),
+        }
+    }
+}
+
+/// A table privilege granted with GRANT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Privilege {
+    Select,
+    Insert,
+    Update,
+    Delete,
+}
+
+impl Privilege {
+    /// Every table privilege, as granted by `GRANT ALL`
+    pub const ALL: [Privilege; 4] = [Privilege::Select, Privilege::Insert, Privilege::Update, Privilege::Delete];
+}
+
+impl fmt::Display for Privilege {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Privilege::Select => write!(f, "SELECT"),
+            Privilege::Insert => write!(f, "INSERT"),
+            Privilege::Update => write!(f, "UPDATE"),
+            Privilege::Delete => write!(f, "DELETE"),
+        }
+    }
+}
+
+impl std::str::FromStr for StorageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s).ok_or_else(|| {
+            format!("invalid storage format '{}' (expected jsonl or binary)", s)
+        })
+    }
+}
+
+/// What happens to child rows when the parent row they reference is deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ReferentialAction {
+    /// Reject the delete while child rows exist (also `NO ACTION`)
+    #[default]
+    Restrict,
+    /// Delete the child rows as well
+    Cascade,
+}
+
+impl fmt::Display for ReferentialAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferentialAction::Restrict => write!(f, "RESTRICT"),
+            ReferentialAction::Cascade => write!(f, "CASCADE"),
+        }
+    }
+}
+
+/// A foreign key: `columns` of the owning table reference `ref_columns` of `ref_table`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForeignKey {
+    /// Constraint name
+    pub name: String,
+    /// Referencing columns in the child table
+    pub columns: Vec<String>,
+    /// Referenced (parent) table
+    pub ref_table: String,
+    /// Referenced columns in the parent table, matching `columns` by position
+    pub ref_columns: Vec<String>,
+    /// Action on delete of a referenced parent row
+    #[serde(default)]
+    pub on_delete: ReferentialAction,
+}
+
+impl ForeignKey {
+    /// SQL definition, as used by SHOW CREATE TABLE and in constraint errors
+    pub fn constraint_sql(&self) -> String {
+        let quote = |cols: &[String]| cols.iter().map(|c| format!("`{}`", c)).collect::<Vec<_>>().join(", ");
+        let mut sql = format!(
+            "CONSTRAINT `{}` FOREIGN KEY ({}) REFERENCES `{}` ({})",
+            self.name, quote(&self.columns), self.ref_table, quote(&self.ref_columns)
+        );
+        if self.on_delete == ReferentialAction::Cascade {
+            sql.push_str(" ON DELETE CASCADE");
+        }
+        sql
+    }
+}
+
 /// Index metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "IndexMetadataRepr")]
 pub struct IndexMetadata {
     /// Index name
     pub name: String,
@@ -314,6 +647,43 @@ pub struct IndexMetadata {
     pub is_primary: bool,
 }
 
+/// On-disk shape of [`IndexMetadata`].
+///
+/// Catalogs written before composite indexes stored a single `column`
+/// (or `column_name`) string instead of `columns`. Both shapes are accepted
+/// and normalized to the composite representation on load; the catalog is
+/// written back in the current format on the next save.
+#[derive(Deserialize)]
+struct IndexMetadataRepr {
+    name: String,
+    #[serde(alias = "table")]
+    table_name: String,
+    #[serde(default)]
+    columns: Vec<String>,
+    #[serde(default, alias = "column_name")]
+    column: Option<String>,
+    #[serde(default)]
+    unique: bool,
+    #[serde(default)]
+    is_primary: bool,
+}
+
+impl From<IndexMetadataRepr> for IndexMetadata {
+    fn from(repr: IndexMetadataRepr) -> Self {
+        let mut columns = repr.columns;
+        if columns.is_empty() {
+            columns.extend(repr.column);
+        }
+        Self {
+            name: repr.name,
+            table_name: repr.table_name,
+            columns,
+            unique: repr.unique,
+            is_primary: repr.is_primary,
+        }
+    }
+}
+
 impl IndexMetadata {
     /// Check if this index covers the given columns (exact match or prefix match)
     /// Returns Some(n) where n is the number of columns that match as a prefix
@@ -375,6 +745,9 @@ impl TableSchema {
 
     /// Get primary key column indices
     pub fn primary_key_columns(&self) -> Vec<usize> {
+        if !self.primary_key.is_empty() {
+            return self.primary_key.iter().filter_map(|name| self.find_column(name)).collect();
+        }
         self.columns
             .iter()
             .enumerate()
@@ -448,6 +821,94 @@ impl ResultSet {
     }
 }
 
+/// Transaction isolation level of a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IsolationLevel {
+    /// Accepted for compatibility; behaves like READ COMMITTED
+    ReadUncommitted,
+    /// Every statement reads the latest row versions (default)
+    #[default]
+    ReadCommitted,
+    /// Granite rows are snapshotted on first read within a transaction
+    RepeatableRead,
+    /// Accepted for compatibility; downgraded to REPEATABLE READ
+    Serializable,
+}
+
+impl IsolationLevel {
+    /// Parse a level name as used by `transaction_isolation`
+    /// ('READ-COMMITTED', 'REPEATABLE READ', ...), case-insensitively
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_uppercase().replace(['-', '_'], " ").as_str() {
+            "READ UNCOMMITTED" => Some(IsolationLevel::ReadUncommitted),
+            "READ COMMITTED" => Some(IsolationLevel::ReadCommitted),
+            "REPEATABLE READ" => Some(IsolationLevel::RepeatableRead),
+            "SERIALIZABLE" => Some(IsolationLevel::Serializable),
+            _ => None,
+        }
+    }
+
+    /// Whether reads inside a transaction are served from a snapshot
+    pub fn uses_snapshot(&self) -> bool {
+        matches!(self, IsolationLevel::RepeatableRead | IsolationLevel::Serializable)
+    }
+}
+
+impl fmt::Display for IsolationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IsolationLevel::ReadUncommitted => write!(f, "READ-UNCOMMITTED"),
+            IsolationLevel::ReadCommitted => write!(f, "READ-COMMITTED"),
+            IsolationLevel::RepeatableRead => write!(f, "REPEATABLE-READ"),
+            IsolationLevel::Serializable => write!(f, "SERIALIZABLE"),
+        }
+    }
+}
+
+/// SQL mode of a session, set with `SET sql_mode = '...'`. ANSI_QUOTES (also
+/// implied by ANSI) makes double quotes quote identifiers instead of strings,
+/// and the strict modes reject strings too long for their column instead of
+/// truncating them. Other modes are kept so `@@sql_mode` reports them back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlMode {
+    modes: Vec<String>,
+}
+
+impl SqlMode {
+    /// Parse a comma-separated list of modes, case-insensitively
+    pub fn parse(text: &str) -> Self {
+        let mut modes: Vec<String> = Vec::new();
+        for mode in text.split(',').map(|m| m.trim().to_uppercase()) {
+            if !mode.is_empty() && !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
+        SqlMode { modes }
+    }
+
+    /// Whether double quotes delimit identifiers rather than strings
+    pub fn ansi_quotes(&self) -> bool {
+        self.modes.iter().any(|m| m == "ANSI_QUOTES" || m == "ANSI")
+    }
+
+    /// Whether invalid writes fail rather than being adjusted to fit
+    pub fn strict(&self) -> bool {
+        self.modes.iter().any(|m| m == "STRICT_TRANS_TABLES" || m == "STRICT_ALL_TABLES" || m == "TRADITIONAL")
+    }
+}
+
+impl Default for SqlMode {
+    fn default() -> Self {
+        SqlMode::parse("ONLY_FULL_GROUP_BY,STRICT_TRANS_TABLES")
+    }
+}
+
+impl fmt::Display for SqlMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.modes.join(","))
+    }
+}
+
 /// Query execution result
 #[derive(Debug)]
 pub enum QueryResult {
@@ -496,6 +957,20 @@ mod tests {
         assert!(Value::Null < Value::Integer(1));
     }
 
+    #[test]
+    fn test_json_value_serde_round_trip() {
+        let value = Value::Json(json!({"a": [1, 2.5, null], "b": {"c": "x"}}));
+
+        // JSON row files keep the document nested
+        let text = serde_json::to_string(&value).unwrap();
+        assert_eq!(text, r#"{"Json":{"a":[1,2.5,null],"b":{"c":"x"}}}"#);
+        assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), value);
+
+        // bincode cannot decode a nested document, so it carries the text
+        let bytes = bincode::serialize(&value).unwrap();
+        assert_eq!(bincode::deserialize::<Value>(&bytes).unwrap(), value);
+    }
+
     #[test]
     fn test_json_get() {
         let val = Value::Json(json!({"a": 1, "b": {"c": 2}, "d": [10, 20]}));
@@ -506,6 +981,29 @@ mod tests {
         assert_eq!(val.json_get("nonexistent"), Value::Null);
     }
 
+    #[test]
+    fn test_json_get_path() {
+        let val = Value::Json(json!({
+            "name": "Ann",
+            "addresses": [{"city": "Oslo"}, {"city": "Bergen", "zip": null}],
+            "a b": {"c": [1, [2, 3]]}
+        }));
+
+        assert_eq!(val.json_get("$"), val);
+        assert_eq!(val.json_get("$.name"), Value::Json(json!("Ann")));
+        assert_eq!(val.json_get("$.addresses[1].city"), Value::Json(json!("Bergen")));
+        assert_eq!(val.json_get("$.addresses[1].zip"), Value::Json(json!(null)));
+        assert_eq!(val.json_get("$.\"a b\".c[1][0]"), Value::Json(json!(2)));
+        assert_eq!(Value::Json(json!([5, 6])).json_get("$[1]"), Value::Json(json!(6)));
+
+        assert_eq!(val.json_get("$.addresses[2].city"), Value::Null);
+        assert_eq!(val.json_get("$.name.first"), Value::Null);
+        assert_eq!(val.json_get("$.addresses.city"), Value::Null);
+        assert_eq!(val.json_get("$.missing[0]"), Value::Null);
+        assert_eq!(val.json_get("$..name"), Value::Null);
+        assert_eq!(val.json_get("$.addresses[x]"), Value::Null);
+    }
+
     #[test]
     fn test_json_get_text() {
         let val = Value::Json(json!({"a": "hello", "b": 123}));
@@ -514,6 +1012,70 @@ mod tests {
         assert_eq!(val.json_get_text("b"), Value::String("123".into()));
     }
 
+    #[test]
+    fn test_integer_float_comparison() {
+        assert_eq!(Value::Integer(1), Value::Float(1.0));
+        assert_eq!(Value::Float(-7.0), Value::Integer(-7));
+        assert_ne!(Value::Integer(1), Value::Float(1.5));
+        assert!(Value::Integer(1) < Value::Float(1.5));
+        assert!(Value::Float(-1.5) < Value::Integer(-1));
+        assert!(Value::Float(2.5) > Value::Integer(2));
+
+        // Integers beyond 2^53 are not rounded to the nearest float
+        let big = 9_007_199_254_740_993_i64;
+        assert_ne!(Value::Integer(big), Value::Float(9_007_199_254_740_992.0));
+        assert!(Value::Integer(big) > Value::Float(9_007_199_254_740_992.0));
+        assert!(Value::Integer(i64::MAX) < Value::Float(9.3e18));
+        assert!(Value::Integer(i64::MIN) > Value::Float(-1e19));
+        assert_eq!(Value::Integer(1).partial_cmp(&Value::Float(f64::NAN)), None);
+
+        // Non-numeric types stay distinct
+        assert_ne!(Value::Integer(1), Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_integer_float_hash_consistent() {
+        use std::collections::HashMap;
+
+        let mut map: HashMap<Value, &str> = HashMap::new();
+        map.insert(Value::Integer(3), "three");
+        map.insert(Value::Float(2.5), "two and a half");
+
+        assert_eq!(map.get(&Value::Float(3.0)), Some(&"three"));
+        assert_eq!(map.get(&Value::Float(2.5)), Some(&"two and a half"));
+        assert_eq!(map.get(&Value::Float(3.5)), None);
+    }
+
+    #[test]
+    fn test_date_string_hash_consistent() {
+        use std::collections::HashMap;
+
+        let date = Value::Date(Date::parse("2024-03-01").unwrap());
+        let mut map: HashMap<Value, &str> = HashMap::new();
+        map.insert(date.clone(), "march");
+
+        for text in ["2024-03-01", "2024-03-01 00:00:00"] {
+            assert_eq!(Value::String(text.into()), date);
+            assert_eq!(map.get(&Value::String(text.into())), Some(&"march"));
+        }
+        assert_eq!(map.get(&Value::String("2024-03-01 12:00:00".into())), None);
+        assert_eq!(map.get(&Value::String("march".into())), None);
+    }
+
+    #[test]
+    fn test_bytes_string_hash_consistent() {
+        use std::collections::HashMap;
+
+        let mut map: HashMap<Value, &str> = HashMap::new();
+        map.insert(Value::String("abc".into()), "text");
+        map.insert(Value::Bytes(vec![0xff, 0x00]), "binary");
+
+        assert_eq!(Value::Bytes(b"abc".to_vec()), Value::String("abc".into()));
+        assert_eq!(map.get(&Value::Bytes(b"abc".to_vec())), Some(&"text"));
+        assert_eq!(map.get(&Value::Bytes(vec![0xff, 0x00])), Some(&"binary"));
+        assert_eq!(map.get(&Value::Bytes(b"abd".to_vec())), None);
+    }
+
     #[test]
     fn test_value_as_hashmap_key() {
         use std::collections::HashMap;
@@ -0,0 +1,176 @@
+//! Tests for aggregate functions over non-numeric columns, NULLs and empty groups, and GROUP_CONCAT
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{DataType, QueryResult, ResultSet, Value};
+use tempfile::tempdir;
+
+fn create_test_executor() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    (Executor::new(storage, txn_manager), Session::new(), dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select(executor: &Executor, session: &mut Session, sql: &str) -> ResultSet {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs,
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn text(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn create_users(executor: &Executor, session: &mut Session) {
+    execute(executor, session,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, team INTEGER, score INTEGER)");
+    execute(executor, session,
+        "INSERT INTO users VALUES (1, 'mia', 1, NULL), (2, 'ben', 1, NULL), (3, NULL, 2, NULL), \
+         (4, 'zoe', 2, NULL), (5, NULL, 3, NULL)");
+}
+
+#[test]
+fn test_min_max_over_text() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_users(&executor, &mut session);
+
+    let rs = select(&executor, &mut session, "SELECT MAX(name), MIN(name) FROM users");
+    assert_eq!(rs.column_types, vec![DataType::Text, DataType::Text]);
+    assert_eq!(rs.rows, vec![vec![text("zoe"), text("ben")]]);
+
+    // Lexicographic, not numeric, for text
+    execute(&executor, &mut session, "CREATE TABLE codes (id INTEGER PRIMARY KEY, code TEXT)");
+    execute(&executor, &mut session, "INSERT INTO codes VALUES (1, '9'), (2, '10'), (3, '100')");
+    let rs = select(&executor, &mut session, "SELECT MIN(code), MAX(code) FROM codes");
+    assert_eq!(rs.rows, vec![vec![text("10"), text("9")]]);
+}
+
+#[test]
+fn test_min_max_ignore_nulls() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_users(&executor, &mut session);
+
+    let mut rs = select(&executor, &mut session, "SELECT team, MIN(name), MAX(name) FROM users GROUP BY team");
+    rs.rows.sort_by_key(|row| match row[0] {
+        Value::Integer(team) => team,
+        _ => panic!("Expected integer team"),
+    });
+    assert_eq!(rs.rows, vec![
+        vec![Value::Integer(1), text("ben"), text("mia")],
+        // NULL names are skipped
+        vec![Value::Integer(2), text("zoe"), text("zoe")],
+        // A group with only NULLs gives NULL
+        vec![Value::Integer(3), Value::Null, Value::Null],
+    ]);
+
+    // An all-NULL column gives NULL, not a default such as 0
+    let rs = select(&executor, &mut session, "SELECT MAX(score), MIN(score), SUM(score), COUNT(score) FROM users");
+    assert_eq!(rs.rows, vec![vec![Value::Null, Value::Null, Value::Null, Value::Integer(0)]]);
+}
+
+#[test]
+fn test_aggregates_over_empty_input() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_users(&executor, &mut session);
+
+    // Without GROUP BY there is always one row
+    let rs = select(&executor, &mut session,
+        "SELECT MIN(name), MAX(name), COUNT(*), AVG(score) FROM users WHERE id > 100");
+    assert_eq!(rs.rows, vec![vec![Value::Null, Value::Null, Value::Integer(0), Value::Null]]);
+
+    // With GROUP BY, no input means no groups
+    let rs = select(&executor, &mut session, "SELECT team, MAX(name) FROM users WHERE id > 100 GROUP BY team");
+    assert!(rs.rows.is_empty());
+}
+
+#[test]
+fn test_aggregate_argument_count() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_users(&executor, &mut session);
+
+    let err = executor.execute(Parser::parse("SELECT MIN() FROM users").unwrap(), &mut session).unwrap_err();
+    assert_eq!(err.mysql_error_code(), 1582);
+}
+
+fn create_products(executor: &Executor, session: &mut Session) {
+    execute(executor, session, "CREATE TABLE products (id INTEGER PRIMARY KEY, category TEXT, name TEXT, price INTEGER)");
+    execute(executor, session,
+        "INSERT INTO products VALUES (1, 'fruit', 'apple', 3), (2, 'veg', 'leek', 2), (3, 'fruit', 'pear', 4), \
+         (4, 'fruit', NULL, 5), (5, 'veg', 'kale', 1), (6, 'misc', NULL, NULL)");
+}
+
+fn sorted_by_first_column(mut rs: ResultSet) -> Vec<Vec<Value>> {
+    rs.rows.sort_by_key(|row| row[0].to_string());
+    rs.rows
+}
+
+#[test]
+fn test_group_concat() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_products(&executor, &mut session);
+
+    let rs = select(&executor, &mut session, "SELECT category, GROUP_CONCAT(name) FROM products GROUP BY category");
+    assert_eq!(rs.column_types, vec![DataType::Text, DataType::Text]);
+    // Scan order within each group; NULLs are skipped and an all-NULL group gives NULL
+    assert_eq!(sorted_by_first_column(rs), vec![
+        vec![text("fruit"), text("apple,pear")],
+        vec![text("misc"), Value::Null],
+        vec![text("veg"), text("leek,kale")],
+    ]);
+
+    // Non-text values are rendered as text
+    let rs = select(&executor, &mut session, "SELECT GROUP_CONCAT(price) FROM products WHERE category = 'fruit'");
+    assert_eq!(rs.rows, vec![vec![text("3,4,5")]]);
+}
+
+#[test]
+fn test_group_concat_separator() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_products(&executor, &mut session);
+
+    let rs = select(&executor, &mut session,
+        "SELECT category, GROUP_CONCAT(name SEPARATOR ' | ') AS names FROM products GROUP BY category");
+    assert_eq!(rs.columns, vec!["category", "names"]);
+    assert_eq!(sorted_by_first_column(rs), vec![
+        vec![text("fruit"), text("apple | pear")],
+        vec![text("misc"), Value::Null],
+        vec![text("veg"), text("leek | kale")],
+    ]);
+
+    let rs = select(&executor, &mut session, "SELECT GROUP_CONCAT(id SEPARATOR '') FROM products");
+    assert_eq!(rs.rows, vec![vec![text("123456")]]);
+
+    assert!(Parser::parse("SELECT GROUP_CONCAT(name SEPARATOR ;) FROM products").is_err());
+    assert!(Parser::parse("SELECT GROUP_CONCAT(name, id) FROM products").is_err());
+}
+
+#[test]
+fn test_count_and_sum_distinct() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_products(&executor, &mut session);
+
+    // Repeated categories are counted once; plain COUNT still counts every non-NULL value
+    let rs = select(&executor, &mut session, "SELECT COUNT(DISTINCT category), COUNT(category), COUNT(*) FROM products");
+    assert_eq!(rs.rows, vec![vec![Value::Integer(3), Value::Integer(6), Value::Integer(6)]]);
+    assert_eq!(rs.columns, vec!["COUNT(DISTINCT ...)", "COUNT(...)", "COUNT(*)"]);
+
+    execute(&executor, &mut session, "INSERT INTO products VALUES (7, 'veg', 'kale', 2), (8, NULL, NULL, 3)");
+    let rs = select(&executor, &mut session,
+        "SELECT COUNT(DISTINCT category), SUM(DISTINCT price), SUM(price), COUNT(DISTINCT name) FROM products");
+    // NULLs are excluded: 3 categories, prices {1..5}, names {apple, leek, pear, kale}
+    assert_eq!(rs.rows, vec![vec![Value::Integer(3), Value::Integer(15), Value::Integer(20), Value::Integer(4)]]);
+
+    let rs = select(&executor, &mut session,
+        "SELECT category, COUNT(DISTINCT name), COUNT(name) FROM products WHERE category = 'veg' GROUP BY category");
+    assert_eq!(rs.rows, vec![vec![text("veg"), Value::Integer(2), Value::Integer(3)]]);
+    assert_eq!(rs.columns, vec!["category", "COUNT(DISTINCT ...)", "COUNT(...)"]);
+}
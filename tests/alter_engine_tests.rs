@@ -38,6 +38,7 @@ fn test_parse_alter_table_engine_sandstone() {
                 minisql::parser::AlterTableAction::ChangeEngine(engine) => {
                     assert_eq!(engine, EngineType::Sandstone);
                 }
+                other => panic!("Expected ChangeEngine, got {:?}", other),
             }
         }
         _ => panic!("Expected AlterTable statement"),
@@ -54,6 +55,7 @@ fn test_parse_alter_table_engine_granite() {
                 minisql::parser::AlterTableAction::ChangeEngine(engine) => {
                     assert_eq!(engine, EngineType::Granite);
                 }
+                other => panic!("Expected ChangeEngine, got {:?}", other),
             }
         }
         _ => panic!("Expected AlterTable statement"),
@@ -79,6 +81,7 @@ fn test_parse_alter_table_engine_case_insensitive() {
                     minisql::parser::AlterTableAction::ChangeEngine(engine) => {
                         assert_eq!(engine, expected_engine, "Failed for SQL: {}", sql);
                     }
+                    other => panic!("Expected ChangeEngine for SQL {}, got {:?}", sql, other),
                 }
             }
             _ => panic!("Expected AlterTable statement for SQL: {}", sql),
@@ -219,3 +222,92 @@ fn test_alter_sandstone_not_enabled_error() {
     assert!(err_msg.contains("Sandstone") && err_msg.contains("not enabled"), 
             "Expected 'Sandstone' and 'not enabled' in error message, got: {}", err_msg);
 }
+
+fn run(executor: &Executor, session: &mut Session, sql: &str) -> minisql::types::QueryResult {
+    executor.execute(Parser::parse(sql).unwrap(), session)
+        .unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn names(executor: &Executor, session: &mut Session, sql: &str) -> Vec<String> {
+    match run(executor, session, sql) {
+        minisql::types::QueryResult::Select(rs) => rs.rows.iter().map(|r| r[0].to_string()).collect(),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_migrate_populated_table_both_directions() {
+    let (temp_dir, executor) = setup_sandstone_executor();
+    let mut session = Session::new();
+
+    run(&executor, &mut session, "CREATE TABLE people (id INT PRIMARY KEY, name TEXT)");
+    run(&executor, &mut session, "CREATE INDEX idx_name ON people (name)");
+    run(&executor, &mut session, "INSERT INTO people VALUES (1, 'Alice'), (2, 'Bob'), (3, 'Carol')");
+
+    // The migration writes the table out, so it is refused inside a transaction
+    run(&executor, &mut session, "BEGIN");
+    assert!(executor.execute(Parser::parse("ALTER TABLE people ENGINE = Sandstone").unwrap(), &mut session).is_err());
+    run(&executor, &mut session, "ROLLBACK");
+
+    // Granite -> Sandstone, then change rows that only Sandstone holds so far
+    run(&executor, &mut session, "ALTER TABLE people ENGINE = Sandstone");
+    assert_eq!(executor.storage().get_schema("people").unwrap().engine_type, EngineType::Sandstone);
+    run(&executor, &mut session, "INSERT INTO people VALUES (4, 'Dave')");
+    run(&executor, &mut session, "UPDATE people SET name = 'Bobby' WHERE id = 2");
+    run(&executor, &mut session, "DELETE FROM people WHERE id = 3");
+    assert_eq!(names(&executor, &mut session, "SELECT name FROM people ORDER BY id"), vec!["Alice", "Bobby", "Dave"]);
+
+    // Sandstone -> Granite keeps the unflushed changes and working indexes
+    run(&executor, &mut session, "ALTER TABLE people ENGINE = Granite");
+    assert_eq!(executor.storage().get_schema("people").unwrap().engine_type, EngineType::Granite);
+    assert_eq!(names(&executor, &mut session, "SELECT name FROM people ORDER BY id"), vec!["Alice", "Bobby", "Dave"]);
+    assert_eq!(names(&executor, &mut session, "SELECT name FROM people WHERE id = 4"), vec!["Dave"]);
+    assert_eq!(names(&executor, &mut session, "SELECT id FROM people WHERE name = 'Bobby'"), vec!["2"]);
+    assert!(executor.execute(Parser::parse("INSERT INTO people VALUES (1, 'Again')").unwrap(), &mut session).is_err());
+
+    // Granite writes after the migration are not overwritten by Sandstone's old copy
+    run(&executor, &mut session, "INSERT INTO people VALUES (5, 'Eve')");
+    run(&executor, &mut session, "FLUSH TABLES");
+    assert_eq!(names(&executor, &mut session, "SELECT name FROM people ORDER BY id"), vec!["Alice", "Bobby", "Dave", "Eve"]);
+
+    // And everything survives a restart
+    drop(executor);
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(temp_dir.path().to_path_buf()).unwrap();
+    txn_manager.recover(&storage).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig::default()).unwrap();
+    assert_eq!(names(&executor, &mut session, "SELECT name FROM people ORDER BY id"), vec!["Alice", "Bobby", "Dave", "Eve"]);
+}
+
+#[test]
+fn test_migration_waits_for_other_transactions() {
+    let (temp_dir, executor) = setup_sandstone_executor();
+    let mut session = Session::new();
+    let mut other = Session::new();
+
+    run(&executor, &mut session, "CREATE TABLE items (id INT PRIMARY KEY, qty INT)");
+    run(&executor, &mut session, "CREATE TABLE audit (id INT PRIMARY KEY)");
+    run(&executor, &mut session, "INSERT INTO items VALUES (1, 10)");
+
+    // An open transaction keeps the WAL from being truncated, so the
+    // migration is refused rather than leaving the table's records to replay
+    run(&executor, &mut other, "BEGIN");
+    run(&executor, &mut other, "INSERT INTO audit VALUES (1)");
+    assert!(executor.execute(Parser::parse("ALTER TABLE items ENGINE = Sandstone").unwrap(), &mut session).is_err());
+    assert_eq!(executor.storage().get_schema("items").unwrap().engine_type, EngineType::Granite);
+    run(&executor, &mut other, "COMMIT");
+
+    run(&executor, &mut session, "ALTER TABLE items ENGINE = Sandstone");
+    run(&executor, &mut session, "UPDATE items SET qty = 20 WHERE id = 1");
+    run(&executor, &mut session, "FLUSH TABLES");
+
+    // Recovery does not replay the Granite insert over the Sandstone update
+    drop(executor);
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(temp_dir.path().to_path_buf()).unwrap();
+    txn_manager.recover(&storage).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig::default()).unwrap();
+    executor.init_engine_table("items").unwrap();
+    assert_eq!(names(&executor, &mut session, "SELECT qty FROM items"), vec!["20"]);
+    assert_eq!(names(&executor, &mut session, "SELECT id FROM audit"), vec!["1"]);
+}
@@ -187,4 +187,175 @@ mod arithmetic_tests {
             _ => panic!("Expected SELECT result"),
         }
     }
+
+    #[test]
+    fn test_math_functions_on_columns() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE readings (id INTEGER PRIMARY KEY, delta INTEGER, level FLOAT)");
+        execute_ok(&executor, &mut session, "INSERT INTO readings VALUES (1, -12, 2.345), (2, 7, NULL)");
+
+        let res = execute(&executor, &mut session,
+            "SELECT ABS(delta), ROUND(level, 1), CEIL(level), FLOOR(level), MOD(delta, 5), POWER(delta, 2), SQRT(ABS(delta) + 4) \
+             FROM readings ORDER BY id");
+        match res {
+            QueryResult::Select(rs) => assert_eq!(rs.rows, vec![
+                vec![Value::Integer(12), Value::Float(2.3), Value::Float(3.0), Value::Float(2.0),
+                     Value::Integer(-2), Value::Float(144.0), Value::Float(4.0)],
+                vec![Value::Integer(7), Value::Null, Value::Null, Value::Null,
+                     Value::Integer(2), Value::Float(49.0), Value::Float(11f64.sqrt())],
+            ]),
+            _ => panic!("Expected SELECT result"),
+        }
+
+        let res = execute(&executor, &mut session, "SELECT id FROM readings WHERE ABS(delta) > 10");
+        assert_eq!(first_column(&res), vec![Value::Integer(1)]);
+    }
+
+    #[test]
+    fn test_compare_numeric_column_with_string_literal() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE products (id INTEGER PRIMARY KEY, price INTEGER, weight FLOAT)");
+        execute_ok(&executor, &mut session, "INSERT INTO products VALUES (1, 50, 0.5)");
+        execute_ok(&executor, &mut session, "INSERT INTO products VALUES (2, 100, 1.5)");
+        execute_ok(&executor, &mut session, "INSERT INTO products VALUES (3, 250, 2.5)");
+
+        let res = execute(&executor, &mut session, "SELECT id FROM products WHERE price > '100'");
+        assert_eq!(first_column(&res), vec![Value::Integer(3)]);
+
+        // Fractional and padded strings compare numerically too
+        let res = execute(&executor, &mut session, "SELECT id FROM products WHERE price < ' 99.5 ' ORDER BY id");
+        assert_eq!(first_column(&res), vec![Value::Integer(1)]);
+        let res = execute(&executor, &mut session, "SELECT id FROM products WHERE weight >= '1.5' ORDER BY id");
+        assert_eq!(first_column(&res), vec![Value::Integer(2), Value::Integer(3)]);
+
+        // A non-numeric string converts to 0, as in MySQL
+        let res = execute(&executor, &mut session, "SELECT id FROM products WHERE price > 'abc' ORDER BY id");
+        assert_eq!(first_column(&res).len(), 3);
+    }
+
+    #[test]
+    fn test_arithmetic_with_numeric_string() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE stock (qty INTEGER)");
+        execute_ok(&executor, &mut session, "INSERT INTO stock VALUES (10)");
+
+        let res = execute(&executor, &mut session, "SELECT qty + '5', qty * '1.5', '7' - qty, qty + 'abc' FROM stock");
+        assert_eq!(first_row(&res), vec![
+            Value::Integer(15),
+            Value::Float(15.0),
+            Value::Integer(-3),
+            Value::Integer(10),
+        ]);
+
+        execute_ok(&executor, &mut session, "UPDATE stock SET qty = qty + '5'");
+        let res = execute(&executor, &mut session, "SELECT qty FROM stock");
+        assert_eq!(first_column(&res), vec![Value::Integer(15)]);
+    }
+
+    #[test]
+    fn test_indexed_equality_with_string_literal() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        // The index lookup must use the numeric key, not the string
+        execute_ok(&executor, &mut session, "CREATE TABLE p (id INTEGER PRIMARY KEY, price INTEGER)");
+        execute_ok(&executor, &mut session, "CREATE INDEX idx_price ON p (price)");
+        execute_ok(&executor, &mut session, "INSERT INTO p VALUES (1, 100)");
+        let res = execute(&executor, &mut session, "SELECT id FROM p WHERE price = '100'");
+        assert_eq!(first_column(&res), vec![Value::Integer(1)]);
+        let res = execute(&executor, &mut session, "SELECT id FROM p WHERE id = '1'");
+        assert_eq!(first_column(&res), vec![Value::Integer(1)]);
+    }
+
+    #[test]
+    fn test_negative_literals() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        let res = execute(&executor, &mut session, "SELECT -5, - 5.5, 1 - -2, 2 * -3, -(2 + 3), - -4, -9223372036854775808");
+        assert_eq!(first_row(&res), vec![
+            Value::Integer(-5),
+            Value::Float(-5.5),
+            Value::Integer(3),
+            Value::Integer(-6),
+            Value::Integer(-5),
+            Value::Integer(4),
+            Value::Integer(i64::MIN),
+        ]);
+
+        // `--` only starts a comment when followed by whitespace
+        let res = execute(&executor, &mut session, "SELECT 1--2, 1 -- - 2\n, 3 /* -4 */ # -5");
+        assert_eq!(first_row(&res), vec![Value::Integer(3), Value::Integer(1), Value::Integer(3)]);
+
+        let res = execute(&executor, &mut session, "SELECT -NULL, -'7', -TRUE");
+        assert_eq!(first_row(&res), vec![Value::Null, Value::Integer(-7), Value::Integer(-1)]);
+        let stmt = Parser::parse("SELECT -DATE '2024-01-01'").unwrap();
+        assert!(executor.execute(stmt, &mut session).is_err());
+
+        execute_ok(&executor, &mut session, "CREATE TABLE acct (id INTEGER PRIMARY KEY, balance INTEGER DEFAULT -1, rate FLOAT)");
+        execute_ok(&executor, &mut session, "INSERT INTO acct VALUES (1, -3, -0.5), (2, 4, 1.5)");
+        execute_ok(&executor, &mut session, "INSERT INTO acct (id) VALUES (-3)");
+        let res = execute(&executor, &mut session, "SELECT id FROM acct WHERE balance = -3");
+        assert_eq!(first_column(&res), vec![Value::Integer(1)]);
+        let res = execute(&executor, &mut session, "SELECT balance FROM acct WHERE id = -3");
+        assert_eq!(first_column(&res), vec![Value::Integer(-1)]);
+    }
+
+    #[test]
+    fn test_hex_and_binary_literals() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        let res = execute(&executor, &mut session, "SELECT 0xFF, x'1A', X'', 0b101, b'11', -0x10, 0x10 + 1");
+        assert_eq!(first_row(&res), vec![
+            Value::Integer(255),
+            Value::Integer(26),
+            Value::Integer(0),
+            Value::Integer(5),
+            Value::Integer(3),
+            Value::Integer(-16),
+            Value::Integer(17),
+        ]);
+
+        execute_ok(&executor, &mut session, "CREATE TABLE flags (id INTEGER PRIMARY KEY, mask INTEGER)");
+        execute_ok(&executor, &mut session, "INSERT INTO flags VALUES (1, 0x10), (2, b'1111'), (x'03', 0xff)");
+        let res = execute(&executor, &mut session, "SELECT mask FROM flags WHERE id = 1");
+        assert_eq!(first_column(&res), vec![Value::Integer(16)]);
+        let res = execute(&executor, &mut session, "SELECT id FROM flags WHERE mask = 0xF");
+        assert_eq!(first_column(&res), vec![Value::Integer(2)]);
+        let res = execute(&executor, &mut session, "SELECT id FROM flags WHERE mask > x'0F' ORDER BY id");
+        assert_eq!(first_column(&res), vec![Value::Integer(1), Value::Integer(3)]);
+
+        let stmt = Parser::parse("SELECT 0xFFFFFFFFFFFFFFFF");
+        assert!(stmt.is_err());
+        assert!(Parser::parse("SELECT x'ABC'").is_err());
+    }
+
+    #[test]
+    fn test_unary_minus_on_columns() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE acct (id INTEGER PRIMARY KEY, balance INTEGER, rate FLOAT)");
+        execute_ok(&executor, &mut session, "INSERT INTO acct VALUES (1, -3, -0.5), (2, 4, 1.5), (3, NULL, NULL)");
+
+        match execute(&executor, &mut session, "SELECT -balance, balance * -1, -rate FROM acct ORDER BY id") {
+            QueryResult::Select(rs) => {
+                assert_eq!(rs.columns, vec!["-balance", "?", "-rate"]);
+                assert_eq!(rs.rows, vec![
+                    vec![Value::Integer(3), Value::Integer(3), Value::Float(0.5)],
+                    vec![Value::Integer(-4), Value::Integer(-4), Value::Float(-1.5)],
+                    vec![Value::Null, Value::Null, Value::Null],
+                ]);
+            }
+            _ => panic!("Expected SELECT result"),
+        }
+
+        let res = execute(&executor, &mut session, "SELECT id FROM acct WHERE -balance > 0");
+        assert_eq!(first_column(&res), vec![Value::Integer(1)]);
+        let res = execute(&executor, &mut session, "SELECT id FROM acct WHERE balance IS NOT NULL ORDER BY -balance");
+        assert_eq!(first_column(&res), vec![Value::Integer(2), Value::Integer(1)]);
+        execute_ok(&executor, &mut session, "UPDATE acct SET balance = -balance WHERE id = 2");
+        let res = execute(&executor, &mut session, "SELECT balance FROM acct WHERE id = 2");
+        assert_eq!(first_column(&res), vec![Value::Integer(-4)]);
+    }
 }
@@ -0,0 +1,244 @@
+//! caching_sha2_password authentication over the wire
+//!
+//! Covers the fast-auth path on a plaintext connection, rejection of a bad
+//! scramble without TLS, the auth switch for an unknown plugin, the
+//! cleartext full-auth path over TLS, refusal of an empty scramble when a
+//! password is set, and GRANT checks for the logins of a custom authenticator.
+
+mod common;
+
+use std::sync::Arc;
+
+use minisql::executor::Session;
+use minisql::protocol::{AuthPlugin, Authenticator};
+use minisql::{Config, Server};
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{self, ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+use common::{handshake_response, read_packet, serve, write_packet, CAPS, CLIENT_SSL};
+
+const PASSWORD: &str = "s3cret";
+
+/// Nonce and default auth plugin from the server greeting
+fn parse_greeting(greeting: &[u8]) -> (Vec<u8>, String) {
+    let mut pos = 1 + greeting[1..].iter().position(|&b| b == 0).unwrap() + 1;
+    pos += 4; // connection id
+    let mut nonce = greeting[pos..pos + 8].to_vec();
+    pos += 8 + 1 + 2 + 1 + 2 + 2 + 1 + 10;
+    nonce.extend_from_slice(&greeting[pos..pos + 12]);
+    pos += 13;
+    let end = greeting[pos..].iter().position(|&b| b == 0).unwrap();
+    (nonce, String::from_utf8(greeting[pos..pos + end].to_vec()).unwrap())
+}
+
+/// SHA256(pw) XOR SHA256(SHA256(SHA256(pw)) + nonce)
+fn sha2_scramble(password: &str, nonce: &[u8]) -> Vec<u8> {
+    let stage1 = Sha256::digest(password.as_bytes());
+    let stage2 = Sha256::digest(stage1);
+    let stage3 = Sha256::new().chain_update(stage2).chain_update(nonce).finalize();
+    stage1.iter().zip(stage3.iter()).map(|(a, b)| a ^ b).collect()
+}
+
+/// Start a server requiring `PASSWORD`, optionally with a self-signed certificate
+async fn start_server(dir: &TempDir, tls: bool) -> (Arc<Server>, std::net::SocketAddr, Option<ClientConfig>) {
+    let mut config = Config {
+        data_dir: dir.path().join("data"),
+        password: PASSWORD.to_string(),
+        ..Config::default()
+    };
+    let mut client_config = None;
+    if tls {
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_path = dir.path().join("server.crt");
+        let key_path = dir.path().join("server.key");
+        std::fs::write(&cert_path, certified.cert.pem()).unwrap();
+        std::fs::write(&key_path, certified.key_pair.serialize_pem()).unwrap();
+        config.tls_cert_path = Some(cert_path);
+        config.tls_key_path = Some(key_path);
+
+        let mut roots = RootCertStore::empty();
+        roots.add(certified.cert.der().clone()).unwrap();
+        client_config = Some(
+            ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_root_certificates(roots)
+                .with_no_client_auth(),
+        );
+    }
+    let server = Arc::new(Server::new(config).await.unwrap());
+    let addr = serve(&server).await;
+    (server, addr, client_config)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_caching_sha2_fast_auth() {
+    let dir = TempDir::new().unwrap();
+    let (server, addr, _) = start_server(&dir, false).await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let (nonce, plugin) = parse_greeting(&read_packet(&mut stream).await);
+    assert_eq!(plugin, "caching_sha2_password");
+
+    let scramble = sha2_scramble(PASSWORD, &nonce);
+    write_packet(&mut stream, 1, &handshake_response("root", CAPS, &scramble, "caching_sha2_password")).await;
+    assert_eq!(read_packet(&mut stream).await, vec![0x01, 0x03], "fast auth success");
+    assert_eq!(read_packet(&mut stream).await[0], 0x00, "OK after fast auth");
+
+    server.shutdown();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_caching_sha2_wrong_password_rejected_without_tls() {
+    let dir = TempDir::new().unwrap();
+    let (server, addr, _) = start_server(&dir, false).await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let (nonce, _) = parse_greeting(&read_packet(&mut stream).await);
+    let scramble = sha2_scramble("wrong", &nonce);
+    write_packet(&mut stream, 1, &handshake_response("root", CAPS, &scramble, "caching_sha2_password")).await;
+
+    let err = read_packet(&mut stream).await;
+    assert_eq!(err[0], 0xFF);
+    assert_eq!(u16::from_le_bytes([err[1], err[2]]), 1045);
+
+    server.shutdown();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unknown_plugin_is_switched_to_caching_sha2() {
+    let dir = TempDir::new().unwrap();
+    let (server, addr, _) = start_server(&dir, false).await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let _greeting = read_packet(&mut stream).await;
+    write_packet(&mut stream, 1, &handshake_response("root", CAPS, b"", "sha256_password")).await;
+
+    // Auth switch request: 0xFE, plugin name, fresh copy of the nonce
+    let switch = read_packet(&mut stream).await;
+    assert_eq!(switch[0], 0xFE);
+    let name_end = 1 + switch[1..].iter().position(|&b| b == 0).unwrap();
+    assert_eq!(&switch[1..name_end], b"caching_sha2_password");
+    let nonce = &switch[name_end + 1..name_end + 21];
+
+    write_packet(&mut stream, 3, &sha2_scramble(PASSWORD, nonce)).await;
+    assert_eq!(read_packet(&mut stream).await, vec![0x01, 0x03]);
+    assert_eq!(read_packet(&mut stream).await[0], 0x00);
+
+    server.shutdown();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_caching_sha2_full_auth_over_tls() {
+    let dir = TempDir::new().unwrap();
+    let (server, addr, client_config) = start_server(&dir, true).await;
+
+    let mut tcp = TcpStream::connect(addr).await.unwrap();
+    let _greeting = read_packet(&mut tcp).await;
+    let mut ssl_request = handshake_response("root", CAPS | CLIENT_SSL, b"", "");
+    ssl_request.truncate(32);
+    write_packet(&mut tcp, 1, &ssl_request).await;
+
+    let connector = TlsConnector::from(Arc::new(client_config.unwrap()));
+    let mut tls = connector.connect(ServerName::try_from("localhost").unwrap(), tcp).await.unwrap();
+
+    // An empty scramble cannot be verified, so the server asks for the password
+    write_packet(&mut tls, 2, &handshake_response("root", CAPS | CLIENT_SSL, b"", "caching_sha2_password")).await;
+    assert_eq!(read_packet(&mut tls).await, vec![0x01, 0x04], "perform full authentication");
+    write_packet(&mut tls, 4, format!("{}\0", PASSWORD).as_bytes()).await;
+    assert_eq!(read_packet(&mut tls).await[0], 0x00, "OK after full auth");
+
+    server.shutdown();
+}
+
+/// Accepts a fixed set of users, each with its own password
+struct UserTable(Vec<(&'static str, &'static str)>);
+
+impl Authenticator for UserTable {
+    fn verify(&self, user: &str, plugin: AuthPlugin, auth_response: &[u8], challenge: &[u8]) -> bool {
+        self.0
+            .iter()
+            .any(|(name, password)| *name == user && auth_response == plugin.scramble(password, challenge))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_custom_authenticator_accepts_several_users() {
+    let dir = TempDir::new().unwrap();
+    let config = Config { data_dir: dir.path().join("data"), ..Config::default() };
+    let users = UserTable(vec![("alice", "wonderland"), ("bob", "builder")]);
+    let server = Arc::new(Server::new(config).await.unwrap().with_authenticator(Arc::new(users)));
+    let addr = serve(&server).await;
+
+    for (user, password, accepted) in [
+        ("alice", "wonderland", true),
+        ("bob", "builder", true),
+        ("alice", "builder", false),
+        ("root", PASSWORD, false),
+    ] {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let (nonce, _) = parse_greeting(&read_packet(&mut stream).await);
+        let scramble = sha2_scramble(password, &nonce);
+        write_packet(&mut stream, 1, &handshake_response(user, CAPS, &scramble, "caching_sha2_password")).await;
+
+        if accepted {
+            assert_eq!(read_packet(&mut stream).await, vec![0x01, 0x03], "fast auth success for {}", user);
+            assert_eq!(read_packet(&mut stream).await[0], 0x00);
+
+            // Logins other than the configured user are subject to GRANT privileges
+            write_packet(&mut stream, 0, b"\x03CREATE TABLE t (id INT)").await;
+            let err = read_packet(&mut stream).await;
+            assert_eq!(err[0], 0xFF);
+            assert_eq!(u16::from_le_bytes([err[1], err[2]]), 1227);
+        } else {
+            let err = read_packet(&mut stream).await;
+            assert_eq!(err[0], 0xFF, "{} with password {} should be rejected", user, password);
+            assert_eq!(u16::from_le_bytes([err[1], err[2]]), 1045);
+        }
+    }
+
+    server.shutdown();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_empty_native_response_refused_and_grants_enforced() {
+    let dir = TempDir::new().unwrap();
+    let (server, addr, _) = start_server(&dir, false).await;
+
+    // No scramble is no password, and the configured user has one
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let _greeting = read_packet(&mut stream).await;
+    write_packet(&mut stream, 1, &handshake_response("root", CAPS, b"", "mysql_native_password")).await;
+    let err = read_packet(&mut stream).await;
+    assert_eq!(err[0], 0xFF, "empty auth response should be rejected");
+    assert_eq!(u16::from_le_bytes([err[1], err[2]]), 1045);
+    server.shutdown();
+
+    let dir = TempDir::new().unwrap();
+    let config = Config { data_dir: dir.path().join("data"), ..Config::default() };
+    let users = UserTable(vec![("alice", "wonderland")]);
+    let server = Arc::new(Server::new(config).await.unwrap().with_authenticator(Arc::new(users)));
+    server.executor().execute_sql("CREATE TABLE t (id INT)", &mut Session::new()).unwrap();
+    let addr = serve(&server).await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let (nonce, _) = parse_greeting(&read_packet(&mut stream).await);
+    let scramble = AuthPlugin::NativePassword.scramble("wonderland", &nonce);
+    write_packet(&mut stream, 1, &handshake_response("alice", CAPS, &scramble, "mysql_native_password")).await;
+    assert_eq!(read_packet(&mut stream).await[0], 0x00);
+
+    // alice holds no grant on t, so neither its rows nor its columns are
+    // shown: not by a query, COM_FIELD_LIST or a prepared statement's metadata
+    for command in [&b"\x03SELECT * FROM t"[..], b"\x04t\0", b"\x16SELECT * FROM t"] {
+        write_packet(&mut stream, 0, command).await;
+        let err = read_packet(&mut stream).await;
+        assert_eq!(err[0], 0xFF, "command 0x{:02X} should be refused", command[0]);
+        assert_eq!(u16::from_le_bytes([err[1], err[2]]), 1142);
+    }
+
+    server.shutdown();
+}
@@ -0,0 +1,83 @@
+//! Tests for ALTER TABLE ... AUTO_INCREMENT
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn setup(dir: &Path) -> (Executor, Session) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    txn_manager.recover(&storage).unwrap();
+    (Executor::new(storage, txn_manager), Session::new())
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn insert_id(executor: &Executor, session: &mut Session, name: &str) -> u64 {
+    match execute(executor, session, &format!("INSERT INTO users (name) VALUES ('{}')", name)) {
+        QueryResult::Modified { last_insert_id, .. } => last_insert_id,
+        other => panic!("Expected Modified result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_alter_auto_increment_sets_next_id() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    execute(&executor, &mut session, "CREATE TABLE users (id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT)");
+    assert_eq!(insert_id(&executor, &mut session, "a"), 1);
+
+    execute(&executor, &mut session, "ALTER TABLE users AUTO_INCREMENT = 1000");
+    assert_eq!(insert_id(&executor, &mut session, "b"), 1000);
+    assert_eq!(insert_id(&executor, &mut session, "c"), 1001);
+}
+
+#[test]
+fn test_alter_auto_increment_below_max_id_clamps() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    execute(&executor, &mut session, "CREATE TABLE users (id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT)");
+    execute(&executor, &mut session, "INSERT INTO users VALUES (50, 'x')");
+    execute(&executor, &mut session, "INSERT INTO users VALUES (10, 'y')");
+
+    execute(&executor, &mut session, "ALTER TABLE users AUTO_INCREMENT = 5");
+    assert_eq!(executor.storage().get_auto_increment("users").unwrap(), 51);
+    assert_eq!(insert_id(&executor, &mut session, "z"), 51);
+
+    // Lowering is allowed down to max id + 1 once the high ids are gone
+    execute(&executor, &mut session, "DELETE FROM users WHERE id >= 50");
+    execute(&executor, &mut session, "ALTER TABLE users AUTO_INCREMENT=1");
+    assert_eq!(insert_id(&executor, &mut session, "w"), 11);
+}
+
+#[test]
+fn test_alter_auto_increment_persists() {
+    let dir = tempdir().unwrap();
+    {
+        let (executor, mut session) = setup(dir.path());
+        execute(&executor, &mut session, "CREATE TABLE users (id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT)");
+        execute(&executor, &mut session, "ALTER TABLE users AUTO_INCREMENT = 700");
+    }
+
+    let (executor, mut session) = setup(dir.path());
+    assert_eq!(insert_id(&executor, &mut session, "after restart"), 700);
+    match execute(&executor, &mut session, "SELECT id FROM users") {
+        QueryResult::Select(rs) => assert_eq!(rs.rows, vec![vec![Value::Integer(700)]]),
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+#[test]
+fn test_alter_auto_increment_unknown_table() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    let stmt = Parser::parse("ALTER TABLE missing AUTO_INCREMENT = 10").unwrap();
+    assert!(executor.execute(stmt, &mut session).is_err());
+}
@@ -0,0 +1,147 @@
+//! Tests for SET autocommit and the implicit transactions it starts
+
+mod common;
+
+use std::sync::Arc;
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use minisql::{Config, Server};
+use tempfile::{tempdir, TempDir};
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+
+use common::{connect, query, read_packet, serve, write_packet};
+
+fn setup() -> (TempDir, Executor, Session) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE t (id INT PRIMARY KEY)");
+    (dir, executor, session)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    executor.execute_sql(sql, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn ids(executor: &Executor, session: &mut Session) -> Vec<Value> {
+    match execute(executor, session, "SELECT id FROM t ORDER BY id") {
+        QueryResult::Select(rs) => rs.rows.into_iter().map(|mut row| row.remove(0)).collect(),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_autocommit_off_starts_implicit_transactions() {
+    let (_dir, executor, mut session) = setup();
+    assert!(session.autocommit);
+
+    execute(&executor, &mut session, "SET autocommit = 0");
+    assert!(!session.autocommit);
+    // Reads do not start a transaction; the first write does
+    execute(&executor, &mut session, "SELECT * FROM t");
+    assert!(session.txn_id.is_none());
+    execute(&executor, &mut session, "INSERT INTO t VALUES (1)");
+    assert!(session.txn_id.is_some());
+    execute(&executor, &mut session, "ROLLBACK");
+    assert!(ids(&executor, &mut session).is_empty());
+
+    // Each COMMIT ends one transaction, and the next write starts another
+    execute(&executor, &mut session, "INSERT INTO t VALUES (2)");
+    execute(&executor, &mut session, "COMMIT");
+    assert!(session.txn_id.is_none());
+    execute(&executor, &mut session, "INSERT INTO t VALUES (3)");
+    assert!(session.txn_id.is_some());
+
+    // Turning autocommit back on commits the open transaction
+    execute(&executor, &mut session, "SET autocommit = 1");
+    assert!(session.txn_id.is_none());
+    assert!(executor.execute_sql("ROLLBACK", &mut session).is_err());
+    assert_eq!(ids(&executor, &mut session), vec![Value::Integer(2), Value::Integer(3)]);
+
+    // With autocommit on, SET autocommit = 1 leaves a BEGIN transaction open
+    execute(&executor, &mut session, "BEGIN");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (4)");
+    execute(&executor, &mut session, "SET autocommit = 1");
+    execute(&executor, &mut session, "ROLLBACK");
+    assert_eq!(ids(&executor, &mut session), vec![Value::Integer(2), Value::Integer(3)]);
+}
+
+#[test]
+fn test_autocommit_off_select_for_update_starts_a_transaction() {
+    let (_dir, executor, mut session) = setup();
+    execute(&executor, &mut session, "INSERT INTO t VALUES (1)");
+
+    execute(&executor, &mut session, "SET @@session.autocommit = OFF, sql_mode = 'ANSI_QUOTES'");
+    assert!(!session.autocommit);
+    assert!(session.sql_mode.ansi_quotes());
+
+    // A locking read takes its row locks inside the implicit transaction,
+    // so they are held until COMMIT rather than released at once
+    execute(&executor, &mut session, "SELECT id FROM t WHERE id = 1 FOR UPDATE");
+    assert!(session.txn_id.is_some());
+    execute(&executor, &mut session, "COMMIT");
+    assert!(session.txn_id.is_none());
+
+    execute(&executor, &mut session, "SET @@autocommit = 1");
+    assert!(session.autocommit);
+    execute(&executor, &mut session, "SELECT id FROM t WHERE id = 1 FOR UPDATE");
+    assert!(session.txn_id.is_none());
+}
+
+/// The single value of a one-row, one-column text result set
+async fn query_value(stream: &mut TcpStream, sql: &str) -> Vec<u8> {
+    assert_eq!(query(stream, sql).await, vec![1]);
+    let _column = read_packet(stream).await;
+    assert_eq!(read_packet(stream).await[0], 0xFE);
+    let row = read_packet(stream).await;
+    assert_eq!(read_packet(stream).await[0], 0xFE);
+    row[1..].to_vec()
+}
+
+/// Send COM_QUIT and wait for the server to close the connection, after it
+/// has rolled back any open transaction
+async fn disconnect(mut stream: TcpStream) {
+    write_packet(&mut stream, 0, &[0x01]).await;
+    let mut rest = Vec::new();
+    stream.read_to_end(&mut rest).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_uncommitted_writes_are_lost_on_reconnect() {
+    let dir = tempdir().unwrap();
+    let config = Config {
+        data_dir: dir.path().to_path_buf(),
+        password: String::new(),
+        ..Config::default()
+    };
+    let server = Arc::new(Server::new(config).await.unwrap());
+    let addr = serve(&server).await;
+
+    let mut stream = connect(addr).await;
+    assert_eq!(query(&mut stream, "CREATE TABLE t (id INT PRIMARY KEY)").await[0], 0x00);
+    assert_eq!(query(&mut stream, "SET autocommit = 0").await[0], 0x00);
+    assert_eq!(query_value(&mut stream, "SELECT @@autocommit").await, b"0");
+    assert_eq!(query(&mut stream, "INSERT INTO t VALUES (1)").await[0], 0x00);
+    disconnect(stream).await;
+
+    // The connection closed without COMMIT, so the row is gone
+    let mut stream = connect(addr).await;
+    assert_eq!(query_value(&mut stream, "SELECT @@autocommit").await, b"1");
+    assert_eq!(query_value(&mut stream, "SELECT COUNT(*) FROM t").await, b"0");
+    assert_eq!(query(&mut stream, "SET @@session.autocommit = OFF").await[0], 0x00);
+    assert_eq!(query(&mut stream, "INSERT INTO t VALUES (2)").await[0], 0x00);
+    assert_eq!(query(&mut stream, "COMMIT").await[0], 0x00);
+    disconnect(stream).await;
+
+    let mut stream = connect(addr).await;
+    assert_eq!(query_value(&mut stream, "SELECT id FROM t").await, b"2");
+    disconnect(stream).await;
+
+    server.shutdown();
+}
@@ -0,0 +1,184 @@
+//! Tests for the BLOB column type and binary values over the wire
+
+mod common;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use minisql::{Config, Server};
+use tempfile::tempdir;
+use tokio::net::TcpStream;
+
+use common::{connect, read_packet, serve, write_packet};
+
+/// Bytes that are not valid UTF-8, including a NUL
+const PAYLOAD: [u8; 6] = [0x00, 0xFF, 0x80, 0x41, 0xC3, 0x28];
+
+fn setup(dir: &Path) -> (Executor, Session) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    (Executor::new(storage, txn_manager), Session::new())
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+#[test]
+fn test_hex_and_unhex_round_trip() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    execute(&executor, &mut session, "CREATE TABLE files (id INT PRIMARY KEY, data BLOB, tag VARBINARY(16))");
+    execute(&executor, &mut session, "INSERT INTO files VALUES (1, UNHEX('00FF8041C328'), 'abc'), (2, NULL, NULL)");
+
+    let rows = select_rows(&executor, &mut session, "SELECT data, HEX(data), tag FROM files ORDER BY id");
+    assert_eq!(rows, vec![
+        vec![Value::Bytes(PAYLOAD.to_vec()), Value::String("00FF8041C328".into()), Value::Bytes(b"abc".to_vec())],
+        vec![Value::Null, Value::Null, Value::Null],
+    ]);
+
+    // Blob columns compare bytewise against string literals
+    let rows = select_rows(&executor, &mut session, "SELECT id FROM files WHERE tag = 'abc'");
+    assert_eq!(rows, vec![vec![Value::Integer(1)]]);
+
+    // Invalid hex yields NULL, as in MySQL
+    let rows = select_rows(&executor, &mut session, "SELECT UNHEX('zz'), HEX('hi')");
+    assert_eq!(rows, vec![vec![Value::Null, Value::String("6869".into())]]);
+}
+
+#[test]
+fn test_non_utf8_bytes_are_rejected_by_text_columns() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    execute(&executor, &mut session, "CREATE TABLE notes (id INT PRIMARY KEY, body TEXT)");
+
+    let stmt = Parser::parse("INSERT INTO notes VALUES (1, UNHEX('FF00'))").unwrap();
+    assert!(executor.execute(stmt, &mut session).is_err());
+
+    // Valid UTF-8 bytes are stored as text
+    execute(&executor, &mut session, "INSERT INTO notes VALUES (2, UNHEX('6869'))");
+    let rows = select_rows(&executor, &mut session, "SELECT body FROM notes");
+    assert_eq!(rows, vec![vec![Value::String("hi".into())]]);
+}
+
+#[test]
+fn test_blobs_persist_in_both_storage_formats() {
+    for (options, extension) in [("", "dat"), ("STORAGE FORMAT = BINARY", "bdat")] {
+        let dir = tempdir().unwrap();
+        {
+            let (executor, mut session) = setup(dir.path());
+            execute(&executor, &mut session, &format!("CREATE TABLE files (id INT PRIMARY KEY, data BLOB) {}", options));
+            execute(&executor, &mut session, "INSERT INTO files VALUES (1, UNHEX('00FF8041C328'))");
+            executor.shutdown().unwrap();
+        }
+
+        let contents = std::fs::read(dir.path().join("tables").join(format!("files.{}", extension))).unwrap();
+        if extension == "dat" {
+            // JSONL rows carry blobs as base64
+            assert!(String::from_utf8(contents).unwrap().contains(r#"{"Bytes":"AP+AQcMo"}"#));
+        }
+
+        let (executor, mut session) = setup(dir.path());
+        let rows = select_rows(&executor, &mut session, "SELECT data FROM files");
+        assert_eq!(rows, vec![vec![Value::Bytes(PAYLOAD.to_vec())]]);
+    }
+}
+
+/// Prepare `sql`, skip the parameter and column definitions, and return the statement id
+async fn prepare(stream: &mut TcpStream, sql: &str) -> u32 {
+    let mut packet = vec![0x16];
+    packet.extend_from_slice(sql.as_bytes());
+    write_packet(stream, 0, &packet).await;
+
+    let ok = read_packet(stream).await;
+    assert_eq!(ok[0], 0x00, "expected COM_STMT_PREPARE_OK");
+    let stmt_id = u32::from_le_bytes([ok[1], ok[2], ok[3], ok[4]]);
+    let columns = u16::from_le_bytes([ok[5], ok[6]]);
+    let params = u16::from_le_bytes([ok[7], ok[8]]);
+    for count in [params, columns] {
+        if count > 0 {
+            for _ in 0..=count {
+                read_packet(stream).await;
+            }
+        }
+    }
+    stmt_id
+}
+
+/// Execute a prepared statement with `(type, value)` parameters
+async fn execute_prepared(stream: &mut TcpStream, stmt_id: u32, params: &[(u8, Vec<u8>)]) {
+    let mut packet = vec![0x17];
+    packet.extend_from_slice(&stmt_id.to_le_bytes());
+    packet.push(0); // CURSOR_TYPE_NO_CURSOR
+    packet.extend_from_slice(&1u32.to_le_bytes());
+    packet.extend(std::iter::repeat(0).take(params.len().div_ceil(8)));
+    packet.push(1); // new-params-bound-flag
+    for (type_byte, _) in params {
+        packet.extend_from_slice(&[*type_byte, 0]);
+    }
+    for (_, value) in params {
+        packet.extend_from_slice(value);
+    }
+    write_packet(stream, 0, &packet).await;
+}
+
+#[tokio::test]
+async fn test_prepared_statements_round_trip_binary_data() {
+    let dir = tempdir().unwrap();
+    let config = Config {
+        data_dir: dir.path().to_path_buf(),
+        password: String::new(),
+        ..Config::default()
+    };
+    let server = Arc::new(Server::new(config).await.unwrap());
+    let create = Parser::parse("CREATE TABLE files (id INT PRIMARY KEY, data BLOB)").unwrap();
+    server.executor().execute(create, &mut Session::new()).unwrap();
+
+    let addr = serve(&server).await;
+    let mut client = connect(addr).await;
+
+    // MYSQL_TYPE_LONGLONG id and a MYSQL_TYPE_BLOB payload
+    let insert = prepare(&mut client, "INSERT INTO files VALUES (?, ?)").await;
+    let mut blob = vec![PAYLOAD.len() as u8];
+    blob.extend_from_slice(&PAYLOAD);
+    execute_prepared(&mut client, insert, &[(0x08, 7i64.to_le_bytes().to_vec()), (0xFC, blob)]).await;
+    let ok = read_packet(&mut client).await;
+    assert_eq!(ok[0], 0x00, "expected OK for INSERT, got {:?}", ok);
+
+    let select = prepare(&mut client, "SELECT data FROM files WHERE id = ?").await;
+    execute_prepared(&mut client, select, &[(0x08, 7i64.to_le_bytes().to_vec())]).await;
+    assert_eq!(read_packet(&mut client).await, vec![1]);
+    let column = read_packet(&mut client).await;
+    // Column definitions end with charset(2), length(4), type(1), flags(2), decimals(1), filler(2)
+    let tail = &column[column.len() - 12..];
+    assert_eq!(u16::from_le_bytes([tail[0], tail[1]]), 63, "binary charset");
+    assert_eq!(tail[6], 0xFC, "MYSQL_TYPE_BLOB");
+    assert_eq!(read_packet(&mut client).await[0], 0xFE);
+
+    // Binary row: header, NULL bitmap, then the lenenc bytes untouched
+    let row = read_packet(&mut client).await;
+    assert_eq!(row[0], 0x00);
+    assert_eq!(row[2], PAYLOAD.len() as u8);
+    assert_eq!(&row[3..], &PAYLOAD);
+    assert_eq!(read_packet(&mut client).await[0], 0xFE);
+
+    // The bytes landed in the table as-is
+    let stmt = Parser::parse("SELECT data FROM files").unwrap();
+    match server.executor().execute(stmt, &mut Session::new()).unwrap() {
+        QueryResult::Select(rs) => assert_eq!(rs.rows, vec![vec![Value::Bytes(PAYLOAD.to_vec())]]),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
@@ -0,0 +1,220 @@
+//! Tests for bulk loading with `Executor::bulk_load` and LOAD DATA INFILE
+
+use std::path::Path;
+
+use minisql::engines::granite::TransactionManager;
+use minisql::engines::SandstoneConfig;
+use minisql::executor::{Executor, Session, IMPORT_DIR};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::tempdir;
+
+fn setup(dir: &Path) -> (Executor, Session) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig::default()).unwrap();
+    (executor, Session::new())
+}
+
+fn try_execute(executor: &Executor, session: &mut Session, sql: &str) -> minisql::error::Result<QueryResult> {
+    executor.execute(Parser::parse(sql).expect(&format!("Failed to parse: {}", sql)), session)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    try_execute(executor, session, sql).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn scalar(executor: &Executor, session: &mut Session, sql: &str) -> Value {
+    select_rows(executor, session, sql).remove(0).remove(0)
+}
+
+fn rows_affected(result: QueryResult) -> u64 {
+    match result {
+        QueryResult::Modified { rows_affected, .. } => rows_affected,
+        other => panic!("Expected Modified result, got {:?}", other),
+    }
+}
+
+fn create_items(executor: &Executor, session: &mut Session, engine: &str) {
+    execute(executor, session, &format!(
+        "CREATE TABLE items (id INT PRIMARY KEY, sku TEXT NOT NULL, qty INT) ENGINE={}",
+        engine
+    ));
+    execute(executor, session, "CREATE INDEX idx_sku ON items (sku)");
+    execute(executor, session, "INSERT INTO items VALUES (0, 'existing', 1)");
+}
+
+fn item(id: i64, qty: i64) -> Vec<Value> {
+    vec![Value::Integer(id), Value::String(format!("sku-{}", id)), Value::Integer(qty)]
+}
+
+#[test]
+fn test_bulk_load_50k_rows_with_indexes() {
+    let dir = tempdir().unwrap();
+    {
+        let (executor, mut session) = setup(dir.path());
+        create_items(&executor, &mut session, "Granite");
+
+        let rows: Vec<Vec<Value>> = (1..=50_000).map(|id| item(id, id % 7)).collect();
+        let result = executor.bulk_load("items", rows, &mut session).unwrap();
+        assert_eq!(rows_affected(result), 50_000);
+
+        assert_eq!(scalar(&executor, &mut session, "SELECT COUNT(*) FROM items"), Value::Integer(50_001));
+        assert_eq!(scalar(&executor, &mut session, "SELECT SUM(qty) FROM items WHERE qty = 6"), Value::Integer(6 * 7_143));
+        assert!(executor.storage().check_indexes().is_empty());
+        assert_eq!(
+            select_rows(&executor, &mut session, "SELECT id, qty FROM items WHERE sku = 'sku-31337'"),
+            vec![vec![Value::Integer(31337), Value::Integer(31337 % 7)]]
+        );
+        assert_eq!(
+            select_rows(&executor, &mut session, "SELECT id FROM items WHERE sku = 'existing'"),
+            vec![vec![Value::Integer(0)]]
+        );
+        executor.shutdown().unwrap();
+    }
+
+    // The rows and the index survive a restart
+    let (executor, mut session) = setup(dir.path());
+    assert_eq!(scalar(&executor, &mut session, "SELECT COUNT(*) FROM items"), Value::Integer(50_001));
+    assert!(executor.storage().check_indexes().is_empty());
+    assert_eq!(
+        select_rows(&executor, &mut session, "SELECT id FROM items WHERE sku = 'sku-50000'"),
+        vec![vec![Value::Integer(50_000)]]
+    );
+}
+
+#[test]
+fn test_bulk_load_validates_every_row_first() {
+    for engine in ["Granite", "Sandstone"] {
+        let dir = tempdir().unwrap();
+        let (executor, mut session) = setup(dir.path());
+        create_items(&executor, &mut session, engine);
+
+        let bad_batches = [
+            vec![item(1, 1), vec![Value::Integer(2), Value::String("short".into())]],
+            vec![item(1, 1), vec![Value::Integer(2), Value::Null, Value::Integer(1)]],
+            vec![item(1, 1), vec![Value::Integer(2), Value::String("x".into()), Value::String("many".into())]],
+        ];
+        for rows in bad_batches {
+            assert!(executor.bulk_load("items", rows.clone(), &mut session).is_err(), "{} {:?}", engine, rows);
+            assert_eq!(scalar(&executor, &mut session, "SELECT COUNT(*) FROM items"), Value::Integer(1), "{}", engine);
+        }
+    }
+
+    // Granite also rejects primary keys repeated within the batch or already in the table
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_items(&executor, &mut session, "Granite");
+    for rows in [vec![item(1, 1), item(2, 1), item(1, 2)], vec![item(1, 1), item(0, 1)]] {
+        let err = executor.bulk_load("items", rows, &mut session).unwrap_err();
+        assert!(err.to_string().starts_with("Duplicate entry"), "{}", err);
+        assert_eq!(scalar(&executor, &mut session, "SELECT COUNT(*) FROM items"), Value::Integer(1));
+    }
+    assert!(executor.storage().check_indexes().is_empty());
+}
+
+#[test]
+fn test_bulk_load_checks_foreign_keys_and_generates_ids() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    execute(&executor, &mut session, "CREATE TABLE owners (id INT PRIMARY KEY)");
+    execute(&executor, &mut session, "INSERT INTO owners VALUES (1)");
+    execute(&executor, &mut session,
+        "CREATE TABLE pets (id INT PRIMARY KEY AUTO_INCREMENT, owner_id INT, FOREIGN KEY (owner_id) REFERENCES owners (id))");
+
+    let pet = |owner: i64| vec![Value::Null, Value::Integer(owner)];
+    assert!(executor.bulk_load("pets", vec![pet(1), pet(2)], &mut session).is_err());
+    assert_eq!(scalar(&executor, &mut session, "SELECT COUNT(*) FROM pets"), Value::Integer(0));
+
+    match executor.bulk_load("pets", vec![pet(1), pet(1)], &mut session).unwrap() {
+        QueryResult::Modified { rows_affected, last_insert_id } => {
+            assert_eq!(rows_affected, 2);
+            assert!(last_insert_id > 0);
+            assert_eq!(session.last_insert_id, last_insert_id);
+            assert_eq!(
+                select_rows(&executor, &mut session, "SELECT id FROM pets ORDER BY id"),
+                vec![vec![Value::Integer(last_insert_id as i64)], vec![Value::Integer(last_insert_id as i64 + 1)]]
+            );
+        }
+        other => panic!("Expected Modified result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_bulk_load_joins_an_open_transaction() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_items(&executor, &mut session, "Granite");
+
+    execute(&executor, &mut session, "BEGIN");
+    executor.bulk_load("items", (1..=100).map(|id| item(id, 1)).collect(), &mut session).unwrap();
+    assert_eq!(scalar(&executor, &mut session, "SELECT COUNT(*) FROM items"), Value::Integer(101));
+    execute(&executor, &mut session, "ROLLBACK");
+
+    assert_eq!(scalar(&executor, &mut session, "SELECT COUNT(*) FROM items"), Value::Integer(1));
+    assert!(executor.storage().check_indexes().is_empty());
+}
+
+#[test]
+fn test_load_data_infile() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_items(&executor, &mut session, "Granite");
+
+    let import_dir = dir.path().join(IMPORT_DIR);
+    std::fs::create_dir_all(import_dir.join("batch")).unwrap();
+    std::fs::write(import_dir.join("items.tsv"), "id\tsku\tqty\n1\twidget\t5\n2\tgadget\t\\N\n").unwrap();
+    let result = execute(&executor, &mut session, "LOAD DATA INFILE 'items.tsv' INTO TABLE items IGNORE 1 LINES");
+    assert_eq!(rows_affected(result), 2);
+
+    // A subdirectory, another separator and a column list; unlisted columns take their defaults
+    std::fs::write(import_dir.join("batch/more.csv"), "gizmo,3\nthing\\, big,4\n").unwrap();
+    let sql = "LOAD DATA INFILE 'batch/more.csv' INTO TABLE items FIELDS TERMINATED BY ',' (sku, id)";
+    assert_eq!(rows_affected(execute(&executor, &mut session, sql)), 2);
+
+    assert_eq!(
+        select_rows(&executor, &mut session, "SELECT id, sku, qty FROM items WHERE id > 0 ORDER BY id"),
+        vec![
+            vec![Value::Integer(1), Value::String("widget".into()), Value::Integer(5)],
+            vec![Value::Integer(2), Value::String("gadget".into()), Value::Null],
+            vec![Value::Integer(3), Value::String("gizmo".into()), Value::Null],
+            vec![Value::Integer(4), Value::String("thing, big".into()), Value::Null],
+        ]
+    );
+
+    assert!(try_execute(&executor, &mut session, "LOAD DATA INFILE 'missing.tsv' INTO TABLE items").is_err());
+
+    // Nothing outside the import directory can be read
+    std::fs::write(dir.path().join("outside.tsv"), "9\tsecret\t1\n").unwrap();
+    let absolute = format!("LOAD DATA INFILE '{}' INTO TABLE items", dir.path().join("outside.tsv").display());
+    for sql in [
+        absolute.as_str(),
+        "LOAD DATA INFILE '../outside.tsv' INTO TABLE items",
+        "LOAD DATA INFILE 'batch/../../outside.tsv' INTO TABLE items",
+    ] {
+        let err = try_execute(&executor, &mut session, sql).unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1290, "{}", sql);
+    }
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(dir.path().join("outside.tsv"), import_dir.join("link.tsv")).unwrap();
+        let err = try_execute(&executor, &mut session, "LOAD DATA INFILE 'link.tsv' INTO TABLE items").unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1290);
+    }
+
+    // Loading reads server files, so only the superuser may
+    let mut alice = Session::new();
+    alice.user = Some("alice".to_string());
+    execute(&executor, &mut session, "GRANT INSERT ON items TO alice");
+    let err = try_execute(&executor, &mut alice, "LOAD DATA INFILE 'items.tsv' INTO TABLE items").unwrap_err();
+    assert_eq!(err.mysql_error_code(), 1227);
+    assert!(Parser::parse("LOAD DATA LOCAL INFILE 'items.tsv' INTO TABLE items").is_err());
+}
@@ -0,0 +1,106 @@
+//! Tests for CAST(expr AS type)
+
+use tempfile::tempdir;
+use minisql::storage::StorageEngine;
+use minisql::executor::{Executor, Session};
+use minisql::engines::granite::TransactionManager;
+use minisql::parser::Parser;
+use minisql::types::{DataType, QueryResult, Value};
+
+fn create_test_executor() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let session = Session::new();
+    (executor, session, dir)
+}
+
+fn execute_ok(executor: &Executor, session: &mut Session, sql: &str) {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    let result = executor.execute(stmt, session);
+    assert!(result.is_ok(), "SQL '{}' failed: {:?}", sql, result.err());
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    let result = executor.execute(stmt, session);
+    assert!(result.is_ok(), "SQL '{}' failed: {:?}", sql, result.err());
+    result.unwrap()
+}
+
+fn first_row(result: &QueryResult) -> Vec<Value> {
+    match result {
+        QueryResult::Select(rs) => rs.rows[0].clone(),
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+#[cfg(test)]
+mod cast_tests {
+    use super::*;
+
+    #[test]
+    fn test_cast_numeric_string_to_integer() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        let res = execute(&executor, &mut session,
+            "SELECT CAST('42' AS INTEGER), CAST(' -7 ' AS SIGNED), CAST('12abc' AS UNSIGNED), CAST('abc' AS INT), CAST(3.9 AS INTEGER)");
+        assert_eq!(first_row(&res), vec![
+            Value::Integer(42),
+            Value::Integer(-7),
+            Value::Integer(12),
+            Value::Integer(0),
+            Value::Integer(3),
+        ]);
+    }
+
+    #[test]
+    fn test_cast_integer_to_text() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        let res = execute(&executor, &mut session, "SELECT CAST(123 AS TEXT), CAST(12345 AS CHAR(3)), CAST(2 AS FLOAT)");
+        assert_eq!(first_row(&res), vec![
+            Value::String("123".to_string()),
+            Value::String("123".to_string()),
+            Value::Float(2.0),
+        ]);
+
+        match res {
+            QueryResult::Select(rs) => {
+                assert_eq!(rs.column_types[0], DataType::Text);
+                assert_eq!(rs.column_types[1], DataType::Varchar(Some(3)));
+                assert_eq!(rs.column_types[2], DataType::Float);
+            }
+            _ => panic!("Expected SELECT result"),
+        }
+    }
+
+    #[test]
+    fn test_cast_columns_in_where_and_null() {
+        let (executor, mut session, _dir) = create_test_executor();
+        execute_ok(&executor, &mut session, "CREATE TABLE codes (id INTEGER PRIMARY KEY, code TEXT)");
+        execute_ok(&executor, &mut session, "INSERT INTO codes VALUES (1, '10'), (2, '9'), (3, NULL)");
+
+        // Compared as text '9' > '10'; as integers only 10 qualifies
+        let res = execute(&executor, &mut session, "SELECT id FROM codes WHERE CAST(code AS INTEGER) > 9");
+        match res {
+            QueryResult::Select(rs) => assert_eq!(rs.rows, vec![vec![Value::Integer(1)]]),
+            _ => panic!("Expected SELECT result"),
+        }
+
+        let res = execute(&executor, &mut session, "SELECT CAST(code AS INTEGER) FROM codes WHERE id = 3");
+        assert_eq!(first_row(&res), vec![Value::Null]);
+    }
+
+    #[test]
+    fn test_cast_invalid_json_is_error() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        let res = execute(&executor, &mut session, "SELECT CAST('{\"a\": 1}' AS JSON)");
+        assert_eq!(first_row(&res), vec![Value::Json(serde_json::json!({"a": 1}))]);
+
+        let stmt = Parser::parse("SELECT CAST('not json' AS JSON)").unwrap();
+        assert!(executor.execute(stmt, &mut session).is_err());
+    }
+}
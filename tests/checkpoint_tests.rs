@@ -0,0 +1,94 @@
+//! Tests for the background Granite checkpointer
+
+use minisql::engines::granite::{GraniteConfig, TransactionManager};
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+const THRESHOLD: u64 = 64 * 1024;
+
+fn open(dir: &Path) -> Executor {
+    let config = GraniteConfig {
+        checkpoint_threshold_bytes: THRESHOLD,
+        ..GraniteConfig::default()
+    };
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new_with_config(dir.to_path_buf(), config).unwrap();
+    txn_manager.recover(&storage).unwrap();
+    Executor::new(storage, txn_manager)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn wal_size(dir: &Path) -> u64 {
+    std::fs::metadata(dir.join("wal.log")).map(|m| m.len()).unwrap_or(0)
+}
+
+#[test]
+fn test_autocommit_writes_trigger_background_checkpoint() {
+    let dir = tempdir().unwrap();
+    let executor = open(dir.path());
+    let mut session = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE events (id INTEGER PRIMARY KEY, payload TEXT)");
+
+    // Auto-commit inserts never reach a COMMIT, so only the checkpointer can truncate
+    let payload = "x".repeat(512);
+    let mut peak = 0;
+    for id in 0..400 {
+        execute(&executor, &mut session, &format!("INSERT INTO events VALUES ({}, '{}')", id, payload));
+        peak = peak.max(wal_size(dir.path()));
+    }
+    assert!(peak > THRESHOLD, "WAL never crossed the threshold (peak {} bytes)", peak);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while wal_size(dir.path()) > THRESHOLD && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert!(wal_size(dir.path()) <= THRESHOLD, "WAL was not truncated ({} bytes)", wal_size(dir.path()));
+    assert!(dir.path().join("wal.checkpoint").exists());
+    drop(executor);
+
+    let executor = open(dir.path());
+    let mut session = Session::new();
+    match execute(&executor, &mut session, "SELECT id, payload FROM events ORDER BY id") {
+        QueryResult::Select(rs) => {
+            assert_eq!(rs.rows.len(), 400);
+            assert_eq!(rs.rows[399][0], Value::Integer(399));
+            assert_eq!(rs.rows[0][1], Value::String(payload));
+        }
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_no_checkpoint_while_transaction_open() {
+    let dir = tempdir().unwrap();
+    let executor = open(dir.path());
+    let mut session = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE events (id INTEGER PRIMARY KEY, payload TEXT)");
+
+    let payload = "y".repeat(512);
+    execute(&executor, &mut session, "BEGIN");
+    for id in 0..300 {
+        execute(&executor, &mut session, &format!("INSERT INTO events VALUES ({}, '{}')", id, payload));
+    }
+    assert!(wal_size(dir.path()) > THRESHOLD);
+
+    // The open transaction's records must stay in the WAL
+    thread::sleep(Duration::from_millis(300));
+    assert!(wal_size(dir.path()) > THRESHOLD);
+
+    execute(&executor, &mut session, "COMMIT");
+    match execute(&executor, &mut session, "SELECT id FROM events") {
+        QueryResult::Select(rs) => assert_eq!(rs.rows.len(), 300),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
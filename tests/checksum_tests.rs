@@ -0,0 +1,135 @@
+//! Tests for CRC32 corruption detection in table data files and the WAL
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// Create a table with a few rows and write everything to disk
+fn write_users(dir: &Path, options: &str) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+
+    for sql in [
+        format!("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT) {}", options),
+        "INSERT INTO users VALUES (1, 'alice')".to_string(),
+        "INSERT INTO users VALUES (2, 'bob')".to_string(),
+        "INSERT INTO users VALUES (3, 'carol')".to_string(),
+    ] {
+        executor.execute(Parser::parse(&sql).unwrap(), &mut session).unwrap();
+    }
+    executor.shutdown().unwrap();
+}
+
+/// Flip one bit of the first occurrence of `needle` in the file
+fn corrupt(path: &Path, needle: &[u8]) -> usize {
+    let mut data = std::fs::read(path).unwrap();
+    let pos = data.windows(needle.len()).position(|w| w == needle).expect("needle not found");
+    data[pos] ^= 0x01;
+    std::fs::write(path, data).unwrap();
+    pos
+}
+
+#[test]
+fn test_corrupted_jsonl_table_fails_checksum() {
+    let dir = tempdir().unwrap();
+    write_users(dir.path(), "");
+
+    let path = dir.path().join("tables").join("users.dat");
+    let text = std::fs::read_to_string(&path).unwrap();
+    let second_line_offset = text.find('\n').unwrap() + 1;
+    assert!(text.lines().all(|l| l.contains('\t')), "rows should carry a checksum: {}", text);
+    corrupt(&path, b"bob");
+
+    let err = StorageEngine::new(dir.path().to_path_buf()).err().expect("corrupted table must not load");
+    let msg = err.to_string();
+    assert!(msg.contains("Table 'users' is marked as crashed"), "unexpected error: {}", msg);
+    assert!(msg.contains(&format!("byte offset {}", second_line_offset)), "unexpected error: {}", msg);
+    assert_eq!(err.mysql_error_code(), 1194);
+}
+
+#[test]
+fn test_corrupted_binary_table_fails_checksum() {
+    let dir = tempdir().unwrap();
+    write_users(dir.path(), "STORAGE FORMAT = BINARY");
+
+    corrupt(&dir.path().join("tables").join("users.bdat"), b"carol");
+
+    let err = StorageEngine::new(dir.path().to_path_buf()).err().expect("corrupted table must not load");
+    let msg = err.to_string();
+    assert!(msg.contains("Table 'users' is marked as crashed"), "unexpected error: {}", msg);
+    assert!(msg.contains("checksum mismatch"), "unexpected error: {}", msg);
+}
+
+#[test]
+fn test_table_without_checksums_still_loads() {
+    let dir = tempdir().unwrap();
+    write_users(dir.path(), "");
+
+    // Rewrite the file in the pre-checksum format
+    let path = dir.path().join("tables").join("users.dat");
+    let legacy: String = std::fs::read_to_string(&path).unwrap()
+        .lines()
+        .map(|l| format!("{}\n", l.split('\t').next().unwrap()))
+        .collect();
+    std::fs::write(&path, legacy).unwrap();
+
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    assert_eq!(storage.scan_table("users").unwrap().len(), 3);
+}
+
+#[test]
+fn test_corrupted_wal_record_fails_recovery() {
+    let dir = tempdir().unwrap();
+    write_users(dir.path(), "");
+
+    corrupt(&dir.path().join("wal.log"), b"carol");
+
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let err = txn_manager.recover(&storage).err().expect("corrupted WAL must not replay");
+    assert!(err.to_string().contains("WAL is corrupted"), "unexpected error: {}", err);
+}
+
+#[test]
+fn test_wal_without_version_header_still_recovers() {
+    let dir = tempdir().unwrap();
+    {
+        // Stop without a checkpoint, so the rows only exist in the WAL
+        let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+        let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+        let executor = Executor::new(storage, txn_manager);
+        let mut session = Session::new();
+        for sql in ["CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)", "BEGIN",
+                    "INSERT INTO users VALUES (1, 'alice')", "INSERT INTO users VALUES (2, 'bob')", "COMMIT"] {
+            executor.execute(Parser::parse(sql).unwrap(), &mut session).unwrap();
+        }
+    }
+
+    // Rewrite the WAL in the unversioned format: no file header and
+    // `[len][payload]` frames without a checksum
+    let path = dir.path().join("wal.log");
+    let data = std::fs::read(&path).unwrap();
+    assert_eq!(&data[..8], b"MSQLWAL\x02");
+    let mut legacy = Vec::new();
+    let mut pos = 8;
+    while pos < data.len() {
+        let len = u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap()) as usize;
+        legacy.extend_from_slice(&data[pos..pos + 4]);
+        legacy.extend_from_slice(&data[pos + 8..pos + 8 + len]);
+        pos += 8 + len;
+    }
+    std::fs::write(&path, legacy).unwrap();
+
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    assert!(storage.scan_table("users").unwrap().is_empty());
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    txn_manager.recover(&storage).unwrap();
+    assert_eq!(storage.scan_table("users").unwrap().len(), 2);
+    // New records go to a versioned WAL
+    assert!(std::fs::read(&path).unwrap().starts_with(b"MSQLWAL\x02"));
+}
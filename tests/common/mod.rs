@@ -0,0 +1,86 @@
+//! MySQL wire-protocol helpers shared by the integration tests that talk to
+//! a running server
+
+// Every test crate compiles its own copy and uses only some of these
+#![allow(dead_code)]
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use minisql::Server;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+pub const CLIENT_PROTOCOL_41: u32 = 512;
+pub const CLIENT_SSL: u32 = 2048;
+pub const CLIENT_SECURE_CONNECTION: u32 = 32768;
+pub const CLIENT_PLUGIN_AUTH: u32 = 0x00080000;
+
+/// Capabilities of a plain client logging in with a plugin
+pub const CAPS: u32 = CLIENT_PROTOCOL_41 | CLIENT_SECURE_CONNECTION | CLIENT_PLUGIN_AUTH;
+
+/// Accept connections for `server` on an ephemeral local port
+pub async fn serve(server: &Arc<Server>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let serving = Arc::clone(server);
+    tokio::spawn(async move { serving.serve(listener).await.map_err(|e| e.to_string()) });
+    addr
+}
+
+/// Read one packet's payload, or the error that ended the stream
+pub async fn try_read_packet<S: AsyncRead + Unpin>(stream: &mut S) -> io::Result<Vec<u8>> {
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).await?;
+    let len = u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize;
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).await?;
+    Ok(payload)
+}
+
+pub async fn read_packet<S: AsyncRead + Unpin>(stream: &mut S) -> Vec<u8> {
+    try_read_packet(stream).await.unwrap()
+}
+
+pub async fn write_packet<S: AsyncWrite + Unpin>(stream: &mut S, seq: u8, payload: &[u8]) {
+    let len = payload.len() as u32;
+    let header = [len as u8, (len >> 8) as u8, (len >> 16) as u8, seq];
+    stream.write_all(&header).await.unwrap();
+    stream.write_all(payload).await.unwrap();
+    stream.flush().await.unwrap();
+}
+
+/// Handshake response logging in as `user` with `auth` for `plugin`
+pub fn handshake_response(user: &str, caps: u32, auth: &[u8], plugin: &str) -> Vec<u8> {
+    let mut resp = Vec::new();
+    resp.extend_from_slice(&caps.to_le_bytes());
+    resp.extend_from_slice(&0u32.to_le_bytes());
+    resp.push(45);
+    resp.extend_from_slice(&[0u8; 23]);
+    resp.extend_from_slice(user.as_bytes());
+    resp.push(0);
+    resp.push(auth.len() as u8);
+    resp.extend_from_slice(auth);
+    resp.extend_from_slice(plugin.as_bytes());
+    resp.push(0);
+    resp
+}
+
+/// Connect and log in as root to a server without a password
+pub async fn connect(addr: SocketAddr) -> TcpStream {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let _greeting = read_packet(&mut stream).await;
+    write_packet(&mut stream, 1, &handshake_response("root", CAPS, b"", "mysql_native_password")).await;
+    let ok = read_packet(&mut stream).await;
+    assert_eq!(ok[0], 0x00, "expected OK after handshake");
+    stream
+}
+
+/// Send a COM_QUERY and return the first packet of the response
+pub async fn query(stream: &mut TcpStream, sql: &str) -> Vec<u8> {
+    let mut packet = vec![0x03];
+    packet.extend_from_slice(sql.as_bytes());
+    write_packet(stream, 0, &packet).await;
+    read_packet(stream).await
+}
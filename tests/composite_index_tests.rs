@@ -5,7 +5,7 @@ use minisql::storage::StorageEngine;
 use minisql::executor::{Executor, Session};
 use minisql::engines::granite::TransactionManager;
 use minisql::parser::Parser;
-use minisql::types::QueryResult;
+use minisql::types::{QueryResult, Value};
 
 /// Helper to create an executor with a temporary data directory
 fn create_test_executor() -> (Executor, Session, tempfile::TempDir) {
@@ -240,6 +240,21 @@ mod composite_index_tests {
         assert_eq!(count_rows(&result), 1);
     }
     
+    #[test]
+    fn test_composite_index_with_gap_in_equality_columns() {
+        let (executor, mut session, _dir) = create_test_executor();
+        
+        execute_ok(&executor, &mut session, "CREATE TABLE t1 (a INTEGER, b INTEGER, c INTEGER)");
+        execute_ok(&executor, &mut session, "INSERT INTO t1 VALUES (1, 1, 3)");
+        execute_ok(&executor, &mut session, "INSERT INTO t1 VALUES (1, 2, 3)");
+        execute_ok(&executor, &mut session, "INSERT INTO t1 VALUES (1, 2, 4)");
+        execute_ok(&executor, &mut session, "CREATE INDEX idx_abc ON t1 (a, b, c)");
+        
+        // Only `a` is usable as a prefix; `c` must be checked against every row
+        let result = execute(&executor, &mut session, "SELECT b FROM t1 WHERE a = 1 AND c = 3");
+        assert_eq!(count_rows(&result), 2);
+    }
+    
     #[test]
     fn test_single_column_index_backward_compatible() {
         let (executor, mut session, _dir) = create_test_executor();
@@ -285,4 +300,78 @@ mod composite_index_tests {
         let result = execute(&executor, &mut session, "SELECT data FROM perf_test WHERE a = 0 AND b = 0 AND c = 999");
         assert_eq!(count_rows(&result), 0);
     }
+
+    #[test]
+    fn test_show_index_lists_single_and_composite_indexes() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE people (id INTEGER PRIMARY KEY, last TEXT, first TEXT, age INTEGER)");
+        execute_ok(&executor, &mut session, "CREATE TABLE other (x INTEGER)");
+        execute_ok(&executor, &mut session, "CREATE INDEX idx_age ON people (age)");
+        execute_ok(&executor, &mut session, "CREATE INDEX idx_name ON people (last, first)");
+        execute_ok(&executor, &mut session, "CREATE INDEX idx_x ON other (x)");
+
+        let rs = match execute(&executor, &mut session, "SHOW INDEX FROM people") {
+            QueryResult::Select(rs) => rs,
+            _ => panic!("Expected SELECT result"),
+        };
+        assert_eq!(rs.columns, vec!["Table", "Non_unique", "Key_name", "Seq_in_index", "Column_name"]);
+
+        let rows: Vec<(String, i64, String, i64, String)> = rs.rows.iter().map(|row| match &row[..] {
+            [Value::String(t), Value::Integer(nu), Value::String(k), Value::Integer(seq), Value::String(c)] =>
+                (t.clone(), *nu, k.clone(), *seq, c.clone()),
+            other => panic!("Unexpected SHOW INDEX row: {:?}", other),
+        }).collect();
+        let expected = [
+            ("people", 0, "PRIMARY", 1, "id"),
+            ("people", 1, "idx_age", 1, "age"),
+            ("people", 1, "idx_name", 1, "last"),
+            ("people", 1, "idx_name", 2, "first"),
+        ];
+        assert_eq!(rows.len(), expected.len(), "rows: {:?}", rows);
+        for (row, (t, nu, k, seq, c)) in rows.iter().zip(expected) {
+            assert_eq!(row, &(t.to_string(), nu, k.to_string(), seq, c.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_show_index_unknown_table_fails() {
+        let (executor, mut session, _dir) = create_test_executor();
+        let stmt = Parser::parse("SHOW INDEX FROM missing").unwrap();
+        assert!(executor.execute(stmt, &mut session).is_err());
+    }
+
+    #[test]
+    fn test_describe_reports_keys_defaults_and_extra() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE people (id INTEGER PRIMARY KEY AUTO_INCREMENT, last TEXT, first TEXT, status TEXT DEFAULT 'new', age INTEGER)");
+        execute_ok(&executor, &mut session, "CREATE INDEX idx_name ON people (last, first)");
+
+        let rs = match execute(&executor, &mut session, "DESCRIBE people") {
+            QueryResult::Select(rs) => rs,
+            _ => panic!("Expected SELECT result"),
+        };
+        assert_eq!(rs.columns, vec!["Field", "Type", "Null", "Key", "Default", "Extra"]);
+
+        let text = |v: &Value| match v {
+            Value::String(s) => s.clone(),
+            Value::Null => "NULL".to_string(),
+            other => panic!("Unexpected DESCRIBE value: {:?}", other),
+        };
+        let rows: Vec<(String, String, String, String)> = rs.rows.iter()
+            .map(|row| (text(&row[0]), text(&row[3]), text(&row[4]), text(&row[5])))
+            .collect();
+        let expected = [
+            ("id", "PRI", "NULL", "auto_increment"),
+            ("last", "MUL", "NULL", ""),
+            ("first", "", "NULL", ""),
+            ("status", "", "new", ""),
+            ("age", "", "NULL", ""),
+        ];
+        assert_eq!(rows.len(), expected.len());
+        for (row, (field, key, default, extra)) in rows.iter().zip(expected) {
+            assert_eq!(row, &(field.to_string(), key.to_string(), default.to_string(), extra.to_string()));
+        }
+    }
 }
@@ -0,0 +1,62 @@
+//! `Config::max_connections`: clients past the limit are refused with error 1040
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use minisql::{Config, Server};
+use tempfile::TempDir;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+
+use common::{read_packet, serve};
+
+/// Wait until the server's slot count settles on `expected`
+async fn wait_for_active(server: &Server, expected: usize) {
+    for _ in 0..200 {
+        if server.active_connections() == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("expected {} active connection(s), got {}", expected, server.active_connections());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_connection_past_limit_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    let config = Config {
+        data_dir: dir.path().join("data"),
+        max_connections: 2,
+        ..Config::default()
+    };
+    let server = Arc::new(Server::new(config).await.unwrap());
+    let addr = serve(&server).await;
+
+    let mut first = TcpStream::connect(addr).await.unwrap();
+    let mut second = TcpStream::connect(addr).await.unwrap();
+    assert_eq!(read_packet(&mut first).await[0], 10, "greeting");
+    assert_eq!(read_packet(&mut second).await[0], 10, "greeting");
+    assert_eq!(server.active_connections(), 2);
+
+    // Third client gets ERR 1040 / 08004 in place of the greeting, then EOF
+    let mut third = TcpStream::connect(addr).await.unwrap();
+    let err = read_packet(&mut third).await;
+    assert_eq!(err[0], 0xFF);
+    assert_eq!(u16::from_le_bytes([err[1], err[2]]), 1040);
+    assert_eq!(&err[3..9], b"#08004");
+    assert_eq!(&err[9..], b"Too many connections");
+    let mut rest = Vec::new();
+    third.read_to_end(&mut rest).await.unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(server.active_connections(), 2);
+
+    // Closing a client frees its slot
+    drop(first);
+    wait_for_active(&server, 1).await;
+    let mut fourth = TcpStream::connect(addr).await.unwrap();
+    assert_eq!(read_packet(&mut fourth).await[0], 10, "greeting after a slot frees up");
+
+    server.shutdown();
+}
@@ -0,0 +1,208 @@
+//! Tests for scalar subqueries in the SELECT list and WHERE, correlated and uncorrelated
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{DataType, QueryResult, ResultSet, Value};
+use tempfile::tempdir;
+
+fn create_test_executor() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    (Executor::new(storage, txn_manager), Session::new(), dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select(executor: &Executor, session: &mut Session, sql: &str) -> ResultSet {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs,
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn create_shop(executor: &Executor, session: &mut Session) {
+    execute(executor, session, "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)");
+    execute(executor, session, "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER, total INTEGER)");
+    execute(executor, session, "CREATE INDEX idx_orders_user ON orders (user_id)");
+    execute(executor, session, "INSERT INTO users VALUES (1, 'ann'), (2, 'bob'), (3, 'cy')");
+    execute(executor, session,
+        "INSERT INTO orders VALUES (10, 1, 5), (11, 2, 7), (12, 1, 9), (13, 1, 11), (14, 3, 13)");
+    execute(executor, session, "INSERT INTO users VALUES (4, 'dee')");
+}
+
+#[test]
+fn test_correlated_count_matches_join_aggregate() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    let rs = select(&executor, &mut session,
+        "SELECT id, name, (SELECT COUNT(*) FROM orders o WHERE o.user_id = u.id) AS order_count \
+         FROM users u ORDER BY id");
+    assert_eq!(rs.columns, vec!["id", "name", "order_count"]);
+    assert_eq!(rs.column_types[2], DataType::Integer);
+
+    // The same counts aggregated by hand from a join
+    let joined = select(&executor, &mut session, "SELECT u.id FROM users u JOIN orders o ON o.user_id = u.id");
+    for row in &rs.rows {
+        let expected = joined.rows.iter().filter(|j| j[0] == row[0]).count() as i64;
+        assert_eq!(row[2], Value::Integer(expected), "user {:?}", row[0]);
+    }
+    // A user without orders counts zero
+    assert_eq!(rs.rows[3], vec![Value::Integer(4), Value::String("dee".into()), Value::Integer(0)]);
+}
+
+#[test]
+fn test_correlated_subquery_forms() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    // Unqualified outer reference, inside a larger expression, with no matching rows giving NULL
+    let rs = select(&executor, &mut session,
+        "SELECT id, (SELECT MAX(total) FROM orders WHERE user_id = users.id) + 1 AS next_total \
+         FROM users ORDER BY id");
+    assert_eq!(rs.rows, vec![
+        vec![Value::Integer(1), Value::Integer(12)],
+        vec![Value::Integer(2), Value::Integer(8)],
+        vec![Value::Integer(3), Value::Integer(14)],
+        vec![Value::Integer(4), Value::Null],
+    ]);
+
+    // Inside a join query, referencing both sides
+    let rs = select(&executor, &mut session,
+        "SELECT o.id, (SELECT COUNT(*) FROM orders x WHERE x.user_id = u.id AND x.total < o.total) \
+         FROM orders o JOIN users u ON o.user_id = u.id WHERE u.id = 1 ORDER BY o.id");
+    assert_eq!(rs.rows, vec![
+        vec![Value::Integer(10), Value::Integer(0)],
+        vec![Value::Integer(12), Value::Integer(1)],
+        vec![Value::Integer(13), Value::Integer(2)],
+    ]);
+
+    // Uncorrelated: the same value on every row
+    let rs = select(&executor, &mut session,
+        "SELECT id, (SELECT COUNT(*) FROM orders) FROM users WHERE id <= 2 ORDER BY id");
+    assert_eq!(rs.rows, vec![
+        vec![Value::Integer(1), Value::Integer(5)],
+        vec![Value::Integer(2), Value::Integer(5)],
+    ]);
+}
+
+#[test]
+fn test_correlated_subquery_uses_index() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    // One scan of users; each per-row subquery is an index lookup on orders.user_id
+    let scans = executor.storage().full_scan_count();
+    select(&executor, &mut session,
+        "SELECT name, (SELECT COUNT(*) FROM orders o WHERE o.user_id = u.id) FROM users u");
+    assert_eq!(executor.storage().full_scan_count(), scans + 1);
+}
+
+#[test]
+fn test_scalar_subquery_cardinality_errors() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    let stmt = Parser::parse("SELECT id, (SELECT id FROM orders o WHERE o.user_id = u.id) FROM users u").unwrap();
+    let err = executor.execute(stmt, &mut session).unwrap_err();
+    assert_eq!(err.to_string(), "Subquery returns more than 1 row");
+    assert_eq!(err.mysql_error_code(), 1242);
+    assert_eq!(err.sql_state(), "21000");
+
+    let stmt = Parser::parse("SELECT id, (SELECT id, total FROM orders WHERE id = 10) FROM users").unwrap();
+    let err = executor.execute(stmt, &mut session).unwrap_err();
+    assert_eq!(err.mysql_error_code(), 1241);
+}
+
+#[test]
+fn test_uncorrelated_subquery_runs_once() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    // One scan of users and one of orders, however many users there are
+    let scans = executor.storage().full_scan_count();
+    select(&executor, &mut session, "SELECT id, (SELECT SUM(total) FROM orders) FROM users");
+    assert_eq!(executor.storage().full_scan_count(), scans + 2);
+
+    // COUNT(*) of a whole table reads its row count rather than scanning it
+    let scans = executor.storage().full_scan_count();
+    select(&executor, &mut session, "SELECT id, (SELECT COUNT(*) FROM orders) FROM users");
+    assert_eq!(executor.storage().full_scan_count(), scans + 1);
+
+    let scans = executor.storage().full_scan_count();
+    // The folded subquery bounds a range read of the primary index, so only orders is scanned
+    select(&executor, &mut session, "SELECT id FROM users WHERE id < (SELECT MAX(user_id) FROM orders)");
+    assert_eq!(executor.storage().full_scan_count(), scans + 1);
+}
+
+#[test]
+fn test_uncorrelated_subquery_in_where() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    let ids = |executor: &Executor, session: &mut Session, sql: &str| -> Vec<Value> {
+        select(executor, session, sql).rows.into_iter().map(|row| row[0].clone()).collect()
+    };
+    // AVG(total) is 9
+    assert_eq!(
+        ids(&executor, &mut session, "SELECT id FROM orders WHERE total > (SELECT AVG(total) FROM orders) ORDER BY id"),
+        vec![Value::Integer(13), Value::Integer(14)]
+    );
+    assert_eq!(
+        ids(&executor, &mut session, "SELECT name FROM users WHERE id = (SELECT user_id FROM orders WHERE id = 14)"),
+        vec![Value::String("cy".into())]
+    );
+    // No row: the subquery is NULL and the comparison never true
+    assert!(ids(&executor, &mut session, "SELECT id FROM users WHERE id = (SELECT user_id FROM orders WHERE id = 99)").is_empty());
+
+    let stmt = Parser::parse("SELECT id FROM users WHERE id = (SELECT user_id FROM orders)").unwrap();
+    assert_eq!(executor.execute(stmt, &mut session).unwrap_err().mysql_error_code(), 1242);
+}
+
+#[test]
+fn test_correlated_subquery_in_where() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    let rs = select(&executor, &mut session,
+        "SELECT name FROM users u WHERE (SELECT COUNT(*) FROM orders o WHERE o.user_id = u.id) >= 2");
+    assert_eq!(rs.rows, vec![vec![Value::String("ann".into())]]);
+
+    // Through the aggregate and ORDER BY paths
+    let rs = select(&executor, &mut session,
+        "SELECT COUNT(*) FROM users WHERE (SELECT COUNT(*) FROM orders WHERE user_id = users.id) = 0");
+    assert_eq!(rs.rows, vec![vec![Value::Integer(1)]]);
+    let rs = select(&executor, &mut session,
+        "SELECT id FROM orders o WHERE total > (SELECT MIN(total) FROM orders x WHERE x.user_id = o.user_id) ORDER BY id");
+    assert_eq!(rs.rows, vec![vec![Value::Integer(12)], vec![Value::Integer(13)]]);
+
+    // In a join, referencing either side
+    let rs = select(&executor, &mut session,
+        "SELECT o.id FROM users u JOIN orders o ON o.user_id = u.id \
+         WHERE o.total = (SELECT MAX(total) FROM orders x WHERE x.user_id = u.id) ORDER BY o.id");
+    assert_eq!(rs.rows, vec![vec![Value::Integer(11)], vec![Value::Integer(13)], vec![Value::Integer(14)]]);
+}
+
+#[test]
+fn test_correlated_subquery_in_dml() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    // ann's orders total 25, cy's 13
+    execute(&executor, &mut session,
+        "UPDATE users SET name = 'vip' WHERE (SELECT SUM(total) FROM orders WHERE user_id = users.id) > 20");
+    execute(&executor, &mut session,
+        "DELETE FROM users WHERE (SELECT COUNT(*) FROM orders WHERE orders.user_id = users.id) = 0");
+    let rs = select(&executor, &mut session, "SELECT id, name FROM users ORDER BY id");
+    assert_eq!(rs.rows, vec![
+        vec![Value::Integer(1), Value::String("vip".into())],
+        vec![Value::Integer(2), Value::String("bob".into())],
+        vec![Value::Integer(3), Value::String("cy".into())],
+    ]);
+}
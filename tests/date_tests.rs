@@ -0,0 +1,166 @@
+//! Tests for the DATE column type and the date functions
+
+use minisql::datetime::Date;
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, StorageFormat, Value};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn setup(dir: &Path) -> (Executor, Session) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    (Executor::new(storage, txn_manager), Session::new())
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn date(s: &str) -> Value {
+    Value::Date(Date::parse(s).unwrap())
+}
+
+fn create_events(executor: &Executor, session: &mut Session, options: &str) {
+    execute(executor, session, &format!("CREATE TABLE events (id INT PRIMARY KEY, name TEXT, day DATE) {}", options));
+    execute(executor, session, "INSERT INTO events VALUES (1, 'launch', '2024-03-15'), (2, 'party', '2023-12-31'), \
+                                (3, 'leap', DATE '2024-02-29'), (4, 'unknown', NULL)");
+}
+
+#[test]
+fn test_extract_date_parts() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_events(&executor, &mut session, "");
+
+    let rows = select_rows(&executor, &mut session, "SELECT YEAR(day), MONTH(day), DAY(day), DATE(day) FROM events ORDER BY id");
+    assert_eq!(rows, vec![
+        vec![Value::Integer(2024), Value::Integer(3), Value::Integer(15), date("2024-03-15")],
+        vec![Value::Integer(2023), Value::Integer(12), Value::Integer(31), date("2023-12-31")],
+        vec![Value::Integer(2024), Value::Integer(2), Value::Integer(29), date("2024-02-29")],
+        vec![Value::Null, Value::Null, Value::Null, Value::Null],
+    ]);
+
+    // Strings are read as dates, and DATE() drops the time of day
+    let rows = select_rows(&executor, &mut session,
+        "SELECT DATE('2024-07-04 18:30:00'), DAYOFMONTH('2024-07-04'), YEAR('not a date')");
+    assert_eq!(rows, vec![vec![date("2024-07-04"), Value::Integer(4), Value::Null]]);
+
+    let rows = select_rows(&executor, &mut session,
+        "SELECT CAST('2024-07-04' AS DATE), CAST(DATE '2024-07-04' AS CHAR), CAST('soon' AS DATE)");
+    assert_eq!(rows, vec![vec![date("2024-07-04"), Value::String("2024-07-04".into()), Value::Null]]);
+}
+
+#[test]
+fn test_date_arithmetic_crosses_month_and_year_boundaries() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    let rows = select_rows(&executor, &mut session,
+        "SELECT DATE_ADD(DATE '2024-01-31', INTERVAL 1 DAY), DATE_ADD(DATE '2024-02-28', INTERVAL 2 DAY), \
+         DATE_ADD(DATE '2023-02-28', INTERVAL 1 DAY), DATE_ADD(DATE '2023-12-31', INTERVAL 1 DAY), \
+         DATE_SUB(DATE '2024-03-01', INTERVAL 1 DAY), DATE '2024-01-31' + INTERVAL 1 MONTH");
+    assert_eq!(rows, vec![vec![
+        date("2024-02-01"),
+        date("2024-03-01"),
+        date("2023-03-01"),
+        date("2024-01-01"),
+        date("2024-02-29"),
+        date("2024-02-29"),
+    ]]);
+
+    // Adding a time of day turns the date into a datetime
+    let rows = select_rows(&executor, &mut session, "SELECT DATE_ADD(DATE '2024-01-31', INTERVAL 25 HOUR)");
+    assert_eq!(rows, vec![vec![Value::String("2024-02-01 01:00:00".into())]]);
+
+    create_events(&executor, &mut session, "");
+    execute(&executor, &mut session, "UPDATE events SET day = DATE_ADD(day, INTERVAL 1 DAY) WHERE id = 3");
+    let rows = select_rows(&executor, &mut session, "SELECT day FROM events WHERE id = 3");
+    assert_eq!(rows, vec![vec![date("2024-03-01")]]);
+}
+
+#[test]
+fn test_date_comparisons_and_order_by() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_events(&executor, &mut session, "");
+
+    let rows = select_rows(&executor, &mut session, "SELECT id FROM events WHERE day IS NOT NULL ORDER BY day");
+    assert_eq!(rows, vec![vec![Value::Integer(2)], vec![Value::Integer(3)], vec![Value::Integer(1)]]);
+    let rows = select_rows(&executor, &mut session, "SELECT id FROM events ORDER BY day DESC LIMIT 1");
+    assert_eq!(rows, vec![vec![Value::Integer(1)]]);
+
+    let queries = [
+        ("SELECT id FROM events WHERE day = '2024-02-29'", vec![3]),
+        ("SELECT id FROM events WHERE day >= '2024-01-01' ORDER BY id", vec![1, 3]),
+        ("SELECT id FROM events WHERE day < DATE '2024-01-01'", vec![2]),
+        ("SELECT id FROM events WHERE day > '2024-02-29 12:00:00'", vec![1]),
+        ("SELECT id FROM events WHERE day >= '2024-02-01' AND day <= '2024-03-31' ORDER BY id", vec![1, 3]),
+    ];
+    let check = |executor: &Executor, session: &mut Session| {
+        for (sql, expected) in &queries {
+            let ids: Vec<Value> = select_rows(executor, session, sql).into_iter().map(|r| r[0].clone()).collect();
+            let expected: Vec<Value> = expected.iter().map(|i| Value::Integer(*i)).collect();
+            assert_eq!(ids, expected, "{}", sql);
+        }
+    };
+    check(&executor, &mut session);
+
+    // An index on the column gives the same answers
+    execute(&executor, &mut session, "CREATE INDEX idx_day ON events (day)");
+    check(&executor, &mut session);
+}
+
+#[test]
+fn test_invalid_date_is_rejected() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_events(&executor, &mut session, "");
+
+    for sql in [
+        "INSERT INTO events VALUES (5, 'bad', '2024-02-30')",
+        "INSERT INTO events VALUES (5, 'bad', 'tomorrow')",
+    ] {
+        let err = executor.execute(Parser::parse(sql).unwrap(), &mut session).unwrap_err();
+        assert!(err.to_string().contains("Incorrect date value"), "{}: {}", sql, err);
+    }
+    assert!(Parser::parse("SELECT DATE '2024-13-01'").is_err());
+}
+
+#[test]
+fn test_dates_persist_in_both_storage_formats() {
+    for (options, extension) in [("", "dat"), ("STORAGE FORMAT = BINARY", "bdat")] {
+        let dir = tempdir().unwrap();
+        {
+            let (executor, mut session) = setup(dir.path());
+            create_events(&executor, &mut session, options);
+            executor.shutdown().unwrap();
+        }
+
+        let contents = std::fs::read(dir.path().join("tables").join(format!("events.{}", extension))).unwrap();
+        if extension == "dat" {
+            // JSONL rows stay readable
+            assert!(String::from_utf8(contents).unwrap().contains(r#"{"Date":"2024-03-15"}"#));
+        }
+
+        let (executor, mut session) = setup(dir.path());
+        let expected_format = if extension == "dat" { StorageFormat::Jsonl } else { StorageFormat::Binary };
+        assert_eq!(executor.storage().get_schema("events").unwrap().storage_format, expected_format);
+        let rows = select_rows(&executor, &mut session, "SELECT day FROM events ORDER BY id");
+        assert_eq!(rows, vec![
+            vec![date("2024-03-15")],
+            vec![date("2023-12-31")],
+            vec![date("2024-02-29")],
+            vec![Value::Null],
+        ]);
+    }
+}
@@ -0,0 +1,124 @@
+//! Tests for deadlock detection between Granite transactions
+
+use std::sync::Barrier;
+use std::thread;
+
+use minisql::engines::granite::TransactionManager;
+use minisql::error::MiniSqlError;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Executor) {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(temp_dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+    run(&executor, &mut session, "CREATE TABLE accounts (id INT PRIMARY KEY, owner TEXT)");
+    run(&executor, &mut session, "CREATE INDEX idx_owner ON accounts (owner)");
+    run(&executor, &mut session, "INSERT INTO accounts VALUES (1, 'nobody'), (2, 'nobody')");
+    (temp_dir, executor)
+}
+
+fn try_run(executor: &Executor, session: &mut Session, sql: &str) -> Result<QueryResult, MiniSqlError> {
+    executor.execute(Parser::parse(sql).unwrap(), session)
+}
+
+fn run(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    try_run(executor, session, sql).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn ids(executor: &Executor, sql: &str) -> Vec<Value> {
+    match run(executor, &mut Session::new(), sql) {
+        QueryResult::Select(rs) => rs.rows.into_iter().map(|r| r[0].clone()).collect(),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+/// Lock `first` then, once the other transaction holds its first row, `second`.
+/// Commits and returns Ok when the second update succeeds.
+fn claim_rows(executor: &Executor, barrier: &Barrier, owner: &str, first: i64, second: i64) -> Result<(), MiniSqlError> {
+    let mut session = Session::new();
+    run(executor, &mut session, "BEGIN");
+    run(executor, &mut session, &format!("UPDATE accounts SET owner = '{}' WHERE id = {}", owner, first));
+    barrier.wait();
+    let result = try_run(executor, &mut session, &format!("UPDATE accounts SET owner = '{}' WHERE id = {}", owner, second));
+    match result {
+        Ok(_) => {
+            run(executor, &mut session, "COMMIT");
+            Ok(())
+        }
+        Err(e) => {
+            // The victim's transaction is already gone
+            assert!(session.txn_id.is_none());
+            Err(e)
+        }
+    }
+}
+
+#[test]
+fn test_two_transaction_deadlock_has_one_victim() {
+    let (_temp_dir, executor) = setup();
+    let barrier = Barrier::new(2);
+
+    let (a, b) = thread::scope(|scope| {
+        let a = scope.spawn(|| claim_rows(&executor, &barrier, "a", 1, 2));
+        let b = scope.spawn(|| claim_rows(&executor, &barrier, "b", 2, 1));
+        (a.join().unwrap(), b.join().unwrap())
+    });
+
+    let (winner, loser, err) = match (a, b) {
+        (Ok(()), Err(e)) => ("a", "b", e),
+        (Err(e), Ok(())) => ("b", "a", e),
+        other => panic!("Expected exactly one deadlock victim, got {:?}", other),
+    };
+    assert_eq!(err.mysql_error_code(), 1213, "{}", err);
+    assert_eq!(err.sql_state(), "40001");
+
+    // The winner's changes stand and the victim's are rolled back, index included
+    assert_eq!(
+        ids(&executor, &format!("SELECT id FROM accounts WHERE owner = '{}' ORDER BY id", winner)),
+        vec![Value::Integer(1), Value::Integer(2)]
+    );
+    assert!(ids(&executor, &format!("SELECT id FROM accounts WHERE owner = '{}'", loser)).is_empty());
+    assert!(ids(&executor, "SELECT id FROM accounts WHERE owner = 'nobody'").is_empty());
+}
+
+#[test]
+fn test_deadlock_victim_can_retry() {
+    let (_temp_dir, executor) = setup();
+    let mut first = Session::new();
+    let mut second = Session::new();
+    run(&executor, &mut first, "BEGIN");
+    run(&executor, &mut first, "SELECT * FROM accounts WHERE id = 1 FOR UPDATE");
+    run(&executor, &mut second, "BEGIN");
+    run(&executor, &mut second, "INSERT INTO accounts VALUES (3, 'second')");
+    run(&executor, &mut second, "SELECT * FROM accounts WHERE id = 2 FOR UPDATE");
+
+    thread::scope(|scope| {
+        let waiter = scope.spawn(|| {
+            let mut first = first;
+            run(&executor, &mut first, "UPDATE accounts SET owner = 'first' WHERE id = 2");
+            run(&executor, &mut first, "COMMIT");
+        });
+        // Let the first transaction start waiting for row 2
+        thread::sleep(std::time::Duration::from_millis(200));
+        assert!(!waiter.is_finished());
+
+        let err = try_run(&executor, &mut second, "DELETE FROM accounts WHERE id = 1").unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1213);
+        waiter.join().unwrap();
+    });
+
+    // The victim's insert was undone; retrying its work now succeeds
+    assert!(ids(&executor, "SELECT id FROM accounts WHERE id = 3").is_empty());
+    run(&executor, &mut second, "BEGIN");
+    run(&executor, &mut second, "INSERT INTO accounts VALUES (3, 'second')");
+    run(&executor, &mut second, "DELETE FROM accounts WHERE id = 1");
+    run(&executor, &mut second, "COMMIT");
+    assert_eq!(ids(&executor, "SELECT id FROM accounts ORDER BY id"), vec![Value::Integer(2), Value::Integer(3)]);
+    assert_eq!(ids(&executor, "SELECT id FROM accounts WHERE owner = 'first'"), vec![Value::Integer(2)]);
+}
@@ -0,0 +1,121 @@
+//! Tests for single-table UPDATE and DELETE with ORDER BY and LIMIT
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::types::{QueryResult, Value};
+use tempfile::tempdir;
+
+fn setup() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = minisql::storage::StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+
+    execute(&executor, &mut session, "CREATE TABLE jobs (id INTEGER PRIMARY KEY, state TEXT)");
+    execute(&executor, &mut session,
+        "INSERT INTO jobs VALUES (1, 'done'), (2, 'queued'), (3, 'done'), (4, 'done'), (5, 'queued'), (6, 'done')");
+    (executor, session, dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).unwrap_or_else(|e| panic!("Failed to parse '{}': {:?}", sql, e));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn affected(result: QueryResult) -> u64 {
+    match result {
+        QueryResult::Modified { rows_affected, .. } => rows_affected,
+        other => panic!("Expected Modified result, got {:?}", other),
+    }
+}
+
+fn ids(executor: &Executor, session: &mut Session, sql: &str) -> Vec<i64> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows.iter().map(|r| match r[0] {
+            Value::Integer(id) => id,
+            ref other => panic!("Expected integer id, got {:?}", other),
+        }).collect(),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_delete_limit_removes_first_matching_rows() {
+    let (executor, mut session, _dir) = setup();
+    assert_eq!(affected(execute(&executor, &mut session, "DELETE FROM jobs WHERE state = 'done' LIMIT 2")), 2);
+    assert_eq!(ids(&executor, &mut session, "SELECT id FROM jobs WHERE state = 'done' ORDER BY id"), vec![4, 6]);
+    assert_eq!(ids(&executor, &mut session, "SELECT id FROM jobs WHERE state = 'queued' ORDER BY id"), vec![2, 5]);
+}
+
+#[test]
+fn test_delete_limit_larger_than_matches() {
+    let (executor, mut session, _dir) = setup();
+    assert_eq!(affected(execute(&executor, &mut session, "DELETE FROM jobs WHERE state = 'queued' LIMIT 10")), 2);
+    assert_eq!(affected(execute(&executor, &mut session, "DELETE FROM jobs LIMIT 0")), 0);
+    assert_eq!(ids(&executor, &mut session, "SELECT id FROM jobs ORDER BY id"), vec![1, 3, 4, 6]);
+}
+
+#[test]
+fn test_update_limit_changes_first_matching_rows() {
+    let (executor, mut session, _dir) = setup();
+    assert_eq!(
+        affected(execute(&executor, &mut session, "UPDATE jobs SET state = 'archived' WHERE state = 'done' LIMIT 3")),
+        3
+    );
+    assert_eq!(ids(&executor, &mut session, "SELECT id FROM jobs WHERE state = 'archived' ORDER BY id"), vec![1, 3, 4]);
+    assert_eq!(ids(&executor, &mut session, "SELECT id FROM jobs WHERE state = 'done' ORDER BY id"), vec![6]);
+}
+
+#[test]
+fn test_update_limit_without_where() {
+    let (executor, mut session, _dir) = setup();
+    assert_eq!(affected(execute(&executor, &mut session, "UPDATE jobs SET state = 'paused' LIMIT 4")), 4);
+    assert_eq!(ids(&executor, &mut session, "SELECT id FROM jobs WHERE state = 'paused' ORDER BY id"), vec![1, 2, 3, 4]);
+    assert_eq!(ids(&executor, &mut session, "SELECT id FROM jobs WHERE state <> 'paused' ORDER BY id"), vec![5, 6]);
+}
+
+/// Rows inserted out of id order, so scan order differs from id order
+fn setup_unordered() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = minisql::storage::StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+
+    execute(&executor, &mut session, "CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT, seen INTEGER)");
+    execute(&executor, &mut session, "CREATE INDEX idx_kind ON events (kind)");
+    execute(&executor, &mut session,
+        "INSERT INTO events VALUES (5, 'click', 0), (2, 'click', 0), (7, 'view', 0), (1, 'click', 0), (4, 'click', 0)");
+    (executor, session, dir)
+}
+
+#[test]
+fn test_delete_order_by_limit_removes_lowest_ids() {
+    let (executor, mut session, _dir) = setup_unordered();
+    assert_eq!(
+        affected(execute(&executor, &mut session, "DELETE FROM events WHERE kind = 'click' ORDER BY id LIMIT 2")),
+        2
+    );
+    assert_eq!(ids(&executor, &mut session, "SELECT id FROM events ORDER BY id"), vec![4, 5, 7]);
+    // The index no longer returns the deleted rows
+    assert_eq!(ids(&executor, &mut session, "SELECT id FROM events WHERE kind = 'click' ORDER BY id"), vec![4, 5]);
+}
+
+#[test]
+fn test_delete_order_by_desc() {
+    let (executor, mut session, _dir) = setup_unordered();
+    assert_eq!(affected(execute(&executor, &mut session, "DELETE FROM events ORDER BY id DESC LIMIT 1")), 1);
+    assert_eq!(ids(&executor, &mut session, "SELECT id FROM events ORDER BY id"), vec![1, 2, 4, 5]);
+}
+
+#[test]
+fn test_update_order_by_limit() {
+    let (executor, mut session, _dir) = setup_unordered();
+    assert_eq!(
+        affected(execute(&executor, &mut session, "UPDATE events SET seen = 1 WHERE kind = 'click' ORDER BY id DESC LIMIT 3")),
+        3
+    );
+    assert_eq!(ids(&executor, &mut session, "SELECT id FROM events WHERE seen = 1 ORDER BY id"), vec![2, 4, 5]);
+}
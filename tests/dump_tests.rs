@@ -0,0 +1,151 @@
+//! Tests for SQL dump/import and SHOW CREATE TABLE
+
+use minisql::engines::granite::TransactionManager;
+use minisql::engines::SandstoneConfig;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn setup(dir: &Path) -> (Executor, Session) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig::default()).unwrap();
+    (executor, Session::new())
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn populate(executor: &Executor, session: &mut Session) {
+    for sql in [
+        "CREATE TABLE customers (id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT NOT NULL, \
+            country VARCHAR(2) DEFAULT 'NL', vip BOOLEAN)",
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, customer_id INTEGER, total FLOAT, meta JSON) \
+            STORAGE FORMAT = BINARY",
+        "CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT) ENGINE=Sandstone",
+        "CREATE INDEX idx_customer ON orders (customer_id)",
+        "CREATE INDEX idx_name_country ON customers (name, country)",
+        "INSERT INTO customers (name, country, vip) VALUES ('Ann', 'NL', TRUE)",
+        "INSERT INTO customers (name, country, vip) VALUES ('O''Brien; \\\\ -- not a comment', 'IE', FALSE)",
+        "INSERT INTO customers (name, vip) VALUES ('Zoë', NULL)",
+        "INSERT INTO customers (name, vip) VALUES ('Gone', NULL)",
+        "DELETE FROM customers WHERE name = 'Gone'",
+        r#"INSERT INTO orders VALUES (1, 1, 19.5, '{"items": [1, 2], "note": "it''s"}')"#,
+        "INSERT INTO orders VALUES (2, 2, 3.0, NULL)",
+        "INSERT INTO orders VALUES (3, 1, -0.125, '[]')",
+        "INSERT INTO events VALUES (1, 'signup')",
+        "INSERT INTO events VALUES (2, 'login')",
+    ] {
+        execute(executor, session, sql);
+    }
+    for i in 10..260 {
+        execute(executor, session, &format!("INSERT INTO orders VALUES ({}, {}, {}.25, NULL)", i, i % 3, i));
+    }
+}
+
+#[test]
+fn test_dump_and_import_round_trip() {
+    let source_dir = tempdir().unwrap();
+    let (source, mut source_session) = setup(source_dir.path());
+    populate(&source, &mut source_session);
+
+    let mut dump = Vec::new();
+    source.dump(&mut dump).unwrap();
+    let dump = String::from_utf8(dump).unwrap();
+
+    let target_dir = tempdir().unwrap();
+    let (target, mut target_session) = setup(target_dir.path());
+    let executed = target.import(&dump, &mut target_session).unwrap();
+    assert!(executed > 3, "expected several statements, got {}", executed);
+
+    // Schemas match, including engine, storage format and AUTO_INCREMENT counter
+    let mut tables = target.storage().list_tables();
+    tables.sort();
+    assert_eq!(tables, vec!["customers", "events", "orders"]);
+    for table in &tables {
+        let expected = source.storage().get_schema(table).unwrap();
+        let actual = target.storage().get_schema(table).unwrap();
+        assert_eq!(format!("{:?}", actual), format!("{:?}", expected), "schema of {}", table);
+    }
+    assert_eq!(target.storage().get_auto_increment("customers").unwrap(), 5);
+
+    // Indexes match
+    let index_names = |executor: &Executor| {
+        let mut names: Vec<_> = executor.storage().list_indexes().into_iter()
+            .map(|idx| (idx.name, idx.table_name, idx.columns, idx.unique, idx.is_primary))
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(index_names(&target), index_names(&source));
+
+    // Data matches
+    for sql in [
+        "SELECT * FROM customers ORDER BY id",
+        "SELECT * FROM orders ORDER BY id",
+        "SELECT * FROM events ORDER BY id",
+    ] {
+        assert_eq!(
+            select_rows(&target, &mut target_session, sql),
+            select_rows(&source, &mut source_session, sql),
+            "{}", sql
+        );
+    }
+
+    // The restored AUTO_INCREMENT counter skips the deleted id
+    execute(&target, &mut target_session, "INSERT INTO customers (name) VALUES ('New')");
+    assert_eq!(
+        select_rows(&target, &mut target_session, "SELECT id FROM customers WHERE name = 'New'"),
+        vec![vec![Value::Integer(5)]]
+    );
+
+    // Dumping the restored database gives the same script
+    let mut redump = Vec::new();
+    target.execute(Parser::parse("DELETE FROM customers WHERE id = 5").unwrap(), &mut target_session).unwrap();
+    target.dump(&mut redump).unwrap();
+    assert_eq!(String::from_utf8(redump).unwrap(), dump.replace("AUTO_INCREMENT=5", "AUTO_INCREMENT=6"));
+}
+
+#[test]
+fn test_show_create_table() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    execute(&executor, &mut session,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT NOT NULL, n INTEGER DEFAULT 7) AUTO_INCREMENT = 42");
+
+    let rows = select_rows(&executor, &mut session, "SHOW CREATE TABLE t");
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0][0], Value::String("t".into()));
+    assert_eq!(
+        rows[0][1],
+        Value::String(
+            "CREATE TABLE `t` (\n  `id` INTEGER PRIMARY KEY AUTO_INCREMENT,\n  `name` TEXT NOT NULL,\n  \
+             `n` INTEGER DEFAULT 7\n) ENGINE=Granite STORAGE FORMAT=JSONL AUTO_INCREMENT=42".into()
+        )
+    );
+
+    execute(&executor, &mut session, "INSERT INTO t (name) VALUES ('x')");
+    assert_eq!(select_rows(&executor, &mut session, "SELECT id FROM t"), vec![vec![Value::Integer(42)]]);
+}
+
+#[test]
+fn test_import_stops_at_first_error() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+
+    let script = "CREATE TABLE a (id INTEGER);\nINSERT INTO missing VALUES (1);\nCREATE TABLE b (id INTEGER);";
+    assert!(executor.import(script, &mut session).is_err());
+    assert_eq!(executor.storage().list_tables(), vec!["a".to_string()]);
+}
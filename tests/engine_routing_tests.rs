@@ -111,6 +111,32 @@ fn test_engine_persistence() {
     }
 }
 
+#[test]
+fn test_default_engine_for_create_table() {
+    let (_temp_dir, mut executor) = setup_sandstone_executor();
+    executor.set_default_engine(EngineType::Sandstone);
+    let mut session = Session::new();
+
+    for sql in [
+        "CREATE TABLE defaulted (id INT PRIMARY KEY)",
+        "CREATE TABLE explicit_granite (id INT PRIMARY KEY) ENGINE = granite",
+        "CREATE TABLE explicit_sandstone (id INT PRIMARY KEY) ENGINE SANDSTONE",
+    ] {
+        executor.execute(Parser::parse(sql).unwrap(), &mut session).unwrap();
+    }
+
+    let engine = |name: &str| executor.storage().get_schema(name).unwrap().engine_type;
+    assert_eq!(engine("defaulted"), EngineType::Sandstone);
+    assert_eq!(engine("explicit_granite"), EngineType::Granite);
+    assert_eq!(engine("explicit_sandstone"), EngineType::Sandstone);
+
+    let err = Parser::parse("CREATE TABLE t (id INT) ENGINE=InnoDB").unwrap_err();
+    assert!(err.to_string().contains("Unknown engine type: 'InnoDB'"), "{}", err);
+    assert!(Parser::parse("ALTER TABLE t ENGINE=Basalt").is_err());
+    assert_eq!("Sandstone".parse::<EngineType>(), Ok(EngineType::Sandstone));
+    assert!("innodb".parse::<EngineType>().is_err());
+}
+
 #[test]
 fn test_alter_table_switching_execution() {
     let (_temp_dir, executor) = setup_sandstone_executor();
@@ -338,3 +364,119 @@ fn test_modified_engines_tracking() {
     executor.execute(Parser::parse("COMMIT").unwrap(), &mut session).unwrap();
     assert!(session.modified_engines.is_empty(), "modified_engines should be cleared after COMMIT");
 }
+
+#[test]
+fn test_sandstone_drop_and_recreate_starts_empty() {
+    let (_temp_dir, executor) = setup_sandstone_executor();
+    let mut session = Session::new();
+
+    let run = |sql: &str, session: &mut Session| {
+        executor.execute(Parser::parse(sql).unwrap(), session).unwrap()
+    };
+    let count = |session: &mut Session| match run("SELECT * FROM s", session) {
+        minisql::types::QueryResult::Select(rs) => rs.rows.len(),
+        _ => panic!("Expected select result"),
+    };
+
+    run("CREATE TABLE s (id INT PRIMARY KEY, val TEXT) ENGINE=Sandstone", &mut session);
+    run("INSERT INTO s VALUES (1, 'a')", &mut session);
+    run("INSERT INTO s VALUES (2, 'b')", &mut session);
+    assert_eq!(count(&mut session), 2);
+
+    // Dropped rows must not resurface through the in-memory page table
+    run("DROP TABLE s", &mut session);
+    run("CREATE TABLE s (id INT PRIMARY KEY, val TEXT) ENGINE=Sandstone", &mut session);
+    assert_eq!(count(&mut session), 0);
+
+    // Nor be written back to disk by a later flush
+    run("INSERT INTO s VALUES (3, 'c')", &mut session);
+    executor.shutdown().unwrap();
+    assert_eq!(executor.storage().scan_table("s").unwrap().len(), 1);
+
+    // TRUNCATE clears the in-memory rows as well
+    run("TRUNCATE TABLE s", &mut session);
+    assert_eq!(count(&mut session), 0);
+}
+
+#[test]
+fn test_sandstone_shutdown_with_pending_flush() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(temp_dir.path().to_path_buf()).unwrap();
+    // Long interval: the worker is still waiting when shutdown is requested
+    let config = SandstoneConfig { flush_interval_ms: 60_000, ..SandstoneConfig::default() };
+    let executor = Executor::with_sandstone(storage, txn_manager, config).unwrap();
+    let mut session = Session::new();
+
+    for i in 0..40 {
+        let create = format!("CREATE TABLE s{} (id INT PRIMARY KEY, val TEXT) ENGINE=Sandstone", i);
+        executor.execute(Parser::parse(&create).unwrap(), &mut session).unwrap();
+        let insert = format!("INSERT INTO s{} VALUES (1, 'row')", i);
+        executor.execute(Parser::parse(&insert).unwrap(), &mut session).unwrap();
+    }
+
+    // Dropping the executor stops the worker, which writes every dirty table
+    let start = std::time::Instant::now();
+    drop(executor);
+    assert!(start.elapsed() < std::time::Duration::from_secs(10), "shutdown waited for flush interval");
+
+    let tables_dir = temp_dir.path().join("tables");
+    let leftovers: Vec<_> = std::fs::read_dir(&tables_dir).unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "partial table files left behind: {:?}", leftovers);
+
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    for i in 0..40 {
+        assert_eq!(storage.scan_table(&format!("s{}", i)).unwrap().len(), 1);
+    }
+}
+
+#[test]
+fn test_stale_table_temp_file_removed_on_open() {
+    let temp_dir = TempDir::new().unwrap();
+    let tables_dir = temp_dir.path().join("tables");
+    std::fs::create_dir_all(&tables_dir).unwrap();
+    std::fs::write(tables_dir.join("t.dat.tmp"), "{\"partial\":").unwrap();
+
+    let _storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    assert!(!tables_dir.join("t.dat.tmp").exists());
+}
+
+#[test]
+fn test_sandstone_truncate_before_flush_returns_no_rows() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(temp_dir.path().to_path_buf()).unwrap();
+    // Long interval: the inserted rows exist only in memory when TRUNCATE runs
+    let config = SandstoneConfig { flush_interval_ms: 60_000, ..SandstoneConfig::default() };
+    let executor = Executor::with_sandstone(storage, txn_manager, config).unwrap();
+    let mut session = Session::new();
+
+    let run = |sql: &str, session: &mut Session| {
+        executor.execute(Parser::parse(sql).unwrap(), session).unwrap()
+    };
+    let select = |sql: &str, session: &mut Session| match run(sql, session) {
+        minisql::types::QueryResult::Select(rs) => rs.rows,
+        _ => panic!("Expected select result"),
+    };
+
+    run("CREATE TABLE s (id INT PRIMARY KEY AUTO_INCREMENT, val TEXT) ENGINE=Sandstone", &mut session);
+    for val in ["a", "b", "c"] {
+        run(&format!("INSERT INTO s (val) VALUES ('{}')", val), &mut session);
+    }
+    assert_eq!(select("SELECT * FROM s", &mut session).len(), 3);
+
+    run("TRUNCATE TABLE s", &mut session);
+    assert!(select("SELECT * FROM s", &mut session).is_empty());
+
+    // AUTO_INCREMENT restarts and the truncated rows never reach disk
+    run("INSERT INTO s (val) VALUES ('d')", &mut session);
+    assert_eq!(
+        select("SELECT id, val FROM s", &mut session),
+        vec![vec![minisql::types::Value::Integer(1), minisql::types::Value::String("d".into())]]
+    );
+    executor.shutdown().unwrap();
+    assert_eq!(executor.storage().scan_table("s").unwrap().len(), 1);
+}
@@ -0,0 +1,171 @@
+//! Tests for EXPLAIN SELECT
+
+use minisql::engines::granite::TransactionManager;
+use minisql::engines::SandstoneConfig;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::tempdir;
+
+fn setup() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig::default()).unwrap();
+    let mut session = Session::new();
+    for sql in [
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, city TEXT)",
+        "CREATE INDEX idx_email ON users (email)",
+        "INSERT INTO users VALUES (1, 'a@x', 'Oslo'), (2, 'b@x', 'Oslo'), (3, 'c@x', 'Rome')",
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER, total INTEGER)",
+        "INSERT INTO orders VALUES (1, 1, 10), (2, 1, 20), (3, 3, 30), (4, 2, 40)",
+    ] {
+        execute(&executor, &mut session, sql);
+    }
+    (executor, session, dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+/// EXPLAIN rows as (table, type, key, rows, Extra)
+fn explain(executor: &Executor, session: &mut Session, select: &str) -> Vec<(Value, Value, Value, Value, Value)> {
+    match execute(executor, session, &format!("EXPLAIN {}", select)) {
+        QueryResult::Select(rs) => {
+            assert_eq!(rs.columns, vec!["id", "table", "type", "key", "rows", "Extra"]);
+            rs.rows.into_iter().map(|r| (r[1].clone(), r[2].clone(), r[3].clone(), r[4].clone(), r[5].clone())).collect()
+        }
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+fn text(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+#[test]
+fn test_explain_indexed_equality_uses_index() {
+    let (executor, mut session, _dir) = setup();
+
+    assert_eq!(explain(&executor, &mut session, "SELECT * FROM users WHERE email = 'b@x'"), vec![
+        (text("users"), text("index"), text("idx_email"), Value::Integer(1), text("Using where")),
+    ]);
+
+    // Primary key lookups go through the primary index
+    assert_eq!(explain(&executor, &mut session, "SELECT * FROM users u WHERE u.id = 3 AND city = 'Rome'"), vec![
+        (text("u"), text("index"), text("PRIMARY"), Value::Integer(1), text("Using where")),
+    ]);
+}
+
+#[test]
+fn test_primary_key_equality_is_a_single_lookup() {
+    let (executor, mut session, _dir) = setup();
+    // A wider index leading with the key column must not win over the primary key
+    execute(&executor, &mut session, "CREATE INDEX idx_id_city ON users (id, city)");
+
+    assert_eq!(executor.storage().find_index_for_columns("users", &["id".to_string()]).unwrap().name, "PRIMARY_users");
+    assert_eq!(explain(&executor, &mut session, "SELECT * FROM users WHERE id = 2"), vec![
+        (text("users"), text("index"), text("PRIMARY"), Value::Integer(1), text("Using where")),
+    ]);
+    match execute(&executor, &mut session, "SELECT * FROM users WHERE id = 2") {
+        QueryResult::Select(rs) => assert_eq!(rs.rows, vec![vec![Value::Integer(2), text("b@x"), text("Oslo")]]),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_explain_unindexed_predicate_is_full_scan() {
+    let (executor, mut session, _dir) = setup();
+
+    assert_eq!(explain(&executor, &mut session, "SELECT * FROM users WHERE city = 'Oslo'"), vec![
+        (text("users"), text("ALL"), Value::Null, Value::Integer(3), text("Using where")),
+    ]);
+    assert_eq!(explain(&executor, &mut session, "SELECT COUNT(*) FROM orders"), vec![
+        (text("orders"), text("ALL"), Value::Null, Value::Integer(4), Value::Null),
+    ]);
+    assert_eq!(explain(&executor, &mut session, "SELECT 1")[0].4, text("No tables used"));
+}
+
+#[test]
+fn test_explain_reports_join_strategy() {
+    let (executor, mut session, _dir) = setup();
+
+    let plan = explain(&executor, &mut session,
+        "SELECT u.email, o.total FROM users u JOIN orders o ON u.id = o.user_id JOIN orders o2 ON o2.total > u.id");
+    assert_eq!(plan, vec![
+        (text("u"), text("ALL"), Value::Null, Value::Integer(3), Value::Null),
+        (text("o"), text("ALL"), Value::Null, Value::Integer(4), text("Using hash join")),
+        (text("o2"), text("ALL"), Value::Null, Value::Integer(4), text("Using nested loop join")),
+    ]);
+}
+
+#[test]
+fn test_explain_sandstone_table_scans() {
+    let (executor, mut session, _dir) = setup();
+    execute(&executor, &mut session, "CREATE TABLE cache (k TEXT PRIMARY KEY, v TEXT) ENGINE=Sandstone");
+    execute(&executor, &mut session, "INSERT INTO cache VALUES ('a', '1')");
+
+    assert_eq!(explain(&executor, &mut session, "SELECT v FROM cache WHERE k = 'a'"), vec![
+        (text("cache"), text("ALL"), Value::Null, Value::Integer(1), text("Using where")),
+    ]);
+}
+
+#[test]
+fn test_explain_range_on_indexed_column() {
+    let (executor, mut session, _dir) = setup();
+    execute(&executor, &mut session, "CREATE TABLE readings (id INTEGER PRIMARY KEY, temp INTEGER)");
+    execute(&executor, &mut session, "CREATE INDEX idx_temp ON readings (temp)");
+    execute(&executor, &mut session, "INSERT INTO readings VALUES (1, -12), (2, -4), (3, 0), (4, 3), (5, 7), (6, 25)");
+
+    let select = "SELECT id FROM readings WHERE temp > 0 AND temp <= 7";
+    assert_eq!(explain(&executor, &mut session, select), vec![
+        (text("readings"), text("range"), text("idx_temp"), Value::Integer(2), text("Using where")),
+    ]);
+    match execute(&executor, &mut session, &format!("{} ORDER BY id", select)) {
+        QueryResult::Select(rs) => assert_eq!(rs.rows, vec![
+            vec![Value::Integer(4)],
+            vec![Value::Integer(5)],
+        ]),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_explain_or_of_indexed_columns_merges_indexes() {
+    let (executor, mut session, _dir) = setup();
+    execute(&executor, &mut session, "CREATE INDEX idx_city ON users (city)");
+
+    // Row 1 matches both branches: it is estimated once per branch but read once
+    let select = "SELECT id FROM users WHERE email = 'a@x' OR city = 'Oslo'";
+    assert_eq!(explain(&executor, &mut session, select), vec![
+        (text("users"), text("index_merge"), text("idx_email,idx_city"), Value::Integer(3), text("Using where")),
+    ]);
+    match execute(&executor, &mut session, &format!("{} ORDER BY id", select)) {
+        QueryResult::Select(rs) => assert_eq!(rs.rows, vec![vec![Value::Integer(1)], vec![Value::Integer(2)]]),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+
+    // The remaining predicate is still applied to the merged rows
+    match execute(&executor, &mut session, "SELECT id FROM users WHERE (email = 'c@x' OR city = 'Oslo') AND id <> 1 ORDER BY id") {
+        QueryResult::Select(rs) => assert_eq!(rs.rows, vec![vec![Value::Integer(2)], vec![Value::Integer(3)]]),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+
+    // One unindexed branch means the whole OR needs a scan
+    assert_eq!(explain(&executor, &mut session, "SELECT id FROM users WHERE email = 'a@x' OR id + 1 = 3")[0].1, text("ALL"));
+}
+
+#[test]
+fn test_explain_estimates_rows_without_reading_the_table() {
+    let (executor, mut session, _dir) = setup();
+    let scans = executor.storage().full_scan_count();
+
+    assert_eq!(explain(&executor, &mut session, "SELECT * FROM users u JOIN orders o ON u.id = o.user_id WHERE u.city = 'Oslo'"), vec![
+        (text("u"), text("ALL"), Value::Null, Value::Integer(3), text("Using where")),
+        (text("o"), text("ALL"), Value::Null, Value::Integer(4), text("Using hash join")),
+    ]);
+    assert_eq!(executor.storage().full_scan_count(), scans);
+}
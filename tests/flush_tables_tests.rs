@@ -0,0 +1,107 @@
+//! Tests for FLUSH TABLES
+
+use minisql::engines::granite::TransactionManager;
+use minisql::engines::SandstoneConfig;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+/// Row values of `table` as read from its file on disk
+fn rows_on_disk(dir: &Path, table: &str) -> Vec<Vec<Value>> {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    storage.scan_table(table).unwrap().into_iter().map(|row| row.values).collect()
+}
+
+#[test]
+fn test_flush_tables_writes_sandstone_rows_before_interval() {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    // The background worker would not flush for a minute
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig {
+        flush_interval_ms: 60_000,
+        ..SandstoneConfig::default()
+    }).unwrap();
+    let mut session = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE hits (id INTEGER PRIMARY KEY, page TEXT) ENGINE=Sandstone");
+
+    // Committed Sandstone writes stay in memory until a flush
+    execute(&executor, &mut session, "BEGIN");
+    execute(&executor, &mut session, "INSERT INTO hits VALUES (1, '/home')");
+    execute(&executor, &mut session, "INSERT INTO hits VALUES (2, '/about')");
+    execute(&executor, &mut session, "COMMIT");
+    assert!(rows_on_disk(dir.path(), "hits").is_empty());
+
+    assert!(matches!(execute(&executor, &mut session, "FLUSH TABLES"), QueryResult::Ok));
+
+    let file = fs::read_to_string(dir.path().join("tables").join("hits.dat")).unwrap();
+    assert!(file.contains("/about"), "hits.dat is missing the rows: {}", file);
+    assert_eq!(rows_on_disk(dir.path(), "hits"), vec![
+        vec![Value::Integer(1), Value::String("/home".to_string())],
+        vec![Value::Integer(2), Value::String("/about".to_string())],
+    ]);
+}
+
+#[test]
+fn test_flush_tables_rejected_inside_transaction() {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+
+    execute(&executor, &mut session, "BEGIN");
+    let err = executor.execute(Parser::parse("FLUSH TABLES").unwrap(), &mut session).unwrap_err();
+    assert!(err.to_string().contains("inside a transaction"), "{}", err);
+    execute(&executor, &mut session, "ROLLBACK");
+    assert!(matches!(execute(&executor, &mut session, "flush tables;"), QueryResult::Ok));
+}
+
+#[test]
+fn test_worker_flushes_early_past_max_dirty_tables() {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    // Only the dirty-table bound can make the worker flush within the test
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig {
+        flush_interval_ms: 60_000,
+        max_dirty_tables: Some(2),
+        ..SandstoneConfig::default()
+    }).unwrap();
+    let mut session = Session::new();
+    for table in ["a", "b", "c"] {
+        execute(&executor, &mut session, &format!("CREATE TABLE {} (id INTEGER) ENGINE=Sandstone", table));
+    }
+
+    // Writes inside a transaction are left to the background worker
+    execute(&executor, &mut session, "BEGIN");
+    execute(&executor, &mut session, "INSERT INTO a VALUES (1)");
+    execute(&executor, &mut session, "INSERT INTO b VALUES (2)");
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(rows_on_disk(dir.path(), "a").is_empty(), "two dirty tables are within the bound");
+
+    execute(&executor, &mut session, "INSERT INTO c VALUES (3)");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let on_disk = |table| rows_on_disk(dir.path(), table);
+    while ["a", "b", "c"].iter().any(|table| on_disk(table).is_empty()) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(on_disk("a"), vec![vec![Value::Integer(1)]]);
+    assert_eq!(on_disk("b"), vec![vec![Value::Integer(2)]]);
+    assert_eq!(on_disk("c"), vec![vec![Value::Integer(3)]]);
+    for table in ["a", "b", "c"] {
+        let file = dir.path().join("tables").join(format!("{}.dat", table));
+        assert!(fs::metadata(&file).unwrap().len() > 0, "{} is empty", file.display());
+    }
+    execute(&executor, &mut session, "COMMIT");
+}
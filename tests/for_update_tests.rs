@@ -0,0 +1,178 @@
+//! Tests for SELECT ... FOR UPDATE row locking
+//!
+//! Two sessions share one executor; the second runs on its own thread so it
+//! can block on a row the first has locked.
+
+mod common;
+
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use minisql::engines::granite::TransactionManager;
+use minisql::engines::SandstoneConfig;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use minisql::{Config, Server};
+use tempfile::TempDir;
+
+use common::{connect, query, read_packet, serve, write_packet};
+
+fn setup(engine: &str) -> (TempDir, Executor) {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(temp_dir.path().to_path_buf()).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig::default()).unwrap();
+    let mut session = Session::new();
+    run(&executor, &mut session, &format!("CREATE TABLE accounts (id INT PRIMARY KEY, balance INT) ENGINE={}", engine));
+    run(&executor, &mut session, "INSERT INTO accounts VALUES (1, 100), (2, 200)");
+    (temp_dir, executor)
+}
+
+fn run(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    executor.execute(Parser::parse(sql).unwrap(), session)
+        .unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn scalar(executor: &Executor, session: &mut Session, sql: &str) -> Value {
+    match run(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows[0][0].clone(),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+/// Session whose lock waits give up after `ms` milliseconds
+fn session_with_lock_wait(ms: u64) -> Session {
+    let mut session = Session::new();
+    session.lock_wait_timeout_ms = ms;
+    session
+}
+
+#[test]
+fn test_update_blocks_until_locking_transaction_commits() {
+    let (_temp_dir, executor) = setup("Granite");
+    let mut first = Session::new();
+    run(&executor, &mut first, "BEGIN");
+    assert_eq!(scalar(&executor, &mut first, "SELECT balance FROM accounts WHERE id = 1 FOR UPDATE"), Value::Integer(100));
+
+    thread::scope(|scope| {
+        let (done_tx, done_rx) = mpsc::channel();
+        let executor = &executor;
+        scope.spawn(move || {
+            let mut second = Session::new();
+            run(executor, &mut second, "BEGIN");
+            run(executor, &mut second, "UPDATE accounts SET balance = balance - 10 WHERE id = 1");
+            run(executor, &mut second, "COMMIT");
+            done_tx.send(()).unwrap();
+        });
+
+        // The second transaction is stuck on the locked row
+        assert!(done_rx.recv_timeout(Duration::from_millis(300)).is_err());
+
+        run(executor, &mut first, "UPDATE accounts SET balance = balance + 50 WHERE id = 1");
+        run(executor, &mut first, "COMMIT");
+        done_rx.recv_timeout(Duration::from_secs(10)).expect("the blocked UPDATE should finish after COMMIT");
+    });
+
+    // The blocked UPDATE applied on top of the first transaction's change
+    let mut session = Session::new();
+    assert_eq!(scalar(&executor, &mut session, "SELECT balance FROM accounts WHERE id = 1"), Value::Integer(140));
+}
+
+#[test]
+fn test_lock_wait_timeout_and_release_on_rollback() {
+    let (_temp_dir, executor) = setup("Granite");
+    let mut first = Session::new();
+    run(&executor, &mut first, "BEGIN");
+    run(&executor, &mut first, "SELECT * FROM accounts WHERE id = 1 FOR UPDATE");
+
+    let mut second = session_with_lock_wait(50);
+    let err = executor
+        .execute(Parser::parse("DELETE FROM accounts WHERE id = 1").unwrap(), &mut second)
+        .unwrap_err();
+    assert_eq!(err.mysql_error_code(), 1205);
+    run(&executor, &mut second, "BEGIN");
+    assert!(executor
+        .execute(Parser::parse("SELECT * FROM accounts WHERE id = 1 FOR UPDATE").unwrap(), &mut second)
+        .is_err());
+
+    // Rows the first transaction did not lock are free
+    run(&executor, &mut second, "UPDATE accounts SET balance = 0 WHERE id = 2");
+
+    // Rolling back releases the lock, and the second transaction still works
+    run(&executor, &mut first, "ROLLBACK");
+    run(&executor, &mut second, "UPDATE accounts SET balance = 1 WHERE id = 1");
+    run(&executor, &mut second, "COMMIT");
+    assert_eq!(scalar(&executor, &mut first, "SELECT SUM(balance) FROM accounts"), Value::Integer(1));
+}
+
+#[test]
+fn test_locking_read_waits_for_uncommitted_write() {
+    let (_temp_dir, executor) = setup("Granite");
+    let mut writer = Session::new();
+    run(&executor, &mut writer, "BEGIN");
+    run(&executor, &mut writer, "UPDATE accounts SET balance = 150 WHERE id = 1");
+
+    let mut reader = session_with_lock_wait(50);
+    run(&executor, &mut reader, "BEGIN");
+    assert!(executor
+        .execute(Parser::parse("SELECT balance FROM accounts WHERE id = 1 FOR UPDATE").unwrap(), &mut reader)
+        .is_err());
+
+    run(&executor, &mut writer, "COMMIT");
+    assert_eq!(scalar(&executor, &mut reader, "SELECT balance FROM accounts WHERE id = 1 FOR UPDATE"), Value::Integer(150));
+    run(&executor, &mut reader, "COMMIT");
+}
+
+#[test]
+fn test_sandstone_ignores_for_update() {
+    let (_temp_dir, executor) = setup("Sandstone");
+    let mut first = Session::new();
+    run(&executor, &mut first, "BEGIN");
+    assert_eq!(scalar(&executor, &mut first, "SELECT balance FROM accounts WHERE id = 1 FOR UPDATE"), Value::Integer(100));
+
+    let mut second = session_with_lock_wait(0);
+    run(&executor, &mut second, "UPDATE accounts SET balance = 5 WHERE id = 1");
+    run(&executor, &mut first, "COMMIT");
+    assert_eq!(scalar(&executor, &mut second, "SELECT balance FROM accounts WHERE id = 1"), Value::Integer(5));
+}
+
+/// A connection waiting for a row lock must not stall the runtime thread the
+/// connection holding the lock needs to commit (a current-thread runtime has
+/// only the one)
+#[tokio::test]
+async fn test_lock_wait_does_not_block_other_connections() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config {
+        data_dir: temp_dir.path().to_path_buf(),
+        password: String::new(),
+        ..Config::default()
+    };
+    let server = Arc::new(Server::new(config).await.unwrap());
+    let executor = server.executor();
+    let mut session = Session::new();
+    run(&executor, &mut session, "CREATE TABLE accounts (id INT PRIMARY KEY, balance INT)");
+    run(&executor, &mut session, "INSERT INTO accounts VALUES (1, 100)");
+    let addr = serve(&server).await;
+
+    let mut holder = connect(addr).await;
+    assert_eq!(query(&mut holder, "BEGIN").await[0], 0x00);
+    assert_eq!(query(&mut holder, "UPDATE accounts SET balance = 150 WHERE id = 1").await[0], 0x00);
+
+    let mut waiter = connect(addr).await;
+    write_packet(&mut waiter, 0, b"\x03UPDATE accounts SET balance = balance + 1 WHERE id = 1").await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let commit = tokio::time::timeout(Duration::from_secs(10), query(&mut holder, "COMMIT"))
+        .await
+        .expect("COMMIT should not wait behind the blocked UPDATE");
+    assert_eq!(commit[0], 0x00);
+    let ok = read_packet(&mut waiter).await;
+    assert_eq!(ok[0], 0x00, "the blocked UPDATE should finish after COMMIT, got {:?}", ok);
+    assert_eq!(scalar(&executor, &mut session, "SELECT balance FROM accounts WHERE id = 1"), Value::Integer(151));
+
+    server.shutdown();
+}
@@ -0,0 +1,221 @@
+//! Tests for FOREIGN KEY constraints
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn setup(dir: &Path) -> (Executor, Session) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    txn_manager.recover(&storage).unwrap();
+    (Executor::new(storage, txn_manager), Session::new())
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn execute_err(executor: &Executor, session: &mut Session, sql: &str) -> u16 {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    match executor.execute(stmt, session) {
+        Ok(result) => panic!("SQL '{}' should have failed, got {:?}", sql, result),
+        Err(e) => e.mysql_error_code(),
+    }
+}
+
+fn select_ids(executor: &Executor, session: &mut Session, sql: &str) -> Vec<i64> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows.iter().map(|row| match row[0] {
+            Value::Integer(i) => i,
+            ref other => panic!("Expected integer, got {:?}", other),
+        }).collect(),
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn create_users_and_orders(executor: &Executor, session: &mut Session, on_delete: &str) {
+    execute(executor, session, "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)");
+    execute(executor, session, &format!(
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER, \
+         FOREIGN KEY (user_id) REFERENCES users(id) {})",
+        on_delete
+    ));
+    execute(executor, session, "INSERT INTO users VALUES (1, 'ann')");
+    execute(executor, session, "INSERT INTO users VALUES (2, 'bob')");
+    execute(executor, session, "INSERT INTO orders VALUES (10, 1)");
+    execute(executor, session, "INSERT INTO orders VALUES (11, 1)");
+    execute(executor, session, "INSERT INTO orders VALUES (20, 2)");
+}
+
+#[test]
+fn test_insert_orphan_rejected() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_users_and_orders(&executor, &mut session, "");
+
+    assert_eq!(execute_err(&executor, &mut session, "INSERT INTO orders VALUES (30, 99)"), 1452);
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM orders WHERE id = 30"), Vec::<i64>::new());
+
+    // A NULL key references nothing and is always allowed
+    execute(&executor, &mut session, "INSERT INTO orders VALUES (31, NULL)");
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM orders WHERE id = 31"), vec![31]);
+}
+
+#[test]
+fn test_update_checks_both_sides() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_users_and_orders(&executor, &mut session, "");
+
+    // Child pointed at a missing parent
+    assert_eq!(execute_err(&executor, &mut session, "UPDATE orders SET user_id = 99 WHERE id = 10"), 1452);
+    execute(&executor, &mut session, "UPDATE orders SET user_id = 2 WHERE id = 10");
+
+    // Referenced parent key changed
+    assert_eq!(execute_err(&executor, &mut session, "UPDATE users SET id = 5 WHERE id = 2"), 1451);
+    execute(&executor, &mut session, "UPDATE users SET name = 'bobby' WHERE id = 2");
+
+    // Once unreferenced, the key may change
+    execute(&executor, &mut session, "DELETE FROM orders WHERE id = 11");
+    execute(&executor, &mut session, "UPDATE users SET id = 7 WHERE id = 1");
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM users ORDER BY id"), vec![2, 7]);
+}
+
+#[test]
+fn test_delete_restrict_rejected() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_users_and_orders(&executor, &mut session, "ON DELETE RESTRICT");
+
+    assert_eq!(execute_err(&executor, &mut session, "DELETE FROM users WHERE id = 1"), 1451);
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM users ORDER BY id"), vec![1, 2]);
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM orders ORDER BY id"), vec![10, 11, 20]);
+
+    // Without children the parent can go
+    execute(&executor, &mut session, "DELETE FROM orders WHERE user_id = 1");
+    execute(&executor, &mut session, "DELETE FROM users WHERE id = 1");
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM users"), vec![2]);
+}
+
+#[test]
+fn test_delete_cascade_removes_children() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_users_and_orders(&executor, &mut session, "ON DELETE CASCADE");
+    execute(&executor, &mut session,
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, order_id INTEGER, \
+         CONSTRAINT fk_item_order FOREIGN KEY (order_id) REFERENCES orders (id) ON DELETE CASCADE)");
+    execute(&executor, &mut session, "INSERT INTO items VALUES (100, 10)");
+    execute(&executor, &mut session, "INSERT INTO items VALUES (101, 11)");
+    execute(&executor, &mut session, "INSERT INTO items VALUES (200, 20)");
+
+    match execute(&executor, &mut session, "DELETE FROM users WHERE id = 1") {
+        QueryResult::Modified { rows_affected, .. } => assert_eq!(rows_affected, 1),
+        other => panic!("Expected Modified result, got {:?}", other),
+    }
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM orders ORDER BY id"), vec![20]);
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM items ORDER BY id"), vec![200]);
+}
+
+#[test]
+fn test_cascade_blocked_by_restrict_grandchild() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_users_and_orders(&executor, &mut session, "ON DELETE CASCADE");
+    execute(&executor, &mut session,
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, order_id INTEGER, FOREIGN KEY (order_id) REFERENCES orders (id))");
+    execute(&executor, &mut session, "INSERT INTO items VALUES (100, 10)");
+
+    assert_eq!(execute_err(&executor, &mut session, "DELETE FROM users WHERE id = 1"), 1451);
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM orders ORDER BY id"), vec![10, 11, 20]);
+
+    // The parent without restricted grandchildren cascades normally
+    execute(&executor, &mut session, "DELETE FROM users WHERE id = 2");
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM orders ORDER BY id"), vec![10, 11]);
+}
+
+#[test]
+fn test_self_reference_cascade() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    execute(&executor, &mut session,
+        "CREATE TABLE nodes (id INTEGER PRIMARY KEY, parent_id INTEGER, \
+         FOREIGN KEY (parent_id) REFERENCES nodes (id) ON DELETE CASCADE)");
+    execute(&executor, &mut session, "INSERT INTO nodes VALUES (1, NULL)");
+    execute(&executor, &mut session, "INSERT INTO nodes VALUES (2, 1)");
+    execute(&executor, &mut session, "INSERT INTO nodes VALUES (3, 2)");
+    execute(&executor, &mut session, "INSERT INTO nodes VALUES (4, 4)");
+    execute(&executor, &mut session, "INSERT INTO nodes VALUES (5, NULL)");
+
+    execute(&executor, &mut session, "DELETE FROM nodes WHERE id = 1");
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM nodes ORDER BY id"), vec![4, 5]);
+}
+
+#[test]
+fn test_parent_lookups_use_indexes() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_users_and_orders(&executor, &mut session, "ON DELETE CASCADE");
+    execute(&executor, &mut session, "CREATE INDEX idx_orders_user ON orders (user_id)");
+
+    let scans = executor.storage().full_scan_count();
+    execute(&executor, &mut session, "INSERT INTO orders VALUES (12, 2)");
+    execute(&executor, &mut session, "DELETE FROM users WHERE id = 1");
+    assert_eq!(executor.storage().full_scan_count(), scans);
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM orders ORDER BY id"), vec![12, 20]);
+}
+
+#[test]
+fn test_drop_and_truncate_referenced_parent_rejected() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_users_and_orders(&executor, &mut session, "");
+
+    assert_eq!(execute_err(&executor, &mut session, "DROP TABLE users"), 3730);
+    assert_eq!(execute_err(&executor, &mut session, "TRUNCATE TABLE users"), 1701);
+    assert_eq!(
+        execute_err(&executor, &mut session,
+            "CREATE TABLE bad (id INTEGER, FOREIGN KEY (id) REFERENCES missing (id))"),
+        1146
+    );
+
+    execute(&executor, &mut session, "DROP TABLE orders");
+    execute(&executor, &mut session, "DROP TABLE users");
+}
+
+#[test]
+fn test_foreign_keys_persist_and_round_trip_through_dump() {
+    let dir = tempdir().unwrap();
+    {
+        let (executor, mut session) = setup(dir.path());
+        // Child created under a name that sorts before its parent
+        execute(&executor, &mut session, "CREATE TABLE b_parent (id INTEGER PRIMARY KEY)");
+        execute(&executor, &mut session,
+            "CREATE TABLE a_child (id INTEGER PRIMARY KEY, p INTEGER, \
+             CONSTRAINT fk_p FOREIGN KEY (p) REFERENCES b_parent (id) ON DELETE CASCADE)");
+        execute(&executor, &mut session, "INSERT INTO b_parent VALUES (1)");
+        execute(&executor, &mut session, "INSERT INTO a_child VALUES (1, 1)");
+        executor.shutdown().unwrap();
+    }
+
+    let (executor, mut session) = setup(dir.path());
+    assert_eq!(execute_err(&executor, &mut session, "INSERT INTO a_child VALUES (2, 9)"), 1452);
+
+    let mut script = Vec::new();
+    executor.dump(&mut script).unwrap();
+    let script = String::from_utf8(script).unwrap();
+    assert!(script.contains("CONSTRAINT `fk_p` FOREIGN KEY (`p`) REFERENCES `b_parent` (`id`) ON DELETE CASCADE"));
+    assert!(script.find("CREATE TABLE `b_parent`").unwrap() < script.find("CREATE TABLE `a_child`").unwrap());
+
+    let copy = tempdir().unwrap();
+    let (restored, mut restored_session) = setup(copy.path());
+    restored.import(&script, &mut restored_session).unwrap();
+    assert!(restored_session.foreign_key_checks);
+    execute(&restored, &mut restored_session, "DELETE FROM b_parent WHERE id = 1");
+    assert_eq!(select_ids(&restored, &mut restored_session, "SELECT id FROM a_child"), Vec::<i64>::new());
+}
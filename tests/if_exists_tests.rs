@@ -0,0 +1,75 @@
+//! Tests for CREATE TABLE IF NOT EXISTS, DROP TABLE IF EXISTS and DROP INDEX IF EXISTS
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn open(dir: &Path) -> (Executor, Session) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    txn_manager.recover(&storage).unwrap();
+    (Executor::new(storage, txn_manager), Session::new())
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn execute_err(executor: &Executor, session: &mut Session, sql: &str) -> String {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).expect_err(&format!("SQL '{}' should fail", sql)).to_string()
+}
+
+#[test]
+fn test_create_table_if_not_exists_keeps_existing_table() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = open(dir.path());
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (1, 'kept')");
+
+    assert!(matches!(
+        execute(&executor, &mut session, "CREATE TABLE IF NOT EXISTS t (other INTEGER)"),
+        QueryResult::Ok
+    ));
+    execute_err(&executor, &mut session, "CREATE TABLE t (other INTEGER)");
+
+    // Neither the schema nor the rows changed, before or after a restart
+    drop(executor);
+    let (executor, mut session) = open(dir.path());
+    match execute(&executor, &mut session, "SELECT id, name FROM t") {
+        QueryResult::Select(rs) => assert_eq!(rs.rows, vec![vec![Value::Integer(1), Value::String("kept".to_string())]]),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_drop_table_if_exists() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = open(dir.path());
+
+    assert!(matches!(execute(&executor, &mut session, "DROP TABLE IF EXISTS missing"), QueryResult::Ok));
+    assert!(execute_err(&executor, &mut session, "DROP TABLE missing").contains("missing"));
+
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER)");
+    execute(&executor, &mut session, "DROP TABLE IF EXISTS t");
+    assert!(!executor.storage().table_exists("t"));
+}
+
+#[test]
+fn test_drop_index_if_exists() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = open(dir.path());
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER, email TEXT)");
+
+    assert!(matches!(execute(&executor, &mut session, "DROP INDEX IF EXISTS idx_email"), QueryResult::Ok));
+    assert!(execute_err(&executor, &mut session, "DROP INDEX idx_email").contains("idx_email"));
+
+    execute(&executor, &mut session, "CREATE INDEX idx_email ON t (email)");
+    execute(&executor, &mut session, "DROP INDEX IF EXISTS idx_email");
+    assert!(executor.storage().get_index("idx_email").is_err());
+}
@@ -0,0 +1,90 @@
+//! Tests for INSERT with an explicit column list
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::tempdir;
+
+fn create_test_executor() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    (Executor::new(storage, txn_manager), Session::new(), dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+#[test]
+fn test_reordered_columns_map_to_schema_order() {
+    let (executor, mut session, _dir) = create_test_executor();
+    execute(&executor, &mut session, "CREATE TABLE t (a INTEGER PRIMARY KEY, b INTEGER, c TEXT)");
+    execute(&executor, &mut session, "INSERT INTO t (b, a, c) VALUES (1, 2, 'x')");
+    execute(&executor, &mut session, "INSERT INTO t (c, b, a) VALUES ('y', 10, 20), ('z', 30, 40)");
+
+    assert_eq!(select_rows(&executor, &mut session, "SELECT a, b, c FROM t ORDER BY a"), vec![
+        vec![Value::Integer(2), Value::Integer(1), Value::String("x".into())],
+        vec![Value::Integer(20), Value::Integer(10), Value::String("y".into())],
+        vec![Value::Integer(40), Value::Integer(30), Value::String("z".into())],
+    ]);
+}
+
+#[test]
+fn test_omitted_columns_take_defaults() {
+    let (executor, mut session, _dir) = create_test_executor();
+    execute(&executor, &mut session,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY AUTO_INCREMENT, status TEXT DEFAULT 'new', \
+         qty INTEGER NOT NULL DEFAULT 1, note TEXT)");
+    execute(&executor, &mut session, "INSERT INTO t (note) VALUES ('first')");
+    execute(&executor, &mut session, "INSERT INTO t (qty, status) VALUES (5, 'done')");
+
+    assert_eq!(select_rows(&executor, &mut session, "SELECT id, status, qty, note FROM t ORDER BY id"), vec![
+        vec![Value::Integer(1), Value::String("new".into()), Value::Integer(1), Value::String("first".into())],
+        vec![Value::Integer(2), Value::String("done".into()), Value::Integer(5), Value::Null],
+    ]);
+
+    // A NOT NULL column without a default cannot be left out
+    execute(&executor, &mut session, "CREATE TABLE strict (id INTEGER PRIMARY KEY, name TEXT NOT NULL)");
+    let stmt = Parser::parse("INSERT INTO strict (id) VALUES (1)").unwrap();
+    let err = executor.execute(stmt, &mut session).unwrap_err();
+    assert_eq!(err.to_string(), "Field 'name' doesn't have a default value");
+    assert_eq!(err.mysql_error_code(), 1364);
+}
+
+#[test]
+fn test_column_list_errors() {
+    let (executor, mut session, _dir) = create_test_executor();
+    execute(&executor, &mut session, "CREATE TABLE t (a INTEGER PRIMARY KEY, b INTEGER)");
+
+    for sql in [
+        "INSERT INTO t (a, b) VALUES (1)",
+        "INSERT INTO t (a) VALUES (1, 2)",
+        "INSERT INTO t (a, b) VALUES (1, 2), (3)",
+        "INSERT INTO t VALUES (1)",
+    ] {
+        let err = executor.execute(Parser::parse(sql).unwrap(), &mut session).unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1136, "{}", sql);
+        assert_eq!(err.sql_state(), "21S01", "{}", sql);
+    }
+
+    let err = executor.execute(Parser::parse("INSERT INTO t (a, b, a) VALUES (1, 2, 3)").unwrap(), &mut session).unwrap_err();
+    assert_eq!(err.to_string(), "Column 'a' specified twice");
+    assert_eq!(err.mysql_error_code(), 1110);
+
+    let err = executor.execute(Parser::parse("INSERT INTO t (a, nope) VALUES (1, 2)").unwrap(), &mut session).unwrap_err();
+    assert_eq!(err.mysql_error_code(), 1054);
+
+    // Nothing from the failed statements was inserted
+    assert!(select_rows(&executor, &mut session, "SELECT * FROM t").is_empty());
+}
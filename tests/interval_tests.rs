@@ -0,0 +1,111 @@
+//! Tests for INTERVAL arithmetic on timestamps
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::tempdir;
+
+fn create_test_executor() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    (Executor::new(storage, txn_manager), Session::new(), dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn text(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+#[test]
+fn test_day_interval_arithmetic() {
+    let (executor, mut session, _dir) = create_test_executor();
+    let rows = select_rows(&executor, &mut session,
+        "SELECT '2024-02-28' + INTERVAL 1 DAY, '2024-03-01' - INTERVAL 1 DAY, \
+         '2023-12-31 23:00:00' + INTERVAL 2 HOUR, '2024-01-01' - INTERVAL 30 SECOND, \
+         DATE_ADD('2024-01-10', INTERVAL -2 WEEK), DATE_SUB('2024-01-01 00:00:00', INTERVAL 1 MINUTE)");
+    assert_eq!(rows, vec![vec![
+        text("2024-02-29"),
+        text("2024-02-29"),
+        text("2024-01-01 01:00:00"),
+        text("2023-12-31 23:59:30"),
+        text("2023-12-27"),
+        text("2023-12-31 23:59:00"),
+    ]]);
+}
+
+#[test]
+fn test_month_and_year_interval_arithmetic() {
+    let (executor, mut session, _dir) = create_test_executor();
+    let rows = select_rows(&executor, &mut session,
+        "SELECT '2024-01-31' + INTERVAL 1 MONTH, '2023-01-31' + INTERVAL 1 MONTH, \
+         '2024-03-31' - INTERVAL 1 MONTH, '2024-12-15' + INTERVAL 1 MONTH, \
+         '2024-02-29' + INTERVAL 1 YEAR, '2024-01-31 12:00:00' + INTERVAL 13 MONTH");
+    assert_eq!(rows, vec![vec![
+        text("2024-02-29"),
+        text("2023-02-28"),
+        text("2024-02-29"),
+        text("2025-01-15"),
+        text("2025-02-28"),
+        text("2025-02-28 12:00:00"),
+    ]]);
+}
+
+#[test]
+fn test_interval_in_where_rolling_window() {
+    let (executor, mut session, _dir) = create_test_executor();
+    execute(&executor, &mut session, "CREATE TABLE events (id INTEGER PRIMARY KEY, created_at TEXT)");
+    execute(&executor, &mut session, "INSERT INTO events VALUES (1, NOW() - INTERVAL 40 DAY)");
+    execute(&executor, &mut session, "INSERT INTO events VALUES (2, NOW() - INTERVAL 8 DAY)");
+    execute(&executor, &mut session, "INSERT INTO events VALUES (3, NOW() - INTERVAL 6 DAY)");
+    execute(&executor, &mut session, "INSERT INTO events VALUES (4, NOW() - INTERVAL 1 HOUR)");
+
+    assert_eq!(
+        select_rows(&executor, &mut session,
+            "SELECT id FROM events WHERE created_at > NOW() - INTERVAL 7 DAY ORDER BY id"),
+        vec![vec![Value::Integer(3)], vec![Value::Integer(4)]]
+    );
+    assert_eq!(
+        select_rows(&executor, &mut session,
+            "SELECT id FROM events WHERE created_at + INTERVAL 1 MONTH < NOW() ORDER BY id"),
+        vec![vec![Value::Integer(1)]]
+    );
+}
+
+#[test]
+fn test_interval_nulls_and_errors() {
+    let (executor, mut session, _dir) = create_test_executor();
+    assert_eq!(
+        select_rows(&executor, &mut session,
+            "SELECT NULL + INTERVAL 1 DAY, 'not a date' - INTERVAL 1 DAY, '9999-12-31' + INTERVAL 1 DAY"),
+        vec![vec![Value::Null, Value::Null, Value::Null]]
+    );
+
+    assert!(Parser::parse("SELECT '2024-01-01' + INTERVAL 1 FORTNIGHT").is_err());
+    let stmt = Parser::parse("SELECT 5 + INTERVAL 1 DAY").unwrap();
+    assert!(executor.execute(stmt, &mut session).is_err());
+}
+
+#[test]
+fn test_column_named_interval_is_not_an_interval() {
+    let (executor, mut session, _dir) = create_test_executor();
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY, interval INTEGER)");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (1, 10)");
+    assert_eq!(
+        select_rows(&executor, &mut session, "SELECT id + interval FROM t"),
+        vec![vec![Value::Integer(11)]]
+    );
+}
@@ -0,0 +1,108 @@
+//! Transaction isolation level tests
+//!
+//! Two sessions share one executor; one reads inside a transaction while the
+//! other commits an update in between.
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{IsolationLevel, QueryResult, Value};
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Executor) {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(temp_dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+    run(&executor, &mut session, "CREATE TABLE accounts (id INT PRIMARY KEY, balance INT)");
+    run(&executor, &mut session, "INSERT INTO accounts VALUES (1, 100)");
+    run(&executor, &mut session, "INSERT INTO accounts VALUES (2, 200)");
+    (temp_dir, executor)
+}
+
+fn run(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    executor.execute(Parser::parse(sql).unwrap(), session).unwrap()
+}
+
+fn scalar(executor: &Executor, session: &mut Session, sql: &str) -> Value {
+    match run(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows[0][0].clone(),
+        _ => panic!("Expected select result"),
+    }
+}
+
+#[test]
+fn test_read_committed_sees_concurrent_update() {
+    let (_temp_dir, executor) = setup();
+    let mut reader = Session::new();
+    let mut writer = Session::new();
+    assert_eq!(reader.isolation_level, IsolationLevel::ReadCommitted);
+
+    run(&executor, &mut reader, "BEGIN");
+    let balance = "SELECT balance FROM accounts WHERE id = 1";
+    assert_eq!(scalar(&executor, &mut reader, balance), Value::Integer(100));
+
+    run(&executor, &mut writer, "UPDATE accounts SET balance = 150 WHERE id = 1");
+
+    assert_eq!(scalar(&executor, &mut reader, balance), Value::Integer(150));
+    run(&executor, &mut reader, "COMMIT");
+}
+
+#[test]
+fn test_repeatable_read_keeps_first_read() {
+    let (_temp_dir, executor) = setup();
+    let mut reader = Session::new();
+    let mut writer = Session::new();
+
+    run(&executor, &mut reader, "SET SESSION TRANSACTION ISOLATION LEVEL REPEATABLE READ");
+    run(&executor, &mut reader, "BEGIN");
+    let balance = "SELECT balance FROM accounts WHERE id = 1";
+    let total = "SELECT SUM(balance) FROM accounts";
+    assert_eq!(scalar(&executor, &mut reader, balance), Value::Integer(100));
+
+    run(&executor, &mut writer, "UPDATE accounts SET balance = 150 WHERE id = 1");
+    run(&executor, &mut writer, "INSERT INTO accounts VALUES (3, 300)");
+
+    // Neither the update nor the new row is visible inside the transaction
+    assert_eq!(scalar(&executor, &mut reader, balance), Value::Integer(100));
+    assert_eq!(scalar(&executor, &mut reader, total), Value::Integer(300));
+
+    // The transaction's own writes are visible on top of its snapshot
+    run(&executor, &mut reader, "UPDATE accounts SET balance = 250 WHERE id = 2");
+    assert_eq!(scalar(&executor, &mut reader, total), Value::Integer(350));
+
+    run(&executor, &mut reader, "COMMIT");
+    assert_eq!(scalar(&executor, &mut reader, balance), Value::Integer(150));
+    assert_eq!(scalar(&executor, &mut reader, total), Value::Integer(700));
+}
+
+#[test]
+fn test_set_transaction_applies_to_next_transaction_only() {
+    let (_temp_dir, executor) = setup();
+    let mut reader = Session::new();
+    let mut writer = Session::new();
+    let balance = "SELECT balance FROM accounts WHERE id = 1";
+
+    run(&executor, &mut reader, "SET TRANSACTION ISOLATION LEVEL SERIALIZABLE");
+    run(&executor, &mut reader, "BEGIN");
+    assert_eq!(scalar(&executor, &mut reader, balance), Value::Integer(100));
+
+    // Characteristics cannot change mid-transaction
+    let err = executor
+        .execute(Parser::parse("SET TRANSACTION ISOLATION LEVEL READ COMMITTED").unwrap(), &mut reader)
+        .unwrap_err();
+    assert_eq!(err.mysql_error_code(), 1568);
+
+    run(&executor, &mut writer, "UPDATE accounts SET balance = 150 WHERE id = 1");
+    assert_eq!(scalar(&executor, &mut reader, balance), Value::Integer(100));
+    run(&executor, &mut reader, "COMMIT");
+
+    // The following transaction is back to the session default (READ COMMITTED)
+    run(&executor, &mut reader, "BEGIN");
+    assert_eq!(scalar(&executor, &mut reader, balance), Value::Integer(150));
+    run(&executor, &mut writer, "UPDATE accounts SET balance = 175 WHERE id = 1");
+    assert_eq!(scalar(&executor, &mut reader, balance), Value::Integer(175));
+    run(&executor, &mut reader, "ROLLBACK");
+}
@@ -0,0 +1,75 @@
+//! Tests for JSON path expressions in ->, ->> and JSON_EXTRACT
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use serde_json::json;
+use tempfile::tempdir;
+
+fn setup() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE people (id INTEGER PRIMARY KEY, doc JSON)");
+    execute(&executor, &mut session,
+        r#"INSERT INTO people VALUES
+            (1, '{"name": {"first": "Ann"}, "addresses": [{"city": "Oslo"}, {"city": "Bergen"}]}'),
+            (2, '{"name": {"first": "Bo"}, "addresses": []}')"#);
+    (executor, session, dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_nested_object_access() {
+    let (executor, mut session, _dir) = setup();
+
+    let rows = select_rows(&executor, &mut session,
+        "SELECT doc->'$.name.first', doc->>'$.name.first' FROM people ORDER BY id");
+    assert_eq!(rows, vec![
+        vec![Value::Json(json!("Ann")), Value::String("Ann".to_string())],
+        vec![Value::Json(json!("Bo")), Value::String("Bo".to_string())],
+    ]);
+
+    let rows = select_rows(&executor, &mut session, "SELECT id FROM people WHERE doc->>'$.name.first' = 'Bo'");
+    assert_eq!(rows, vec![vec![Value::Integer(2)]]);
+}
+
+#[test]
+fn test_array_indexing() {
+    let (executor, mut session, _dir) = setup();
+
+    let rows = select_rows(&executor, &mut session,
+        "SELECT JSON_EXTRACT(doc, '$.addresses[0].city'), doc->>'$.addresses[1].city' FROM people WHERE id = 1");
+    assert_eq!(rows, vec![vec![Value::Json(json!("Oslo")), Value::String("Bergen".to_string())]]);
+
+    // Single-key access keeps working alongside paths
+    let rows = select_rows(&executor, &mut session, "SELECT JSON_EXTRACT(doc, '$.addresses'), doc->'addresses' FROM people WHERE id = 2");
+    assert_eq!(rows, vec![vec![Value::Json(json!([])), Value::Json(json!([]))]]);
+}
+
+#[test]
+fn test_missing_and_out_of_range_paths_are_null() {
+    let (executor, mut session, _dir) = setup();
+
+    let rows = select_rows(&executor, &mut session,
+        "SELECT JSON_EXTRACT(doc, '$.addresses[5].city'), doc->'$.addresses[0].city', doc->>'$.name.last', JSON_EXTRACT(doc, '$.phone[0]') FROM people WHERE id = 2");
+    assert_eq!(rows, vec![vec![Value::Null, Value::Null, Value::Null, Value::Null]]);
+
+    let rows = select_rows(&executor, &mut session, "SELECT id FROM people WHERE doc->'$.addresses[1]' IS NULL");
+    assert_eq!(rows, vec![vec![Value::Integer(2)]]);
+}
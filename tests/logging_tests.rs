@@ -0,0 +1,65 @@
+//! Structured logging tests
+//!
+//! Installs the JSON logger with a capturing target. Only one test in this
+//! binary may install the global logger.
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use minisql::logging::{self, LogFormat};
+
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_json_log_lines_are_structured() {
+    let capture = Capture::default();
+    logging::builder(LogFormat::Json)
+        .filter_level(log::LevelFilter::Debug)
+        .target(env_logger::Target::Pipe(Box::new(capture.clone())))
+        .try_init()
+        .unwrap();
+
+    let sql = "SELECT 'quoted \"value\"'";
+    log::info!(target: "minisql::protocol", conn_id = 7u32, sql = sql, time_ms = 12u64; "COM_QUERY finished");
+    log::warn!("plain message without context");
+
+    let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("log line should be valid JSON"))
+        .collect();
+    assert_eq!(lines.len(), 2);
+
+    let query = &lines[0];
+    assert_eq!(query["level"], "INFO");
+    assert_eq!(query["target"], "minisql::protocol");
+    assert_eq!(query["message"], "COM_QUERY finished");
+    assert_eq!(query["conn_id"], 7);
+    assert_eq!(query["sql"], sql);
+    assert_eq!(query["time_ms"], 12);
+    assert!(query["timestamp"].as_str().unwrap().ends_with('Z'));
+
+    let plain = &lines[1];
+    assert_eq!(plain["level"], "WARN");
+    assert_eq!(plain["message"], "plain message without context");
+    assert!(plain.get("conn_id").is_none());
+}
+
+#[test]
+fn test_log_format_parsing() {
+    assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+    assert_eq!("TEXT".parse::<LogFormat>().unwrap(), LogFormat::Text);
+    assert!("xml".parse::<LogFormat>().is_err());
+}
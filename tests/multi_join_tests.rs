@@ -325,3 +325,72 @@ fn test_duplicate_alias_case_insensitive() {
     let err_msg = res.unwrap_err().to_string();
     assert!(err_msg.contains("Not unique table/alias"), "Error message should mention 'Not unique table/alias', got: {}", err_msg);
 }
+
+/// t1 (id, grp) joined to t2 (t1_id, b): every t1 row has two t2 rows
+fn setup_order_tables(executor: &Executor, session: &mut Session) {
+    for sql in [
+        "CREATE TABLE t1 (id INT PRIMARY KEY, grp TEXT)",
+        "CREATE TABLE t2 (id INT PRIMARY KEY, t1_id INT, b INT)",
+        "INSERT INTO t1 VALUES (1, 'x'), (2, 'y'), (3, 'x')",
+        "INSERT INTO t2 VALUES (1, 1, 40), (2, 1, 10), (3, 2, 30), (4, 2, 50), (5, 3, 20), (6, 3, 60)",
+    ] {
+        executor.execute(Parser::parse(sql).unwrap(), session).unwrap();
+    }
+}
+
+fn join_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match executor.execute(Parser::parse(sql).unwrap(), session).unwrap() {
+        QueryResult::Select(result_set) => result_set.rows,
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_join_order_by_desc_with_limit() {
+    let (executor, mut session, _dir) = setup_test();
+    setup_order_tables(&executor, &mut session);
+
+    let rows = join_rows(&executor, &mut session,
+        "SELECT t1.id, t2.b FROM t1 JOIN t2 ON t1.id = t2.t1_id ORDER BY t2.b DESC LIMIT 1");
+    assert_eq!(rows, vec![vec![Value::Integer(3), Value::Integer(60)]]);
+
+    // LIMIT is applied after sorting, not to the join's natural order
+    let rows = join_rows(&executor, &mut session,
+        "SELECT t2.b FROM t1 JOIN t2 ON t1.id = t2.t1_id ORDER BY t2.b LIMIT 2");
+    assert_eq!(rows, vec![vec![Value::Integer(10)], vec![Value::Integer(20)]]);
+}
+
+#[test]
+fn test_join_multi_column_order_by() {
+    let (executor, mut session, _dir) = setup_test();
+    setup_order_tables(&executor, &mut session);
+
+    // Keys from both tables, mixed directions; t1.grp is not projected
+    let rows = join_rows(&executor, &mut session,
+        "SELECT t1.id, t2.b FROM t1 JOIN t2 ON t1.id = t2.t1_id ORDER BY t1.grp DESC, t2.b ASC");
+    let expected: Vec<Vec<Value>> = [(2, 30), (2, 50), (1, 10), (3, 20), (1, 40), (3, 60)]
+        .into_iter()
+        .map(|(id, b)| vec![Value::Integer(id), Value::Integer(b)])
+        .collect();
+    assert_eq!(rows, expected);
+
+    // Positional keys refer to the select list
+    let rows = join_rows(&executor, &mut session,
+        "SELECT t1.id, t2.b FROM t1 JOIN t2 ON t1.id = t2.t1_id ORDER BY 1 DESC, 2 DESC LIMIT 3");
+    assert_eq!(rows, vec![
+        vec![Value::Integer(3), Value::Integer(60)],
+        vec![Value::Integer(3), Value::Integer(20)],
+        vec![Value::Integer(2), Value::Integer(50)],
+    ]);
+}
+
+#[test]
+fn test_join_projects_qualified_columns() {
+    let (executor, mut session, _dir) = setup_test();
+    setup_order_tables(&executor, &mut session);
+
+    // Both tables have an `id`; qualifiers pick the right one
+    let rows = join_rows(&executor, &mut session,
+        "SELECT t2.id, t1.id, t1.grp FROM t1 JOIN t2 ON t1.id = t2.t1_id WHERE t2.b = 50");
+    assert_eq!(rows, vec![vec![Value::Integer(4), Value::Integer(2), Value::String("y".to_string())]]);
+}
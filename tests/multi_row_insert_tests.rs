@@ -0,0 +1,88 @@
+//! Tests for multi-row INSERT ... VALUES (...), (...)
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn setup(dir: &Path) -> (Executor, Session) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    txn_manager.recover(&storage).unwrap();
+    (Executor::new(storage, txn_manager), Session::new())
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_three_row_insert() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)");
+
+    match execute(&executor, &mut session, "INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, 'c')") {
+        QueryResult::Modified { rows_affected, .. } => assert_eq!(rows_affected, 3),
+        other => panic!("Expected Modified result, got {:?}", other),
+    }
+    assert_eq!(select_rows(&executor, &mut session, "SELECT id, name FROM t ORDER BY id"), vec![
+        vec![Value::Integer(1), Value::String("a".to_string())],
+        vec![Value::Integer(2), Value::String("b".to_string())],
+        vec![Value::Integer(3), Value::String("c".to_string())],
+    ]);
+
+    // The rows survive a restart
+    drop(executor);
+    let (executor, mut session) = setup(dir.path());
+    assert_eq!(select_rows(&executor, &mut session, "SELECT COUNT(*) FROM t"), vec![vec![Value::Integer(3)]]);
+}
+
+#[test]
+fn test_multi_row_last_insert_id_is_first_generated_id() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    execute(&executor, &mut session, "CREATE TABLE users (id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT)");
+    execute(&executor, &mut session, "INSERT INTO users (name) VALUES ('seed')");
+
+    match execute(&executor, &mut session, "INSERT INTO users (name) VALUES ('a'), ('b'), ('c')") {
+        QueryResult::Modified { rows_affected, last_insert_id } => {
+            assert_eq!(rows_affected, 3);
+            assert_eq!(last_insert_id, 2);
+        }
+        other => panic!("Expected Modified result, got {:?}", other),
+    }
+    assert_eq!(select_rows(&executor, &mut session, "SELECT LAST_INSERT_ID()"), vec![vec![Value::Integer(2)]]);
+    assert_eq!(select_rows(&executor, &mut session, "SELECT MAX(id) FROM users"), vec![vec![Value::Integer(4)]]);
+}
+
+#[test]
+fn test_failed_multi_row_insert_leaves_no_rows() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (3, 'existing')");
+
+    // The third row collides with the existing key; the first two must not stay behind
+    let stmt = Parser::parse("INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, 'c')").unwrap();
+    assert!(executor.execute(stmt, &mut session).is_err());
+    assert!(session.txn_id.is_none());
+    assert_eq!(select_rows(&executor, &mut session, "SELECT id, name FROM t"), vec![
+        vec![Value::Integer(3), Value::String("existing".to_string())],
+    ]);
+
+    drop(executor);
+    let (executor, mut session) = setup(dir.path());
+    assert_eq!(select_rows(&executor, &mut session, "SELECT COUNT(*) FROM t"), vec![vec![Value::Integer(1)]]);
+}
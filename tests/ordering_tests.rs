@@ -174,4 +174,104 @@ mod ordering_tests {
         ];
         assert_eq!(vals, expected);
     }
+
+    #[test]
+    fn test_order_by_mixed_integer_and_float() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE nums (v FLOAT)");
+        execute_ok(&executor, &mut session, "INSERT INTO nums VALUES (2)");
+        execute_ok(&executor, &mut session, "INSERT INTO nums VALUES (1.5)");
+        execute_ok(&executor, &mut session, "INSERT INTO nums VALUES (-3)");
+        execute_ok(&executor, &mut session, "INSERT INTO nums VALUES (2.25)");
+        execute_ok(&executor, &mut session, "INSERT INTO nums VALUES (1)");
+
+        let res = execute(&executor, &mut session, "SELECT v FROM nums ORDER BY v ASC");
+        let vals: Vec<f64> = first_column_values(&res).iter().map(|v| match v {
+            Value::Integer(i) => *i as f64,
+            Value::Float(f) => *f,
+            other => panic!("Expected number, got {:?}", other),
+        }).collect();
+        assert_eq!(vals, vec![-3.0, 1.0, 1.5, 2.0, 2.25]);
+    }
+
+    fn create_scores(executor: &Executor, session: &mut Session) {
+        execute_ok(executor, session, "CREATE TABLE scores (id INTEGER PRIMARY KEY, name TEXT, score INTEGER)");
+        execute_ok(executor, session,
+            "INSERT INTO scores VALUES (1, 'ann', 20), (2, 'bob', NULL), (3, 'cy', 10), (4, 'di', NULL), (5, 'ed', 20)");
+    }
+
+    #[test]
+    fn test_order_by_nulls_first_last() {
+        let (executor, mut session, _dir) = create_test_executor();
+        create_scores(&executor, &mut session);
+        let ids = |res: &QueryResult| first_column_values(res);
+        let int = |ids: &[i64]| ids.iter().map(|i| Value::Integer(*i)).collect::<Vec<_>>();
+
+        // MySQL default: NULLs are smallest, so first for ASC and last for DESC
+        let res = execute(&executor, &mut session, "SELECT id FROM scores ORDER BY score, id");
+        assert_eq!(ids(&res), int(&[2, 4, 3, 1, 5]));
+        let res = execute(&executor, &mut session, "SELECT id FROM scores ORDER BY score DESC, id");
+        assert_eq!(ids(&res), int(&[1, 5, 3, 2, 4]));
+
+        // Explicit placement is independent of the direction
+        let res = execute(&executor, &mut session, "SELECT id FROM scores ORDER BY score ASC NULLS LAST, id");
+        assert_eq!(ids(&res), int(&[3, 1, 5, 2, 4]));
+        let res = execute(&executor, &mut session, "SELECT id FROM scores ORDER BY score DESC NULLS FIRST, id DESC");
+        assert_eq!(ids(&res), int(&[4, 2, 5, 1, 3]));
+    }
+
+    #[test]
+    fn test_order_by_column_position() {
+        let (executor, mut session, _dir) = create_test_executor();
+        create_scores(&executor, &mut session);
+
+        let res = execute(&executor, &mut session, "SELECT name, score FROM scores WHERE score IS NOT NULL ORDER BY 2 DESC, 1");
+        assert_eq!(first_two_columns_values(&res), vec![
+            (Value::String("ann".into()), Value::Integer(20)),
+            (Value::String("ed".into()), Value::Integer(20)),
+            (Value::String("cy".into()), Value::Integer(10)),
+        ]);
+
+        // Positions count the columns * expands to
+        let res = execute(&executor, &mut session, "SELECT * FROM scores ORDER BY 3 NULLS LAST, 2 DESC");
+        assert_eq!(first_column_values(&res), vec![
+            Value::Integer(3), Value::Integer(5), Value::Integer(1), Value::Integer(4), Value::Integer(2),
+        ]);
+
+        for sql in ["SELECT name FROM scores ORDER BY 2", "SELECT name FROM scores ORDER BY 0"] {
+            let err = executor.execute(Parser::parse(sql).unwrap(), &mut session).unwrap_err();
+            assert_eq!(err.mysql_error_code(), 1054, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_order_by_mixed_directions_is_stable() {
+        let (executor, mut session, _dir) = create_test_executor();
+        execute_ok(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY, a INTEGER, b TEXT)");
+        execute_ok(&executor, &mut session,
+            "INSERT INTO t VALUES (1, 1, 'x'), (2, 2, 'y'), (3, 1, 'y'), (4, 2, 'x'), (5, 1, 'y'), (6, 2, 'y')");
+
+        // Rows equal on every key keep their insertion order
+        let res = execute(&executor, &mut session, "SELECT id FROM t ORDER BY a DESC, b ASC");
+        assert_eq!(first_column_values(&res), vec![
+            Value::Integer(4), Value::Integer(2), Value::Integer(6), Value::Integer(1), Value::Integer(3), Value::Integer(5),
+        ]);
+    }
+
+    #[test]
+    fn test_order_by_position_in_join() {
+        let (executor, mut session, _dir) = create_test_executor();
+        create_scores(&executor, &mut session);
+        execute_ok(&executor, &mut session, "CREATE TABLE teams (member_id INTEGER, team TEXT)");
+        execute_ok(&executor, &mut session, "INSERT INTO teams VALUES (1, 'red'), (3, 'blue'), (5, 'green')");
+
+        let res = execute(&executor, &mut session,
+            "SELECT s.name, t.team FROM scores s INNER JOIN teams t ON s.id = t.member_id ORDER BY 2");
+        assert_eq!(first_two_columns_values(&res), vec![
+            (Value::String("cy".into()), Value::String("blue".into())),
+            (Value::String("ed".into()), Value::String("green".into())),
+            (Value::String("ann".into()), Value::String("red".into())),
+        ]);
+    }
 }
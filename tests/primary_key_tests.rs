@@ -172,3 +172,120 @@ mod primary_key_tests {
         ]);
     }
 }
+
+mod primary_key_index {
+    use minisql::engines::granite::TransactionManager;
+    use minisql::executor::{Executor, Session};
+    use minisql::storage::StorageEngine;
+    use minisql::types::{QueryResult, Value};
+    use tempfile::{tempdir, TempDir};
+
+    fn setup() -> (TempDir, Executor, Session) {
+        let dir = tempdir().unwrap();
+        let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+        let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+        (dir, Executor::new(storage, txn_manager), Session::new())
+    }
+
+    fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+        executor.execute_sql(sql, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+    }
+
+    #[test]
+    fn test_primary_key_gets_a_unique_index() {
+        let (_dir, executor, mut session) = setup();
+        execute(&executor, &mut session, "CREATE TABLE t1 (id INTEGER PRIMARY KEY, name TEXT)");
+
+        let index = executor.storage().get_primary_key_index("t1").unwrap();
+        assert_eq!(index.name, "PRIMARY_t1");
+        assert_eq!(index.columns, vec!["id".to_string()]);
+        assert!(index.unique && index.is_primary);
+
+        execute(&executor, &mut session, "INSERT INTO t1 VALUES (1, 'Alice'), (2, 'Bob')");
+        let err = executor.execute_sql("INSERT INTO t1 VALUES (1, 'Carol')", &mut session).unwrap_err();
+        assert_eq!(err.to_string(), "Duplicate entry '1' for key 'PRIMARY'");
+        let err = executor.execute_sql("UPDATE t1 SET id = 1 WHERE id = 2", &mut session).unwrap_err();
+        assert!(err.to_string().starts_with("Duplicate entry"), "{}", err);
+
+        // A deleted key is free again, and the index follows the change
+        execute(&executor, &mut session, "DELETE FROM t1 WHERE id = 1");
+        execute(&executor, &mut session, "INSERT INTO t1 VALUES (1, 'Carol')");
+        let rows = executor.storage().get_rows_by_index_key("t1", "PRIMARY_t1", &[Value::Integer(1)]).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].values[1], Value::String("Carol".into()));
+
+        match execute(&executor, &mut session, "EXPLAIN SELECT name FROM t1 WHERE id = 1") {
+            QueryResult::Select(rs) => assert_eq!(rs.rows[0][3], Value::String("PRIMARY".into())),
+            other => panic!("Expected SELECT result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_table_level_composite_primary_key() {
+        let (_dir, executor, mut session) = setup();
+        execute(&executor, &mut session, "CREATE TABLE grades (student INTEGER, course TEXT, grade INTEGER, PRIMARY KEY (student, course))");
+
+        let index = executor.storage().get_primary_key_index("grades").unwrap();
+        assert_eq!(index.columns, vec!["student".to_string(), "course".to_string()]);
+
+        // Rows differing in one component are distinct keys
+        execute(&executor, &mut session, "INSERT INTO grades VALUES (1, 'math', 5), (1, 'art', 4), (2, 'math', 3)");
+        let err = executor.execute_sql("INSERT INTO grades VALUES (1, 'math', 2)", &mut session).unwrap_err();
+        assert_eq!(err.to_string(), "Duplicate entry '1-math' for key 'PRIMARY'");
+        let err = executor.execute_sql("UPDATE grades SET course = 'math' WHERE student = 1 AND course = 'art'", &mut session).unwrap_err();
+        assert!(err.to_string().starts_with("Duplicate entry"), "{}", err);
+        // Key columns are NOT NULL
+        assert!(executor.execute_sql("INSERT INTO grades VALUES (3, NULL, 1)", &mut session).is_err());
+
+        match execute(&executor, &mut session, "SHOW CREATE TABLE grades") {
+            QueryResult::Select(rs) => assert_eq!(rs.rows[0][1], Value::String(
+                "CREATE TABLE `grades` (\n  `student` INTEGER NOT NULL,\n  `course` TEXT NOT NULL,\n  `grade` INTEGER,\n  \
+                 PRIMARY KEY (`student`, `course`)\n) ENGINE=Granite STORAGE FORMAT=JSONL".into()
+            )),
+            other => panic!("Expected SELECT result, got {:?}", other),
+        }
+        match execute(&executor, &mut session, "DESCRIBE grades") {
+            QueryResult::Select(rs) => {
+                let keys: Vec<Value> = rs.rows.iter().map(|row| row[3].clone()).collect();
+                assert_eq!(keys, vec![Value::String("PRI".into()), Value::String("PRI".into()), Value::String("".into())]);
+            }
+            other => panic!("Expected SELECT result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_table_level_primary_keys() {
+        let (_dir, executor, mut session) = setup();
+        let err = executor.execute_sql("CREATE TABLE t (a INTEGER PRIMARY KEY, b INTEGER, PRIMARY KEY (b))", &mut session).unwrap_err();
+        assert_eq!((err.to_string().as_str(), err.mysql_error_code()), ("Multiple primary key defined", 1068));
+        let err = executor.execute_sql("CREATE TABLE t (a INTEGER, PRIMARY KEY (missing))", &mut session).unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1072);
+        let err = executor.execute_sql("CREATE TABLE t (a INTEGER, b INTEGER, PRIMARY KEY (a, A))", &mut session).unwrap_err();
+        assert_eq!((err.to_string().as_str(), err.mysql_error_code()), ("Duplicate column name 'a'", 1060));
+        assert!(!executor.storage().table_exists("t"));
+    }
+
+    #[test]
+    fn test_primary_key_keeps_declared_column_order() {
+        let (_dir, executor, mut session) = setup();
+        execute(&executor, &mut session, "CREATE TABLE t (a INTEGER, b TEXT, PRIMARY KEY (B, a))");
+
+        let index = executor.storage().get_primary_key_index("t").unwrap();
+        assert_eq!(index.columns, vec!["b".to_string(), "a".to_string()]);
+        execute(&executor, &mut session, "INSERT INTO t VALUES (1, 'x')");
+        let err = executor.execute_sql("INSERT INTO t VALUES (1, 'x')", &mut session).unwrap_err();
+        assert_eq!(err.to_string(), "Duplicate entry 'x-1' for key 'PRIMARY'");
+
+        match execute(&executor, &mut session, "SHOW CREATE TABLE t") {
+            QueryResult::Select(rs) => assert_eq!(rs.rows[0][1], Value::String(
+                "CREATE TABLE `t` (\n  `a` INTEGER NOT NULL,\n  `b` TEXT NOT NULL,\n  \
+                 PRIMARY KEY (`b`, `a`)\n) ENGINE=Granite STORAGE FORMAT=JSONL".into()
+            )),
+            other => panic!("Expected SELECT result, got {:?}", other),
+        }
+
+        // Renaming a key column keeps its place in the key
+        execute(&executor, &mut session, "ALTER TABLE t RENAME COLUMN b TO c");
+        assert_eq!(executor.storage().get_schema("t").unwrap().primary_key, vec!["c".to_string(), "a".to_string()]);
+    }
+}
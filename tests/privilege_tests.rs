@@ -0,0 +1,141 @@
+//! Tests for table privileges granted with GRANT and taken away with REVOKE
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::{tempdir, TempDir};
+
+fn setup() -> (TempDir, Executor, Session) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut root = login("root");
+    execute(&executor, &mut root, "CREATE TABLE orders (id INT PRIMARY KEY, total INT)");
+    execute(&executor, &mut root, "CREATE TABLE secrets (id INT PRIMARY KEY)");
+    execute(&executor, &mut root, "INSERT INTO orders VALUES (1, 10)");
+    (dir, executor, root)
+}
+
+/// A session logged in as `user`
+fn login(user: &str) -> Session {
+    let mut session = Session::new();
+    session.user = Some(user.to_string());
+    session
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    executor.execute_sql(sql, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+/// Assert that `sql` fails with the given MySQL error code and message
+fn assert_denied(executor: &Executor, session: &mut Session, sql: &str, code: u16, message: &str) {
+    let err = executor.execute_sql(sql, session).unwrap_err();
+    assert_eq!(err.mysql_error_code(), code, "{}", sql);
+    assert_eq!(err.sql_state(), "42000");
+    assert_eq!(err.to_string(), message);
+}
+
+#[test]
+fn test_select_only_grant() {
+    let (_dir, executor, mut root) = setup();
+    execute(&executor, &mut root, "GRANT SELECT ON orders TO alice");
+    let mut alice = login("alice");
+
+    match execute(&executor, &mut alice, "SELECT total FROM orders") {
+        QueryResult::Select(rs) => assert_eq!(rs.rows, vec![vec![Value::Integer(10)]]),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+    assert_denied(
+        &executor, &mut alice, "INSERT INTO orders VALUES (2, 20)",
+        1142, "INSERT command denied to user 'alice' for table 'orders'",
+    );
+    assert_denied(
+        &executor, &mut alice, "DELETE FROM orders",
+        1142, "DELETE command denied to user 'alice' for table 'orders'",
+    );
+    // Subqueries and joins read their tables too
+    assert_denied(
+        &executor, &mut alice, "SELECT id FROM orders WHERE id IN (SELECT id FROM secrets)",
+        1142, "SELECT command denied to user 'alice' for table 'secrets'",
+    );
+    assert_denied(
+        &executor, &mut alice, "SELECT * FROM secrets",
+        1142, "SELECT command denied to user 'alice' for table 'secrets'",
+    );
+
+    // Other users hold no privileges at all
+    let mut bob = login("bob");
+    assert_denied(
+        &executor, &mut bob, "SELECT * FROM orders",
+        1142, "SELECT command denied to user 'bob' for table 'orders'",
+    );
+}
+
+#[test]
+fn test_grant_and_revoke() {
+    let (_dir, executor, mut root) = setup();
+    let mut alice = login("alice");
+
+    execute(&executor, &mut root, "GRANT ALL PRIVILEGES ON orders TO 'alice'@'%'");
+    execute(&executor, &mut alice, "INSERT INTO orders VALUES (2, 20)");
+    execute(&executor, &mut alice, "UPDATE orders SET total = 25 WHERE id = 2");
+
+    execute(&executor, &mut root, "REVOKE INSERT, UPDATE ON orders FROM alice");
+    assert_denied(
+        &executor, &mut alice, "UPDATE orders SET total = 0",
+        1142, "UPDATE command denied to user 'alice' for table 'orders'",
+    );
+    execute(&executor, &mut alice, "DELETE FROM orders WHERE id = 2");
+
+    execute(&executor, &mut root, "REVOKE SELECT, DELETE ON orders FROM alice");
+    assert_denied(
+        &executor, &mut root, "REVOKE SELECT ON orders FROM alice",
+        1147, "There is no such grant defined for user 'alice' on host '%' on table 'orders'",
+    );
+    assert!(executor.execute_sql("GRANT SELECT ON missing TO alice", &mut root).is_err());
+}
+
+#[test]
+fn test_ddl_and_grants_are_reserved_to_the_superuser() {
+    let (_dir, executor, _root) = setup();
+    let mut alice = login("alice");
+    let message = "Access denied; you need (at least one of) the SUPER privilege(s) for this operation";
+
+    assert_denied(&executor, &mut alice, "CREATE TABLE mine (id INT)", 1227, message);
+    assert_denied(&executor, &mut alice, "DROP TABLE orders", 1227, message);
+    assert_denied(&executor, &mut alice, "GRANT SELECT ON orders TO alice", 1227, message);
+
+    // Statements that touch no table need no privilege
+    execute(&executor, &mut alice, "BEGIN");
+    execute(&executor, &mut alice, "COMMIT");
+    execute(&executor, &mut alice, "SHOW TABLES");
+
+    // Sessions not bound to a login are not checked
+    execute(&executor, &mut Session::new(), "CREATE TABLE mine (id INT)");
+}
+
+#[test]
+fn test_grants_survive_restart() {
+    let dir = tempdir().unwrap();
+    {
+        let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+        let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+        let executor = Executor::new(storage, txn_manager);
+        let mut root = login("root");
+        execute(&executor, &mut root, "CREATE TABLE orders (id INT PRIMARY KEY)");
+        execute(&executor, &mut root, "GRANT SELECT ON orders TO alice");
+        executor.shutdown().unwrap();
+    }
+
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut alice = login("alice");
+    execute(&executor, &mut alice, "SELECT * FROM orders");
+    assert_denied(
+        &executor, &mut alice, "INSERT INTO orders VALUES (1)",
+        1142, "INSERT command denied to user 'alice' for table 'orders'",
+    );
+}
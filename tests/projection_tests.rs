@@ -0,0 +1,92 @@
+//! Tests for SELECT lists mixing `*` / `table.*` with explicit expressions
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{DataType, QueryResult, ResultSet, Value};
+use tempfile::tempdir;
+
+fn create_test_executor() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    (Executor::new(storage, txn_manager), Session::new(), dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select(executor: &Executor, session: &mut Session, sql: &str) -> ResultSet {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs,
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn create_items(executor: &Executor, session: &mut Session) {
+    execute(executor, session, "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, price INTEGER)");
+    execute(executor, session, "INSERT INTO items VALUES (1, 'pen', 3)");
+    execute(executor, session, "INSERT INTO items VALUES (2, 'book', 12)");
+}
+
+#[test]
+fn test_star_with_extra_expression() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_items(&executor, &mut session);
+
+    let rs = select(&executor, &mut session, "SELECT *, price * 2 AS double_price FROM items ORDER BY id");
+    assert_eq!(rs.columns, vec!["id", "name", "price", "double_price"]);
+    assert_eq!(rs.column_types, vec![DataType::Integer, DataType::Text, DataType::Integer, DataType::Integer]);
+    assert_eq!(rs.rows, vec![
+        vec![Value::Integer(1), Value::String("pen".into()), Value::Integer(3), Value::Integer(6)],
+        vec![Value::Integer(2), Value::String("book".into()), Value::Integer(12), Value::Integer(24)],
+    ]);
+}
+
+#[test]
+fn test_star_expanded_in_place() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_items(&executor, &mut session);
+
+    // Explicit columns on both sides of the star, through the indexed lookup path
+    let rs = select(&executor, &mut session, "SELECT name AS label, *, 1 AS one FROM items WHERE id = 2");
+    assert_eq!(rs.columns, vec!["label", "id", "name", "price", "one"]);
+    assert_eq!(rs.rows, vec![vec![
+        Value::String("book".into()),
+        Value::Integer(2),
+        Value::String("book".into()),
+        Value::Integer(12),
+        Value::Integer(1),
+    ]]);
+
+    // A star may appear more than once
+    let rs = select(&executor, &mut session, "SELECT *, * FROM items WHERE id = 1");
+    assert_eq!(rs.columns.len(), 6);
+    assert_eq!(rs.rows[0][..3], rs.rows[0][3..]);
+}
+
+#[test]
+fn test_qualified_star_with_extra_expression() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_items(&executor, &mut session);
+
+    let rs = select(&executor, &mut session, "SELECT i.*, price + 1 AS next_price FROM items i WHERE price > 5");
+    assert_eq!(rs.columns, vec!["id", "name", "price", "next_price"]);
+    assert_eq!(rs.rows, vec![vec![
+        Value::Integer(2),
+        Value::String("book".into()),
+        Value::Integer(12),
+        Value::Integer(13),
+    ]]);
+
+    let rs = select(&executor, &mut session, "SELECT items.*, id FROM items WHERE id = 1");
+    assert_eq!(rs.columns, vec!["id", "name", "price", "id"]);
+
+    // A qualifier that names no table in the query is rejected
+    let stmt = Parser::parse("SELECT other.*, id FROM items").unwrap();
+    let err = executor.execute(stmt, &mut session).unwrap_err();
+    assert_eq!(err.mysql_error_code(), 1054);
+}
@@ -0,0 +1,110 @@
+//! Tests for table-qualified column references in single-table selects
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::{tempdir, TempDir};
+
+fn setup() -> (TempDir, Executor, Session) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+    for sql in [
+        "CREATE TABLE users (id INT PRIMARY KEY, name TEXT, team INT)",
+        "INSERT INTO users VALUES (1, 'alice', 1), (2, 'bob', 1), (3, 'carol', 2)",
+    ] {
+        execute(&executor, &mut session, sql).unwrap();
+    }
+    (dir, executor, session)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> minisql::error::Result<QueryResult> {
+    executor.execute(Parser::parse(sql).expect(&format!("Failed to parse: {}", sql)), session)
+}
+
+fn select(executor: &Executor, session: &mut Session, sql: &str) -> (Vec<String>, Vec<Vec<Value>>) {
+    match execute(executor, session, sql) {
+        Ok(QueryResult::Select(rs)) => (rs.columns, rs.rows),
+        other => panic!("Expected SELECT result for '{}', got {:?}", sql, other),
+    }
+}
+
+fn error(executor: &Executor, session: &mut Session, sql: &str) -> String {
+    match execute(executor, session, sql) {
+        Err(e) => e.to_string(),
+        Ok(result) => panic!("Expected '{}' to fail, got {:?}", sql, result),
+    }
+}
+
+#[test]
+fn test_qualified_columns_with_alias() {
+    let (_dir, executor, mut session) = setup();
+
+    let (columns, rows) = select(&executor, &mut session, "SELECT u.name FROM users u WHERE u.id = 1");
+    assert_eq!(columns, vec!["name"]);
+    assert_eq!(rows, vec![vec![Value::String("alice".into())]]);
+
+    let (columns, rows) = select(&executor, &mut session, "SELECT u.* FROM users u WHERE u.team = 2");
+    assert_eq!(columns, vec!["id", "name", "team"]);
+    assert_eq!(rows, vec![vec![Value::Integer(3), Value::String("carol".into()), Value::Integer(2)]]);
+
+    let (_, rows) = select(&executor, &mut session, "SELECT u.id FROM users u ORDER BY u.name DESC");
+    assert_eq!(rows, vec![vec![Value::Integer(3)], vec![Value::Integer(2)], vec![Value::Integer(1)]]);
+
+    // Groups come back in no particular order
+    let (columns, mut rows) = select(&executor, &mut session,
+        "SELECT u.team, COUNT(*) FROM users u GROUP BY u.team");
+    rows.sort_by_key(|row| format!("{:?}", row));
+    assert_eq!(columns, vec!["team", "COUNT(*)"]);
+    assert_eq!(rows, vec![
+        vec![Value::Integer(1), Value::Integer(2)],
+        vec![Value::Integer(2), Value::Integer(1)],
+    ]);
+}
+
+#[test]
+fn test_qualified_columns_with_table_name() {
+    let (_dir, executor, mut session) = setup();
+
+    let (columns, rows) = select(&executor, &mut session, "SELECT users.id, users.name FROM users WHERE users.id = 2");
+    assert_eq!(columns, vec!["id", "name"]);
+    assert_eq!(rows, vec![vec![Value::Integer(2), Value::String("bob".into())]]);
+
+    let (_, rows) = select(&executor, &mut session, "SELECT users.* FROM users WHERE users.name LIKE 'c%'");
+    assert_eq!(rows.len(), 1);
+}
+
+#[test]
+fn test_mismatched_qualifiers_are_rejected() {
+    let (_dir, executor, mut session) = setup();
+    execute(&executor, &mut session, "CREATE TABLE empty (id INT PRIMARY KEY)").unwrap();
+
+    for (sql, expected) in [
+        ("SELECT x.id FROM users", "Unknown column 'x.id' in 'field list'"),
+        ("SELECT x.id FROM empty", "Unknown column 'x.id' in 'field list'"),
+        // Once aliased, the table can only be referred to by its alias
+        ("SELECT users.name FROM users u", "Unknown column 'users.name' in 'field list'"),
+        ("SELECT u.name FROM users", "Unknown column 'u.name' in 'field list'"),
+        ("SELECT x.* FROM users u", "Unknown table 'x' in field list"),
+        ("SELECT id FROM users u WHERE x.id = 1", "Unknown column 'x.id' in 'where clause'"),
+        ("SELECT COUNT(*) FROM users u WHERE x.id = 1", "Unknown column 'x.id' in 'where clause'"),
+        ("SELECT id FROM users u ORDER BY x.name", "Unknown column 'x.name' in 'order clause'"),
+        ("SELECT u.team, COUNT(*) FROM users u GROUP BY x.team", "Unknown column 'x.team' in 'group statement'"),
+        ("SELECT MAX(x.id) FROM users u", "Unknown column 'x.id' in 'field list'"),
+    ] {
+        assert_eq!(error(&executor, &mut session, sql), expected, "{}", sql);
+    }
+}
+
+#[test]
+fn test_outer_alias_in_correlated_subquery() {
+    let (_dir, executor, mut session) = setup();
+
+    let (_, rows) = select(&executor, &mut session,
+        "SELECT u.name FROM users u WHERE u.id = (SELECT MAX(v.id) FROM users v WHERE v.team = u.team) ORDER BY u.id");
+    assert_eq!(rows, vec![vec![Value::String("bob".into())], vec![Value::String("carol".into())]]);
+}
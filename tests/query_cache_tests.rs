@@ -0,0 +1,108 @@
+//! Integration tests for the SELECT result cache
+
+use tempfile::tempdir;
+use minisql::storage::StorageEngine;
+use minisql::executor::{Executor, QueryCacheStats, Session};
+use minisql::engines::granite::TransactionManager;
+use minisql::types::{QueryResult, Value};
+
+/// Helper to create an executor with the query cache enabled
+fn create_test_executor() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let mut executor = Executor::new(storage, txn_manager);
+    executor.set_query_cache_entries(Some(16));
+    let session = Session::new();
+    (executor, session, dir)
+}
+
+/// Helper to execute SQL through the cache-aware entry point
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let result = executor.execute_sql(sql, session);
+    assert!(result.is_ok(), "SQL '{}' failed: {:?}", sql, result.err());
+    result.unwrap()
+}
+
+fn first_column_values(result: &QueryResult) -> Vec<Value> {
+    match result {
+        QueryResult::Select(rs) => rs.rows.iter().map(|r| r[0].clone()).collect(),
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn stats(executor: &Executor) -> QueryCacheStats {
+    executor.query_cache_stats().expect("query cache should be enabled")
+}
+
+#[test]
+fn test_repeated_select_is_a_cache_hit() {
+    let (executor, mut session, _dir) = create_test_executor();
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (1, 'a'), (2, 'b')");
+
+    let first = execute(&executor, &mut session, "SELECT id FROM t ORDER BY id");
+    assert_eq!(stats(&executor), QueryCacheStats { hits: 0, misses: 1, entries: 1 });
+
+    // Whitespace and keyword case do not change the key
+    let second = execute(&executor, &mut session, "select id  from t\norder by id");
+    assert_eq!(stats(&executor), QueryCacheStats { hits: 1, misses: 1, entries: 1 });
+    assert_eq!(first_column_values(&first), first_column_values(&second));
+
+    // A different literal is a different query
+    execute(&executor, &mut session, "SELECT id FROM t WHERE id = 1");
+    execute(&executor, &mut session, "SELECT id FROM t WHERE id = 2");
+    assert_eq!(stats(&executor).misses, 3);
+}
+
+#[test]
+fn test_insert_invalidates_cached_result() {
+    let (executor, mut session, _dir) = create_test_executor();
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (1, 'a')");
+
+    let before = execute(&executor, &mut session, "SELECT COUNT(*) FROM t");
+    assert_eq!(first_column_values(&before), vec![Value::Integer(1)]);
+
+    execute(&executor, &mut session, "INSERT INTO t VALUES (2, 'b')");
+    assert_eq!(stats(&executor).entries, 0);
+
+    let after = execute(&executor, &mut session, "SELECT COUNT(*) FROM t");
+    assert_eq!(first_column_values(&after), vec![Value::Integer(2)]);
+    assert_eq!(stats(&executor), QueryCacheStats { hits: 0, misses: 2, entries: 1 });
+}
+
+#[test]
+fn test_rollback_invalidates_results_seen_during_transaction() {
+    let (executor, mut session, _dir) = create_test_executor();
+    let mut writer = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY)");
+
+    execute(&executor, &mut writer, "BEGIN");
+    execute(&executor, &mut writer, "INSERT INTO t VALUES (1)");
+    // Uncommitted rows are visible to autocommit readers and get cached
+    let during = execute(&executor, &mut session, "SELECT id FROM t");
+    assert_eq!(first_column_values(&during), vec![Value::Integer(1)]);
+    execute(&executor, &mut writer, "ROLLBACK");
+
+    let after = execute(&executor, &mut session, "SELECT id FROM t");
+    assert_eq!(first_column_values(&after), Vec::<Value>::new());
+    assert_eq!(stats(&executor).hits, 0);
+}
+
+#[test]
+fn test_uncacheable_queries_always_run() {
+    let (executor, mut session, _dir) = create_test_executor();
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY)");
+
+    execute(&executor, &mut session, "SELECT NOW()");
+    execute(&executor, &mut session, "SELECT NOW()");
+
+    // SELECTs inside a transaction bypass the cache
+    execute(&executor, &mut session, "BEGIN");
+    execute(&executor, &mut session, "SELECT id FROM t");
+    execute(&executor, &mut session, "SELECT id FROM t");
+    execute(&executor, &mut session, "COMMIT");
+
+    assert_eq!(stats(&executor), QueryCacheStats { hits: 0, misses: 0, entries: 0 });
+}
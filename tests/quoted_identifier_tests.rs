@@ -0,0 +1,64 @@
+//! Tests for backtick-quoted identifiers, including reserved words as names
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::{tempdir, TempDir};
+
+fn setup() -> (TempDir, Executor, Session) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    (dir, Executor::new(storage, txn_manager), Session::new())
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select(executor: &Executor, session: &mut Session, sql: &str) -> (Vec<String>, Vec<Vec<Value>>) {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => (rs.columns, rs.rows),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_reserved_words_as_names() {
+    let (_dir, executor, mut session) = setup();
+    execute(&executor, &mut session,
+        "CREATE TABLE `order` (`select` INT PRIMARY KEY, `group` TEXT, `limit` INT, `from` INT)");
+    execute(&executor, &mut session, "CREATE INDEX `index` ON `order` (`group`)");
+    execute(&executor, &mut session,
+        "INSERT INTO `order` (`select`, `group`, `limit`, `from`) VALUES (1, 'a', 10, 0), (2, 'b', 20, 1), (3, 'b', 30, 1)");
+
+    let (columns, rows) = select(&executor, &mut session,
+        "SELECT `o`.`select`, `group` FROM `order` AS `o` WHERE `o`.`limit` > 10 ORDER BY `select` DESC");
+    assert_eq!(columns, vec!["select", "group"]);
+    assert_eq!(rows, vec![
+        vec![Value::Integer(3), Value::String("b".into())],
+        vec![Value::Integer(2), Value::String("b".into())],
+    ]);
+
+    execute(&executor, &mut session, "UPDATE `order` SET `limit` = `limit` + `from` WHERE `group` = 'b'");
+    execute(&executor, &mut session, "DELETE FROM `order` WHERE `select` = 1");
+    let (_, rows) = select(&executor, &mut session, "SELECT `group`, SUM(`limit`) FROM `order` GROUP BY `group`");
+    assert_eq!(rows, vec![vec![Value::String("b".into()), Value::Integer(52)]]);
+}
+
+#[test]
+fn test_special_characters_and_escaped_backticks() {
+    let (_dir, executor, mut session) = setup();
+    execute(&executor, &mut session, "CREATE TABLE `odd``name` (id INT PRIMARY KEY, `unit price` INT)");
+    execute(&executor, &mut session, "INSERT INTO `odd``name` VALUES (1, 5)");
+
+    let (columns, rows) = select(&executor, &mut session, "SELECT `unit price` AS `price, each` FROM `odd``name`");
+    assert_eq!(columns, vec!["price, each"]);
+    assert_eq!(rows, vec![vec![Value::Integer(5)]]);
+    assert!(executor.storage().get_schema("odd`name").is_ok());
+
+    assert!(Parser::parse("SELECT `unit price FROM t").is_err());
+}
@@ -0,0 +1,63 @@
+//! Tests for read-only replica mode (`Config::read_only` / `--read-only`)
+
+use minisql::executor::Session;
+use minisql::parser::Parser;
+use minisql::types::{QueryResult, Value};
+use minisql::{Config, Server};
+use tempfile::TempDir;
+
+fn config(temp_dir: &TempDir, read_only: bool) -> Config {
+    Config {
+        data_dir: temp_dir.path().to_path_buf(),
+        read_only,
+        ..Config::default()
+    }
+}
+
+#[tokio::test]
+async fn test_read_only_rejects_writes_but_serves_reads() {
+    let temp_dir = TempDir::new().unwrap();
+    {
+        let server = Server::new(config(&temp_dir, false)).await.unwrap();
+        let executor = server.executor();
+        let mut session = Session::new();
+        for sql in [
+            "CREATE TABLE users (id INT PRIMARY KEY, name TEXT)",
+            "INSERT INTO users VALUES (1, 'Alice')",
+        ] {
+            executor.execute(Parser::parse(sql).unwrap(), &mut session).unwrap();
+        }
+    }
+
+    let server = Server::new(config(&temp_dir, true)).await.unwrap();
+    let executor = server.executor();
+    let mut session = Session::new();
+
+    for sql in [
+        "INSERT INTO users VALUES (2, 'Bob')",
+        "UPDATE users SET name = 'Eve' WHERE id = 1",
+        "DELETE FROM users",
+        "LOAD DATA INFILE 'users.tsv' INTO TABLE users",
+        "CREATE TABLE other (id INT PRIMARY KEY)",
+        "CREATE INDEX idx_name ON users (name)",
+        "ALTER TABLE users ENGINE=Sandstone",
+        "TRUNCATE TABLE users",
+        "DROP TABLE users",
+    ] {
+        let err = executor.execute(Parser::parse(sql).unwrap(), &mut session).unwrap_err();
+        assert_eq!(err.mysql_error_code(), 1290, "{}", sql);
+        assert_eq!(err.sql_state(), "HY000", "{}", sql);
+    }
+
+    // Reads, metadata and read-only transactions still work
+    for sql in ["SHOW TABLES", "DESCRIBE users", "BEGIN", "SELECT * FROM users FOR UPDATE", "COMMIT"] {
+        executor.execute(Parser::parse(sql).unwrap(), &mut session)
+            .unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e));
+    }
+    match executor.execute(Parser::parse("SELECT id, name FROM users").unwrap(), &mut session).unwrap() {
+        QueryResult::Select(rs) => {
+            assert_eq!(rs.rows, vec![vec![Value::Integer(1), Value::String("Alice".to_string())]]);
+        }
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
@@ -0,0 +1,111 @@
+//! Tests for ALTER TABLE ... RENAME COLUMN
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn setup(dir: &Path) -> (Executor, Session) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    txn_manager.recover(&storage).unwrap();
+    (Executor::new(storage, txn_manager), Session::new())
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn create_people(executor: &Executor, session: &mut Session) {
+    execute(executor, session, "CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT, city TEXT)");
+    execute(executor, session, "CREATE INDEX idx_city_name ON people (city, name)");
+    execute(executor, session, "INSERT INTO people VALUES (1, 'ann', 'oslo')");
+    execute(executor, session, "INSERT INTO people VALUES (2, 'bob', 'rome')");
+}
+
+#[test]
+fn test_rename_column() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_people(&executor, &mut session);
+
+    execute(&executor, &mut session, "ALTER TABLE people RENAME COLUMN name TO full_name");
+
+    assert_eq!(
+        select_rows(&executor, &mut session, "SELECT full_name FROM people WHERE id = 2"),
+        vec![vec![Value::String("bob".into())]]
+    );
+
+    let err = executor.execute(Parser::parse("SELECT name FROM people").unwrap(), &mut session).unwrap_err();
+    assert!(err.to_string().contains("Unknown column 'name'"), "unexpected error: {}", err);
+    assert_eq!(err.mysql_error_code(), 1054);
+}
+
+#[test]
+fn test_rename_column_updates_index_metadata() {
+    let dir = tempdir().unwrap();
+    {
+        let (executor, mut session) = setup(dir.path());
+        create_people(&executor, &mut session);
+        execute(&executor, &mut session, "ALTER TABLE people RENAME COLUMN city TO town");
+        execute(&executor, &mut session, "ALTER TABLE people RENAME COLUMN id TO person_id");
+
+        let index = executor.storage().get_index("idx_city_name").unwrap();
+        assert_eq!(index.columns, vec!["town".to_string(), "name".to_string()]);
+        let pk = executor.storage().get_index("PRIMARY_people").unwrap();
+        assert_eq!(pk.columns, vec!["person_id".to_string()]);
+
+        // The renamed index still serves lookups without a full scan
+        let scans = executor.storage().full_scan_count();
+        execute(&executor, &mut session, "UPDATE people SET name = 'bo' WHERE town = 'rome'");
+        assert_eq!(executor.storage().full_scan_count(), scans);
+        executor.shutdown().unwrap();
+    }
+
+    // The rename survives a restart
+    let (executor, mut session) = setup(dir.path());
+    assert_eq!(
+        select_rows(&executor, &mut session, "SELECT person_id, name FROM people WHERE town = 'rome'"),
+        vec![vec![Value::Integer(2), Value::String("bo".into())]]
+    );
+    assert_eq!(
+        select_rows(&executor, &mut session, "SELECT name FROM people WHERE person_id = 1"),
+        vec![vec![Value::String("ann".into())]]
+    );
+}
+
+#[test]
+fn test_rename_column_rejects_collision_and_unknown_column() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    create_people(&executor, &mut session);
+
+    let err = executor.execute(
+        Parser::parse("ALTER TABLE people RENAME COLUMN name TO city").unwrap(), &mut session
+    ).unwrap_err();
+    assert_eq!(err.to_string(), "Duplicate column name 'city'");
+    assert_eq!(err.mysql_error_code(), 1060);
+
+    let err = executor.execute(
+        Parser::parse("ALTER TABLE people RENAME COLUMN missing TO other").unwrap(), &mut session
+    ).unwrap_err();
+    assert!(err.to_string().contains("Unknown column 'missing'"), "unexpected error: {}", err);
+
+    // Nothing changed
+    assert_eq!(select_rows(&executor, &mut session, "SELECT name, city FROM people WHERE id = 1").len(), 1);
+
+    // Changing only the case of a name is allowed
+    execute(&executor, &mut session, "ALTER TABLE people RENAME COLUMN name TO Name");
+    assert_eq!(executor.storage().get_schema("people").unwrap().columns[1].name, "Name");
+}
@@ -0,0 +1,177 @@
+//! Tests for Sandstone delta replication between nodes
+
+use std::sync::Arc;
+
+use minisql::engines::granite::TransactionManager;
+use minisql::engines::SandstoneConfig;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::protocol::{serve_replication, sync_table, ReplicationClient, ReplicationRequest, ReplicationResponse};
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+
+const SECRET: &str = "shared secret";
+
+fn setup_node(replica_id: u16) -> (TempDir, Arc<Executor>) {
+    let (temp_dir, executor) = setup_executor(replica_id);
+    (temp_dir, Arc::new(executor))
+}
+
+fn setup_executor(replica_id: u16) -> (TempDir, Executor) {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(temp_dir.path().to_path_buf()).unwrap();
+    let executor = Executor::with_sandstone(
+        storage,
+        txn_manager,
+        SandstoneConfig { replica_id, ..SandstoneConfig::default() },
+    ).unwrap();
+    (temp_dir, executor)
+}
+
+fn run(executor: &Executor, sql: &str) -> QueryResult {
+    executor.execute(Parser::parse(sql).unwrap(), &mut Session::new()).unwrap()
+}
+
+fn sorted_rows(executor: &Executor, sql: &str) -> Vec<Vec<Value>> {
+    match run(executor, sql) {
+        QueryResult::Select(rs) => {
+            let mut rows = rs.rows;
+            rows.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            rows
+        }
+        other => panic!("Expected a result set, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_two_nodes_converge_after_exchanging_deltas() {
+    let (_dir_a, node_a) = setup_node(1);
+    let (_dir_b, node_b) = setup_node(2);
+    for node in [&node_a, &node_b] {
+        run(node, "CREATE TABLE events (id INT, name TEXT) ENGINE=Sandstone");
+    }
+
+    // Node B serves its deltas to node A
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let server = tokio::spawn(serve_replication(listener, Arc::clone(&node_b), SECRET.into(), shutdown_rx));
+
+    run(&node_a, "INSERT INTO events VALUES (1, 'from a'), (2, 'also from a')");
+    run(&node_b, "INSERT INTO events VALUES (3, 'from b')");
+    run(&node_b, "UPDATE events SET name = 'changed on b' WHERE id = 3");
+
+    let (pushed, pulled) = sync_table(&node_a, addr, SECRET, "events").await.unwrap();
+    assert_eq!((pushed, pulled), (2, 2));
+
+    let expected = vec![
+        vec![Value::Integer(1), Value::String("from a".into())],
+        vec![Value::Integer(2), Value::String("also from a".into())],
+        vec![Value::Integer(3), Value::String("changed on b".into())],
+    ];
+    assert_eq!(sorted_rows(&node_a, "SELECT id, name FROM events"), expected);
+    assert_eq!(sorted_rows(&node_b, "SELECT id, name FROM events"), expected);
+
+    // A delete on either side reaches the other with the next exchange
+    run(&node_b, "DELETE FROM events WHERE id = 1");
+    assert_eq!(sync_table(&node_a, addr, SECRET, "events").await.unwrap(), (0, 1));
+    assert_eq!(
+        sorted_rows(&node_a, "SELECT id FROM events"),
+        sorted_rows(&node_b, "SELECT id FROM events"),
+    );
+    assert_eq!(sorted_rows(&node_a, "SELECT id FROM events").len(), 2);
+
+    shutdown_tx.send_replace(true);
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_replication_rejects_non_sandstone_tables() {
+    let (_dir, node) = setup_node(1);
+    run(&node, "CREATE TABLE accounts (id INT) ENGINE=Granite");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let server = tokio::spawn(serve_replication(listener, Arc::clone(&node), SECRET.into(), shutdown_rx));
+
+    let mut client = ReplicationClient::connect(addr, SECRET).await.unwrap();
+    let err = client.pull("accounts").await.unwrap_err();
+    assert!(err.to_string().contains("only Sandstone tables replicate"), "{}", err);
+    let err = client.pull("missing").await.unwrap_err();
+    assert!(err.to_string().contains("missing"), "{}", err);
+
+    // The connection stays usable after an error response
+    run(&node, "CREATE TABLE logs (id INT) ENGINE=Sandstone");
+    assert!(client.pull("logs").await.unwrap().is_empty());
+
+    shutdown_tx.send_replace(true);
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_replication_requires_the_shared_secret() {
+    let (_dir, node) = setup_node(1);
+    run(&node, "CREATE TABLE events (id INT) ENGINE=Sandstone");
+    run(&node, "INSERT INTO events VALUES (1)");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let server = tokio::spawn(serve_replication(listener, Arc::clone(&node), SECRET.into(), shutdown_rx));
+
+    let err = ReplicationClient::connect(addr, "wrong secret").await.err().unwrap();
+    assert!(err.to_string().contains("authentication failed"), "{}", err);
+
+    // A request sent without answering the challenge is refused, and the
+    // connection closed
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len).await.unwrap();
+    stream.read_exact(&mut vec![0u8; u32::from_le_bytes(len) as usize]).await.unwrap();
+    let pull = bincode::serialize(&ReplicationRequest::Pull { table: "events".into() }).unwrap();
+    stream.write_all(&(pull.len() as u32).to_le_bytes()).await.unwrap();
+    stream.write_all(&pull).await.unwrap();
+    stream.read_exact(&mut len).await.unwrap();
+    let mut payload = vec![0u8; u32::from_le_bytes(len) as usize];
+    stream.read_exact(&mut payload).await.unwrap();
+    assert!(matches!(bincode::deserialize(&payload).unwrap(), ReplicationResponse::Error(_)));
+    assert_eq!(stream.read(&mut len).await.unwrap(), 0);
+
+    // Neither took the pending delta
+    let mut client = ReplicationClient::connect(addr, SECRET).await.unwrap();
+    assert_eq!(client.pull("events").await.unwrap().len(), 1);
+
+    shutdown_tx.send_replace(true);
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_read_only_node_refuses_pushes() {
+    let (_dir_a, node_a) = setup_node(1);
+    let (_dir_b, mut node_b) = setup_executor(2);
+    run(&node_a, "CREATE TABLE events (id INT) ENGINE=Sandstone");
+    run(&node_b, "CREATE TABLE events (id INT) ENGINE=Sandstone");
+    node_b.set_read_only(true);
+    let node_b = Arc::new(node_b);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let server = tokio::spawn(serve_replication(listener, Arc::clone(&node_b), SECRET.into(), shutdown_rx));
+
+    run(&node_a, "INSERT INTO events VALUES (1)");
+    let err = sync_table(&node_a, addr, SECRET, "events").await.unwrap_err();
+    assert!(err.to_string().contains("--read-only option"), "{}", err);
+    assert!(sorted_rows(&node_b, "SELECT id FROM events").is_empty());
+    // The refused delta stays pending on the sender
+    assert_eq!(node_a.take_replication_deltas("events").unwrap().len(), 1);
+
+    shutdown_tx.send_replace(true);
+    server.await.unwrap().unwrap();
+}
@@ -0,0 +1,137 @@
+//! Tests for answering an unfiltered COUNT(*) from the per-table row count
+
+use std::path::Path;
+
+use minisql::engines::granite::TransactionManager;
+use minisql::engines::SandstoneConfig;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::tempdir;
+
+fn setup(dir: &Path) -> (Executor, Session) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig::default()).unwrap();
+    (executor, Session::new())
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+/// Run a single-value COUNT query, returning the count and the rows it examined
+fn count(executor: &Executor, session: &mut Session, sql: &str) -> (i64, u64) {
+    session.take_read_stats();
+    let count = match execute(executor, session, sql) {
+        QueryResult::Select(rs) => match rs.rows[..] {
+            [ref row] => match row[..] {
+                [Value::Integer(n)] => n,
+                ref other => panic!("Expected one integer, got {:?}", other),
+            },
+            ref other => panic!("Expected one row, got {:?}", other),
+        },
+        other => panic!("Expected SELECT result, got {:?}", other),
+    };
+    (count, session.take_read_stats().rows_examined)
+}
+
+/// Assert that the cached COUNT(*) agrees with a scan, and was not one
+fn assert_cached_count(executor: &Executor, session: &mut Session, table: &str, expected: i64) {
+    let (scanned, examined) = count(executor, session, &format!("SELECT COUNT(*) FROM {} WHERE 1 = 1", table));
+    assert_eq!(examined, scanned as u64);
+    let (cached, examined) = count(executor, session, &format!("SELECT COUNT(*) FROM {}", table));
+    assert_eq!((cached, scanned), (expected, expected), "{}", table);
+    assert_eq!(examined, 0, "COUNT(*) on {} scanned the table", table);
+}
+
+#[test]
+fn test_count_star_tracks_inserts_and_deletes() {
+    for engine in ["Granite", "Sandstone"] {
+        let dir = tempdir().unwrap();
+        {
+            let (executor, mut session) = setup(dir.path());
+            execute(&executor, &mut session, &format!("CREATE TABLE t (id INT PRIMARY KEY, n INT) ENGINE={}", engine));
+            assert_cached_count(&executor, &mut session, "t", 0);
+
+            execute(&executor, &mut session, "INSERT INTO t VALUES (1, 1), (2, 2), (3, 3), (4, 4), (5, 5)");
+            execute(&executor, &mut session, "INSERT INTO t VALUES (6, 6)");
+            assert_cached_count(&executor, &mut session, "t", 6);
+
+            execute(&executor, &mut session, "DELETE FROM t WHERE n > 4");
+            execute(&executor, &mut session, "DELETE FROM t WHERE id = 100");
+            execute(&executor, &mut session, "UPDATE t SET n = 0 WHERE id = 1");
+            assert_cached_count(&executor, &mut session, "t", 4);
+
+            // A failed multi-row insert adds nothing (Sandstone does not check keys)
+            if engine == "Granite" {
+                let stmt = Parser::parse("INSERT INTO t VALUES (7, 7), (1, 1)").unwrap();
+                assert!(executor.execute(stmt, &mut session).is_err());
+                assert_cached_count(&executor, &mut session, "t", 4);
+            }
+
+            execute(&executor, &mut session, "FLUSH TABLES");
+            assert_cached_count(&executor, &mut session, "t", 4);
+            executor.shutdown().unwrap();
+        }
+
+        // The count is rebuilt from the rows on disk
+        let (executor, mut session) = setup(dir.path());
+        executor.init_engine_table("t").unwrap();
+        assert_cached_count(&executor, &mut session, "t", 4);
+
+        execute(&executor, &mut session, "TRUNCATE TABLE t");
+        assert_cached_count(&executor, &mut session, "t", 0);
+    }
+}
+
+#[test]
+fn test_count_star_counts_committed_rows() {
+    let dir = tempdir().unwrap();
+    let (executor, mut session) = setup(dir.path());
+    let mut other = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE t (id INT PRIMARY KEY)");
+    execute(&executor, &mut session, "CREATE TABLE u (id INT PRIMARY KEY)");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (1), (2), (3)");
+
+    execute(&executor, &mut session, "BEGIN");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (4), (5)");
+    execute(&executor, &mut session, "DELETE FROM t WHERE id = 1");
+
+    // The transaction sees its own changes; another session only committed rows
+    assert_cached_count(&executor, &mut session, "t", 4);
+    assert_cached_count(&executor, &mut session, "u", 0);
+    let (committed, examined) = count(&executor, &mut other, "SELECT COUNT(*) FROM t");
+    assert_eq!((committed, examined), (3, 0));
+
+    execute(&executor, &mut session, "COMMIT");
+    assert_cached_count(&executor, &mut other, "t", 4);
+
+    execute(&executor, &mut session, "BEGIN");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (6)");
+    execute(&executor, &mut session, "SAVEPOINT sp");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (7), (8)");
+    execute(&executor, &mut session, "ROLLBACK TO SAVEPOINT sp");
+    execute(&executor, &mut session, "COMMIT");
+    assert_cached_count(&executor, &mut session, "t", 5);
+
+    execute(&executor, &mut session, "BEGIN");
+    execute(&executor, &mut session, "DELETE FROM t");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (9)");
+    execute(&executor, &mut session, "ROLLBACK");
+    assert_cached_count(&executor, &mut session, "t", 5);
+
+    // A mix of statements, checked after each one
+    for (sql, expected) in [
+        ("INSERT INTO t VALUES (10), (11), (12)", 8),
+        ("DELETE FROM t WHERE id < 5", 5),
+        ("INSERT INTO t VALUES (1)", 6),
+        ("DELETE FROM t WHERE id > 10", 4),
+        ("INSERT INTO t VALUES (20), (21)", 6),
+    ] {
+        execute(&executor, &mut session, sql);
+        assert_cached_count(&executor, &mut session, "t", expected);
+    }
+}
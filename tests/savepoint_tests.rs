@@ -0,0 +1,117 @@
+use minisql::engines::granite::TransactionManager;
+use minisql::engines::SandstoneConfig;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn open_executor(path: &Path) -> Executor {
+    let storage = StorageEngine::new(path.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(path.to_path_buf()).unwrap();
+    txn_manager.recover(&storage).unwrap();
+    Executor::with_sandstone(storage, txn_manager, SandstoneConfig::default()).unwrap()
+}
+
+fn run(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    executor.execute(Parser::parse(sql).unwrap(), session).unwrap()
+}
+
+fn ids(executor: &Executor, session: &mut Session, table: &str) -> Vec<Value> {
+    match run(executor, session, &format!("SELECT id FROM {} ORDER BY id", table)) {
+        QueryResult::Select(rs) => rs.rows.into_iter().map(|r| r[0].clone()).collect(),
+        _ => panic!("Expected select result"),
+    }
+}
+
+#[test]
+fn test_rollback_to_savepoint_keeps_earlier_work() {
+    let temp_dir = TempDir::new().unwrap();
+    let executor = open_executor(temp_dir.path());
+    let mut session = Session::new();
+
+    run(&executor, &mut session, "CREATE TABLE t (id INT PRIMARY KEY, name TEXT)");
+    run(&executor, &mut session, "BEGIN");
+    run(&executor, &mut session, "INSERT INTO t VALUES (1, 'first')");
+    run(&executor, &mut session, "SAVEPOINT sp1");
+    run(&executor, &mut session, "INSERT INTO t VALUES (2, 'second')");
+    run(&executor, &mut session, "UPDATE t SET name = 'changed' WHERE id = 1");
+    run(&executor, &mut session, "ROLLBACK TO SAVEPOINT sp1");
+    run(&executor, &mut session, "COMMIT");
+
+    assert_eq!(ids(&executor, &mut session, "t"), vec![Value::Integer(1)]);
+    match run(&executor, &mut session, "SELECT name FROM t WHERE id = 1") {
+        QueryResult::Select(rs) => assert_eq!(rs.rows[0][0], Value::String("first".into())),
+        _ => panic!("Expected select result"),
+    }
+
+    // The primary key index no longer holds the rolled-back row
+    run(&executor, &mut session, "INSERT INTO t VALUES (2, 'again')");
+}
+
+#[test]
+fn test_savepoint_rollback_survives_recovery() {
+    let temp_dir = TempDir::new().unwrap();
+    {
+        let executor = open_executor(temp_dir.path());
+        let mut session = Session::new();
+        run(&executor, &mut session, "CREATE TABLE t (id INT PRIMARY KEY, name TEXT)");
+        run(&executor, &mut session, "BEGIN");
+        run(&executor, &mut session, "INSERT INTO t VALUES (1, 'first')");
+        run(&executor, &mut session, "SAVEPOINT sp1");
+        run(&executor, &mut session, "INSERT INTO t VALUES (2, 'second')");
+        run(&executor, &mut session, "DELETE FROM t WHERE id = 1");
+        run(&executor, &mut session, "ROLLBACK TO sp1");
+        run(&executor, &mut session, "COMMIT");
+    }
+
+    // Replaying the WAL applies the compensation records too
+    let executor = open_executor(temp_dir.path());
+    let mut session = Session::new();
+    assert_eq!(ids(&executor, &mut session, "t"), vec![Value::Integer(1)]);
+}
+
+#[test]
+fn test_release_and_unknown_savepoints() {
+    let temp_dir = TempDir::new().unwrap();
+    let executor = open_executor(temp_dir.path());
+    let mut session = Session::new();
+
+    run(&executor, &mut session, "CREATE TABLE t (id INT PRIMARY KEY)");
+    run(&executor, &mut session, "BEGIN");
+    run(&executor, &mut session, "SAVEPOINT a");
+    run(&executor, &mut session, "INSERT INTO t VALUES (1)");
+    run(&executor, &mut session, "SAVEPOINT b");
+    run(&executor, &mut session, "RELEASE SAVEPOINT a");
+
+    // Releasing a also released b, which was set after it
+    let err = executor.execute(Parser::parse("ROLLBACK TO SAVEPOINT b").unwrap(), &mut session).unwrap_err();
+    assert_eq!(err.mysql_error_code(), 1305);
+    assert_eq!(err.to_string(), "SAVEPOINT b does not exist");
+
+    // The transaction is still usable after the error
+    run(&executor, &mut session, "ROLLBACK");
+    assert!(ids(&executor, &mut session, "t").is_empty());
+
+    // Outside a transaction SAVEPOINT is accepted but nothing can be rolled back to
+    run(&executor, &mut session, "SAVEPOINT outside");
+    assert!(executor.execute(Parser::parse("ROLLBACK TO outside").unwrap(), &mut session).is_err());
+}
+
+#[test]
+fn test_savepoint_does_not_affect_sandstone_tables() {
+    let temp_dir = TempDir::new().unwrap();
+    let executor = open_executor(temp_dir.path());
+    let mut session = Session::new();
+
+    run(&executor, &mut session, "CREATE TABLE s (id INT PRIMARY KEY) ENGINE=Sandstone");
+    run(&executor, &mut session, "BEGIN");
+    run(&executor, &mut session, "SAVEPOINT sp1");
+    run(&executor, &mut session, "INSERT INTO s VALUES (1)");
+    run(&executor, &mut session, "ROLLBACK TO SAVEPOINT sp1");
+    run(&executor, &mut session, "COMMIT");
+
+    // Sandstone is not transactional, so the insert stays
+    assert_eq!(ids(&executor, &mut session, "s"), vec![Value::Integer(1)]);
+}
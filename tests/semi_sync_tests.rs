@@ -0,0 +1,131 @@
+//! Semi-synchronous replication tests
+//!
+//! The replica is simulated by a thread that follows the primary's durable
+//! LSN and acknowledges it through `TransactionManager::acknowledge_replica`.
+//! Only one test in this binary may install the global logger.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use minisql::engines::granite::{GraniteConfig, TransactionManager};
+use minisql::executor::{Executor, Session};
+use minisql::logging::{self, LogFormat};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::QueryResult;
+use tempfile::tempdir;
+
+fn create_semi_sync_executor(timeout_ms: u64) -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let config = GraniteConfig {
+        semi_sync_timeout_ms: Some(timeout_ms),
+        ..GraniteConfig::synchronous()
+    };
+    let txn_manager = TransactionManager::new_with_config(dir.path().to_path_buf(), config).unwrap();
+    (Executor::new(storage, txn_manager), Session::new(), dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+/// Run `sql` in an explicit transaction and return how long the COMMIT took
+fn commit_insert(executor: &Executor, session: &mut Session, sql: &str) -> Duration {
+    execute(executor, session, "BEGIN");
+    execute(executor, session, sql);
+    let start = Instant::now();
+    execute(executor, session, "COMMIT");
+    start.elapsed()
+}
+
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_semi_sync_commit_waits_for_replica_ack() {
+    let (executor, mut session, _dir) = create_semi_sync_executor(10_000);
+    let primary = Arc::clone(executor.txn_manager());
+
+    // The replica acknowledges each new durable LSN after a delay
+    let acks = Arc::new(AtomicU64::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let replica = {
+        let (primary, acks, stop) = (Arc::clone(&primary), Arc::clone(&acks), Arc::clone(&stop));
+        thread::spawn(move || {
+            while !stop.load(Ordering::Acquire) {
+                let lsn = primary.durable_lsn();
+                if lsn > primary.replica_acked_lsn() {
+                    thread::sleep(Duration::from_millis(200));
+                    acks.fetch_add(1, Ordering::AcqRel);
+                    primary.acknowledge_replica(lsn);
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+        })
+    };
+
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT)");
+    let acks_before = acks.load(Ordering::Acquire);
+    let elapsed = commit_insert(&executor, &mut session, "INSERT INTO t VALUES (1, 'a')");
+
+    assert!(acks.load(Ordering::Acquire) > acks_before, "commit returned before the replica acknowledged");
+    assert!(elapsed >= Duration::from_millis(150));
+    assert!(primary.replica_acked_lsn() >= primary.durable_lsn());
+    assert!(primary.semi_sync_active());
+
+    // An autocommit write waits for the replica the same way
+    let acks_before = acks.load(Ordering::Acquire);
+    let start = Instant::now();
+    execute(&executor, &mut session, "INSERT INTO t VALUES (2, 'b')");
+    assert!(acks.load(Ordering::Acquire) > acks_before, "autocommit write returned before the replica acknowledged");
+    assert!(start.elapsed() >= Duration::from_millis(150));
+
+    stop.store(true, Ordering::Release);
+    replica.join().unwrap();
+}
+
+#[test]
+fn test_semi_sync_timeout_degrades_to_async() {
+    let capture = Capture::default();
+    logging::builder(LogFormat::Text)
+        .filter_level(log::LevelFilter::Warn)
+        .target(env_logger::Target::Pipe(Box::new(capture.clone())))
+        .try_init()
+        .unwrap();
+
+    let (executor, mut session, _dir) = create_semi_sync_executor(300);
+    let primary = Arc::clone(executor.txn_manager());
+    assert!(primary.semi_sync_active());
+
+    // No replica acknowledges: the COMMIT still succeeds once the timeout passes
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY)");
+    let elapsed = commit_insert(&executor, &mut session, "INSERT INTO t VALUES (1)");
+    assert!(elapsed >= Duration::from_millis(300));
+    assert!(!primary.semi_sync_active());
+    let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("falling back to asynchronous replication"), "missing warning in: {}", output);
+
+    // Degraded to async: later commits don't wait for the replica
+    let elapsed = commit_insert(&executor, &mut session, "INSERT INTO t VALUES (2)");
+    assert!(elapsed < Duration::from_millis(300));
+
+    // Once a replica catches up, semi-sync resumes
+    primary.acknowledge_replica(primary.durable_lsn());
+    assert!(primary.semi_sync_active());
+}
@@ -0,0 +1,48 @@
+use minisql::engines::{GraniteConfig, SandstoneConfig};
+use minisql::executor::Session;
+use minisql::parser::Parser;
+use minisql::types::{QueryResult, Value};
+use minisql::{Config, Server};
+use tempfile::TempDir;
+
+fn sync_config(temp_dir: &TempDir) -> Config {
+    Config {
+        data_dir: temp_dir.path().to_path_buf(),
+        granite_config: GraniteConfig::synchronous(),
+        sandstone_config: SandstoneConfig::low_latency(),
+        ..Config::default()
+    }
+}
+
+#[tokio::test]
+async fn test_synchronous_config_commit_survives_reopen() {
+    let temp_dir = TempDir::new().unwrap();
+
+    {
+        let server = Server::new(sync_config(&temp_dir)).await.unwrap();
+        assert_eq!(server.config().granite_config.fsync_interval_ms, 0);
+
+        let executor = server.executor();
+        let mut session = Session::new();
+        for sql in [
+            "CREATE TABLE durable (id INT PRIMARY KEY, name TEXT)",
+            "BEGIN",
+            "INSERT INTO durable VALUES (1, 'kept')",
+            "COMMIT",
+        ] {
+            executor.execute(Parser::parse(sql).unwrap(), &mut session).unwrap();
+        }
+    }
+
+    let server = Server::new(sync_config(&temp_dir)).await.unwrap();
+    let executor = server.executor();
+    let mut session = Session::new();
+    let stmt = Parser::parse("SELECT name FROM durable WHERE id = 1").unwrap();
+    match executor.execute(stmt, &mut session).unwrap() {
+        QueryResult::Select(rs) => {
+            assert_eq!(rs.rows.len(), 1);
+            assert_eq!(rs.rows[0][0], Value::String("kept".to_string()));
+        }
+        _ => panic!("Expected select result"),
+    }
+}
@@ -0,0 +1,185 @@
+//! Tests for SHOW STATUS
+
+use minisql::engines::granite::TransactionManager;
+use minisql::engines::SandstoneConfig;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use std::fs;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+/// SHOW STATUS as (Variable_name, Value) pairs
+fn show_status(executor: &Executor, session: &mut Session) -> Vec<(String, String)> {
+    match execute(executor, session, "SHOW STATUS") {
+        QueryResult::Select(rs) => {
+            assert_eq!(rs.columns, vec!["Variable_name", "Value"]);
+            rs.rows.iter().map(|row| match &row[..] {
+                [Value::String(name), Value::String(value)] => (name.clone(), value.clone()),
+                other => panic!("Unexpected SHOW STATUS row: {:?}", other),
+            }).collect()
+        }
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+fn status_text<'a>(status: &'a [(String, String)], name: &str) -> &'a str {
+    &status.iter().find(|(n, _)| n == name).unwrap_or_else(|| panic!("{} missing", name)).1
+}
+
+fn status_value(status: &[(String, String)], name: &str) -> u64 {
+    status_text(status, name).parse().unwrap_or_else(|_| panic!("{} is not a number", name))
+}
+
+/// Poll SHOW STATUS until `done` holds, for up to five seconds
+fn wait_for_status(
+    executor: &Executor,
+    session: &mut Session,
+    done: impl Fn(&[(String, String)]) -> bool,
+) -> Vec<(String, String)> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let status = show_status(executor, session);
+        if done(&status) || Instant::now() > deadline {
+            return status;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn test_show_status_reports_counters() {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig {
+        flush_interval_ms: 60_000,
+        ..SandstoneConfig::default()
+    }).unwrap();
+    let mut session = Session::new();
+
+    let status = show_status(&executor, &mut session);
+    let names: Vec<&str> = status.iter().map(|(n, _)| n.as_str()).collect();
+    for expected in [
+        "Questions", "Com_select", "Com_insert", "Com_update", "Com_delete", "Uptime",
+        "Granite_durable_lsn", "Sandstone_dirty_tables", "Sandstone_last_flush",
+        "Sandstone_flush_failures", "Sandstone_last_flush_error", "Sandstone_pending_deltas",
+        "Sandstone_tombstones", "Open_tables",
+    ] {
+        assert!(names.contains(&expected), "{} missing from {:?}", expected, names);
+    }
+    let questions = status_value(&status, "Questions");
+
+    execute(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY, v INTEGER)");
+    execute(&executor, &mut session, "CREATE TABLE s (id INTEGER PRIMARY KEY) ENGINE=Sandstone");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (1, 1)");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (2, 2)");
+    // Inside a transaction the Sandstone write stays in memory until COMMIT
+    execute(&executor, &mut session, "BEGIN");
+    execute(&executor, &mut session, "INSERT INTO s VALUES (1)");
+    execute(&executor, &mut session, "UPDATE t SET v = 3 WHERE id = 1");
+    execute(&executor, &mut session, "DELETE FROM t WHERE id = 2");
+    execute(&executor, &mut session, "SELECT * FROM t");
+
+    let status = show_status(&executor, &mut session);
+    // Nine statements plus the SHOW STATUS that reported the first snapshot
+    assert_eq!(status_value(&status, "Questions"), questions + 10);
+    assert_eq!(status_value(&status, "Com_insert"), 3);
+    assert_eq!(status_value(&status, "Com_update"), 1);
+    assert_eq!(status_value(&status, "Com_delete"), 1);
+    assert_eq!(status_value(&status, "Com_select"), 1);
+    assert_eq!(status_value(&status, "Open_tables"), 2);
+    assert_eq!(status_value(&status, "Sandstone_dirty_tables"), 1);
+
+    // COMMIT waits for its WAL records to be fsynced
+    execute(&executor, &mut session, "COMMIT");
+    let status = show_status(&executor, &mut session);
+    assert!(status_value(&status, "Granite_durable_lsn") > 0);
+}
+
+#[test]
+fn test_show_global_and_session_status_parse() {
+    for sql in ["SHOW STATUS", "SHOW GLOBAL STATUS", "show session status"] {
+        assert!(Parser::parse(sql).is_ok(), "{} should parse", sql);
+    }
+    assert!(Parser::parse("SHOW GLOBAL TABLES").is_err());
+}
+
+#[test]
+fn test_show_status_reports_sandstone_flush_failures() {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig {
+        flush_interval_ms: 20,
+        ..SandstoneConfig::default()
+    }).unwrap();
+    let mut session = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE s (id INTEGER PRIMARY KEY) ENGINE=Sandstone");
+
+    let status = show_status(&executor, &mut session);
+    assert_eq!(status_value(&status, "Sandstone_flush_failures"), 0);
+    assert_eq!(status_text(&status, "Sandstone_last_flush_error"), "");
+
+    // A file where the tables directory should be makes every table write fail
+    let tables = dir.path().join("tables");
+    let moved = dir.path().join("tables.moved");
+    fs::rename(&tables, &moved).unwrap();
+    fs::write(&tables, b"").unwrap();
+
+    // An auto-commit write flushes synchronously and reports the failure
+    let stmt = Parser::parse("INSERT INTO s VALUES (1)").unwrap();
+    assert!(executor.execute(stmt, &mut session).is_err());
+    let status = show_status(&executor, &mut session);
+    assert!(status_value(&status, "Sandstone_flush_failures") > 0);
+    assert_ne!(status_text(&status, "Sandstone_last_flush_error"), "");
+    assert_eq!(status_value(&status, "Sandstone_dirty_tables"), 1);
+
+    // Once the disk is writable again the background worker's retry succeeds and clears the error
+    fs::remove_file(&tables).unwrap();
+    fs::rename(&moved, &tables).unwrap();
+    let status = wait_for_status(&executor, &mut session, |s| status_text(s, "Sandstone_last_flush_error").is_empty());
+    assert_eq!(status_text(&status, "Sandstone_last_flush_error"), "");
+    assert!(status_value(&status, "Sandstone_last_flush") > 0);
+    assert_eq!(status_value(&status, "Sandstone_dirty_tables"), 0);
+}
+
+#[test]
+fn test_sandstone_tombstones_stay_bounded() {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig {
+        flush_interval_ms: 20,
+        tombstone_gc_ms: Some(0),
+        ..SandstoneConfig::default()
+    }).unwrap();
+    let mut session = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE s (id INTEGER, v INTEGER) ENGINE=Sandstone");
+
+    let mut peak = 0;
+    for cycle in 0..20 {
+        for i in 0..50 {
+            execute(&executor, &mut session, &format!("INSERT INTO s VALUES ({}, {})", i, cycle));
+        }
+        execute(&executor, &mut session, "DELETE FROM s");
+        let status = show_status(&executor, &mut session);
+        peak = peak.max(status_value(&status, "Sandstone_tombstones"));
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    // 1000 rows were deleted in all, but the worker keeps pruning
+    assert!(peak < 1000, "tombstones grew to {}", peak);
+
+    let status = wait_for_status(&executor, &mut session, |status| {
+        status_value(status, "Sandstone_tombstones") == 0
+            && status_value(status, "Sandstone_pending_deltas") == 0
+    });
+    assert_eq!(status_value(&status, "Sandstone_tombstones"), 0);
+    assert_eq!(status_value(&status, "Sandstone_pending_deltas"), 0);
+}
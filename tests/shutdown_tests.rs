@@ -0,0 +1,61 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use minisql::engines::SandstoneConfig;
+use minisql::executor::Session;
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::Value;
+use minisql::{Config, Server};
+use tempfile::TempDir;
+use tokio::net::{TcpListener, TcpStream};
+
+use common::{connect, query, try_read_packet};
+
+#[tokio::test]
+async fn test_shutdown_drains_connections_and_flushes() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config {
+        data_dir: temp_dir.path().to_path_buf(),
+        password: String::new(),
+        // Long flush interval so only the shutdown flush can persist the row
+        sandstone_config: SandstoneConfig::high_throughput(),
+        shutdown_timeout: Duration::from_secs(5),
+        ..Config::default()
+    };
+
+    let server = Arc::new(Server::new(config).await.unwrap());
+    let mut session = Session::new();
+    let create = Parser::parse("CREATE TABLE events (id INT PRIMARY KEY, name TEXT) ENGINE=Sandstone").unwrap();
+    server.executor().execute(create, &mut session).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let serving = Arc::clone(&server);
+    let serve_task = tokio::spawn(async move { serving.serve(listener).await.map_err(|e| e.to_string()) });
+
+    let mut client = connect(addr).await;
+    let ok = query(&mut client, "INSERT INTO events VALUES (1, 'before shutdown')").await;
+    assert_eq!(ok[0], 0x00, "expected OK for INSERT");
+
+    server.shutdown();
+    tokio::time::timeout(Duration::from_secs(10), serve_task)
+        .await
+        .expect("server did not stop")
+        .unwrap()
+        .unwrap();
+
+    // The idle connection was closed by the server
+    assert!(try_read_packet(&mut client).await.is_err());
+
+    // New connections are refused
+    assert!(TcpStream::connect(addr).await.is_err());
+
+    // The Sandstone row reached disk before the background flush interval elapsed
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    let rows = storage.scan_table("events").unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].values[1], Value::String("before shutdown".to_string()));
+}
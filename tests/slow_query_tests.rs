@@ -0,0 +1,53 @@
+//! Tests for the per-statement read statistics reported by the slow query log
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, ReadStats, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Executor, Session) {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = StorageEngine::new(temp_dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(temp_dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+    for sql in [
+        "CREATE TABLE users (id INT PRIMARY KEY, name TEXT, age INT)",
+        "CREATE INDEX idx_name ON users (name)",
+        "INSERT INTO users VALUES (1, 'Alice', 30), (2, 'Bob', 25), (3, 'Carol', 35), (4, 'Dave', 40)",
+    ] {
+        executor.execute(Parser::parse(sql).unwrap(), &mut session).unwrap();
+    }
+    (temp_dir, executor, session)
+}
+
+fn read_stats(executor: &Executor, session: &mut Session, sql: &str) -> ReadStats {
+    session.take_read_stats();
+    executor.execute(Parser::parse(sql).unwrap(), session)
+        .unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e));
+    session.take_read_stats()
+}
+
+#[test]
+fn test_read_stats_report_rows_examined_and_index_use() {
+    let (_temp_dir, executor, mut session) = setup();
+
+    let stats = read_stats(&executor, &mut session, "SELECT * FROM users WHERE name = 'Bob'");
+    assert_eq!(stats, ReadStats { rows_examined: 1, index_used: true });
+
+    let stats = read_stats(&executor, &mut session, "SELECT * FROM users WHERE age > 30");
+    assert_eq!(stats, ReadStats { rows_examined: 4, index_used: false });
+
+    let stats = read_stats(&executor, &mut session, "SELECT COUNT(*) FROM users WHERE name = 'Carol'");
+    assert_eq!(stats, ReadStats { rows_examined: 1, index_used: true });
+
+    let stats = read_stats(&executor, &mut session, "UPDATE users SET age = 26 WHERE name = 'Bob'");
+    assert_eq!(stats, ReadStats { rows_examined: 1, index_used: true });
+
+    let stats = read_stats(&executor, &mut session, "DELETE FROM users WHERE age = 40");
+    assert_eq!(stats, ReadStats { rows_examined: 4, index_used: false });
+
+    // Taking the stats resets them
+    assert_eq!(session.take_read_stats(), ReadStats::default());
+}
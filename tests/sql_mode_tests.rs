@@ -0,0 +1,96 @@
+//! Tests for double-quoted strings and the ANSI_QUOTES SQL mode
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, SqlMode, Value};
+use tempfile::{tempdir, TempDir};
+
+fn setup() -> (TempDir, Executor, Session) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE people (id INT PRIMARY KEY, name TEXT)");
+    execute(&executor, &mut session, "INSERT INTO people VALUES (1, \"O'Brien\"), (2, 'name')");
+    (dir, executor, session)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    executor.execute_sql(sql, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+fn text(s: &str) -> Value {
+    Value::String(s.into())
+}
+
+#[test]
+fn test_double_quoted_string_literals() {
+    let (_dir, executor, mut session) = setup();
+
+    // By default "name" is a string, as in MySQL
+    assert_eq!(
+        select_rows(&executor, &mut session, "SELECT id FROM people WHERE name = \"name\""),
+        vec![vec![Value::Integer(2)]]
+    );
+    assert_eq!(
+        select_rows(&executor, &mut session, "SELECT name FROM people WHERE id = 1"),
+        vec![vec![text("O'Brien")]]
+    );
+    assert_eq!(
+        select_rows(&executor, &mut session, r#"SELECT "say ""hi""", "tab\there""#),
+        vec![vec![text("say \"hi\""), text("tab\there")]]
+    );
+}
+
+#[test]
+fn test_ansi_quotes_mode() {
+    let (_dir, executor, mut session) = setup();
+
+    execute(&executor, &mut session, "SET sql_mode = 'STRICT_TRANS_TABLES,ANSI_QUOTES'");
+    assert!(session.sql_mode.ansi_quotes());
+    assert_eq!(session.sql_mode.to_string(), "STRICT_TRANS_TABLES,ANSI_QUOTES");
+
+    // Now "name" is the column, so every row matches
+    assert_eq!(
+        select_rows(&executor, &mut session, "SELECT \"p\".\"id\" FROM \"people\" AS \"p\" WHERE \"name\" = name ORDER BY id"),
+        vec![vec![Value::Integer(1)], vec![Value::Integer(2)]]
+    );
+    assert!(executor.execute_sql("SELECT \"missing\" FROM people", &mut session).is_err());
+
+    // The ANSI combination mode implies ANSI_QUOTES; DEFAULT switches it off again
+    execute(&executor, &mut session, "SET SESSION sql_mode = 'ansi'");
+    assert!(session.sql_mode.ansi_quotes());
+    execute(&executor, &mut session, "SET sql_mode = DEFAULT");
+    assert_eq!(session.sql_mode, SqlMode::default());
+
+    // Connectors set the mode with @@session. names, lists and expressions
+    execute(&executor, &mut session, "SET NAMES utf8mb4, @@session.sql_mode = 'ANSI_QUOTES'");
+    assert!(session.sql_mode.ansi_quotes());
+    execute(&executor, &mut session, "SET @@SESSION.sql_mode = DEFAULT");
+    execute(&executor, &mut session, "SET SESSION sql_mode = CONCAT(@@sql_mode, ',ANSI_QUOTES')");
+    assert_eq!(session.sql_mode, SqlMode::default());
+    assert_eq!(
+        select_rows(&executor, &mut session, "SELECT id FROM people WHERE name = \"name\""),
+        vec![vec![Value::Integer(2)]]
+    );
+}
+
+#[test]
+fn test_query_cache_respects_sql_mode() {
+    let (_dir, mut executor, mut session) = setup();
+    executor.set_query_cache_entries(Some(16));
+    let sql = "SELECT COUNT(*) FROM people WHERE name = \"name\"";
+
+    assert_eq!(select_rows(&executor, &mut session, sql), vec![vec![Value::Integer(1)]]);
+    execute(&executor, &mut session, "SET sql_mode = \"ANSI_QUOTES\"");
+    assert_eq!(select_rows(&executor, &mut session, sql), vec![vec![Value::Integer(2)]]);
+}
@@ -0,0 +1,81 @@
+//! Integration tests for `Session::statement_timeout_ms`
+
+use tempfile::tempdir;
+use minisql::storage::StorageEngine;
+use minisql::executor::{Executor, Session};
+use minisql::engines::granite::TransactionManager;
+use minisql::parser::Parser;
+use minisql::types::{QueryResult, Value};
+
+/// Helper to create an executor and a session with the given timeout
+fn create_test_executor(timeout_ms: Option<u64>) -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+    session.statement_timeout_ms = timeout_ms;
+    (executor, session, dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let result = executor.execute(Parser::parse(sql).unwrap(), session);
+    assert!(result.is_ok(), "SQL '{}' failed: {:?}", sql, result.err());
+    result.unwrap()
+}
+
+/// Create `t` with `n` rows
+fn populate(executor: &Executor, session: &mut Session, n: i64) {
+    execute(executor, session, "CREATE TABLE t (id INTEGER PRIMARY KEY, v INTEGER)");
+    let values: Vec<String> = (1..=n).map(|i| format!("({}, {})", i, i % 7)).collect();
+    execute(executor, session, &format!("INSERT INTO t VALUES {}", values.join(", ")));
+}
+
+/// A three-way non-equi join over 300 rows: 27 million ON evaluations
+const EXPENSIVE_JOIN: &str =
+    "SELECT a.id FROM t a JOIN t b ON a.id <> b.id JOIN t c ON b.id <> c.id LIMIT 1";
+
+#[test]
+fn test_expensive_join_times_out() {
+    let (executor, mut session, _dir) = create_test_executor(None);
+    populate(&executor, &mut session, 300);
+    session.statement_timeout_ms = Some(20);
+
+    let err = executor.execute(Parser::parse(EXPENSIVE_JOIN).unwrap(), &mut session).unwrap_err();
+    assert_eq!(err.mysql_error_code(), 3024);
+    assert_eq!(err.sql_state(), "HY000");
+
+    // Cheap statements still run within the same session
+    match execute(&executor, &mut session, "SELECT COUNT(*) FROM t") {
+        QueryResult::Select(rs) => assert_eq!(rs.rows[0][0], Value::Integer(300)),
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+#[test]
+fn test_timeout_rolls_back_transaction() {
+    let (executor, mut session, _dir) = create_test_executor(None);
+    populate(&executor, &mut session, 300);
+    session.statement_timeout_ms = Some(20);
+
+    execute(&executor, &mut session, "BEGIN");
+    execute(&executor, &mut session, "INSERT INTO t VALUES (1000, 1)");
+    let err = executor.execute(Parser::parse(EXPENSIVE_JOIN).unwrap(), &mut session).unwrap_err();
+    assert!(err.is_statement_timeout());
+    assert!(session.txn_id.is_none(), "transaction should be rolled back");
+
+    match execute(&executor, &mut session, "SELECT COUNT(*) FROM t WHERE id = 1000") {
+        QueryResult::Select(rs) => assert_eq!(rs.rows[0][0], Value::Integer(0)),
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+#[test]
+fn test_no_timeout_by_default() {
+    let (executor, mut session, _dir) = create_test_executor(None);
+    populate(&executor, &mut session, 50);
+    match execute(&executor, &mut session, "SELECT a.id FROM t a JOIN t b ON a.id <> b.id") {
+        QueryResult::Select(rs) => assert_eq!(rs.rows.len(), 50 * 49),
+        _ => panic!("Expected SELECT result"),
+    }
+}
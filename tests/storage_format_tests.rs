@@ -0,0 +1,169 @@
+//! Tests for the per-table on-disk storage format (JSONL `.dat` vs binary `.bdat`)
+
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::engines::granite::TransactionManager;
+use minisql::types::{QueryResult, StorageFormat, Value};
+use serde_json::json;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn setup(dir: &Path) -> (Executor, Session) {
+    let storage = StorageEngine::new(dir.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.to_path_buf()).unwrap();
+    (Executor::new(storage, txn_manager), Session::new())
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn create_docs(executor: &Executor, session: &mut Session, options: &str) {
+    execute(executor, session, &format!(
+        "CREATE TABLE docs (id INTEGER PRIMARY KEY, title TEXT, meta JSON, score FLOAT) {}",
+        options
+    ));
+    execute(executor, session, r#"INSERT INTO docs VALUES (1, 'first', '{"tags": ["a", "b"], "n": 1}', 1.5)"#);
+    execute(executor, session, "INSERT INTO docs VALUES (2, NULL, NULL, NULL)");
+    execute(executor, session, r#"INSERT INTO docs VALUES (3, 'quote '' and ünïcode', '[1, null, "x"]', -2)"#);
+}
+
+fn expected_docs() -> Vec<Vec<Value>> {
+    vec![
+        vec![
+            Value::Integer(1),
+            Value::String("first".into()),
+            Value::Json(json!({"tags": ["a", "b"], "n": 1})),
+            Value::Float(1.5),
+        ],
+        vec![Value::Integer(2), Value::Null, Value::Null, Value::Null],
+        vec![
+            Value::Integer(3),
+            Value::String("quote ' and ünïcode".into()),
+            Value::Json(json!([1, null, "x"])),
+            Value::Float(-2.0),
+        ],
+    ]
+}
+
+#[test]
+fn test_binary_format_round_trip() {
+    let dir = tempdir().unwrap();
+    {
+        let (executor, mut session) = setup(dir.path());
+        create_docs(&executor, &mut session, "STORAGE FORMAT = BINARY");
+        executor.shutdown().unwrap();
+    }
+
+    let tables_dir = dir.path().join("tables");
+    assert!(tables_dir.join("docs.bdat").exists());
+    assert!(!tables_dir.join("docs.dat").exists());
+
+    let (executor, mut session) = setup(dir.path());
+    assert_eq!(executor.storage().get_schema("docs").unwrap().storage_format, StorageFormat::Binary);
+    let rows = select_rows(&executor, &mut session, "SELECT * FROM docs ORDER BY id");
+    assert_eq!(rows, expected_docs());
+}
+
+#[test]
+fn test_default_storage_format_from_config() {
+    let dir = tempdir().unwrap();
+    let (mut executor, mut session) = setup(dir.path());
+    executor.set_default_storage_format(StorageFormat::Binary);
+
+    execute(&executor, &mut session, "CREATE TABLE a (id INTEGER)");
+    execute(&executor, &mut session, "CREATE TABLE b (id INTEGER) STORAGE FORMAT JSONL");
+
+    assert_eq!(executor.storage().get_schema("a").unwrap().storage_format, StorageFormat::Binary);
+    assert_eq!(executor.storage().get_schema("b").unwrap().storage_format, StorageFormat::Jsonl);
+}
+
+#[test]
+fn test_vacuum_migrates_jsonl_to_binary() {
+    let dir = tempdir().unwrap();
+    let tables_dir = dir.path().join("tables");
+    {
+        let (executor, mut session) = setup(dir.path());
+        create_docs(&executor, &mut session, "");
+        executor.shutdown().unwrap();
+        assert!(tables_dir.join("docs.dat").exists());
+
+        execute(&executor, &mut session, "VACUUM STORAGE FORMAT = BINARY");
+    }
+
+    assert!(tables_dir.join("docs.bdat").exists());
+    assert!(!tables_dir.join("docs.dat").exists());
+
+    let (executor, mut session) = setup(dir.path());
+    assert_eq!(executor.storage().get_schema("docs").unwrap().storage_format, StorageFormat::Binary);
+    let rows = select_rows(&executor, &mut session, "SELECT * FROM docs ORDER BY id");
+    assert_eq!(rows, expected_docs());
+
+    // And back again
+    execute(&executor, &mut session, "VACUUM STORAGE FORMAT = JSONL");
+    assert!(tables_dir.join("docs.dat").exists());
+    assert!(!tables_dir.join("docs.bdat").exists());
+}
+
+#[test]
+fn test_truncated_binary_file_is_rejected() {
+    let dir = tempdir().unwrap();
+    {
+        let (executor, mut session) = setup(dir.path());
+        create_docs(&executor, &mut session, "STORAGE FORMAT = BINARY");
+        executor.shutdown().unwrap();
+    }
+
+    let path = dir.path().join("tables").join("docs.bdat");
+    let data = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &data[..data.len() - 3]).unwrap();
+
+    let err = StorageEngine::new(dir.path().to_path_buf()).err().expect("truncated file should fail to load");
+    assert!(err.to_string().contains("truncated record"), "unexpected error: {}", err);
+}
+
+#[test]
+fn test_binary_format_is_smaller_than_jsonl() {
+    let row_count = 20_000;
+    let mut results = Vec::new();
+
+    for format in [StorageFormat::Jsonl, StorageFormat::Binary] {
+        let dir = tempdir().unwrap();
+        {
+            let (executor, mut session) = setup(dir.path());
+            execute(&executor, &mut session, &format!(
+                "CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT, amount FLOAT, payload JSON) STORAGE FORMAT = {}",
+                format
+            ));
+            let storage = executor.storage();
+            for i in 0..row_count {
+                storage.insert_row("events", vec![
+                    Value::Integer(i),
+                    Value::String(format!("kind-{}", i % 7)),
+                    Value::Float(i as f64 * 0.25),
+                    Value::Json(json!({"seq": i, "ok": i % 2 == 0})),
+                ]).unwrap();
+            }
+
+            storage.save_table("events").unwrap();
+
+            let path = dir.path().join("tables").join(format!("events.{}", format.extension()));
+            let size = std::fs::metadata(&path).unwrap().len();
+
+            let reopened = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+            assert_eq!(reopened.scan_table("events").unwrap().len(), row_count as usize);
+            results.push(size);
+        }
+    }
+
+    assert!(results[1] < results[0], "binary file should be smaller than JSONL ({} vs {})", results[1], results[0]);
+}
@@ -0,0 +1,178 @@
+//! Streaming SELECT tests
+//!
+//! Rows are handed to a sink one at a time; these tests check the sink sees
+//! the same rows the buffered path returns, and that the wire protocol path
+//! delivers a large result set.
+
+mod common;
+
+use std::sync::Arc;
+
+use minisql::engines::granite::TransactionManager;
+use minisql::error::{MiniSqlError, Result};
+use minisql::executor::{Executor, RowSink, Session};
+use minisql::parser::{Parser, SelectStmt, Statement};
+use minisql::storage::StorageEngine;
+use minisql::types::{DataType, QueryResult, Value};
+use minisql::{Config, Server};
+use tempfile::TempDir;
+use tokio::net::TcpStream;
+
+use common::{connect, query, read_packet, serve};
+
+const ROWS: i64 = 100_000;
+
+/// Counts rows without keeping them
+#[derive(Default)]
+struct CountingSink {
+    columns: Vec<String>,
+    rows: u64,
+    id_sum: i64,
+    first_ids: Vec<i64>,
+    fail_after: Option<u64>,
+}
+
+impl RowSink for CountingSink {
+    fn begin(&mut self, columns: &[String], _column_types: &[DataType]) -> Result<()> {
+        assert!(self.columns.is_empty(), "begin called twice");
+        self.columns = columns.to_vec();
+        Ok(())
+    }
+
+    fn send_row(&mut self, row: Vec<Value>) -> Result<()> {
+        assert!(!self.columns.is_empty(), "row before begin");
+        if Some(self.rows) == self.fail_after {
+            return Err(MiniSqlError::Protocol("sink closed".into()));
+        }
+        if let Value::Integer(id) = row[0] {
+            self.id_sum += id;
+            if self.first_ids.len() < 3 {
+                self.first_ids.push(id);
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+}
+
+fn select(sql: &str) -> SelectStmt {
+    match Parser::parse(sql).unwrap() {
+        Statement::Select(select) => select,
+        _ => panic!("Expected SELECT"),
+    }
+}
+
+fn populated_executor(path: &std::path::Path) -> Executor {
+    let storage = StorageEngine::new(path.to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(path.to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let create = Parser::parse("CREATE TABLE big (id INT PRIMARY KEY, label TEXT)").unwrap();
+    executor.execute(create, &mut Session::new()).unwrap();
+    // Load through the storage engine directly; row-at-a-time SQL inserts are too slow here
+    for id in 1..=ROWS {
+        executor.storage()
+            .insert_row("big", vec![Value::Integer(id), Value::String(format!("row {}", id))])
+            .unwrap();
+    }
+    executor
+}
+
+#[test]
+fn test_streaming_select_over_100k_rows() {
+    let temp_dir = TempDir::new().unwrap();
+    let executor = populated_executor(temp_dir.path());
+    let mut session = Session::new();
+
+    // Full scan: every row reaches the sink exactly once
+    let mut sink = CountingSink::default();
+    let sent = executor.execute_select_streaming(select("SELECT id, label FROM big"), &mut session, &mut sink).unwrap();
+    assert_eq!(sent, ROWS as u64);
+    assert_eq!(sink.rows, ROWS as u64);
+    assert_eq!(sink.columns, vec!["id".to_string(), "label".to_string()]);
+    assert_eq!(sink.id_sum, ROWS * (ROWS + 1) / 2);
+
+    // WHERE and LIMIT stop early without scanning into the sink
+    let mut sink = CountingSink::default();
+    executor.execute_select_streaming(select("SELECT id FROM big WHERE id > 500 LIMIT 10"), &mut session, &mut sink).unwrap();
+    assert_eq!(sink.rows, 10);
+
+    // ORDER BY is sorted before streaming
+    let mut sink = CountingSink::default();
+    executor.execute_select_streaming(select("SELECT id FROM big ORDER BY id DESC LIMIT 3"), &mut session, &mut sink).unwrap();
+    assert_eq!(sink.first_ids, vec![ROWS, ROWS - 1, ROWS - 2]);
+
+    // Aggregates go through the buffered path but still reach the sink
+    let mut sink = CountingSink::default();
+    executor.execute_select_streaming(select("SELECT COUNT(*) FROM big"), &mut session, &mut sink).unwrap();
+    assert_eq!(sink.rows, 1);
+    assert_eq!(sink.id_sum, ROWS);
+
+    // A sink error aborts the query
+    let mut sink = CountingSink { fail_after: Some(1000), ..Default::default() };
+    assert!(executor.execute_select_streaming(select("SELECT id FROM big"), &mut session, &mut sink).is_err());
+    assert_eq!(sink.rows, 1000);
+
+    // The buffered path returns the same rows
+    match executor.execute(Statement::Select(select("SELECT id FROM big WHERE id <= 5")), &mut session).unwrap() {
+        QueryResult::Select(rs) => assert_eq!(rs.rows.len(), 5),
+        _ => panic!("Expected select result"),
+    }
+}
+
+/// Send a COM_QUERY and count the row packets of its text result set
+async fn query_row_count(stream: &mut TcpStream, sql: &str) -> std::result::Result<usize, Vec<u8>> {
+    let first = query(stream, sql).await;
+    if first[0] == 0xFF {
+        return Err(first);
+    }
+    let columns = first[0] as usize;
+    for _ in 0..columns {
+        read_packet(stream).await;
+    }
+    let eof = read_packet(stream).await;
+    assert_eq!(eof[0], 0xFE, "expected EOF after column definitions");
+
+    let mut rows = 0;
+    loop {
+        let packet = read_packet(stream).await;
+        match packet[0] {
+            0xFE if packet.len() < 9 => return Ok(rows),
+            0xFF => return Err(packet),
+            _ => rows += 1,
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_select_streams_over_the_wire() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config {
+        data_dir: temp_dir.path().to_path_buf(),
+        password: String::new(),
+        ..Config::default()
+    };
+    let server = Arc::new(Server::new(config).await.unwrap());
+    let executor = server.executor();
+    let create = Parser::parse("CREATE TABLE t (id INT PRIMARY KEY, label TEXT)").unwrap();
+    executor.execute(create, &mut Session::new()).unwrap();
+    for id in 1..=5000 {
+        executor.storage()
+            .insert_row("t", vec![Value::Integer(id), Value::String(format!("label {}", id))])
+            .unwrap();
+    }
+
+    let addr = serve(&server).await;
+
+    let mut client = connect(addr).await;
+    assert_eq!(query_row_count(&mut client, "SELECT * FROM t").await.unwrap(), 5000);
+    assert_eq!(query_row_count(&mut client, "SELECT id FROM t WHERE id <= 10").await.unwrap(), 10);
+
+    // Errors before the first row arrive as a plain ERR packet
+    let err = query_row_count(&mut client, "SELECT * FROM missing").await.unwrap_err();
+    assert_eq!(u16::from_le_bytes([err[1], err[2]]), 1146);
+
+    // The session survives the round trip through the executor thread
+    assert_eq!(query_row_count(&mut client, "SELECT id FROM t LIMIT 3").await.unwrap(), 3);
+
+    server.shutdown();
+}
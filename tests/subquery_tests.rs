@@ -0,0 +1,145 @@
+//! Tests for `expr [NOT] IN (SELECT ...)` subqueries
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::tempdir;
+
+fn create_test_executor() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    (Executor::new(storage, txn_manager), Session::new(), dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_ids(executor: &Executor, session: &mut Session, sql: &str) -> Vec<i64> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows.iter().map(|row| match row[0] {
+            Value::Integer(i) => i,
+            ref other => panic!("Expected integer, got {:?}", other),
+        }).collect(),
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+fn create_shop(executor: &Executor, session: &mut Session) {
+    execute(executor, session, "CREATE TABLE customers (id INTEGER PRIMARY KEY, name TEXT, active BOOLEAN)");
+    execute(executor, session, "CREATE TABLE orders (id INTEGER PRIMARY KEY, customer_id INTEGER, total INTEGER)");
+    execute(executor, session, "INSERT INTO customers VALUES (1, 'ann', true), (2, 'bob', false), (3, 'cy', true)");
+    execute(executor, session,
+        "INSERT INTO orders VALUES (10, 1, 5), (11, 2, 7), (12, 3, 9), (13, 1, 11), (14, NULL, 13), (15, 4, 15)");
+}
+
+#[test]
+fn test_in_subquery() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    assert_eq!(
+        select_ids(&executor, &mut session,
+            "SELECT * FROM orders WHERE customer_id IN (SELECT id FROM customers WHERE active = true) ORDER BY id"),
+        vec![10, 12, 13]
+    );
+    assert_eq!(
+        select_ids(&executor, &mut session,
+            "SELECT id FROM orders WHERE customer_id NOT IN (SELECT id FROM customers WHERE active = true) ORDER BY id"),
+        vec![11, 15]
+    );
+    // Combined with other conditions and inside a join
+    assert_eq!(
+        select_ids(&executor, &mut session,
+            "SELECT o.id FROM orders o JOIN customers c ON o.customer_id = c.id \
+             WHERE o.total > 6 AND c.id IN (SELECT id FROM customers WHERE name <> 'bob') ORDER BY o.id"),
+        vec![12, 13]
+    );
+}
+
+#[test]
+fn test_in_subquery_null_handling() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    // A NULL in the subquery makes NOT IN unknown for every non-matching row
+    assert_eq!(
+        select_ids(&executor, &mut session,
+            "SELECT id FROM orders WHERE id NOT IN (SELECT customer_id FROM orders)"),
+        Vec::<i64>::new()
+    );
+    // ...but IN still finds the rows that do match
+    assert_eq!(
+        select_ids(&executor, &mut session,
+            "SELECT id FROM customers WHERE id IN (SELECT customer_id FROM orders) ORDER BY id"),
+        vec![1, 2, 3]
+    );
+
+    // An empty subquery: IN is false and NOT IN true, even for a NULL operand
+    assert_eq!(
+        select_ids(&executor, &mut session,
+            "SELECT id FROM orders WHERE customer_id IN (SELECT id FROM customers WHERE id > 100)"),
+        Vec::<i64>::new()
+    );
+    assert_eq!(
+        select_ids(&executor, &mut session,
+            "SELECT id FROM orders WHERE customer_id NOT IN (SELECT id FROM customers WHERE id > 100) ORDER BY id"),
+        vec![10, 11, 12, 13, 14, 15]
+    );
+}
+
+#[test]
+fn test_in_subquery_runs_once() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    // One scan of customers for the subquery and one of orders, not one per order
+    let scans = executor.storage().full_scan_count();
+    execute(&executor, &mut session,
+        "SELECT id FROM orders WHERE customer_id IN (SELECT id FROM customers WHERE active = true)");
+    assert_eq!(executor.storage().full_scan_count(), scans + 2);
+}
+
+#[test]
+fn test_nested_subquery_and_dml() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    assert_eq!(
+        select_ids(&executor, &mut session,
+            "SELECT id FROM customers WHERE id IN \
+             (SELECT customer_id FROM orders WHERE total IN (SELECT total FROM orders WHERE total > 8))"),
+        vec![1, 3]
+    );
+
+    execute(&executor, &mut session,
+        "UPDATE orders SET total = 0 WHERE customer_id IN (SELECT id FROM customers WHERE active = false)");
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM orders WHERE total = 0"), vec![11]);
+
+    execute(&executor, &mut session,
+        "DELETE FROM orders WHERE customer_id NOT IN (SELECT id FROM customers)");
+    assert_eq!(
+        select_ids(&executor, &mut session, "SELECT id FROM orders ORDER BY id"),
+        vec![10, 11, 12, 13, 14]
+    );
+}
+
+#[test]
+fn test_in_subquery_must_return_one_column() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_shop(&executor, &mut session);
+
+    for sql in [
+        "SELECT id FROM orders WHERE customer_id IN (SELECT id, name FROM customers)",
+        "SELECT id FROM orders WHERE customer_id IN (SELECT * FROM customers)",
+    ] {
+        let err = executor.execute(Parser::parse(sql).unwrap(), &mut session).unwrap_err();
+        assert_eq!(err.to_string(), "Operand should contain 1 column(s)", "{}", sql);
+        assert_eq!(err.mysql_error_code(), 1241);
+        assert_eq!(err.sql_state(), "21000");
+    }
+}
@@ -0,0 +1,137 @@
+//! Tests for `TABLESAMPLE (n PERCENT | n ROWS) [REPEATABLE (seed)]`
+
+use std::collections::HashSet;
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::tempdir;
+
+fn create_test_executor() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    (Executor::new(storage, txn_manager), Session::new(), dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_ids(executor: &Executor, session: &mut Session, sql: &str) -> Vec<i64> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows.iter().map(|row| match row[0] {
+            Value::Integer(i) => i,
+            ref other => panic!("Expected integer, got {:?}", other),
+        }).collect(),
+        _ => panic!("Expected SELECT result"),
+    }
+}
+
+/// Table `t` with ids 1..=500
+fn create_table(executor: &Executor, session: &mut Session) {
+    execute(executor, session, "CREATE TABLE t (id INTEGER PRIMARY KEY, grp INTEGER)");
+    let values: Vec<String> = (1..=500).map(|i| format!("({}, {})", i, i % 10)).collect();
+    execute(executor, session, &format!("INSERT INTO t VALUES {}", values.join(", ")));
+}
+
+#[test]
+fn test_percent_sample_is_approximately_proportional() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_table(&executor, &mut session);
+
+    for sql in [
+        "SELECT id FROM t TABLESAMPLE (10 PERCENT)",
+        "SELECT id FROM t TABLESAMPLE (10 PERCENT) REPEATABLE (42)",
+    ] {
+        let ids = select_ids(&executor, &mut session, sql);
+        // Binomial(500, 0.1): mean 50, standard deviation under 7
+        assert!((20..=80).contains(&ids.len()), "{} returned {} rows", sql, ids.len());
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+    }
+
+    assert!(select_ids(&executor, &mut session, "SELECT id FROM t TABLESAMPLE (0 PERCENT)").is_empty());
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM t TABLESAMPLE (100 PERCENT)").len(), 500);
+    let ids = select_ids(&executor, &mut session, "SELECT id FROM t TABLESAMPLE (2.5 PERCENT) REPEATABLE (7)");
+    assert!((2..=30).contains(&ids.len()), "2.5 PERCENT returned {} rows", ids.len());
+}
+
+#[test]
+fn test_rows_sample_has_exact_count() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_table(&executor, &mut session);
+
+    let ids = select_ids(&executor, &mut session, "SELECT id FROM t TABLESAMPLE (25 ROWS)");
+    assert_eq!(ids.len(), 25);
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 25);
+    assert!(ids.iter().all(|id| (1..=500).contains(id)));
+
+    assert!(select_ids(&executor, &mut session, "SELECT id FROM t TABLESAMPLE (0 ROWS)").is_empty());
+    // A sample larger than the table returns the whole table
+    assert_eq!(select_ids(&executor, &mut session, "SELECT id FROM t TABLESAMPLE (5000 ROWS)").len(), 500);
+
+    // Only the sampled rows are read from the table
+    session.take_read_stats();
+    select_ids(&executor, &mut session, "SELECT id FROM t TABLESAMPLE (25 ROWS)");
+    assert_eq!(session.take_read_stats().rows_examined, 25);
+
+    // Through the aggregate path
+    assert_eq!(
+        select_ids(&executor, &mut session, "SELECT COUNT(*) FROM t TABLESAMPLE (40 ROWS) REPEATABLE (3)"),
+        vec![40]
+    );
+}
+
+#[test]
+fn test_repeatable_seed_is_reproducible() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_table(&executor, &mut session);
+
+    for size in ["10 PERCENT", "30 ROWS"] {
+        let first = select_ids(&executor, &mut session,
+            &format!("SELECT id FROM t TABLESAMPLE ({}) REPEATABLE (1234) ORDER BY id", size));
+        let second = select_ids(&executor, &mut session,
+            &format!("SELECT id FROM t TABLESAMPLE ({}) REPEATABLE (1234) ORDER BY id", size));
+        let other = select_ids(&executor, &mut session,
+            &format!("SELECT id FROM t TABLESAMPLE ({}) REPEATABLE (99) ORDER BY id", size));
+        assert_eq!(first, second, "{}", size);
+        assert_ne!(first, other, "{}", size);
+    }
+}
+
+#[test]
+fn test_sample_is_taken_before_where_and_joins() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_table(&executor, &mut session);
+
+    // WHERE filters the sampled rows, so the result never exceeds the sample
+    let sampled = select_ids(&executor, &mut session,
+        "SELECT id FROM t s TABLESAMPLE (100 ROWS) REPEATABLE (5) ORDER BY id");
+    let filtered = select_ids(&executor, &mut session,
+        "SELECT id FROM t s TABLESAMPLE (100 ROWS) REPEATABLE (5) WHERE s.grp = 0 ORDER BY id");
+    let expected: Vec<i64> = sampled.iter().copied().filter(|id| id % 10 == 0).collect();
+    assert_eq!(filtered, expected);
+
+    // Each FROM item is sampled independently
+    execute(&executor, &mut session, "CREATE TABLE groups (grp INTEGER PRIMARY KEY, label TEXT)");
+    execute(&executor, &mut session, "INSERT INTO groups VALUES (0, 'zero'), (1, 'one'), (2, 'two')");
+    let joined = select_ids(&executor, &mut session,
+        "SELECT t.id FROM t TABLESAMPLE (50 ROWS) JOIN groups g ON t.grp = g.grp");
+    assert!(joined.len() <= 50);
+}
+
+#[test]
+fn test_invalid_tablesample() {
+    for sql in [
+        "SELECT * FROM t TABLESAMPLE (150 PERCENT)",
+        "SELECT * FROM t TABLESAMPLE (10)",
+        "SELECT * FROM t TABLESAMPLE (1.5 ROWS)",
+        "SELECT * FROM t TABLESAMPLE 10 PERCENT",
+        "SELECT * FROM t TABLESAMPLE (10 PERCENT) REPEATABLE (x)",
+    ] {
+        assert!(Parser::parse(sql).is_err(), "{} should not parse", sql);
+    }
+}
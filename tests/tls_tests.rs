@@ -0,0 +1,128 @@
+//! TLS connection tests
+//!
+//! The server is given a self-signed certificate; a client sends the SSL
+//! request, completes the TLS handshake against that certificate and then
+//! authenticates and queries over the encrypted stream.
+
+mod common;
+
+use std::sync::Arc;
+
+use minisql::{Config, Server};
+use tempfile::TempDir;
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{self, ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+use common::{connect, handshake_response, read_packet, serve, write_packet, CAPS, CLIENT_SSL};
+
+/// Capability flags from the server greeting
+fn greeting_capabilities(greeting: &[u8]) -> u32 {
+    let version_end = 1 + greeting[1..].iter().position(|&b| b == 0).unwrap() + 1;
+    // connection id (4), auth data part 1 (8), filler (1)
+    let lower = version_end + 13;
+    let upper = lower + 2 + 1 + 2;
+    u16::from_le_bytes([greeting[lower], greeting[lower + 1]]) as u32
+        | (u16::from_le_bytes([greeting[upper], greeting[upper + 1]]) as u32) << 16
+}
+
+/// Server configured with a freshly generated self-signed certificate for `localhost`
+async fn tls_server(dir: &TempDir) -> (Arc<Server>, std::net::SocketAddr, rustls::pki_types::CertificateDer<'static>) {
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_path = dir.path().join("server.crt");
+    let key_path = dir.path().join("server.key");
+    std::fs::write(&cert_path, certified.cert.pem()).unwrap();
+    std::fs::write(&key_path, certified.key_pair.serialize_pem()).unwrap();
+
+    let config = Config {
+        data_dir: dir.path().join("data"),
+        password: String::new(),
+        tls_cert_path: Some(cert_path),
+        tls_key_path: Some(key_path),
+        ..Config::default()
+    };
+    let server = Arc::new(Server::new(config).await.unwrap());
+    let addr = serve(&server).await;
+    (server, addr, certified.cert.der().clone())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tls_handshake_and_query() {
+    let dir = TempDir::new().unwrap();
+    let (server, addr, cert) = tls_server(&dir).await;
+
+    let mut tcp = TcpStream::connect(addr).await.unwrap();
+    let greeting = read_packet(&mut tcp).await;
+    assert_ne!(greeting_capabilities(&greeting) & CLIENT_SSL, 0, "server should offer SSL");
+
+    // SSL request, then the TLS handshake on the same socket
+    let caps = CAPS | CLIENT_SSL;
+    let mut ssl_request = handshake_response("root", caps, b"", "");
+    ssl_request.truncate(32);
+    write_packet(&mut tcp, 1, &ssl_request).await;
+
+    let mut roots = RootCertStore::empty();
+    roots.add(cert).unwrap();
+    let client_config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(client_config));
+    let mut tls = connector
+        .connect(ServerName::try_from("localhost").unwrap(), tcp)
+        .await
+        .expect("TLS handshake failed");
+
+    // Handshake response over TLS continues the packet sequence
+    write_packet(&mut tls, 2, &handshake_response("root", caps, b"", "mysql_native_password")).await;
+    let ok = read_packet(&mut tls).await;
+    assert_eq!(ok[0], 0x00, "expected OK after handshake");
+
+    let mut query = vec![0x03];
+    query.extend_from_slice(b"SELECT 1 + 1");
+    write_packet(&mut tls, 0, &query).await;
+    assert_eq!(read_packet(&mut tls).await, vec![1], "one column");
+    read_packet(&mut tls).await; // column definition
+    assert_eq!(read_packet(&mut tls).await[0], 0xFE);
+    assert_eq!(read_packet(&mut tls).await, vec![1, b'2']);
+    assert_eq!(read_packet(&mut tls).await[0], 0xFE);
+
+    server.shutdown();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_plaintext_client_still_accepted_with_tls_configured() {
+    let dir = TempDir::new().unwrap();
+    let (server, addr, _cert) = tls_server(&dir).await;
+
+    let _client = connect(addr).await;
+
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn test_ssl_not_offered_without_certificate() {
+    let dir = TempDir::new().unwrap();
+    let config = Config { data_dir: dir.path().to_path_buf(), ..Config::default() };
+    let server = Arc::new(Server::new(config).await.unwrap());
+    let addr = serve(&server).await;
+
+    let mut tcp = TcpStream::connect(addr).await.unwrap();
+    let greeting = read_packet(&mut tcp).await;
+    assert_eq!(greeting_capabilities(&greeting) & CLIENT_SSL, 0);
+
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn test_certificate_without_key_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    let config = Config {
+        data_dir: dir.path().to_path_buf(),
+        tls_cert_path: Some(dir.path().join("server.crt")),
+        ..Config::default()
+    };
+    assert!(Server::new(config).await.is_err());
+}
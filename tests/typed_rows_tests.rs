@@ -0,0 +1,105 @@
+//! Tests for typed row access over query results
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::QueryResult;
+use serde_json::json;
+use tempfile::tempdir;
+
+fn create_test_executor() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    (Executor::new(storage, txn_manager), Session::new(), dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn create_people(executor: &Executor, session: &mut Session) {
+    execute(executor, session,
+        "CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT, score FLOAT, active BOOLEAN, profile JSON)");
+    execute(executor, session,
+        "INSERT INTO people VALUES (1, 'ann', 9.5, true, '{\"langs\": [\"rust\", \"sql\"]}')");
+    execute(executor, session, "INSERT INTO people VALUES (2, NULL, NULL, NULL, NULL)");
+}
+
+#[test]
+fn test_typed_accessors_by_index_and_name() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_people(&executor, &mut session);
+
+    let result = execute(&executor, &mut session, "SELECT * FROM people WHERE id = 1");
+    let row = result.rows().next().unwrap();
+    assert_eq!(row.get_i64(0).unwrap(), Some(1));
+    assert_eq!(row.get_i64("ID").unwrap(), Some(1));
+    assert_eq!(row.get_str("name").unwrap(), Some("ann"));
+    assert_eq!(row.get_f64("score").unwrap(), Some(9.5));
+    assert_eq!(row.get_f64("id").unwrap(), Some(1.0));
+    assert_eq!(row.get_bool("active").unwrap(), Some(true));
+    assert_eq!(row.get_json("profile").unwrap(), Some(json!({"langs": ["rust", "sql"]})));
+    assert_eq!(row.columns().len(), 5);
+    assert_eq!(row.values().len(), 5);
+}
+
+#[test]
+fn test_null_values_are_none() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_people(&executor, &mut session);
+
+    let result = execute(&executor, &mut session, "SELECT * FROM people WHERE id = 2");
+    let row = result.rows().next().unwrap();
+    assert_eq!(row.get_str("name").unwrap(), None);
+    assert_eq!(row.get_f64("score").unwrap(), None);
+    assert_eq!(row.get_bool("active").unwrap(), None);
+    assert_eq!(row.get_json("profile").unwrap(), None);
+    assert!(row.get("name").unwrap().is_null());
+}
+
+#[test]
+fn test_type_mismatch_and_missing_column_errors() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_people(&executor, &mut session);
+
+    let result = execute(&executor, &mut session, "SELECT id, name, score FROM people WHERE id = 1");
+    let row = result.rows().next().unwrap();
+
+    let err = row.get_i64("name").unwrap_err();
+    assert_eq!(err.to_string(), "Column 'name' holds a string, not an integer");
+    assert!(row.get_str("id").is_err());
+    assert!(row.get_i64("score").is_err());
+    assert!(row.get_json("score").is_err());
+    // A string that is not a JSON document
+    assert!(row.get_json("name").is_err());
+
+    assert_eq!(row.get_i64("nope").unwrap_err().mysql_error_code(), 1054);
+    assert!(row.get_i64(3).is_err());
+}
+
+#[test]
+fn test_iterating_results() {
+    let (executor, mut session, _dir) = create_test_executor();
+    create_people(&executor, &mut session);
+
+    let result = execute(&executor, &mut session, "SELECT id, name FROM people ORDER BY id");
+    assert_eq!(result.rows().len(), 2);
+    let names: Vec<Option<&str>> = result.rows().map(|row| row.get_str("name").unwrap()).collect();
+    assert_eq!(names, vec![Some("ann"), None]);
+
+    let result_set = result.result_set().unwrap();
+    assert_eq!(result_set.column_index("NAME"), Some(1));
+    assert_eq!(result_set.column_index("missing"), None);
+    assert_eq!(result_set.row(1).unwrap().get_i64("id").unwrap(), Some(2));
+    assert!(result_set.row(2).is_none());
+    let ids: Vec<i64> = result_set.into_iter().map(|row| row.get_i64(0).unwrap().unwrap()).collect();
+    assert_eq!(ids, vec![1, 2]);
+
+    // Statements without a result set have no rows
+    let result = execute(&executor, &mut session, "UPDATE people SET name = 'x' WHERE id = 2");
+    assert!(result.result_set().is_none());
+    assert_eq!(result.rows().count(), 0);
+}
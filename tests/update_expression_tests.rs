@@ -0,0 +1,77 @@
+//! Tests for UPDATE assignments computed from the row's columns
+
+use minisql::engines::granite::TransactionManager;
+use minisql::engines::SandstoneConfig;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::{tempdir, TempDir};
+
+fn setup(engine: &str) -> (TempDir, Executor, Session) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig::default()).unwrap();
+    let mut session = Session::new();
+    execute(&executor, &mut session, &format!(
+        "CREATE TABLE orders (id INT PRIMARY KEY, price INT, qty INT, total INT, updated INT) ENGINE={}",
+        engine
+    ));
+    execute(&executor, &mut session, "INSERT INTO orders VALUES (1, 5, 3, NULL, 0), (2, 7, 2, NULL, 4)");
+    (dir, executor, session)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+fn ints(rows: &[&[i64]]) -> Vec<Vec<Value>> {
+    rows.iter().map(|row| row.iter().map(|&n| Value::Integer(n)).collect()).collect()
+}
+
+#[test]
+fn test_update_computed_from_other_columns() {
+    for engine in ["Granite", "Sandstone"] {
+        let (_dir, executor, mut session) = setup(engine);
+        execute(&executor, &mut session, "UPDATE orders SET total = price * qty, updated = updated + 1");
+        assert_eq!(
+            select_rows(&executor, &mut session, "SELECT id, total, updated FROM orders ORDER BY id"),
+            ints(&[&[1, 15, 1], &[2, 14, 5]]),
+            "{}", engine
+        );
+
+        execute(&executor, &mut session, "UPDATE orders SET qty = qty + 1, updated = updated + 1 WHERE id = 2");
+        execute(&executor, &mut session, "UPDATE orders SET total = orders.price * orders.qty WHERE total < qty * 10");
+        assert_eq!(
+            select_rows(&executor, &mut session, "SELECT id, qty, total, updated FROM orders ORDER BY id"),
+            ints(&[&[1, 3, 15, 1], &[2, 3, 21, 6]]),
+            "{}", engine
+        );
+    }
+}
+
+#[test]
+fn test_update_assignments_apply_left_to_right() {
+    let (_dir, executor, mut session) = setup("Granite");
+
+    // Later assignments see the values given by earlier ones
+    execute(&executor, &mut session, "UPDATE orders SET price = price + 1, total = price * qty WHERE id = 1");
+    assert_eq!(select_rows(&executor, &mut session, "SELECT price, total FROM orders WHERE id = 1"), ints(&[&[6, 18]]));
+
+    // So swapping two columns needs a third; this copies qty into price and back
+    execute(&executor, &mut session, "UPDATE orders SET price = qty, qty = price WHERE id = 2");
+    assert_eq!(select_rows(&executor, &mut session, "SELECT price, qty FROM orders WHERE id = 2"), ints(&[&[2, 2]]));
+
+    // A column assigned twice keeps the last value
+    execute(&executor, &mut session, "UPDATE orders SET updated = updated + 10, updated = updated * 2 WHERE id = 1");
+    assert_eq!(select_rows(&executor, &mut session, "SELECT updated FROM orders WHERE id = 1"), ints(&[&[20]]));
+}
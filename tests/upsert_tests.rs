@@ -0,0 +1,112 @@
+//! Tests for INSERT ... ON DUPLICATE KEY UPDATE
+
+use minisql::engines::granite::TransactionManager;
+use minisql::engines::SandstoneConfig;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::{tempdir, TempDir};
+
+fn setup(engine: &str) -> (Executor, Session, TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::with_sandstone(storage, txn_manager, SandstoneConfig::default()).unwrap();
+    let mut session = Session::new();
+    execute(&executor, &mut session, &format!(
+        "CREATE TABLE counters (name TEXT PRIMARY KEY, hits INTEGER, label TEXT) ENGINE={}", engine
+    ));
+    (executor, session, dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).unwrap_or_else(|e| panic!("Failed to parse '{}': {:?}", sql, e));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn affected(result: QueryResult) -> u64 {
+    match result {
+        QueryResult::Modified { rows_affected, .. } => rows_affected,
+        other => panic!("Expected Modified result, got {:?}", other),
+    }
+}
+
+fn rows(executor: &Executor, session: &mut Session) -> Vec<Vec<Value>> {
+    match execute(executor, session, "SELECT name, hits, label FROM counters ORDER BY name") {
+        QueryResult::Select(rs) => rs.rows,
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+fn row(name: &str, hits: i64, label: &str) -> Vec<Value> {
+    vec![Value::String(name.into()), Value::Integer(hits), Value::String(label.into())]
+}
+
+const UPSERT: &str = "INSERT INTO counters VALUES ('home', 1, 'Home') ON DUPLICATE KEY UPDATE hits = hits + 1";
+
+#[test]
+fn test_upsert_inserts_new_row() {
+    let (executor, mut session, _dir) = setup("Granite");
+    assert_eq!(affected(execute(&executor, &mut session, UPSERT)), 1);
+    assert_eq!(rows(&executor, &mut session), vec![row("home", 1, "Home")]);
+}
+
+#[test]
+fn test_upsert_updates_existing_row() {
+    let (executor, mut session, _dir) = setup("Granite");
+    execute(&executor, &mut session, UPSERT);
+    assert_eq!(affected(execute(&executor, &mut session, UPSERT)), 2);
+    assert_eq!(affected(execute(&executor, &mut session, UPSERT)), 2);
+    assert_eq!(rows(&executor, &mut session), vec![row("home", 3, "Home")]);
+}
+
+#[test]
+fn test_upsert_values_reference() {
+    let (executor, mut session, _dir) = setup("Granite");
+    execute(&executor, &mut session, "INSERT INTO counters VALUES ('home', 5, 'Home'), ('about', 1, 'About')");
+
+    let sql = "INSERT INTO counters (name, hits, label) VALUES ('home', 10, 'Start'), ('faq', 2, 'FAQ') \
+               ON DUPLICATE KEY UPDATE hits = hits + VALUES(hits), label = VALUES(label)";
+    // One update (2) plus one insert (1)
+    assert_eq!(affected(execute(&executor, &mut session, sql)), 3);
+    assert_eq!(rows(&executor, &mut session), vec![
+        row("about", 1, "About"),
+        row("faq", 2, "FAQ"),
+        row("home", 15, "Start"),
+    ]);
+}
+
+#[test]
+fn test_upsert_unchanged_row_counts_zero() {
+    let (executor, mut session, _dir) = setup("Granite");
+    execute(&executor, &mut session, "INSERT INTO counters VALUES ('home', 1, 'Home')");
+    let sql = "INSERT INTO counters VALUES ('home', 1, 'Home') ON DUPLICATE KEY UPDATE label = VALUES(label)";
+    assert_eq!(affected(execute(&executor, &mut session, sql)), 0);
+}
+
+#[test]
+fn test_upsert_assignments_see_earlier_assignments() {
+    let (executor, mut session, _dir) = setup("Granite");
+    execute(&executor, &mut session, "INSERT INTO counters VALUES ('home', 1, 'Home')");
+    let sql = "INSERT INTO counters VALUES ('home', 0, '') ON DUPLICATE KEY UPDATE hits = 7, hits = hits * 2";
+    execute(&executor, &mut session, sql);
+    assert_eq!(rows(&executor, &mut session), vec![row("home", 14, "Home")]);
+}
+
+#[test]
+fn test_upsert_sandstone() {
+    let (executor, mut session, _dir) = setup("Sandstone");
+    assert_eq!(affected(execute(&executor, &mut session, UPSERT)), 1);
+    assert_eq!(affected(execute(&executor, &mut session, UPSERT)), 2);
+    assert_eq!(rows(&executor, &mut session), vec![row("home", 2, "Home")]);
+}
+
+#[test]
+fn test_plain_insert_still_rejects_duplicates() {
+    let (executor, mut session, _dir) = setup("Granite");
+    execute(&executor, &mut session, "INSERT INTO counters VALUES ('home', 1, 'Home')");
+    let stmt = Parser::parse("INSERT INTO counters VALUES ('home', 1, 'Home')").unwrap();
+    let err = executor.execute(stmt, &mut session).unwrap_err();
+    assert!(err.to_string().starts_with("Duplicate entry 'home'"), "{}", err);
+}
@@ -0,0 +1,103 @@
+//! Tests for user-defined session variables (SET @name = expr, @name in expressions)
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::Parser;
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::tempdir;
+
+fn setup() -> (Executor, Session, tempfile::TempDir) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, price INTEGER)");
+    execute(&executor, &mut session,
+        "INSERT INTO items VALUES (1, 'pen', 3), (2, 'book', 12), (3, 'lamp', 40)");
+    (executor, session, dir)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    let stmt = Parser::parse(sql).expect(&format!("Failed to parse: {}", sql));
+    executor.execute(stmt, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn select_rows(executor: &Executor, session: &mut Session, sql: &str) -> Vec<Vec<Value>> {
+    match execute(executor, session, sql) {
+        QueryResult::Select(rs) => rs.rows,
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_set_and_select_variable() {
+    let (executor, mut session, _dir) = setup();
+
+    assert!(matches!(execute(&executor, &mut session, "SET @answer = 6 * 7"), QueryResult::Ok));
+    match execute(&executor, &mut session, "SELECT @answer") {
+        QueryResult::Select(rs) => {
+            assert_eq!(rs.columns, vec!["@answer".to_string()]);
+            assert_eq!(rs.rows, vec![vec![Value::Integer(42)]]);
+        }
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+
+    // Names are case-insensitive, and later assignments see earlier ones
+    execute(&executor, &mut session, "SET @Greeting = 'hi', @n = @ANSWER + 1");
+    assert_eq!(select_rows(&executor, &mut session, "SELECT @greeting, @N"), vec![
+        vec![Value::String("hi".to_string()), Value::Integer(43)],
+    ]);
+}
+
+#[test]
+fn test_undefined_variable_is_null() {
+    let (executor, mut session, _dir) = setup();
+
+    assert_eq!(select_rows(&executor, &mut session, "SELECT @missing, @missing IS NULL"), vec![
+        vec![Value::Null, Value::Boolean(true)],
+    ]);
+    assert!(select_rows(&executor, &mut session, "SELECT id FROM items WHERE price > @missing").is_empty());
+}
+
+#[test]
+fn test_variable_in_where_clause() {
+    let (executor, mut session, _dir) = setup();
+
+    execute(&executor, &mut session, "SET @min_price = 10");
+    assert_eq!(select_rows(&executor, &mut session, "SELECT id FROM items WHERE price >= @min_price ORDER BY id"), vec![
+        vec![Value::Integer(2)],
+        vec![Value::Integer(3)],
+    ]);
+
+    // The variable is read when the statement runs, not when it is set
+    execute(&executor, &mut session, "SET @min_price = 20");
+    assert_eq!(select_rows(&executor, &mut session, "SELECT id FROM items WHERE price >= @min_price"), vec![
+        vec![Value::Integer(3)],
+    ]);
+}
+
+#[test]
+fn test_variable_from_subquery_in_writes() {
+    let (executor, mut session, _dir) = setup();
+
+    execute(&executor, &mut session, "SET @top = (SELECT MAX(price) FROM items)");
+    execute(&executor, &mut session, "UPDATE items SET price = @top WHERE id = 1");
+    execute(&executor, &mut session, "INSERT INTO items VALUES (4, 'desk', @top * 2)");
+    execute(&executor, &mut session, "DELETE FROM items WHERE price = @top");
+    assert_eq!(select_rows(&executor, &mut session, "SELECT id, price FROM items ORDER BY id"), vec![
+        vec![Value::Integer(2), Value::Integer(12)],
+        vec![Value::Integer(4), Value::Integer(80)],
+    ]);
+}
+
+#[test]
+fn test_variables_are_per_session() {
+    let (executor, mut session, _dir) = setup();
+    execute(&executor, &mut session, "SET @x = 1");
+
+    let mut other = Session::new();
+    assert_eq!(select_rows(&executor, &mut other, "SELECT @x"), vec![vec![Value::Null]]);
+    assert_eq!(select_rows(&executor, &mut session, "SELECT @x"), vec![vec![Value::Integer(1)]]);
+}
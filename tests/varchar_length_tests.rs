@@ -0,0 +1,89 @@
+//! Tests for VARCHAR(n) length limits under strict and lenient SQL modes
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, Value};
+use tempfile::{tempdir, TempDir};
+
+fn setup() -> (TempDir, Executor, Session) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE users (id INT PRIMARY KEY, code VARCHAR(5), bio TEXT)");
+    (dir, executor, session)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    executor.execute_sql(sql, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+fn codes(executor: &Executor, session: &mut Session) -> Vec<Vec<Value>> {
+    match execute(executor, session, "SELECT code FROM users ORDER BY id") {
+        QueryResult::Select(rs) => rs.rows,
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+fn text(s: &str) -> Value {
+    Value::String(s.into())
+}
+
+#[test]
+fn test_within_limit_values_are_unchanged() {
+    let (_dir, executor, mut session) = setup();
+    let long_bio = "x".repeat(1000);
+    execute(&executor, &mut session, &format!("INSERT INTO users VALUES (1, 'abcde', '{}'), (2, 'ünï', NULL)", long_bio));
+    assert_eq!(codes(&executor, &mut session), vec![vec![text("abcde")], vec![text("ünï")]]);
+}
+
+#[test]
+fn test_strict_mode_rejects_long_strings() {
+    let (_dir, executor, mut session) = setup();
+
+    // STRICT_TRANS_TABLES is part of the default sql_mode
+    let err = executor.execute_sql("INSERT INTO users VALUES (1, 'abcdef', NULL)", &mut session).unwrap_err();
+    assert_eq!(err.to_string(), "Data too long for column 'code'");
+    assert_eq!(err.mysql_error_code(), 1406);
+    assert_eq!(err.sql_state(), "22001");
+    assert!(codes(&executor, &mut session).is_empty());
+
+    execute(&executor, &mut session, "INSERT INTO users VALUES (1, 'abc', NULL)");
+    let err = executor.execute_sql("UPDATE users SET code = 'much too long' WHERE id = 1", &mut session).unwrap_err();
+    assert_eq!(err.sql_state(), "22001");
+    let err = executor
+        .execute_sql("INSERT INTO users VALUES (1, 'x', NULL) ON DUPLICATE KEY UPDATE code = 'much too long'", &mut session)
+        .unwrap_err();
+    assert_eq!(err.sql_state(), "22001");
+    assert_eq!(codes(&executor, &mut session), vec![vec![text("abc")]]);
+}
+
+#[test]
+fn test_lenient_mode_truncates_long_strings() {
+    let (_dir, executor, mut session) = setup();
+    execute(&executor, &mut session, "SET sql_mode = ''");
+
+    execute(&executor, &mut session, "INSERT INTO users VALUES (1, 'abcdefgh', NULL), (2, 'ünïcödé', NULL)");
+    assert_eq!(codes(&executor, &mut session), vec![vec![text("abcde")], vec![text("ünïcö")]]);
+
+    execute(&executor, &mut session, "UPDATE users SET code = 'zyxwvuts' WHERE id = 1");
+    assert_eq!(codes(&executor, &mut session)[0], vec![text("zyxwv")]);
+}
+
+#[test]
+fn test_numbers_are_checked_as_text() {
+    let (_dir, executor, mut session) = setup();
+
+    let err = executor.execute_sql("INSERT INTO users VALUES (2, 1234567, NULL)", &mut session).unwrap_err();
+    assert_eq!(err.sql_state(), "22001");
+    execute(&executor, &mut session, "INSERT INTO users VALUES (1, 12345, NULL)");
+    let err = executor.execute_sql("UPDATE users SET code = 1234567 WHERE id = 1", &mut session).unwrap_err();
+    assert_eq!(err.sql_state(), "22001");
+    assert_eq!(codes(&executor, &mut session), vec![vec![text("12345")]]);
+
+    execute(&executor, &mut session, "SET sql_mode = ''");
+    execute(&executor, &mut session, "UPDATE users SET code = 1.23456789 WHERE id = 1");
+    assert_eq!(codes(&executor, &mut session), vec![vec![text("1.234")]]);
+}
@@ -0,0 +1,158 @@
+//! Tests for statement warnings and SHOW WARNINGS
+
+mod common;
+
+use std::sync::Arc;
+
+use minisql::engines::granite::TransactionManager;
+use minisql::executor::{Executor, Session};
+use minisql::parser::{Parser, Statement};
+use minisql::storage::StorageEngine;
+use minisql::types::{QueryResult, ResultSet, Value};
+use minisql::{Config, Server};
+use tempfile::{tempdir, TempDir};
+
+use common::{connect, query, serve};
+
+fn setup() -> (TempDir, Executor, Session) {
+    let dir = tempdir().unwrap();
+    let storage = StorageEngine::new(dir.path().to_path_buf()).unwrap();
+    let txn_manager = TransactionManager::new(dir.path().to_path_buf()).unwrap();
+    let executor = Executor::new(storage, txn_manager);
+    let mut session = Session::new();
+    execute(&executor, &mut session, "CREATE TABLE users (id INT PRIMARY KEY, code VARCHAR(3), score INT)");
+    (dir, executor, session)
+}
+
+fn execute(executor: &Executor, session: &mut Session, sql: &str) -> QueryResult {
+    executor.execute_sql(sql, session).unwrap_or_else(|e| panic!("SQL '{}' failed: {:?}", sql, e))
+}
+
+/// Rows of SHOW WARNINGS as (level, code, message)
+fn show_warnings(executor: &Executor, session: &mut Session) -> Vec<(String, i64, String)> {
+    match execute(executor, session, "SHOW WARNINGS") {
+        QueryResult::Select(rs) => {
+            assert_eq!(rs.columns, vec!["Level", "Code", "Message"]);
+            rs.rows
+                .into_iter()
+                .map(|row| match &row[..] {
+                    [Value::String(level), Value::Integer(code), Value::String(message)] => {
+                        (level.clone(), *code, message.clone())
+                    }
+                    other => panic!("Unexpected SHOW WARNINGS row {:?}", other),
+                })
+                .collect()
+        }
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+fn warning(level: &str, code: i64, message: &str) -> (String, i64, String) {
+    (level.to_string(), code, message.to_string())
+}
+
+#[test]
+fn test_truncation_warnings() {
+    let (_dir, executor, mut session) = setup();
+    execute(&executor, &mut session, "SET sql_mode = ''");
+
+    execute(&executor, &mut session, "INSERT INTO users VALUES (1, 'abc', 1), (2, 'abcdef', 2)");
+    assert_eq!(session.warning_count(), 1);
+    let expected = vec![warning("Warning", 1265, "Data truncated for column 'code' at row 2")];
+    assert_eq!(show_warnings(&executor, &mut session), expected);
+    // SHOW WARNINGS does not clear the list; the next statement does
+    assert_eq!(show_warnings(&executor, &mut session), expected);
+
+    execute(&executor, &mut session, "UPDATE users SET code = 'wxyz' WHERE id = 1");
+    assert_eq!(
+        show_warnings(&executor, &mut session),
+        vec![warning("Warning", 1265, "Data truncated for column 'code' at row 1")]
+    );
+
+    execute(&executor, &mut session, "SELECT * FROM users");
+    assert!(show_warnings(&executor, &mut session).is_empty());
+}
+
+#[test]
+fn test_non_numeric_strings_in_numeric_columns() {
+    let (_dir, executor, mut session) = setup();
+
+    // Strict mode rejects the value, and SHOW WARNINGS lists the error
+    let err = executor.execute_sql("INSERT INTO users VALUES (1, 'a', 'many')", &mut session).unwrap_err();
+    assert_eq!(
+        show_warnings(&executor, &mut session),
+        vec![warning("Error", err.mysql_error_code() as i64, &err.to_string())]
+    );
+
+    execute(&executor, &mut session, "SET sql_mode = ''");
+    execute(&executor, &mut session, "INSERT INTO users VALUES (1, 'a', 'many')");
+    assert_eq!(
+        show_warnings(&executor, &mut session),
+        vec![warning("Warning", 1366, "Incorrect integer value: 'many' for column 'score' at row 1")]
+    );
+    match execute(&executor, &mut session, "SELECT score FROM users") {
+        QueryResult::Select(rs) => assert_eq!(rs.rows, vec![vec![Value::Integer(0)]]),
+        other => panic!("Expected SELECT result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_if_exists_notes() {
+    let (_dir, executor, mut session) = setup();
+
+    execute(&executor, &mut session, "DROP TABLE IF EXISTS missing");
+    assert_eq!(
+        show_warnings(&executor, &mut session),
+        vec![warning("Note", 1051, "Unknown table 'minisql.missing'")]
+    );
+    execute(&executor, &mut session, "CREATE TABLE IF NOT EXISTS users (id INT)");
+    assert_eq!(
+        show_warnings(&executor, &mut session),
+        vec![warning("Note", 1050, "Table 'users' already exists")]
+    );
+    execute(&executor, &mut session, "DROP INDEX IF EXISTS idx_missing");
+    assert_eq!(
+        show_warnings(&executor, &mut session),
+        vec![warning("Note", 1091, "Can't DROP 'idx_missing'; check that column/key exists")]
+    );
+}
+
+#[test]
+fn test_streamed_select_errors_are_recorded() {
+    let (_dir, executor, mut session) = setup();
+    let Statement::Select(select) = Parser::parse("SELECT * FROM missing").unwrap() else {
+        panic!("Expected SELECT");
+    };
+
+    let err = executor.execute_select_streaming(select, &mut session, &mut ResultSet::empty()).unwrap_err();
+    assert_eq!(err.mysql_error_code(), 1146);
+    assert_eq!(
+        show_warnings(&executor, &mut session),
+        vec![warning("Error", 1146, &err.to_string())]
+    );
+}
+
+#[tokio::test]
+async fn test_ok_packet_reports_warning_count() {
+    let dir = tempdir().unwrap();
+    let config = Config {
+        data_dir: dir.path().to_path_buf(),
+        password: String::new(),
+        ..Config::default()
+    };
+    let server = Arc::new(Server::new(config).await.unwrap());
+    let addr = serve(&server).await;
+
+    let mut stream = connect(addr).await;
+
+    assert_eq!(query(&mut stream, "CREATE TABLE t (code VARCHAR(2))").await[0], 0x00);
+    assert_eq!(query(&mut stream, "SET sql_mode = ''").await[0], 0x00);
+
+    // OK: header, affected rows, last insert id, status flags (2), warnings (2)
+    let ok = query(&mut stream, "INSERT INTO t VALUES ('abc'), ('def'), ('g')").await;
+    assert_eq!(ok[0], 0x00);
+    assert_eq!(ok[1], 3);
+    assert_eq!(u16::from_le_bytes([ok[5], ok[6]]), 2);
+
+    server.shutdown();
+}
@@ -159,6 +159,87 @@ mod where_operator_tests {
         assert_eq!(vals, vec![Value::String("banana".into())]);
     }
 
+    #[test]
+    fn test_not_like_and_single_char_wildcard() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE t_like (s TEXT)");
+        execute_ok(&executor, &mut session, "INSERT INTO t_like VALUES ('cat'), ('cut'), ('cart'), ('ct'), (NULL)");
+
+        // _ matches exactly one character
+        let res = execute(&executor, &mut session, "SELECT s FROM t_like WHERE s LIKE 'c_t' ORDER BY s");
+        assert_eq!(first_column(&res), vec![Value::String("cat".into()), Value::String("cut".into())]);
+
+        // NOT LIKE is the complement, and NULL matches neither
+        let res = execute(&executor, &mut session, "SELECT s FROM t_like WHERE s NOT LIKE 'c_t' ORDER BY s");
+        assert_eq!(first_column(&res), vec![Value::String("cart".into()), Value::String("ct".into())]);
+        let res = execute(&executor, &mut session, "SELECT s FROM t_like WHERE NOT s LIKE 'c%' ORDER BY s");
+        assert!(first_column(&res).is_empty());
+    }
+
+    #[test]
+    fn test_like_escaped_wildcards() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE t_like (s TEXT)");
+        execute_ok(&executor, &mut session, "INSERT INTO t_like VALUES ('a%b'), ('axb'), ('a_b'), ('50%')");
+
+        // Backslash is the default escape character
+        let res = execute(&executor, &mut session, "SELECT s FROM t_like WHERE s LIKE 'a\\%b'");
+        assert_eq!(first_column(&res), vec![Value::String("a%b".into())]);
+        let res = execute(&executor, &mut session, "SELECT s FROM t_like WHERE s LIKE 'a\\_b'");
+        assert_eq!(first_column(&res), vec![Value::String("a_b".into())]);
+
+        // ESCAPE picks another character
+        let res = execute(&executor, &mut session, "SELECT s FROM t_like WHERE s LIKE '%!%' ESCAPE '!'");
+        assert_eq!(first_column(&res), vec![Value::String("50%".into())]);
+        let res = execute(&executor, &mut session, "SELECT s FROM t_like WHERE s NOT LIKE 'a#_b' ESCAPE '#' ORDER BY s");
+        assert_eq!(first_column(&res), vec![
+            Value::String("50%".into()),
+            Value::String("a%b".into()),
+            Value::String("axb".into()),
+        ]);
+
+        assert!(Parser::parse("SELECT s FROM t_like WHERE s LIKE 'a' ESCAPE 'ab'").is_err());
+    }
+
+    #[test]
+    fn test_like_is_case_insensitive_for_multibyte_text() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE t_like (s TEXT)");
+        execute_ok(&executor, &mut session, "INSERT INTO t_like VALUES ('Ärger'), ('ÉCOLE'), ('straße')");
+
+        let res = execute(&executor, &mut session, "SELECT s FROM t_like WHERE s LIKE 'är%'");
+        assert_eq!(first_column(&res), vec![Value::String("Ärger".into())]);
+        let res = execute(&executor, &mut session, "SELECT s FROM t_like WHERE s LIKE '_cole'");
+        assert_eq!(first_column(&res), vec![Value::String("ÉCOLE".into())]);
+        let res = execute(&executor, &mut session, "SELECT s FROM t_like WHERE s LIKE 'STRA_E'");
+        assert_eq!(first_column(&res), vec![Value::String("straße".into())]);
+    }
+
+    #[test]
+    fn test_like_with_many_wildcards_over_long_strings() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE t_like (id INT PRIMARY KEY, s TEXT)");
+        let long = "xy".repeat(5_000);
+        for (id, tail) in [(1, "abc"), (2, "cba"), (3, "")] {
+            let sql = format!("INSERT INTO t_like VALUES ({}, '{}{}')", id, long, tail);
+            execute_ok(&executor, &mut session, &sql);
+        }
+
+        // Each `%` retries every split, so a backtracking matcher would not finish
+        let started = std::time::Instant::now();
+        let res = execute(&executor, &mut session, "SELECT id FROM t_like WHERE s LIKE '%a%b%c%' ORDER BY id");
+        assert_eq!(first_column(&res), vec![Value::Integer(1)]);
+        let res = execute(&executor, &mut session, "SELECT id FROM t_like WHERE s LIKE '%x%x%x%x%x%x%x%x%q' ORDER BY id");
+        assert!(first_column(&res).is_empty());
+        let res = execute(&executor, &mut session, "SELECT id FROM t_like WHERE s NOT LIKE '%y%y%y%y%y%y%y%y%_' ORDER BY id");
+        assert!(first_column(&res).is_empty());
+        assert!(started.elapsed() < std::time::Duration::from_secs(5), "LIKE took {:?}", started.elapsed());
+    }
+
     #[test]
     fn test_between_equivalent_and_comparisons() {
         let (executor, mut session, _dir) = create_test_executor();
@@ -192,4 +273,32 @@ mod where_operator_tests {
         let vals = first_column(&res);
         assert_eq!(vals, vec![Value::Integer(9), Value::Integer(10)]);
     }
+
+    #[test]
+    fn test_integer_float_equality() {
+        let (executor, mut session, _dir) = create_test_executor();
+
+        execute_ok(&executor, &mut session, "CREATE TABLE t (id INTEGER PRIMARY KEY, n INTEGER)");
+        execute_ok(&executor, &mut session, "INSERT INTO t VALUES (1, 10)");
+        execute_ok(&executor, &mut session, "INSERT INTO t VALUES (2, 20)");
+
+        // Full scan comparison
+        let res = execute(&executor, &mut session, "SELECT id FROM t WHERE n = 10.0");
+        assert_eq!(first_column(&res), vec![Value::Integer(1)]);
+        let res = execute(&executor, &mut session, "SELECT id FROM t WHERE n = 10.5");
+        assert!(first_column(&res).is_empty());
+        let res = execute(&executor, &mut session, "SELECT id FROM t WHERE n < 20.0");
+        assert_eq!(first_column(&res), vec![Value::Integer(1)]);
+
+        // Primary key index lookup
+        let res = execute(&executor, &mut session, "SELECT n FROM t WHERE id = 2.0");
+        assert_eq!(first_column(&res), vec![Value::Integer(20)]);
+        let res = execute(&executor, &mut session, "SELECT n FROM t WHERE id = 1.5");
+        assert!(first_column(&res).is_empty());
+
+        // Secondary index lookup
+        execute_ok(&executor, &mut session, "CREATE INDEX idx_n ON t (n)");
+        let res = execute(&executor, &mut session, "SELECT id FROM t WHERE n = 20.0");
+        assert_eq!(first_column(&res), vec![Value::Integer(2)]);
+    }
 }